@@ -0,0 +1,83 @@
+//! Throughput of the u/v and long-s normalizers and of a full pipeline on
+//! the sample texts in `benches/data`.
+//!
+//! Run with `cargo bench`, then `cargo run --example check_benchmarks` to
+//! compare the results with `benches/thresholds.toml`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use latincy_preprocess::long_s::{self, DEFAULT_THRESHOLD};
+use latincy_preprocess::pipeline::{self, Normalizer, Pipeline};
+use latincy_preprocess::uv;
+
+/// Modern-spelled prose with u for consonantal v (Caesar, *BG* 1.1–3).
+const PROSE: &str = include_str!("data/caesar.txt");
+/// The same text as an early-modern print reads after OCR: long s read as
+/// f, some ſ and ligatures left in.
+const LONG_S: &str = include_str!("data/long_s.txt");
+
+fn words(text: &str) -> Vec<&str> {
+    text.split(|c: char| !c.is_alphabetic()).filter(|word| !word.is_empty()).collect()
+}
+
+fn bench_uv(c: &mut Criterion) {
+    let mut group = c.benchmark_group("uv");
+    // Every u and v of the prose, each classified on its own
+    let positions: Vec<(&str, usize)> = words(PROSE)
+        .into_iter()
+        .flat_map(|word| word.chars().enumerate().filter(|&(_, c)| matches!(c, 'u' | 'v')).map(move |(i, _)| (word, i)))
+        .collect();
+    group.throughput(Throughput::Elements(positions.len() as u64));
+    group.bench_function("normalize_char", |b| {
+        b.iter(|| {
+            for &(word, i) in &positions {
+                black_box(uv::normalize_char(black_box(word), i));
+            }
+        })
+    });
+    group.throughput(Throughput::Bytes(PROSE.len() as u64));
+    group.bench_function("normalize_text", |b| b.iter(|| uv::normalize(black_box(PROSE)).len()));
+    group.finish();
+}
+
+fn bench_long_s(c: &mut Criterion) {
+    let mut group = c.benchmark_group("long_s");
+    let words = words(LONG_S);
+    group.throughput(Throughput::Elements(words.len() as u64));
+    group.bench_function("pass1", |b| {
+        b.iter(|| {
+            for &word in &words {
+                black_box(long_s::pass1(black_box(word)));
+            }
+        })
+    });
+    // Load the model before timing the pass that needs it
+    long_s::pass2("funt", DEFAULT_THRESHOLD);
+    group.bench_function("pass2", |b| {
+        b.iter(|| {
+            for &word in &words {
+                black_box(long_s::pass2(black_box(word), DEFAULT_THRESHOLD));
+            }
+        })
+    });
+    group.throughput(Throughput::Bytes(LONG_S.len() as u64));
+    group.bench_function("normalize_text", |b| {
+        b.iter(|| long_s::normalize_text(black_box(LONG_S), true, DEFAULT_THRESHOLD).len())
+    });
+    group.finish();
+}
+
+fn bench_pipeline(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pipeline");
+    let mut stages = Pipeline::new();
+    for name in ["unicode", "ligatures", "long_s", "uv"] {
+        stages.push(pipeline::stage(name).expect("built-in stage"));
+    }
+    group.throughput(Throughput::Bytes(LONG_S.len() as u64));
+    group.bench_function("unicode_ligatures_long_s_uv", |b| {
+        b.iter(|| stages.normalize(black_box(LONG_S)).normalized.len())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_uv, bench_long_s, bench_pipeline);
+criterion_main!(benches);
@@ -0,0 +1,35 @@
+//! Chooses the n-gram model that the `embed-data` feature compiles in:
+//! `data/ngrams.bin`, or the binary model at
+//! `$LATINCY_PREPROCESS_EMBED_NGRAMS` (absolute, or relative to this
+//! directory), such as one pruned with `convert_ngrams --min-count N` for a
+//! smaller WebAssembly module. Its metadata is the `metadata.json` beside
+//! it, if there is one.
+
+use std::path::PathBuf;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=LATINCY_PREPROCESS_EMBED_NGRAMS");
+    if std::env::var_os("CARGO_FEATURE_EMBED_DATA").is_none() {
+        return;
+    }
+
+    let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let model = match std::env::var_os("LATINCY_PREPROCESS_EMBED_NGRAMS") {
+        Some(path) => manifest_dir.join(path),
+        None => manifest_dir.join("data/ngrams.bin"),
+    };
+    if !model.is_file() {
+        panic!("LATINCY_PREPROCESS_EMBED_NGRAMS: {} is not a file", model.display());
+    }
+    let mut metadata = model.with_file_name("metadata.json");
+    if !metadata.is_file() {
+        metadata = PathBuf::from(std::env::var_os("OUT_DIR").unwrap()).join("metadata.json");
+        std::fs::write(&metadata, "{}").unwrap();
+    }
+
+    println!("cargo:rerun-if-changed={}", model.display());
+    println!("cargo:rerun-if-changed={}", metadata.display());
+    println!("cargo:rustc-env=LATINCY_EMBEDDED_NGRAMS={}", model.display());
+    println!("cargo:rustc-env=LATINCY_EMBEDDED_METADATA={}", metadata.display());
+}
@@ -0,0 +1,61 @@
+//! Compare the last `cargo bench` results with `benches/thresholds.toml`.
+//!
+//! Usage: cargo run --example check_benchmarks [CRITERION_DIR]
+//!
+//! Reads the estimates criterion writes under `target/criterion` (or
+//! `$CARGO_TARGET_DIR/criterion`) and exits with status 1 if a benchmark is
+//! slower than its ceiling or has no results.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// The mean time per iteration of the benchmark whose results are in
+/// `dir`, in microseconds.
+fn mean_micros(dir: &Path) -> Option<f64> {
+    let estimates = std::fs::read_to_string(dir.join("new").join("estimates.json")).ok()?;
+    let estimates: serde_json::Value = serde_json::from_str(&estimates).ok()?;
+    Some(estimates["mean"]["point_estimate"].as_f64()? / 1000.0)
+}
+
+fn main() -> ExitCode {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let results = std::env::args_os()
+        .nth(1)
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("CARGO_TARGET_DIR").map(|dir| PathBuf::from(dir).join("criterion")))
+        .unwrap_or_else(|| manifest_dir.join("../target/criterion"));
+    let thresholds = std::fs::read_to_string(manifest_dir.join("benches/thresholds.toml"))
+        .expect("benches/thresholds.toml is readable");
+    let thresholds: toml::Table = toml::from_str(&thresholds).expect("benches/thresholds.toml is valid TOML");
+
+    let mut failures = 0;
+    for (group, benchmarks) in &thresholds {
+        let Some(benchmarks) = benchmarks.as_table() else {
+            continue;
+        };
+        for (name, ceiling) in benchmarks {
+            let id = format!("{}/{}", group, name);
+            let Some(ceiling) = ceiling.as_float().or_else(|| ceiling.as_integer().map(|n| n as f64)) else {
+                println!("{:<40} ceiling is not a number", id);
+                failures += 1;
+                continue;
+            };
+            match mean_micros(&results.join(group).join(name)) {
+                Some(mean) if mean <= ceiling => println!("{:<40} {:>10.1} µs  (ceiling {} µs)", id, mean, ceiling),
+                Some(mean) => {
+                    println!("{:<40} {:>10.1} µs  SLOWER than {} µs", id, mean, ceiling);
+                    failures += 1;
+                }
+                None => {
+                    println!("{:<40} no results in {}", id, results.display());
+                    failures += 1;
+                }
+            }
+        }
+    }
+    if failures > 0 {
+        println!("{} benchmark(s) failed; run `cargo bench --bench normalize` first if results are missing", failures);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,42 @@
+//! Convert the JSON n-gram tables to the binary model format.
+//!
+//! Usage: cargo run --example convert_ngrams [--min-count N] [JSON_DIR] [OUTPUT]
+//!
+//! Defaults to the bundled tables, writing `ngrams.bin` alongside them and
+//! refreshing the copy in `rust/data` that the `embed-data` feature embeds.
+//! `--min-count` drops rarer trigrams and 4-grams for a smaller model, such
+//! as one loaded in a browser.
+
+use latincy_preprocess::ngrams::{NgramData, BINARY_FILE_NAME, METADATA_FILE_NAME};
+use std::path::PathBuf;
+
+fn main() -> std::io::Result<()> {
+    let mut args = std::env::args_os().skip(1).peekable();
+    let mut min_count = 0;
+    if args.peek().is_some_and(|arg| arg == "--min-count") {
+        args.next();
+        min_count = args
+            .next()
+            .and_then(|n| n.to_str().and_then(|n| n.parse().ok()))
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "--min-count needs a number"))?;
+    }
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let bundled = args.peek().is_none();
+    let dir = args
+        .next()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| manifest_dir.join("../src/latincy_preprocess/long_s/data/ngrams"));
+    let output = args.next().map(PathBuf::from).unwrap_or_else(|| dir.join(BINARY_FILE_NAME));
+
+    let data = NgramData::from_dir(&dir)?.pruned(min_count);
+    data.write_binary(&output)?;
+    println!("Wrote {}", output.display());
+
+    if bundled && min_count == 0 {
+        let embedded = manifest_dir.join("data");
+        std::fs::copy(&output, embedded.join(BINARY_FILE_NAME))?;
+        std::fs::copy(dir.join(METADATA_FILE_NAME), embedded.join(METADATA_FILE_NAME))?;
+        println!("Wrote {}", embedded.display());
+    }
+    Ok(())
+}
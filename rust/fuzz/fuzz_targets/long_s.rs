@@ -0,0 +1,21 @@
+//! Long-s correction of arbitrary text: no panics in either word pass, and
+//! the text and detailed paths agree.
+#![no_main]
+
+use latincy_preprocess::long_s::{self, DEFAULT_THRESHOLD};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|text: &str| {
+    long_s::pass1(text);
+    long_s::pass2(text, DEFAULT_THRESHOLD);
+
+    let chars = text.chars().count();
+    for apply_pass2 in [false, true] {
+        let normalized = long_s::normalize_text(text, apply_pass2, DEFAULT_THRESHOLD);
+        let detailed = long_s::normalize_detailed(text, apply_pass2, DEFAULT_THRESHOLD);
+        assert_eq!(detailed.normalized, normalized);
+        for change in &detailed.changes {
+            assert!(change.position < chars);
+        }
+    }
+});
@@ -0,0 +1,33 @@
+//! Every built-in stage, alone and all together, on arbitrary text: no
+//! panics, and offset maps that are monotonic and span both texts.
+#![no_main]
+
+use latincy_preprocess::pipeline::{self, NormalizeOutput, Normalizer, Pipeline};
+use libfuzzer_sys::fuzz_target;
+
+fn check(output: &NormalizeOutput, text: &str) {
+    assert_eq!(output.original, text);
+    let (original, normalized) = (output.original.chars().count(), output.normalized.chars().count());
+    assert_eq!(output.offsets.len(), normalized + 1);
+    assert_eq!(output.offsets[normalized], original);
+    assert!(output.offsets.windows(2).all(|pair| pair[0] <= pair[1]));
+    let inverse = output.normalized_offsets();
+    assert_eq!(inverse.len(), original + 1);
+    assert_eq!(inverse[original], normalized);
+    assert!(inverse.windows(2).all(|pair| pair[0] <= pair[1]));
+    for change in &output.changes {
+        assert!(change.position <= original);
+    }
+}
+
+fuzz_target!(|text: &str| {
+    let mut all = Pipeline::new();
+    for &name in pipeline::STAGES {
+        let stage = pipeline::stage(name).expect("a built-in stage");
+        check(&stage.normalize(text), text);
+        all.push(stage);
+    }
+    let output = all.normalize(text);
+    check(&output, text);
+    assert_eq!(all.normalize_text(text), output.normalized);
+});
@@ -0,0 +1,27 @@
+//! The streaming entry points on arbitrary bytes: no panics, invalid UTF-8
+//! rejected, and valid input written out as valid UTF-8.
+#![no_main]
+
+use latincy_preprocess::long_s::{self, DEFAULT_THRESHOLD};
+use latincy_preprocess::pipeline;
+use latincy_preprocess::uv::UvNormalizer;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|bytes: &[u8]| {
+    let text = std::str::from_utf8(bytes).ok();
+
+    let mut out = Vec::new();
+    let result = long_s::normalize_stream(bytes, &mut out, true, DEFAULT_THRESHOLD);
+    assert_eq!(result.is_ok(), text.is_some());
+    if let Some(text) = text {
+        assert_eq!(String::from_utf8(out).expect("valid UTF-8"), long_s::normalize_text(text, true, DEFAULT_THRESHOLD));
+    }
+
+    let uv = UvNormalizer::new();
+    let mut out = Vec::new();
+    let result = pipeline::normalize_stream(&uv, bytes, &mut out);
+    assert_eq!(result.is_ok(), text.is_some());
+    if let Some(text) = text {
+        assert_eq!(String::from_utf8(out).expect("valid UTF-8"), uv.normalize_text(text));
+    }
+});
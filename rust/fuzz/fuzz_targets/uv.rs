@@ -0,0 +1,26 @@
+//! u/v normalization of arbitrary text: no panics, one character out for
+//! each character in, and `normalize_char` and the detailed path agree with
+//! `normalize`.
+#![no_main]
+
+use latincy_preprocess::uv;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|text: &str| {
+    let normalized = uv::normalize(text);
+    let chars: Vec<char> = text.chars().collect();
+    assert_eq!(normalized.chars().count(), chars.len());
+    for (i, (c, normalized)) in chars.iter().zip(normalized.chars()).enumerate() {
+        if matches!(c, 'u' | 'v' | 'U' | 'V') {
+            assert_eq!(uv::normalize_char(text, i).0, normalized.to_string());
+        } else {
+            assert_eq!(*c, normalized);
+        }
+    }
+
+    let detailed = uv::normalize_detailed(text);
+    assert_eq!(detailed.normalized, normalized);
+    for change in &detailed.changes {
+        assert_eq!(chars[change.position].to_string(), change.original);
+    }
+});
@@ -0,0 +1,115 @@
+#ifndef LATINCY_PREPROCESS_H
+#define LATINCY_PREPROCESS_H
+
+/* Generated by cbindgen from src/capi.rs; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// A pipeline, opaque to C.
+typedef struct LatincyPipeline LatincyPipeline;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// The crate version, as a static string the caller must not free.
+const char *latincy_version(void);
+
+// The message of the last failure on this thread, or null. The string is
+// owned by the library and valid until the next call.
+const char *latincy_last_error(void);
+
+// Frees a string returned by this library. Null is ignored.
+//
+// # Safety
+//
+// `s` must be null or a string returned by this library, not yet freed.
+void latincy_string_free(char *s);
+
+// `text` with u/v normalized, as by [`uv::normalize`](crate::uv::normalize).
+//
+// # Safety
+//
+// `text` must be null or point to a NUL-terminated string.
+char *latincy_normalize_uv(const char *text);
+
+// The detailed JSON of u/v normalization of `text`.
+//
+// # Safety
+//
+// `text` must be null or point to a NUL-terminated string.
+char *latincy_normalize_uv_detailed(const char *text);
+
+// `text` with long-s misreadings corrected by a default
+// [`LongSNormalizer`], with the model of [`latincy_load_ngrams`] if one
+// was loaded.
+//
+// # Safety
+//
+// `text` must be null or point to a NUL-terminated string.
+char *latincy_normalize_long_s(const char *text);
+
+// The detailed JSON of long-s correction of `text`.
+//
+// # Safety
+//
+// `text` must be null or point to a NUL-terminated string.
+char *latincy_normalize_long_s_detailed(const char *text);
+
+// Replaces the n-gram model of the long-s functions with the binary model
+// (as written by `NgramData::write_binary`) in the `len` bytes at `bytes`,
+// which are copied. Returns false, keeping the current model, if they are
+// not a valid model.
+//
+// # Safety
+//
+// `bytes` must point to `len` readable bytes.
+bool latincy_load_ngrams(const uint8_t *bytes, size_t len);
+
+// A new pipeline of the comma-separated stage names `stages`
+// (`"ligatures,long_s,uv"`), each in its default configuration.
+//
+// # Safety
+//
+// `stages` must be null or point to a NUL-terminated string.
+LatincyPipeline *latincy_pipeline_new(const char *stages);
+
+// The pipeline declared in the TOML or JSON config file at `path`.
+//
+// # Safety
+//
+// `path` must be null or point to a NUL-terminated string.
+LatincyPipeline *latincy_pipeline_from_config(const char *path);
+
+// Frees a pipeline. Null is ignored.
+//
+// # Safety
+//
+// `pipeline` must be null or a pipeline returned by this library, not yet
+// freed.
+void latincy_pipeline_free(LatincyPipeline *pipeline);
+
+// `text` run through `pipeline`. A pipeline may be used from several
+// threads at once.
+//
+// # Safety
+//
+// `pipeline` must be a live pipeline returned by this library, and `text`
+// null or a NUL-terminated string.
+char *latincy_pipeline_normalize(const LatincyPipeline *pipeline, const char *text);
+
+// The detailed JSON of `text` run through `pipeline`.
+//
+// # Safety
+//
+// As [`latincy_pipeline_normalize`].
+char *latincy_pipeline_normalize_detailed(const LatincyPipeline *pipeline, const char *text);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* LATINCY_PREPROCESS_H */
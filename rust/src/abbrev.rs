@@ -0,0 +1,427 @@
+//! Expansion of Latin scribal and print abbreviations.
+//!
+//! Medieval manuscripts and incunabula abbreviate common words and syllables
+//! with special characters: the Tironian *et* (`⁊`), *-que* written `q;`,
+//! `ꝑ` for *per*, `ꝓ` for *pro*, `ꝙ` for *quod*, and letters written above
+//! the line (`qͥ` for *qui*). [`Abbreviations`] expands them from a mapping
+//! table that can be extended or loaded from a file. Nasal bars (`quā` for
+//! *quam*) are a separate problem and are not handled here.
+
+use crate::changes::{self, ChangeRecord};
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use std::io;
+use std::path::Path;
+
+/// The built-in abbreviations and their expansions.
+pub const ABBREVIATIONS: &[(&str, &str)] = &[
+    ("⁊", "et"),
+    ("q;", "que"),
+    ("Q;", "Que"),
+    ("q\u{0301}", "que"),
+    ("Q\u{0301}", "Que"),
+    ("ꝑ", "per"),
+    ("Ꝑ", "Per"),
+    ("ꝓ", "pro"),
+    ("Ꝓ", "Pro"),
+    ("ꝙ", "quod"),
+    ("Ꝙ", "Quod"),
+    ("ꝯ", "con"),
+    ("Ꝯ", "Con"),
+    ("ꝝ", "rum"),
+    ("ꝰ", "us"),
+    ("ꝭ", "is"),
+    ("q\u{0363}", "qua"),
+    ("q\u{0364}", "que"),
+    ("q\u{0365}", "qui"),
+    ("q\u{0366}", "quo"),
+];
+
+/// Combining superscript letters (U+0363–U+036F) and the letters they stand
+/// for. Outside the table they are written inline (`ergͦ` → `ergo`).
+const SUPERSCRIPT_LETTERS: &[(char, char)] = &[
+    ('\u{0363}', 'a'),
+    ('\u{0364}', 'e'),
+    ('\u{0365}', 'i'),
+    ('\u{0366}', 'o'),
+    ('\u{0367}', 'u'),
+    ('\u{0368}', 'c'),
+    ('\u{0369}', 'd'),
+    ('\u{036A}', 'h'),
+    ('\u{036B}', 'm'),
+    ('\u{036C}', 'r'),
+    ('\u{036D}', 't'),
+    ('\u{036E}', 'v'),
+    ('\u{036F}', 'x'),
+];
+
+fn superscript_letter(c: char) -> Option<char> {
+    SUPERSCRIPT_LETTERS.iter().find(|&&(sup, _)| sup == c).map(|&(_, letter)| letter)
+}
+
+/// A table of abbreviations, applied in one left-to-right scan where the
+/// longest abbreviation matching at each position wins, like
+/// [`crate::long_s::Pass1Rules`]. Matching is exact, so capital forms need
+/// their own entries.
+///
+/// ```
+/// use latincy_preprocess::abbrev::Abbreviations;
+///
+/// let abbreviations = Abbreviations::default();
+/// assert_eq!(abbreviations.expand("ꝑ deum atq; hoīes ⁊ ꝓ"), "per deum atque hoīes et pro");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Abbreviations {
+    /// `(abbreviation, expansion)`, longest abbreviation first.
+    entries: Vec<(String, String)>,
+    ampersand: bool,
+}
+
+impl Default for Abbreviations {
+    /// The built-in table, leaving `&` alone.
+    fn default() -> Self {
+        let mut abbreviations = Self::empty();
+        for &(abbreviation, expansion) in ABBREVIATIONS {
+            abbreviations.add(abbreviation, expansion).expect("built-in abbreviations are valid");
+        }
+        abbreviations
+    }
+}
+
+impl Abbreviations {
+    /// A table with no entries.
+    pub fn empty() -> Self {
+        Abbreviations {
+            entries: Vec::new(),
+            ampersand: false,
+        }
+    }
+
+    /// Also expand `&` to *et* (off by default, since modern editions use
+    /// `&` too).
+    pub fn with_ampersand(mut self, expand: bool) -> Self {
+        self.ampersand = expand;
+        self
+    }
+
+    pub fn ampersand(&self) -> bool {
+        self.ampersand
+    }
+
+    /// Add an entry, replacing any entry for the same abbreviation. Fails
+    /// with `InvalidInput` if the abbreviation is empty.
+    pub fn add(&mut self, abbreviation: &str, expansion: &str) -> io::Result<()> {
+        if abbreviation.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid abbreviation → {}: empty abbreviation", expansion),
+            ));
+        }
+        self.entries.retain(|(existing, _)| existing != abbreviation);
+        let len = abbreviation.chars().count();
+        let at = self.entries.iter().position(|(existing, _)| existing.chars().count() < len).unwrap_or(self.entries.len());
+        self.entries.insert(at, (abbreviation.to_string(), expansion.to_string()));
+        Ok(())
+    }
+
+    /// Add the entries in a mapping file: a JSON object from abbreviation to
+    /// expansion if the file has a `.json` extension, otherwise one
+    /// abbreviation and its expansion per line, separated by whitespace.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            io::Error::new(e.kind(), format!("Failed to read abbreviations {}: {}", path.display(), e))
+        })?;
+        let invalid = |msg: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Failed to parse abbreviations {}: {}", path.display(), msg),
+            )
+        };
+
+        let mut entries = Vec::new();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            let map: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+            for (abbreviation, expansion) in map {
+                let serde_json::Value::String(expansion) = expansion else {
+                    return Err(invalid(format!("expansion of {:?} is not a string", abbreviation)));
+                };
+                entries.push((abbreviation, expansion));
+            }
+        } else {
+            for (lineno, line) in content.lines().enumerate() {
+                let mut fields = line.split_whitespace();
+                let Some(abbreviation) = fields.next() else {
+                    continue;
+                };
+                let (Some(expansion), None) = (fields.next(), fields.next()) else {
+                    return Err(invalid(format!("line {}: expected an abbreviation and its expansion", lineno + 1)));
+                };
+                entries.push((abbreviation.to_string(), expansion.to_string()));
+            }
+        }
+        for (abbreviation, expansion) in entries {
+            self.add(&abbreviation, &expansion).map_err(|e| invalid(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// The entries as `(abbreviation, expansion)`, longest abbreviation
+    /// first.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(abbreviation, expansion)| (abbreviation.as_str(), expansion.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Expand the abbreviations in `text`.
+    pub fn expand(&self, text: &str) -> String {
+        self.expand_traced(text, None)
+    }
+
+    /// Like [`Abbreviations::expand`], recording one [`ChangeRecord`] per
+    /// expanded abbreviation.
+    pub fn expand_detailed(&self, text: &str) -> DetailedResult {
+        let mut changes = Vec::new();
+        let normalized = self.expand_traced(text, Some(&mut changes));
+        DetailedResult {
+            original: text.to_string(),
+            normalized,
+            changes,
+        }
+    }
+
+    fn expand_traced(&self, text: &str, mut trace: Option<&mut Vec<ChangeRecord>>) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut result = String::with_capacity(text.len() + 16);
+        let mut i = 0;
+        while i < chars.len() {
+            let rest = &chars[i..];
+            let entry = self.entries.iter().find(|(abbreviation, _)| {
+                let mut rest = rest.iter();
+                abbreviation.chars().all(|c| rest.next() == Some(&c))
+            });
+            let (len, expansion, rule) = match entry {
+                Some((abbreviation, expansion)) => {
+                    let len = abbreviation.chars().count();
+                    let rule = format!("{} → {}", abbreviation, expansion);
+                    (len, expansion.clone(), rule)
+                }
+                None => match rest[0] {
+                    '&' if self.ampersand => (1, "et".to_string(), "& → et".to_string()),
+                    c => match superscript_letter(c) {
+                        Some(letter) => (1, letter.to_string(), format!("superscript {}", letter)),
+                        None => {
+                            result.push(c);
+                            i += 1;
+                            continue;
+                        }
+                    },
+                },
+            };
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.push(ChangeRecord {
+                    position: i,
+                    original: rest[..len].iter().collect(),
+                    normalized: expansion.clone(),
+                    pass: 1,
+                    rule,
+                    context: changes::context(&chars, i, len),
+                });
+            }
+            result.push_str(&expansion);
+            i += len;
+        }
+        result
+    }
+}
+
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<ChangeRecord>,
+}
+
+/// Expand abbreviations with the built-in table.
+pub fn expand(text: &str) -> String {
+    Abbreviations::default().expand(text)
+}
+
+impl Normalizer for Abbreviations {
+    fn name(&self) -> &str {
+        "abbreviations"
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = self.expand_detailed(text);
+        NormalizeOutput::from_changes(result.original, result.normalized, result.changes)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "expand_abbreviations", signature = (text, ampersand=false))]
+pub fn py_expand_abbreviations(py: Python<'_>, text: &str, ampersand: bool) -> String {
+    crate::allow_threads(py, text, || Abbreviations::default().with_ampersand(ampersand).expand(text))
+}
+
+/// Python view of [`Abbreviations`], exposed as `_rust.Abbreviations`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "Abbreviations")]
+pub struct PyAbbreviations {
+    pub(crate) inner: Abbreviations,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyAbbreviations {
+    /// The built-in table (or an empty one with `builtin=False`), plus any
+    /// `entries` and the entries of the mapping file `table`.
+    #[new]
+    #[pyo3(signature = (entries=None, table=None, ampersand=false, builtin=true))]
+    fn new(
+        entries: Option<Vec<(String, String)>>,
+        table: Option<std::path::PathBuf>,
+        ampersand: bool,
+        builtin: bool,
+    ) -> PyResult<Self> {
+        let mut inner = if builtin { Abbreviations::default() } else { Abbreviations::empty() };
+        inner = inner.with_ampersand(ampersand);
+        for (abbreviation, expansion) in entries.unwrap_or_default() {
+            inner
+                .add(&abbreviation, &expansion)
+                .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        }
+        if let Some(table) = table {
+            inner.load(table)?;
+        }
+        Ok(PyAbbreviations { inner })
+    }
+
+    fn add(&mut self, abbreviation: &str, expansion: &str) -> PyResult<()> {
+        self.inner
+            .add(abbreviation, expansion)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    }
+
+    /// The entries as `(abbreviation, expansion)` pairs, longest first.
+    #[getter]
+    fn entries(&self) -> Vec<(String, String)> {
+        self.inner.entries().map(|(a, e)| (a.to_string(), e.to_string())).collect()
+    }
+
+    #[getter]
+    fn ampersand(&self) -> bool {
+        self.inner.ampersand()
+    }
+
+    fn expand(&self, py: Python<'_>, text: &str) -> String {
+        crate::allow_threads(py, text, || self.inner.expand(text))
+    }
+
+    fn expand_detailed(&self, py: Python<'_>, text: &str) -> PyResult<changes::PyDetailedResult> {
+        let result = crate::allow_threads(py, text, || self.inner.expand_detailed(text));
+        changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Abbreviations(entries={}, ampersand={})",
+            self.inner.len(),
+            if self.inner.ampersand() { "True" } else { "False" }
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        assert_eq!(expand("senatus populusq; romanus"), "senatus populusque romanus");
+        assert_eq!(expand("⁊ ꝑ ꝓ ꝙ"), "et per pro quod");
+        assert_eq!(expand("Ꝑ ꝯtra"), "Per contra");
+        assert_eq!(expand("atq\u{0301}"), "atque");
+        assert_eq!(expand("q\u{0365} dixit"), "qui dixit");
+        // Superscript letters outside the table are written inline
+        assert_eq!(expand("erg\u{0366}"), "ergo");
+        assert_eq!(expand("arma & uirum"), "arma & uirum");
+        assert_eq!(expand(""), "");
+    }
+
+    #[test]
+    fn test_ampersand() {
+        let abbreviations = Abbreviations::default().with_ampersand(true);
+        assert_eq!(abbreviations.expand("arma & uirum &c."), "arma et uirum etc.");
+    }
+
+    #[test]
+    fn test_add() {
+        let mut abbreviations = Abbreviations::empty();
+        abbreviations.add("dns", "dominus").unwrap();
+        abbreviations.add("ds", "deus").unwrap();
+        assert_eq!(abbreviations.entries().next(), Some(("dns", "dominus")));
+        assert_eq!(abbreviations.expand("ds et dns"), "deus et dominus");
+        assert!(abbreviations.add("", "et").is_err());
+        abbreviations.add("ds", "dies").unwrap();
+        assert_eq!(abbreviations.len(), 2);
+        assert_eq!(abbreviations.expand("ds"), "dies");
+    }
+
+    #[test]
+    fn test_expand_detailed() {
+        let result = Abbreviations::default().expand_detailed("atq; ꝑ");
+        assert_eq!(result.normalized, "atque per");
+        assert_eq!(result.changes.len(), 2);
+        assert_eq!(result.changes[0].position, 2);
+        assert_eq!(result.changes[0].original, "q;");
+        assert_eq!(result.changes[0].normalized, "que");
+        assert_eq!(result.changes[0].rule, "q; → que");
+        assert_eq!(result.changes[0].context, "at[q;] ꝑ");
+        assert_eq!(result.changes[1].position, 5);
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = std::env::temp_dir().join(format!("latincy_abbrev_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let txt = dir.join("abbreviations.txt");
+        std::fs::write(&txt, "dns dominus\nscs sanctus\n\n").unwrap();
+        let json = dir.join("abbreviations.json");
+        std::fs::write(&json, r#"{"xps": "christus"}"#).unwrap();
+        let bad = dir.join("bad.txt");
+        std::fs::write(&bad, "dns\n").unwrap();
+
+        let mut abbreviations = Abbreviations::default();
+        abbreviations.load(&txt).unwrap();
+        abbreviations.load(&json).unwrap();
+        assert_eq!(abbreviations.expand("scs xps ⁊ dns"), "sanctus christus et dominus");
+        let err = abbreviations.load(&bad).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("line 1"));
+        assert_eq!(abbreviations.load(dir.join("missing.txt")).unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
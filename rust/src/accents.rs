@@ -0,0 +1,281 @@
+//! Stripping the accents of early modern Latin prints.
+//!
+//! Printers of the 16th to 18th centuries set acute, grave, and circumflex
+//! accents on Latin vowels to tell homographs apart: *à* and *è* for the
+//! prepositions, *certè* and *ferè* for adverbs, *musâ* for the ablative,
+//! *ǽtas*. Modern editions and models expect none of them.
+//! [`AccentStripper`] removes the chosen [`Accent`]s from Latin letters,
+//! whether the text has precomposed letters (`é`) or combining marks (`e` +
+//! U+0301); macrons and other marks are kept.
+
+use crate::changes::{self, ChangeRecord};
+use crate::pipeline::{NormalizeOutput, Normalizer};
+use crate::unicode;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+
+/// An accent of early modern printing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Accent {
+    /// U+0301: *ubí*, *ǽtas*.
+    Acute,
+    /// U+0300: *à*, *certè*.
+    Grave,
+    /// U+0302: *musâ*, *nostrûm*.
+    Circumflex,
+}
+
+impl Accent {
+    pub const ALL: [Accent; 3] = [Accent::Acute, Accent::Grave, Accent::Circumflex];
+
+    /// The accent's name, as used by the Python bindings and in change
+    /// records.
+    pub fn name(self) -> &'static str {
+        match self {
+            Accent::Acute => "acute",
+            Accent::Grave => "grave",
+            Accent::Circumflex => "circumflex",
+        }
+    }
+
+    /// The combining mark.
+    pub fn mark(self) -> char {
+        match self {
+            Accent::Acute => '\u{0301}',
+            Accent::Grave => '\u{0300}',
+            Accent::Circumflex => '\u{0302}',
+        }
+    }
+
+    fn from_mark(c: char) -> Option<Accent> {
+        Accent::ALL.into_iter().find(|accent| accent.mark() == c)
+    }
+}
+
+/// Removes a chosen set of accents.
+///
+/// ```
+/// use latincy_preprocess::accents::{Accent, AccentStripper};
+///
+/// let stripper = AccentStripper::new();
+/// assert_eq!(stripper.strip("à certè musâ"), "a certe musa");
+/// let stripper = stripper.accent(Accent::Circumflex, false);
+/// assert_eq!(stripper.strip("à certè musâ"), "a certe musâ");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccentStripper {
+    accents: Vec<Accent>,
+}
+
+impl Default for AccentStripper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AccentStripper {
+    /// Strip all three accents.
+    pub fn new() -> Self {
+        AccentStripper {
+            accents: Accent::ALL.to_vec(),
+        }
+    }
+
+    /// Strip `accent`, or leave it.
+    pub fn accent(mut self, accent: Accent, strip: bool) -> Self {
+        self.accents.retain(|&a| a != accent);
+        if strip {
+            self.accents.push(accent);
+        }
+        self
+    }
+
+    /// Whether `accent` is stripped.
+    pub fn strips(&self, accent: Accent) -> bool {
+        self.accents.contains(&accent)
+    }
+
+    pub fn strip(&self, text: &str) -> String {
+        if text.is_ascii() {
+            return text.to_string();
+        }
+        self.strip_detailed(text).normalized
+    }
+
+    /// Like [`AccentStripper::strip`], recording one [`ChangeRecord`] per
+    /// letter that lost an accent. The rule is `"remove "` and the names of
+    /// the removed accents (`"remove acute"`).
+    pub fn strip_detailed(&self, text: &str) -> DetailedResult {
+        let chars: Vec<char> = text.chars().collect();
+        let mut normalized = String::with_capacity(text.len());
+        let mut changes = Vec::new();
+
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i].is_ascii() && chars.get(i + 1).is_none_or(|n| n.is_ascii()) {
+                normalized.push(chars[i]);
+                i += 1;
+                continue;
+            }
+            // A letter and the combining marks that follow it
+            let end = chars[i + 1..]
+                .iter()
+                .position(|&m| unicode::combining_class(m) == 0)
+                .map_or(chars.len(), |p| i + 1 + p);
+            let cluster = &chars[i..end];
+            let decomposed = unicode::decompose(cluster, false);
+            let removed: Vec<Accent> = if is_latin_letter(decomposed[0]) {
+                decomposed[1..]
+                    .iter()
+                    .filter_map(|&m| Accent::from_mark(m))
+                    .filter(|accent| self.strips(*accent))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            if removed.is_empty() {
+                normalized.extend(cluster);
+                i = end;
+                continue;
+            }
+            let kept: Vec<char> = decomposed
+                .into_iter()
+                .filter(|&c| Accent::from_mark(c).is_none_or(|accent| !removed.contains(&accent)))
+                .collect();
+            let stripped = unicode::compose(&kept);
+            changes.push(ChangeRecord {
+                position: i,
+                original: cluster.iter().collect(),
+                normalized: stripped.clone(),
+                pass: 1,
+                rule: format!("remove {}", removed.iter().map(|accent| accent.name()).collect::<Vec<_>>().join(" and ")),
+                context: changes::context(&chars, i, cluster.len()),
+            });
+            normalized.push_str(&stripped);
+            i = end;
+        }
+
+        DetailedResult {
+            original: text.to_string(),
+            normalized,
+            changes,
+        }
+    }
+}
+
+fn is_latin_letter(c: char) -> bool {
+    c.is_ascii_alphabetic() || matches!(c, 'æ' | 'Æ' | 'œ' | 'Œ')
+}
+
+/// Strip acute, grave, and circumflex accents from Latin letters.
+///
+/// ```
+/// use latincy_preprocess::accents::strip_accents;
+///
+/// assert_eq!(strip_accents("Quàm ferè bene\u{0301} ǽtas"), "Quam fere bene ætas");
+/// ```
+pub fn strip_accents(text: &str) -> String {
+    AccentStripper::new().strip(text)
+}
+
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<ChangeRecord>,
+}
+
+impl Normalizer for AccentStripper {
+    fn name(&self) -> &str {
+        "accents"
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = self.strip_detailed(text);
+        NormalizeOutput::from_changes(result.original, result.normalized, result.changes)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+fn build_stripper(acute: bool, grave: bool, circumflex: bool) -> AccentStripper {
+    AccentStripper::new()
+        .accent(Accent::Acute, acute)
+        .accent(Accent::Grave, grave)
+        .accent(Accent::Circumflex, circumflex)
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "strip_accents", signature = (text, acute=true, grave=true, circumflex=true))]
+pub fn py_strip_accents(py: Python<'_>, text: &str, acute: bool, grave: bool, circumflex: bool) -> String {
+    crate::allow_threads(py, text, || build_stripper(acute, grave, circumflex).strip(text))
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "strip_accents_detailed", signature = (text, acute=true, grave=true, circumflex=true))]
+pub fn py_strip_accents_detailed(
+    py: Python<'_>,
+    text: &str,
+    acute: bool,
+    grave: bool,
+    circumflex: bool,
+) -> PyResult<changes::PyDetailedResult> {
+    let result = crate::allow_threads(py, text, || build_stripper(acute, grave, circumflex).strip_detailed(text));
+    changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precomposed() {
+        assert_eq!(strip_accents("à è ì ò ù á é í ó ú â ê î ô û"), "a e i o u a e i o u a e i o u");
+        assert_eq!(strip_accents("À ÉTAS Ǽ"), "A ETAS Æ");
+    }
+
+    #[test]
+    fn test_combining() {
+        assert_eq!(strip_accents("certe\u{0300} musa\u{0302}"), "certe musa");
+        // Macrons stay, recomposed with their letter
+        assert_eq!(strip_accents("a\u{0304}\u{0301} ā́"), "ā ā");
+    }
+
+    #[test]
+    fn test_per_accent() {
+        let stripper = AccentStripper::new().accent(Accent::Acute, false).accent(Accent::Grave, false);
+        assert_eq!(stripper.strip("benè bene\u{0301} musâ"), "benè bene\u{0301} musa");
+        assert!(!stripper.strips(Accent::Grave));
+        assert!(stripper.strips(Accent::Circumflex));
+    }
+
+    #[test]
+    fn test_other_scripts() {
+        assert_eq!(strip_accents("λόγος"), "λόγος");
+        assert_eq!(strip_accents("arma, 12"), "arma, 12");
+    }
+
+    #[test]
+    fn test_strip_detailed() {
+        let result = AccentStripper::new().strip_detailed("quàm bene\u{0301}");
+        assert_eq!(result.normalized, "quam bene");
+        assert_eq!(result.changes.len(), 2);
+        assert_eq!(result.changes[0].position, 2);
+        assert_eq!(result.changes[0].original, "à");
+        assert_eq!(result.changes[0].normalized, "a");
+        assert_eq!(result.changes[0].rule, "remove grave");
+        assert_eq!(result.changes[1].position, 8);
+        assert_eq!(result.changes[1].original, "e\u{0301}");
+        assert_eq!(result.changes[1].rule, "remove acute");
+        let result = AccentStripper::new().strip_detailed("ǽ\u{0302}");
+        assert_eq!(result.changes[0].rule, "remove acute and circumflex");
+    }
+}
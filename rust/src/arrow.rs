@@ -0,0 +1,680 @@
+//! Normalization of Arrow string columns through the Arrow C data interface.
+//!
+//! [`normalize_array`] reads a `utf8` or `large_utf8` array in place, with
+//! no copy of its strings, normalizes the rows in parallel, and returns a
+//! [`StringColumn`] of the same type that can be handed back across the
+//! interface. The structs here are the interface's stable C ABI, so arrays
+//! can come from pyarrow, Polars, or arrow-rs without linking any of them.
+//!
+//! ```
+//! use latincy_preprocess::arrow::{self, StringColumn, StringType};
+//! use latincy_preprocess::uv::UvNormalizer;
+//! use std::sync::Arc;
+//!
+//! let column = Arc::new(StringColumn::new(StringType::Utf8, &[Some("uir"), None, Some("uia")]).unwrap());
+//! let (schema, array) = column.to_ffi();
+//! let normalized = unsafe { arrow::normalize_array(&UvNormalizer::new(), &schema, &array) }.unwrap();
+//! assert_eq!(normalized.rows().collect::<Vec<_>>(), [Some("vir"), None, Some("via")]);
+//! ```
+//!
+//! In Python, `Pipeline.normalize_arrow` takes any object with
+//! `__arrow_c_array__` or `__arrow_c_stream__` (a pyarrow `Array` or
+//! `ChunkedArray`, a Polars `Series`) and returns an `ArrowStrings`, which
+//! pyarrow and other consumers of the PyCapsule interface import directly.
+
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::types::{PyCapsule, PyTuple};
+
+use crate::pipeline::Normalizer;
+use rayon::prelude::*;
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::io;
+use std::ptr;
+use std::sync::Arc;
+
+/// The C data interface's `ArrowSchema`. Dropping it calls its `release`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ArrowSchema {
+    pub format: *const c_char,
+    pub name: *const c_char,
+    pub metadata: *const c_char,
+    pub flags: i64,
+    pub n_children: i64,
+    pub children: *mut *mut ArrowSchema,
+    pub dictionary: *mut ArrowSchema,
+    pub release: Option<unsafe extern "C" fn(*mut ArrowSchema)>,
+    pub private_data: *mut c_void,
+}
+
+/// The C data interface's `ArrowArray`. Dropping it calls its `release`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ArrowArray {
+    pub length: i64,
+    pub null_count: i64,
+    pub offset: i64,
+    pub n_buffers: i64,
+    pub n_children: i64,
+    pub buffers: *mut *const c_void,
+    pub children: *mut *mut ArrowArray,
+    pub dictionary: *mut ArrowArray,
+    pub release: Option<unsafe extern "C" fn(*mut ArrowArray)>,
+    pub private_data: *mut c_void,
+}
+
+/// The C stream interface's `ArrowArrayStream`. Dropping it calls its
+/// `release`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ArrowArrayStream {
+    pub get_schema: Option<unsafe extern "C" fn(*mut ArrowArrayStream, *mut ArrowSchema) -> c_int>,
+    pub get_next: Option<unsafe extern "C" fn(*mut ArrowArrayStream, *mut ArrowArray) -> c_int>,
+    pub get_last_error: Option<unsafe extern "C" fn(*mut ArrowArrayStream) -> *const c_char>,
+    pub release: Option<unsafe extern "C" fn(*mut ArrowArrayStream)>,
+    pub private_data: *mut c_void,
+}
+
+// Exported structs own their data through `private_data`, which is only
+// freed by `release`
+unsafe impl Send for ArrowSchema {}
+unsafe impl Send for ArrowArray {}
+
+macro_rules! release_on_drop {
+    ($($ty:ty),*) => {
+        $(impl Drop for $ty {
+            fn drop(&mut self) {
+                if let Some(release) = self.release {
+                    // SAFETY: the producer's release callback, called once
+                    unsafe { release(self) }
+                }
+            }
+        })*
+    };
+}
+
+release_on_drop!(ArrowSchema, ArrowArray, ArrowArrayStream);
+
+impl ArrowSchema {
+    /// A released schema, for a producer to fill in.
+    pub fn empty() -> Self {
+        ArrowSchema {
+            format: ptr::null(),
+            name: ptr::null(),
+            metadata: ptr::null(),
+            flags: 0,
+            n_children: 0,
+            children: ptr::null_mut(),
+            dictionary: ptr::null_mut(),
+            release: None,
+            private_data: ptr::null_mut(),
+        }
+    }
+}
+
+impl ArrowArray {
+    /// A released array, for a producer to fill in.
+    pub fn empty() -> Self {
+        ArrowArray {
+            length: 0,
+            null_count: 0,
+            offset: 0,
+            n_buffers: 0,
+            n_children: 0,
+            buffers: ptr::null_mut(),
+            children: ptr::null_mut(),
+            dictionary: ptr::null_mut(),
+            release: None,
+            private_data: ptr::null_mut(),
+        }
+    }
+}
+
+/// The string types [`normalize_array`] reads and writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringType {
+    /// `utf8` (format `u`), with 32-bit offsets.
+    Utf8,
+    /// `large_utf8` (format `U`), with 64-bit offsets.
+    LargeUtf8,
+}
+
+impl StringType {
+    /// The type with the C data interface format string `format`.
+    pub fn from_format(format: &str) -> Option<Self> {
+        match format {
+            "u" => Some(StringType::Utf8),
+            "U" => Some(StringType::LargeUtf8),
+            _ => None,
+        }
+    }
+
+    fn format(self) -> &'static CStr {
+        match self {
+            StringType::Utf8 => c"u",
+            StringType::LargeUtf8 => c"U",
+        }
+    }
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// The type of the string array `schema` describes.
+///
+/// # Safety
+///
+/// `schema` must be a valid C data interface schema.
+pub unsafe fn string_type(schema: &ArrowSchema) -> io::Result<StringType> {
+    if schema.release.is_none() || schema.format.is_null() {
+        return Err(invalid("the Arrow schema has been released"));
+    }
+    let format = CStr::from_ptr(schema.format).to_string_lossy();
+    match StringType::from_format(&format) {
+        Some(string_type) if schema.dictionary.is_null() => Ok(string_type),
+        _ => Err(invalid(format!("expected a utf8 or large_utf8 array, got Arrow format {format:?}"))),
+    }
+}
+
+/// The rows of the string array `array` of type `string_type`, `None` for
+/// nulls, borrowed from its buffers.
+///
+/// # Safety
+///
+/// `array` must be a valid C data interface array of `string_type`, and
+/// stay unreleased while the rows are in use.
+pub unsafe fn rows(string_type: StringType, array: &ArrowArray) -> io::Result<Vec<Option<&str>>> {
+    if array.release.is_none() {
+        return Err(invalid("the Arrow array has been released"));
+    }
+    if array.n_buffers != 3 || array.buffers.is_null() || array.length < 0 || array.offset < 0 {
+        return Err(invalid("malformed Arrow string array"));
+    }
+    let (length, offset) = (array.length as usize, array.offset as usize);
+    let validity = *array.buffers as *const u8;
+    let offsets = *array.buffers.add(1);
+    let values = *array.buffers.add(2) as *const u8;
+    if offsets.is_null() && length > 0 {
+        return Err(invalid("malformed Arrow string array"));
+    }
+    let bound = |k: usize| -> usize {
+        match string_type {
+            StringType::Utf8 => *(offsets as *const i32).add(k) as usize,
+            StringType::LargeUtf8 => *(offsets as *const i64).add(k) as usize,
+        }
+    };
+    let mut rows = Vec::with_capacity(length);
+    for i in offset..offset + length {
+        if array.null_count != 0 && !validity.is_null() && *validity.add(i / 8) & (1 << (i % 8)) == 0 {
+            rows.push(None);
+            continue;
+        }
+        let (start, end) = (bound(i), bound(i + 1));
+        let bytes = match end - start {
+            0 => &[][..],
+            len => std::slice::from_raw_parts(values.add(start), len),
+        };
+        let text = std::str::from_utf8(bytes).map_err(|_| invalid(format!("row {} is not UTF-8", i - offset)))?;
+        rows.push(Some(text));
+    }
+    Ok(rows)
+}
+
+/// `normalizer` applied to each row of the string array `array`, in
+/// parallel on the current rayon pool. Nulls stay null, and the column has
+/// the type of `array`.
+///
+/// # Safety
+///
+/// `schema` and `array` must be valid C data interface structs for the
+/// same array.
+pub unsafe fn normalize_array(
+    normalizer: &dyn Normalizer,
+    schema: &ArrowSchema,
+    array: &ArrowArray,
+) -> io::Result<StringColumn> {
+    let string_type = string_type(schema)?;
+    normalize_rows(normalizer, string_type, &rows(string_type, array)?, true)
+}
+
+/// Like [`normalize_array`], for every array of the stream `stream`,
+/// concatenated into one column.
+///
+/// # Safety
+///
+/// `stream` must be a valid C stream interface stream of string arrays.
+pub unsafe fn normalize_stream(normalizer: &dyn Normalizer, stream: &mut ArrowArrayStream) -> io::Result<StringColumn> {
+    let (string_type, chunks) = read_stream(stream)?;
+    let mut all = Vec::new();
+    for chunk in &chunks {
+        all.extend(rows(string_type, chunk)?);
+    }
+    normalize_rows(normalizer, string_type, &all, true)
+}
+
+/// The string type and the arrays of the stream `stream`, read to its end.
+///
+/// # Safety
+///
+/// `stream` must be a valid C stream interface stream.
+pub unsafe fn read_stream(stream: &mut ArrowArrayStream) -> io::Result<(StringType, Vec<ArrowArray>)> {
+    let (Some(get_schema), Some(get_next)) = (stream.get_schema, stream.get_next) else {
+        return Err(invalid("the Arrow stream has been released"));
+    };
+    let stream_error = |stream: &mut ArrowArrayStream, code: c_int| {
+        let message = stream.get_last_error.map(|get| get(stream)).filter(|message| !message.is_null());
+        match message {
+            Some(message) => invalid(format!("Arrow stream: {}", CStr::from_ptr(message).to_string_lossy())),
+            None => invalid(format!("Arrow stream error {code}")),
+        }
+    };
+    let mut schema = ArrowSchema::empty();
+    let code = get_schema(stream, &mut schema);
+    if code != 0 {
+        return Err(stream_error(stream, code));
+    }
+    let string_type = string_type(&schema)?;
+    let mut chunks = Vec::new();
+    loop {
+        let mut array = ArrowArray::empty();
+        let code = get_next(stream, &mut array);
+        if code != 0 {
+            return Err(stream_error(stream, code));
+        }
+        if array.release.is_none() {
+            return Ok((string_type, chunks));
+        }
+        chunks.push(array);
+    }
+}
+
+fn normalize_rows(
+    normalizer: &dyn Normalizer,
+    string_type: StringType,
+    rows: &[Option<&str>],
+    parallel: bool,
+) -> io::Result<StringColumn> {
+    let normalize = |row: &Option<&str>| row.map(|text| normalizer.normalize_text(text));
+    let normalized: Vec<Option<String>> = match parallel {
+        true => rows.par_iter().map(normalize).collect(),
+        false => rows.iter().map(normalize).collect(),
+    };
+    StringColumn::new(string_type, &normalized)
+}
+
+#[derive(Debug)]
+enum Offsets {
+    Utf8(Vec<i32>),
+    LargeUtf8(Vec<i64>),
+}
+
+/// An owned Arrow string array, shared with consumers by [`StringColumn::to_ffi`]
+/// without copying.
+#[derive(Debug)]
+pub struct StringColumn {
+    validity: Option<Vec<u8>>,
+    null_count: usize,
+    offsets: Offsets,
+    values: Vec<u8>,
+}
+
+impl StringColumn {
+    /// A column of `string_type` holding `rows`, `None` for nulls. Fails if
+    /// the strings are too long for 32-bit offsets.
+    pub fn new<S: AsRef<str>>(string_type: StringType, rows: &[Option<S>]) -> io::Result<Self> {
+        let mut values = Vec::with_capacity(rows.iter().flatten().map(|row| row.as_ref().len()).sum());
+        let mut offsets = Vec::with_capacity(rows.len() + 1);
+        offsets.push(0);
+        for row in rows {
+            if let Some(row) = row {
+                values.extend_from_slice(row.as_ref().as_bytes());
+            }
+            offsets.push(values.len());
+        }
+        let offsets = match string_type {
+            StringType::Utf8 => Offsets::Utf8(
+                offsets
+                    .into_iter()
+                    .map(i32::try_from)
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| invalid("the strings do not fit a utf8 array; use large_utf8"))?,
+            ),
+            StringType::LargeUtf8 => Offsets::LargeUtf8(offsets.into_iter().map(|k| k as i64).collect()),
+        };
+        let null_count = rows.iter().filter(|row| row.is_none()).count();
+        let validity = (null_count > 0).then(|| {
+            let mut bits = vec![0u8; rows.len().div_ceil(8)];
+            for (i, _) in rows.iter().enumerate().filter(|(_, row)| row.is_some()) {
+                bits[i / 8] |= 1 << (i % 8);
+            }
+            bits
+        });
+        Ok(StringColumn {
+            validity,
+            null_count,
+            offsets,
+            values,
+        })
+    }
+
+    pub fn string_type(&self) -> StringType {
+        match self.offsets {
+            Offsets::Utf8(_) => StringType::Utf8,
+            Offsets::LargeUtf8(_) => StringType::LargeUtf8,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.offsets {
+            Offsets::Utf8(offsets) => offsets.len() - 1,
+            Offsets::LargeUtf8(offsets) => offsets.len() - 1,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn null_count(&self) -> usize {
+        self.null_count
+    }
+
+    /// Row `i`, or `None` if it is null.
+    pub fn get(&self, i: usize) -> Option<&str> {
+        if let Some(validity) = &self.validity {
+            if validity[i / 8] & (1 << (i % 8)) == 0 {
+                return None;
+            }
+        }
+        let (start, end) = match &self.offsets {
+            Offsets::Utf8(offsets) => (offsets[i] as usize, offsets[i + 1] as usize),
+            Offsets::LargeUtf8(offsets) => (offsets[i] as usize, offsets[i + 1] as usize),
+        };
+        // The values are concatenated strings, split at their bounds
+        Some(std::str::from_utf8(&self.values[start..end]).unwrap_or_default())
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = Option<&str>> {
+        (0..self.len()).map(|i| self.get(i))
+    }
+
+    /// The column as C data interface structs. The array shares the
+    /// column's buffers, which live until both it and the column are gone.
+    pub fn to_ffi(self: &Arc<Self>) -> (ArrowSchema, ArrowArray) {
+        let mut schema = ArrowSchema::empty();
+        schema.format = self.string_type().format().as_ptr();
+        schema.name = c"".as_ptr();
+        // ARROW_FLAG_NULLABLE
+        schema.flags = 2;
+        schema.release = Some(release_schema);
+
+        let offsets = match &self.offsets {
+            Offsets::Utf8(offsets) => offsets.as_ptr() as *const c_void,
+            Offsets::LargeUtf8(offsets) => offsets.as_ptr() as *const c_void,
+        };
+        let validity = self.validity.as_ref().map_or(ptr::null(), |bits| bits.as_ptr() as *const c_void);
+        let mut exported = Box::new(Exported {
+            _column: Arc::clone(self),
+            buffers: [validity, offsets, self.values.as_ptr() as *const c_void],
+        });
+        let mut array = ArrowArray::empty();
+        array.length = self.len() as i64;
+        array.null_count = self.null_count as i64;
+        array.n_buffers = 3;
+        array.buffers = exported.buffers.as_mut_ptr();
+        array.private_data = Box::into_raw(exported) as *mut c_void;
+        array.release = Some(release_array);
+        (schema, array)
+    }
+}
+
+/// The private data of an exported array.
+struct Exported {
+    _column: Arc<StringColumn>,
+    buffers: [*const c_void; 3],
+}
+
+unsafe extern "C" fn release_schema(schema: *mut ArrowSchema) {
+    // The format and name are static
+    (*schema).release = None;
+}
+
+unsafe extern "C" fn release_array(array: *mut ArrowArray) {
+    drop(Box::from_raw((*array).private_data as *mut Exported));
+    (*array).private_data = ptr::null_mut();
+    (*array).release = None;
+}
+
+// =============================================================================
+// PyO3 wrappers
+// =============================================================================
+
+/// An Arrow string array built in Rust, exposed as `_rust.ArrowStrings`:
+/// `pyarrow.array()` and other consumers of the Arrow PyCapsule interface
+/// import it without a copy.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "ArrowStrings", frozen)]
+pub struct PyArrowStrings {
+    pub(crate) inner: Arc<StringColumn>,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyArrowStrings {
+    /// A `large_utf8` column of `rows`, with `large=False` a `utf8` one.
+    #[new]
+    #[pyo3(signature = (rows, large=true))]
+    fn new(rows: Vec<Option<String>>, large: bool) -> PyResult<Self> {
+        let string_type = if large { StringType::LargeUtf8 } else { StringType::Utf8 };
+        let inner = StringColumn::new(string_type, &rows).map_err(value_error)?;
+        Ok(PyArrowStrings { inner: Arc::new(inner) })
+    }
+
+    /// `"utf8"` or `"large_utf8"`.
+    #[getter]
+    fn string_type(&self) -> &'static str {
+        match self.inner.string_type() {
+            StringType::Utf8 => "utf8",
+            StringType::LargeUtf8 => "large_utf8",
+        }
+    }
+
+    #[getter]
+    fn null_count(&self) -> usize {
+        self.inner.null_count()
+    }
+
+    fn to_pylist(&self) -> Vec<Option<&str>> {
+        self.inner.rows().collect()
+    }
+
+    /// The Arrow PyCapsule protocol; `requested_schema` is ignored.
+    #[pyo3(signature = (requested_schema=None))]
+    fn __arrow_c_array__<'py>(
+        &self,
+        py: Python<'py>,
+        requested_schema: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyTuple>> {
+        let _ = requested_schema;
+        let (schema, array) = self.inner.to_ffi();
+        let schema = PyCapsule::new(py, schema, Some(c"arrow_schema".to_owned()))?;
+        let array = PyCapsule::new(py, array, Some(c"arrow_array".to_owned()))?;
+        PyTuple::new(py, [schema.into_any(), array.into_any()])
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ArrowStrings(len={}, type='{}')", self.inner.len(), self.string_type())
+    }
+}
+
+#[cfg(feature = "pyo3-backend")]
+fn value_error(e: io::Error) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(e.to_string())
+}
+
+/// The capsule `capsule` named `name`, as a pointer to its struct.
+#[cfg(feature = "pyo3-backend")]
+fn capsule_pointer<T>(capsule: &Bound<'_, PyAny>, name: &CStr) -> PyResult<*mut T> {
+    let capsule = capsule.downcast::<PyCapsule>()?;
+    if capsule.name()? != Some(name) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "expected a PyCapsule named {:?}",
+            name.to_string_lossy()
+        )));
+    }
+    Ok(capsule.pointer() as *mut T)
+}
+
+/// `pipeline` applied to the Arrow string array or stream `array`, with
+/// the rows normalized in parallel on `threads` threads (0 for one per
+/// core) unless a stage is a Python function.
+#[cfg(feature = "pyo3-backend")]
+pub(crate) fn py_normalize_arrow(
+    pipeline: &crate::pipeline::PyPipeline,
+    py: Python<'_>,
+    array: &Bound<'_, PyAny>,
+    threads: usize,
+) -> PyResult<PyArrowStrings> {
+    let normalizer: &dyn Normalizer = &pipeline.inner;
+    let run = |string_type: StringType, rows: &[Option<&str>]| -> PyResult<StringColumn> {
+        let column = if pipeline.python_stages() {
+            normalize_rows(normalizer, string_type, rows, false)
+        } else {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+            py.allow_threads(|| pool.install(|| normalize_rows(normalizer, string_type, rows, true)))
+        };
+        pipeline.checked(column)?.map_err(value_error)
+    };
+    let column = if array.hasattr("__arrow_c_array__")? {
+        let capsules = array.call_method0("__arrow_c_array__")?;
+        let schema = capsule_pointer::<ArrowSchema>(&capsules.get_item(0)?, c"arrow_schema")?;
+        let array = capsule_pointer::<ArrowArray>(&capsules.get_item(1)?, c"arrow_array")?;
+        // SAFETY: the capsules hold valid structs, which their destructors
+        // release once `capsules` is dropped
+        let string_type = unsafe { string_type(&*schema) }.map_err(value_error)?;
+        let rows = unsafe { rows(string_type, &*array) }.map_err(value_error)?;
+        run(string_type, &rows)?
+    } else if array.hasattr("__arrow_c_stream__")? {
+        let capsule = array.call_method0("__arrow_c_stream__")?;
+        let stream = capsule_pointer::<ArrowArrayStream>(&capsule, c"arrow_array_stream")?;
+        // SAFETY: as above
+        let (string_type, chunks) = unsafe { read_stream(&mut *stream) }.map_err(value_error)?;
+        let mut all = Vec::new();
+        for chunk in &chunks {
+            all.extend(unsafe { rows(string_type, chunk) }.map_err(value_error)?);
+        }
+        run(string_type, &all)?
+    } else {
+        return Err(pyo3::exceptions::PyTypeError::new_err(format!(
+            "expected an Arrow array or stream, not {}",
+            array.get_type().name()?
+        )));
+    };
+    Ok(PyArrowStrings { inner: Arc::new(column) })
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uv::UvNormalizer;
+
+    fn column(string_type: StringType, rows: &[Option<&str>]) -> Arc<StringColumn> {
+        Arc::new(StringColumn::new(string_type, rows).unwrap())
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let rows = [Some("uir"), None, Some(""), Some("Cæsar")];
+        for string_type in [StringType::Utf8, StringType::LargeUtf8] {
+            let column = column(string_type, &rows);
+            assert_eq!(column.rows().collect::<Vec<_>>(), rows);
+            assert_eq!((column.len(), column.null_count()), (4, 1));
+            let (schema, array) = column.to_ffi();
+            assert_eq!(unsafe { self::string_type(&schema) }.unwrap(), string_type);
+            assert_eq!(unsafe { self::rows(string_type, &array) }.unwrap(), rows);
+        }
+    }
+
+    #[test]
+    fn test_normalize_array() {
+        let column = column(StringType::Utf8, &[Some("uirtus"), None, Some("Arma uirumque")]);
+        let (schema, mut array) = column.to_ffi();
+        let normalized = unsafe { normalize_array(&UvNormalizer::new(), &schema, &array) }.unwrap();
+        assert_eq!(normalized.rows().collect::<Vec<_>>(), [Some("virtus"), None, Some("Arma virumque")]);
+        assert_eq!(normalized.string_type(), StringType::Utf8);
+        // A slice of the array
+        (array.offset, array.length) = (1, 2);
+        let normalized = unsafe { normalize_array(&UvNormalizer::new(), &schema, &array) }.unwrap();
+        assert_eq!(normalized.rows().collect::<Vec<_>>(), [None, Some("Arma virumque")]);
+    }
+
+    /// A stream of `chunks`, as a producer would export it.
+    fn stream(chunks: Vec<Arc<StringColumn>>) -> ArrowArrayStream {
+        unsafe extern "C" fn get_schema(stream: *mut ArrowArrayStream, out: *mut ArrowSchema) -> c_int {
+            let chunks = &*((*stream).private_data as *const std::vec::IntoIter<Arc<StringColumn>>);
+            ptr::write(out, chunks.as_slice()[0].to_ffi().0);
+            0
+        }
+        unsafe extern "C" fn get_next(stream: *mut ArrowArrayStream, out: *mut ArrowArray) -> c_int {
+            let chunks = &mut *((*stream).private_data as *mut std::vec::IntoIter<Arc<StringColumn>>);
+            ptr::write(out, chunks.next().map_or_else(ArrowArray::empty, |chunk| chunk.to_ffi().1));
+            0
+        }
+        unsafe extern "C" fn release(stream: *mut ArrowArrayStream) {
+            drop(Box::from_raw((*stream).private_data as *mut std::vec::IntoIter<Arc<StringColumn>>));
+            (*stream).release = None;
+        }
+        ArrowArrayStream {
+            get_schema: Some(get_schema),
+            get_next: Some(get_next),
+            get_last_error: None,
+            release: Some(release),
+            private_data: Box::into_raw(Box::new(chunks.into_iter())) as *mut c_void,
+        }
+    }
+
+    #[test]
+    fn test_normalize_stream() {
+        let chunks = vec![
+            column(StringType::LargeUtf8, &[Some("uir")]),
+            column(StringType::LargeUtf8, &[None, Some("uia")]),
+        ];
+        let mut stream = stream(chunks);
+        let normalized = unsafe { normalize_stream(&UvNormalizer::new(), &mut stream) }.unwrap();
+        assert_eq!(normalized.rows().collect::<Vec<_>>(), [Some("vir"), None, Some("via")]);
+        assert_eq!(normalized.string_type(), StringType::LargeUtf8);
+    }
+
+    #[test]
+    fn test_release() {
+        let column = column(StringType::LargeUtf8, &[Some("uia")]);
+        let (schema, array) = column.to_ffi();
+        assert_eq!(Arc::strong_count(&column), 2);
+        drop((schema, array));
+        assert_eq!(Arc::strong_count(&column), 1);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let column = column(StringType::Utf8, &[Some("uia")]);
+        let (mut schema, array) = column.to_ffi();
+        schema.format = c"i".as_ptr();
+        let err = unsafe { normalize_array(&UvNormalizer::new(), &schema, &array) }.unwrap_err();
+        assert_eq!(err.to_string(), "expected a utf8 or large_utf8 array, got Arrow format \"i\"");
+        assert!(unsafe { rows(StringType::Utf8, &ArrowArray::empty()) }.is_err());
+    }
+}
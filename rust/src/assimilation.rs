@@ -0,0 +1,265 @@
+//! Normalization of prefix assimilation: *adfert*/*affert*,
+//! *inperium*/*imperium*, *conlega*/*collega*.
+//!
+//! Editions and manuscripts differ in whether a prefix keeps its final
+//! consonant before the stem or assimilates it. [`normalize`] rewrites the
+//! prefixes in [`ASSIMILATION_RULES`] to one [`Style`], so that frequency
+//! counts see one form. Only the start of a word is touched.
+
+use crate::changes::{self, ChangeRecord};
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+
+/// `(unassimilated, assimilated)` word beginnings: a prefix and the first
+/// letter of the stem. Both sides have the same length.
+pub const ASSIMILATION_RULES: &[(&str, &str)] = &[
+    ("adc", "acc"),
+    ("adf", "aff"),
+    ("adg", "agg"),
+    ("adl", "all"),
+    ("adn", "ann"),
+    ("adp", "app"),
+    ("adq", "acq"),
+    ("adr", "arr"),
+    ("ads", "ass"),
+    ("adt", "att"),
+    ("inb", "imb"),
+    ("inl", "ill"),
+    ("inm", "imm"),
+    ("inp", "imp"),
+    ("inr", "irr"),
+    ("conb", "comb"),
+    ("conl", "coll"),
+    ("conm", "comm"),
+    ("conp", "comp"),
+    ("conr", "corr"),
+    ("obc", "occ"),
+    ("obf", "off"),
+    ("obp", "opp"),
+    ("subc", "succ"),
+    ("subf", "suff"),
+    ("subg", "sugg"),
+    ("subm", "summ"),
+    ("subp", "supp"),
+    ("subr", "surr"),
+    ("exf", "eff"),
+];
+
+/// Words whose double consonant is not an assimilated prefix, so they are
+/// never unassimilated (*ille*, not *inle*; *summus*, not *submus*).
+const NOT_ASSIMILATED: &[&str] = &[
+    "ille", "illa", "illud", "illi", "illo", "illos", "illas", "illis", "illorum", "illarum", "illum",
+    "illam", "illic", "illinc", "illuc", "illac", "collum", "colla", "collo", "colli", "allium", "alli",
+    "allio", "succus", "succi", "succo", "succum", "offa", "offam", "offae", "offas", "summus", "summa",
+    "summum", "summi", "summo", "summam", "summae", "summis", "summos", "summas", "summe",
+];
+
+/// Which spelling of an assimilable prefix to write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+    /// The assimilated form (*affert*, *imperium*, *collega*); the default,
+    /// as in most modern editions.
+    #[default]
+    Assimilated,
+    /// The prefix kept intact (*adfert*, *inperium*, *conlega*).
+    Unassimilated,
+}
+
+impl Style {
+    /// Parse `"assimilated"` or `"unassimilated"`.
+    pub fn from_name(name: &str) -> Option<Style> {
+        match name {
+            "assimilated" => Some(Style::Assimilated),
+            "unassimilated" => Some(Style::Unassimilated),
+            _ => None,
+        }
+    }
+}
+
+/// The rule that rewrites the start of a lowercase `word` to `style`, as
+/// `(from, to)`.
+fn rule_for(word: &str, style: Style) -> Option<(&'static str, &'static str)> {
+    ASSIMILATION_RULES.iter().find_map(|&(unassimilated, assimilated)| {
+        let (from, to) = match style {
+            Style::Assimilated => (unassimilated, assimilated),
+            Style::Unassimilated => (assimilated, unassimilated),
+        };
+        // A bare prefix (*ad*) or a word that is only the pattern is left
+        (word.len() > from.len() && word.starts_with(from)).then_some((from, to))
+    })
+}
+
+/// Rewrite assimilable prefixes to `style`, keeping case.
+///
+/// ```
+/// use latincy_preprocess::assimilation::{normalize, Style};
+///
+/// assert_eq!(normalize("adfert inperium conlega", Style::Assimilated), "affert imperium collega");
+/// assert_eq!(normalize("Affert imperium ille", Style::Unassimilated), "Adfert inperium ille");
+/// ```
+pub fn normalize(text: &str, style: Style) -> String {
+    normalize_detailed(text, style).normalized
+}
+
+/// Like [`normalize`], recording one [`ChangeRecord`] per rewritten prefix.
+pub fn normalize_detailed(text: &str, style: Style) -> DetailedResult {
+    let chars: Vec<char> = text.chars().collect();
+    let mut normalized = String::with_capacity(text.len());
+    let mut changes = Vec::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_alphabetic() {
+            normalized.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let end = chars[i..].iter().position(|c| !c.is_alphabetic()).map_or(chars.len(), |p| i + p);
+        let original = &chars[i..end];
+        let lower: String = original.iter().flat_map(|c| c.to_lowercase()).collect();
+        let rule = match style {
+            Style::Unassimilated if NOT_ASSIMILATED.contains(&lower.as_str()) => None,
+            _ => rule_for(&lower, style),
+        };
+        match rule {
+            Some((from, to)) if lower.chars().count() == original.len() => {
+                let len = from.chars().count();
+                // The patterns are ASCII and of equal length, so each letter
+                // keeps the case of the one it replaces
+                let rewritten: String = original[..len]
+                    .iter()
+                    .zip(to.chars())
+                    .map(|(&o, t)| if o.is_uppercase() { t.to_ascii_uppercase() } else { t })
+                    .collect();
+                if rewritten.chars().ne(original[..len].iter().copied()) {
+                    changes.push(ChangeRecord {
+                        position: i,
+                        original: original[..len].iter().collect(),
+                        normalized: rewritten.clone(),
+                        pass: 1,
+                        rule: format!("{}- → {}-", from, to),
+                        context: changes::context(&chars, i, len),
+                    });
+                }
+                normalized.push_str(&rewritten);
+                normalized.extend(&original[len..]);
+            }
+            _ => normalized.extend(original),
+        }
+        i = end;
+    }
+
+    DetailedResult {
+        original: text.to_string(),
+        normalized,
+        changes,
+    }
+}
+
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<ChangeRecord>,
+}
+
+impl Normalizer for Style {
+    fn name(&self) -> &str {
+        "assimilation"
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = normalize_detailed(text, *self);
+        NormalizeOutput::from_changes(result.original, result.normalized, result.changes)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+fn parse_style(style: &str) -> PyResult<Style> {
+    Style::from_name(style).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "style must be 'assimilated' or 'unassimilated', got {:?}",
+            style
+        ))
+    })
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "normalize_assimilation", signature = (text, style="assimilated"))]
+pub fn py_normalize_assimilation(py: Python<'_>, text: &str, style: &str) -> PyResult<String> {
+    let style = parse_style(style)?;
+    Ok(crate::allow_threads(py, text, || normalize(text, style)))
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "normalize_assimilation_detailed", signature = (text, style="assimilated"))]
+pub fn py_normalize_assimilation_detailed(
+    py: Python<'_>,
+    text: &str,
+    style: &str,
+) -> PyResult<changes::PyDetailedResult> {
+    let style = parse_style(style)?;
+    let result = crate::allow_threads(py, text, || normalize_detailed(text, style));
+    changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assimilate() {
+        let assimilate = |text| normalize(text, Style::Assimilated);
+        assert_eq!(assimilate("adfert adsum adtingo"), "affert assum attingo");
+        assert_eq!(assimilate("inperium inlustris inmortalis"), "imperium illustris immortalis");
+        assert_eq!(assimilate("conlega conrumpo conpono"), "collega corrumpo compono");
+        assert_eq!(assimilate("obfero subfero exfero"), "offero suffero effero");
+        // Only word beginnings, and not a bare prefix
+        assert_eq!(assimilate("ad inde"), "ad inde");
+        assert_eq!(assimilate("affert"), "affert");
+    }
+
+    #[test]
+    fn test_unassimilate() {
+        let unassimilate = |text| normalize(text, Style::Unassimilated);
+        assert_eq!(unassimilate("affert imperium collega"), "adfert inperium conlega");
+        assert_eq!(unassimilate("succedo opprimo"), "subcedo obprimo");
+        // Genuine double consonants are kept
+        assert_eq!(unassimilate("ille summus collum"), "ille summus collum");
+    }
+
+    #[test]
+    fn test_case() {
+        assert_eq!(normalize("Adfert CONLEGA", Style::Assimilated), "Affert COLLEGA");
+        assert_eq!(normalize("IMPERIUM, Imperium.", Style::Unassimilated), "INPERIUM, Inperium.");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = "adfert inperium conlega subfero";
+        let assimilated = normalize(text, Style::Assimilated);
+        assert_eq!(normalize(&assimilated, Style::Unassimilated), text);
+    }
+
+    #[test]
+    fn test_normalize_detailed() {
+        let result = normalize_detailed("et adfert", Style::Assimilated);
+        assert_eq!(result.normalized, "et affert");
+        assert_eq!(result.changes.len(), 1);
+        assert_eq!(result.changes[0].position, 3);
+        assert_eq!(result.changes[0].original, "adf");
+        assert_eq!(result.changes[0].normalized, "aff");
+        assert_eq!(result.changes[0].rule, "adf- → aff-");
+        assert_eq!(result.changes[0].context, "et [adf]ert");
+    }
+}
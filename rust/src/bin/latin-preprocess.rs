@@ -0,0 +1,1254 @@
+//! Command-line interface for latincy-preprocess.
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use latincy_preprocess::changes::{ChangeSummary, OutputDetail};
+use latincy_preprocess::eval;
+use latincy_preprocess::lexicon::Lexicon;
+use latincy_preprocess::long_s::{LongSNormalizer, ReviewItem};
+use latincy_preprocess::ngrams::{ModelMetadata, NgramData, BINARY_FILE_NAME};
+use latincy_preprocess::pipeline::{NormalizeOutput, Normalizer, Pipeline};
+use latincy_preprocess::profile;
+use latincy_preprocess::protect::Detector;
+use latincy_preprocess::regression;
+use latincy_preprocess::uv::UvNormalizer;
+use latincy_preprocess::uv_model::UvModel;
+use rayon::prelude::*;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "latin-preprocess", version, about = "Latin text preprocessing")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Number of files to process at once (default: one per CPU)
+    #[arg(short, long, global = true)]
+    jobs: Option<usize>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Build long-s n-gram tables from a directory of clean Latin text
+    TrainNgrams {
+        /// Directory containing the training corpus
+        input: PathBuf,
+        /// Directory to write the model to
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Glob pattern, relative to INPUT, selecting the corpus files
+        #[arg(long, default_value = "**/*.txt")]
+        pattern: String,
+        /// Model format(s) to write
+        #[arg(long, value_enum, default_value_t = ModelFormat::All)]
+        format: ModelFormat,
+    },
+    /// Train a u/v model on a directory of clean Latin text spelled with
+    /// both u and v
+    TrainUv {
+        /// Directory containing the training corpus
+        input: PathBuf,
+        /// File to write the model to
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Glob pattern, relative to INPUT, selecting the corpus files
+        #[arg(long, default_value = "**/*.txt")]
+        pattern: String,
+        /// Least times a feature must be seen to be used
+        #[arg(long, default_value_t = latincy_preprocess::uv_model::DEFAULT_MIN_COUNT)]
+        min_count: u64,
+        /// Least confidence for the model to override a rule
+        #[arg(long, default_value_t = latincy_preprocess::uv_model::DEFAULT_MIN_CONFIDENCE)]
+        min_confidence: f64,
+    },
+    /// Generate long-s Pass 2 word lists from a wordform frequency list
+    WordLists {
+        /// Lexicon file: one word and frequency per line, or JSON
+        lexicon: PathBuf,
+        /// File to write the allowlist of attested f-initial words to
+        #[arg(long)]
+        allowlist: PathBuf,
+        /// File to write the denylist of unattested fu-/fe-/fi- forms to
+        #[arg(long)]
+        denylist: Option<PathBuf>,
+        /// Minimum frequency for a word to count as attested
+        #[arg(long, default_value_t = 5)]
+        min_frequency: u64,
+    },
+    /// Normalize u/v spelling
+    Uv {
+        #[command(flatten)]
+        files: Files,
+        /// u/v model (from train-uv) to decide what the rules guess by
+        /// position
+        #[arg(long)]
+        model: Option<PathBuf>,
+    },
+    /// Correct long-s OCR errors (f for s)
+    LongS {
+        #[command(flatten)]
+        files: Files,
+        /// Pass 2 decision threshold
+        #[arg(long)]
+        threshold: Option<f64>,
+        /// Apply only the Pass 1 rules
+        #[arg(long)]
+        no_pass2: bool,
+        /// Directory of n-gram tables to use in place of the bundled model
+        #[arg(long)]
+        ngrams: Option<PathBuf>,
+        /// Lexicon file for the Pass 3 backoff
+        #[arg(long)]
+        lexicon: Option<PathBuf>,
+    },
+    /// Normalize text files through a pipeline of stages
+    #[command(visible_alias = "pipeline")]
+    Normalize {
+        #[command(flatten)]
+        files: Files,
+        #[command(flatten)]
+        stages: Stages,
+        /// Write nothing; print a unified diff of each file's proposed
+        /// changes to stdout
+        #[arg(long, conflicts_with_all = ["output", "in_place", "check"])]
+        dry_run: bool,
+        /// With --dry-run, print a word-level diff instead
+        #[arg(long, requires = "dry_run")]
+        word_diff: bool,
+        /// Lines of context in unified diffs
+        #[arg(long, default_value_t = 3)]
+        context: usize,
+    },
+    /// Count the changes a pipeline would make, by stage and rule
+    Stats {
+        #[command(flatten)]
+        inputs: Inputs,
+        #[command(flatten)]
+        stages: Stages,
+    },
+    /// Count what a corpus has that the normalizers deal with (v, j, long
+    /// s, ligatures, sigla, capitals, Greek) and guess its era
+    Profile {
+        #[command(flatten)]
+        inputs: Inputs,
+        /// Print the profile as JSON
+        #[arg(long)]
+        json: bool,
+        /// Write a pipeline config of the stages the profile suggests to
+        /// this file (TOML if it ends in .toml, else JSON)
+        #[arg(long, value_name = "CONFIG")]
+        recommend: Option<PathBuf>,
+    },
+    /// Check a pipeline against a golden corpus of NAME.input.txt and
+    /// NAME.expected.txt files, exiting with status 1 on any mismatch
+    TestCorpus {
+        /// Directory of the corpus
+        dir: PathBuf,
+        #[command(flatten)]
+        stages: Stages,
+        /// Write the expected files from the pipeline's output instead
+        #[arg(long)]
+        bless: bool,
+        /// Print the report as JSON
+        #[arg(long, conflicts_with = "bless")]
+        json: bool,
+    },
+    /// Score a pipeline against hand-corrected texts: precision, recall,
+    /// and F1 for each rule and confusion, and the errors in context
+    Eval {
+        /// Raw text, or a directory of raw texts; with --format tsv or tei,
+        /// the gold-standard file or a directory of them
+        raw: PathBuf,
+        /// The gold text, or a directory of gold texts with the same names;
+        /// not read with --format tsv or tei
+        gold: Option<PathBuf>,
+        /// How the gold standard is given
+        #[arg(long, value_enum, default_value_t = GoldFormat::Text)]
+        format: GoldFormat,
+        #[command(flatten)]
+        stages: Stages,
+        /// Print the evaluation as JSON, with every error
+        #[arg(long)]
+        json: bool,
+        /// Most errors (or patterns) to list
+        #[arg(long, default_value_t = 50)]
+        max_errors: usize,
+        /// List the patterns of the errors, with the exception words,
+        /// stems, and rule gaps they suggest, instead of the errors
+        #[arg(long)]
+        mine: bool,
+    },
+}
+
+/// The files a subcommand reads.
+#[derive(Args)]
+struct Inputs {
+    /// Files to read; stdin if none
+    inputs: Vec<PathBuf>,
+    /// Read every file under this directory, recursively, instead
+    #[arg(long, conflicts_with = "inputs")]
+    input_dir: Option<PathBuf>,
+    /// With --input-dir, read only the files matching this glob; a pattern
+    /// with a `/` is matched against the path under the directory, any
+    /// other against the file name (repeatable)
+    #[arg(long, requires = "input_dir")]
+    include: Vec<String>,
+    /// With --input-dir, skip the files matching this glob (repeatable)
+    #[arg(long, requires = "input_dir")]
+    exclude: Vec<String>,
+    /// Encoding of the input; by default each file's is detected
+    #[arg(long, value_enum)]
+    encoding: Option<Encoding>,
+}
+
+/// The input and output of a normalizing subcommand.
+#[derive(Args)]
+struct Files {
+    #[command(flatten)]
+    inputs: Inputs,
+    /// Directory to write the normalized files to, under their file names
+    /// (which must differ) or, with --input-dir, their paths under it;
+    /// without it the normalized text goes to stdout
+    #[arg(short, long, visible_alias = "output-dir")]
+    output: Option<PathBuf>,
+    /// Rewrite the input files that change, each atomically
+    #[arg(short, long, conflicts_with = "output")]
+    in_place: bool,
+    /// Write nothing; list the changes that would be made, and exit with
+    /// status 1 if there are more than --max-changes
+    #[arg(long, conflicts_with_all = ["output", "in_place"])]
+    check: bool,
+    /// With --check, the number of changes to allow
+    #[arg(long, requires = "check", default_value_t = 0)]
+    max_changes: usize,
+    /// With --in-place, keep each original under its name plus this suffix
+    #[arg(long, requires = "in_place")]
+    backup_suffix: Option<String>,
+    /// Encoding to write the normalized text in; by default each file's
+    /// input encoding
+    #[arg(long, value_enum)]
+    output_encoding: Option<Encoding>,
+    #[command(flatten)]
+    report: ReportArgs,
+    /// Show progress on stderr: files done, bytes read, and the last file
+    #[arg(long)]
+    progress: bool,
+}
+
+/// The `--report` options of a normalizing subcommand.
+#[derive(Args)]
+struct ReportArgs {
+    /// Write statistics for each file and in total: changes per rule,
+    /// changed words, and low-confidence changes
+    #[arg(long, value_enum)]
+    report: Option<ReportFormat>,
+    /// File to write the report to (default: stderr)
+    #[arg(long, requires = "report")]
+    report_file: Option<PathBuf>,
+    /// Confidence below which a long-s change is reported as low-confidence
+    #[arg(long, default_value_t = 0.9)]
+    review_below: f64,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    /// One JSON object
+    Json,
+    /// Tab-separated rows of file, kind, item, replacement, and value
+    Tsv,
+}
+
+impl Files {
+    /// Where the normalized text goes.
+    fn output(&self) -> Output {
+        if self.check {
+            Output::Check(self.max_changes)
+        } else if self.in_place {
+            Output::InPlace(self.backup_suffix.clone())
+        } else {
+            Output::Text(self.output.clone())
+        }
+    }
+}
+
+/// A file to read, and its path under an output directory.
+struct Input {
+    path: PathBuf,
+    relative: PathBuf,
+}
+
+impl Inputs {
+    /// The files to read, in order: the input files, or stdin (`-`), or the
+    /// selected files under the input directory, sorted by path.
+    fn resolve(&self) -> Result<Vec<Input>, String> {
+        let Some(dir) = &self.input_dir else {
+            let paths = if self.inputs.is_empty() { vec![PathBuf::from("-")] } else { self.inputs.clone() };
+            let inputs = paths.into_iter().map(|path| {
+                let relative = PathBuf::from(path.file_name().unwrap_or(path.as_os_str()));
+                Input { path, relative }
+            });
+            return Ok(inputs.collect());
+        };
+        let patterns = |globs: &[String]| {
+            globs
+                .iter()
+                .map(|glob| glob::Pattern::new(glob).map_err(|e| format!("invalid pattern {}: {}", glob, e)))
+                .collect::<Result<Vec<_>, String>>()
+        };
+        let (include, exclude) = (patterns(&self.include)?, patterns(&self.exclude)?);
+        let mut files = Vec::new();
+        walk(dir, &mut files).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        let mut inputs = Vec::new();
+        for path in files {
+            let relative = path.strip_prefix(dir).unwrap_or(&path).to_path_buf();
+            if (include.is_empty() || matches_any(&include, &relative)) && !matches_any(&exclude, &relative) {
+                inputs.push(Input { path, relative });
+            }
+        }
+        Ok(inputs)
+    }
+}
+
+/// Collect the files under `dir`, recursively, sorted by path.
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let mut entries =
+        std::fs::read_dir(dir)?.map(|entry| entry.map(|entry| entry.path())).collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            walk(&path, files)?;
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Whether any of `patterns` matches `relative`: its `/`-separated path for
+/// patterns with a `/`, its file name for the others.
+fn matches_any(patterns: &[glob::Pattern], relative: &Path) -> bool {
+    let options = glob::MatchOptions { require_literal_separator: true, ..glob::MatchOptions::new() };
+    let path = relative.components().map(|part| part.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+    let name = relative.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    patterns.iter().any(|pattern| {
+        if pattern.as_str().contains('/') {
+            pattern.matches_with(&path, options)
+        } else {
+            pattern.matches_with(&name, options)
+        }
+    })
+}
+
+/// The encoding a file was read in, and its output is written in.
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Encoding {
+    #[value(name = "utf-8")]
+    Utf8,
+    /// UTF-8 with a byte order mark
+    #[value(name = "utf-8-bom")]
+    Utf8Bom,
+    #[value(name = "utf-16le")]
+    Utf16Le,
+    #[value(name = "utf-16be")]
+    Utf16Be,
+    /// ISO 8859-1
+    #[value(name = "latin-1")]
+    Latin1,
+    #[value(name = "windows-1252")]
+    Windows1252,
+}
+
+/// The characters Windows-1252 has at 0x80-0x9F, where Latin-1 has C1
+/// controls. The five unassigned bytes keep their control characters, so
+/// every byte reads and writes back unchanged.
+const WINDOWS_1252: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+impl Encoding {
+    /// The text of `bytes`, and the encoding it was in. Without an
+    /// `encoding`, a byte order mark names it; failing that, text that is
+    /// not valid UTF-8 is read as Windows-1252 if it has bytes in
+    /// 0x80-0x9F, which are control characters in Latin-1, and as Latin-1
+    /// otherwise.
+    fn decode(bytes: Vec<u8>, encoding: Option<Encoding>) -> Result<(String, Encoding), String> {
+        let utf16 = |bytes: &[u8], from: fn([u8; 2]) -> u16| {
+            if bytes.len() % 2 == 1 {
+                return Err("truncated UTF-16".to_string());
+            }
+            let units = bytes.chunks_exact(2).map(|pair| from([pair[0], pair[1]]));
+            char::decode_utf16(units).collect::<Result<String, _>>().map_err(|e| format!("invalid UTF-16: {}", e))
+        };
+        let utf8 = |bytes: &[u8]| String::from_utf8(bytes.to_vec()).map_err(|e| format!("invalid UTF-8: {}", e));
+        let single_byte = |bytes: &[u8], encoding| {
+            let decode = |b: u8| match (encoding, b) {
+                (Encoding::Windows1252, 0x80..=0x9F) => WINDOWS_1252[usize::from(b - 0x80)],
+                _ => char::from(b),
+            };
+            (bytes.iter().map(|&b| decode(b)).collect(), encoding)
+        };
+        match (encoding, bytes.as_slice()) {
+            (None | Some(Encoding::Utf8 | Encoding::Utf8Bom), [0xEF, 0xBB, 0xBF, rest @ ..]) => {
+                Ok((utf8(rest)?, Encoding::Utf8Bom))
+            }
+            (None | Some(Encoding::Utf16Le), [0xFF, 0xFE, rest @ ..]) => {
+                Ok((utf16(rest, u16::from_le_bytes)?, Encoding::Utf16Le))
+            }
+            (None | Some(Encoding::Utf16Be), [0xFE, 0xFF, rest @ ..]) => {
+                Ok((utf16(rest, u16::from_be_bytes)?, Encoding::Utf16Be))
+            }
+            (Some(Encoding::Utf8 | Encoding::Utf8Bom), bytes) => Ok((utf8(bytes)?, Encoding::Utf8)),
+            (Some(Encoding::Utf16Le), bytes) => Ok((utf16(bytes, u16::from_le_bytes)?, Encoding::Utf16Le)),
+            (Some(Encoding::Utf16Be), bytes) => Ok((utf16(bytes, u16::from_be_bytes)?, Encoding::Utf16Be)),
+            (Some(encoding), bytes) => Ok(single_byte(bytes, encoding)),
+            (None, bytes) => match std::str::from_utf8(bytes) {
+                Ok(text) => Ok((text.to_string(), Encoding::Utf8)),
+                Err(_) if bytes.iter().any(|b| (0x80..=0x9F).contains(b)) => {
+                    Ok(single_byte(bytes, Encoding::Windows1252))
+                }
+                Err(_) => Ok(single_byte(bytes, Encoding::Latin1)),
+            },
+        }
+    }
+
+    /// `text` in this encoding; UTF-16 is written with a byte order mark.
+    fn encode(self, text: &str) -> Result<Vec<u8>, String> {
+        let utf16 =
+            |bom: [u8; 2], to: fn(u16) -> [u8; 2]| bom.into_iter().chain(text.encode_utf16().flat_map(to)).collect();
+        let single_byte = |encode: &dyn Fn(char) -> Option<u8>, name: &str| {
+            text.chars()
+                .map(|c| encode(c).ok_or_else(|| format!("{:?} cannot be written as {}", c, name)))
+                .collect::<Result<_, _>>()
+        };
+        Ok(match self {
+            Encoding::Utf8 => text.as_bytes().to_vec(),
+            Encoding::Utf8Bom => [&[0xEF, 0xBB, 0xBF], text.as_bytes()].concat(),
+            Encoding::Utf16Le => utf16([0xFF, 0xFE], u16::to_le_bytes),
+            Encoding::Utf16Be => utf16([0xFE, 0xFF], u16::to_be_bytes),
+            Encoding::Latin1 => single_byte(&|c| u8::try_from(c).ok(), "Latin-1")?,
+            Encoding::Windows1252 => single_byte(
+                &|c| match WINDOWS_1252.iter().position(|&w| w == c) {
+                    Some(i) => Some(0x80 + i as u8),
+                    None => u8::try_from(c).ok().filter(|b| !(0x80..=0x9F).contains(b)),
+                },
+                "Windows-1252",
+            )?,
+        })
+    }
+}
+
+/// The stages of a pipeline subcommand.
+#[derive(Args)]
+struct Stages {
+    /// Pipeline config file (TOML or JSON)
+    #[arg(long, conflicts_with = "stages")]
+    config: Option<PathBuf>,
+    /// Comma-separated stage names, run in order
+    #[arg(long, value_delimiter = ',', required_unless_present = "config")]
+    stages: Vec<String>,
+    /// Comma-separated kinds of span to copy verbatim: urls, entities,
+    /// greek, citations
+    #[arg(long, value_delimiter = ',', value_parser = parse_detector)]
+    protect: Vec<Detector>,
+}
+
+impl Stages {
+    fn pipeline(&self) -> Result<Pipeline, String> {
+        let pipeline = match &self.config {
+            Some(config) => Pipeline::from_config(config).map_err(|e| format!("{}: {}", config.display(), e))?,
+            None => pipeline_of(&self.stages)?,
+        };
+        Ok(self.protect.iter().fold(pipeline, |pipeline, &detector| pipeline.protect(detector)))
+    }
+}
+
+fn parse_detector(name: &str) -> Result<Detector, String> {
+    Detector::from_name(name).ok_or_else(|| {
+        let names: Vec<&str> = Detector::ALL.iter().map(|d| d.name()).collect();
+        format!("expected one of {}", names.join(", "))
+    })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum GoldFormat {
+    /// A raw text and its corrected form
+    Text,
+    /// A raw text and its corrected form, line by line
+    Aligned,
+    /// Word pairs, `raw<TAB>gold` on each line
+    Tsv,
+    /// TEI, with the readings of each <choice>: <orig> and <reg>, <sic>
+    /// and <corr>, or <abbr> and <expan>
+    Tei,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ModelFormat {
+    /// bigrams.json, trigrams.json, and 4grams.json
+    Json,
+    /// ngrams.bin
+    Binary,
+    /// Both JSON and binary
+    All,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    if let Some(jobs) = cli.jobs {
+        if let Err(e) = rayon::ThreadPoolBuilder::new().num_threads(jobs).build_global() {
+            eprintln!("error: {}", e);
+            return ExitCode::from(2);
+        }
+    }
+    let result = match cli.command {
+        Command::TrainNgrams { input, output, pattern, format } => {
+            train_ngrams(&input, &output, &pattern, format).map(|()| ExitCode::SUCCESS)
+        }
+        Command::TrainUv { input, output, pattern, min_count, min_confidence } => {
+            train_uv(&input, &output, &pattern, min_count, min_confidence).map(|()| ExitCode::SUCCESS)
+        }
+        Command::WordLists { lexicon, allowlist, denylist, min_frequency } => {
+            word_lists(&lexicon, &allowlist, denylist.as_deref(), min_frequency).map(|()| ExitCode::SUCCESS)
+        }
+        Command::Uv { files, model } => uv(model.as_deref())
+            .and_then(|normalizer| normalize(&normalizer, None, &files, &files.output())),
+        Command::LongS { files, threshold, no_pass2, ngrams, lexicon } => {
+            long_s(threshold, no_pass2, ngrams.as_deref(), lexicon.as_deref()).and_then(|normalizer| {
+                let below = files.report.review_below;
+                let review = |text: &str| normalizer.normalize_detailed(text).review(below);
+                normalize(&normalizer, Some(&review), &files, &files.output())
+            })
+        }
+        Command::Normalize {
+            files,
+            stages,
+            dry_run,
+            word_diff,
+            context,
+        } => {
+            let mode = match (dry_run, word_diff) {
+                (true, true) => Output::WordDiff,
+                (true, false) => Output::Diff(context),
+                _ => files.output(),
+            };
+            stages.pipeline().and_then(|pipeline| normalize(&pipeline, None, &files, &mode))
+        }
+        Command::Stats { inputs, stages } => {
+            stages.pipeline().and_then(|pipeline| stats(&pipeline, &inputs)).map(|()| ExitCode::SUCCESS)
+        }
+        Command::Profile { inputs, json, recommend } => {
+            profile(&inputs, json, recommend.as_deref()).map(|()| ExitCode::SUCCESS)
+        }
+        Command::TestCorpus { dir, stages, bless, json } => {
+            stages.pipeline().and_then(|pipeline| test_corpus(&pipeline, &dir, bless, json))
+        }
+        Command::Eval { raw, gold, format, stages, json, max_errors, mine } => stages
+            .pipeline()
+            .and_then(|pipeline| evaluate(&pipeline, &raw, gold.as_deref(), format, json, max_errors, mine))
+            .map(|()| ExitCode::SUCCESS),
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn train_ngrams(input: &Path, output: &Path, pattern: &str, format: ModelFormat) -> Result<(), String> {
+    let files = corpus_files(input, pattern)?;
+    if files.is_empty() {
+        return Err(format!("no files matching {} in {}", pattern, input.display()));
+    }
+
+    let data = files
+        .par_iter()
+        .map(|path| -> Result<NgramData, String> {
+            let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            Ok(latincy_preprocess::long_s::train_ngrams(text.lines()))
+        })
+        .try_reduce(NgramData::default, |mut a, b| {
+            a.merge(b);
+            Ok(a)
+        })?;
+    let metadata = ModelMetadata {
+        source: Some(input.display().to_string()),
+        tokens: Some(data.token_count()),
+        built: Some(today()),
+        ..ModelMetadata::default()
+    };
+    let data = data.with_metadata(metadata);
+
+    std::fs::create_dir_all(output).map_err(|e| format!("{}: {}", output.display(), e))?;
+    if matches!(format, ModelFormat::Json | ModelFormat::All) {
+        data.write_json_dir(output).map_err(|e| format!("{}: {}", output.display(), e))?;
+    }
+    if matches!(format, ModelFormat::Binary | ModelFormat::All) {
+        let path = output.join(BINARY_FILE_NAME);
+        data.write_binary(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if matches!(format, ModelFormat::Binary) {
+            data.metadata().write(output).map_err(|e| format!("{}: {}", output.display(), e))?;
+        }
+    }
+    eprintln!("Trained on {} files; wrote model to {}", files.len(), output.display());
+    Ok(())
+}
+
+fn train_uv(input: &Path, output: &Path, pattern: &str, min_count: u64, min_confidence: f64) -> Result<(), String> {
+    let files = corpus_files(input, pattern)?;
+    if files.is_empty() {
+        return Err(format!("no files matching {} in {}", pattern, input.display()));
+    }
+    let model = files
+        .par_iter()
+        .map(|path| -> Result<UvModel, String> {
+            let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            Ok(UvModel::train([text]))
+        })
+        .try_reduce(UvModel::default, |mut a, b| {
+            a.merge(b);
+            Ok(a)
+        })?;
+    let model = model.min_count(min_count).min_confidence(min_confidence);
+    model.save(output).map_err(|e| e.to_string())?;
+    eprintln!(
+        "Trained on {} files ({} u and v, {} features); wrote model to {}",
+        files.len(),
+        model.occurrences(),
+        model.feature_count(),
+        output.display()
+    );
+    Ok(())
+}
+
+fn word_lists(lexicon: &Path, allowlist: &Path, denylist: Option<&Path>, min_frequency: u64) -> Result<(), String> {
+    let lexicon = Lexicon::from_file(lexicon).map_err(|e| e.to_string())?;
+    let words = lexicon.f_allowlist(min_frequency);
+    write_word_list(allowlist, &words)?;
+    eprintln!("Wrote {} allowlist words to {}", words.len(), allowlist.display());
+    if let Some(denylist) = denylist {
+        let words = lexicon.f_denylist(min_frequency);
+        write_word_list(denylist, &words)?;
+        eprintln!("Wrote {} denylist words to {}", words.len(), denylist.display());
+    }
+    Ok(())
+}
+
+/// The pipeline of the named stages.
+fn pipeline_of(stages: &[String]) -> Result<Pipeline, String> {
+    let mut pipeline = Pipeline::new();
+    for name in stages {
+        let stage = latincy_preprocess::registry::get(name).ok_or_else(|| {
+            format!("unknown stage {:?}; expected one of {}", name, latincy_preprocess::registry::list().join(", "))
+        })?;
+        pipeline.push(stage);
+    }
+    Ok(pipeline)
+}
+
+/// What `normalize` writes.
+enum Output {
+    /// The normalized text, to files in a directory or to stdout.
+    Text(Option<PathBuf>),
+    /// The normalized text, over the input files, backing each up under
+    /// its name plus the suffix if there is one.
+    InPlace(Option<String>),
+    /// A unified diff with this many lines of context, to stdout.
+    Diff(usize),
+    /// A word-level diff, to stdout.
+    WordDiff,
+    /// The location of each change, to stdout, failing if there are more
+    /// changes than this.
+    Check(usize),
+}
+
+/// The low-confidence changes in a text, for normalizers that score their
+/// changes.
+type Review<'a> = &'a (dyn Fn(&str) -> Vec<ReviewItem> + Sync);
+
+fn normalize(
+    normalizer: &dyn Normalizer,
+    review: Option<Review>,
+    files: &Files,
+    output: &Output,
+) -> Result<ExitCode, String> {
+    let inputs = files.inputs.resolve()?;
+    let report = &files.report;
+    // Stdin declared UTF-8 is streamed to stdout, in memory bounded by its
+    // longest paragraph. Anything else is read whole and its encoding
+    // detected, as for files: a non-UTF-8 byte may come anywhere.
+    let utf8_out = files.output_encoding.is_none_or(|e| e == Encoding::Utf8);
+    if let ([input], Output::Text(None), None, true) = (&inputs[..], output, report.report, utf8_out) {
+        if is_stdin(&input.path) && files.inputs.encoding == Some(Encoding::Utf8) {
+            let stdin = std::io::stdin().lock();
+            let writer = std::io::BufWriter::new(std::io::stdout().lock());
+            let mut progress = |p: &latincy_preprocess::pipeline::Progress| {
+                if files.progress {
+                    eprint!("\r\x1b[K{:.1} MB, {} words", megabytes(p.bytes), p.words);
+                }
+            };
+            latincy_preprocess::pipeline::normalize_stream_monitored(normalizer, stdin, writer, &mut progress, None)
+                .map_err(|e| format!("stdin: {}", e))?;
+            if files.progress {
+                eprintln!();
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+    }
+    if inputs.iter().any(|input| is_stdin(&input.path)) {
+        match output {
+            Output::Text(Some(_)) => return Err("stdin cannot be written to an output directory".to_string()),
+            Output::InPlace(_) => return Err("stdin cannot be rewritten in place".to_string()),
+            _ => {}
+        }
+    }
+    if let Output::Text(Some(dir)) = output {
+        // Explicit inputs are written under their file names, which need
+        // not be unique
+        let mut targets: HashMap<&Path, &Path> = HashMap::new();
+        for input in &inputs {
+            if let Some(other) = targets.insert(&input.relative, &input.path) {
+                return Err(format!(
+                    "{} and {} would both be written to {}",
+                    other.display(),
+                    input.path.display(),
+                    dir.join(&input.relative).display()
+                ));
+            }
+        }
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    let start = std::time::Instant::now();
+    let (files_done, bytes_done) = (AtomicUsize::new(0), AtomicUsize::new(0));
+    let results = inputs
+        .par_iter()
+        .map(|input| -> Result<(Vec<u8>, usize, Option<FileReport>), String> {
+            let path = &input.path;
+            let (text, input_encoding) = read(path, files.inputs.encoding)?;
+            if files.progress {
+                let done = files_done.fetch_add(1, Ordering::Relaxed) + 1;
+                let bytes = bytes_done.fetch_add(text.len(), Ordering::Relaxed) + text.len();
+                eprint!("\r\x1b[K{}/{} files, {:.1} MB: {}", done, inputs.len(), megabytes(bytes), name(path));
+            }
+            let encoding = files.output_encoding.unwrap_or(input_encoding);
+            let result = normalizer.normalize(&text);
+            let file_report = report.report.map(|_| {
+                let low_confidence = review.map(|review| review(&text)).unwrap_or_default();
+                FileReport::new(path.display().to_string(), &result, low_confidence)
+            });
+            let locations = matches!(output, Output::Check(_)).then(|| locations(path, &result));
+            let (normalized, changes) = (result.normalized, result.changes.len());
+            let bytes = match output {
+                Output::Text(None) => encoding.encode(&normalized).map_err(|e| format!("{}: {}", name(path), e))?,
+                Output::Text(Some(dir)) => {
+                    let target = dir.join(&input.relative);
+                    let bytes = encoding.encode(&normalized).map_err(|e| format!("{}: {}", path.display(), e))?;
+                    if let Some(parent) = target.parent() {
+                        std::fs::create_dir_all(parent).map_err(|e| format!("{}: {}", parent.display(), e))?;
+                    }
+                    std::fs::write(&target, bytes).map_err(|e| format!("{}: {}", target.display(), e))?;
+                    Vec::new()
+                }
+                // A file is rewritten if only its encoding changes
+                Output::InPlace(_) if normalized == text && encoding == input_encoding => Vec::new(),
+                Output::InPlace(backup_suffix) => {
+                    let bytes = encoding.encode(&normalized).map_err(|e| format!("{}: {}", path.display(), e))?;
+                    if let Some(suffix) = backup_suffix {
+                        let mut backup = path.clone().into_os_string();
+                        backup.push(suffix);
+                        std::fs::copy(path, &backup).map_err(|e| format!("{}: {}", Path::new(&backup).display(), e))?;
+                    }
+                    write_atomic(path, &bytes).map_err(|e| format!("{}: {}", path.display(), e))?;
+                    Vec::new()
+                }
+                Output::Diff(context) => {
+                    let name = path.display().to_string();
+                    latincy_preprocess::diff::unified(&text, &normalized, &name, &name, *context).into_bytes()
+                }
+                Output::Check(_) => locations.unwrap_or_default().into_bytes(),
+                Output::WordDiff => {
+                    let diff = latincy_preprocess::diff::words(&text, &normalized);
+                    if diff.is_empty() {
+                        Vec::new()
+                    } else {
+                        format!("{}\n{}", path.display(), diff).into_bytes()
+                    }
+                }
+            };
+            // A stage that reports no change records still counts as one change
+            Ok((bytes, if normalized == text { 0 } else { changes.max(1) }, file_report))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if files.progress {
+        eprint!("\r\x1b[K");
+    }
+    // Output in input order, however the files were scheduled
+    let mut stdout = std::io::stdout().lock();
+    for (bytes, _, _) in &results {
+        stdout.write_all(bytes).map_err(|e| e.to_string())?;
+    }
+    let changed = results.iter().filter(|(_, changes, _)| *changes > 0).count();
+    let changes: usize = results.iter().map(|(_, changes, _)| changes).sum();
+    let summary = |verb: &str| {
+        let elapsed = start.elapsed().as_secs_f64();
+        format!("{} of {} files {} ({} changes) in {:.2}s", changed, inputs.len(), verb, changes, elapsed)
+    };
+    match output {
+        Output::Text(None) => eprintln!("{}", summary("changed")),
+        Output::Text(Some(dir)) => {
+            eprintln!("{}; wrote {} files to {}", summary("changed"), inputs.len(), dir.display())
+        }
+        Output::InPlace(_) => eprintln!("{}; rewrote them in place", summary("changed")),
+        Output::Diff(_) | Output::WordDiff | Output::Check(_) => eprintln!("{}", summary("would change")),
+    }
+    if let Some(format) = report.report {
+        let reports: Vec<FileReport> = results.into_iter().filter_map(|(_, _, report)| report).collect();
+        let content = match format {
+            ReportFormat::Json => {
+                serde_json::to_string_pretty(&report_json(&reports)).map_err(|e| e.to_string())? + "\n"
+            }
+            ReportFormat::Tsv => report_tsv(&reports),
+        };
+        match &report.report_file {
+            Some(path) => std::fs::write(path, content).map_err(|e| format!("{}: {}", path.display(), e))?,
+            None => eprint!("{}", content),
+        }
+    }
+    match output {
+        Output::Check(max_changes) if changes > *max_changes => Ok(ExitCode::FAILURE),
+        _ => Ok(ExitCode::SUCCESS),
+    }
+}
+
+/// One line per edit of `output`, `path:line:column: original ->
+/// replacement (rules)`, the way compilers report errors.
+fn locations(path: &Path, output: &NormalizeOutput) -> String {
+    let chars: Vec<char> = output.original.chars().collect();
+    let mut lines = String::new();
+    let (mut line, mut line_start, mut scanned) = (1, 0, 0);
+    for edit in latincy_preprocess::patch::Patch::from_output(output).edits {
+        for (i, &c) in chars.iter().enumerate().take(edit.start).skip(scanned) {
+            if c == '\n' {
+                line += 1;
+                line_start = i + 1;
+            }
+        }
+        scanned = edit.start;
+        lines.push_str(&format!(
+            "{}:{}:{}: {:?} -> {:?} ({})\n",
+            path.display(),
+            line,
+            edit.start - line_start + 1,
+            edit.original,
+            edit.replacement,
+            edit.rules.join(", ")
+        ));
+    }
+    lines
+}
+
+/// The `--report` statistics of one file, or of all of them.
+#[derive(Default)]
+struct FileReport {
+    path: String,
+    characters: usize,
+    changes: usize,
+    rules: BTreeMap<String, usize>,
+    /// Changed words, `(original, normalized)`.
+    words: BTreeMap<(String, String), usize>,
+    low_confidence: Vec<ReviewItem>,
+}
+
+impl FileReport {
+    fn new(path: String, output: &NormalizeOutput, low_confidence: Vec<ReviewItem>) -> Self {
+        let mut report = FileReport {
+            path,
+            characters: output.original.chars().count(),
+            changes: output.changes.len(),
+            low_confidence,
+            ..FileReport::default()
+        };
+        for change in &output.changes {
+            *report.rules.entry(change.rule.clone()).or_default() += 1;
+        }
+        for word in latincy_preprocess::patch::changed_words(output) {
+            *report.words.entry(word).or_default() += 1;
+        }
+        report
+    }
+
+    /// Add the counts of `other` to these.
+    fn add(&mut self, other: &FileReport) {
+        self.characters += other.characters;
+        self.changes += other.changes;
+        for (rule, count) in &other.rules {
+            *self.rules.entry(rule.clone()).or_default() += count;
+        }
+        for (word, count) in &other.words {
+            *self.words.entry(word.clone()).or_default() += count;
+        }
+    }
+
+    /// The changed words, most frequent first.
+    fn words(&self) -> Vec<(&(String, String), usize)> {
+        let mut words: Vec<_> = self.words.iter().map(|(word, &count)| (word, count)).collect();
+        words.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        words
+    }
+
+    fn to_json(&self) -> Value {
+        let words = self.words().into_iter().map(|((original, normalized), count)| {
+            json!({"original": original, "normalized": normalized, "count": count})
+        });
+        json!({
+            "characters": self.characters,
+            "changes": self.changes,
+            "rules": self.rules,
+            "words": words.collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// The report as one JSON object: `files`, each with its `path` and
+/// `low_confidence` changes, and the `total` over all of them.
+fn report_json(reports: &[FileReport]) -> Value {
+    let mut total = FileReport::default();
+    let mut files = Vec::new();
+    for report in reports {
+        total.add(report);
+        let mut file = report.to_json();
+        file["path"] = json!(report.path);
+        let items = report.low_confidence.iter().map(|item| serde_json::from_str(&item.to_json()));
+        file["low_confidence"] = Value::Array(items.collect::<Result<_, _>>().unwrap_or_default());
+        files.push(file);
+    }
+    let mut total_json = total.to_json();
+    total_json["files"] = json!(reports.len());
+    total_json["changed_files"] = json!(reports.iter().filter(|report| report.changes > 0).count());
+    total_json["low_confidence"] = json!(reports.iter().map(|report| report.low_confidence.len()).sum::<usize>());
+    json!({"files": files, "total": total_json})
+}
+
+/// The report as TSV with a header row; the totals have the file `*`.
+fn report_tsv(reports: &[FileReport]) -> String {
+    let mut tsv = String::from("file\tkind\titem\treplacement\tvalue\n");
+    let mut total = FileReport { path: "*".to_string(), ..FileReport::default() };
+    for report in reports {
+        total.add(report);
+        tsv_counts(&mut tsv, report);
+        for item in &report.low_confidence {
+            let confidence = format!("{:.3}", item.confidence);
+            tsv_row(&mut tsv, [&report.path, "low_confidence", &item.original, &item.normalized, &confidence]);
+        }
+    }
+    tsv_row(&mut tsv, ["*", "files", "", "", &reports.len().to_string()]);
+    tsv_counts(&mut tsv, &total);
+    tsv
+}
+
+fn tsv_counts(tsv: &mut String, report: &FileReport) {
+    tsv_row(tsv, [&report.path, "characters", "", "", &report.characters.to_string()]);
+    tsv_row(tsv, [&report.path, "changes", "", "", &report.changes.to_string()]);
+    for (rule, count) in &report.rules {
+        tsv_row(tsv, [&report.path, "rule", rule, "", &count.to_string()]);
+    }
+    for ((original, normalized), count) in report.words() {
+        tsv_row(tsv, [&report.path, "word", original, normalized, &count.to_string()]);
+    }
+}
+
+/// Append a row, with backslashes, tabs, and newlines in fields escaped.
+fn tsv_row(tsv: &mut String, fields: [&str; 5]) {
+    let escape = |field: &str| field.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n");
+    tsv.push_str(&(fields.map(escape).join("\t") + "\n"));
+}
+
+/// Replace the file at `path` with `bytes` by writing them to a temporary
+/// file beside it, syncing it to disk, and renaming it over `path`, so an
+/// interrupted run leaves either the old file or the new one.
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp = path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+    let result = (|| {
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(bytes)?;
+        file.set_permissions(std::fs::metadata(path)?.permissions())?;
+        file.sync_all()?;
+        std::fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// The u/v normalizer, with the model at `model` if one is given.
+fn uv(model: Option<&Path>) -> Result<UvNormalizer, String> {
+    let normalizer = UvNormalizer::new();
+    match model {
+        Some(path) => Ok(normalizer.model(Arc::new(UvModel::load(path).map_err(|e| e.to_string())?))),
+        None => Ok(normalizer),
+    }
+}
+
+/// The long-s normalizer the `long-s` options describe.
+fn long_s(
+    threshold: Option<f64>,
+    no_pass2: bool,
+    ngrams: Option<&Path>,
+    lexicon: Option<&Path>,
+) -> Result<LongSNormalizer, String> {
+    let mut builder = LongSNormalizer::builder().apply_pass2(!no_pass2);
+    if let Some(threshold) = threshold {
+        builder = builder.threshold(threshold);
+    }
+    if let Some(dir) = ngrams {
+        builder = builder.ngram_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    if let Some(path) = lexicon {
+        builder = builder.lexicon_file(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    Ok(builder.build())
+}
+
+/// Print the number of changes `pipeline` would make to the inputs, in
+/// total and for each stage and rule, most frequent first.
+fn stats(pipeline: &Pipeline, inputs: &Inputs) -> Result<(), String> {
+    let encoding = inputs.encoding;
+    let inputs = inputs.resolve()?;
+    let (characters, summary) = inputs
+        .par_iter()
+        .map(|input| -> Result<(usize, ChangeSummary), String> {
+            let (text, _) = read(&input.path, encoding)?;
+            let summary = pipeline.normalize_with_detail(&text, OutputDetail::Summary).summary().unwrap_or_default();
+            Ok((text.chars().count(), summary))
+        })
+        .try_reduce(
+            || (0, ChangeSummary::new()),
+            |(a, mut total), (b, summary)| {
+                total.merge(&summary);
+                Ok((a + b, total))
+            },
+        )?;
+    let mut counts: Vec<(&String, &usize)> = summary.rules.iter().collect();
+    counts.sort_by_key(|&(_, count)| std::cmp::Reverse(*count));
+    println!("{} files, {} characters, {} changes", inputs.len(), characters, summary.changes);
+    for (rule, count) in counts {
+        println!("{:>8}  {}", count, rule);
+    }
+    Ok(())
+}
+
+/// Print the profile of the inputs together, and write the pipeline it
+/// suggests to `recommend`.
+fn profile(inputs: &Inputs, json: bool, recommend: Option<&Path>) -> Result<(), String> {
+    let encoding = inputs.encoding;
+    let profile = inputs
+        .resolve()?
+        .par_iter()
+        .map(|input| read(&input.path, encoding).map(|(text, _)| profile::profile(&text)))
+        .try_reduce(profile::CorpusProfile::new, |mut a, b| {
+            a.merge(&b);
+            Ok(a)
+        })?;
+    if json {
+        println!("{}", profile.to_json());
+    } else {
+        println!("{}", profile);
+    }
+    if let Some(path) = recommend {
+        let config = profile::recommend_pipeline(&profile);
+        config.save(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        for stage in &config.stages {
+            eprintln!("{}: {}", stage.name, stage.reason.as_deref().unwrap_or(""));
+        }
+    }
+    Ok(())
+}
+
+fn test_corpus(pipeline: &Pipeline, dir: &Path, bless: bool, json: bool) -> Result<ExitCode, String> {
+    let error = |e: std::io::Error| e.to_string();
+    if bless {
+        let blessed = regression::bless(pipeline, dir).map_err(error)?;
+        for name in &blessed {
+            eprintln!("wrote {}{}", name, regression::EXPECTED_SUFFIX);
+        }
+        eprintln!("{} expected files written", blessed.len());
+        return Ok(ExitCode::SUCCESS);
+    }
+    let report = regression::run(pipeline, &regression::Corpus::load(dir).map_err(error)?);
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        println!("{}", report);
+    }
+    Ok(if report.passed() { ExitCode::SUCCESS } else { ExitCode::from(1) })
+}
+
+fn evaluate(
+    pipeline: &Pipeline,
+    raw: &Path,
+    gold: Option<&Path>,
+    format: GoldFormat,
+    json: bool,
+    max_errors: usize,
+    mine: bool,
+) -> Result<(), String> {
+    let mut files = Vec::new();
+    if raw.is_dir() {
+        for entry in std::fs::read_dir(raw).map_err(|e| format!("{}: {}", raw.display(), e))? {
+            let path = entry.map_err(|e| format!("{}: {}", raw.display(), e))?.path();
+            if path.is_file() {
+                let gold = gold.map(|gold| gold.join(path.file_name().expect("a file has a name")));
+                files.push((path, gold));
+            }
+        }
+        files.sort();
+    } else {
+        files.push((raw.to_path_buf(), gold.map(Path::to_path_buf)));
+    }
+    // Each pair, with the file (and line) it came from
+    let mut pairs = Vec::new();
+    let mut labels = Vec::new();
+    for (path, gold) in &files {
+        let text = read(path, None)?.0;
+        let gold = || match gold {
+            Some(gold) => read(gold, None).map(|(gold, _)| gold),
+            None => Err("a GOLD text is needed with this --format".to_string()),
+        };
+        let located = |e: std::io::Error| format!("{}: {}", path.display(), e);
+        let loaded = match format {
+            GoldFormat::Text => vec![eval::Pair::new(text, gold()?)],
+            GoldFormat::Aligned => eval::load_aligned(&text, &gold()?).map_err(located)?,
+            GoldFormat::Tsv => eval::load_tsv(&text).map_err(located)?,
+            GoldFormat::Tei => vec![eval::load_tei(&text).map_err(located)?],
+        };
+        for pair in &loaded {
+            labels.push(match pair.name.as_str() {
+                "" => path.display().to_string(),
+                line => format!("{}:{}", path.display(), line),
+            });
+        }
+        pairs.extend(loaded);
+    }
+    let mut evaluation = eval::evaluate_pairs(pipeline, &pairs);
+    if json {
+        let mut value: Value = serde_json::from_str(&evaluation.to_json()).expect("the evaluation is JSON");
+        for (error, json) in evaluation.errors.iter().zip(value["errors"].as_array_mut().expect("errors are a list")) {
+            json["source"] = labels[error.pair].clone().into();
+        }
+        if mine {
+            let patterns = eval::mine_errors(&evaluation, &pairs).iter().map(eval::ErrorPattern::to_json).collect();
+            value["patterns"] = Value::Array(patterns);
+        }
+        println!("{}", value);
+        return Ok(());
+    }
+    if mine {
+        let patterns = eval::mine_errors(&evaluation, &pairs);
+        evaluation.errors.clear();
+        println!("{}", evaluation);
+        if !patterns.is_empty() {
+            println!();
+        }
+        for pattern in patterns.iter().take(max_errors) {
+            println!("{}", pattern);
+        }
+        if patterns.len() > max_errors {
+            println!("... and {} more patterns", patterns.len() - max_errors);
+        }
+        return Ok(());
+    }
+    let errors = std::mem::take(&mut evaluation.errors);
+    println!("{}", evaluation);
+    if !errors.is_empty() {
+        println!();
+    }
+    for error in errors.iter().take(max_errors) {
+        println!("{}:{}", labels[error.pair], error);
+    }
+    if errors.len() > max_errors {
+        println!("... and {} more errors", errors.len() - max_errors);
+    }
+    Ok(())
+}
+
+fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+fn megabytes(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+/// `path` for messages.
+fn name(path: &Path) -> String {
+    if is_stdin(path) {
+        "stdin".to_string()
+    } else {
+        path.display().to_string()
+    }
+}
+
+/// The text of the file at `path`, or of stdin for `-`, and its encoding
+/// (see [`Encoding::decode`]).
+fn read(path: &Path, encoding: Option<Encoding>) -> Result<(String, Encoding), String> {
+    let bytes = if is_stdin(path) {
+        let mut bytes = Vec::new();
+        std::io::stdin().read_to_end(&mut bytes).map(|_| bytes)
+    } else {
+        std::fs::read(path)
+    };
+    let text = bytes.map_err(|e| e.to_string()).and_then(|bytes| Encoding::decode(bytes, encoding));
+    text.map_err(|e| format!("{}: {}", name(path), e))
+}
+
+/// Write words as a JSON array if `path` ends in `.json`, otherwise one per
+/// line, the formats the normalizer's word-list loaders read.
+fn write_word_list(path: &Path, words: &[String]) -> Result<(), String> {
+    let content = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::to_string_pretty(words).map_err(|e| e.to_string())? + "\n"
+    } else {
+        words.iter().map(|word| format!("{}\n", word)).collect()
+    };
+    std::fs::write(path, content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn corpus_files(input: &Path, pattern: &str) -> Result<Vec<PathBuf>, String> {
+    let full = input.join(pattern);
+    let paths = glob::glob(&full.to_string_lossy()).map_err(|e| format!("invalid pattern {}: {}", pattern, e))?;
+    let mut files = Vec::new();
+    for entry in paths {
+        let path = entry.map_err(|e| e.to_string())?;
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Today's date (UTC) as `YYYY-MM-DD`.
+fn today() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    // Days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,224 @@
+//! A bounded cache of per-word normalization results.
+//!
+//! Word types recur constantly in a text (*fuit*, *funt*, *seruus*), so a
+//! normalizer with a [`WordCache`] runs its rules once per type instead of
+//! once per token. One cache can be shared between normalizers and kept for
+//! a whole corpus: entries are keyed by the word and by the configuration
+//! of the normalizer that produced them, so differently configured
+//! normalizers never see each other's results.
+//!
+//! ```
+//! use latincy_preprocess::cache::WordCache;
+//! use latincy_preprocess::long_s::LongSNormalizer;
+//! use std::sync::Arc;
+//!
+//! let cache = Arc::new(WordCache::new(10_000));
+//! let normalizer = LongSNormalizer::builder().word_cache(Arc::clone(&cache)).build();
+//! # #[cfg(feature = "embed-data")] {
+//! assert_eq!(normalizer.normalize_text("funt funt"), "sunt sunt");
+//! assert_eq!((cache.hits(), cache.misses()), (1, 1));
+//! # }
+//! ```
+
+use crate::telemetry;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// Normalized forms of words, keyed by normalizer configuration and word.
+///
+/// The cache holds at most `capacity` entries in two halves: new entries go
+/// into the current half, and once it is full it replaces the previous
+/// half, dropping whatever that held. A hit in the previous half moves the
+/// entry back into the current one, so words still in use survive the
+/// turnover, an approximation of least-recently-used eviction without
+/// per-entry bookkeeping.
+pub struct WordCache {
+    capacity: usize,
+    halves: Mutex<Halves>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Default)]
+struct Halves {
+    current: Half,
+    previous: Half,
+}
+
+/// Words by configuration key, with the total number of words.
+#[derive(Default)]
+struct Half {
+    entries: HashMap<u64, HashMap<String, String>>,
+    len: usize,
+}
+
+impl Half {
+    fn get(&self, config: u64, word: &str) -> Option<&String> {
+        self.entries.get(&config)?.get(word)
+    }
+
+    fn remove(&mut self, config: u64, word: &str) -> Option<String> {
+        let removed = self.entries.get_mut(&config)?.remove(word)?;
+        self.len -= 1;
+        Some(removed)
+    }
+
+    fn insert(&mut self, config: u64, word: String, normalized: String) {
+        if self.entries.entry(config).or_default().insert(word, normalized).is_none() {
+            self.len += 1;
+        }
+    }
+}
+
+impl WordCache {
+    /// An empty cache holding up to `capacity` words. A capacity below 2
+    /// caches nothing.
+    pub fn new(capacity: usize) -> Self {
+        WordCache {
+            capacity,
+            halves: Mutex::new(Halves::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of words cached, over every configuration.
+    pub fn len(&self) -> usize {
+        let halves = self.lock();
+        halves.current.len + halves.previous.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many lookups found their word.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// How many lookups had to normalize their word.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Drop every entry and reset the counts, e.g. between documents.
+    pub fn clear(&self) {
+        *self.lock() = Halves::default();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    /// The cached normalization of `word` under `config`, or `normalize()`
+    /// stored for next time. The rules run outside the lock, so normalizers
+    /// sharing the cache never wait on each other's words.
+    pub(crate) fn get_or_insert_with(&self, config: u64, word: &str, normalize: impl FnOnce() -> String) -> String {
+        {
+            let mut halves = self.lock();
+            if let Some(normalized) = halves.current.get(config, word) {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                telemetry::cache_lookup(true);
+                return normalized.clone();
+            }
+            if let Some(normalized) = halves.previous.remove(config, word) {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                telemetry::cache_lookup(true);
+                self.insert(&mut halves, config, word, normalized.clone());
+                return normalized;
+            }
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        telemetry::cache_lookup(false);
+        let normalized = normalize();
+        self.insert(&mut self.lock(), config, word, normalized.clone());
+        normalized
+    }
+
+    fn insert(&self, halves: &mut Halves, config: u64, word: &str, normalized: String) {
+        let half = self.capacity / 2;
+        if half == 0 {
+            return;
+        }
+        if halves.current.len >= half {
+            halves.previous = std::mem::take(&mut halves.current);
+        }
+        halves.current.insert(config, word.to_string(), normalized);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Halves> {
+        // The lock is never held while a rule runs, so a panic cannot leave
+        // the halves half-updated
+        self.halves.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for WordCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WordCache")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .field("hits", &self.hits())
+            .field("misses", &self.misses())
+            .finish()
+    }
+}
+
+/// A key for a configuration no normalizer has had before. Normalizers take
+/// a fresh one whenever their rules change; clones share theirs, since they
+/// normalize alike. Key 0 is the default configuration.
+pub(crate) fn new_config() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(cache: &WordCache, config: u64, word: &str) -> String {
+        cache.get_or_insert_with(config, word, || word.to_uppercase())
+    }
+
+    #[test]
+    fn test_hits_and_configs() {
+        let cache = WordCache::new(8);
+        assert_eq!(lookup(&cache, 0, "fuit"), "FUIT");
+        assert_eq!(cache.get_or_insert_with(0, "fuit", || unreachable!()), "FUIT");
+        assert_eq!(cache.get_or_insert_with(1, "fuit", || "fuit".to_string()), "fuit");
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 2, 2));
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.hits(), 0);
+    }
+
+    #[test]
+    fn test_eviction_keeps_recent_words() {
+        let cache = WordCache::new(4);
+        for word in ["a", "b", "c"] {
+            lookup(&cache, 0, word);
+        }
+        // "c" started a new half, so "a" and "b" are next to go; using "a"
+        // saves it
+        lookup(&cache, 0, "a");
+        lookup(&cache, 0, "d");
+        assert_eq!(cache.len(), 3);
+        let misses = cache.misses();
+        lookup(&cache, 0, "a");
+        lookup(&cache, 0, "d");
+        assert_eq!(cache.misses(), misses);
+        lookup(&cache, 0, "b");
+        assert_eq!(cache.misses(), misses + 1);
+        assert!(cache.len() <= cache.capacity());
+
+        let tiny = WordCache::new(1);
+        lookup(&tiny, 0, "a");
+        lookup(&tiny, 0, "a");
+        assert_eq!((tiny.hits(), tiny.len()), (0, 0));
+    }
+}
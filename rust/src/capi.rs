@@ -0,0 +1,370 @@
+//! A C ABI for R, Julia, Java, and other callers outside Python.
+//!
+//! Strings cross the boundary as NUL-terminated UTF-8. Every returned
+//! string is owned by the caller and must be freed with
+//! [`latincy_string_free`], and every pipeline with
+//! [`latincy_pipeline_free`]. On failure (a null or non-UTF-8 argument, an
+//! unknown stage, an unreadable config) a function returns null, and
+//! [`latincy_last_error`] describes the problem until the next call on the
+//! same thread. The declarations are in `include/latincy_preprocess.h`,
+//! generated by `cbindgen --config cbindgen.toml --output
+//! include/latincy_preprocess.h` in `rust/`.
+//!
+//! The detailed functions return the JSON of
+//! [`pipeline::DetailedResult::to_json`](crate::pipeline::DetailedResult::to_json).
+
+use crate::long_s::LongSNormalizer;
+use crate::ngrams::NgramData;
+use crate::pipeline::{self, Normalizer, Pipeline};
+use crate::uv::UvNormalizer;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+use std::sync::{Arc, RwLock};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// The model [`latincy_load_ngrams`] loaded, in place of the bundled one.
+static NGRAMS: RwLock<Option<Arc<NgramData>>> = RwLock::new(None);
+
+/// The normalizer of the long-s functions.
+fn long_s() -> LongSNormalizer {
+    match &*NGRAMS.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(ngrams) => LongSNormalizer::builder().ngrams(Arc::clone(ngrams)).build(),
+        None => LongSNormalizer::new(),
+    }
+}
+
+fn set_error(message: impl Into<String>) {
+    // An interior NUL cannot come from the messages here
+    let message = CString::new(message.into()).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// The UTF-8 string `text` points to, or `None` after setting the error.
+///
+/// # Safety
+///
+/// `text` must be null or point to a NUL-terminated string.
+unsafe fn text<'a>(text: *const c_char, what: &str) -> Option<&'a str> {
+    if text.is_null() {
+        set_error(format!("{what} is null"));
+        return None;
+    }
+    match CStr::from_ptr(text).to_str() {
+        Ok(text) => Some(text),
+        Err(_) => {
+            set_error(format!("{what} is not valid UTF-8"));
+            None
+        }
+    }
+}
+
+/// `s` as a string for the caller to free, or null if it holds a NUL.
+fn owned(s: String) -> *mut c_char {
+    match CString::new(s) {
+        Ok(s) => s.into_raw(),
+        Err(_) => {
+            set_error("the result contains a NUL character");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// `f` applied to the string `input`, as a string for the caller to free.
+unsafe fn map(input: *const c_char, f: impl FnOnce(&str) -> String) -> *mut c_char {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+    match text(input, "text") {
+        Some(input) => owned(f(input)),
+        None => ptr::null_mut(),
+    }
+}
+
+/// The detailed JSON of `normalizer` run on `text`.
+fn detailed_json(normalizer: impl Normalizer + 'static, text: &str) -> String {
+    Pipeline::new().stage(normalizer).normalize_detailed(text).to_json()
+}
+
+/// The crate version, as a static string the caller must not free.
+#[no_mangle]
+pub extern "C" fn latincy_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// The message of the last failure on this thread, or null. The string is
+/// owned by the library and valid until the next call.
+#[no_mangle]
+pub extern "C" fn latincy_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Frees a string returned by this library. Null is ignored.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by this library, not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn latincy_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// `text` with u/v normalized, as by [`uv::normalize`](crate::uv::normalize).
+///
+/// # Safety
+///
+/// `text` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn latincy_normalize_uv(text: *const c_char) -> *mut c_char {
+    map(text, |text| crate::uv::normalize(text).into_owned())
+}
+
+/// The detailed JSON of u/v normalization of `text`.
+///
+/// # Safety
+///
+/// `text` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn latincy_normalize_uv_detailed(text: *const c_char) -> *mut c_char {
+    map(text, |text| detailed_json(UvNormalizer::new(), text))
+}
+
+/// `text` with long-s misreadings corrected by a default
+/// [`LongSNormalizer`], with the model of [`latincy_load_ngrams`] if one
+/// was loaded.
+///
+/// # Safety
+///
+/// `text` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn latincy_normalize_long_s(text: *const c_char) -> *mut c_char {
+    map(text, |text| long_s().normalize_text(text))
+}
+
+/// The detailed JSON of long-s correction of `text`.
+///
+/// # Safety
+///
+/// `text` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn latincy_normalize_long_s_detailed(text: *const c_char) -> *mut c_char {
+    map(text, |text| detailed_json(long_s(), text))
+}
+
+/// Replaces the n-gram model of the long-s functions with the binary model
+/// (as written by `NgramData::write_binary`) in the `len` bytes at `bytes`,
+/// which are copied. Returns false, keeping the current model, if they are
+/// not a valid model.
+///
+/// # Safety
+///
+/// `bytes` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn latincy_load_ngrams(bytes: *const u8, len: usize) -> bool {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+    if bytes.is_null() {
+        set_error("bytes is null");
+        return false;
+    }
+    match NgramData::from_bytes(std::slice::from_raw_parts(bytes, len)) {
+        Ok(data) => {
+            *NGRAMS.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(data));
+            true
+        }
+        Err(e) => {
+            set_error(e.to_string());
+            false
+        }
+    }
+}
+
+/// A pipeline, opaque to C.
+pub struct LatincyPipeline(Pipeline);
+
+/// A new pipeline of the comma-separated stage names `stages`
+/// (`"ligatures,long_s,uv"`), each in its default configuration.
+///
+/// # Safety
+///
+/// `stages` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn latincy_pipeline_new(stages: *const c_char) -> *mut LatincyPipeline {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+    let Some(stages) = text(stages, "stages") else {
+        return ptr::null_mut();
+    };
+    let mut inner = Pipeline::new();
+    for name in stages.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        match pipeline::stage(name) {
+            Some(stage) => inner.push(stage),
+            None => {
+                set_error(format!("unknown pipeline stage {name:?}"));
+                return ptr::null_mut();
+            }
+        }
+    }
+    Box::into_raw(Box::new(LatincyPipeline(inner)))
+}
+
+/// The pipeline declared in the TOML or JSON config file at `path`.
+///
+/// # Safety
+///
+/// `path` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn latincy_pipeline_from_config(path: *const c_char) -> *mut LatincyPipeline {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+    let Some(path) = text(path, "path") else {
+        return ptr::null_mut();
+    };
+    match Pipeline::from_config(path) {
+        Ok(inner) => Box::into_raw(Box::new(LatincyPipeline(inner))),
+        Err(e) => {
+            set_error(e.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Frees a pipeline. Null is ignored.
+///
+/// # Safety
+///
+/// `pipeline` must be null or a pipeline returned by this library, not yet
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn latincy_pipeline_free(pipeline: *mut LatincyPipeline) {
+    if !pipeline.is_null() {
+        drop(Box::from_raw(pipeline));
+    }
+}
+
+/// `text` run through `pipeline`. A pipeline may be used from several
+/// threads at once.
+///
+/// # Safety
+///
+/// `pipeline` must be a live pipeline returned by this library, and `text`
+/// null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn latincy_pipeline_normalize(
+    pipeline: *const LatincyPipeline,
+    text: *const c_char,
+) -> *mut c_char {
+    match pipeline.as_ref() {
+        Some(pipeline) => map(text, |text| pipeline.0.normalize_text(text)),
+        None => {
+            set_error("pipeline is null");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// The detailed JSON of `text` run through `pipeline`.
+///
+/// # Safety
+///
+/// As [`latincy_pipeline_normalize`].
+#[no_mangle]
+pub unsafe extern "C" fn latincy_pipeline_normalize_detailed(
+    pipeline: *const LatincyPipeline,
+    text: *const c_char,
+) -> *mut c_char {
+    match pipeline.as_ref() {
+        Some(pipeline) => map(text, |text| pipeline.0.normalize_detailed(text).to_json()),
+        None => {
+            set_error("pipeline is null");
+            ptr::null_mut()
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Held by the tests of the long-s functions, so none sees the model
+    /// another loaded.
+    static MODEL: Mutex<()> = Mutex::new(());
+
+    /// The string `s` points to, freed.
+    unsafe fn take(s: *mut c_char) -> String {
+        assert!(!s.is_null());
+        let text = CStr::from_ptr(s).to_str().unwrap().to_string();
+        latincy_string_free(s);
+        text
+    }
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(latincy_last_error()) }.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_functions() {
+        let _model = MODEL.lock().unwrap_or_else(|e| e.into_inner());
+        unsafe {
+            assert_eq!(take(latincy_normalize_uv(c"Arma uirumque cano".as_ptr())), "Arma virumque cano");
+            assert_eq!(take(latincy_normalize_long_s(c"funt".as_ptr())), "sunt");
+            let json = take(latincy_normalize_uv_detailed(c"uir".as_ptr()));
+            let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+            assert_eq!(json["normalized"], "vir");
+            assert_eq!(json["changes"][0]["stage"], "uv");
+            let json = take(latincy_normalize_long_s_detailed(c"funt".as_ptr()));
+            assert!(json.contains("\"normalized\":\"sunt\""));
+            assert!(latincy_last_error().is_null());
+            let version = CStr::from_ptr(latincy_version()).to_str().unwrap();
+            assert_eq!(version, env!("CARGO_PKG_VERSION"));
+        }
+    }
+
+    #[test]
+    fn test_pipeline() {
+        unsafe {
+            let pipeline = latincy_pipeline_new(c"ligatures, uv".as_ptr());
+            assert_eq!(take(latincy_pipeline_normalize(pipeline, c"Cæsar uidit".as_ptr())), "Caesar vidit");
+            let json = take(latincy_pipeline_normalize_detailed(pipeline, c"uir".as_ptr()));
+            assert!(json.contains("\"stage\":\"uv\""));
+            latincy_pipeline_free(pipeline);
+            assert!(latincy_pipeline_new(c"uv,vu".as_ptr()).is_null());
+            assert_eq!(last_error(), "unknown pipeline stage \"vu\"");
+            assert!(latincy_pipeline_from_config(c"/nonexistent.toml".as_ptr()).is_null());
+            assert!(!latincy_last_error().is_null());
+        }
+    }
+
+    #[test]
+    fn test_load_ngrams() {
+        let _model = MODEL.lock().unwrap_or_else(|e| e.into_inner());
+        unsafe {
+            assert!(!latincy_load_ngrams(b"nope".as_ptr(), 4));
+            assert!(!latincy_last_error().is_null());
+            assert!(NGRAMS.read().unwrap().is_none());
+            let bytes = crate::ngrams::bundled().unwrap().pruned(3).to_bytes();
+            assert!(latincy_load_ngrams(bytes.as_ptr(), bytes.len()));
+            assert_eq!(take(latincy_normalize_long_s(c"funt".as_ptr())), "sunt");
+        }
+        *NGRAMS.write().unwrap() = None;
+    }
+
+    #[test]
+    fn test_errors() {
+        unsafe {
+            assert!(latincy_normalize_uv(ptr::null()).is_null());
+            assert_eq!(last_error(), "text is null");
+            assert!(latincy_normalize_uv(c"\xff".as_ptr()).is_null());
+            assert_eq!(last_error(), "text is not valid UTF-8");
+            assert!(latincy_pipeline_normalize(ptr::null(), c"uir".as_ptr()).is_null());
+            assert_eq!(last_error(), "pipeline is null");
+            latincy_string_free(ptr::null_mut());
+            latincy_pipeline_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! The capitalization of a word, captured before the rules see it in
+//! lowercase and put back on what they return.
+//!
+//! The u/v and long-s rules are written for lowercase letters. [`WordCase`]
+//! lowercases a word a character at a time, in full (`İ` becomes two
+//! characters), remembering which original character each lowercase one
+//! came from. Restoring then works character by character: letters the
+//! rules left alone come back exactly as they were, so *McDonald* keeps its
+//! inner capital and a titlecase digraph (`ǅ`) stays titlecase, and a letter
+//! the rules changed takes the case of the letter it replaced (*Fuit* →
+//! *Suit*, *Æfopus* → *Æsopus*), or uppercase throughout an all-caps word.
+
+use crate::long_s::LONG_S;
+
+/// Whether `c` is a capital: an uppercase or titlecase (`ǅ`) letter.
+pub(crate) fn is_capital(c: char) -> bool {
+    if c.is_ascii() {
+        c.is_ascii_uppercase()
+    } else {
+        c.is_uppercase() || (!c.is_lowercase() && !c.to_lowercase().eq([c]))
+    }
+}
+
+/// Push `c` in lowercase, in full, onto `out`.
+pub(crate) fn push_lowercase(out: &mut String, c: char) {
+    if c.is_ascii() {
+        out.push(c.to_ascii_lowercase());
+    } else {
+        out.extend(c.to_lowercase());
+    }
+}
+
+/// Whether `c` has no capital of its own to tell the case of a word by:
+/// long s, and letters that uppercase to several (`ß`, `ﬁ`).
+fn is_caseless(c: char) -> bool {
+    c == LONG_S || (!c.is_ascii() && c.to_uppercase().nth(1).is_some())
+}
+
+/// Detect `(is_upper, is_title)` for a word before lowercasing, from its
+/// letters only.
+///
+/// Long s and ligatures are treated as caseless, so `POſSUM` and `FﬁLIUS`
+/// still count as all-caps even though `ſ` and `ﬁ` are lowercase letters.
+pub(crate) fn pattern(word: &str) -> (bool, bool) {
+    let chars: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    let has_caseless = chars.iter().any(|&c| is_caseless(c));
+    let is_upper = chars.len() > 1
+        && chars.iter().all(|&c| is_caseless(c) || !c.is_lowercase())
+        && (!has_caseless || chars.iter().any(|c| c.is_uppercase()));
+    let is_title = chars.first().is_some_and(|&c| is_capital(c)) && (chars.len() == 1 || !is_upper);
+    (is_upper, is_title)
+}
+
+/// A word in lowercase, with what it takes to give a normalized form of it
+/// the word's capitalization back.
+pub(crate) struct WordCase<'a> {
+    word: &'a str,
+    lower: String,
+    /// One per char of `lower`.
+    letters: Vec<Letter>,
+    /// Whether the word is all caps, so changed letters are capitals even
+    /// where they replace a caseless one.
+    upper: bool,
+}
+
+#[derive(Clone, Copy)]
+struct Letter {
+    lower: char,
+    /// The char of the word this one is (part of) the lowercase of.
+    source: char,
+    /// Whether this is the first char of its source's lowercase.
+    first: bool,
+}
+
+impl<'a> WordCase<'a> {
+    pub(crate) fn of(word: &'a str) -> Self {
+        let mut lower = String::with_capacity(word.len());
+        let mut letters = Vec::with_capacity(word.len());
+        for source in word.chars() {
+            let start = lower.len();
+            push_lowercase(&mut lower, source);
+            letters.extend(lower[start..].chars().enumerate().map(|(i, lower)| Letter {
+                lower,
+                source,
+                first: i == 0,
+            }));
+        }
+        WordCase {
+            word,
+            lower,
+            letters,
+            upper: pattern(word).0,
+        }
+    }
+
+    /// The word in lowercase, for the rules.
+    pub(crate) fn lower(&self) -> &str {
+        &self.lower
+    }
+
+    /// `normalized`, a normalized form of [`Self::lower`], in the case of
+    /// the word. The two are aligned on their common prefix and suffix when
+    /// the rules changed the length (`ﬁ` → `fi`); letters in between take
+    /// the case of the letters they stand in for in order.
+    pub(crate) fn restore(&self, normalized: &str) -> String {
+        if normalized == self.lower {
+            return self.word.to_string();
+        }
+        let to: Vec<char> = normalized.chars().collect();
+        let mut result = String::with_capacity(normalized.len());
+        if to.len() == self.letters.len() {
+            self.restore_aligned(&mut result, 0, &to);
+            return result;
+        }
+
+        let prefix = self.letters.iter().zip(&to).take_while(|(letter, &c)| letter.lower == c).count();
+        let suffix = self.letters[prefix..]
+            .iter()
+            .rev()
+            .zip(to[prefix..].iter().rev())
+            .take_while(|(letter, &c)| letter.lower == c)
+            .count();
+        let (from_middle, to_middle) = (prefix..self.letters.len() - suffix, prefix..to.len() - suffix);
+        self.restore_aligned(&mut result, 0, &to[..prefix]);
+        // Letters beyond the ones they replace are capitals between capitals
+        let before = from_middle.end.checked_sub(1).map(|i| self.letters[i].source);
+        let after = self.letters.get(from_middle.end).map(|letter| letter.source);
+        let inserted_capital = self.upper || (before.is_some_and(is_capital) && after.is_some_and(is_capital));
+        for (k, &c) in to[to_middle.clone()].iter().enumerate() {
+            match self.letters[from_middle.clone()].get(k) {
+                Some(letter) => self.recase(&mut result, c, letter.source),
+                None => push_case(&mut result, c, inserted_capital),
+            }
+        }
+        self.restore_aligned(&mut result, from_middle.end, &to[to_middle.end..]);
+        result
+    }
+
+    /// Push `to`, which stands in for the letters from `start` on one for
+    /// one: a source char whose lowercase survived whole as itself, and any
+    /// other char in the case of its letter's source.
+    fn restore_aligned(&self, out: &mut String, start: usize, to: &[char]) {
+        let mut k = 0;
+        while k < to.len() {
+            let letter = self.letters[start + k];
+            let len = 1 + self.letters[start + k + 1..].iter().take_while(|letter| !letter.first).count();
+            let unchanged = letter.first
+                && k + len <= to.len()
+                && (k..k + len).all(|j| to[j] == self.letters[start + j].lower);
+            if unchanged {
+                out.push(letter.source);
+                k += len;
+            } else {
+                self.recase(out, to[k], letter.source);
+                k += 1;
+            }
+        }
+    }
+
+    /// Push `c` as a capital if it replaced one, or goes in an all-caps word.
+    fn recase(&self, out: &mut String, c: char, source: char) {
+        push_case(out, c, self.upper || is_capital(source));
+    }
+}
+
+fn push_case(out: &mut String, c: char, capital: bool) {
+    if capital {
+        out.extend(c.to_uppercase());
+    } else {
+        out.push(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(word: &str, rules: impl Fn(&str) -> String) -> String {
+        let case = WordCase::of(word);
+        case.restore(&rules(case.lower()))
+    }
+
+    #[test]
+    fn test_restore() {
+        let f_to_s = |lower: &str| lower.replace(['f', LONG_S], "s");
+        for (word, restored) in [
+            ("fuit", "suit"),
+            ("Fuit", "Suit"),
+            ("FUIT", "SUIT"),
+            ("McFerfus", "McSersus"),
+            ("mcDonald", "mcDonald"),
+            ("Æfopus", "Æsopus"),
+            ("POſSUM", "POSSUM"),
+            ("ǅfuit", "ǅsuit"),
+            ("İftud", "İstud"),
+            ("ẞfuit", "ẞsuit"),
+            ("", ""),
+        ] {
+            assert_eq!(round_trip(word, f_to_s), restored, "{}", word);
+        }
+
+        // Length changes align on what is left alone around them
+        let expand = |lower: &str| lower.replace('ﬁ', "fi").replace("ﬅ", "st");
+        assert_eq!(round_trip("ﬁlius", expand), "filius");
+        assert_eq!(round_trip("FﬁLIUS", expand), "FFILIUS");
+        assert_eq!(round_trip("McﬅAtua", expand), "McstAtua");
+        assert_eq!(round_trip("ſIﬅE", |lower| expand(lower).replace(LONG_S, "s")), "SISTE");
+        assert_eq!(round_trip("Ab", |_| "xyzb".to_string()), "Xyzb");
+        assert_eq!(round_trip("ABC", |_| "axyzc".to_string()), "AXYZC");
+    }
+
+    #[test]
+    fn test_pattern() {
+        assert_eq!(pattern("POſSUM"), (true, false));
+        assert_eq!(pattern("Fuit"), (false, true));
+        assert_eq!(pattern("ǅuro"), (false, true));
+        assert_eq!(pattern("ſ"), (false, false));
+        assert_eq!(pattern("FﬁLIUS"), (true, false));
+        assert_eq!(pattern("STRAßE"), (true, false));
+        assert!(is_capital('ǅ') && is_capital('Æ') && !is_capital('ǆ') && !is_capital('1'));
+    }
+
+    proptest::proptest! {
+        /// Letters the rules leave alone come back exactly as they were,
+        /// whatever their case mapping.
+        #[test]
+        fn test_restore_keeps_unchanged_letters(word in crate::testing::latin_text()) {
+            let restored = round_trip(&word, |lower| lower.replace('f', "s"));
+            proptest::prop_assert_eq!(restored.chars().count(), word.chars().count());
+            for (original, restored) in word.chars().zip(restored.chars()) {
+                if original.to_lowercase().eq(['f']) {
+                    proptest::prop_assert!(restored == 's' || restored == 'S');
+                } else {
+                    proptest::prop_assert_eq!(original, restored);
+                }
+            }
+        }
+    }
+}
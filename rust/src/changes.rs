@@ -0,0 +1,542 @@
+//! Character-level change records shared by the normalizers.
+
+use crate::graphemes;
+use std::collections::BTreeMap;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::types::{PyDict, PyList};
+
+/// A single character-level change: `original` at `position` became
+/// `normalized`. All the normalizers report changes in this form, so alignment
+/// and reporting code can treat them alike.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeRecord {
+    /// Character offset in the original text.
+    pub position: usize,
+    /// The original character(s): one character, or for long-s a ligature
+    /// (`ﬁ`) or the removed separator of a hyphenated line break.
+    pub original: String,
+    /// The replacement; empty if the characters were removed.
+    pub normalized: String,
+    /// The pass that made the change: the
+    /// [`crate::long_s::AppliedRule::pass`] for long-s, always 1 for the
+    /// single-pass normalizers.
+    pub pass: u8,
+    /// The rule that made the change.
+    pub rule: String,
+    /// The original text around the change, with the changed characters in
+    /// brackets, e.g. `ep[f]um`.
+    pub context: String,
+}
+
+/// Characters of original text on each side of a [`ChangeRecord::context`],
+/// counting a letter with its combining marks as one.
+pub const CONTEXT_CHARS: usize = 3;
+
+/// `chars` around `chars[start..start + len]`, with that span bracketed.
+/// The span and the context are whole grapheme clusters, so a mark is
+/// never cut off from its letter (`[u\u{0304}]a`, not `[u]\u{0304}a`).
+pub(crate) fn context(chars: &[char], start: usize, len: usize) -> String {
+    let end = graphemes::cluster_end(chars, (start + len).min(chars.len()));
+    let start = graphemes::cluster_start(chars, start.min(end));
+    let mut before = start;
+    for _ in 0..CONTEXT_CHARS {
+        if before == 0 {
+            break;
+        }
+        before = graphemes::cluster_start(chars, before - 1);
+    }
+    let mut after = end;
+    for _ in 0..CONTEXT_CHARS {
+        if after == chars.len() {
+            break;
+        }
+        after = graphemes::cluster_end(chars, after + 1);
+    }
+    let mut result: String = chars[before..start].iter().collect();
+    result.push('[');
+    result.extend(&chars[start..end]);
+    result.push(']');
+    result.extend(&chars[end..after]);
+    result
+}
+
+/// How much of its change log a run keeps (see
+/// [`Pipeline::normalize_with_detail`](crate::pipeline::Pipeline::normalize_with_detail)).
+/// A [`ChangeSummary`] grows with the number of rules rather than the
+/// number of changes, so suits totals over a corpus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputDetail {
+    /// The normalized text alone.
+    None,
+    /// The normalized text and a [`ChangeSummary`].
+    Summary,
+    /// The normalized text, every [`ChangeRecord`], and the offsets.
+    #[default]
+    Full,
+}
+
+impl OutputDetail {
+    pub const ALL: [OutputDetail; 3] = [OutputDetail::None, OutputDetail::Summary, OutputDetail::Full];
+
+    /// Parse `"none"`, `"summary"`, or `"full"`.
+    pub fn from_name(name: &str) -> Option<OutputDetail> {
+        OutputDetail::ALL.into_iter().find(|detail| detail.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputDetail::None => "none",
+            OutputDetail::Summary => "summary",
+            OutputDetail::Full => "full",
+        }
+    }
+}
+
+/// Counts of the changes of a run, kept as the changes are made instead of
+/// the records themselves.
+///
+/// ```
+/// use latincy_preprocess::changes::ChangeSummary;
+/// use latincy_preprocess::uv;
+///
+/// let mut summary = ChangeSummary::new();
+/// summary.extend("uv", &uv::normalize_detailed("uia uita").changes);
+/// assert_eq!((summary.changes, summary.chars_changed), (2, 2));
+/// assert_eq!(summary.modules["uv"], 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeSummary {
+    /// The number of change records.
+    pub changes: usize,
+    /// The characters changed, each change counting the longer of its
+    /// `original` and `normalized`.
+    pub chars_changed: usize,
+    /// Changes per rule, each rule prefixed with its module (`"uv:
+    /// initial_before_vowel"`).
+    pub rules: BTreeMap<String, usize>,
+    /// Changes per module: a pipeline stage, or the normalizer that made
+    /// them. A module without changes is not listed.
+    pub modules: BTreeMap<String, usize>,
+}
+
+impl ChangeSummary {
+    pub fn new() -> Self {
+        ChangeSummary::default()
+    }
+
+    /// Count `change`, made by `module`.
+    pub fn add(&mut self, module: &str, change: &ChangeRecord) {
+        self.changes += 1;
+        self.chars_changed += change.original.chars().count().max(change.normalized.chars().count());
+        count(&mut self.rules, &format!("{}: {}", module, change.rule), 1);
+        count(&mut self.modules, module, 1);
+    }
+
+    /// Count each of `changes`, made by `module`.
+    pub fn extend<'a>(&mut self, module: &str, changes: impl IntoIterator<Item = &'a ChangeRecord>) {
+        for change in changes {
+            self.add(module, change);
+        }
+    }
+
+    /// Add the counts of `other` to these, e.g. to total a corpus.
+    pub fn merge(&mut self, other: &ChangeSummary) {
+        self.changes += other.changes;
+        self.chars_changed += other.chars_changed;
+        for (rule, &n) in &other.rules {
+            count(&mut self.rules, rule, n);
+        }
+        for (module, &n) in &other.modules {
+            count(&mut self.modules, module, n);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes == 0
+    }
+
+    /// The summary as a JSON object with `changes`, `chars_changed`,
+    /// `rules`, and `modules`.
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "changes": self.changes,
+            "chars_changed": self.chars_changed,
+            "rules": self.rules,
+            "modules": self.modules,
+        })
+        .to_string()
+    }
+}
+
+/// Add `n` to the count of `key`, allocating the key only when it is new.
+fn count(counts: &mut BTreeMap<String, usize>, key: &str, n: usize) {
+    match counts.get_mut(key) {
+        Some(total) => *total += n,
+        None => {
+            counts.insert(key.to_string(), n);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+/// Python view of a [`ChangeRecord`], exposed as `_rust.ChangeRecord`.
+/// Pipeline changes also carry the stage that made them; `pass` is a Python
+/// keyword, so that field is the `pass_` attribute (and the `"pass"` key).
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "ChangeRecord", frozen)]
+pub struct PyChangeRecord {
+    pub(crate) inner: ChangeRecord,
+    /// Stage name, stage index, and position in the stage's input.
+    pub(crate) stage: Option<(String, usize, usize)>,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyChangeRecord {
+    #[getter]
+    fn position(&self) -> usize {
+        self.inner.position
+    }
+
+    #[getter]
+    fn original(&self) -> &str {
+        &self.inner.original
+    }
+
+    #[getter]
+    fn normalized(&self) -> &str {
+        &self.inner.normalized
+    }
+
+    #[getter]
+    fn pass_(&self) -> u8 {
+        self.inner.pass
+    }
+
+    #[getter]
+    fn rule(&self) -> &str {
+        &self.inner.rule
+    }
+
+    #[getter]
+    fn context(&self) -> &str {
+        &self.inner.context
+    }
+
+    #[getter]
+    fn stage(&self) -> Option<&str> {
+        self.stage.as_ref().map(|(stage, _, _)| stage.as_str())
+    }
+
+    #[getter]
+    fn index(&self) -> Option<usize> {
+        self.stage.as_ref().map(|&(_, index, _)| index)
+    }
+
+    #[getter]
+    fn stage_position(&self) -> Option<usize> {
+        self.stage.as_ref().map(|&(_, _, position)| position)
+    }
+
+    /// The record as a dict; the stage keys only for pipeline changes.
+    pub(crate) fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("position", self.inner.position)?;
+        dict.set_item("original", &self.inner.original)?;
+        dict.set_item("normalized", &self.inner.normalized)?;
+        dict.set_item("pass", self.inner.pass)?;
+        dict.set_item("rule", &self.inner.rule)?;
+        dict.set_item("context", &self.inner.context)?;
+        if let Some((stage, index, stage_position)) = &self.stage {
+            dict.set_item("stage", stage)?;
+            dict.set_item("index", index)?;
+            dict.set_item("stage_position", stage_position)?;
+        }
+        Ok(dict)
+    }
+
+    /// Dict-style access, for code written against the old dict results.
+    fn __getitem__<'py>(&self, py: Python<'py>, key: &str) -> PyResult<Bound<'py, PyAny>> {
+        get_item(&self.to_dict(py)?, key)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ChangeRecord(position={}, original={:?}, normalized={:?}, rule={:?})",
+            self.inner.position, self.inner.original, self.inner.normalized, self.inner.rule
+        )
+    }
+}
+
+/// Python view of a [`ChangeSummary`], exposed as `_rust.ChangeSummary`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "ChangeSummary", frozen)]
+pub struct PyChangeSummary {
+    pub(crate) inner: ChangeSummary,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyChangeSummary {
+    #[getter]
+    fn changes(&self) -> usize {
+        self.inner.changes
+    }
+
+    #[getter]
+    fn chars_changed(&self) -> usize {
+        self.inner.chars_changed
+    }
+
+    /// Changes per rule, as `{"stage: rule": count}`.
+    #[getter]
+    fn rules(&self) -> BTreeMap<String, usize> {
+        self.inner.rules.clone()
+    }
+
+    /// Changes per stage.
+    #[getter]
+    fn modules(&self) -> BTreeMap<String, usize> {
+        self.inner.modules.clone()
+    }
+
+    pub(crate) fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("changes", self.inner.changes)?;
+        dict.set_item("chars_changed", self.inner.chars_changed)?;
+        dict.set_item("rules", &self.inner.rules)?;
+        dict.set_item("modules", &self.inner.modules)?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ChangeSummary(changes={}, chars_changed={}, modules={:?})",
+            self.inner.changes, self.inner.chars_changed, self.inner.modules
+        )
+    }
+}
+
+/// Python view of a detailed result, exposed as `_rust.DetailedResult`: the
+/// `original` and `normalized` text, the `changes`, and where the function
+/// reports them, the `offsets`, long-s `char_changes`, `review` queue,
+/// `warnings`, and pipeline `summary` (otherwise `None`).
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "DetailedResult", frozen)]
+pub struct PyDetailedResult {
+    original: String,
+    normalized: String,
+    changes: Py<PyList>,
+    pub(crate) offsets: Option<Vec<usize>>,
+    pub(crate) char_changes: Option<Py<PyList>>,
+    pub(crate) review: Option<Py<PyList>>,
+    pub(crate) warnings: Option<Py<PyList>>,
+    pub(crate) summary: Option<Py<PyChangeSummary>>,
+}
+
+#[cfg(feature = "pyo3-backend")]
+impl PyDetailedResult {
+    /// A result with the given changes, each a pyclass with a `to_dict`
+    /// method.
+    pub(crate) fn new(original: &str, normalized: &str, changes: Bound<'_, PyList>) -> Self {
+        PyDetailedResult {
+            original: original.to_string(),
+            normalized: normalized.to_string(),
+            changes: changes.unbind(),
+            offsets: None,
+            char_changes: None,
+            review: None,
+            warnings: None,
+            summary: None,
+        }
+    }
+
+    /// A result with the usual character-level changes.
+    pub(crate) fn from_records(
+        py: Python<'_>,
+        original: &str,
+        normalized: &str,
+        records: &[ChangeRecord],
+    ) -> PyResult<Self> {
+        Ok(Self::new(original, normalized, to_py_list(py, records)?))
+    }
+
+    /// This result with `warnings`, from a function that reports them.
+    pub(crate) fn with_warnings(mut self, py: Python<'_>, warnings: &[crate::diagnostics::Warning]) -> PyResult<Self> {
+        self.warnings = Some(crate::diagnostics::to_py_list(py, warnings)?.unbind());
+        Ok(self)
+    }
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyDetailedResult {
+    #[getter]
+    fn original(&self) -> &str {
+        &self.original
+    }
+
+    #[getter]
+    fn normalized(&self) -> &str {
+        &self.normalized
+    }
+
+    #[getter]
+    fn changes<'py>(&self, py: Python<'py>) -> Bound<'py, PyList> {
+        self.changes.bind(py).clone()
+    }
+
+    #[getter]
+    fn offsets(&self) -> Option<Vec<usize>> {
+        self.offsets.clone()
+    }
+
+    #[getter]
+    fn char_changes<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyList>> {
+        self.char_changes.as_ref().map(|list| list.bind(py).clone())
+    }
+
+    #[getter]
+    fn review<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyList>> {
+        self.review.as_ref().map(|list| list.bind(py).clone())
+    }
+
+    #[getter]
+    fn warnings<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyList>> {
+        self.warnings.as_ref().map(|list| list.bind(py).clone())
+    }
+
+    #[getter]
+    fn summary<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyChangeSummary>> {
+        self.summary.as_ref().map(|summary| summary.bind(py).clone())
+    }
+
+    /// The result as nested dicts, with only the keys the function reports.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("original", &self.original)?;
+        dict.set_item("normalized", &self.normalized)?;
+        dict.set_item("changes", to_dicts(self.changes.bind(py))?)?;
+        if let Some(offsets) = &self.offsets {
+            dict.set_item("offsets", offsets)?;
+        }
+        if let Some(char_changes) = &self.char_changes {
+            dict.set_item("char_changes", to_dicts(char_changes.bind(py))?)?;
+        }
+        if let Some(review) = &self.review {
+            dict.set_item("review", review.bind(py))?;
+        }
+        if let Some(warnings) = &self.warnings {
+            dict.set_item("warnings", to_dicts(warnings.bind(py))?)?;
+        }
+        if let Some(summary) = &self.summary {
+            dict.set_item("summary", summary.get().to_dict(py)?)?;
+        }
+        Ok(dict)
+    }
+
+    /// Dict-style access, for code written against the old dict results.
+    fn __getitem__<'py>(&self, py: Python<'py>, key: &str) -> PyResult<Bound<'py, PyAny>> {
+        match key {
+            "changes" => Ok(self.changes(py).into_any()),
+            "char_changes" if self.char_changes.is_some() => Ok(self.char_changes(py).into_pyobject(py)?),
+            "review" if self.review.is_some() => Ok(self.review(py).into_pyobject(py)?),
+            "warnings" if self.warnings.is_some() => Ok(self.warnings(py).into_pyobject(py)?),
+            "summary" if self.summary.is_some() => Ok(self.summary(py).into_pyobject(py)?.into_any()),
+            _ => get_item(&self.to_dict(py)?, key),
+        }
+    }
+
+    fn __len__(&self, py: Python<'_>) -> usize {
+        self.changes.bind(py).len()
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> String {
+        format!(
+            "DetailedResult(original={:?}, normalized={:?}, changes={})",
+            self.original,
+            self.normalized,
+            self.changes.bind(py).len()
+        )
+    }
+}
+
+/// Change records as a Python list of [`PyChangeRecord`]s.
+#[cfg(feature = "pyo3-backend")]
+pub(crate) fn to_py_list<'py>(py: Python<'py>, records: &[ChangeRecord]) -> PyResult<Bound<'py, PyList>> {
+    let list = PyList::empty(py);
+    for change in records {
+        list.append(PyChangeRecord { inner: change.clone(), stage: None })?;
+    }
+    Ok(list)
+}
+
+/// `list` with each item replaced by its `to_dict()`.
+#[cfg(feature = "pyo3-backend")]
+fn to_dicts<'py>(list: &Bound<'py, PyList>) -> PyResult<Bound<'py, PyList>> {
+    let dicts = PyList::empty(list.py());
+    for item in list.iter() {
+        dicts.append(item.call_method0("to_dict")?)?;
+    }
+    Ok(dicts)
+}
+
+/// `dict[key]`, raising `KeyError` like a dict.
+#[cfg(feature = "pyo3-backend")]
+pub(crate) fn get_item<'py>(dict: &Bound<'py, PyDict>, key: &str) -> PyResult<Bound<'py, PyAny>> {
+    dict.get_item(key)?.ok_or_else(|| pyo3::exceptions::PyKeyError::new_err(key.to_string()))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context() {
+        let chars: Vec<char> = "ipfum est".chars().collect();
+        assert_eq!(context(&chars, 2, 1), "ip[f]um ");
+        assert_eq!(context(&chars, 0, 2), "[ip]fum");
+        assert_eq!(context(&chars, 8, 1), " es[t]");
+
+        // Marks stay with their letters, and count with them
+        let chars: Vec<char> = "no\u{0304}u\u{0304}a\u{0323}e\u{0301}rit".chars().collect();
+        assert_eq!(context(&chars, 3, 1), "no\u{0304}[u\u{0304}]a\u{0323}e\u{0301}r");
+        assert_eq!(context(&chars, 8, 1), "o\u{0304}u\u{0304}a\u{0323}[e\u{0301}]rit");
+        assert_eq!(context(&chars, 4, 0), "no\u{0304}[u\u{0304}]a\u{0323}e\u{0301}r");
+    }
+
+    #[test]
+    fn test_change_summary() {
+        let mut summary = ChangeSummary::new();
+        summary.extend("ligatures", &crate::ligatures::expand_detailed("Cæsar", crate::ligatures::Capitals::Auto).changes);
+        summary.extend("uv", &crate::uv::normalize_detailed("uia").changes);
+        assert_eq!((summary.changes, summary.chars_changed), (2, 3));
+        assert_eq!(summary.modules.len(), 2);
+        assert_eq!(summary.rules["ligatures: æ → ae"], 1);
+
+        let mut total = summary.clone();
+        total.merge(&summary);
+        assert_eq!((total.changes, total.chars_changed, total.modules["uv"]), (4, 6, 2));
+        let json: serde_json::Value = serde_json::from_str(&total.to_json()).unwrap();
+        assert_eq!(json["rules"]["ligatures: æ → ae"], 2);
+        assert!(ChangeSummary::new().is_empty());
+    }
+
+    #[test]
+    fn test_output_detail_names() {
+        for detail in OutputDetail::ALL {
+            assert_eq!(OutputDetail::from_name(detail.name()), Some(detail));
+        }
+        assert_eq!(OutputDetail::from_name("records"), None);
+        assert_eq!(OutputDetail::default(), OutputDetail::Full);
+    }
+}
@@ -0,0 +1,667 @@
+//! Stripping the editorial markup of critical-edition transcriptions.
+//!
+//! Editions mark the editor's interventions in the text itself: `[...]`
+//! and `<...>` for supplied letters, `{...}` for deletions, `†...†` for
+//! corrupt passages, and `(!)` or `(sic)` after a reading kept despite
+//! doubts; digitized editions add marginal line numbers and page headers.
+//! None of it is Latin, so remove it before any statistical pass counts it.
+//! [`MarkupCleaner`] has one [`Policy`] per [`Marker`].
+
+use crate::changes::{self, ChangeRecord};
+use crate::diagnostics::{Warning, WarningKind};
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// A kind of editorial markup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Marker {
+    /// `[...]`: letters lost in the source and restored by the editor.
+    Square,
+    /// `<...>`: letters the editor adds.
+    Angle,
+    /// `{...}`: letters the editor deletes.
+    Brace,
+    /// `†...†`, or a single `†`: a corrupt passage.
+    Obelus,
+    /// `(!)`, `(sic)`, `(sic!)` after a doubtful reading.
+    Sic,
+    /// A 1–4 digit number at the start or end of a line, separated from the
+    /// text by whitespace.
+    LineNumber,
+    /// A line holding only a page number, or a running head: a line with a
+    /// page number whose words recur on other such lines.
+    PageHeader,
+}
+
+impl Marker {
+    pub const ALL: [Marker; 7] = [
+        Marker::Square,
+        Marker::Angle,
+        Marker::Brace,
+        Marker::Obelus,
+        Marker::Sic,
+        Marker::LineNumber,
+        Marker::PageHeader,
+    ];
+
+    /// The marker's name, as used by the Python bindings and in change
+    /// records.
+    pub fn name(self) -> &'static str {
+        match self {
+            Marker::Square => "square",
+            Marker::Angle => "angle",
+            Marker::Brace => "brace",
+            Marker::Obelus => "obelus",
+            Marker::Sic => "sic",
+            Marker::LineNumber => "line_number",
+            Marker::PageHeader => "page_header",
+        }
+    }
+}
+
+/// What to do with one kind of markup. Sic markers, line numbers, and page
+/// headers have no content of their own, so both `KeepContent` and
+/// `DropContent` remove them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// Remove the marks, keeping the text between them (`[et]` → `et`).
+    KeepContent,
+    /// Remove the marks and the text between them (`{et}` → nothing).
+    DropContent,
+    /// Leave the markup as it is.
+    KeepMarker,
+}
+
+impl Policy {
+    /// Parse `"keep_content"`, `"drop_content"`, or `"keep_marker"`.
+    pub fn from_name(name: &str) -> Option<Policy> {
+        match name {
+            "keep_content" => Some(Policy::KeepContent),
+            "drop_content" => Some(Policy::DropContent),
+            "keep_marker" => Some(Policy::KeepMarker),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Policy::KeepContent => "keep_content",
+            Policy::DropContent => "drop_content",
+            Policy::KeepMarker => "keep_marker",
+        }
+    }
+}
+
+/// Running heads needed before a repeated page-header line is recognized
+/// (see [`MarkupCleaner::min_header_repeats`]).
+pub const DEFAULT_MIN_HEADER_REPEATS: usize = 3;
+
+/// Sic markers, matched ignoring case.
+const SIC_MARKERS: &[&str] = &["(sic!)", "(sic)", "(!)"];
+
+/// Longest line number or page number, in digits.
+const MAX_NUMBER_DIGITS: usize = 4;
+
+/// Most words in a running head.
+const MAX_HEADER_WORDS: usize = 8;
+
+/// `chars[start..end]` becomes `replacement`.
+struct Span {
+    start: usize,
+    end: usize,
+    replacement: String,
+    marker: Marker,
+    policy: Policy,
+    /// Also remove a space left doubled by the removal.
+    tidy: bool,
+}
+
+fn is_blank(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r')
+}
+
+/// A number of at most [`MAX_NUMBER_DIGITS`] digits.
+fn is_number(chars: &[char]) -> bool {
+    (1..=MAX_NUMBER_DIGITS).contains(&chars.len()) && chars.iter().all(char::is_ascii_digit)
+}
+
+/// The index range of each line, without its `\n`.
+fn lines(chars: &[char]) -> Vec<(usize, usize)> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '\n' {
+            result.push((start, i));
+            start = i + 1;
+        }
+    }
+    result.push((start, chars.len()));
+    result
+}
+
+/// `line` without surrounding blanks, as an index range.
+fn trimmed(chars: &[char], (start, end): (usize, usize)) -> (usize, usize) {
+    let start = chars[start..end].iter().position(|&c| !is_blank(c)).map_or(end, |p| start + p);
+    let end = chars[start..end].iter().rposition(|&c| !is_blank(c)).map_or(start, |p| start + p + 1);
+    (start, end)
+}
+
+/// A line that is only a page number, perhaps decorated (`- 12 -`, `[12]`,
+/// `p. 12`).
+fn is_page_number(line: &[char]) -> bool {
+    let text: String = line.iter().collect();
+    let text = text.trim_start_matches("p.").trim_start_matches("S.");
+    let digits: Vec<char> = text
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '\t' | '-' | '–' | '—' | '[' | ']' | '(' | ')' | '.'))
+        .collect();
+    is_number(&digits)
+}
+
+/// The words of a line starting or ending with a page number, lowercased:
+/// the key that running heads share (`12 LIBER PRIMVS`, `LIBER PRIMVS 13`).
+fn running_head_key(line: &[char]) -> Option<String> {
+    let text: String = line.iter().collect();
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let number = |word: &str| is_number(&word.chars().collect::<Vec<_>>());
+    let rest = match words.as_slice() {
+        [first, rest @ ..] if number(first) => rest,
+        [rest @ .., last] if number(last) => rest,
+        _ => return None,
+    };
+    if rest.is_empty() || rest.len() > MAX_HEADER_WORDS || !rest.iter().all(|w| w.chars().any(char::is_alphabetic)) {
+        return None;
+    }
+    Some(rest.join(" ").to_lowercase())
+}
+
+/// Removes editorial markup.
+///
+/// ```
+/// use latincy_preprocess::cleanup::{MarkupCleaner, Marker, Policy};
+///
+/// let cleaner = MarkupCleaner::new();
+/// assert_eq!(cleaner.clean("arma [uir]umque {et} cano †troiae† (!) qui"), "arma uirumque cano troiae qui");
+/// let cleaner = cleaner.policy(Marker::Obelus, Policy::KeepMarker);
+/// assert_eq!(cleaner.clean("cano †troiae†"), "cano †troiae†");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MarkupCleaner {
+    policies: HashMap<Marker, Policy>,
+    min_header_repeats: usize,
+}
+
+impl Default for MarkupCleaner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkupCleaner {
+    /// Keep supplied text (`[...]`, `<...>`) and obelized text without the
+    /// marks, and remove deletions (`{...}`), sic markers, line numbers, and
+    /// page headers.
+    pub fn new() -> Self {
+        let policies = Marker::ALL
+            .into_iter()
+            .map(|marker| {
+                let policy = match marker {
+                    Marker::Square | Marker::Angle | Marker::Obelus => Policy::KeepContent,
+                    _ => Policy::DropContent,
+                };
+                (marker, policy)
+            })
+            .collect();
+        MarkupCleaner {
+            policies,
+            min_header_repeats: DEFAULT_MIN_HEADER_REPEATS,
+        }
+    }
+
+    /// Set the policy for one kind of markup.
+    pub fn policy(mut self, marker: Marker, policy: Policy) -> Self {
+        self.policies.insert(marker, policy);
+        self
+    }
+
+    /// Running heads with the same words needed before they count as page
+    /// headers (default [`DEFAULT_MIN_HEADER_REPEATS`]). Lines holding only
+    /// a page number are headers regardless.
+    pub fn min_header_repeats(mut self, repeats: usize) -> Self {
+        self.min_header_repeats = repeats;
+        self
+    }
+
+    pub fn policy_for(&self, marker: Marker) -> Policy {
+        self.policies[&marker]
+    }
+
+    fn removes(&self, marker: Marker) -> bool {
+        self.policy_for(marker) != Policy::KeepMarker
+    }
+
+    fn span(&self, start: usize, end: usize, marker: Marker) -> Span {
+        Span {
+            start,
+            end,
+            replacement: String::new(),
+            marker,
+            policy: self.policy_for(marker),
+            tidy: true,
+        }
+    }
+
+    /// Page-header lines, with their line breaks, and line numbers.
+    fn line_spans(&self, chars: &[char], spans: &mut Vec<Span>) {
+        let lines = lines(chars);
+        let mut heads: HashMap<String, usize> = HashMap::new();
+        for &line in &lines {
+            let (start, end) = trimmed(chars, line);
+            if let Some(key) = running_head_key(&chars[start..end]) {
+                *heads.entry(key).or_insert(0) += 1;
+            }
+        }
+
+        for &(line_start, line_end) in &lines {
+            let (start, end) = trimmed(chars, (line_start, line_end));
+            if start == end {
+                continue;
+            }
+            let line = &chars[start..end];
+            let header = is_page_number(line)
+                || running_head_key(line).is_some_and(|key| heads[&key] >= self.min_header_repeats.max(1));
+            if header {
+                if self.removes(Marker::PageHeader) {
+                    let mut span = self.span(line_start, (line_end + 1).min(chars.len()), Marker::PageHeader);
+                    span.tidy = false;
+                    spans.push(span);
+                }
+                continue;
+            }
+            if !self.removes(Marker::LineNumber) {
+                continue;
+            }
+            // A number before the text, and one after it
+            let digits = line.iter().take_while(|c| c.is_ascii_digit()).count();
+            let after = line[digits..].iter().take_while(|&&c| is_blank(c)).count();
+            if is_number(&line[..digits]) && after > 0 && line.get(digits + after).is_some_and(|c| c.is_alphabetic()) {
+                let mut span = self.span(start, start + digits + after, Marker::LineNumber);
+                span.tidy = false;
+                spans.push(span);
+            }
+            let digits = line.iter().rev().take_while(|c| c.is_ascii_digit()).count();
+            let before = line[..line.len() - digits].iter().rev().take_while(|&&c| is_blank(c)).count();
+            let text_end = line.len() - digits - before;
+            if is_number(&line[line.len() - digits..])
+                && before > 0
+                && line[..text_end].last().is_some_and(|c| !c.is_ascii_digit())
+            {
+                let mut span = self.span(start + text_end, end, Marker::LineNumber);
+                span.tidy = false;
+                spans.push(span);
+            }
+        }
+    }
+
+    /// Brackets, matched innermost first, where not inside `skip`. A
+    /// bracket with no partner is left in place, with a warning.
+    fn bracket_spans(&self, chars: &[char], skip: &[bool], spans: &mut Vec<Span>, warnings: &mut Vec<Warning>) {
+        let unbalanced = |i: usize| {
+            let (partner, side) = match chars[i] {
+                '[' => (']', "closing"),
+                ']' => ('[', "opening"),
+                '<' => ('>', "closing"),
+                '>' => ('<', "opening"),
+                '{' => ('}', "closing"),
+                _ => ('{', "opening"),
+            };
+            let message = format!("`{}` with no {side} `{partner}`", chars[i]);
+            Warning::new(chars, i, 1, WarningKind::UnbalancedBracket, message)
+        };
+        let mut open: Vec<(usize, Marker)> = Vec::new();
+        for (i, &c) in chars.iter().enumerate() {
+            if skip[i] {
+                continue;
+            }
+            let (marker, opening) = match c {
+                '[' => (Marker::Square, true),
+                ']' => (Marker::Square, false),
+                '<' => (Marker::Angle, true),
+                '>' => (Marker::Angle, false),
+                '{' => (Marker::Brace, true),
+                '}' => (Marker::Brace, false),
+                _ => continue,
+            };
+            if opening {
+                open.push((i, marker));
+                continue;
+            }
+            // A close matches the nearest open of its kind; unmatched opens
+            // in between are left as they are
+            let Some(k) = open.iter().rposition(|&(_, m)| m == marker) else {
+                warnings.push(unbalanced(i));
+                continue;
+            };
+            let (start, _) = open[k];
+            warnings.extend(open[k + 1..].iter().map(|&(j, _)| unbalanced(j)));
+            open.truncate(k);
+            match self.policy_for(marker) {
+                Policy::KeepContent => {
+                    let mut span = self.span(start, start + 1, marker);
+                    span.tidy = false;
+                    spans.push(span);
+                    let mut span = self.span(i, i + 1, marker);
+                    span.tidy = false;
+                    spans.push(span);
+                }
+                Policy::DropContent => spans.push(self.span(start, i + 1, marker)),
+                Policy::KeepMarker => {}
+            }
+        }
+        warnings.extend(open.iter().map(|&(j, _)| unbalanced(j)));
+    }
+
+    /// Obeli, paired within each line, and sic markers.
+    fn mark_spans(&self, chars: &[char], skip: &[bool], spans: &mut Vec<Span>) {
+        if self.removes(Marker::Obelus) {
+            for (start, end) in lines(chars) {
+                let obeli: Vec<usize> = (start..end).filter(|&i| chars[i] == '†' && !skip[i]).collect();
+                for pair in obeli.chunks(2) {
+                    match (pair, self.policy_for(Marker::Obelus)) {
+                        ([open, close], Policy::DropContent) => spans.push(self.span(*open, close + 1, Marker::Obelus)),
+                        _ => pair.iter().for_each(|&i| spans.push(self.span(i, i + 1, Marker::Obelus))),
+                    }
+                }
+            }
+        }
+        if self.removes(Marker::Sic) {
+            let mut i = 0;
+            while i < chars.len() {
+                let found = SIC_MARKERS.iter().map(|m| m.chars().collect::<Vec<_>>()).find(|m| {
+                    !skip[i] && chars.len() >= i + m.len() && m.iter().zip(&chars[i..]).all(|(a, b)| *a == b.to_ascii_lowercase())
+                });
+                match found {
+                    Some(marker) => {
+                        spans.push(self.span(i, i + marker.len(), Marker::Sic));
+                        i += marker.len();
+                    }
+                    None => i += 1,
+                }
+            }
+        }
+    }
+
+    /// Remove the markup from `text`.
+    pub fn clean(&self, text: &str) -> String {
+        self.clean_detailed(text).normalized
+    }
+
+    /// Like [`MarkupCleaner::clean`], recording one [`ChangeRecord`] per
+    /// removed mark or span. The rule is the marker's and the policy's
+    /// names, e.g. `"brace: drop_content"`. Unbalanced brackets are
+    /// reported as [`Warning`]s.
+    pub fn clean_detailed(&self, text: &str) -> DetailedResult {
+        let chars: Vec<char> = text.chars().collect();
+        let mut spans = Vec::new();
+        self.line_spans(&chars, &mut spans);
+        let mut skip = vec![false; chars.len()];
+        for span in &spans {
+            if span.marker == Marker::PageHeader {
+                skip[span.start..span.end].iter_mut().for_each(|s| *s = true);
+            }
+        }
+        let mut warnings = Vec::new();
+        self.bracket_spans(&chars, &skip, &mut spans, &mut warnings);
+        warnings.sort_by_key(|warning| warning.position);
+        self.mark_spans(&chars, &skip, &mut spans);
+        // Outer spans first, so that spans inside removed text are skipped
+        spans.sort_by_key(|span| (span.start, std::cmp::Reverse(span.end)));
+
+        let mut normalized = String::with_capacity(text.len());
+        let mut changes = Vec::new();
+        let mut cursor = 0;
+        for (k, span) in spans.iter().enumerate() {
+            if span.start < cursor {
+                continue;
+            }
+            let (mut start, mut end) = (span.start, span.end);
+            if span.tidy && span.replacement.is_empty() {
+                let next_start = spans[k + 1..].iter().map(|s| s.start).find(|&s| s >= end);
+                let free = |i: usize| next_start.is_none_or(|s| s > i);
+                if chars.get(end) == Some(&' ') && free(end) && (start == 0 || chars[start - 1].is_whitespace()) {
+                    // `arma {et} virum` → `arma virum`
+                    end += 1;
+                } else if start > cursor
+                    && chars[start - 1] == ' '
+                    && chars.get(end).is_none_or(|c| !c.is_alphanumeric())
+                {
+                    // `verbum (!).` → `verbum.`
+                    start -= 1;
+                }
+            }
+            normalized.extend(&chars[cursor..start]);
+            normalized.push_str(&span.replacement);
+            changes.push(ChangeRecord {
+                position: start,
+                original: chars[start..end].iter().collect(),
+                normalized: span.replacement.clone(),
+                pass: 1,
+                rule: format!("{}: {}", span.marker.name(), span.policy.name()),
+                context: changes::context(&chars, start, end - start),
+            });
+            cursor = end;
+        }
+        normalized.extend(&chars[cursor..]);
+
+        DetailedResult {
+            original: text.to_string(),
+            normalized,
+            changes,
+            warnings,
+        }
+    }
+}
+
+/// Remove editorial markup with the default [`MarkupCleaner`] policies.
+///
+/// ```
+/// use latincy_preprocess::cleanup::clean;
+///
+/// assert_eq!(clean("12\nGallia est omnis <di>uisa {in} partes tres 5"), "Gallia est omnis diuisa partes tres");
+/// ```
+pub fn clean(text: &str) -> String {
+    MarkupCleaner::new().clean(text)
+}
+
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<ChangeRecord>,
+    pub warnings: Vec<Warning>,
+}
+
+impl Normalizer for MarkupCleaner {
+    fn name(&self) -> &str {
+        "markup"
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = self.clean_detailed(text);
+        NormalizeOutput::from_changes(result.original, result.normalized, result.changes).with_warnings(result.warnings)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "clean_markup")]
+pub fn py_clean_markup(py: Python<'_>, text: &str) -> String {
+    crate::allow_threads(py, text, || clean(text))
+}
+
+/// Python view of [`MarkupCleaner`], exposed as `_rust.MarkupCleaner`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "MarkupCleaner", frozen)]
+pub struct PyMarkupCleaner {
+    pub(crate) inner: MarkupCleaner,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyMarkupCleaner {
+    /// Policies are `"keep_content"`, `"drop_content"`, or `"keep_marker"`;
+    /// `None` keeps the default for that marker.
+    #[new]
+    #[pyo3(signature = (
+        square=None,
+        angle=None,
+        brace=None,
+        obelus=None,
+        sic=None,
+        line_number=None,
+        page_header=None,
+        min_header_repeats=DEFAULT_MIN_HEADER_REPEATS
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        square: Option<&str>,
+        angle: Option<&str>,
+        brace: Option<&str>,
+        obelus: Option<&str>,
+        sic: Option<&str>,
+        line_number: Option<&str>,
+        page_header: Option<&str>,
+        min_header_repeats: usize,
+    ) -> PyResult<Self> {
+        let mut inner = MarkupCleaner::new().min_header_repeats(min_header_repeats);
+        let policies = [square, angle, brace, obelus, sic, line_number, page_header];
+        for (marker, policy) in Marker::ALL.into_iter().zip(policies) {
+            let Some(policy) = policy else {
+                continue;
+            };
+            let policy = Policy::from_name(policy).ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "{} policy must be 'keep_content', 'drop_content', or 'keep_marker', got {:?}",
+                    marker.name(),
+                    policy
+                ))
+            })?;
+            inner = inner.policy(marker, policy);
+        }
+        Ok(PyMarkupCleaner { inner })
+    }
+
+    /// The policy for each marker, by name.
+    #[getter]
+    fn policies(&self) -> Vec<(&'static str, &'static str)> {
+        Marker::ALL.iter().map(|&marker| (marker.name(), self.inner.policy_for(marker).name())).collect()
+    }
+
+    fn clean(&self, py: Python<'_>, text: &str) -> String {
+        crate::allow_threads(py, text, || self.inner.clean(text))
+    }
+
+    fn clean_detailed(&self, py: Python<'_>, text: &str) -> PyResult<changes::PyDetailedResult> {
+        let result = crate::allow_threads(py, text, || self.inner.clean_detailed(text));
+        changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)?
+            .with_warnings(py, &result.warnings)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brackets() {
+        assert_eq!(clean("arma [uir]umque <et> cano"), "arma uirumque et cano");
+        assert_eq!(clean("arma {et} uirum {que}"), "arma uirum");
+        assert_eq!(clean("[[abc]] [a <b> c]"), "abc a b c");
+        // A deletion takes the markup inside it along
+        assert_eq!(clean("arma {et [que]} uirum"), "arma uirum");
+        // Unmatched brackets are left
+        assert_eq!(clean("a < b [c]"), "a < b c");
+        assert_eq!(clean("a ] b"), "a ] b");
+    }
+
+    #[test]
+    fn test_obeli_and_sic() {
+        assert_eq!(clean("cano †troiae qui† primus"), "cano troiae qui primus");
+        assert_eq!(clean("cano †troiae"), "cano troiae");
+        assert_eq!(clean("uerbum (!) est uerbum (sic)."), "uerbum est uerbum.");
+        assert_eq!(clean("uerbum (SIC!) est"), "uerbum est");
+        let cleaner = MarkupCleaner::new().policy(Marker::Obelus, Policy::DropContent);
+        assert_eq!(cleaner.clean("cano †troiae qui† primus"), "cano primus");
+    }
+
+    #[test]
+    fn test_line_numbers() {
+        assert_eq!(
+            clean("Arma uirumque cano\nItaliam fato profugus    5\n10 Litora multum ille"),
+            "Arma uirumque cano\nItaliam fato profugus\nLitora multum ille"
+        );
+        // Numbers in the text, and section numbers, are not line numbers
+        assert_eq!(clean("1. Gallia est\nanno 44 a.C."), "1. Gallia est\nanno 44 a.C.");
+    }
+
+    #[test]
+    fn test_page_headers() {
+        let text = "12\nGallia est\n- 13 -\nomnis diuisa\n14 LIBER PRIMVS\nin partes\nLIBER PRIMVS 15\ntres\n16 LIBER PRIMVS\nquarum";
+        assert_eq!(clean(text), "Gallia est\nomnis diuisa\nin partes\ntres\nquarum");
+        // Fewer running heads than the threshold are kept
+        let text = "14 LIBER PRIMVS\nin partes\nLIBER PRIMVS 15";
+        assert_eq!(clean(text), "LIBER PRIMVS\nin partes\nLIBER PRIMVS");
+        assert_eq!(MarkupCleaner::new().min_header_repeats(2).clean(text), "in partes\n");
+        let cleaner = MarkupCleaner::new().policy(Marker::PageHeader, Policy::KeepMarker);
+        assert_eq!(cleaner.clean("12\nGallia"), "12\nGallia");
+    }
+
+    #[test]
+    fn test_keep_marker() {
+        let mut cleaner = MarkupCleaner::new();
+        for marker in Marker::ALL {
+            cleaner = cleaner.policy(marker, Policy::KeepMarker);
+        }
+        let text = "12\narma [uir]umque {et} cano †troiae† (!)    5";
+        assert_eq!(cleaner.clean(text), text);
+    }
+
+    #[test]
+    fn test_clean_detailed() {
+        let result = MarkupCleaner::new().clean_detailed("arma {et} [uir]um");
+        assert_eq!(result.normalized, "arma uirum");
+        assert_eq!(result.changes.len(), 3);
+        assert_eq!(result.changes[0].position, 5);
+        assert_eq!(result.changes[0].original, "{et} ");
+        assert_eq!(result.changes[0].rule, "brace: drop_content");
+        assert_eq!(result.changes[1].original, "[");
+        assert_eq!(result.changes[1].rule, "square: keep_content");
+        assert_eq!(result.changes[2].context, "uir[]]um");
+    }
+
+    #[test]
+    fn test_unbalanced_bracket_warnings() {
+        let result = MarkupCleaner::new().clean_detailed("[ab urbe] condita] et [Roma");
+        assert_eq!(result.normalized, "ab urbe condita] et [Roma");
+        let warnings: Vec<_> = result.warnings.iter().map(|w| (w.position, w.message.as_str())).collect();
+        assert_eq!(warnings, [(17, "`]` with no opening `[`"), (22, "`[` with no closing `]`")]);
+        assert!(result.warnings.iter().all(|w| w.kind == WarningKind::UnbalancedBracket));
+        assert!(MarkupCleaner::new().clean_detailed("arma {et} [uir]um").warnings.is_empty());
+    }
+
+    #[test]
+    fn test_policy_names() {
+        assert_eq!(Policy::from_name("drop_content"), Some(Policy::DropContent));
+        assert_eq!(Policy::from_name("drop"), None);
+        assert!(Marker::ALL.iter().all(|m| MarkupCleaner::new().policy_for(*m) != Policy::KeepMarker));
+    }
+}
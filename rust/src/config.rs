@@ -0,0 +1,825 @@
+//! Pipelines declared in configuration files.
+//!
+//! A config file lists the stages of a [`Pipeline`] in order. A stage is a
+//! name from the [`registry`](crate::registry) (or `"diphthongs"`, which
+//! needs a `lexicon`), or a table with a `name` and the stage's options.
+//! The options of the built-in stages are named after the Python
+//! constructor arguments of the same normalizer; registered stages take
+//! none:
+//!
+//! ```toml
+//! [[stages]]
+//! name = "unicode"
+//!
+//! [[stages]]
+//! name = "long_s"
+//! threshold = 3.0
+//! denylist = "denylist.txt"
+//!
+//! [[stages]]
+//! name = "medieval"
+//! e_for_ae = false
+//! ```
+//!
+//! or, in JSON, `{"stages": ["unicode", {"name": "long_s", "threshold": 3.0}]}`.
+//! A top-level `protect` list names the [`Detector`]s of spans the pipeline
+//! copies verbatim (`protect = ["urls", "greek"]`).
+//! Files ending in `.toml` are read as TOML, anything else as JSON. Relative
+//! paths (lexicons, word lists, n-gram directories, tables) are resolved
+//! against the directory of the config file. Unknown stages, unknown options,
+//! and options of the wrong type are errors, so a typo does not silently fall
+//! back to a default.
+//!
+//! [`PipelineConfig`] is a config as data, for code that writes config
+//! files rather than reads them, such as
+//! [`profile::recommend_pipeline`](crate::profile::recommend_pipeline).
+
+use crate::lexicon::Lexicon;
+use crate::names::NameGuard;
+use crate::ngrams::NgramData;
+use crate::pipeline::{Normalizer, Pipeline};
+use crate::protect::Detector;
+use crate::uv_model::UvModel;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use serde_json::{Map, Value};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+impl Pipeline {
+    /// The pipeline declared in the TOML or JSON file at `path`.
+    ///
+    /// Malformed configs are reported as [`io::ErrorKind::InvalidData`],
+    /// naming the stage and option at fault.
+    pub fn from_config(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let config = if path.extension().is_some_and(|ext| ext == "toml") {
+            let table: toml::Table = text.parse().map_err(|e| invalid(format!("{}: {}", path.display(), e)))?;
+            serde_json::to_value(table).map_err(|e| invalid(e.to_string()))?
+        } else {
+            serde_json::from_str(&text).map_err(|e| invalid(format!("{}: {}", path.display(), e)))?
+        };
+        Self::from_config_value(&config, path.parent().unwrap_or(Path::new("")))
+    }
+
+    /// The pipeline declared by an already parsed config, with relative
+    /// paths resolved against `base`.
+    pub fn from_config_value(config: &Value, base: &Path) -> io::Result<Self> {
+        let Value::Object(config) = config else {
+            return Err(invalid("a pipeline config must be a table with a `stages` list".to_string()));
+        };
+        if let Some(key) = config.keys().find(|key| !matches!(key.as_str(), "stages" | "protect")) {
+            return Err(invalid(format!("unknown pipeline config key {key:?}")));
+        }
+        let stages = match config.get("stages") {
+            Some(Value::Array(stages)) => stages.as_slice(),
+            Some(_) => return Err(invalid("`stages` must be a list".to_string())),
+            None => &[],
+        };
+        let mut pipeline = Pipeline::new();
+        let detectors = match config.get("protect") {
+            Some(Value::Array(names)) => names.as_slice(),
+            Some(_) => return Err(invalid("`protect` must be a list of detector names".to_string())),
+            None => &[],
+        };
+        for name in detectors {
+            let detector = name.as_str().and_then(Detector::from_name).ok_or_else(|| {
+                let names: Vec<&str> = Detector::ALL.iter().map(|d| d.name()).collect();
+                invalid(format!("unknown detector {name} in `protect`; expected one of {}", names.join(", ")))
+            })?;
+            pipeline = pipeline.protect(detector);
+        }
+        for (index, stage) in stages.iter().enumerate() {
+            let (name, options) = match stage {
+                Value::String(name) => (name.as_str(), Map::new()),
+                Value::Object(table) => {
+                    let Some(name) = table.get("name").and_then(Value::as_str) else {
+                        return Err(invalid(format!("stage {}: missing a `name` string", index + 1)));
+                    };
+                    let mut options = table.clone();
+                    options.remove("name");
+                    (name, options)
+                }
+                _ => return Err(invalid(format!("stage {}: must be a stage name or a table", index + 1))),
+            };
+            let mut options = Options {
+                stage: name,
+                index,
+                options,
+                base,
+            };
+            let normalizer = build(name, &mut options)?;
+            options.finish()?;
+            pipeline.push(normalizer);
+        }
+        Ok(pipeline)
+    }
+}
+
+/// A pipeline config as data: its stages and protected spans, written out
+/// with [`to_toml`](Self::to_toml) or [`to_json`](Self::to_json) and read
+/// back by [`Pipeline::from_config`].
+///
+/// ```
+/// use latincy_preprocess::config::{PipelineConfig, StageConfig};
+/// use latincy_preprocess::pipeline::Normalizer;
+/// use std::path::Path;
+///
+/// let mut config = PipelineConfig::new();
+/// config.stages.push(StageConfig::new("ligatures"));
+/// config.stages.push(StageConfig::new("long_s").option("threshold", 3.0).reason("the text has long s"));
+/// assert!(config.to_toml().contains("# the text has long s\n[[stages]]\nname = \"long_s\"\nthreshold = 3.0\n"));
+/// assert_eq!(config.build(Path::new("")).unwrap().normalize_text("Cæſar"), "Caesar");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PipelineConfig {
+    pub stages: Vec<StageConfig>,
+    /// Detectors of spans the pipeline copies verbatim.
+    pub protect: Vec<Detector>,
+}
+
+/// One stage of a [`PipelineConfig`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageConfig {
+    pub name: String,
+    /// Options by name, as a config file gives them.
+    pub options: Map<String, Value>,
+    /// Why the stage is there, written as a comment in TOML.
+    pub reason: Option<String>,
+}
+
+impl StageConfig {
+    /// The stage `name` in its default configuration.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            options: Map::new(),
+            reason: None,
+        }
+    }
+
+    /// Set the option `key`.
+    pub fn option(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.options.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+}
+
+impl PipelineConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The stage names, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.stages.iter().map(|stage| stage.name.as_str())
+    }
+
+    /// The config as [`Pipeline::from_config_value`] reads it: a stage
+    /// without options is its name, and reasons are left out.
+    pub fn to_value(&self) -> Value {
+        let stages = self.stages.iter().map(|stage| {
+            if stage.options.is_empty() {
+                Value::String(stage.name.clone())
+            } else {
+                let mut table = Map::new();
+                table.insert("name".into(), stage.name.clone().into());
+                table.extend(stage.options.clone());
+                Value::Object(table)
+            }
+        });
+        let mut config = Map::new();
+        if !self.protect.is_empty() {
+            config.insert("protect".into(), self.protect.iter().map(|d| d.name()).collect());
+        }
+        config.insert("stages".into(), stages.collect());
+        Value::Object(config)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_value()).unwrap_or_default()
+    }
+
+    /// The config as a TOML file, each stage a `[[stages]]` table with its
+    /// reason as a comment above it.
+    pub fn to_toml(&self) -> String {
+        let mut toml = String::new();
+        if !self.protect.is_empty() {
+            let names: Vec<String> = self.protect.iter().map(|d| format!("{:?}", d.name())).collect();
+            toml.push_str(&format!("protect = [{}]\n", names.join(", ")));
+        }
+        for stage in &self.stages {
+            if !toml.is_empty() {
+                toml.push('\n');
+            }
+            for line in stage.reason.iter().flat_map(|reason| reason.lines()) {
+                toml.push_str(&format!("# {line}\n"));
+            }
+            toml.push_str(&format!("[[stages]]\nname = {}\n", toml::Value::String(stage.name.clone())));
+            for (key, value) in &stage.options {
+                // Config options are strings, numbers, booleans, and lists
+                // of them, which TOML has
+                if let Ok(value) = toml::Value::try_from(value) {
+                    toml.push_str(&format!("{key} = {value}\n"));
+                }
+            }
+        }
+        toml
+    }
+
+    /// Write the config to `path`, as TOML if it ends in `.toml` and as JSON
+    /// otherwise.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            std::fs::write(path, self.to_toml())
+        } else {
+            std::fs::write(path, self.to_json() + "\n")
+        }
+    }
+
+    /// The pipeline the config declares, with relative paths resolved
+    /// against `base`.
+    pub fn build(&self, base: &Path) -> io::Result<Pipeline> {
+        Pipeline::from_config_value(&self.to_value(), base)
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// The options of one stage, taken out one by one as the stage is built.
+struct Options<'a> {
+    stage: &'a str,
+    index: usize,
+    options: Map<String, Value>,
+    base: &'a Path,
+}
+
+impl Options<'_> {
+    fn error(&self, message: impl std::fmt::Display) -> io::Error {
+        invalid(format!("stage {} ({}): {}", self.index + 1, self.stage, message))
+    }
+
+    fn take<T>(&mut self, key: &str, kind: &str, get: impl Fn(&Value) -> Option<T>) -> io::Result<Option<T>> {
+        match self.options.remove(key) {
+            None => Ok(None),
+            Some(value) => get(&value).map(Some).ok_or_else(|| self.error(format!("`{key}` must be {kind}"))),
+        }
+    }
+
+    fn bool(&mut self, key: &str, default: bool) -> io::Result<bool> {
+        Ok(self.take(key, "true or false", Value::as_bool)?.unwrap_or(default))
+    }
+
+    fn f64(&mut self, key: &str) -> io::Result<Option<f64>> {
+        self.take(key, "a number", Value::as_f64)
+    }
+
+    fn usize(&mut self, key: &str) -> io::Result<Option<usize>> {
+        self.take(key, "a non-negative integer", |value| value.as_u64().map(|n| n as usize))
+    }
+
+    fn str(&mut self, key: &str) -> io::Result<Option<String>> {
+        self.take(key, "a string", |value| value.as_str().map(str::to_string))
+    }
+
+    /// A string option parsed by `parse`; `expected` lists the valid values.
+    fn choice<T>(&mut self, key: &str, expected: &str, parse: impl Fn(&str) -> Option<T>) -> io::Result<Option<T>> {
+        let Some(value) = self.str(key)? else {
+            return Ok(None);
+        };
+        parse(&value).map(Some).ok_or_else(|| self.error(format!("`{key}` must be {expected}, got {value:?}")))
+    }
+
+    fn path(&mut self, key: &str) -> io::Result<Option<PathBuf>> {
+        Ok(self.str(key)?.map(|path| self.base.join(path)))
+    }
+
+    fn lexicon(&mut self, key: &str) -> io::Result<Option<Arc<Lexicon>>> {
+        let Some(path) = self.path(key)? else {
+            return Ok(None);
+        };
+        Lexicon::from_file(&path).map(|lexicon| Some(Arc::new(lexicon))).map_err(|e| self.error(format!("{key}: {e}")))
+    }
+
+    fn ngrams(&mut self, key: &str) -> io::Result<Option<Arc<NgramData>>> {
+        let Some(dir) = self.path(key)? else {
+            return Ok(None);
+        };
+        NgramData::from_dir(&dir).map(|ngrams| Some(Arc::new(ngrams))).map_err(|e| self.error(format!("{key}: {e}")))
+    }
+
+    /// A list of words, given inline or as the path of a word list with one
+    /// word per line.
+    fn words(&mut self, key: &str) -> io::Result<Option<Vec<String>>> {
+        match self.options.remove(key) {
+            None => Ok(None),
+            Some(Value::String(path)) => {
+                let path = self.base.join(path);
+                let text = std::fs::read_to_string(&path).map_err(|e| self.error(format!("{key}: {e}")))?;
+                Ok(Some(text.lines().map(str::trim).filter(|w| !w.is_empty()).map(str::to_string).collect()))
+            }
+            Some(Value::Array(words)) => words
+                .iter()
+                .map(|word| word.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+                .map(Some)
+                .ok_or_else(|| self.error(format!("`{key}` must be a list of strings or a file path"))),
+            Some(_) => Err(self.error(format!("`{key}` must be a list of strings or a file path"))),
+        }
+    }
+
+    /// A list of `n`-element lists of strings (the last may be a number
+    /// when `number_last`).
+    fn tuples(&mut self, key: &str, n: usize, number_last: bool) -> io::Result<Option<Vec<Vec<Value>>>> {
+        let kind = format!("a list of {n}-element lists");
+        self.take(key, &kind, |value| {
+            value
+                .as_array()?
+                .iter()
+                .map(|tuple| {
+                    let tuple = tuple.as_array().filter(|tuple| tuple.len() == n)?;
+                    let typed = tuple.iter().enumerate().all(|(i, item)| {
+                        if number_last && i == n - 1 {
+                            item.is_number()
+                        } else {
+                            item.is_string()
+                        }
+                    });
+                    typed.then(|| tuple.clone())
+                })
+                .collect()
+        })
+    }
+
+    /// The name guard from `protect_names`, `names`, and `capitalized_names`.
+    fn name_guard(&mut self) -> io::Result<Option<NameGuard>> {
+        let protect = self.bool("protect_names", true)?;
+        let names = self.lexicon("names")?;
+        let capitalized = self.bool("capitalized_names", false)?;
+        if !protect {
+            return Ok(None);
+        }
+        let mut guard = NameGuard::new().capitalized(capitalized);
+        if let Some(names) = names {
+            guard = guard.names(names);
+        }
+        Ok(Some(guard))
+    }
+
+    /// An error for the first option no builder took.
+    fn finish(self) -> io::Result<()> {
+        match self.options.keys().next() {
+            Some(key) => Err(self.error(format!("unknown option {key:?}"))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The normalizer of the stage `name`, configured from `options`.
+fn build(name: &str, options: &mut Options<'_>) -> io::Result<Box<dyn Normalizer>> {
+    use crate::*;
+    let stage: Box<dyn Normalizer> = match name {
+        "unicode" => {
+            let form = options.choice("form", "'nfc' or 'nfkc'", unicode::Form::from_name)?;
+            Box::new(form.unwrap_or_default())
+        }
+        "dehyphenate" => {
+            let mut dehyphenator = dehyphenate::Dehyphenator::new().backoff(options.bool("backoff", false)?);
+            if let Some(layout) = options.choice("layout", "'reflow' or 'preserve'", dehyphenate::Layout::from_name)? {
+                dehyphenator = dehyphenator.layout(layout);
+            }
+            if let Some(lexicon) = options.lexicon("lexicon")? {
+                dehyphenator = dehyphenator.lexicon(lexicon);
+            }
+            if let Some(ngrams) = options.ngrams("ngram_dir")? {
+                dehyphenator = dehyphenator.ngrams(ngrams);
+            }
+            if let Some(threshold) = options.f64("threshold")? {
+                dehyphenator = dehyphenator.threshold(threshold);
+            }
+            Box::new(dehyphenator)
+        }
+        "markup" => {
+            let mut cleaner = cleanup::MarkupCleaner::new();
+            for marker in cleanup::Marker::ALL {
+                let expected = "'keep_content', 'drop_content', or 'keep_marker'";
+                if let Some(policy) = options.choice(marker.name(), expected, cleanup::Policy::from_name)? {
+                    cleaner = cleaner.policy(marker, policy);
+                }
+            }
+            if let Some(repeats) = options.usize("min_header_repeats")? {
+                cleaner = cleaner.min_header_repeats(repeats);
+            }
+            Box::new(cleaner)
+        }
+        "ligatures" => {
+            let capitals = options.choice("capitals", "'auto', 'title', or 'upper'", |name| match name {
+                "auto" => Some(ligatures::Capitals::Auto),
+                "title" => Some(ligatures::Capitals::Title),
+                "upper" => Some(ligatures::Capitals::Upper),
+                _ => None,
+            })?;
+            Box::new(capitals.unwrap_or_default())
+        }
+        "abbreviations" => {
+            let builtin = options.bool("builtin", true)?;
+            let mut table = if builtin { abbrev::Abbreviations::default() } else { abbrev::Abbreviations::empty() };
+            table = table.with_ampersand(options.bool("ampersand", false)?);
+            for entry in options.tuples("entries", 2, false)?.unwrap_or_default() {
+                let (abbreviation, expansion) = (entry[0].as_str().unwrap_or(""), entry[1].as_str().unwrap_or(""));
+                table.add(abbreviation, expansion).map_err(|e| options.error(e))?;
+            }
+            if let Some(path) = options.path("table")? {
+                table.load(&path).map_err(|e| options.error(format!("table: {e}")))?;
+            }
+            Box::new(table)
+        }
+        "nasal_bars" => {
+            let mut nasal = nasal::NasalBars::default()
+                .macrons(options.bool("macrons", true)?)
+                .keep_length_marks(options.bool("keep_length_marks", false)?);
+            if let Some(threshold) = options.f64("threshold")? {
+                nasal = nasal.threshold(threshold);
+            }
+            if let Some(ngrams) = options.ngrams("ngram_dir")? {
+                nasal = nasal.ngrams(ngrams);
+            }
+            Box::new(nasal)
+        }
+        "long_s" => Box::new(long_s_normalizer(options)?),
+        "ocr" => {
+            let mut corrector = ocr::OcrCorrector::new();
+            if let Some(confusions) = options.tuples("confusions", 3, true)? {
+                let mut matrix = ocr::ConfusionMatrix::empty();
+                for confusion in confusions {
+                    let (from, to) = (confusion[0].as_str().unwrap_or(""), confusion[1].as_str().unwrap_or(""));
+                    matrix.add(from, to, confusion[2].as_f64().unwrap_or(0.0)).map_err(|e| options.error(e))?;
+                }
+                corrector = corrector.confusions(matrix);
+            }
+            if let Some(lexicon) = options.lexicon("lexicon")? {
+                corrector = corrector.lexicon(lexicon);
+            }
+            if let Some(ngrams) = options.ngrams("ngram_dir")? {
+                corrector = corrector.ngrams(ngrams);
+            }
+            Box::new(corrector)
+        }
+        "uv" => {
+            let mut normalizer = uv::UvNormalizer::new()
+                .vocalic_words(options.words("vocalic_words")?.unwrap_or_default())
+                .vocalic_stems(options.words("vocalic_stems")?.unwrap_or_default());
+            if let Some(path) = options.path("model")? {
+                let model = UvModel::load(&path).map_err(|e| options.error(format!("model: {e}")))?;
+                normalizer = normalizer.model(Arc::new(model));
+            }
+            Box::new(normalizer)
+        }
+        "medieval" => {
+            let mut normalizer = medieval::MedievalNormalizer::default();
+            for rule in medieval::MedievalRule::ALL {
+                normalizer = normalizer.rule(rule, options.bool(rule.name(), true)?);
+            }
+            if let Some(lexicon) = options.lexicon("lexicon")? {
+                normalizer = normalizer.lexicon(lexicon);
+            }
+            Box::new(normalizer.name_guard(options.name_guard()?))
+        }
+        "diphthongs" => {
+            let Some(lexicon) = options.lexicon("lexicon")? else {
+                return Err(options.error("needs a `lexicon`"));
+            };
+            let mut restorer = diphthongs::DiphthongRestorer::new(lexicon).backoff(options.bool("backoff", false)?);
+            if let Some(ngrams) = options.ngrams("ngram_dir")? {
+                restorer = restorer.ngrams(ngrams);
+            }
+            if let Some(threshold) = options.f64("threshold")? {
+                restorer = restorer.threshold(threshold);
+            }
+            Box::new(restorer.name_guard(options.name_guard()?))
+        }
+        "loanwords" => {
+            let mut normalizer = loanwords::LoanNormalizer::new()
+                .y_to_i(options.bool("y_to_i", true)?)
+                .k_to_c(options.bool("k_to_c", true)?);
+            let expected = "'keep' or 'simplify'";
+            if let Some(policy) = options.choice("ph", expected, loanwords::Digraph::from_name)? {
+                normalizer = normalizer.ph(policy);
+            }
+            if let Some(policy) = options.choice("th", expected, loanwords::Digraph::from_name)? {
+                normalizer = normalizer.th(policy);
+            }
+            if let Some(policy) = options.choice("ch", expected, loanwords::Digraph::from_name)? {
+                normalizer = normalizer.ch(policy);
+            }
+            Box::new(normalizer)
+        }
+        "epigraphic" => {
+            let mut normalizer = epigraphic::EpigraphicNormalizer::default();
+            for rule in epigraphic::EpigraphicRule::ALL {
+                normalizer = normalizer.rule(rule, options.bool(rule.name(), true)?);
+            }
+            if let Some(lexicon) = options.lexicon("lexicon")? {
+                normalizer = normalizer.lexicon(lexicon);
+            }
+            Box::new(normalizer)
+        }
+        "assimilation" => {
+            let expected = "'assimilated' or 'unassimilated'";
+            Box::new(options.choice("style", expected, assimilation::Style::from_name)?.unwrap_or_default())
+        }
+        "accents" => {
+            let mut stripper = accents::AccentStripper::new();
+            for accent in accents::Accent::ALL {
+                stripper = stripper.accent(accent, options.bool(accent.name(), true)?);
+            }
+            Box::new(stripper)
+        }
+        "recase" => {
+            let mut recaser = recase::Recaser::new();
+            if let Some(names) = options.lexicon("names")? {
+                recaser = recaser.names(names);
+            }
+            Box::new(recaser)
+        }
+        "enclitics" => {
+            let mut splitter = enclitics::EncliticSplitter::new();
+            for enclitic in enclitics::Enclitic::ALL {
+                splitter = splitter.enclitic(enclitic, options.bool(enclitic.name(), true)?);
+            }
+            if let Some(lexicon) = options.lexicon("lexicon")? {
+                splitter = splitter.lexicon(lexicon);
+            }
+            if let Some(separator) = options.str("separator")? {
+                splitter = splitter.separator(&separator);
+            }
+            Box::new(splitter)
+        }
+        _ => match registry::get(name) {
+            Some(stage) => stage,
+            None => {
+                let expected = registry::list().join(", ");
+                return Err(options.error(format!("unknown stage; expected one of {expected}, diphthongs")));
+            }
+        },
+    };
+    Ok(stage)
+}
+
+fn long_s_normalizer(options: &mut Options<'_>) -> io::Result<crate::long_s::LongSNormalizer> {
+    use crate::long_s::{Hyphenation, Language, LongSNormalizer, Pass2Scoring, DEFAULT_DOMAIN_WEIGHT};
+    let language = options.choice("language", "'la', 'en', 'de', or 'fr'", Language::from_code)?;
+    let mut builder = LongSNormalizer::builder().language(language.unwrap_or_default());
+    if let Some(threshold) = options.f64("threshold")? {
+        builder = builder.threshold(threshold);
+    }
+    builder = builder
+        .apply_pass2(options.bool("apply_pass2", true)?)
+        .candidate_search(options.bool("candidate_search", false)?)
+        .medial_pass(options.bool("medial_pass", false)?)
+        .double_f(options.bool("double_f", false)?)
+        .context(options.bool("context", false)?);
+    let scoring = options.choice("scoring", "'logprob' or 'ratio'", |name| match name {
+        "logprob" => Some(Pass2Scoring::LogProb),
+        "ratio" => Some(Pass2Scoring::Ratio),
+        _ => None,
+    })?;
+    let hyphenation = options.choice("hyphenation", "'separate', 'rejoin', or 'preserve'", |name| match name {
+        "separate" => Some(Hyphenation::Separate),
+        "rejoin" => Some(Hyphenation::Rejoin),
+        "preserve" => Some(Hyphenation::Preserve),
+        _ => None,
+    })?;
+    builder = builder.scoring(scoring.unwrap_or_default()).hyphenation(hyphenation.unwrap_or_default());
+    if let Some(words) = options.words("allowlist")? {
+        builder = builder.allowlist(words);
+    }
+    if let Some(words) = options.words("denylist")? {
+        builder = builder.denylist(words);
+    }
+    let model = options.str("model")?;
+    let ngrams = options.ngrams("ngram_dir")?;
+    match (model, ngrams) {
+        (Some(_), Some(_)) => return Err(options.error("model cannot be combined with ngram_dir")),
+        (Some(model), None) => builder = builder.model(&model).map_err(|e| options.error(format!("model: {e}")))?,
+        (None, Some(ngrams)) => builder = builder.ngrams(ngrams),
+        (None, None) => {}
+    }
+    let weight = options.f64("domain_weight")?.unwrap_or(DEFAULT_DOMAIN_WEIGHT);
+    if !(0.0..=1.0).contains(&weight) {
+        return Err(options.error(format!("domain_weight must be between 0 and 1, got {weight}")));
+    }
+    if let Some(domain) = options.ngrams("domain_ngram_dir")? {
+        builder = builder.domain_ngrams(domain, weight);
+    }
+    if let Some(lexicon) = options.lexicon("lexicon")? {
+        builder = builder.lexicon(lexicon);
+    }
+    let mut normalizer = builder.name_guard(options.name_guard()?).build();
+    for rule in options.tuples("pass1_rules", 2, false)?.unwrap_or_default() {
+        let (pattern, replacement) = (rule[0].as_str().unwrap_or(""), rule[1].as_str().unwrap_or(""));
+        normalizer.add_pass1_rule(pattern, replacement).map_err(|e| options.error(e))?;
+    }
+    Ok(normalizer)
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+/// A malformed config as `ValueError`, other I/O errors as `OSError`.
+#[cfg(feature = "pyo3-backend")]
+pub(crate) fn config_error(e: io::Error) -> PyErr {
+    match e.kind() {
+        io::ErrorKind::InvalidData => pyo3::exceptions::PyValueError::new_err(e.to_string()),
+        _ => e.into(),
+    }
+}
+
+/// Python view of a [`PipelineConfig`], exposed as `_rust.PipelineConfig`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "PipelineConfig", frozen)]
+pub struct PyPipelineConfig {
+    pub(crate) inner: PipelineConfig,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyPipelineConfig {
+    /// Names of the stages, in order.
+    #[getter]
+    fn stages(&self) -> Vec<String> {
+        self.inner.names().map(str::to_string).collect()
+    }
+
+    /// `(stage, reason)` for each stage that gives a reason.
+    #[getter]
+    fn reasons(&self) -> Vec<(String, String)> {
+        let reasons = self.inner.stages.iter();
+        reasons.filter_map(|stage| Some((stage.name.clone(), stage.reason.clone()?))).collect()
+    }
+
+    /// Names of the detectors of protected spans.
+    #[getter]
+    fn protect(&self) -> Vec<&'static str> {
+        self.inner.protect.iter().map(|d| d.name()).collect()
+    }
+
+    fn to_toml(&self) -> String {
+        self.inner.to_toml()
+    }
+
+    fn to_json(&self) -> String {
+        self.inner.to_json()
+    }
+
+    /// Write the config as TOML if `path` ends in `.toml`, else as JSON.
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.inner.save(path)?)
+    }
+
+    /// The `Pipeline` the config declares.
+    fn pipeline(&self) -> PyResult<crate::pipeline::PyPipeline> {
+        let inner = self.inner.build(Path::new("")).map_err(config_error)?;
+        Ok(crate::pipeline::PyPipeline::from_pipeline(inner))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PipelineConfig(stages={:?}, protect={:?})", self.stages(), self.protect())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_config_value() {
+        let config = json!({"stages": [
+            "ligatures",
+            {"name": "abbreviations", "builtin": false, "entries": [["xps", "christus"]]},
+            {"name": "enclitics", "separator": " -", "ne": false},
+            {"name": "uv", "vocalic_stems": ["genu"]},
+        ]});
+        let pipeline = Pipeline::from_config_value(&config, Path::new("")).unwrap();
+        assert_eq!(pipeline.names().collect::<Vec<_>>(), ["ligatures", "abbreviations", "enclitics", "uv"]);
+        assert_eq!(pipeline.normalize_text("Cæsar xps uirumque"), "Caesar christus virum -que");
+        assert_eq!(pipeline.normalize_text("dns"), "dns");
+        assert_eq!(pipeline.normalize_text("genua uia"), "genua via");
+        assert!(Pipeline::from_config_value(&json!({}), Path::new("")).unwrap().is_empty());
+
+        let config = json!({"stages": ["uv"], "protect": ["urls", "greek"]});
+        let pipeline = Pipeline::from_config_value(&config, Path::new("")).unwrap();
+        assert_eq!(pipeline.detectors(), [Detector::Urls, Detector::Greek]);
+        assert_eq!(pipeline.normalize_text("uide www.uia.org"), "vide www.uia.org");
+    }
+
+    #[test]
+    fn test_registered_stage() {
+        use crate::pipeline::FnNormalizer;
+        let tironian = |text: &str| text.replace('⁊', "et");
+        crate::registry::register("config_test", move || Box::new(FnNormalizer::new("config_test", tironian)));
+        let config = json!({"stages": ["config_test", "uv"]});
+        let pipeline = Pipeline::from_config_value(&config, Path::new("")).unwrap();
+        assert_eq!(pipeline.normalize_text("uir ⁊ uxor"), "vir et uxor");
+        let config = json!({"stages": [{"name": "config_test", "threshold": 2}]});
+        let err = Pipeline::from_config_value(&config, Path::new("")).unwrap_err();
+        assert_eq!(err.to_string(), "stage 1 (config_test): unknown option \"threshold\"");
+    }
+
+    #[test]
+    fn test_from_config_errors() {
+        let error = |config: Value| Pipeline::from_config_value(&config, Path::new("")).unwrap_err().to_string();
+        assert!(error(json!({"stages": ["uv", "vu"]})).starts_with("stage 2 (vu): unknown stage"));
+        assert_eq!(error(json!({"stages": [{"name": "uv", "x": 1}]})), "stage 1 (uv): unknown option \"x\"");
+        assert_eq!(
+            error(json!({"stages": [{"name": "long_s", "threshold": "high"}]})),
+            "stage 1 (long_s): `threshold` must be a number"
+        );
+        assert_eq!(
+            error(json!({"stages": [{"name": "ligatures", "capitals": "lower"}]})),
+            "stage 1 (ligatures): `capitals` must be 'auto', 'title', or 'upper', got \"lower\""
+        );
+        assert_eq!(error(json!({"stages": ["diphthongs"]})), "stage 1 (diphthongs): needs a `lexicon`");
+        assert_eq!(error(json!({"stage": []})), "unknown pipeline config key \"stage\"");
+        assert_eq!(
+            error(json!({"protect": ["latin"]})),
+            "unknown detector \"latin\" in `protect`; expected one of urls, entities, greek, citations"
+        );
+        assert_eq!(error(json!({"stages": [{"threshold": 2}]})), "stage 1: missing a `name` string");
+    }
+
+    #[test]
+    fn test_from_config_file() {
+        let dir = std::env::temp_dir().join(format!("latincy_config_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("names.txt"), "Marcus\n").unwrap();
+        let toml = dir.join("pipeline.toml");
+        std::fs::write(
+            &toml,
+            "[[stages]]\nname = \"recase\"\nnames = \"names.txt\"\n\n[[stages]]\nname = \"long_s\"\nthreshold = 3.0\n",
+        )
+        .unwrap();
+        let pipeline = Pipeline::from_config(&toml).unwrap();
+        assert_eq!(pipeline.names().collect::<Vec<_>>(), ["recase", "long_s"]);
+        assert_eq!(pipeline.normalize_text("MARCUS ﬅat"), "Marcus stat");
+
+        let json = dir.join("pipeline.json");
+        std::fs::write(&json, r#"{"stages": [{"name": "recase", "names": "missing.txt"}]}"#).unwrap();
+        let err = Pipeline::from_config(&json).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("stage 1 (recase): names: "));
+        UvModel::train(["genua genuum genuamus genuent"]).save(dir.join("uv.json")).unwrap();
+        std::fs::write(&json, r#"{"stages": [{"name": "uv", "model": "uv.json"}]}"#).unwrap();
+        assert_eq!(Pipeline::from_config(&json).unwrap().normalize_text("genua"), "genua");
+        std::fs::write(&json, "{\"stages\": [").unwrap();
+        assert_eq!(Pipeline::from_config(&json).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(Pipeline::from_config(dir.join("none.json")).unwrap_err().kind(), io::ErrorKind::NotFound);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_pipeline_config() {
+        let config = PipelineConfig {
+            stages: vec![
+                StageConfig::new("ligatures").reason("æ"),
+                StageConfig::new("long_s").option("threshold", 3.0).option("denylist", json!(["fit"])),
+            ],
+            protect: vec![Detector::Greek],
+        };
+        assert_eq!(
+            config.to_value(),
+            json!({"protect": ["greek"], "stages": ["ligatures", {"name": "long_s", "threshold": 3.0, "denylist": ["fit"]}]})
+        );
+        assert_eq!(
+            config.to_toml(),
+            "protect = [\"greek\"]\n\n# æ\n[[stages]]\nname = \"ligatures\"\n\n[[stages]]\nname = \"long_s\"\n\
+             denylist = [\"fit\"]\nthreshold = 3.0\n"
+        );
+
+        let dir = std::env::temp_dir().join(format!("latincy_config_out_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in ["pipeline.toml", "pipeline.json"] {
+            config.save(dir.join(file)).unwrap();
+            let pipeline = Pipeline::from_config(dir.join(file)).unwrap();
+            assert_eq!(pipeline.names().collect::<Vec<_>>(), ["ligatures", "long_s"]);
+            assert_eq!(pipeline.detectors(), [Detector::Greek]);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
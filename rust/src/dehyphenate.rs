@@ -0,0 +1,451 @@
+//! Rejoining words split across line breaks (`impera-` / `tor`).
+//!
+//! OCR output keeps the printer's hyphenation, which every later pass would
+//! otherwise see as two words; run this first. A hyphen at the end of a line
+//! is usually a break, but compounds such as *Graeco-Romanus* keep theirs: a
+//! [`Dehyphenator`] checks each join against a [`Lexicon`] and, optionally,
+//! the n-gram model before removing the hyphen. The rejoined word either
+//! closes up the line break ([`Layout::Reflow`]) or moves to the end of the
+//! first line ([`Layout::Preserve`]), and [`DetailedResult::offsets`] maps
+//! the result back onto the original text.
+
+use crate::changes::{self, ChangeRecord};
+use crate::lexicon::Lexicon;
+use crate::long_s::LINE_BREAK_HYPHENS;
+use crate::ngrams::{self, NgramData};
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use crate::ngrams::PyNgramData;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use std::sync::Arc;
+
+/// Where a rejoined word goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+    /// Remove the hyphen and the line break, joining the two lines
+    /// (`impera-\ntor est` → `imperator est`); the default.
+    #[default]
+    Reflow,
+    /// Keep the lines: the word moves up to the end of the first line, and
+    /// the space after it becomes the line break (`impera-\ntor est` →
+    /// `imperator\nest`).
+    Preserve,
+}
+
+impl Layout {
+    /// Parse `"reflow"` or `"preserve"`.
+    pub fn from_name(name: &str) -> Option<Layout> {
+        match name {
+            "reflow" => Some(Layout::Reflow),
+            "preserve" => Some(Layout::Preserve),
+            _ => None,
+        }
+    }
+}
+
+fn is_blank(c: char) -> bool {
+    matches!(c, ' ' | '\t')
+}
+
+/// If a line-break hyphen starts at `i` (after a letter, with the next line
+/// starting with a letter), the index of that letter.
+fn line_break_at(chars: &[char], i: usize) -> Option<usize> {
+    if i == 0 || !chars[i - 1].is_alphabetic() || !LINE_BREAK_HYPHENS.contains(&chars[i]) {
+        return None;
+    }
+    let mut j = i + 1;
+    while chars.get(j).copied().is_some_and(is_blank) {
+        j += 1;
+    }
+    if chars.get(j) == Some(&'\r') {
+        j += 1;
+    }
+    if chars.get(j) != Some(&'\n') {
+        return None;
+    }
+    j += 1;
+    while chars.get(j).copied().is_some_and(is_blank) {
+        j += 1;
+    }
+    chars.get(j).is_some_and(|c| c.is_alphabetic()).then_some(j)
+}
+
+/// Rejoins words hyphenated across line breaks.
+///
+/// ```
+/// use latincy_preprocess::dehyphenate::{Dehyphenator, Layout};
+/// use latincy_preprocess::lexicon::Lexicon;
+/// use std::sync::Arc;
+///
+/// let lexicon = Lexicon::from_words(["quasi", "contractus"]);
+/// let dehyphenator = Dehyphenator::new().lexicon(Arc::new(lexicon));
+/// assert_eq!(dehyphenator.dehyphenate("impera-\ntor ex quasi-\ncontractus"), "imperator ex quasi-contractus");
+/// let dehyphenator = dehyphenator.layout(Layout::Preserve);
+/// assert_eq!(dehyphenator.dehyphenate("impera-\ntor est"), "imperator\nest");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Dehyphenator {
+    lexicon: Option<Arc<Lexicon>>,
+    ngrams: Option<Arc<NgramData>>,
+    backoff: bool,
+    threshold: f64,
+    layout: Layout,
+}
+
+impl Dehyphenator {
+    /// Join every line-break hyphen except before a capital (*Graeco-* /
+    /// *Romanus*), reflowing the lines.
+    pub fn new() -> Self {
+        Dehyphenator {
+            lexicon: None,
+            ngrams: None,
+            backoff: false,
+            threshold: 1.0,
+            layout: Layout::default(),
+        }
+    }
+
+    /// Check joins against `lexicon`: a join it attests is made, and a
+    /// hyphen is kept where it attests the hyphenated compound, or both
+    /// halves but not the joined word.
+    pub fn lexicon(mut self, lexicon: Arc<Lexicon>) -> Self {
+        self.lexicon = Some(lexicon);
+        self
+    }
+
+    /// For joins the lexicon does not decide, keep the hyphen if the n-gram
+    /// model prefers the two halves as words of their own (off by default).
+    pub fn backoff(mut self, backoff: bool) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Back off to `ngrams` instead of the bundled model.
+    pub fn ngrams(mut self, ngrams: Arc<NgramData>) -> Self {
+        self.ngrams = Some(ngrams);
+        self
+    }
+
+    /// The probability ratio the two halves must beat the joined word by
+    /// when backing off (default 1.0).
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    fn model(&self) -> Option<&NgramData> {
+        match &self.ngrams {
+            Some(ngrams) => Some(ngrams),
+            None => ngrams::bundled().ok(),
+        }
+    }
+
+    /// Whether the hyphen between `first` and `second` belongs to the word.
+    fn keep_hyphen(&self, first: &str, second: &str) -> bool {
+        let joined = format!("{}{}", first, second);
+        if let Some(lexicon) = &self.lexicon {
+            if lexicon.contains(&joined) {
+                return false;
+            }
+            if lexicon.contains(&format!("{}-{}", first, second)) {
+                return true;
+            }
+        }
+        // A capital after the break starts a name (Graeco-Romanus), unless
+        // the whole word is in capitals
+        if second.starts_with(char::is_uppercase) && first.chars().any(char::is_lowercase) {
+            return true;
+        }
+        if let Some(lexicon) = &self.lexicon {
+            if lexicon.contains(first) && lexicon.contains(second) {
+                return true;
+            }
+        }
+        let Some(model) = self.model().filter(|_| self.backoff) else {
+            return false;
+        };
+        let halves = model.log_prob(first) + model.log_prob(second);
+        self.threshold <= 0.0 || halves - model.log_prob(&joined) > self.threshold.ln() + 1e-9
+    }
+
+    /// Rejoin the hyphenated words in `text`.
+    pub fn dehyphenate(&self, text: &str) -> String {
+        self.dehyphenate_detailed(text).normalized
+    }
+
+    /// Like [`Dehyphenator::dehyphenate`], recording a [`ChangeRecord`] per
+    /// removed line break (and, with [`Layout::Preserve`], per moved one).
+    pub fn dehyphenate_detailed(&self, text: &str) -> DetailedResult {
+        let chars: Vec<char> = text.chars().collect();
+        let mut normalized = String::with_capacity(text.len());
+        let mut offsets = Vec::with_capacity(chars.len() + 1);
+        let mut changes = Vec::new();
+        let mut emit = |c: char, position: usize| {
+            normalized.push(c);
+            offsets.push(position);
+        };
+
+        let mut word_start = 0;
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let Some(start) = line_break_at(&chars, i) else {
+                if !c.is_alphabetic() {
+                    word_start = i + 1;
+                }
+                emit(c, i);
+                i += 1;
+                continue;
+            };
+            let end = chars[start..].iter().position(|c| !c.is_alphabetic()).map_or(chars.len(), |p| start + p);
+            let first: String = chars[word_start..i].iter().collect();
+            let second: String = chars[start..end].iter().collect();
+            // A soft hyphen is only ever a break
+            let keep = c != '\u{00AD}' && self.keep_hyphen(&first, &second);
+            let (replacement, rule) = if keep {
+                let hyphen = if c == '¬' { '-' } else { c };
+                emit(hyphen, i);
+                (hyphen.to_string(), "line break removed after hyphen")
+            } else {
+                (String::new(), "line-break hyphen removed")
+            };
+            changes.push(ChangeRecord {
+                position: i,
+                original: chars[i..start].iter().collect(),
+                normalized: replacement,
+                pass: 1,
+                rule: rule.to_string(),
+                context: changes::context(&chars, i, start - i),
+            });
+            for (k, &c) in chars.iter().enumerate().take(end).skip(start) {
+                emit(c, k);
+            }
+            word_start = start;
+            i = end;
+
+            if self.layout == Layout::Preserve {
+                // Punctuation after the word moves up with it
+                while i < chars.len()
+                    && !chars[i].is_whitespace()
+                    && !chars[i].is_alphabetic()
+                    && line_break_at(&chars, i).is_none()
+                {
+                    emit(chars[i], i);
+                    i += 1;
+                }
+                let blank_end = chars[i..].iter().position(|&c| !is_blank(c)).map_or(chars.len(), |p| i + p);
+                if blank_end > i && blank_end < chars.len() && !matches!(chars[blank_end], '\n' | '\r') {
+                    changes.push(ChangeRecord {
+                        position: i,
+                        original: chars[i..blank_end].iter().collect(),
+                        normalized: "\n".to_string(),
+                        pass: 1,
+                        rule: "line break moved after rejoined word".to_string(),
+                        context: changes::context(&chars, i, blank_end - i),
+                    });
+                    emit('\n', i);
+                    i = blank_end;
+                    word_start = i;
+                }
+            }
+        }
+        offsets.push(chars.len());
+
+        DetailedResult {
+            original: text.to_string(),
+            normalized,
+            changes,
+            offsets,
+        }
+    }
+}
+
+impl Default for Dehyphenator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rejoin words hyphenated across line breaks, reflowing the lines. Only a
+/// capital after the break keeps the hyphen; use a [`Dehyphenator`] with a
+/// lexicon to keep compounds.
+///
+/// ```
+/// use latincy_preprocess::dehyphenate::dehyphenate;
+///
+/// assert_eq!(dehyphenate("Gallia est omnis di-\nuisa in partes"), "Gallia est omnis diuisa in partes");
+/// ```
+pub fn dehyphenate(text: &str) -> String {
+    Dehyphenator::new().dehyphenate(text)
+}
+
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<ChangeRecord>,
+    /// The character offset in `original` of each character of
+    /// `normalized`, then the length of `original`: normalized characters
+    /// `a..b` came from original characters `offsets[a]..offsets[b]`.
+    pub offsets: Vec<usize>,
+}
+
+impl Normalizer for Dehyphenator {
+    fn name(&self) -> &str {
+        "dehyphenate"
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = self.dehyphenate_detailed(text);
+        NormalizeOutput {
+            original: result.original,
+            normalized: result.normalized,
+            changes: result.changes,
+            offsets: result.offsets,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+fn parse_layout(layout: &str) -> PyResult<Layout> {
+    Layout::from_name(layout).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!("layout must be 'reflow' or 'preserve', got {:?}", layout))
+    })
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "dehyphenate", signature = (text, layout="reflow"))]
+pub fn py_dehyphenate(py: Python<'_>, text: &str, layout: &str) -> PyResult<String> {
+    let dehyphenator = Dehyphenator::new().layout(parse_layout(layout)?);
+    Ok(crate::allow_threads(py, text, || dehyphenator.dehyphenate(text)))
+}
+
+/// Python view of [`Dehyphenator`], exposed as `_rust.Dehyphenator`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "Dehyphenator", frozen)]
+pub struct PyDehyphenator {
+    pub(crate) inner: Dehyphenator,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyDehyphenator {
+    #[new]
+    #[pyo3(signature = (lexicon=None, layout="reflow", backoff=false, ngrams=None, threshold=1.0))]
+    fn new(
+        lexicon: Option<std::path::PathBuf>,
+        layout: &str,
+        backoff: bool,
+        ngrams: Option<PyRef<'_, PyNgramData>>,
+        threshold: f64,
+    ) -> PyResult<Self> {
+        let mut inner = Dehyphenator::new()
+            .layout(parse_layout(layout)?)
+            .backoff(backoff)
+            .threshold(threshold);
+        if let Some(lexicon) = lexicon {
+            inner = inner.lexicon(Arc::new(Lexicon::from_file(lexicon)?));
+        }
+        if let Some(ngrams) = ngrams {
+            inner = inner.ngrams(Arc::clone(&ngrams.inner));
+        }
+        Ok(PyDehyphenator { inner })
+    }
+
+    fn dehyphenate(&self, py: Python<'_>, text: &str) -> String {
+        crate::allow_threads(py, text, || self.inner.dehyphenate(text))
+    }
+
+    fn dehyphenate_detailed(&self, py: Python<'_>, text: &str) -> PyResult<changes::PyDetailedResult> {
+        let result = crate::allow_threads(py, text, || self.inner.dehyphenate_detailed(text));
+        let mut detailed =
+            changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)?;
+        detailed.offsets = Some(result.offsets);
+        Ok(detailed)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dehyphenator() -> Dehyphenator {
+        Dehyphenator::new().lexicon(Arc::new(Lexicon::from_words([
+            "imperator",
+            "quasi",
+            "contractus",
+            "graeco-romanus",
+            "in",
+            "de",
+            "deus",
+        ])))
+    }
+
+    #[test]
+    fn test_dehyphenate() {
+        assert_eq!(dehyphenate("impera-\ntor"), "imperator");
+        assert_eq!(dehyphenate("impera- \r\n  tor est"), "imperator est");
+        assert_eq!(dehyphenate("impera¬\ntor impera\u{00AD}\ntor"), "imperator imperator");
+        assert_eq!(dehyphenate("IMPE-\nRATOR"), "IMPERATOR");
+        // Not line-break hyphens
+        assert_eq!(dehyphenate("impera-tor et -\nmox\nest"), "impera-tor et -\nmox\nest");
+        // A capital after the break keeps the hyphen
+        assert_eq!(dehyphenate("Graeco-\nRomani"), "Graeco-Romani");
+    }
+
+    #[test]
+    fn test_lexicon() {
+        let dehyphenator = dehyphenator();
+        assert_eq!(dehyphenator.dehyphenate("quasi-\ncontractus"), "quasi-contractus");
+        assert_eq!(dehyphenator.dehyphenate("de-\nus"), "deus");
+        // The lexicon attests the compound, whatever the case
+        assert_eq!(dehyphenator.dehyphenate("graeco-\nromanus"), "graeco-romanus");
+        // Unknown halves are joined
+        assert_eq!(dehyphenator.dehyphenate("pro-\nuincia"), "prouincia");
+    }
+
+    #[test]
+    fn test_preserve() {
+        let dehyphenator = Dehyphenator::new().layout(Layout::Preserve);
+        assert_eq!(dehyphenator.dehyphenate("impera-\ntor, est\nvir"), "imperator,\nest\nvir");
+        assert_eq!(dehyphenator.dehyphenate("impera-\ntor\nest"), "imperator\nest");
+        assert_eq!(dehyphenator.dehyphenate("impera-\ntor"), "imperator");
+    }
+
+    #[test]
+    fn test_backoff() {
+        let ngrams = Arc::new(crate::ngrams::train_ngrams(["sic et non sic et non"]));
+        let dehyphenator = Dehyphenator::new().ngrams(ngrams);
+        assert_eq!(dehyphenator.dehyphenate("sic-\nnon"), "sicnon");
+        assert_eq!(dehyphenator.backoff(true).dehyphenate("sic-\nnon"), "sic-non");
+    }
+
+    #[test]
+    fn test_dehyphenate_detailed() {
+        let result = Dehyphenator::new().layout(Layout::Preserve).dehyphenate_detailed("di-\nuisa est");
+        assert_eq!(result.normalized, "diuisa\nest");
+        assert_eq!(result.changes.len(), 2);
+        assert_eq!(result.changes[0].position, 2);
+        assert_eq!(result.changes[0].original, "-\n");
+        assert_eq!(result.changes[0].rule, "line-break hyphen removed");
+        assert_eq!(result.changes[0].context, "di[-\n]uis");
+        assert_eq!(result.changes[1].position, 8);
+        assert_eq!(result.changes[1].normalized, "\n");
+        assert_eq!(result.offsets, vec![0, 1, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+    }
+}
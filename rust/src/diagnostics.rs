@@ -0,0 +1,147 @@
+//! Warnings about suspicious input, reported alongside the changes.
+//!
+//! Some input is beyond a normalizer's rules: a word mixing digits and
+//! letters that no confusion explains, a bracket that never closes, a line
+//! of capitals too short to recase. Rather than guess, the normalizer
+//! leaves the text as it is and reports a [`Warning`] on its detailed
+//! result, so the caller can look at the spot. Warnings never stop a run.
+
+use crate::changes;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::types::{PyDict, PyList};
+
+/// What a [`Warning`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WarningKind {
+    /// A word with digits among its letters that no OCR confusion reads as
+    /// letters (`c3sar`).
+    MixedDigits,
+    /// An editorial bracket with no partner (`[ab urbe`), left in place.
+    UnbalancedBracket,
+    /// A run of two or more single capitals (`S P Q R`) not recased, since
+    /// it may be initials or an abbreviation.
+    AllCapsSkipped,
+}
+
+impl WarningKind {
+    pub const ALL: [WarningKind; 3] =
+        [WarningKind::MixedDigits, WarningKind::UnbalancedBracket, WarningKind::AllCapsSkipped];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WarningKind::MixedDigits => "mixed_digits",
+            WarningKind::UnbalancedBracket => "unbalanced_bracket",
+            WarningKind::AllCapsSkipped => "all_caps_skipped",
+        }
+    }
+}
+
+/// Suspicious input at `position`, which the normalizer left unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    /// Character offset in the original text.
+    pub position: usize,
+    /// Length in characters of the suspicious span.
+    pub len: usize,
+    pub kind: WarningKind,
+    pub message: String,
+    /// The original text around the span, with the span in brackets, as
+    /// in [`ChangeRecord::context`](crate::changes::ChangeRecord::context).
+    pub context: String,
+}
+
+impl Warning {
+    /// A warning about `chars[position..position + len]`.
+    pub(crate) fn new(chars: &[char], position: usize, len: usize, kind: WarningKind, message: String) -> Self {
+        Warning {
+            position,
+            len,
+            kind,
+            message,
+            context: changes::context(chars, position, len),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+/// Python view of a [`Warning`], exposed as `_rust.NormalizerWarning`.
+/// Pipeline warnings also carry the stage that reported them.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "NormalizerWarning", frozen)]
+pub struct PyWarning {
+    pub(crate) inner: Warning,
+    pub(crate) stage: Option<String>,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyWarning {
+    #[getter]
+    fn position(&self) -> usize {
+        self.inner.position
+    }
+
+    #[getter]
+    fn len(&self) -> usize {
+        self.inner.len
+    }
+
+    /// `"mixed_digits"`, `"unbalanced_bracket"`, or `"all_caps_skipped"`.
+    #[getter]
+    fn kind(&self) -> &'static str {
+        self.inner.kind.name()
+    }
+
+    #[getter]
+    fn message(&self) -> &str {
+        &self.inner.message
+    }
+
+    #[getter]
+    fn context(&self) -> &str {
+        &self.inner.context
+    }
+
+    #[getter]
+    fn stage(&self) -> Option<&str> {
+        self.stage.as_deref()
+    }
+
+    /// The warning as a dict; the `stage` key only for pipeline warnings.
+    pub(crate) fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("position", self.inner.position)?;
+        dict.set_item("len", self.inner.len)?;
+        dict.set_item("kind", self.inner.kind.name())?;
+        dict.set_item("message", &self.inner.message)?;
+        dict.set_item("context", &self.inner.context)?;
+        if let Some(stage) = &self.stage {
+            dict.set_item("stage", stage)?;
+        }
+        Ok(dict)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "NormalizerWarning(position={}, kind={:?}, message={:?})",
+            self.inner.position,
+            self.inner.kind.name(),
+            self.inner.message
+        )
+    }
+}
+
+/// Warnings as a Python list of [`PyWarning`]s.
+#[cfg(feature = "pyo3-backend")]
+pub(crate) fn to_py_list<'py>(py: Python<'py>, warnings: &[Warning]) -> PyResult<Bound<'py, PyList>> {
+    let list = PyList::empty(py);
+    for warning in warnings {
+        list.append(PyWarning { inner: warning.clone(), stage: None })?;
+    }
+    Ok(list)
+}
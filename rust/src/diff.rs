@@ -0,0 +1,454 @@
+//! Diffs between a text and its normalized form, for reviewing proposed
+//! changes before writing them.
+//!
+//! [`unified`] gives a line-level unified diff in the format of `diff -u`,
+//! which code review tools and `patch` read. [`words`] marks the changed
+//! words of each changed line, in the `[-old-]{+new+}` notation of
+//! `git diff --word-diff=plain`, which is easier to read when a
+//! normalization touches a few letters in long lines.
+//!
+//! Both align their tokens by patience diff: tokens that occur exactly once
+//! on each side anchor the alignment, and the gaps between anchors are
+//! aligned by longest common subsequence when they are small enough.
+
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// Gaps at most this many token pairs (`old × new`) are aligned by longest
+/// common subsequence; larger gaps without unique anchors count as replaced
+/// wholesale.
+const LCS_CELLS: usize = 1 << 20;
+
+/// The index pairs of equal tokens of `old` and `new` in an alignment of
+/// the two, in order.
+fn matches<T: AsRef<str>>(old: &[T], new: &[T]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    align(old, new, 0..old.len(), 0..new.len(), &mut pairs);
+    pairs
+}
+
+fn align<T: AsRef<str>>(
+    old: &[T],
+    new: &[T],
+    a: std::ops::Range<usize>,
+    b: std::ops::Range<usize>,
+    pairs: &mut Vec<(usize, usize)>,
+) {
+    let (mut a_start, mut b_start, mut a_end, mut b_end) = (a.start, b.start, a.end, b.end);
+    while a_start < a_end && b_start < b_end && old[a_start].as_ref() == new[b_start].as_ref() {
+        pairs.push((a_start, b_start));
+        a_start += 1;
+        b_start += 1;
+    }
+    let mut suffix = Vec::new();
+    while a_start < a_end && b_start < b_end && old[a_end - 1].as_ref() == new[b_end - 1].as_ref() {
+        a_end -= 1;
+        b_end -= 1;
+        suffix.push((a_end, b_end));
+    }
+    let (len_a, len_b) = (a_end - a_start, b_end - b_start);
+    if len_a > 0 && len_b > 0 {
+        if len_a.saturating_mul(len_b) <= LCS_CELLS {
+            lcs(old, new, a_start, a_end, b_start, b_end, pairs);
+        } else {
+            let anchors = unique_anchors(old, new, a_start..a_end, b_start..b_end);
+            let (mut a_pos, mut b_pos) = (a_start, b_start);
+            for &(i, j) in &anchors {
+                align(old, new, a_pos..i, b_pos..j, pairs);
+                pairs.push((i, j));
+                (a_pos, b_pos) = (i + 1, j + 1);
+            }
+            if !anchors.is_empty() {
+                align(old, new, a_pos..a_end, b_pos..b_end, pairs);
+            }
+        }
+    }
+    pairs.extend(suffix.into_iter().rev());
+}
+
+/// The longest common subsequence of `old[a_start..a_end]` and
+/// `new[b_start..b_end]`, by dynamic programming.
+fn lcs<T: AsRef<str>>(
+    old: &[T],
+    new: &[T],
+    a_start: usize,
+    a_end: usize,
+    b_start: usize,
+    b_end: usize,
+    pairs: &mut Vec<(usize, usize)>,
+) {
+    let (n, m) = (a_end - a_start, b_end - b_start);
+    // lengths[i][j]: the LCS length of the suffixes from i and j
+    let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i * (m + 1) + j] = if old[a_start + i].as_ref() == new[b_start + j].as_ref() {
+                lengths[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                lengths[(i + 1) * (m + 1) + j].max(lengths[i * (m + 1) + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[a_start + i].as_ref() == new[b_start + j].as_ref() {
+            pairs.push((a_start + i, b_start + j));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * (m + 1) + j] >= lengths[i * (m + 1) + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+}
+
+/// The tokens occurring exactly once in each range, matched to each other,
+/// reduced to their longest increasing run (patience sorting).
+fn unique_anchors<T: AsRef<str>>(
+    old: &[T],
+    new: &[T],
+    a: std::ops::Range<usize>,
+    b: std::ops::Range<usize>,
+) -> Vec<(usize, usize)> {
+    // Each token's count and last index in `old`, and its count in `new`
+    let mut counts: HashMap<&str, (usize, usize, usize)> = HashMap::new();
+    for i in a {
+        let entry = counts.entry(old[i].as_ref()).or_insert((0, i, 0));
+        entry.0 += 1;
+        entry.1 = i;
+    }
+    for j in b.clone() {
+        if let Some(entry) = counts.get_mut(new[j].as_ref()) {
+            entry.2 += 1;
+        }
+    }
+    let candidates: Vec<(usize, usize)> = b
+        .filter_map(|j| match counts.get(new[j].as_ref()) {
+            Some(&(1, i, 1)) => Some((i, j)),
+            _ => None,
+        })
+        .collect();
+    // Longest increasing subsequence of the old indices, in new order
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; candidates.len()];
+    for (k, &(i, _)) in candidates.iter().enumerate() {
+        let pile = tails.partition_point(|&t| candidates[t].0 < i);
+        previous[k] = pile.checked_sub(1).map(|p| tails[p]);
+        if pile == tails.len() {
+            tails.push(k);
+        } else {
+            tails[pile] = k;
+        }
+    }
+    let mut anchors = Vec::with_capacity(tails.len());
+    let mut k = tails.last().copied();
+    while let Some(index) = k {
+        anchors.push(candidates[index]);
+        k = previous[index];
+    }
+    anchors.reverse();
+    anchors
+}
+
+/// A unified diff of `original` and `normalized`, with `context` lines of
+/// context around each change and `from` and `to` as the file names in the
+/// header; empty if the texts are the same.
+///
+/// ```
+/// use latincy_preprocess::diff::unified;
+///
+/// let diff = unified("arma\nuirumque\ncano\n", "arma\nvirumque\ncano\n", "a/aen.txt", "b/aen.txt", 3);
+/// assert_eq!(diff, "--- a/aen.txt\n+++ b/aen.txt\n@@ -1,3 +1,3 @@\n arma\n-uirumque\n+virumque\n cano\n");
+/// ```
+pub fn unified(original: &str, normalized: &str, from: &str, to: &str, context: usize) -> String {
+    if original == normalized {
+        return String::new();
+    }
+    let old: Vec<&str> = original.split_inclusive('\n').collect();
+    let new: Vec<&str> = normalized.split_inclusive('\n').collect();
+    let mut ops: Vec<(char, usize, usize)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (mi, mj) in matches(&old, &new).into_iter().chain([(old.len(), new.len())]) {
+        ops.extend((i..mi).map(|i| ('-', i, j)));
+        ops.extend((j..mj).map(|j| ('+', mi, j)));
+        if mi < old.len() {
+            ops.push((' ', mi, mj));
+        }
+        (i, j) = (mi + 1, mj + 1);
+    }
+
+    let mut out = format!("--- {from}\n+++ {to}\n");
+    let changed: Vec<usize> = (0..ops.len()).filter(|&k| ops[k].0 != ' ').collect();
+    let mut k = 0;
+    while k < changed.len() {
+        // Changes at most 2 * context unchanged lines apart share a hunk
+        let start = changed[k].saturating_sub(context);
+        let mut last = changed[k];
+        while k + 1 < changed.len() && changed[k + 1] - last - 1 <= 2 * context {
+            k += 1;
+            last = changed[k];
+        }
+        let end = (last + context + 1).min(ops.len());
+        let hunk = &ops[start..end];
+        let old_count = hunk.iter().filter(|op| op.0 != '+').count();
+        let new_count = hunk.iter().filter(|op| op.0 != '-').count();
+        let (_, old_start, new_start) = hunk[0];
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(old_start, old_count),
+            range(new_start, new_count)
+        ));
+        for &(op, i, j) in hunk {
+            let line = if op == '+' { new[j] } else { old[i] };
+            out.push(op);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        k += 1;
+    }
+    out
+}
+
+/// A hunk range, `start,count` with 1-based lines; an empty range starts
+/// at the line before it.
+fn range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, count),
+    }
+}
+
+/// The text split into words, runs of spaces, and line breaks.
+fn word_tokens(text: &str) -> Vec<&str> {
+    let class = |c: char| match c {
+        '\n' => 2,
+        c if c.is_whitespace() => 1,
+        _ => 0,
+    };
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut previous = None;
+    for (i, c) in text.char_indices() {
+        if previous.is_some_and(|p| p != class(c) || p == 2) {
+            tokens.push(&text[start..i]);
+            start = i;
+        }
+        previous = Some(class(c));
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// The character edits that turn `old` into `new`, in order, as `(start,
+/// end, replacement)` with `start..end` a char range of `old`. Words are
+/// aligned first and the characters of each changed run of words after, so
+/// a long text with scattered changes aligns as finely as a short one.
+pub(crate) fn char_edits(old: &str, new: &str) -> Vec<(usize, usize, String)> {
+    let (old_words, new_words) = (word_tokens(old), word_tokens(new));
+    let mut edits = Vec::new();
+    let (mut i, mut j, mut pos) = (0, 0, 0);
+    for (mi, mj) in matches(&old_words, &new_words).into_iter().chain([(old_words.len(), new_words.len())]) {
+        if i < mi || j < mj {
+            let (from, to) = (old_words[i..mi].concat(), new_words[j..mj].concat());
+            let (a, b) = (char_tokens(&from), char_tokens(&to));
+            let (mut ci, mut cj) = (0, 0);
+            for (mci, mcj) in matches(&a, &b).into_iter().chain([(a.len(), b.len())]) {
+                if ci < mci || cj < mcj {
+                    edits.push((pos + ci, pos + mci, b[cj..mcj].concat()));
+                }
+                (ci, cj) = (mci + 1, mcj + 1);
+            }
+            pos += a.len();
+        }
+        if mi < old_words.len() {
+            pos += old_words[mi].chars().count();
+        }
+        (i, j) = (mi + 1, mj + 1);
+    }
+    edits
+}
+
+/// The text split into its characters.
+fn char_tokens(text: &str) -> Vec<&str> {
+    text.char_indices().map(|(i, c)| &text[i..i + c.len_utf8()]).collect()
+}
+
+/// The lines of a word diff being written.
+struct WordDiff {
+    out: String,
+    line: String,
+    /// The original line number of the current line.
+    number: usize,
+    /// Original line breaks removed within the current line.
+    joined: usize,
+    changed: bool,
+}
+
+impl WordDiff {
+    fn equal(&mut self, token: &str) {
+        if token != "\n" {
+            self.line.push_str(token);
+            return;
+        }
+        if self.changed {
+            self.out.push_str(&format!("{}: {}\n", self.number, self.line));
+        }
+        self.number += 1 + self.joined;
+        self.line.clear();
+        self.joined = 0;
+        self.changed = false;
+    }
+
+    fn change(&mut self, removed: &[&str], added: &[&str]) {
+        let (removed, added) = (removed.concat(), added.concat());
+        if !removed.is_empty() {
+            self.line.push_str(&format!("[-{}-]", removed.replace('\n', "⏎")));
+            self.joined += removed.matches('\n').count();
+        }
+        if !added.is_empty() {
+            self.line.push_str(&format!("{{+{}+}}", added.replace('\n', "⏎")));
+        }
+        self.changed = true;
+    }
+}
+
+/// The changed lines of `original`, each prefixed with its line number and
+/// with its changes marked as `[-removed-]{+added+}`; empty if the texts are
+/// the same. Line breaks removed or added are marked as `⏎`.
+///
+/// ```
+/// use latincy_preprocess::diff::words;
+///
+/// let diff = words("arma\nuirumque cano\n", "arma\nvirumque cano\n");
+/// assert_eq!(diff, "2: [-uirumque-]{+virumque+} cano\n");
+/// ```
+pub fn words(original: &str, normalized: &str) -> String {
+    if original == normalized {
+        return String::new();
+    }
+    let old = word_tokens(original);
+    let new = word_tokens(normalized);
+    let mut diff = WordDiff {
+        out: String::new(),
+        line: String::new(),
+        number: 1,
+        joined: 0,
+        changed: false,
+    };
+    let (mut i, mut j) = (0, 0);
+    for (mi, mj) in matches(&old, &new).into_iter().chain([(old.len(), new.len())]) {
+        if i < mi || j < mj {
+            diff.change(&old[i..mi], &new[j..mj]);
+        }
+        if mi < old.len() {
+            diff.equal(old[mi]);
+        }
+        (i, j) = (mi + 1, mj + 1);
+    }
+    if diff.changed {
+        diff.equal("\n");
+    }
+    diff.out
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "unified_diff", signature = (original, normalized, fromfile="original", tofile="normalized", context=3))]
+pub fn py_unified_diff(
+    py: Python<'_>,
+    original: &str,
+    normalized: &str,
+    fromfile: &str,
+    tofile: &str,
+    context: usize,
+) -> String {
+    crate::allow_threads(py, original, || unified(original, normalized, fromfile, tofile, context))
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "word_diff")]
+pub fn py_word_diff(py: Python<'_>, original: &str, normalized: &str) -> String {
+    crate::allow_threads(py, original, || words(original, normalized))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_hunks() {
+        let original: String = (1..=20).map(|n| format!("line {n}\n")).collect();
+        let normalized = original.replace("line 2\n", "LINE 2\n").replace("line 18\n", "");
+        let diff = unified(&original, &normalized, "a", "b", 2);
+        let hunks: Vec<&str> = diff.lines().filter(|line| line.starts_with("@@")).collect();
+        assert_eq!(hunks, ["@@ -1,4 +1,4 @@", "@@ -16,5 +16,4 @@"]);
+        assert!(diff.contains("-line 2\n+LINE 2\n"));
+        assert!(diff.contains(" line 17\n-line 18\n line 19\n"));
+        // Nearby changes share a hunk
+        let diff = unified(&original, &normalized, "a", "b", 8);
+        assert_eq!(diff.lines().filter(|line| line.starts_with("@@")).count(), 1);
+        assert_eq!(unified("same", "same", "a", "b", 3), "");
+    }
+
+    #[test]
+    fn test_unified_edges() {
+        assert_eq!(unified("a\n", "a\nb\n", "x", "y", 0), "--- x\n+++ y\n@@ -1,0 +2 @@\n+b\n");
+        assert_eq!(unified("", "a\n", "x", "y", 3), "--- x\n+++ y\n@@ -0,0 +1 @@\n+a\n");
+        assert_eq!(
+            unified("uia", "via", "x", "y", 3),
+            "--- x\n+++ y\n@@ -1 +1 @@\n-uia\n\\ No newline at end of file\n+via\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn test_large_inputs() {
+        // Too large for the LCS table: aligned on the unique lines
+        let original: String = (0..2000).map(|n| format!("uersus {n}\n")).collect();
+        let normalized = original.replace("uersus", "versus");
+        let diff = unified(&original, &normalized, "a", "b", 0);
+        assert_eq!(diff.lines().filter(|line| line.starts_with('-')).count(), 2001);
+        let original = original + "finis\n";
+        let normalized = normalized + "finis\n";
+        assert!(unified(&original, &normalized, "a", "b", 1).ends_with("+versus 1999\n finis\n"));
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(words("uia est\nsic\n", "via est\nsic\n"), "1: [-uia-]{+via+} est\n");
+        // Removed and added line breaks are marked
+        assert_eq!(words("uir-\ntus est\nsic\n", "uirtus est\nsic\n"), "1: [-uir-⏎tus-]{+uirtus+} est\n");
+        assert_eq!(words("a\nb\nuia\n", "a\nb\nvia\n"), "3: [-uia-]{+via+}\n");
+        assert_eq!(words("uia", "via"), "1: [-uia-]{+via+}\n");
+        assert_eq!(words("sic", "sic"), "");
+    }
+
+    #[test]
+    fn test_char_edits() {
+        let edit = |start, end, replacement: &str| (start, end, replacement.to_string());
+        assert_eq!(char_edits("uia est uuae", "via est uvae"), [edit(0, 1, "v"), edit(9, 10, "v")]);
+        assert_eq!(char_edits("ﬁlius ſum", "filius sum"), [edit(0, 1, "fi"), edit(6, 7, "s")]);
+        assert_eq!(char_edits("uir-\ntus", "uirtus"), [edit(3, 5, "")]);
+        assert_eq!(char_edits("ius", "ius"), []);
+        // Far apart in a long text, the edits are still single characters
+        let original: String = (0..3000).map(|n| format!("uersus {n}\n")).collect();
+        let edits = char_edits(&original, &original.replace("uersus", "versus"));
+        assert_eq!(edits.len(), 3000);
+        assert!(edits.iter().all(|(start, end, replacement)| end - start == 1 && replacement == "v"));
+    }
+}
@@ -0,0 +1,419 @@
+//! Normalization between the diphthong spellings *ae*/*oe* and the late and
+//! medieval *e*.
+//!
+//! [`flatten`] writes every diphthong as *e* (*caelum* → *celum*), for
+//! comparing texts across periods. [`DiphthongRestorer`] goes the other way,
+//! which is ambiguous: *celum* is *caelum*, but *cepit* is a word of its own.
+//! It picks the most frequent attested reading in a [`Lexicon`] and can back
+//! off to the n-gram model for words the lexicon does not know.
+
+use crate::changes::{self, ChangeRecord};
+use crate::lexicon::Lexicon;
+use crate::names::NameGuard;
+use crate::ngrams::{self, NgramData};
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use crate::names::PyNameGuard;
+#[cfg(feature = "pyo3-backend")]
+use crate::ngrams::PyNgramData;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use std::sync::Arc;
+
+/// Most *e*s restored in one word; every combination of readings is scored,
+/// so longer words are left unchanged.
+pub const MAX_CHOICES: usize = 4;
+
+/// Write the diphthongs *ae* and *oe* (and the ligatures `æ`, `œ`) as *e*,
+/// keeping case. Words that begin with a hiatus (*aer*, *poeta*) are left
+/// alone.
+///
+/// ```
+/// use latincy_preprocess::diphthongs::flatten;
+///
+/// assert_eq!(flatten("Caesar poenas caeli dedit"), "Cesar penas celi dedit");
+/// ```
+pub fn flatten(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len());
+    let mut word_start = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if !c.is_alphabetic() {
+            word_start = i + 1;
+        }
+        match (c, chars.get(i + 1)) {
+            ('æ' | 'œ', _) => result.push('e'),
+            ('Æ' | 'Œ', _) => result.push('E'),
+            ('a' | 'o' | 'A' | 'O', Some(&next @ ('e' | 'E')))
+                if !crate::ligatures::is_hiatus(&chars[word_start..], i - word_start) =>
+            {
+                // The case of the first vowel wins (Aeneas → Eneas)
+                result.push(if c.is_uppercase() { 'E' } else { next });
+                i += 1;
+            }
+            _ => result.push(c),
+        }
+        i += 1;
+    }
+    result
+}
+
+/// Positions of an *e* that could stand for *ae* or *oe*: not already part
+/// of a diphthong.
+fn e_positions(word: &[char]) -> Vec<usize> {
+    (0..word.len()).filter(|&i| word[i] == 'e' && (i == 0 || !matches!(word[i - 1], 'a' | 'o'))).collect()
+}
+
+/// The lowercase `word` with `readings[k]` (`None`, `'a'`, or `'o'`)
+/// inserted before the *e* at `positions[k]`.
+fn spell(word: &[char], positions: &[usize], readings: &[Option<char>]) -> String {
+    let mut result = String::with_capacity(word.len() + positions.len());
+    let mut k = 0;
+    for (i, &c) in word.iter().enumerate() {
+        if positions.get(k) == Some(&i) {
+            result.extend(readings[k]);
+            k += 1;
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// Restores *ae* and *oe* where late and medieval spelling writes *e*.
+///
+/// ```
+/// use latincy_preprocess::diphthongs::DiphthongRestorer;
+/// use latincy_preprocess::lexicon::Lexicon;
+/// use std::sync::Arc;
+///
+/// let lexicon = Lexicon::from_counts([("caelum", 40), ("cepit", 25), ("poena", 10)]);
+/// let restorer = DiphthongRestorer::new(Arc::new(lexicon));
+/// assert_eq!(restorer.restore("celum cepit pena"), "caelum cepit poena");
+/// ```
+#[derive(Debug, Clone)]
+pub struct DiphthongRestorer {
+    lexicon: Arc<Lexicon>,
+    ngrams: Option<Arc<NgramData>>,
+    backoff: bool,
+    threshold: f64,
+    /// Words restored only to attested readings, never by back-off.
+    names: Option<NameGuard>,
+}
+
+impl DiphthongRestorer {
+    /// Restore diphthongs attested in `lexicon`, without n-gram back-off.
+    pub fn new(lexicon: Arc<Lexicon>) -> Self {
+        DiphthongRestorer {
+            lexicon,
+            ngrams: None,
+            backoff: false,
+            threshold: 1.0,
+            names: Some(NameGuard::new()),
+        }
+    }
+
+    /// For words the lexicon does not know in any reading, choose the
+    /// reading the n-gram model prefers (off by default).
+    pub fn backoff(mut self, backoff: bool) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Back off to `ngrams` instead of the bundled model.
+    pub fn ngrams(mut self, ngrams: Arc<NgramData>) -> Self {
+        self.ngrams = Some(ngrams);
+        self
+    }
+
+    /// The probability ratio a restored reading must beat the word as
+    /// written by when backing off (default 1.0).
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Which words count as proper names, which never back off (default
+    /// [`NameGuard::new`]; `None` lets every word back off).
+    pub fn name_guard(mut self, guard: Option<NameGuard>) -> Self {
+        self.names = guard;
+        self
+    }
+
+    fn model(&self) -> Option<&NgramData> {
+        match &self.ngrams {
+            Some(ngrams) => Some(ngrams),
+            None => ngrams::bundled().ok(),
+        }
+    }
+
+    /// The vowel to insert before each of `positions` in a lowercase word;
+    /// a `name` is not backed off.
+    fn readings(&self, word: &[char], positions: &[usize], name: bool) -> Vec<Option<char>> {
+        const OPTIONS: [Option<char>; 3] = [None, Some('a'), Some('o')];
+        let mut choice = vec![0usize; positions.len()];
+        let mut candidates = Vec::new();
+        loop {
+            let readings: Vec<Option<char>> = choice.iter().map(|&c| OPTIONS[c]).collect();
+            candidates.push((spell(word, positions, &readings), readings));
+            let Some(k) = (0..positions.len()).find(|&k| choice[k] + 1 < OPTIONS.len()) else {
+                break;
+            };
+            choice[k] += 1;
+            choice[..k].iter_mut().for_each(|c| *c = 0);
+        }
+
+        // The most frequent attested reading; the word as written wins ties
+        let mut best: Option<(u64, &Vec<Option<char>>)> = None;
+        for (spelled, readings) in &candidates {
+            let frequency = self.lexicon.frequency(spelled);
+            if frequency > 0 && best.is_none_or(|(best, _)| frequency > best) {
+                best = Some((frequency, readings));
+            }
+        }
+        if let Some((_, readings)) = best {
+            return readings.clone();
+        }
+
+        let unchanged = candidates[0].1.clone();
+        let Some(model) = self.model().filter(|_| self.backoff && !name) else {
+            return unchanged;
+        };
+        let original = model.log_prob(&candidates[0].0);
+        let (score, readings) = candidates[1..]
+            .iter()
+            .map(|(spelled, readings)| (model.log_prob(spelled), readings))
+            .fold((f64::NEG_INFINITY, &unchanged), |best, next| if next.0 > best.0 { next } else { best });
+        if self.threshold <= 0.0 || score - original > self.threshold.ln() + 1e-9 {
+            readings.clone()
+        } else {
+            unchanged
+        }
+    }
+
+    /// Restore the diphthongs in `text`.
+    pub fn restore(&self, text: &str) -> String {
+        self.restore_detailed(text).normalized
+    }
+
+    /// Like [`DiphthongRestorer::restore`], recording one [`ChangeRecord`]
+    /// per restored diphthong.
+    pub fn restore_detailed(&self, text: &str) -> DetailedResult {
+        let chars: Vec<char> = text.chars().collect();
+        let mut normalized = String::with_capacity(text.len() + 8);
+        let mut changes = Vec::new();
+
+        let mut i = 0;
+        while i < chars.len() {
+            if !chars[i].is_alphabetic() {
+                normalized.push(chars[i]);
+                i += 1;
+                continue;
+            }
+            let end = chars[i..].iter().position(|c| !c.is_alphabetic()).map_or(chars.len(), |p| i + p);
+            let original = &chars[i..end];
+            let lower: Vec<char> = original.iter().flat_map(|c| c.to_lowercase()).collect();
+            let positions = if lower.len() == original.len() { e_positions(&lower) } else { Vec::new() };
+            if positions.is_empty() || positions.len() > MAX_CHOICES {
+                normalized.extend(original);
+                i = end;
+                continue;
+            }
+
+            let name = self.backoff
+                && self.names.as_ref().is_some_and(|guard| {
+                    guard.protects(&original.iter().collect::<String>(), &chars[..i])
+                });
+            let readings = self.readings(&lower, &positions, name);
+            let upper = original.len() > 1 && original.iter().all(|c| !c.is_lowercase());
+            let mut k = 0;
+            for (j, &c) in original.iter().enumerate() {
+                match (positions.get(k), readings.get(k)) {
+                    (Some(&at), Some(&reading)) if at == j => {
+                        k += 1;
+                        let Some(vowel) = reading else {
+                            normalized.push(c);
+                            continue;
+                        };
+                        // Ecce → Aecce, ECCE → AECCE: the inserted vowel
+                        // takes the capital
+                        let diphthong = match (c.is_uppercase(), upper) {
+                            (true, true) => format!("{}{}", vowel.to_ascii_uppercase(), c),
+                            (true, false) => format!("{}e", vowel.to_ascii_uppercase()),
+                            (false, _) => format!("{}{}", vowel, c),
+                        };
+                        let position = i + j;
+                        changes.push(ChangeRecord {
+                            position,
+                            original: c.to_string(),
+                            rule: format!("e → {}e", vowel),
+                            normalized: diphthong.clone(),
+                            pass: 1,
+                            context: changes::context(&chars, position, 1),
+                        });
+                        normalized.push_str(&diphthong);
+                    }
+                    _ => normalized.push(c),
+                }
+            }
+            i = end;
+        }
+
+        DetailedResult {
+            original: text.to_string(),
+            normalized,
+            changes,
+        }
+    }
+}
+
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<ChangeRecord>,
+}
+
+impl Normalizer for DiphthongRestorer {
+    fn name(&self) -> &str {
+        "diphthongs"
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = self.restore_detailed(text);
+        NormalizeOutput::from_changes(result.original, result.normalized, result.changes)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "flatten_diphthongs")]
+pub fn py_flatten_diphthongs(py: Python<'_>, text: &str) -> String {
+    crate::allow_threads(py, text, || flatten(text))
+}
+
+/// Python view of [`DiphthongRestorer`], exposed as
+/// `_rust.DiphthongRestorer`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "DiphthongRestorer", frozen)]
+pub struct PyDiphthongRestorer {
+    pub(crate) inner: DiphthongRestorer,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyDiphthongRestorer {
+    #[new]
+    #[pyo3(signature = (lexicon, backoff=false, ngrams=None, threshold=1.0, protect_names=true, name_guard=None))]
+    fn new(
+        lexicon: std::path::PathBuf,
+        backoff: bool,
+        ngrams: Option<PyRef<'_, PyNgramData>>,
+        threshold: f64,
+        protect_names: bool,
+        name_guard: Option<PyRef<'_, PyNameGuard>>,
+    ) -> PyResult<Self> {
+        let mut inner = DiphthongRestorer::new(Arc::new(Lexicon::from_file(lexicon)?))
+            .backoff(backoff)
+            .threshold(threshold)
+            .name_guard(crate::names::from_py(protect_names, name_guard));
+        if let Some(ngrams) = ngrams {
+            inner = inner.ngrams(Arc::clone(&ngrams.inner));
+        }
+        Ok(PyDiphthongRestorer { inner })
+    }
+
+    fn restore(&self, py: Python<'_>, text: &str) -> String {
+        crate::allow_threads(py, text, || self.inner.restore(text))
+    }
+
+    fn restore_detailed(&self, py: Python<'_>, text: &str) -> PyResult<changes::PyDetailedResult> {
+        let result = crate::allow_threads(py, text, || self.inner.restore_detailed(text));
+        changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn restorer() -> DiphthongRestorer {
+        DiphthongRestorer::new(Arc::new(Lexicon::from_counts([
+            ("caelum", 40),
+            ("celeriter", 5),
+            ("cepit", 25),
+            ("caepit", 1),
+            ("poena", 10),
+            ("aequus", 8),
+            ("haec", 60),
+        ])))
+    }
+
+    #[test]
+    fn test_flatten() {
+        assert_eq!(flatten("caelum poena haec"), "celum pena hec");
+        assert_eq!(flatten("Aeneas CAESAR Œdipus cæli"), "Eneas CESAR Edipus celi");
+        // Hiatus words keep both vowels
+        assert_eq!(flatten("aer poeta"), "aer poeta");
+        assert_eq!(flatten("rosa"), "rosa");
+    }
+
+    #[test]
+    fn test_restore() {
+        let restorer = restorer();
+        assert_eq!(restorer.restore("celum pena hec equus"), "caelum poena haec aequus");
+        // The more frequent attested reading wins
+        assert_eq!(restorer.restore("cepit celeriter"), "cepit celeriter");
+        // Unknown words are left alone without back-off
+        assert_eq!(restorer.restore("fenestra"), "fenestra");
+        assert_eq!(restorer.restore(&flatten("caelum poena")), "caelum poena");
+    }
+
+    #[test]
+    fn test_restore_case() {
+        let restorer = restorer();
+        assert_eq!(restorer.restore("Equus CELUM Pena."), "Aequus CAELUM Poena.");
+    }
+
+    #[test]
+    fn test_backoff() {
+        let ngrams = Arc::new(crate::ngrams::train_ngrams(["praeda praemium praeter praesens"]));
+        let restorer = restorer().ngrams(ngrams);
+        assert_eq!(restorer.restore("presto"), "presto");
+        let restorer = restorer.backoff(true);
+        assert_eq!(restorer.restore("presto"), "praesto");
+        assert_eq!(restorer.restore("cepit"), "cepit");
+    }
+
+    #[test]
+    fn test_names_do_not_back_off() {
+        let ngrams = Arc::new(crate::ngrams::train_ngrams(["praeda praemium praeter praesens"]));
+        let guard = NameGuard::new().names(Arc::new(Lexicon::from_words(["prestus"]))).capitalized(true);
+        let restorer = restorer().ngrams(ngrams).backoff(true).name_guard(Some(guard));
+        assert_eq!(restorer.restore("Prestus presto"), "Prestus praesto");
+        assert_eq!(restorer.restore("dixit Presto"), "dixit Presto");
+        // Attested readings are still restored
+        assert_eq!(restorer.restore("in Celum"), "in Caelum");
+        assert_eq!(restorer.name_guard(None).restore("Prestus"), "Praestus");
+    }
+
+    #[test]
+    fn test_restore_detailed() {
+        let result = restorer().restore_detailed("de celo pena");
+        assert_eq!(result.normalized, "de celo poena");
+        assert_eq!(result.changes.len(), 1);
+        assert_eq!(result.changes[0].position, 9);
+        assert_eq!(result.changes[0].original, "e");
+        assert_eq!(result.changes[0].normalized, "oe");
+        assert_eq!(result.changes[0].rule, "e → oe");
+        assert_eq!(result.changes[0].context, "o p[e]na");
+    }
+}
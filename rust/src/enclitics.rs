@@ -0,0 +1,440 @@
+//! Splitting Latin enclitics from their host words.
+//!
+//! Latin writes *-que* (and), *-ne* (the question particle), *-ve* (or), and
+//! the postposed *cum* of *mecum* as one word with the word they lean on,
+//! and a tagger reads *virumque* better as *virum* + *que*.
+//! [`EncliticSplitter`] finds them by ending, with one toggle per
+//! [`Enclitic`], and keeps the many words that only look like compounds:
+//!
+//! - *-que* is never split from the words of [`QUE_EXCEPTIONS`] (*quoque*,
+//!   *neque*, *itaque*, *quinque*), from the forms of *quisque*, *uterque*,
+//!   and *plerique*, or from *-cumque* relatives (*quicumque*),
+//! - *-ne* and *-ve* are only split after a consonant that no simple word
+//!   ends in before them (*videsne*, *estne*, *plusve*, *quidve*), or, for
+//!   *-ne*, from a few pronouns and particles (*egone*, *tune*, *itane*), so
+//!   *bene*, *homine*, *nonne*, *breve*, and *salve* stay whole,
+//! - *cum* is split only from the pronouns it follows (*mecum*, *nobiscum*,
+//!   *quibuscum*).
+//!
+//! Words match in either case and with *u* or *v* (*ARMAQVE*, *plusue*).
+
+use crate::changes::{self, ChangeRecord};
+use crate::lexicon::Lexicon;
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use std::sync::Arc;
+
+/// An enclitic that [`EncliticSplitter`] can split off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Enclitic {
+    /// *-que*: *virumque*, *senatus populusque*.
+    Que,
+    /// *-ne*: *videsne*, *egone*.
+    Ne,
+    /// *-ve*, also written *-ue*: *plusve*, *quidue*.
+    Ve,
+    /// *cum* after a pronoun: *mecum*, *quibuscum*.
+    Cum,
+}
+
+impl Enclitic {
+    pub const ALL: [Enclitic; 4] = [Enclitic::Que, Enclitic::Ne, Enclitic::Ve, Enclitic::Cum];
+
+    /// The enclitic's name, as used by the Python bindings.
+    pub fn name(self) -> &'static str {
+        match self {
+            Enclitic::Que => "que",
+            Enclitic::Ne => "ne",
+            Enclitic::Ve => "ve",
+            Enclitic::Cum => "cum",
+        }
+    }
+}
+
+/// Words ending in *-que* that are not a host and *-que*. Keys are
+/// lowercase with *u* for *v*.
+pub const QUE_EXCEPTIONS: &[&str] = &[
+    "atque", "neque", "itaque", "denique", "namque", "quoque", "usque", "absque", "abusque", "adusque",
+    "quousque", "hucusque", "eousque", "usquequaque", "undique", "ubique", "utique", "quandoque", "plerumque",
+    "utrimque", "utrinque", "susque", "deque", "quinque", "aeque", "peraeque", "inique", "antique", "oblique",
+    "torque", "contorque", "detorque", "extorque", "coque", "concoque", "decoque", "linque", "relinque",
+    "derelinque",
+];
+
+/// The forms of *quisque*, *uterque*, and *plerique*, which are not split
+/// at the end of any word (*unusquisque*, *unumquemque*).
+const PRONOUN_FORMS: &[&str] = &[
+    "quisque", "quaeque", "quodque", "quidque", "quicque", "cuiusque", "cuique", "quemque", "quamque", "quaque",
+    "quique", "quorumque", "quarumque", "quibusque", "quosque", "quasque", "uterque", "utraque", "utrumque",
+    "utriusque", "utrique", "utramque", "utroque", "utraeque", "utrorumque", "utrarumque", "utrisque",
+    "utrosque", "utrasque", "plerique", "pleraeque", "pleraque", "plerorumque", "plerarumque", "plerisque",
+    "plerosque", "plerasque",
+];
+
+/// Vowel-final words that take *-ne*.
+const NE_HOSTS: &[&str] = &["ego", "tu", "me", "ita", "adeo", "ideo", "quo"];
+
+/// Words ending in *-ue* after a consonant of [`VE_AFTER`] that are not a
+/// host and *-ve*: imperatives of *-uere* verbs.
+const VE_EXCEPTIONS: &[&str] = &[
+    "consue", "insue", "assue", "dissue", "resue", "statue", "constitue", "restitue", "destitue", "institue",
+    "substitue", "prostitue", "indue", "acue", "exacue", "peracue",
+];
+
+/// The letters after which *-ne* is split.
+const NE_AFTER: &[char] = &['s', 't', 'c'];
+
+/// The letters after which *-ve* is split.
+const VE_AFTER: &[char] = &['s', 't', 'm', 'd', 'c'];
+
+/// The pronouns that take a postposed *cum*.
+const CUM_HOSTS: &[&str] = &["me", "te", "se", "nobis", "uobis", "quo", "qua", "qui", "quibus"];
+
+/// Letters a host must have before *-que*, *-ne*, or *-ve*.
+const MIN_HOST: usize = 2;
+
+/// Splits enclitics from their host words.
+///
+/// ```
+/// use latincy_preprocess::enclitics::{Enclitic, EncliticSplitter};
+///
+/// let splitter = EncliticSplitter::new();
+/// assert_eq!(splitter.split_text("arma virumque cano"), "arma virum que cano");
+/// assert_eq!(splitter.split_text("itaque mecum"), "itaque me cum");
+/// let splitter = splitter.enclitic(Enclitic::Cum, false).separator(" -");
+/// assert_eq!(splitter.split_text("itaque mecum venitne"), "itaque mecum venit -ne");
+/// ```
+#[derive(Debug, Clone)]
+pub struct EncliticSplitter {
+    enclitics: Vec<Enclitic>,
+    lexicon: Option<Arc<Lexicon>>,
+    separator: String,
+}
+
+impl Default for EncliticSplitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EncliticSplitter {
+    /// Split every enclitic, without a lexicon, separating with a space.
+    pub fn new() -> Self {
+        EncliticSplitter {
+            enclitics: Enclitic::ALL.to_vec(),
+            lexicon: None,
+            separator: " ".to_string(),
+        }
+    }
+
+    /// Split `enclitic`, or leave it.
+    pub fn enclitic(mut self, enclitic: Enclitic, enabled: bool) -> Self {
+        self.enclitics.retain(|&e| e != enclitic);
+        if enabled {
+            self.enclitics.push(enclitic);
+        }
+        self
+    }
+
+    /// Only split a word whose host `lexicon` attests.
+    pub fn lexicon(mut self, lexicon: Arc<Lexicon>) -> Self {
+        self.lexicon = Some(lexicon);
+        self
+    }
+
+    /// What [`EncliticSplitter::split_text`] puts between host and enclitic.
+    pub fn separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    pub fn is_enabled(&self, enclitic: Enclitic) -> bool {
+        self.enclitics.contains(&enclitic)
+    }
+
+    /// The enabled enclitics, in the order of [`Enclitic::ALL`].
+    pub fn enclitics(&self) -> impl Iterator<Item = Enclitic> + '_ {
+        Enclitic::ALL.into_iter().filter(|&enclitic| self.is_enabled(enclitic))
+    }
+
+    /// The enclitic at the end of the word `chars` and the length of its
+    /// host.
+    fn find(&self, chars: &[char]) -> Option<(Enclitic, usize)> {
+        let key: String = chars.iter().map(|&c| fold(c)).collect();
+        let (enclitic, len) = if key.ends_with("que") {
+            let compound = QUE_EXCEPTIONS.contains(&key.as_str())
+                || PRONOUN_FORMS.iter().any(|form| key.ends_with(form))
+                || key.ends_with("cumque")
+                || key.ends_with("cunque");
+            (!compound).then_some((Enclitic::Que, 3))?
+        } else if key.ends_with("cum") {
+            CUM_HOSTS.contains(&&key[..key.len() - 3]).then_some((Enclitic::Cum, 3))?
+        } else if let Some(host) = key.strip_suffix("ne") {
+            let after_consonant = host.chars().last().is_some_and(|c| NE_AFTER.contains(&c));
+            (after_consonant || NE_HOSTS.contains(&host)).then_some((Enclitic::Ne, 2))?
+        } else if let Some(host) = key.strip_suffix("ue") {
+            let after_consonant = host.chars().last().is_some_and(|c| VE_AFTER.contains(&c));
+            (after_consonant && !VE_EXCEPTIONS.contains(&key.as_str())).then_some((Enclitic::Ve, 2))?
+        } else {
+            return None;
+        };
+        let host = chars.len() - len;
+        if !self.is_enabled(enclitic) || (enclitic != Enclitic::Cum && host < MIN_HOST) {
+            return None;
+        }
+        if let Some(lexicon) = &self.lexicon {
+            let lower: String = chars[..host].iter().map(|&c| c.to_lowercase().next().unwrap_or(c)).collect();
+            let folded: String = key.chars().take(host).collect();
+            if !lexicon.contains(&lower) && !lexicon.contains(&folded) {
+                return None;
+            }
+        }
+        Some((enclitic, host))
+    }
+
+    /// The host and enclitic of `word`, as written, or `None` if it has no
+    /// enclitic.
+    pub fn split(&self, word: &str) -> Option<(String, String)> {
+        let chars: Vec<char> = word.chars().collect();
+        let (_, host) = self.find(&chars)?;
+        Some((chars[..host].iter().collect(), chars[host..].iter().collect()))
+    }
+
+    /// The char offsets in `text` at which an enclitic begins.
+    pub fn boundaries(&self, text: &str) -> Vec<usize> {
+        let chars: Vec<char> = text.chars().collect();
+        self.splits(&chars).into_iter().map(|(boundary, _)| boundary).collect()
+    }
+
+    /// Each enclitic of `chars` with the offset at which it begins.
+    fn splits(&self, chars: &[char]) -> Vec<(usize, Enclitic)> {
+        let mut splits = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            if !chars[i].is_alphabetic() {
+                i += 1;
+                continue;
+            }
+            let end = chars[i..].iter().position(|c| !c.is_alphabetic()).map_or(chars.len(), |p| i + p);
+            if let Some((enclitic, host)) = self.find(&chars[i..end]) {
+                splits.push((i + host, enclitic));
+            }
+            i = end;
+        }
+        splits
+    }
+
+    /// `text` with the separator inserted before each enclitic.
+    pub fn split_text(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut result = String::with_capacity(text.len());
+        let mut last = 0;
+        for boundary in self.boundaries(text) {
+            result.extend(&chars[last..boundary]);
+            result.push_str(&self.separator);
+            last = boundary;
+        }
+        result.extend(&chars[last..]);
+        result
+    }
+}
+
+/// `c` lowercased, with *u* for *v* and *i* for *j*.
+fn fold(c: char) -> char {
+    match c.to_lowercase().next().unwrap_or(c) {
+        'v' => 'u',
+        'j' => 'i',
+        lower => lower,
+    }
+}
+
+/// Split the enclitic from `word` with the default [`EncliticSplitter`].
+///
+/// ```
+/// use latincy_preprocess::enclitics::split;
+///
+/// assert_eq!(split("populusque"), Some(("populus".to_string(), "que".to_string())));
+/// assert_eq!(split("quoque"), None);
+/// ```
+pub fn split(word: &str) -> Option<(String, String)> {
+    EncliticSplitter::new().split(word)
+}
+
+/// Insert a space before every enclitic of `text`.
+pub fn split_text(text: &str) -> String {
+    EncliticSplitter::new().split_text(text)
+}
+
+impl Normalizer for EncliticSplitter {
+    fn name(&self) -> &str {
+        "enclitics"
+    }
+
+    /// One [`ChangeRecord`] per inserted separator, with the rule `"-que"`,
+    /// `"-ne"`, `"-ve"`, or `"-cum"`.
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let chars: Vec<char> = text.chars().collect();
+        let changes = self
+            .splits(&chars)
+            .into_iter()
+            .map(|(position, enclitic)| ChangeRecord {
+                position,
+                original: String::new(),
+                normalized: self.separator.clone(),
+                pass: 1,
+                rule: format!("-{}", enclitic.name()),
+                context: changes::context(&chars, position, 0),
+            })
+            .collect();
+        NormalizeOutput::from_changes(text.to_string(), self.split_text(text), changes)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "split_enclitic")]
+pub fn py_split_enclitic(word: &str) -> Option<(String, String)> {
+    split(word)
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "split_enclitics", signature = (text, separator=" "))]
+pub fn py_split_enclitics(py: Python<'_>, text: &str, separator: &str) -> String {
+    crate::allow_threads(py, text, || EncliticSplitter::new().separator(separator).split_text(text))
+}
+
+/// Python view of [`EncliticSplitter`], exposed as `_rust.EncliticSplitter`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "EncliticSplitter", frozen)]
+pub struct PyEncliticSplitter {
+    pub(crate) inner: EncliticSplitter,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyEncliticSplitter {
+    #[new]
+    #[pyo3(signature = (que=true, ne=true, ve=true, cum=true, lexicon=None, separator=" "))]
+    fn new(
+        que: bool,
+        ne: bool,
+        ve: bool,
+        cum: bool,
+        lexicon: Option<std::path::PathBuf>,
+        separator: &str,
+    ) -> PyResult<Self> {
+        let mut inner = EncliticSplitter::new()
+            .enclitic(Enclitic::Que, que)
+            .enclitic(Enclitic::Ne, ne)
+            .enclitic(Enclitic::Ve, ve)
+            .enclitic(Enclitic::Cum, cum)
+            .separator(separator);
+        if let Some(path) = lexicon {
+            inner = inner.lexicon(Arc::new(Lexicon::from_file(path)?));
+        }
+        Ok(PyEncliticSplitter { inner })
+    }
+
+    /// Names of the enabled enclitics.
+    #[getter]
+    fn enclitics(&self) -> Vec<&'static str> {
+        self.inner.enclitics().map(Enclitic::name).collect()
+    }
+
+    fn split(&self, word: &str) -> Option<(String, String)> {
+        self.inner.split(word)
+    }
+
+    fn split_text(&self, py: Python<'_>, text: &str) -> String {
+        crate::allow_threads(py, text, || self.inner.split_text(text))
+    }
+
+    fn boundaries(&self, py: Python<'_>, text: &str) -> Vec<usize> {
+        crate::allow_threads(py, text, || self.inner.boundaries(text))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("EncliticSplitter(enclitics={:?})", self.enclitics())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(host: &str, enclitic: &str) -> Option<(String, String)> {
+        Some((host.to_string(), enclitic.to_string()))
+    }
+
+    #[test]
+    fn test_que() {
+        assert_eq!(split("virumque"), pair("virum", "que"));
+        assert_eq!(split("ARMAQVE"), pair("ARMA", "QVE"));
+        let compounds =
+            ["quoque", "neque", "itaque", "atque", "quinque", "quisque", "unusquisque", "utrumque", "quicumque"];
+        for word in compounds {
+            assert_eq!(split(word), None, "{word}");
+        }
+        assert_eq!(split("que"), None);
+    }
+
+    #[test]
+    fn test_ne() {
+        assert_eq!(split("videsne"), pair("vides", "ne"));
+        assert_eq!(split("estne"), pair("est", "ne"));
+        assert_eq!(split("egone"), pair("ego", "ne"));
+        for word in ["bene", "homine", "ratione", "nonne", "omne", "sine", "tene"] {
+            assert_eq!(split(word), None, "{word}");
+        }
+    }
+
+    #[test]
+    fn test_ve() {
+        assert_eq!(split("plusve"), pair("plus", "ve"));
+        assert_eq!(split("quidue"), pair("quid", "ue"));
+        for word in ["breve", "salve", "sive", "neve", "statue", "indue"] {
+            assert_eq!(split(word), None, "{word}");
+        }
+    }
+
+    #[test]
+    fn test_cum() {
+        assert_eq!(split("mecum"), pair("me", "cum"));
+        assert_eq!(split("Nobiscum"), pair("Nobis", "cum"));
+        assert_eq!(split("VOBISCVM"), pair("VOBIS", "CVM"));
+        assert_eq!(split("tecum"), pair("te", "cum"));
+        assert_eq!(split("secum"), pair("se", "cum"));
+        assert_eq!(split("tamquam"), None);
+        assert_eq!(split("cum"), None);
+    }
+
+    #[test]
+    fn test_text() {
+        let splitter = EncliticSplitter::new();
+        assert_eq!(splitter.boundaries("Senatus populusque Romanus"), vec![15]);
+        assert_eq!(split_text("arma virumque cano, quoque mecum"), "arma virum que cano, quoque me cum");
+        assert_eq!(splitter.separator("|").split_text("plusve minusve"), "plus|ve minus|ve");
+    }
+
+    #[test]
+    fn test_toggles_and_lexicon() {
+        let splitter = EncliticSplitter::new().enclitic(Enclitic::Que, false);
+        assert_eq!(splitter.split("virumque"), None);
+        assert_eq!(splitter.split("mecum"), pair("me", "cum"));
+        assert_eq!(splitter.enclitics().collect::<Vec<_>>(), vec![Enclitic::Ne, Enclitic::Ve, Enclitic::Cum]);
+        let splitter = EncliticSplitter::new().lexicon(Arc::new(Lexicon::from_words(["uirum", "uides"])));
+        assert_eq!(splitter.split("virumque"), pair("virum", "que"));
+        assert_eq!(splitter.split("Videsne"), pair("Vides", "ne"));
+        assert_eq!(splitter.split("arvaque"), None);
+    }
+}
@@ -0,0 +1,442 @@
+//! Normalization of archaic and epigraphic spellings to classical ones.
+//!
+//! Inscriptions and early texts keep spellings that classical Latin gave up:
+//! *o* for *u* after *v* and in final *-om* (*servos*, *servom*, *donom*),
+//! *ei* for long *i* in endings (*virei*, *sueis*), and *xs* for *x*
+//! (*uxsor*, *saxsum*). [`EpigraphicNormalizer`] rewrites them with one
+//! toggle per [`EpigraphicRule`], from a table of word endings and clusters.
+//! Some archaic forms are also classical words (*servos* is the accusative
+//! plural), so with a [`Lexicon`] a word is only changed to an attested form,
+//! and never if the word as written is itself attested.
+//!
+//! Run it after u/v normalization: the rules read *v* for consonantal *u*.
+
+use crate::changes::{self, ChangeRecord};
+use crate::lexicon::Lexicon;
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use std::sync::Arc;
+
+/// One archaic spelling habit that [`EpigraphicNormalizer`] can undo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EpigraphicRule {
+    /// *-vos*, *-vom* for *-vus*, *-vum* (*servos*, *divom*).
+    VosForVus,
+    /// *-om* for *-um* (*donom*, *consolom*).
+    OmForUm,
+    /// *ei* for *i* in the endings *-ei* and *-eis* (*virei*, *sueis*).
+    EiForI,
+    /// *xs* for *x* (*uxsor*, *saxsum*); *ex-* compounds (*exspecto*) keep
+    /// their *xs*.
+    XsForX,
+}
+
+impl EpigraphicRule {
+    pub const ALL: [EpigraphicRule; 4] =
+        [EpigraphicRule::VosForVus, EpigraphicRule::OmForUm, EpigraphicRule::EiForI, EpigraphicRule::XsForX];
+
+    /// The rule's name, as used by the Python bindings.
+    pub fn name(self) -> &'static str {
+        match self {
+            EpigraphicRule::VosForVus => "vos_for_vus",
+            EpigraphicRule::OmForUm => "om_for_um",
+            EpigraphicRule::EiForI => "ei_for_i",
+            EpigraphicRule::XsForX => "xs_for_x",
+        }
+    }
+}
+
+/// The rewrites, as `(rule, pattern, replacement)`; a trailing `>` anchors
+/// the pattern at the end of the word. A word takes at most one ending, the
+/// first that matches.
+const RULES: &[(EpigraphicRule, &str, &str)] = &[
+    (EpigraphicRule::VosForVus, "vos>", "vus>"),
+    (EpigraphicRule::VosForVus, "vom>", "vum>"),
+    (EpigraphicRule::OmForUm, "om>", "um>"),
+    (EpigraphicRule::EiForI, "eis>", "is>"),
+    (EpigraphicRule::EiForI, "ei>", "i>"),
+    (EpigraphicRule::XsForX, "xs", "x"),
+];
+
+/// Letters a word must have before a rewritten ending (so *vos* and *rei*
+/// are left alone).
+const MIN_STEM: usize = 2;
+
+/// Classical words that end like an archaic form.
+const ENDING_EXCEPTIONS: &[&str] = &[
+    "quom", "spei", "diei", "fidei", "aciei", "faciei", "seriei", "speciei", "meridiei", "plebei", "deis",
+    "meis",
+];
+
+/// Prefixes whose *x* is followed by a genuine *s* (*exsilium*, *inexspectatus*).
+const EX_PREFIXES: &[&str] = &["ex", "inex", "coex"];
+
+/// A replacement in a lowercase word: `len` letters at `start` become
+/// `replacement`.
+#[derive(Debug, Clone, PartialEq)]
+struct Edit {
+    start: usize,
+    len: usize,
+    pattern: &'static str,
+    replacement: &'static str,
+}
+
+/// Restores classical spellings in archaic and epigraphic Latin.
+///
+/// ```
+/// use latincy_preprocess::epigraphic::EpigraphicNormalizer;
+///
+/// let normalizer = EpigraphicNormalizer::default();
+/// assert_eq!(normalizer.normalize_text("servos donom dedit uxsori virei"), "servus donum dedit uxori viri");
+/// ```
+#[derive(Debug, Clone)]
+pub struct EpigraphicNormalizer {
+    rules: Vec<EpigraphicRule>,
+    lexicon: Option<Arc<Lexicon>>,
+}
+
+impl Default for EpigraphicNormalizer {
+    /// Every rule enabled, without a lexicon.
+    fn default() -> Self {
+        EpigraphicNormalizer {
+            rules: EpigraphicRule::ALL.to_vec(),
+            lexicon: None,
+        }
+    }
+}
+
+impl EpigraphicNormalizer {
+    /// Enable or disable one rule.
+    pub fn rule(mut self, rule: EpigraphicRule, enabled: bool) -> Self {
+        self.rules.retain(|&r| r != rule);
+        if enabled {
+            self.rules.push(rule);
+        }
+        self
+    }
+
+    /// Check every word against `lexicon`: it is only changed to a form the
+    /// lexicon attests, and never if the word as written is attested.
+    pub fn lexicon(mut self, lexicon: Arc<Lexicon>) -> Self {
+        self.lexicon = Some(lexicon);
+        self
+    }
+
+    pub fn is_enabled(&self, rule: EpigraphicRule) -> bool {
+        self.rules.contains(&rule)
+    }
+
+    /// The enabled rules, in the order of [`EpigraphicRule::ALL`].
+    pub fn rules(&self) -> impl Iterator<Item = EpigraphicRule> + '_ {
+        EpigraphicRule::ALL.into_iter().filter(|&rule| self.is_enabled(rule))
+    }
+
+    /// The edits for a lowercase word, in order.
+    fn edits(&self, word: &[char]) -> Vec<Edit> {
+        let text: String = word.iter().collect();
+        let mut edits = Vec::new();
+        let mut ending = false;
+        for &(rule, pattern, replacement) in RULES {
+            if !self.is_enabled(rule) {
+                continue;
+            }
+            if let Some(suffix) = pattern.strip_suffix('>') {
+                let len = suffix.chars().count();
+                if ending
+                    || word.len() < len + MIN_STEM
+                    || !text.ends_with(suffix)
+                    || ENDING_EXCEPTIONS.contains(&text.as_str())
+                {
+                    continue;
+                }
+                ending = true;
+                edits.push(Edit {
+                    start: word.len() - len,
+                    len,
+                    pattern: suffix,
+                    replacement: replacement.trim_end_matches('>'),
+                });
+                continue;
+            }
+            let needle: Vec<char> = pattern.chars().collect();
+            for start in 0..=word.len().saturating_sub(needle.len()) {
+                let prefix: String = word[..=start].iter().collect();
+                if word[start..].starts_with(&needle) && !EX_PREFIXES.contains(&prefix.as_str()) {
+                    edits.push(Edit {
+                        start,
+                        len: needle.len(),
+                        pattern,
+                        replacement,
+                    });
+                }
+            }
+        }
+        edits.sort_by_key(|edit| edit.start);
+        // An ending that overlaps a cluster edit is dropped
+        edits.dedup_by(|later, earlier| later.start < earlier.start + earlier.len);
+
+        if let Some(lexicon) = &self.lexicon {
+            if edits.is_empty() || lexicon.contains(&text) || !lexicon.contains(&apply(word, &edits)) {
+                return Vec::new();
+            }
+        }
+        edits
+    }
+
+    pub fn normalize_word(&self, word: &str) -> String {
+        self.normalize_detailed(word).normalized
+    }
+
+    pub fn normalize_text(&self, text: &str) -> String {
+        self.normalize_detailed(text).normalized
+    }
+
+    /// Like [`EpigraphicNormalizer::normalize_text`], recording one
+    /// [`ChangeRecord`] per rewrite. The rule is the rewrite itself, e.g.
+    /// `"-vos → -vus"` or `"xs → x"`.
+    pub fn normalize_detailed(&self, text: &str) -> DetailedResult {
+        let chars: Vec<char> = text.chars().collect();
+        let mut normalized = String::with_capacity(text.len());
+        let mut changes = Vec::new();
+
+        let mut i = 0;
+        while i < chars.len() {
+            if !chars[i].is_alphabetic() {
+                normalized.push(chars[i]);
+                i += 1;
+                continue;
+            }
+            let end = chars[i..].iter().position(|c| !c.is_alphabetic()).map_or(chars.len(), |p| i + p);
+            let original = &chars[i..end];
+            // Lowercasing can change the length of a few letters; leave such
+            // words alone rather than misplace the edits
+            let lower: Vec<char> = original.iter().flat_map(|c| c.to_lowercase()).collect();
+            let edits = if lower.len() == original.len() { self.edits(&lower) } else { Vec::new() };
+
+            let upper = original.len() > 1 && original.iter().all(|c| !c.is_lowercase());
+            // Capitals that write V for U (SERVOS) get V (SERVVS)
+            let v_for_u = upper && original.contains(&'V') && !original.contains(&'U');
+            let mut at = 0;
+            for edit in &edits {
+                normalized.extend(&original[at..edit.start]);
+                let replaced = &original[edit.start..edit.start + edit.len];
+                let replacement = match replaced.first() {
+                    Some(c) if c.is_uppercase() && v_for_u => edit.replacement.to_uppercase().replace('U', "V"),
+                    Some(c) if c.is_uppercase() && upper => edit.replacement.to_uppercase(),
+                    Some(c) if c.is_uppercase() => restore_first(edit.replacement),
+                    _ => edit.replacement.to_string(),
+                };
+                let position = i + edit.start;
+                changes.push(ChangeRecord {
+                    position,
+                    original: replaced.iter().collect(),
+                    rule: edit_rule(edit),
+                    normalized: replacement.clone(),
+                    pass: 1,
+                    context: changes::context(&chars, position, edit.len),
+                });
+                normalized.push_str(&replacement);
+                at = edit.start + edit.len;
+            }
+            normalized.extend(&original[at..]);
+            i = end;
+        }
+
+        DetailedResult {
+            original: text.to_string(),
+            normalized,
+            changes,
+        }
+    }
+}
+
+fn apply(word: &[char], edits: &[Edit]) -> String {
+    let mut result = String::with_capacity(word.len());
+    let mut i = 0;
+    for edit in edits {
+        result.extend(&word[i..edit.start]);
+        result.push_str(edit.replacement);
+        i = edit.start + edit.len;
+    }
+    result.extend(&word[i..]);
+    result
+}
+
+fn restore_first(letters: &str) -> String {
+    let mut chars = letters.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// The rewrite an edit made: `-vos → -vus` for an ending, `xs → x` for a
+/// cluster.
+fn edit_rule(edit: &Edit) -> String {
+    if edit.pattern == "xs" {
+        format!("{} → {}", edit.pattern, edit.replacement)
+    } else {
+        format!("-{} → -{}", edit.pattern, edit.replacement)
+    }
+}
+
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<ChangeRecord>,
+}
+
+/// Normalize epigraphic spellings with every rule and no lexicon.
+///
+/// ```
+/// use latincy_preprocess::epigraphic::normalize_text;
+///
+/// assert_eq!(normalize_text("SAXSVM DIVOS"), "SAXVM DIVVS");
+/// ```
+pub fn normalize_text(text: &str) -> String {
+    EpigraphicNormalizer::default().normalize_text(text)
+}
+
+impl Normalizer for EpigraphicNormalizer {
+    fn name(&self) -> &str {
+        "epigraphic"
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = self.normalize_detailed(text);
+        NormalizeOutput::from_changes(result.original, result.normalized, result.changes)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "normalize_epigraphic")]
+pub fn py_normalize_epigraphic(py: Python<'_>, text: &str) -> String {
+    crate::allow_threads(py, text, || normalize_text(text))
+}
+
+/// Python view of [`EpigraphicNormalizer`], exposed as
+/// `_rust.EpigraphicNormalizer`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "EpigraphicNormalizer", frozen)]
+pub struct PyEpigraphicNormalizer {
+    pub(crate) inner: EpigraphicNormalizer,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyEpigraphicNormalizer {
+    #[new]
+    #[pyo3(signature = (vos_for_vus=true, om_for_um=true, ei_for_i=true, xs_for_x=true, lexicon=None))]
+    fn new(
+        vos_for_vus: bool,
+        om_for_um: bool,
+        ei_for_i: bool,
+        xs_for_x: bool,
+        lexicon: Option<std::path::PathBuf>,
+    ) -> PyResult<Self> {
+        let mut inner = EpigraphicNormalizer::default()
+            .rule(EpigraphicRule::VosForVus, vos_for_vus)
+            .rule(EpigraphicRule::OmForUm, om_for_um)
+            .rule(EpigraphicRule::EiForI, ei_for_i)
+            .rule(EpigraphicRule::XsForX, xs_for_x);
+        if let Some(path) = lexicon {
+            inner = inner.lexicon(Arc::new(Lexicon::from_file(path)?));
+        }
+        Ok(PyEpigraphicNormalizer { inner })
+    }
+
+    /// Names of the enabled rules.
+    #[getter]
+    fn rules(&self) -> Vec<&'static str> {
+        self.inner.rules().map(EpigraphicRule::name).collect()
+    }
+
+    fn normalize_word(&self, word: &str) -> String {
+        self.inner.normalize_word(word)
+    }
+
+    fn normalize_text(&self, py: Python<'_>, text: &str) -> String {
+        crate::allow_threads(py, text, || self.inner.normalize_text(text))
+    }
+
+    fn normalize_detailed(&self, py: Python<'_>, text: &str) -> PyResult<changes::PyDetailedResult> {
+        let result = crate::allow_threads(py, text, || self.inner.normalize_detailed(text));
+        changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("EpigraphicNormalizer(rules={:?})", self.rules())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endings() {
+        assert_eq!(normalize_text("servos servom divos"), "servus servum divus");
+        assert_eq!(normalize_text("donom consolom"), "donum consolum");
+        assert_eq!(normalize_text("virei populei sueis"), "viri populi suis");
+        // Short words and classical look-alikes are kept
+        assert_eq!(normalize_text("vos rei dei spei diei quom"), "vos rei dei spei diei quom");
+    }
+
+    #[test]
+    fn test_xs() {
+        assert_eq!(normalize_text("uxsor saxsum maxsumus"), "uxor saxum maxumus");
+        assert_eq!(normalize_text("exspecto exsilium inexspectatus"), "exspecto exsilium inexspectatus");
+    }
+
+    #[test]
+    fn test_case() {
+        assert_eq!(normalize_text("SERVOS Donom, VIREI."), "SERVVS Donum, VIRI.");
+        assert_eq!(normalize_text("Servos"), "Servus");
+        assert_eq!(normalize_text("VXSOR"), "VXOR");
+    }
+
+    #[test]
+    fn test_lexicon() {
+        let lexicon = Arc::new(Lexicon::from_words(["servus", "servos", "donum", "viri"]));
+        let normalizer = EpigraphicNormalizer::default().lexicon(lexicon);
+        // servos is attested (accusative plural); consolum is not
+        assert_eq!(normalizer.normalize_text("servos donom virei consolom"), "servos donum viri consolom");
+    }
+
+    #[test]
+    fn test_toggles() {
+        let normalizer =
+            EpigraphicNormalizer::default().rule(EpigraphicRule::EiForI, false).rule(EpigraphicRule::XsForX, false);
+        assert_eq!(normalizer.normalize_text("virei uxsor servos"), "virei uxsor servus");
+        assert_eq!(normalizer.rules().collect::<Vec<_>>(), [EpigraphicRule::VosForVus, EpigraphicRule::OmForUm]);
+        // Without the vos rule, -vom still falls under -om
+        let normalizer = EpigraphicNormalizer::default().rule(EpigraphicRule::VosForVus, false);
+        assert_eq!(normalizer.normalize_text("servos servom"), "servos servum");
+    }
+
+    #[test]
+    fn test_normalize_detailed() {
+        let result = EpigraphicNormalizer::default().normalize_detailed("in saxsum servos");
+        assert_eq!(result.normalized, "in saxum servus");
+        assert_eq!(result.changes.len(), 2);
+        assert_eq!(result.changes[0].position, 5);
+        assert_eq!(result.changes[0].original, "xs");
+        assert_eq!(result.changes[0].normalized, "x");
+        assert_eq!(result.changes[0].rule, "xs → x");
+        assert_eq!(result.changes[1].position, 13);
+        assert_eq!(result.changes[1].original, "vos");
+        assert_eq!(result.changes[1].normalized, "vus");
+        assert_eq!(result.changes[1].rule, "-vos → -vus");
+        assert_eq!(result.changes[1].context, "ser[vos]");
+    }
+}
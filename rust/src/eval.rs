@@ -0,0 +1,1073 @@
+//! Evaluation against a gold standard: how closely a normalizer's changes
+//! match those of a hand-corrected text.
+//!
+//! [`evaluate`] normalizes a raw text and aligns both its output and the
+//! gold text with the raw text, character by character. An edit that the
+//! normalizer and the gold text both make is a true positive, one that only
+//! the normalizer makes a false positive, and one that only the gold text
+//! makes a false negative. These are counted overall, for each rule, and for
+//! each confusion (`u → v`, `f → s`), and each error is listed with its
+//! context, so that thresholds and exception lists can be tuned on numbers
+//! rather than by reading diffs.
+//!
+//! Gold standards come as [`Pair`]s of raw and corrected text: whole texts,
+//! or the lines of two aligned texts ([`load_aligned`]), a list of word
+//! pairs ([`load_tsv`]), or, with the `xml` feature, a TEI document whose
+//! `<choice>` elements give both readings (`load_tei`).
+//!
+//! ```
+//! use latincy_preprocess::eval;
+//! use latincy_preprocess::uv::UvNormalizer;
+//!
+//! let evaluation = eval::evaluate(&UvNormalizer::new(), "uir uolo uxor", "vir volo vxor");
+//! assert_eq!(evaluation.overall.true_positives, 2);
+//! assert_eq!(evaluation.overall.false_negatives, 1);
+//! assert_eq!(evaluation.errors[0].context, "lo [u]xor");
+//! ```
+
+use crate::changes;
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use crate::pipeline::PyPipeline;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::types::{PyDict, PyList};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io;
+
+/// Counts of edits made and missed, with the usual scores over them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Scores {
+    /// Edits the normalizer made as the gold text does.
+    pub true_positives: usize,
+    /// Edits the normalizer made that the gold text does not.
+    pub false_positives: usize,
+    /// Edits of the gold text the normalizer did not make.
+    pub false_negatives: usize,
+}
+
+impl Scores {
+    /// The share of the normalizer's edits that are right; 1 if it made
+    /// none.
+    pub fn precision(&self) -> f64 {
+        ratio(self.true_positives, self.true_positives + self.false_positives)
+    }
+
+    /// The share of the gold text's edits the normalizer made; 1 if there
+    /// are none.
+    pub fn recall(&self) -> f64 {
+        ratio(self.true_positives, self.true_positives + self.false_negatives)
+    }
+
+    /// The harmonic mean of precision and recall.
+    pub fn f1(&self) -> f64 {
+        let (precision, recall) = (self.precision(), self.recall());
+        if precision + recall == 0.0 {
+            0.0
+        } else {
+            2.0 * precision * recall / (precision + recall)
+        }
+    }
+
+    fn to_json(self) -> serde_json::Value {
+        serde_json::json!({
+            "true_positives": self.true_positives,
+            "false_positives": self.false_positives,
+            "false_negatives": self.false_negatives,
+            "precision": self.precision(),
+            "recall": self.recall(),
+            "f1": self.f1(),
+        })
+    }
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        1.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// How the normalizer's output differs from the gold text at an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ErrorKind {
+    /// The normalizer changed what the gold text keeps.
+    Spurious,
+    /// The normalizer kept what the gold text changes.
+    Missed,
+    /// Both changed it, differently.
+    Wrong,
+}
+
+impl ErrorKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Spurious => "spurious",
+            ErrorKind::Missed => "missed",
+            ErrorKind::Wrong => "wrong",
+        }
+    }
+}
+
+/// A span of a raw text where the normalizer's output and the gold text
+/// disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalError {
+    pub kind: ErrorKind,
+    /// The index of the pair the error is in.
+    pub pair: usize,
+    /// The character offset of the span in the raw text.
+    pub position: usize,
+    /// The raw text of the span.
+    pub original: String,
+    /// What the gold text has in its place.
+    pub expected: String,
+    /// What the normalizer made of it.
+    pub actual: String,
+    /// The rules of the normalizer's changes in the span, if it made any.
+    pub rules: Vec<String>,
+    /// The raw text around the span, with the span in brackets, as in
+    /// [`ChangeRecord::context`](crate::changes::ChangeRecord::context).
+    pub context: String,
+}
+
+/// A raw text and its hand-corrected form.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pair {
+    pub raw: String,
+    pub gold: String,
+    /// Where the pair came from, for reports: the line number for the
+    /// pairs of [`load_aligned`] and [`load_tsv`], otherwise empty.
+    pub name: String,
+}
+
+impl Pair {
+    pub fn new(raw: impl Into<String>, gold: impl Into<String>) -> Self {
+        Pair {
+            raw: raw.into(),
+            gold: gold.into(),
+            name: String::new(),
+        }
+    }
+
+    fn line(raw: &str, gold: &str, index: usize) -> Self {
+        Pair {
+            name: (index + 1).to_string(),
+            ..Pair::new(raw, gold)
+        }
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// The pairs of two line-aligned texts, one per line: line `n` of `gold`
+/// is the corrected form of line `n` of `raw`. Texts with different
+/// numbers of lines are an [`io::ErrorKind::InvalidData`] error.
+pub fn load_aligned(raw: &str, gold: &str) -> io::Result<Vec<Pair>> {
+    let (raw_lines, gold_lines): (Vec<&str>, Vec<&str>) = (raw.lines().collect(), gold.lines().collect());
+    if raw_lines.len() != gold_lines.len() {
+        return Err(invalid(format!(
+            "the raw text has {} lines and the gold text {}",
+            raw_lines.len(),
+            gold_lines.len()
+        )));
+    }
+    Ok(raw_lines.into_iter().zip(gold_lines).enumerate().map(|(k, (raw, gold))| Pair::line(raw, gold, k)).collect())
+}
+
+/// The pairs of a list of word pairs, one `raw<TAB>gold` per line, as
+/// exported from a spreadsheet or a correction database. Blank lines and
+/// lines starting with `#` are skipped; any other line without exactly
+/// two columns is an [`io::ErrorKind::InvalidData`] error naming it.
+pub fn load_tsv(tsv: &str) -> io::Result<Vec<Pair>> {
+    let mut pairs = Vec::new();
+    for (k, line) in tsv.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split('\t').collect::<Vec<_>>()[..] {
+            [raw, gold] => pairs.push(Pair::line(raw, gold, k)),
+            ref columns => {
+                let found = columns.len();
+                return Err(invalid(format!("line {}: expected 2 tab-separated columns, found {found}", k + 1)));
+            }
+        }
+    }
+    Ok(pairs)
+}
+
+/// The pair of a TEI document with editorial `<choice>`s: the raw text
+/// takes the `<orig>` of each (or `<sic>`, `<abbr>`), and the gold text its
+/// `<reg>` (or `<corr>`, `<expan>`). Text outside them goes in both, and
+/// the `teiHeader` in neither. Malformed markup is an
+/// [`io::ErrorKind::InvalidData`] error giving its line.
+///
+/// ```
+/// use latincy_preprocess::eval;
+///
+/// let tei = "<p><choice><orig>uir</orig><reg>vir</reg></choice> bonus</p>";
+/// let pair = eval::load_tei(tei).unwrap();
+/// assert_eq!((pair.raw.as_str(), pair.gold.as_str()), ("uir bonus", "vir bonus"));
+/// ```
+#[cfg(feature = "xml")]
+pub fn load_tei(xml: &str) -> io::Result<Pair> {
+    let (raw, gold) = crate::xml::choice_texts(xml)?;
+    Ok(Pair::new(raw, gold))
+}
+
+/// The outcome of [`evaluate`] or [`evaluate_pairs`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Evaluation {
+    /// How many pairs were evaluated.
+    pub pairs: usize,
+    pub overall: Scores,
+    /// The scores of each rule's edits, by rule name (in a pipeline,
+    /// prefixed with the stage: `"uv: consonantal"`). The false negatives
+    /// of a rule are the gold edits of the confusions it makes that it did
+    /// not make, so where several rules make one confusion their recalls
+    /// add up to the confusion's.
+    pub rules: BTreeMap<String, Scores>,
+    /// The scores of each confusion, as `(from, to)` in lowercase, e.g.
+    /// `("u", "v")` or `("ſ", "s")`.
+    pub confusions: BTreeMap<(String, String), Scores>,
+    /// The errors, in pair and text order.
+    pub errors: Vec<EvalError>,
+}
+
+/// One edit of a raw text: `start..end` (in characters) replaced.
+struct Edit {
+    start: usize,
+    end: usize,
+    replacement: String,
+    rules: Vec<String>,
+}
+
+impl Edit {
+    /// The characters the edit covers, an insertion counting as the
+    /// character it goes before, so edits at one place overlap.
+    fn covers(&self) -> std::ops::Range<usize> {
+        self.start..self.end.max(self.start + 1)
+    }
+
+    fn confusion(&self, raw: &[char]) -> (String, String) {
+        let from: String = raw[self.start..self.end].iter().collect();
+        (from.to_lowercase(), self.replacement.to_lowercase())
+    }
+}
+
+fn edits(raw: &str, text: &str) -> Vec<Edit> {
+    crate::diff::char_edits(raw, text)
+        .into_iter()
+        .map(|(start, end, replacement)| Edit {
+            start,
+            end,
+            replacement,
+            rules: Vec::new(),
+        })
+        .collect()
+}
+
+/// `raw[start..end]` with `edits` in it applied.
+fn apply(raw: &[char], start: usize, end: usize, edits: &[Edit]) -> String {
+    let mut result = String::new();
+    let mut pos = start;
+    for edit in edits {
+        result.extend(&raw[pos..edit.start]);
+        result.push_str(&edit.replacement);
+        pos = edit.end;
+    }
+    result.extend(&raw[pos..end]);
+    result
+}
+
+/// An [`Evaluation`] being counted up, pair by pair.
+#[derive(Default)]
+struct Tally {
+    evaluation: Evaluation,
+    /// The confusions of each rule's edits.
+    rule_confusions: BTreeMap<String, BTreeSet<(String, String)>>,
+}
+
+impl Tally {
+    /// Score `output`, the normalizer's output for the raw text `raw`,
+    /// against `gold`. Edits without a change record of their own are put
+    /// down to the rule `fallback`.
+    fn add(&mut self, raw: &str, output: &NormalizeOutput, gold: &str, fallback: &str) {
+        let pair = self.evaluation.pairs;
+        self.evaluation.pairs += 1;
+        let evaluation = &mut self.evaluation;
+        let chars: Vec<char> = raw.chars().collect();
+        let mut system = edits(raw, &output.normalized);
+        for change in &output.changes {
+            let position = change.position.min(chars.len());
+            let k = system.partition_point(|edit| edit.covers().end <= position);
+            if let Some(edit) = system.get_mut(k).filter(|edit| edit.covers().contains(&position)) {
+                if !edit.rules.contains(&change.rule) {
+                    edit.rules.push(change.rule.clone());
+                }
+            }
+        }
+        for edit in system.iter_mut().filter(|edit| edit.rules.is_empty()) {
+            edit.rules.push(fallback.to_string());
+        }
+        let gold = edits(raw, gold);
+
+        // Group the edits into runs that overlap, and score each run
+        let (mut s, mut g) = (0, 0);
+        while s < system.len() || g < gold.len() {
+            let first = match (system.get(s), gold.get(g)) {
+                (Some(a), Some(b)) => a.start.min(b.start),
+                (Some(a), None) => a.start,
+                (None, Some(b)) => b.start,
+                (None, None) => unreachable!(),
+            };
+            let (s_start, g_start) = (s, g);
+            let mut end = first + 1;
+            loop {
+                if system.get(s).is_some_and(|edit| edit.start < end) {
+                    end = end.max(system[s].covers().end);
+                    s += 1;
+                } else if gold.get(g).is_some_and(|edit| edit.start < end) {
+                    end = end.max(gold[g].covers().end);
+                    g += 1;
+                } else {
+                    break;
+                }
+            }
+            let (made, wanted) = (&system[s_start..s], &gold[g_start..g]);
+            if let ([a], [b]) = (made, wanted) {
+                if (a.start, a.end, &a.replacement) == (b.start, b.end, &b.replacement) {
+                    let confusion = a.confusion(&chars);
+                    evaluation.overall.true_positives += 1;
+                    for rule in &a.rules {
+                        evaluation.rules.entry(rule.clone()).or_default().true_positives += 1;
+                    }
+                    evaluation.confusions.entry(confusion).or_default().true_positives += 1;
+                    continue;
+                }
+            }
+            for edit in made {
+                evaluation.overall.false_positives += 1;
+                for rule in &edit.rules {
+                    evaluation.rules.entry(rule.clone()).or_default().false_positives += 1;
+                }
+                evaluation.confusions.entry(edit.confusion(&chars)).or_default().false_positives += 1;
+            }
+            for edit in wanted {
+                evaluation.overall.false_negatives += 1;
+                evaluation.confusions.entry(edit.confusion(&chars)).or_default().false_negatives += 1;
+            }
+            let start = made.iter().chain(wanted).map(|edit| edit.start).min().unwrap_or(first);
+            let end = made.iter().chain(wanted).map(|edit| edit.end).max().unwrap_or(first).max(start);
+            let kind = match (made.is_empty(), wanted.is_empty()) {
+                (false, true) => ErrorKind::Spurious,
+                (true, false) => ErrorKind::Missed,
+                _ => ErrorKind::Wrong,
+            };
+            let mut rules: Vec<String> = Vec::new();
+            for rule in made.iter().flat_map(|edit| &edit.rules) {
+                if !rules.contains(rule) {
+                    rules.push(rule.clone());
+                }
+            }
+            evaluation.errors.push(EvalError {
+                kind,
+                pair,
+                position: start,
+                original: chars[start..end].iter().collect(),
+                expected: apply(&chars, start, end, wanted),
+                actual: apply(&chars, start, end, made),
+                rules,
+                context: changes::context(&chars, start, end - start),
+            });
+        }
+        for edit in &system {
+            let confusion = edit.confusion(&chars);
+            for rule in &edit.rules {
+                self.rule_confusions.entry(rule.clone()).or_default().insert(confusion.clone());
+            }
+        }
+    }
+
+    /// The evaluation, with each rule charged the gold edits of its
+    /// confusions that it did not make.
+    fn finish(mut self) -> Evaluation {
+        let evaluation = &mut self.evaluation;
+        for (rule, scores) in evaluation.rules.iter_mut() {
+            let gold: usize = self.rule_confusions[rule]
+                .iter()
+                .map(|confusion| {
+                    let confusion = evaluation.confusions[confusion];
+                    confusion.true_positives + confusion.false_negatives
+                })
+                .sum();
+            scores.false_negatives = gold.saturating_sub(scores.true_positives);
+        }
+        self.evaluation
+    }
+}
+
+/// `normalizer` scored on `raw` against its hand-corrected form `gold`.
+/// The two should differ only where normalization changes them: other
+/// differences count as errors too.
+pub fn evaluate(normalizer: &dyn Normalizer, raw: &str, gold: &str) -> Evaluation {
+    let mut tally = Tally::default();
+    tally.add(raw, &normalizer.normalize(raw), gold, normalizer.name());
+    tally.finish()
+}
+
+/// `normalizer` scored on each of `pairs`, with the counts summed and the
+/// errors of all the pairs listed, each with the index of its pair.
+pub fn evaluate_pairs(normalizer: &dyn Normalizer, pairs: &[Pair]) -> Evaluation {
+    let mut tally = Tally::default();
+    for pair in pairs {
+        tally.add(&pair.raw, &normalizer.normalize(&pair.raw), &pair.gold, normalizer.name());
+    }
+    tally.finish()
+}
+
+impl Evaluation {
+    /// The evaluation as a JSON object: the number of pairs, the `overall`
+    /// scores, the `rules` by name, the `confusions` and `errors` as lists.
+    /// Scores give the three counts and `precision`, `recall`, and `f1`.
+    pub fn to_json(&self) -> String {
+        let rules: serde_json::Map<String, serde_json::Value> =
+            self.rules.iter().map(|(rule, scores)| (rule.clone(), scores.to_json())).collect();
+        let confusions: Vec<serde_json::Value> = self
+            .confusions
+            .iter()
+            .map(|((from, to), scores)| {
+                let mut value = scores.to_json();
+                value["from"] = from.as_str().into();
+                value["to"] = to.as_str().into();
+                value
+            })
+            .collect();
+        let errors: Vec<serde_json::Value> = self
+            .errors
+            .iter()
+            .map(|error| {
+                serde_json::json!({
+                    "kind": error.kind.name(),
+                    "pair": error.pair,
+                    "position": error.position,
+                    "original": error.original,
+                    "expected": error.expected,
+                    "actual": error.actual,
+                    "rules": error.rules,
+                    "context": error.context,
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "pairs": self.pairs,
+            "overall": self.overall.to_json(),
+            "rules": rules,
+            "confusions": confusions,
+            "errors": errors,
+        })
+        .to_string()
+    }
+}
+
+/// A row of the score table.
+fn row(label: &str, scores: &Scores) -> String {
+    format!(
+        "{:>9.3} {:>6.3} {:>6.3} {:>6} {:>6} {:>6}  {}",
+        scores.precision(),
+        scores.recall(),
+        scores.f1(),
+        scores.true_positives,
+        scores.false_positives,
+        scores.false_negatives,
+        label
+    )
+}
+
+/// A table of the overall, rule, and confusion scores, then the errors,
+/// one per line as `pair:position: kind "context" -> "expected"`.
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = vec!["precision recall     f1     tp     fp     fn".to_string(), row("overall", &self.overall)];
+        lines.extend(self.rules.iter().map(|(rule, scores)| row(rule, scores)));
+        lines.extend(self.confusions.iter().map(|((from, to), scores)| row(&format!("{from:?} -> {to:?}"), scores)));
+        if !self.errors.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(self.errors.iter().map(|error| format!("{}:{}", error.pair, error)));
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// `position: kind "context" -> "expected"`, then what the normalizer made
+/// of the span and the rules that did it, unless it missed the edit.
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} {:?} -> {:?}", self.position, self.kind.name(), self.context, self.expected)?;
+        if self.kind != ErrorKind::Missed {
+            write!(f, ", not {:?} ({})", self.actual, self.rules.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Error mining
+// ---------------------------------------------------------------------------
+
+/// Stems shorter than this many characters are too unspecific to list as
+/// exceptions.
+pub const MIN_STEM_CHARS: usize = 4;
+
+/// What a pattern of errors suggests doing about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Candidate {
+    /// The errors are all in one word: list it as an exception.
+    Word,
+    /// The words share a stem: list it, as an exception for all its forms.
+    Stem,
+    /// The words have nothing in common but the context of the error: a
+    /// rule is missing for it, or a rule reaches too far.
+    Rule,
+}
+
+impl Candidate {
+    pub fn name(self) -> &'static str {
+        match self {
+            Candidate::Word => "word",
+            Candidate::Stem => "stem",
+            Candidate::Rule => "rule",
+        }
+    }
+}
+
+/// Errors of one kind, rule, and confusion in one context, from
+/// [`mine_errors`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorPattern {
+    pub kind: ErrorKind,
+    /// The rules of the normalizer's edits, joined with `", "`; empty for
+    /// missed edits.
+    pub rule: String,
+    /// The raw text of the span and what the gold text has in its place,
+    /// in lowercase.
+    pub original: String,
+    pub expected: String,
+    /// The span in its word, with the letters on either side as `C`
+    /// (consonant) or `V` (vowel) and the word boundary as `#`: `#[u]C` is
+    /// a `u` starting a word before a consonant.
+    pub shape: String,
+    /// The two characters before the span in its word, in lowercase; `#`
+    /// marks the start of the word when there are fewer.
+    pub bigram: String,
+    /// How many errors have the pattern.
+    pub count: usize,
+    /// The words the errors are in, in lowercase, with how many errors
+    /// each has, most first.
+    pub words: Vec<(String, usize)>,
+    pub candidate: Candidate,
+    /// The word or stem to list, or for [`Candidate::Rule`] the shape and
+    /// bigram of the context.
+    pub suggestion: String,
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y' | 'æ' | 'œ' | 'ā' | 'ē' | 'ī' | 'ō' | 'ū' | 'ȳ')
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphabetic() || crate::graphemes::is_extender(c)
+}
+
+/// The class of the letter of `word` at `index` for a shape.
+fn letter_class(word: &[char], index: Option<usize>) -> char {
+    match index.and_then(|i| word.get(i)) {
+        None => '#',
+        Some(&c) if is_vowel(c) => 'V',
+        Some(_) => 'C',
+    }
+}
+
+/// The patterns of the errors of `evaluation`, which was made on `pairs`,
+/// most frequent first. Errors fall into one pattern when they have the
+/// same kind, rules, and confusion, and their spans the same [shape] and
+/// preceding [bigram] in their words; each pattern suggests the
+/// [`Candidate`] its words call for. This turns a long error list into a
+/// short one of exceptions to add and rules to write or narrow.
+///
+/// [shape]: ErrorPattern::shape
+/// [bigram]: ErrorPattern::bigram
+///
+/// ```
+/// use latincy_preprocess::eval::{self, Candidate, Pair};
+/// use latincy_preprocess::uv::UvNormalizer;
+///
+/// let pairs = [Pair::new("uxor uxoris", "vxor vxoris"), Pair::new("uxorem", "vxorem")];
+/// let evaluation = eval::evaluate_pairs(&UvNormalizer::new(), &pairs);
+/// let patterns = eval::mine_errors(&evaluation, &pairs);
+/// assert_eq!((patterns[0].count, patterns[0].candidate), (3, Candidate::Stem));
+/// assert_eq!((patterns[0].shape.as_str(), patterns[0].suggestion.as_str()), ("#[u]C", "uxor"));
+/// ```
+pub fn mine_errors(evaluation: &Evaluation, pairs: &[Pair]) -> Vec<ErrorPattern> {
+    type Key = (ErrorKind, String, String, String, String, String);
+    let mut clusters: BTreeMap<Key, BTreeMap<String, usize>> = BTreeMap::new();
+    let mut texts: BTreeMap<usize, Vec<char>> = BTreeMap::new();
+    for error in &evaluation.errors {
+        let Some(pair) = pairs.get(error.pair) else {
+            continue;
+        };
+        let chars = texts.entry(error.pair).or_insert_with(|| pair.raw.chars().collect());
+        let start = error.position.min(chars.len());
+        let end = (start + error.original.chars().count()).min(chars.len());
+        let word_start = start - chars[..start].iter().rev().take_while(|&&c| is_word_char(c)).count();
+        let word_end = end + chars[end..].iter().take_while(|&&c| is_word_char(c)).count();
+        let word: Vec<char> = chars[word_start..word_end].iter().flat_map(|c| c.to_lowercase()).collect();
+        // The span in the lowercase word; lowercasing rarely changes lengths
+        let (span_start, span_end) = (start - word_start, (end - word_start).min(word.len()));
+        let shape = format!(
+            "{}[{}]{}",
+            letter_class(&word, span_start.checked_sub(1)),
+            error.original.to_lowercase(),
+            letter_class(&word, Some(span_end)),
+        );
+        let before: String = word[span_start.saturating_sub(2)..span_start.min(word.len())].iter().collect();
+        let bigram = if span_start < 2 { format!("#{before}") } else { before };
+        let key = (
+            error.kind,
+            error.rules.join(", "),
+            error.original.to_lowercase(),
+            error.expected.to_lowercase(),
+            shape,
+            bigram,
+        );
+        *clusters.entry(key).or_default().entry(word.into_iter().collect()).or_default() += 1;
+    }
+    let mut patterns: Vec<ErrorPattern> = clusters
+        .into_iter()
+        .map(|((kind, rule, original, expected, shape, bigram), words)| {
+            let mut words: Vec<(String, usize)> = words.into_iter().collect();
+            words.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            let stem = words.iter().map(|(word, _)| word.as_str()).reduce(common_prefix).unwrap_or("");
+            let (candidate, suggestion) = if words.len() == 1 {
+                (Candidate::Word, words[0].0.clone())
+            } else if stem.chars().count() >= MIN_STEM_CHARS {
+                (Candidate::Stem, stem.to_string())
+            } else {
+                (Candidate::Rule, format!("{shape} after {bigram}"))
+            };
+            ErrorPattern {
+                kind,
+                rule,
+                original,
+                expected,
+                count: words.iter().map(|(_, count)| count).sum(),
+                shape,
+                bigram,
+                words,
+                candidate,
+                suggestion,
+            }
+        })
+        .collect();
+    patterns.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| b.words.len().cmp(&a.words.len())));
+    patterns
+}
+
+/// The longest prefix of `a` that `b` starts with.
+fn common_prefix<'a>(a: &'a str, b: &str) -> &'a str {
+    let len = a.chars().zip(b.chars()).take_while(|(x, y)| x == y).map(|(x, _)| x.len_utf8()).sum();
+    &a[..len]
+}
+
+/// `count  kind rule "original" -> "expected" shape after bigram: candidate
+/// suggestion (words)`, with at most five words.
+impl fmt::Display for ErrorPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rule = if self.rule.is_empty() { "(no rule)" } else { &self.rule };
+        let words: Vec<String> = self.words.iter().take(5).map(|(word, count)| format!("{word} {count}")).collect();
+        let more = if self.words.len() > 5 { ", ..." } else { "" };
+        write!(
+            f,
+            "{:>6}  {} {} {:?} -> {:?} {} after {}: {} {} ({}{})",
+            self.count,
+            self.kind.name(),
+            rule,
+            self.original,
+            self.expected,
+            self.shape,
+            self.bigram,
+            self.candidate.name(),
+            self.suggestion,
+            words.join(", "),
+            more
+        )
+    }
+}
+
+impl ErrorPattern {
+    /// The pattern as a JSON object with its fields, `words` as
+    /// `[word, count]` pairs.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "kind": self.kind.name(),
+            "rule": self.rule,
+            "original": self.original,
+            "expected": self.expected,
+            "shape": self.shape,
+            "bigram": self.bigram,
+            "count": self.count,
+            "words": self.words,
+            "candidate": self.candidate.name(),
+            "suggestion": self.suggestion,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+/// Python view of an [`Evaluation`], exposed as `_rust.Evaluation`: scores
+/// are dicts of the three counts and `precision`, `recall`, and `f1`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "Evaluation", frozen)]
+pub struct PyEvaluation {
+    inner: Evaluation,
+    /// The pairs evaluated, for [`mine_errors`].
+    texts: Vec<Pair>,
+}
+
+#[cfg(feature = "pyo3-backend")]
+fn scores_dict<'py>(py: Python<'py>, scores: &Scores) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("true_positives", scores.true_positives)?;
+    dict.set_item("false_positives", scores.false_positives)?;
+    dict.set_item("false_negatives", scores.false_negatives)?;
+    dict.set_item("precision", scores.precision())?;
+    dict.set_item("recall", scores.recall())?;
+    dict.set_item("f1", scores.f1())?;
+    Ok(dict)
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyEvaluation {
+    #[getter]
+    fn pairs(&self) -> usize {
+        self.inner.pairs
+    }
+
+    #[getter]
+    fn overall<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        scores_dict(py, &self.inner.overall)
+    }
+
+    /// The scores of each rule, by name.
+    #[getter]
+    fn rules<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for (rule, scores) in &self.inner.rules {
+            dict.set_item(rule, scores_dict(py, scores)?)?;
+        }
+        Ok(dict)
+    }
+
+    /// The scores of each confusion, with its `from` and `to`.
+    #[getter]
+    fn confusions<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let list = PyList::empty(py);
+        for ((from, to), scores) in &self.inner.confusions {
+            let dict = scores_dict(py, scores)?;
+            dict.set_item("from", from)?;
+            dict.set_item("to", to)?;
+            list.append(dict)?;
+        }
+        Ok(list)
+    }
+
+    /// The errors as dicts, with the [`EvalError`] fields and `kind` one of
+    /// `"spurious"`, `"missed"`, and `"wrong"`.
+    #[getter]
+    fn errors<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let list = PyList::empty(py);
+        for error in &self.inner.errors {
+            let dict = PyDict::new(py);
+            dict.set_item("kind", error.kind.name())?;
+            dict.set_item("pair", error.pair)?;
+            dict.set_item("position", error.position)?;
+            dict.set_item("original", &error.original)?;
+            dict.set_item("expected", &error.expected)?;
+            dict.set_item("actual", &error.actual)?;
+            dict.set_item("rules", &error.rules)?;
+            dict.set_item("context", &error.context)?;
+            list.append(dict)?;
+        }
+        Ok(list)
+    }
+
+    /// The patterns of the errors from [`mine_errors`], as dicts with the
+    /// [`ErrorPattern`] fields and `words` as `(word, count)` tuples.
+    fn mine_errors<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let list = PyList::empty(py);
+        for pattern in mine_errors(&self.inner, &self.texts) {
+            let dict = PyDict::new(py);
+            dict.set_item("kind", pattern.kind.name())?;
+            dict.set_item("rule", &pattern.rule)?;
+            dict.set_item("original", &pattern.original)?;
+            dict.set_item("expected", &pattern.expected)?;
+            dict.set_item("shape", &pattern.shape)?;
+            dict.set_item("bigram", &pattern.bigram)?;
+            dict.set_item("count", pattern.count)?;
+            dict.set_item("words", &pattern.words)?;
+            dict.set_item("candidate", pattern.candidate.name())?;
+            dict.set_item("suggestion", &pattern.suggestion)?;
+            list.append(dict)?;
+        }
+        Ok(list)
+    }
+
+    fn to_json(&self) -> String {
+        self.inner.to_json()
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        let overall = &self.inner.overall;
+        format!(
+            "Evaluation(pairs={}, precision={:.3}, recall={:.3}, f1={:.3}, errors={})",
+            self.inner.pairs,
+            overall.precision(),
+            overall.recall(),
+            overall.f1(),
+            self.inner.errors.len()
+        )
+    }
+}
+
+/// The loaders for Python, with pairs as `(raw, gold)` tuples and
+/// `ValueError` for malformed input.
+#[cfg(feature = "pyo3-backend")]
+fn py_pairs(pairs: io::Result<Vec<Pair>>) -> PyResult<Vec<(String, String)>> {
+    let pairs = pairs.map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    Ok(pairs.into_iter().map(|pair| (pair.raw, pair.gold)).collect())
+}
+
+/// The `(raw, gold)` pairs of the lines of two aligned texts.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "load_aligned")]
+pub fn py_load_aligned(raw: &str, gold: &str) -> PyResult<Vec<(String, String)>> {
+    py_pairs(load_aligned(raw, gold))
+}
+
+/// The `(raw, gold)` pairs of the lines of a TSV list of word pairs.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "load_tsv")]
+pub fn py_load_tsv(tsv: &str) -> PyResult<Vec<(String, String)>> {
+    py_pairs(load_tsv(tsv))
+}
+
+/// The `(raw, gold)` texts of a TEI document's `<choice>` readings.
+#[cfg(all(feature = "pyo3-backend", feature = "xml"))]
+#[pyfunction]
+#[pyo3(name = "load_tei")]
+pub fn py_load_tei(xml: &str) -> PyResult<(String, String)> {
+    let pair = load_tei(xml).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    Ok((pair.raw, pair.gold))
+}
+
+/// `pipeline` scored on `raw` against its hand-corrected form `gold`.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "evaluate")]
+pub fn py_evaluate(py: Python<'_>, pipeline: PyRef<'_, PyPipeline>, raw: &str, gold: &str) -> PyResult<PyEvaluation> {
+    let pipeline = &*pipeline;
+    let inner = pipeline.allow_threads(py, raw, || evaluate(&pipeline.inner, raw, gold));
+    let texts = vec![Pair::new(raw, gold)];
+    pipeline.checked(PyEvaluation { inner, texts })
+}
+
+/// `pipeline` scored on each `(raw, gold)` pair.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "evaluate_pairs")]
+pub fn py_evaluate_pairs(
+    py: Python<'_>,
+    pipeline: PyRef<'_, PyPipeline>,
+    pairs: Vec<(String, String)>,
+) -> PyResult<PyEvaluation> {
+    let pipeline = &*pipeline;
+    let pairs: Vec<Pair> = pairs.into_iter().map(|(raw, gold)| Pair::new(raw, gold)).collect();
+    // As `PyPipeline::allow_threads`, for the texts together
+    let bytes: usize = pairs.iter().map(|pair| pair.raw.len()).sum();
+    let inner = if pipeline.python_stages() || bytes < crate::RELEASE_GIL_BYTES {
+        evaluate_pairs(&pipeline.inner, &pairs)
+    } else {
+        py.allow_threads(|| evaluate_pairs(&pipeline.inner, &pairs))
+    };
+    pipeline.checked(PyEvaluation { inner, texts: pairs })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{FnNormalizer, Pipeline};
+    use crate::uv::UvNormalizer;
+
+    #[test]
+    fn test_scores() {
+        let scores = Scores {
+            true_positives: 3,
+            false_positives: 1,
+            false_negatives: 2,
+        };
+        assert_eq!((scores.precision(), scores.recall()), (0.75, 0.6));
+        assert!((scores.f1() - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!((Scores::default().precision(), Scores::default().f1()), (1.0, 1.0));
+        let wrong = Scores {
+            false_positives: 1,
+            false_negatives: 1,
+            ..Scores::default()
+        };
+        assert_eq!(wrong.f1(), 0.0);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let normalizer = FnNormalizer::new("toy", |text: &str| text.replace("uo", "vo").replace('ſ', "f"));
+        let evaluation = evaluate(&normalizer, "uolo ſum\nuir est", "volo sum\nvir est");
+        assert_eq!(evaluation.pairs, 1);
+        let counts = |s: &Scores| (s.true_positives, s.false_positives, s.false_negatives);
+        assert_eq!(counts(&evaluation.overall), (1, 1, 2));
+        assert_eq!(counts(&evaluation.confusions[&("u".into(), "v".into())]), (1, 0, 1));
+        assert_eq!(counts(&evaluation.confusions[&("ſ".into(), "s".into())]), (0, 0, 1));
+        assert_eq!(counts(&evaluation.confusions[&("ſ".into(), "f".into())]), (0, 1, 0));
+        // Changes without records of their own go to the normalizer's name
+        assert_eq!(counts(&evaluation.rules["toy"]), (1, 1, 1));
+
+        let kinds: Vec<(ErrorKind, &str, &str, &str)> = evaluation
+            .errors
+            .iter()
+            .map(|e| (e.kind, e.original.as_str(), e.expected.as_str(), e.actual.as_str()))
+            .collect();
+        assert_eq!(kinds, [(ErrorKind::Wrong, "ſ", "s", "f"), (ErrorKind::Missed, "u", "v", "u")]);
+        assert_eq!((evaluation.errors[0].position, evaluation.errors[0].context.as_str()), (5, "lo [ſ]um\n"));
+        assert_eq!(evaluation.errors[1].rules, Vec::<String>::new());
+
+        // A perfect output has no errors, however the texts align
+        let evaluation = evaluate(&normalizer, "uouo uo", "vovo vo");
+        assert_eq!(counts(&evaluation.overall), (3, 0, 0));
+        assert!(evaluation.errors.is_empty());
+        let same = evaluate(&normalizer, "ius", "ius");
+        assert_eq!((same.overall, same.overall.f1()), (Scores::default(), 1.0));
+    }
+
+    #[test]
+    fn test_rules_and_pairs() {
+        let pipeline = Pipeline::new().stage(UvNormalizer::new());
+        let pairs = [
+            Pair::new("uir uxor", "vir vxor"),
+            Pair::new("Iuuenis ſeruus", "Iuvenis servus"),
+            Pair::new("uia", "via"),
+        ];
+        let evaluation = evaluate_pairs(&pipeline, &pairs);
+        assert_eq!(evaluation.pairs, 3);
+        // Every rule is charged the u -> v edits it did not make
+        let uv = &evaluation.confusions[&("u".into(), "v".into())];
+        let gold = uv.true_positives + uv.false_negatives;
+        for (rule, scores) in &evaluation.rules {
+            assert!(rule.starts_with("uv: "), "{rule}");
+            assert_eq!(scores.true_positives + scores.false_negatives, gold, "{rule}");
+        }
+        let recall: f64 = evaluation.rules.values().map(Scores::recall).sum();
+        assert!((recall - uv.recall()).abs() < 1e-12);
+        let missed: Vec<(usize, &str)> = evaluation
+            .errors
+            .iter()
+            .map(|e| (e.pair, e.context.as_str()))
+            .collect();
+        assert_eq!(missed, [(0, "ir [u]xor"), (1, "is [ſ]eru")]);
+
+        let json: serde_json::Value = serde_json::from_str(&evaluation.to_json()).unwrap();
+        assert_eq!(json["pairs"], 3);
+        assert_eq!(json["errors"][0]["kind"], "missed");
+        assert_eq!(json["confusions"][0]["from"], "u");
+        let table = evaluation.to_string();
+        assert!(table.starts_with("precision recall"));
+        assert!(table.contains("overall\n") && table.contains("\"u\" -> \"v\"\n"));
+        assert!(table.ends_with("1:8: missed \"is [ſ]eru\" -> \"s\""));
+    }
+
+    #[test]
+    fn test_mine_errors() {
+        let normalizer = UvNormalizer::new().vocalic_words(["seruus"]);
+        let pairs = [
+            Pair::new("uxor uxoris seruus", "vxor vxoris servus"),
+            Pair::new("Uxorem uolo", "Vxorem volo"),
+            Pair::new("uult uesper", "vult vesper"),
+        ];
+        let evaluation = evaluate_pairs(&normalizer, &pairs);
+        let patterns = mine_errors(&evaluation, &pairs);
+        let summary: Vec<(&str, &str, usize, Candidate, &str)> = patterns
+            .iter()
+            .map(|p| (p.shape.as_str(), p.bigram.as_str(), p.count, p.candidate, p.suggestion.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [("#[u]C", "#", 3, Candidate::Stem, "uxor"), ("C[u]V", "er", 1, Candidate::Word, "seruus")]
+        );
+        let stem = &patterns[0];
+        assert_eq!((stem.kind, stem.rule.as_str()), (ErrorKind::Missed, ""));
+        assert_eq!(stem.words, [("uxor".to_string(), 1), ("uxorem".into(), 1), ("uxoris".into(), 1)]);
+        let line = stem.to_string();
+        assert!(line.ends_with(r#"missed (no rule) "u" -> "v" #[u]C after #: stem uxor (uxor 1, uxorem 1, uxoris 1)"#));
+        assert_eq!(stem.to_json()["words"][0], serde_json::json!(["uxor", 1]));
+
+        // Unrelated words with the same context point at a rule
+        let pairs = [Pair::new("uxor", "vxor"), Pair::new("ulmus", "vlmus")];
+        let patterns = mine_errors(&evaluate_pairs(&normalizer, &pairs), &pairs);
+        assert_eq!((patterns.len(), patterns[0].candidate), (1, Candidate::Rule));
+        assert_eq!(patterns[0].suggestion, "#[u]C after #");
+    }
+
+    #[test]
+    fn test_loaders() {
+        let pairs = load_aligned("uir\nuxor\n", "vir\nvxor").unwrap();
+        assert_eq!(pairs, [Pair::line("uir", "vir", 0), Pair::line("uxor", "vxor", 1)]);
+        assert_eq!(pairs[1].name, "2");
+        let error = load_aligned("uir\nuxor", "vir").unwrap_err();
+        assert_eq!(error.to_string(), "the raw text has 2 lines and the gold text 1");
+
+        let pairs = load_tsv("# raw\tgold\nuir\tvir\n\nſeruus\tservus\n").unwrap();
+        assert_eq!(pairs, [Pair::line("uir", "vir", 1), Pair::line("ſeruus", "servus", 3)]);
+        let error = load_tsv("uir\tvir\nuxor\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: expected 2 tab-separated columns, found 1");
+        let evaluation = evaluate_pairs(&crate::uv::UvNormalizer::new(), &pairs);
+        assert_eq!(evaluation.overall.true_positives, 2);
+        assert_eq!(evaluation.errors[0].to_string(), "0: missed \"[ſ]eru\" -> \"s\"");
+    }
+
+    #[cfg(feature = "xml")]
+    #[test]
+    fn test_load_tei() {
+        let tei = concat!(
+            "<TEI><teiHeader>uia</teiHeader>",
+            "<text><l><choice><orig>Uirum</orig><reg>Virum</reg></choice> cano</l></text></TEI>",
+        );
+        assert_eq!(load_tei(tei).unwrap(), Pair::new("Uirum cano", "Virum cano"));
+        assert_eq!(load_tei("<l>uia").unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,139 @@
+//! Grapheme clusters, so combining marks stay with their letters.
+//!
+//! Text from epigraphic and critical editions spells macrons, breves, and
+//! underdots as combining marks (`u\u{0304}`), which are separate `char`s
+//! from the letters they sit on. Rules that look at the letters around a
+//! position, words found by runs of letters, and contexts cut a fixed
+//! number of characters wide would all split such a letter from its mark.
+//! These helpers follow the extended grapheme clusters of Unicode Standard
+//! Annex #29 instead.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Whether `c` extends the grapheme cluster of a letter before it: a
+/// combining mark, a joiner, a variation selector, or a spacing mark.
+pub(crate) fn is_extender(c: char) -> bool {
+    if c.is_ascii() {
+        return false;
+    }
+    let mut buf = [0u8; 5];
+    buf[0] = b'a';
+    let len = 1 + c.encode_utf8(&mut buf[1..]).len();
+    let pair = std::str::from_utf8(&buf[..len]).expect("a letter and a char are valid UTF-8");
+    pair.graphemes(true).nth(1).is_none()
+}
+
+/// `chars` without the marks that extend them, with the index in `chars` of
+/// each char kept, so rules can look at neighboring letters across marks.
+pub(crate) fn bases(chars: &[char]) -> (Vec<char>, Vec<usize>) {
+    chars
+        .iter()
+        .enumerate()
+        .filter(|&(_, &c)| !is_extender(c))
+        .map(|(i, &c)| (c, i))
+        .unzip()
+}
+
+/// `start` moved back to the beginning of its grapheme cluster in `chars`.
+pub(crate) fn cluster_start(chars: &[char], mut start: usize) -> usize {
+    while start > 0 && start < chars.len() && is_extender(chars[start]) {
+        start -= 1;
+    }
+    start
+}
+
+/// `end` moved forward past any marks extending the cluster before it.
+pub(crate) fn cluster_end(chars: &[char], mut end: usize) -> usize {
+    while end > 0 && end < chars.len() && is_extender(chars[end]) {
+        end += 1;
+    }
+    end
+}
+
+/// `normalize` applied to the letters of `word` without their marks, with
+/// each mark put back on its letter, so rules written for plain letters
+/// read *ca\u{0304}efar* as *caefar*. Where the rules changed the number of
+/// letters (`ﬁ` → `fi`), the marks of the letters replaced follow their
+/// replacement.
+pub(crate) fn on_bases(word: &str, normalize: impl FnOnce(&str) -> String) -> String {
+    if !word.chars().any(is_extender) {
+        return normalize(word);
+    }
+    // Each letter with the marks on it, after any marks with no letter
+    let mut leading = String::new();
+    let mut clusters: Vec<(char, String)> = Vec::new();
+    for c in word.chars() {
+        match clusters.last_mut() {
+            Some((_, marks)) if is_extender(c) => marks.push(c),
+            None if is_extender(c) => leading.push(c),
+            _ => clusters.push((c, String::new())),
+        }
+    }
+    let letters: String = clusters.iter().map(|&(c, _)| c).collect();
+    let to: Vec<char> = normalize(&letters).chars().collect();
+
+    let prefix = clusters.iter().zip(&to).take_while(|((c, _), to)| c == *to).count();
+    let suffix = clusters[prefix..]
+        .iter()
+        .rev()
+        .zip(to[prefix..].iter().rev())
+        .take_while(|((c, _), to)| c == *to)
+        .count();
+    let (from_middle, to_middle) = (prefix..clusters.len() - suffix, prefix..to.len() - suffix);
+    let mut result = leading;
+    for (c, marks) in &clusters[..prefix] {
+        result.push(*c);
+        result.push_str(marks);
+    }
+    if from_middle.len() == to_middle.len() {
+        for ((_, marks), &c) in clusters[from_middle.clone()].iter().zip(&to[to_middle]) {
+            result.push(c);
+            result.push_str(marks);
+        }
+    } else {
+        result.extend(&to[to_middle]);
+        result.extend(clusters[from_middle.clone()].iter().flat_map(|(_, marks)| marks.chars()));
+    }
+    for (c, marks) in &clusters[from_middle.end..] {
+        result.push(*c);
+        result.push_str(marks);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extenders() {
+        for c in ['\u{0304}', '\u{0323}', '\u{0305}', '\u{200D}', '\u{FE0F}', '\u{1DC4}', '\u{20E3}'] {
+            assert!(is_extender(c), "{:?}", c);
+        }
+        for c in ['a', 'ā', ' ', '\n', '-', 'ſ', '\u{00AD}'] {
+            assert!(!is_extender(c), "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn test_bases_and_clusters() {
+        let chars: Vec<char> = "\u{0304}u\u{0304}ua e\u{0323}\u{0301}t".chars().collect();
+        let (letters, indices) = bases(&chars);
+        assert_eq!(letters.iter().collect::<String>(), "uua et");
+        assert_eq!(indices, [1, 3, 4, 5, 6, 9]);
+        assert_eq!((cluster_start(&chars, 2), cluster_end(&chars, 2)), (1, 3));
+        assert_eq!((cluster_start(&chars, 7), cluster_end(&chars, 7)), (6, 9));
+        assert_eq!((cluster_start(&chars, 4), cluster_end(&chars, 4)), (4, 4));
+        assert_eq!(cluster_end(&chars, chars.len()), chars.len());
+    }
+
+    #[test]
+    fn test_on_bases() {
+        let f_to_s = |letters: &str| letters.replace('f', "s");
+        assert_eq!(on_bases("ca\u{0304}efar", f_to_s), "ca\u{0304}esar");
+        assert_eq!(on_bases("fu\u{0304}f\u{0323}", f_to_s), "su\u{0304}s\u{0323}");
+        assert_eq!(on_bases("\u{0304}f", f_to_s), "\u{0304}s");
+        assert_eq!(on_bases("fuit", f_to_s), "suit");
+        assert_eq!(on_bases("\u{FB01}\u{0304}lius", |letters| letters.replace('\u{FB01}', "fi")), "fi\u{0304}lius");
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+/// A list of attested Latin wordforms, optionally with corpus frequencies.
+///
+/// Used by long-s Pass 3 to prefer the f- or s-spelling of a word that is
+/// actually attested. Keys are stored lowercased.
+#[derive(Debug, Clone, Default)]
+pub struct Lexicon {
+    words: HashMap<String, u64>,
+}
+
+impl Lexicon {
+    /// A lexicon of words without frequencies (each counts as 1).
+    pub fn from_words<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self::from_counts(words.into_iter().map(|w| (w.as_ref().to_string(), 1)))
+    }
+
+    /// A lexicon from `(word, frequency)` pairs. Repeated words (including
+    /// case variants) have their frequencies summed.
+    pub fn from_counts<I, S>(counts: I) -> Self
+    where
+        I: IntoIterator<Item = (S, u64)>,
+        S: AsRef<str>,
+    {
+        let mut words = HashMap::new();
+        for (word, count) in counts {
+            *words.entry(word.as_ref().to_lowercase()).or_insert(0) += count;
+        }
+        Lexicon { words }
+    }
+
+    /// Load a lexicon file.
+    ///
+    /// `.json` files hold either an array of words or an object mapping words
+    /// to frequencies. Other files have one word per line, optionally followed
+    /// by whitespace and a frequency; `#` starts a comment.
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            io::Error::new(e.kind(), format!("Failed to read lexicon {}: {}", path.display(), e))
+        })?;
+        let invalid = |msg: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Failed to parse lexicon {}: {}", path.display(), msg),
+            )
+        };
+
+        if path.extension().is_some_and(|ext| ext == "json") {
+            let value: serde_json::Value = serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+            return match value {
+                serde_json::Value::Array(_) => {
+                    let words: Vec<String> = serde_json::from_value(value).map_err(|e| invalid(e.to_string()))?;
+                    Ok(Self::from_words(words))
+                }
+                serde_json::Value::Object(_) => {
+                    let counts: HashMap<String, u64> = serde_json::from_value(value).map_err(|e| invalid(e.to_string()))?;
+                    Ok(Self::from_counts(counts))
+                }
+                _ => Err(invalid("expected an array or object".to_string())),
+            };
+        }
+
+        let mut counts = Vec::new();
+        for (lineno, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("");
+            let mut fields = line.split_whitespace();
+            let Some(word) = fields.next() else {
+                continue;
+            };
+            let count = match fields.next() {
+                Some(count) => count
+                    .parse()
+                    .map_err(|_| invalid(format!("line {}: bad frequency {:?}", lineno + 1, count)))?,
+                None => 1,
+            };
+            counts.push((word.to_string(), count));
+        }
+        Ok(Self::from_counts(counts))
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains_key(&word.to_lowercase())
+    }
+
+    /// Frequency of `word`, or 0 if it is not in the lexicon.
+    pub fn frequency(&self, word: &str) -> u64 {
+        self.words.get(&word.to_lowercase()).copied().unwrap_or(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// f-initial wordforms attested at least `min_frequency` times, sorted:
+    /// a reproducible long-s Pass 2 allowlist of genuine f-words.
+    pub fn f_allowlist(&self, min_frequency: u64) -> Vec<String> {
+        let mut words: Vec<String> = self
+            .words
+            .iter()
+            .filter(|&(word, &count)| count >= min_frequency && word.starts_with('f'))
+            .map(|(word, _)| word.clone())
+            .collect();
+        words.sort();
+        words
+    }
+
+    /// f-readings of `fu`/`fe`/`fi` words (the ones Pass 2 decides) that
+    /// never occur although their s-reading is attested at least
+    /// `min_frequency` times, sorted: *funt* for *sunt*, but not *fui* for
+    /// *sui*. These are safe for a Pass 2 denylist.
+    pub fn f_denylist(&self, min_frequency: u64) -> Vec<String> {
+        let mut words: Vec<String> = self
+            .words
+            .iter()
+            .filter(|&(word, &count)| count >= min_frequency && PASS2_PREFIXES.iter().any(|p| word.starts_with(p)))
+            .map(|(word, _)| format!("f{}", &word[1..]))
+            .filter(|f_word| !self.words.contains_key(f_word))
+            .collect();
+        words.sort();
+        words
+    }
+}
+
+/// Word-initial s-pairs whose f-reading long-s Pass 2 decides.
+const PASS2_PREFIXES: &[&str] = &["su", "se", "si"];
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_words() {
+        let lexicon = Lexicon::from_words(["Causa", "causa", "mensis"]);
+        assert_eq!(lexicon.len(), 2);
+        assert!(lexicon.contains("CAUSA"));
+        assert_eq!(lexicon.frequency("causa"), 2);
+        assert_eq!(lexicon.frequency("caufa"), 0);
+    }
+
+    #[test]
+    fn test_word_lists() {
+        let lexicon = Lexicon::from_counts([
+            ("fuit", 50),
+            ("sui", 30),
+            ("fui", 20),
+            ("sunt", 100),
+            ("sed", 80),
+            ("fidem", 2),
+            ("sine", 3),
+            ("est", 200),
+        ]);
+        assert_eq!(lexicon.f_allowlist(5), vec!["fui", "fuit"]);
+        assert_eq!(lexicon.f_allowlist(1), vec!["fidem", "fui", "fuit"]);
+        assert_eq!(lexicon.f_denylist(5), vec!["fed", "funt"]);
+        assert_eq!(lexicon.f_denylist(1), vec!["fed", "fine", "funt"]);
+    }
+
+    #[test]
+    fn test_from_file() {
+        let dir = std::env::temp_dir().join(format!("lexicon_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let txt = dir.join("forms.txt");
+        let json = dir.join("forms.json");
+        let words = dir.join("words.json");
+        std::fs::write(&txt, "# wordforms\nsunt 120\nfuga\t8\n\nmensis  # no count\n").unwrap();
+        std::fs::write(&json, r#"{"esse": 40}"#).unwrap();
+        std::fs::write(&words, r#"["posse"]"#).unwrap();
+
+        let lexicon = Lexicon::from_file(&txt).unwrap();
+        assert_eq!(lexicon.frequency("sunt"), 120);
+        assert_eq!(lexicon.frequency("fuga"), 8);
+        assert_eq!(lexicon.frequency("mensis"), 1);
+        assert_eq!(Lexicon::from_file(&json).unwrap().frequency("esse"), 40);
+        assert!(Lexicon::from_file(&words).unwrap().contains("posse"));
+
+        std::fs::write(&txt, "sunt many\n").unwrap();
+        let err = Lexicon::from_file(&txt).unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
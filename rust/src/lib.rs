@@ -1,22 +1,431 @@
+pub mod changes;
+pub mod diagnostics;
 pub mod uv;
+pub mod uv_model;
 pub mod long_s;
+pub mod lexicon;
+pub mod ngrams;
+pub mod ligatures;
+pub mod macrons;
+pub mod abbrev;
+pub mod nasal;
+pub mod medieval;
+pub mod diphthongs;
+pub mod assimilation;
+pub mod styles;
+pub mod unicode;
+pub mod dehyphenate;
+pub mod cleanup;
+pub mod ocr;
+pub mod numerals;
+pub mod loanwords;
+pub mod accents;
+pub mod recase;
+pub mod names;
+pub mod epigraphic;
+pub mod syllables;
+pub mod sentences;
+pub mod enclitics;
+pub mod pipeline;
+pub mod config;
+pub mod registry;
+pub mod diff;
+pub mod patch;
+pub mod protect;
+pub mod regression;
+pub mod eval;
+pub mod profile;
+pub mod cache;
+pub mod telemetry;
+mod case;
+mod graphemes;
+mod scan;
+#[cfg(test)]
+mod testing;
+#[cfg(feature = "xml")]
+pub mod xml;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 #[cfg(feature = "pyo3-backend")]
 use pyo3::prelude::*;
 
+/// Texts of at least this many bytes are processed with the GIL released.
+/// Below it the work is quicker than getting the GIL back from a busy
+/// Python thread, which can take a whole switch interval (5 ms).
+#[cfg(feature = "pyo3-backend")]
+pub(crate) const RELEASE_GIL_BYTES: usize = 1 << 16;
+
+/// `f()`, run with the GIL released if `text` is long enough for that to
+/// pay, so other Python threads run meanwhile.
+#[cfg(feature = "pyo3-backend")]
+pub(crate) fn allow_threads<T, F>(py: Python<'_>, text: &str, f: F) -> T
+where
+    F: pyo3::marker::Ungil + FnOnce() -> T,
+    T: pyo3::marker::Ungil,
+{
+    if text.len() >= RELEASE_GIL_BYTES {
+        py.allow_threads(f)
+    } else {
+        f()
+    }
+}
+
 #[cfg(feature = "pyo3-backend")]
 #[pymodule]
 fn _rust(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    // Detailed results
+    m.add_class::<changes::PyChangeRecord>()?;
+    m.add_class::<changes::PyDetailedResult>()?;
+    m.add_class::<changes::PyChangeSummary>()?;
+    m.add_class::<diagnostics::PyWarning>()?;
+
     // U/V normalization functions
     m.add_function(wrap_pyfunction!(uv::normalize_uv, m)?)?;
     m.add_function(wrap_pyfunction!(uv::normalize_uv_char, m)?)?;
     m.add_function(wrap_pyfunction!(uv::normalize_uv_detailed, m)?)?;
+    m.add_class::<uv::PyUvNormalizer>()?;
+    m.add_class::<uv_model::PyUvModel>()?;
 
     // Long-s normalization functions
     m.add_function(wrap_pyfunction!(long_s::normalize_long_s_word_pass1, m)?)?;
     m.add_function(wrap_pyfunction!(long_s::normalize_long_s_word_pass2, m)?)?;
     m.add_function(wrap_pyfunction!(long_s::normalize_long_s_word_full, m)?)?;
     m.add_function(wrap_pyfunction!(long_s::normalize_long_s_text_full, m)?)?;
+    m.add_function(wrap_pyfunction!(long_s::normalize_long_s_detailed, m)?)?;
+    m.add_function(wrap_pyfunction!(long_s::suggest_long_s, m)?)?;
+    m.add_function(wrap_pyfunction!(long_s::py_decompose_ligatures, m)?)?;
+    m.add_function(wrap_pyfunction!(long_s::py_archaize, m)?)?;
+    m.add_class::<long_s::PyLongSNormalizer>()?;
+    m.add_class::<long_s::PyWordChange>()?;
+
+    // Vowel ligatures
+    m.add_function(wrap_pyfunction!(ligatures::py_expand_ligatures, m)?)?;
+    m.add_function(wrap_pyfunction!(ligatures::py_expand_ligatures_detailed, m)?)?;
+    m.add_function(wrap_pyfunction!(ligatures::py_contract_ligatures, m)?)?;
+
+    // Macrons
+    m.add_function(wrap_pyfunction!(macrons::py_strip_macrons, m)?)?;
+    m.add_function(wrap_pyfunction!(macrons::py_has_macrons, m)?)?;
+    m.add_function(wrap_pyfunction!(macrons::py_transfer_macrons, m)?)?;
+
+    // Abbreviations
+    m.add_function(wrap_pyfunction!(abbrev::py_expand_abbreviations, m)?)?;
+    m.add_class::<abbrev::PyAbbreviations>()?;
+    m.add_function(wrap_pyfunction!(nasal::py_expand_nasal_bars, m)?)?;
+    m.add_function(wrap_pyfunction!(nasal::py_expand_nasal_bars_detailed, m)?)?;
+
+    // Medieval orthography
+    m.add_function(wrap_pyfunction!(medieval::py_normalize_medieval, m)?)?;
+    m.add_class::<medieval::PyMedievalNormalizer>()?;
+
+    // Diphthongs
+    m.add_function(wrap_pyfunction!(diphthongs::py_flatten_diphthongs, m)?)?;
+    m.add_class::<diphthongs::PyDiphthongRestorer>()?;
+
+    // Prefix assimilation
+    m.add_function(wrap_pyfunction!(assimilation::py_normalize_assimilation, m)?)?;
+    m.add_function(wrap_pyfunction!(assimilation::py_normalize_assimilation_detailed, m)?)?;
+
+    // Style profiles
+    m.add_function(wrap_pyfunction!(styles::py_standardize, m)?)?;
+    m.add_function(wrap_pyfunction!(styles::py_style_profiles, m)?)?;
+
+    // Unicode normalization
+    m.add_function(wrap_pyfunction!(unicode::py_normalize_unicode, m)?)?;
+    m.add_function(wrap_pyfunction!(unicode::py_normalize_unicode_detailed, m)?)?;
+
+    // Dehyphenation
+    m.add_function(wrap_pyfunction!(dehyphenate::py_dehyphenate, m)?)?;
+    m.add_class::<dehyphenate::PyDehyphenator>()?;
+
+    // Editorial markup
+    m.add_function(wrap_pyfunction!(cleanup::py_clean_markup, m)?)?;
+    m.add_class::<cleanup::PyMarkupCleaner>()?;
+
+    // OCR confusions
+    m.add_function(wrap_pyfunction!(ocr::py_correct_ocr, m)?)?;
+    m.add_class::<ocr::PyOcrCorrector>()?;
+
+    // Roman numerals
+    m.add_function(wrap_pyfunction!(numerals::py_find_roman_numerals, m)?)?;
+    m.add_function(wrap_pyfunction!(numerals::py_parse_roman, m)?)?;
+    m.add_function(wrap_pyfunction!(numerals::py_roman_to_arabic, m)?)?;
+
+    // Greek loan spellings
+    m.add_function(wrap_pyfunction!(loanwords::py_normalize_loanwords, m)?)?;
+    m.add_function(wrap_pyfunction!(loanwords::py_normalize_loanwords_detailed, m)?)?;
 
+    // Accents
+    m.add_function(wrap_pyfunction!(accents::py_strip_accents, m)?)?;
+    m.add_function(wrap_pyfunction!(accents::py_strip_accents_detailed, m)?)?;
+
+    // Recasing
+    m.add_function(wrap_pyfunction!(recase::py_recase, m)?)?;
+    m.add_class::<recase::PyRecaser>()?;
+
+    // Proper-name protection
+    m.add_class::<names::PyNameGuard>()?;
+
+    // Epigraphic normalization
+    m.add_function(wrap_pyfunction!(epigraphic::py_normalize_epigraphic, m)?)?;
+    m.add_class::<epigraphic::PyEpigraphicNormalizer>()?;
+
+    // Syllabification
+    m.add_function(wrap_pyfunction!(syllables::py_syllabify, m)?)?;
+    m.add_function(wrap_pyfunction!(syllables::py_syllable_boundaries, m)?)?;
+    m.add_function(wrap_pyfunction!(syllables::py_syllabify_text, m)?)?;
+
+    // Sentence segmentation
+    m.add_function(wrap_pyfunction!(sentences::py_split_sentences, m)?)?;
+
+    // Enclitics
+    m.add_function(wrap_pyfunction!(enclitics::py_split_enclitic, m)?)?;
+    m.add_function(wrap_pyfunction!(enclitics::py_split_enclitics, m)?)?;
+    m.add_class::<enclitics::PyEncliticSplitter>()?;
+
+    // Pipelines
+    m.add_function(wrap_pyfunction!(pipeline::py_pipeline_stages, m)?)?;
+    m.add_function(wrap_pyfunction!(pipeline::py_normalize_with_alignment, m)?)?;
+    m.add_class::<pipeline::PyPipeline>()?;
+    m.add_class::<pipeline::PyCallbackStage>()?;
+    m.add_class::<pipeline::PyCancelToken>()?;
+    m.add_class::<pipeline::PyNormalizeIter>()?;
+
+    // Diffs
+    m.add_function(wrap_pyfunction!(diff::py_unified_diff, m)?)?;
+    m.add_function(wrap_pyfunction!(diff::py_word_diff, m)?)?;
+
+    // Patches
+    m.add_function(wrap_pyfunction!(patch::py_apply_patch, m)?)?;
+
+    // Evaluation
+    m.add_function(wrap_pyfunction!(eval::py_evaluate, m)?)?;
+    m.add_function(wrap_pyfunction!(eval::py_evaluate_pairs, m)?)?;
+    m.add_class::<eval::PyEvaluation>()?;
+    m.add_function(wrap_pyfunction!(eval::py_load_aligned, m)?)?;
+    m.add_function(wrap_pyfunction!(eval::py_load_tsv, m)?)?;
+    #[cfg(feature = "xml")]
+    m.add_function(wrap_pyfunction!(eval::py_load_tei, m)?)?;
+
+    // Corpus profiles
+    m.add_function(wrap_pyfunction!(profile::py_profile_text, m)?)?;
+    m.add_function(wrap_pyfunction!(profile::py_profile_texts, m)?)?;
+    m.add_function(wrap_pyfunction!(profile::py_recommend_pipeline, m)?)?;
+    m.add_class::<profile::PyCorpusProfile>()?;
+    m.add_class::<config::PyPipelineConfig>()?;
+
+    // XML
+    #[cfg(feature = "xml")]
+    m.add_function(wrap_pyfunction!(xml::py_normalize_xml, m)?)?;
+    #[cfg(feature = "arrow")]
+    m.add_class::<arrow::PyArrowStrings>()?;
+
+    // N-gram model loading
+    m.add_class::<ngrams::PyNgramData>()?;
+    m.add_function(wrap_pyfunction!(ngrams::py_ngram_frequency, m)?)?;
+    m.add_function(wrap_pyfunction!(ngrams::py_available_models, m)?)?;
+    m.add_function(wrap_pyfunction!(ngrams::py_preload, m)?)?;
+
+    // Submodules, with the Rust names; `dehyphenate` and `recase` are taken
+    // by functions, so those modules are `dehyphenation` and `recasing`
+    submodule(
+        m,
+        "changes",
+        &[
+            ("ChangeRecord", "ChangeRecord"),
+            ("DetailedResult", "DetailedResult"),
+            ("NormalizerWarning", "NormalizerWarning"),
+            ("ChangeSummary", "ChangeSummary"),
+        ],
+    )?;
+    submodule(
+        m,
+        "uv",
+        &[
+            ("normalize", "normalize_uv"),
+            ("normalize_char", "normalize_uv_char"),
+            ("normalize_detailed", "normalize_uv_detailed"),
+            ("UvNormalizer", "UvNormalizer"),
+            ("UvModel", "UvModel"),
+        ],
+    )?;
+    submodule(
+        m,
+        "long_s",
+        &[
+            ("pass1", "normalize_long_s_word_pass1"),
+            ("pass2", "normalize_long_s_word_pass2"),
+            ("normalize_word", "normalize_long_s_word_full"),
+            ("normalize_text", "normalize_long_s_text_full"),
+            ("normalize_detailed", "normalize_long_s_detailed"),
+            ("suggest", "suggest_long_s"),
+            ("decompose_ligatures", "decompose_ligatures"),
+            ("archaize", "archaize"),
+            ("LongSNormalizer", "LongSNormalizer"),
+            ("WordChange", "WordChange"),
+        ],
+    )?;
+    submodule(
+        m,
+        "ligatures",
+        &[
+            ("expand", "expand_ligatures"),
+            ("expand_detailed", "expand_ligatures_detailed"),
+            ("contract", "contract_ligatures"),
+        ],
+    )?;
+    submodule(
+        m,
+        "macrons",
+        &[
+            ("strip_macrons", "strip_macrons"),
+            ("has_macrons", "has_macrons"),
+            ("transfer_macrons", "transfer_macrons"),
+        ],
+    )?;
+    submodule(m, "abbrev", &[("expand", "expand_abbreviations"), ("Abbreviations", "Abbreviations")])?;
+    submodule(m, "nasal", &[("expand", "expand_nasal_bars"), ("expand_detailed", "expand_nasal_bars_detailed")])?;
+    submodule(
+        m,
+        "medieval",
+        &[("normalize_text", "normalize_medieval"), ("MedievalNormalizer", "MedievalNormalizer")],
+    )?;
+    submodule(m, "diphthongs", &[("flatten", "flatten_diphthongs"), ("DiphthongRestorer", "DiphthongRestorer")])?;
+    submodule(
+        m,
+        "assimilation",
+        &[("normalize", "normalize_assimilation"), ("normalize_detailed", "normalize_assimilation_detailed")],
+    )?;
+    submodule(m, "styles", &[("standardize", "standardize"), ("profiles", "style_profiles")])?;
+    submodule(
+        m,
+        "unicode",
+        &[("normalize", "normalize_unicode"), ("normalize_detailed", "normalize_unicode_detailed")],
+    )?;
+    submodule(m, "dehyphenation", &[("dehyphenate", "dehyphenate"), ("Dehyphenator", "Dehyphenator")])?;
+    submodule(m, "cleanup", &[("clean", "clean_markup"), ("MarkupCleaner", "MarkupCleaner")])?;
+    submodule(m, "ocr", &[("correct", "correct_ocr"), ("OcrCorrector", "OcrCorrector")])?;
+    submodule(
+        m,
+        "numerals",
+        &[
+            ("find_roman_numerals", "find_roman_numerals"),
+            ("parse", "parse_roman"),
+            ("to_arabic", "roman_to_arabic"),
+        ],
+    )?;
+    submodule(
+        m,
+        "loanwords",
+        &[("normalize", "normalize_loanwords"), ("normalize_detailed", "normalize_loanwords_detailed")],
+    )?;
+    submodule(
+        m,
+        "accents",
+        &[("strip_accents", "strip_accents"), ("strip_accents_detailed", "strip_accents_detailed")],
+    )?;
+    submodule(m, "recasing", &[("recase", "recase"), ("Recaser", "Recaser")])?;
+    submodule(m, "names", &[("NameGuard", "NameGuard")])?;
+    submodule(
+        m,
+        "epigraphic",
+        &[("normalize_text", "normalize_epigraphic"), ("EpigraphicNormalizer", "EpigraphicNormalizer")],
+    )?;
+    submodule(
+        m,
+        "syllables",
+        &[
+            ("syllabify", "syllabify"),
+            ("boundaries", "syllable_boundaries"),
+            ("syllabify_text", "syllabify_text"),
+        ],
+    )?;
+    submodule(m, "sentences", &[("split", "split_sentences")])?;
+    submodule(
+        m,
+        "enclitics",
+        &[
+            ("split", "split_enclitic"),
+            ("split_text", "split_enclitics"),
+            ("EncliticSplitter", "EncliticSplitter"),
+        ],
+    )?;
+    submodule(
+        m,
+        "pipeline",
+        &[
+            ("stages", "pipeline_stages"),
+            ("normalize_with_alignment", "normalize_with_alignment"),
+            ("Pipeline", "Pipeline"),
+            ("CallbackStage", "CallbackStage"),
+            ("CancelToken", "CancelToken"),
+            ("NormalizeIter", "NormalizeIter"),
+        ],
+    )?;
+    submodule(m, "diff", &[("unified", "unified_diff"), ("words", "word_diff")])?;
+    submodule(m, "patch", &[("apply_patch", "apply_patch")])?;
+    submodule(
+        m,
+        "eval",
+        &[
+            ("evaluate", "evaluate"),
+            ("evaluate_pairs", "evaluate_pairs"),
+            ("Evaluation", "Evaluation"),
+            ("load_aligned", "load_aligned"),
+            ("load_tsv", "load_tsv"),
+            #[cfg(feature = "xml")]
+            ("load_tei", "load_tei"),
+        ],
+    )?;
+    submodule(
+        m,
+        "profile",
+        &[
+            ("profile", "profile_text"),
+            ("profile_texts", "profile_texts"),
+            ("recommend_pipeline", "recommend_pipeline"),
+            ("CorpusProfile", "CorpusProfile"),
+            ("PipelineConfig", "PipelineConfig"),
+        ],
+    )?;
+    #[cfg(feature = "xml")]
+    submodule(m, "xml", &[("normalize", "normalize_xml")])?;
+    #[cfg(feature = "arrow")]
+    submodule(m, "arrow", &[("ArrowStrings", "ArrowStrings")])?;
+    submodule(
+        m,
+        "ngrams",
+        &[
+            ("NgramData", "NgramData"),
+            ("frequency", "ngram_frequency"),
+            ("available_models", "available_models"),
+            ("preload", "preload"),
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Adds `_rust.<name>`, mirroring a Rust module: `items` are `(name in the
+/// submodule, name in _rust)` pairs, so `_rust.uv.normalize` is
+/// `_rust.normalize_uv`. `name` must not be taken in `_rust`. The submodule also goes in `sys.modules`, so
+/// `import latincy_preprocess._rust.uv` works.
+#[cfg(feature = "pyo3-backend")]
+fn submodule(m: &Bound<'_, PyModule>, name: &str, items: &[(&str, &str)]) -> PyResult<()> {
+    let py = m.py();
+    let qualified = format!("latincy_preprocess._rust.{name}");
+    let submodule = PyModule::new(py, &qualified)?;
+    for (alias, item) in items {
+        submodule.add(*alias, m.getattr(*item)?)?;
+    }
+    submodule.add("__all__", items.iter().map(|(alias, _)| *alias).collect::<Vec<_>>())?;
+    debug_assert!(!m.hasattr(name)?, "_rust.{name} is taken");
+    m.add(name, &submodule)?;
+    py.import("sys")?.getattr("modules")?.set_item(qualified, submodule)?;
     Ok(())
 }
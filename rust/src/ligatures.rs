@@ -0,0 +1,291 @@
+//! Expansion of the Latin vowel ligatures `æ`, `œ`, and e caudata `ę`.
+//!
+//! Early modern Latin prints write the diphthongs *ae* and *oe* as ligatures
+//! (*cœlum*, *præmium*) or as e caudata (*quę*). Tokenizers and lexicons
+//! expect the two letters, so these are expanded before anything else; the
+//! typographic `ﬁ`/`ﬂ`/`ﬅ` ligatures are handled by
+//! [`crate::long_s::decompose_ligatures`].
+
+use crate::changes::{self, ChangeRecord};
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+
+/// The vowel ligatures and their lowercase expansions.
+pub const VOWEL_LIGATURES: &[(char, &str)] = &[
+    ('æ', "ae"),
+    ('Æ', "ae"),
+    ('œ', "oe"),
+    ('Œ', "oe"),
+    ('ę', "ae"),
+    ('Ę', "ae"),
+];
+
+/// Words (lowercase prefixes) where *ae*/*oe* is two syllables, not a
+/// diphthong, and so is never contracted: *aer*, *poeta*, *poema*, names in
+/// *-ael*.
+const HIATUS_PREFIXES: &[&str] = &["aer", "poem", "poet", "israel", "michael", "raphael"];
+
+/// How a capital ligature is expanded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Capitals {
+    /// `AE` inside an all-caps word (*CÆSAR* → *CAESAR*), otherwise `Ae`
+    /// (default).
+    #[default]
+    Auto,
+    /// Always `Ae`.
+    Title,
+    /// Always `AE`.
+    Upper,
+}
+
+fn expansion(c: char) -> Option<&'static str> {
+    VOWEL_LIGATURES.iter().find(|&&(lig, _)| lig == c).map(|&(_, letters)| letters)
+}
+
+/// Whether the word around `chars[i]` is written in capitals: a neighbouring
+/// letter is uppercase and none is lowercase.
+fn in_capitals(chars: &[char], i: usize) -> bool {
+    let start = chars[..i].iter().rposition(|c| !c.is_alphabetic()).map_or(0, |p| p + 1);
+    let end = chars[i + 1..].iter().position(|c| !c.is_alphabetic()).map_or(chars.len(), |p| i + 1 + p);
+    let others = chars[start..i].iter().chain(&chars[i + 1..end]);
+    let mut upper = false;
+    for c in others {
+        if c.is_lowercase() {
+            return false;
+        }
+        upper |= c.is_uppercase();
+    }
+    upper
+}
+
+fn expand_at(chars: &[char], i: usize, capitals: Capitals) -> Option<String> {
+    let c = chars[i];
+    let letters = expansion(c)?;
+    if !c.is_uppercase() {
+        return Some(letters.to_string());
+    }
+    let upper = match capitals {
+        Capitals::Auto => in_capitals(chars, i),
+        Capitals::Title => false,
+        Capitals::Upper => true,
+    };
+    let mut result: String = letters[..1].to_uppercase();
+    result.push_str(&if upper { letters[1..].to_uppercase() } else { letters[1..].to_string() });
+    Some(result)
+}
+
+/// Expand `æ`, `œ`, and `ę` (and their capitals) into two letters, with
+/// capitals following [`Capitals::Auto`].
+///
+/// ```
+/// use latincy_preprocess::ligatures::expand;
+///
+/// assert_eq!(expand("Cœlum et Æneas quę CÆSAR"), "Coelum et Aeneas quae CAESAR");
+/// ```
+pub fn expand(text: &str) -> String {
+    expand_with(text, Capitals::Auto)
+}
+
+/// Like [`expand`], with the given treatment of capital ligatures.
+pub fn expand_with(text: &str, capitals: Capitals) -> String {
+    if !text.chars().any(|c| expansion(c).is_some()) {
+        return text.to_string();
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len() + 8);
+    for i in 0..chars.len() {
+        match expand_at(&chars, i, capitals) {
+            Some(letters) => result.push_str(&letters),
+            None => result.push(chars[i]),
+        }
+    }
+    result
+}
+
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<ChangeRecord>,
+}
+
+/// Like [`expand_with`], recording one [`ChangeRecord`] per expanded
+/// ligature.
+pub fn expand_detailed(text: &str, capitals: Capitals) -> DetailedResult {
+    let chars: Vec<char> = text.chars().collect();
+    let mut normalized = String::with_capacity(text.len() + 8);
+    let mut changes = Vec::new();
+    for (i, &c) in chars.iter().enumerate() {
+        match expand_at(&chars, i, capitals) {
+            Some(letters) => {
+                normalized.push_str(&letters);
+                changes.push(ChangeRecord {
+                    position: i,
+                    original: c.to_string(),
+                    rule: format!("{} → {}", c, letters),
+                    normalized: letters,
+                    pass: 1,
+                    context: changes::context(&chars, i, 1),
+                });
+            }
+            None => normalized.push(c),
+        }
+    }
+    DetailedResult {
+        original: text.to_string(),
+        normalized,
+        changes,
+    }
+}
+
+/// The reverse of [`expand`]: write the diphthongs *ae* and *oe* as `æ` and
+/// `œ` (`Ae`/`AE` as `Æ`), for facsimile-style output. Words that begin with
+/// a known hiatus (*aer*, *poeta*, *poema*) keep their two vowels.
+///
+/// ```
+/// use latincy_preprocess::ligatures::contract;
+///
+/// assert_eq!(contract("Caesar poeta praemium COELUM"), "Cæsar poeta præmium CŒLUM");
+/// ```
+pub fn contract(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len());
+    let mut word_start = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if !c.is_alphabetic() {
+            word_start = i + 1;
+        }
+        let ligature = match (c, chars.get(i + 1)) {
+            ('a', Some('e')) => Some('æ'),
+            ('o', Some('e')) => Some('œ'),
+            ('A', Some('e' | 'E')) => Some('Æ'),
+            ('O', Some('e' | 'E')) => Some('Œ'),
+            _ => None,
+        };
+        match ligature {
+            Some(ligature) if !is_hiatus(&chars[word_start..], i - word_start) => {
+                result.push(ligature);
+                i += 2;
+            }
+            _ => {
+                result.push(c);
+                i += 1;
+            }
+        }
+    }
+    result
+}
+
+/// Whether the vowel pair at `offset` in `word` (which starts a word) is a
+/// hiatus from [`HIATUS_PREFIXES`].
+pub(crate) fn is_hiatus(word: &[char], offset: usize) -> bool {
+    let lower: String = word.iter().take_while(|c| c.is_alphabetic()).flat_map(|c| c.to_lowercase()).collect();
+    HIATUS_PREFIXES.iter().any(|prefix| {
+        let pair = prefix.find("ae").or_else(|| prefix.find("oe"));
+        lower.starts_with(prefix) && pair == Some(offset)
+    })
+}
+
+impl Normalizer for Capitals {
+    fn name(&self) -> &str {
+        "ligatures"
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = expand_detailed(text, *self);
+        NormalizeOutput::from_changes(result.original, result.normalized, result.changes)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+fn parse_capitals(capitals: &str) -> PyResult<Capitals> {
+    match capitals {
+        "auto" => Ok(Capitals::Auto),
+        "title" => Ok(Capitals::Title),
+        "upper" => Ok(Capitals::Upper),
+        other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "capitals must be 'auto', 'title', or 'upper', got {:?}",
+            other
+        ))),
+    }
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "expand_ligatures", signature = (text, capitals="auto"))]
+pub fn py_expand_ligatures(py: Python<'_>, text: &str, capitals: &str) -> PyResult<String> {
+    let capitals = parse_capitals(capitals)?;
+    Ok(crate::allow_threads(py, text, || expand_with(text, capitals)))
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "expand_ligatures_detailed", signature = (text, capitals="auto"))]
+pub fn py_expand_ligatures_detailed(py: Python<'_>, text: &str, capitals: &str) -> PyResult<changes::PyDetailedResult> {
+    let capitals = parse_capitals(capitals)?;
+    let result = crate::allow_threads(py, text, || expand_detailed(text, capitals));
+
+    changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "contract_ligatures")]
+pub fn py_contract_ligatures(py: Python<'_>, text: &str) -> String {
+    crate::allow_threads(py, text, || contract(text))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        assert_eq!(expand("præmium cœlum quę"), "praemium coelum quae");
+        assert_eq!(expand("Æneas Œdipus Ęgo"), "Aeneas Oedipus Aego");
+        assert_eq!(expand("CÆSAR ŒCONOMIA"), "CAESAR OECONOMIA");
+        assert_eq!(expand("Rosa"), "Rosa");
+        assert_eq!(expand(""), "");
+    }
+
+    #[test]
+    fn test_capitals() {
+        assert_eq!(expand_with("Æneas CÆSAR", Capitals::Title), "Aeneas CAeSAR");
+        assert_eq!(expand_with("Æneas CÆSAR", Capitals::Upper), "AEneas CAESAR");
+        // A lone capital has no word case to follow
+        assert_eq!(expand_with("Æ", Capitals::Auto), "Ae");
+    }
+
+    #[test]
+    fn test_expand_detailed() {
+        let result = expand_detailed("in cœlo Cæsar", Capitals::Auto);
+        assert_eq!(result.normalized, "in coelo Caesar");
+        assert_eq!(result.changes.len(), 2);
+        assert_eq!(result.changes[0].position, 4);
+        assert_eq!(result.changes[0].original, "œ");
+        assert_eq!(result.changes[0].normalized, "oe");
+        assert_eq!(result.changes[0].rule, "œ → oe");
+        assert_eq!(result.changes[0].context, "n c[œ]lo ");
+        assert_eq!(result.changes[1].position, 9);
+    }
+
+    #[test]
+    fn test_contract() {
+        assert_eq!(contract("Caesar praemium coelum"), "Cæsar præmium cœlum");
+        assert_eq!(contract("AENEAS Oedipus"), "ÆNEAS Œdipus");
+        // Hiatus words keep both vowels
+        assert_eq!(contract("aer poeta Poema Israel"), "aer poeta Poema Israel");
+        assert_eq!(contract("poenae"), "pœnæ");
+        assert_eq!(contract(&expand("cœlum præmium")), "cœlum præmium");
+    }
+}
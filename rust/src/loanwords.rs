@@ -0,0 +1,311 @@
+//! Greek loan spellings: *sylva*/*silva*, *kalendae*/*calendae*,
+//! *philosophia*/*filosofia*.
+//!
+//! Latin writes Greek υ, κ, φ, θ, and χ as *y*, *k*, *ph*, *th*, and *ch*,
+//! but scribes, printers, and editors latinized them to different degrees,
+//! so one corpus has *sylva* and *lacryma* where another has *silva* and
+//! *lacrima*. [`LoanNormalizer`] rewrites them to one spelling so that
+//! lemma lookups see one form.
+
+use crate::changes::{self, ChangeRecord};
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+
+/// What to do with one of the digraphs *ph*, *th*, *ch*.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Digraph {
+    /// Leave as written.
+    #[default]
+    Keep,
+    /// Write the single letter of medieval spelling: *ph* → *f*, *th* →
+    /// *t*, *ch* → *c*.
+    Simplify,
+}
+
+impl Digraph {
+    /// Parse `"keep"` or `"simplify"`.
+    pub fn from_name(name: &str) -> Option<Digraph> {
+        match name {
+            "keep" => Some(Digraph::Keep),
+            "simplify" => Some(Digraph::Simplify),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Digraph::Keep => "keep",
+            Digraph::Simplify => "simplify",
+        }
+    }
+}
+
+/// Rewrites Greek loan spellings, each mapping switched on or off.
+///
+/// ```
+/// use latincy_preprocess::loanwords::{Digraph, LoanNormalizer};
+///
+/// let normalizer = LoanNormalizer::new();
+/// assert_eq!(normalizer.normalize("Kalendis in sylva"), "Calendis in silva");
+/// let normalizer = normalizer.y_to_i(false).ph(Digraph::Simplify);
+/// assert_eq!(normalizer.normalize("philosophia in sylva"), "filosofia in sylva");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoanNormalizer {
+    y_to_i: bool,
+    k_to_c: bool,
+    ph: Digraph,
+    th: Digraph,
+    ch: Digraph,
+}
+
+impl Default for LoanNormalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoanNormalizer {
+    /// Map *y* → *i* and *k* → *c*, and keep the digraphs.
+    pub fn new() -> Self {
+        LoanNormalizer {
+            y_to_i: true,
+            k_to_c: true,
+            ph: Digraph::Keep,
+            th: Digraph::Keep,
+            ch: Digraph::Keep,
+        }
+    }
+
+    /// Write *y* as *i* (*sylva* → *silva*, *lacryma* → *lacrima*).
+    pub fn y_to_i(mut self, enabled: bool) -> Self {
+        self.y_to_i = enabled;
+        self
+    }
+
+    /// Write *k* as *c* (*kalendae* → *calendae*, *Karthago* →
+    /// *Carthago*).
+    pub fn k_to_c(mut self, enabled: bool) -> Self {
+        self.k_to_c = enabled;
+        self
+    }
+
+    pub fn ph(mut self, policy: Digraph) -> Self {
+        self.ph = policy;
+        self
+    }
+
+    /// *posthac* and other *post-* compounds keep their *th*.
+    pub fn th(mut self, policy: Digraph) -> Self {
+        self.th = policy;
+        self
+    }
+
+    pub fn ch(mut self, policy: Digraph) -> Self {
+        self.ch = policy;
+        self
+    }
+
+    /// The single letter for the digraph starting at `chars[i]`, if it is
+    /// to be simplified.
+    fn simplified(&self, chars: &[char], i: usize) -> Option<char> {
+        if !chars.get(i + 1).is_some_and(|c| c.eq_ignore_ascii_case(&'h')) {
+            return None;
+        }
+        let (policy, letter) = match chars[i].to_ascii_lowercase() {
+            'p' => (self.ph, 'f'),
+            't' => (self.th, 't'),
+            'c' => (self.ch, 'c'),
+            _ => return None,
+        };
+        if policy == Digraph::Keep || (letter == 't' && is_post_compound(chars, i)) {
+            return None;
+        }
+        Some(letter)
+    }
+
+    pub fn normalize(&self, text: &str) -> String {
+        self.normalize_detailed(text).normalized
+    }
+
+    /// Like [`LoanNormalizer::normalize`], recording one [`ChangeRecord`]
+    /// per rewritten letter or digraph.
+    pub fn normalize_detailed(&self, text: &str) -> DetailedResult {
+        let chars: Vec<char> = text.chars().collect();
+        let mut normalized = String::with_capacity(text.len());
+        let mut changes = Vec::new();
+
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let (len, replacement, rule) = if let Some(letter) = self.simplified(&chars, i) {
+                let rule = format!("{}h → {}", c.to_ascii_lowercase(), letter);
+                (2, letter, rule)
+            } else if self.y_to_i && c.eq_ignore_ascii_case(&'y') {
+                (1, 'i', "y → i".to_string())
+            } else if self.k_to_c && c.eq_ignore_ascii_case(&'k') {
+                (1, 'c', "k → c".to_string())
+            } else {
+                normalized.push(c);
+                i += 1;
+                continue;
+            };
+            let replacement = if c.is_uppercase() { replacement.to_ascii_uppercase() } else { replacement };
+            normalized.push(replacement);
+            changes.push(ChangeRecord {
+                position: i,
+                original: chars[i..i + len].iter().collect(),
+                normalized: replacement.to_string(),
+                pass: 1,
+                rule,
+                context: changes::context(&chars, i, len),
+            });
+            i += len;
+        }
+
+        DetailedResult {
+            original: text.to_string(),
+            normalized,
+            changes,
+        }
+    }
+}
+
+/// Whether the *th* at `chars[i]` joins *post* to a stem (*posthac*).
+fn is_post_compound(chars: &[char], i: usize) -> bool {
+    let start = chars[..i].iter().rposition(|c| !c.is_alphabetic()).map_or(0, |p| p + 1);
+    i == start + 3 && chars[start..i].iter().map(|c| c.to_ascii_lowercase()).eq("pos".chars())
+}
+
+/// Rewrite *y* → *i* and *k* → *c* with the default [`LoanNormalizer`].
+///
+/// ```
+/// use latincy_preprocess::loanwords::normalize;
+///
+/// assert_eq!(normalize("lacrymae kalendarum"), "lacrimae calendarum");
+/// ```
+pub fn normalize(text: &str) -> String {
+    LoanNormalizer::new().normalize(text)
+}
+
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<ChangeRecord>,
+}
+
+impl Normalizer for LoanNormalizer {
+    fn name(&self) -> &str {
+        "loanwords"
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = self.normalize_detailed(text);
+        NormalizeOutput::from_changes(result.original, result.normalized, result.changes)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+fn build_normalizer(y_to_i: bool, k_to_c: bool, ph: &str, th: &str, ch: &str) -> PyResult<LoanNormalizer> {
+    let parse = |digraph: &str, policy: &str| {
+        Digraph::from_name(policy).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "{} policy must be 'keep' or 'simplify', got {:?}",
+                digraph, policy
+            ))
+        })
+    };
+    Ok(LoanNormalizer::new()
+        .y_to_i(y_to_i)
+        .k_to_c(k_to_c)
+        .ph(parse("ph", ph)?)
+        .th(parse("th", th)?)
+        .ch(parse("ch", ch)?))
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "normalize_loanwords", signature = (text, y_to_i=true, k_to_c=true, ph="keep", th="keep", ch="keep"))]
+pub fn py_normalize_loanwords(
+    py: Python<'_>,
+    text: &str,
+    y_to_i: bool,
+    k_to_c: bool,
+    ph: &str,
+    th: &str,
+    ch: &str,
+) -> PyResult<String> {
+    let normalizer = build_normalizer(y_to_i, k_to_c, ph, th, ch)?;
+    Ok(crate::allow_threads(py, text, || normalizer.normalize(text)))
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "normalize_loanwords_detailed", signature = (text, y_to_i=true, k_to_c=true, ph="keep", th="keep", ch="keep"))]
+pub fn py_normalize_loanwords_detailed(
+    py: Python<'_>,
+    text: &str,
+    y_to_i: bool,
+    k_to_c: bool,
+    ph: &str,
+    th: &str,
+    ch: &str,
+) -> PyResult<changes::PyDetailedResult> {
+    let normalizer = build_normalizer(y_to_i, k_to_c, ph, th, ch)?;
+    let result = crate::allow_threads(py, text, || normalizer.normalize_detailed(text));
+    changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults() {
+        assert_eq!(normalize("sylva lacryma Kalendae KARTHAGO"), "silva lacrima Calendae CARTHAGO");
+        assert_eq!(normalize("philosophia theatrum pulcher"), "philosophia theatrum pulcher");
+    }
+
+    #[test]
+    fn test_digraphs() {
+        let normalizer = LoanNormalizer::new().ph(Digraph::Simplify).th(Digraph::Simplify).ch(Digraph::Simplify);
+        assert_eq!(normalizer.normalize("Philosophia theatrum pulcher CHARTA"), "Filosofia teatrum pulcer CARTA");
+        // post- compounds are not Greek th
+        assert_eq!(normalizer.normalize("posthac Posthumus"), "posthac Posthumus");
+        assert_eq!(normalizer.normalize("Athenae"), "Atenae");
+    }
+
+    #[test]
+    fn test_switched_off() {
+        let normalizer = LoanNormalizer::new().y_to_i(false).k_to_c(false);
+        assert_eq!(normalizer.normalize("sylva Kalendae"), "sylva Kalendae");
+    }
+
+    #[test]
+    fn test_normalize_detailed() {
+        let result = LoanNormalizer::new().ph(Digraph::Simplify).normalize_detailed("nymphae");
+        assert_eq!(result.normalized, "nimfae");
+        assert_eq!(result.changes.len(), 2);
+        assert_eq!(result.changes[0].rule, "y → i");
+        assert_eq!(result.changes[1].position, 3);
+        assert_eq!(result.changes[1].original, "ph");
+        assert_eq!(result.changes[1].normalized, "f");
+        assert_eq!(result.changes[1].rule, "ph → f");
+        assert_eq!(result.changes[1].context, "nym[ph]ae");
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(Digraph::from_name("simplify"), Some(Digraph::Simplify));
+        assert_eq!(Digraph::from_name("drop"), None);
+    }
+}
@@ -1,367 +1,3847 @@
+use crate::cache::WordCache;
+use crate::changes;
+use crate::case::WordCase;
+use crate::graphemes;
+use crate::lexicon::Lexicon;
+use crate::names::NameGuard;
+use crate::ngrams::{Gram, NgramData, NGRAM_SMOOTHING};
+use crate::ocr::{self, ConfusionMatrix};
+use crate::pipeline::{NormalizeOutput, Normalizer};
+use crate::scan;
+pub use crate::changes::ChangeRecord;
+pub use crate::ngrams::{train_ngrams, DataSource, NgramsUnavailable};
+#[cfg(feature = "pyo3-backend")]
+use crate::names::PyNameGuard;
+#[cfg(feature = "pyo3-backend")]
+use crate::ngrams::PyNgramData;
 #[cfg(feature = "pyo3-backend")]
 use pyo3::prelude::*;
-use std::collections::{HashMap, HashSet};
+#[cfg(feature = "pyo3-backend")]
+use pyo3::types::{PyDict, PyList};
+use std::collections::HashSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
 #[cfg(feature = "pyo3-backend")]
 use std::path::PathBuf;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
+
+// ---------------------------------------------------------------------------
+// Allowlist
+// ---------------------------------------------------------------------------
+
+/// Legitimate f-words that must not be transformed by Pass 2. This is the
+/// built-in starting point; `LongSNormalizer::extend_allowlist` adds more.
+const ALLOWLIST: &[&str] = &[
+    "facere", "facio", "facit", "faciunt", "feceram", "fecerant", "fecerat", "fecere",
+    "fecerim", "fecerint", "fecerit", "fecerunt", "feci", "fecimus", "fecisse", "fecissem",
+    "fecissent", "fecisset", "fecisti", "fecistis", "fecit", "fecunda", "fecundam", "fecundi",
+    "fecundis", "fecunditas", "fecunditatem", "fecundus", "felice", "felicem", "felices", "felici",
+    "felicibus", "felicis", "feliciter", "felicium", "felix", "femina", "feminae", "feminam",
+    "feminarum", "feminas", "feminis", "fenestra", "fenestram", "fenestras", "fenestris", "feram",
+    "ferebam", "ferebant", "ferebat", "ferebatur", "feremus", "ferendi", "ferendo", "ferendum",
+    "ferens", "ferent", "ferentem", "ferentis", "feres", "feret", "ferimus", "fero",
+    "ferocem", "feroces", "feroci", "ferocis", "ferociter", "ferox", "ferre", "ferrem",
+    "ferrent", "ferret", "ferri", "ferro", "ferrum", "fers", "fert", "fertis",
+    "fertur", "ferunt", "feruntur", "festa", "festi", "festis", "festo", "festum",
+    "fiant", "fiat", "fide", "fidei", "fideles", "fidelibus", "fidelis", "fideliter",
+    "fidelium", "fidem", "fides", "fiebant", "fiebat", "fierent", "fieret", "fieri",
+    "figura", "figurae", "figuram", "figurarum", "figuras", "figuris", "filia", "filiae",
+    "filiam", "filiarum", "filias", "filii", "filiis", "filio", "filiorum", "filios",
+    "filium", "filius", "finem", "fines", "finibus", "finire", "finis", "finit",
+    "finita", "finitum", "finitur", "finium", "fio", "firma", "firmam", "firmamenti",
+    "firmamento", "firmamentum", "firmare", "firmat", "firmi", "firmiter", "firmum", "firmus",
+    "fit", "fiunt", "forma", "formae", "formam", "formas", "fuerat", "fuerint",
+    "fuerit", "fuerunt", "fugere", "fugerunt", "fugi", "fugiens", "fugio", "fugisse",
+    "fugit", "fugiunt", "fuisse", "fuissem", "fuissent", "fuisset", "fuit", "fundamenta",
+    "fundamenti", "fundamento", "fundamentum", "furor", "furore", "furorem", "furoris", "futura",
+    "futuram", "futuri", "futuris", "futurum", "futurus",
+];
 
-/// N-gram frequency tables, loaded lazily on first use.
-struct NgramData {
-    bigrams: HashMap<String, u64>,
-    trigrams: HashMap<String, u64>,
-    fourgrams: HashMap<String, u64>,
+/// Read a word list from disk: a JSON array of strings if the file has a
+/// `.json` extension, otherwise one word per line with `#` comments.
+fn read_word_list(path: &Path) -> io::Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)?;
+    if path.extension().is_some_and(|ext| ext == "json") {
+        return serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+    }
+    Ok(content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
 }
 
 // ---------------------------------------------------------------------------
-// Ngram data loading: two paths depending on feature flags
+// Change tracing
 // ---------------------------------------------------------------------------
 
-/// When pyo3-backend is NOT active, embed ngram JSON at compile time so the
-/// CLI binary is fully self-contained.
-#[cfg(not(feature = "pyo3-backend"))]
-static NGRAM_DATA: LazyLock<NgramData> = LazyLock::new(|| {
-    let bigrams: HashMap<String, u64> =
-        serde_json::from_str(include_str!("../../src/latincy_preprocess/long_s/data/ngrams/bigrams.json"))
-            .expect("embedded bigrams.json is invalid");
-    let trigrams: HashMap<String, u64> =
-        serde_json::from_str(include_str!("../../src/latincy_preprocess/long_s/data/ngrams/trigrams.json"))
-            .expect("embedded trigrams.json is invalid");
-    let fourgrams: HashMap<String, u64> =
-        serde_json::from_str(include_str!("../../src/latincy_preprocess/long_s/data/ngrams/4grams.json"))
-            .expect("embedded 4grams.json is invalid");
-    NgramData {
-        bigrams,
-        trigrams,
-        fourgrams,
+/// A single rule that fired while normalizing a word.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedRule {
+    /// Which pass produced the rule: 1 (fixed patterns), 2 (n-gram
+    /// frequency), 3 (lexicon), or 0 for layout changes such as rejoining a
+    /// hyphenated line break.
+    pub pass: u8,
+    /// Human-readable pattern, e.g. `ft → st` or `<fu → <su`.
+    pub pattern: String,
+    /// N-gram keys compared by Pass 2 and their frequencies (f-form first),
+    /// or the spellings compared by Pass 3 and their lexicon frequencies.
+    /// Empty for Pass 1 rules, which are not frequency-based.
+    pub evidence: Vec<(String, u64)>,
+    /// Scores behind a Pass 2 decision; `None` for rules that are not
+    /// frequency-based (Pass 1, denylist).
+    pub score: Option<Pass2Score>,
+}
+
+/// Log-scale scores (higher is more likely) of the f-reading and s-reading
+/// compared by a Pass 2 decision.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pass2Score {
+    pub f_score: f64,
+    pub s_score: f64,
+}
+
+impl AppliedRule {
+    fn pass1(pattern: &str, replacement: &str) -> Self {
+        AppliedRule {
+            pass: 1,
+            pattern: format!("{} → {}", pattern, replacement),
+            evidence: Vec::new(),
+            score: None,
+        }
     }
-});
 
-/// When pyo3-backend IS active, load ngram files at runtime from the Python
-/// package's data directory (existing behavior).
-#[cfg(feature = "pyo3-backend")]
-static NGRAM_DATA: LazyLock<NgramData> = LazyLock::new(|| {
-    let dir = find_ngram_dir();
-    NgramData {
-        bigrams: load_ngram_file(&dir.join("bigrams.json")),
-        trigrams: load_ngram_file(&dir.join("trigrams.json")),
-        fourgrams: load_ngram_file(&dir.join("4grams.json")),
+    fn pass2(f_key: Gram, f_freq: u64, s_key: Gram, s_freq: u64, score: Pass2Score) -> Self {
+        AppliedRule {
+            pass: 2,
+            pattern: format!("{} → {}", f_key, s_key),
+            evidence: vec![(f_key.to_string(), f_freq), (s_key.to_string(), s_freq)],
+            score: Some(score),
+        }
     }
-});
 
-#[cfg(feature = "pyo3-backend")]
-fn find_ngram_dir() -> PathBuf {
-    if let Ok(dir) = std::env::var("LATINCY_PREPROCESS_NGRAMS") {
-        return PathBuf::from(dir);
+    /// How sure the rule is of its change: the logistic of the margin
+    /// between the chosen and rejected readings' log scores, so 0.5 is a
+    /// coin toss and a 2:1 margin gives 2/3. Rules that are not
+    /// frequency-based count as certain (1.0).
+    pub fn confidence(&self) -> f64 {
+        match self.score {
+            Some(score) => 1.0 / (1.0 + (score.f_score - score.s_score).exp()),
+            None => 1.0,
+        }
     }
+}
 
-    Python::with_gil(|py| {
-        let module = py.import("latincy_preprocess.long_s._rules").ok()?;
-        let file_attr = module.getattr("__file__").ok()?;
-        let file_str: String = file_attr.extract().ok()?;
-        let module_dir = PathBuf::from(file_str).parent()?.to_path_buf();
-        Some(module_dir.join("data").join("ngrams"))
-    })
-    .unwrap_or_else(|| PathBuf::from("src/latincy_preprocess/long_s/data/ngrams"))
+/// Record of a word changed by long-s normalization.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordChange {
+    /// Character offset of the word in the original text.
+    pub position: usize,
+    pub original: String,
+    pub normalized: String,
+    pub rules: Vec<AppliedRule>,
+    /// Confidence of the least confident rule (see
+    /// [`AppliedRule::confidence`]).
+    pub confidence: f64,
 }
 
-#[cfg(feature = "pyo3-backend")]
-fn load_ngram_file(path: &std::path::Path) -> HashMap<String, u64> {
-    let content = std::fs::read_to_string(path)
-        .unwrap_or_else(|e| panic!("Failed to read ngram file {}: {}", path.display(), e));
-    serde_json::from_str(&content)
-        .unwrap_or_else(|e| panic!("Failed to parse ngram file {}: {}", path.display(), e))
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<WordChange>,
 }
 
-// ---------------------------------------------------------------------------
-// Allowlist
-// ---------------------------------------------------------------------------
+/// Characters of original text kept on each side of a [`ReviewItem`].
+pub const REVIEW_CONTEXT_CHARS: usize = 40;
 
-/// Legitimate f-words that must not be transformed by Pass 2.
-static ALLOWLIST: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
-    [
-        "facere", "facio", "facit", "faciunt", "feceram", "fecerant", "fecerat", "fecere",
-        "fecerim", "fecerint", "fecerit", "fecerunt", "feci", "fecimus", "fecisse", "fecissem",
-        "fecissent", "fecisset", "fecisti", "fecistis", "fecit", "fecunda", "fecundam", "fecundi",
-        "fecundis", "fecunditas", "fecunditatem", "fecundus", "felice", "felicem", "felices", "felici",
-        "felicibus", "felicis", "feliciter", "felicium", "felix", "femina", "feminae", "feminam",
-        "feminarum", "feminas", "feminis", "fenestra", "fenestram", "fenestras", "fenestris", "feram",
-        "ferebam", "ferebant", "ferebat", "ferebatur", "feremus", "ferendi", "ferendo", "ferendum",
-        "ferens", "ferent", "ferentem", "ferentis", "feres", "feret", "ferimus", "fero",
-        "ferocem", "feroces", "feroci", "ferocis", "ferociter", "ferox", "ferre", "ferrem",
-        "ferrent", "ferret", "ferri", "ferro", "ferrum", "fers", "fert", "fertis",
-        "fertur", "ferunt", "feruntur", "festa", "festi", "festis", "festo", "festum",
-        "fiant", "fiat", "fide", "fidei", "fideles", "fidelibus", "fidelis", "fideliter",
-        "fidelium", "fidem", "fides", "fiebant", "fiebat", "fierent", "fieret", "fieri",
-        "figura", "figurae", "figuram", "figurarum", "figuras", "figuris", "filia", "filiae",
-        "filiam", "filiarum", "filias", "filii", "filiis", "filio", "filiorum", "filios",
-        "filium", "filius", "finem", "fines", "finibus", "finire", "finis", "finit",
-        "finita", "finitum", "finitur", "finium", "fio", "firma", "firmam", "firmamenti",
-        "firmamento", "firmamentum", "firmare", "firmat", "firmi", "firmiter", "firmum", "firmus",
-        "fit", "fiunt", "forma", "formae", "formam", "formas", "fuerat", "fuerint",
-        "fuerit", "fuerunt", "fugere", "fugerunt", "fugi", "fugiens", "fugio", "fugisse",
-        "fugit", "fugiunt", "fuisse", "fuissem", "fuissent", "fuisset", "fuit", "fundamenta",
-        "fundamenti", "fundamento", "fundamentum", "furor", "furore", "furorem", "furoris", "futura",
-        "futuram", "futuri", "futuris", "futurum", "futurus",
-    ]
-    .into_iter()
-    .collect()
-});
+/// A low-confidence change queued for human review.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewItem {
+    /// Character offset of the word in the original text.
+    pub position: usize,
+    pub original: String,
+    pub normalized: String,
+    pub confidence: f64,
+    /// The original text around the word, up to [`REVIEW_CONTEXT_CHARS`]
+    /// characters on each side.
+    pub context: String,
+    /// Patterns of the rules that fired.
+    pub rules: Vec<String>,
+}
+
+impl ReviewItem {
+    /// The item as a single-line JSON object.
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "position": self.position,
+            "original": self.original,
+            "normalized": self.normalized,
+            "confidence": self.confidence,
+            "context": self.context,
+            "rules": self.rules,
+        })
+        .to_string()
+    }
+}
+
+impl DetailedResult {
+    /// The changes with confidence below `below`, in text order, with their
+    /// surrounding context.
+    pub fn review(&self, below: f64) -> Vec<ReviewItem> {
+        let chars: Vec<char> = self.original.chars().collect();
+        self.changes
+            .iter()
+            .filter(|change| change.confidence < below)
+            .map(|change| {
+                let start = change.position.saturating_sub(REVIEW_CONTEXT_CHARS);
+                let end = (change.position + change.original.chars().count() + REVIEW_CONTEXT_CHARS).min(chars.len());
+                ReviewItem {
+                    position: change.position,
+                    original: change.original.clone(),
+                    normalized: change.normalized.clone(),
+                    confidence: change.confidence,
+                    context: chars[start..end].iter().collect(),
+                    rules: change.rules.iter().map(|rule| rule.pattern.clone()).collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// The review queue for `below` as JSON Lines, one item per line.
+    pub fn review_jsonl(&self, below: f64) -> String {
+        self.review(below).iter().map(|item| item.to_json() + "\n").collect()
+    }
+
+    /// The changes broken down into character-level [`ChangeRecord`]s, in
+    /// text order, the form `uv::normalize_detailed` reports. Each record is
+    /// attributed to the word's rule that made it. A ligature is one record
+    /// (`ﬁ` → `si`), as is the separator removed when rejoining a hyphenated
+    /// word.
+    pub fn char_changes(&self) -> Vec<ChangeRecord> {
+        let chars: Vec<char> = self.original.chars().collect();
+        let mut records: Vec<ChangeRecord> = Vec::new();
+        for change in &self.changes {
+            let original: Vec<char> = change.original.chars().collect();
+            let normalized: Vec<char> = change.normalized.chars().collect();
+            let mut j = 0;
+            for (k, &c) in original.iter().enumerate() {
+                let position = change.position + k;
+                let replacement: String = if let Some(letters) = ligature(c) {
+                    let end = (j + letters.chars().count()).min(normalized.len());
+                    let replacement = normalized[j..end].iter().collect();
+                    j = end;
+                    replacement
+                } else if normalized.get(j) == Some(&c) {
+                    j += 1;
+                    continue;
+                } else if original.len() - k > normalized.len() - j
+                    && (c.is_whitespace() || LINE_BREAK_HYPHENS.contains(&c))
+                {
+                    String::new()
+                } else {
+                    j += 1;
+                    normalized.get(j - 1).map(|n| n.to_string()).unwrap_or_default()
+                };
+
+                // Successive removed characters make up one separator
+                if let Some(last) = records.last_mut() {
+                    if replacement.is_empty()
+                        && last.normalized.is_empty()
+                        && last.position + last.original.chars().count() == position
+                    {
+                        last.original.push(c);
+                        last.context = changes::context(&chars, last.position, last.original.chars().count());
+                        continue;
+                    }
+                }
+                let (pass, rule) = change_rule(&change.rules, c, &replacement)
+                    .map(|rule| (rule.pass, rule.pattern.clone()))
+                    .unwrap_or_default();
+                records.push(ChangeRecord {
+                    position,
+                    original: c.to_string(),
+                    normalized: replacement,
+                    pass,
+                    rule,
+                    context: changes::context(&chars, position, 1),
+                });
+            }
+        }
+        records
+    }
+}
+
+/// The rule among a word's `rules` that turned `from` into `to`: the first
+/// whose pattern rewrites `from` (or its ligature letters) to `to`, a layout
+/// rule for a removed character, or else the last rule.
+fn change_rule<'a>(rules: &'a [AppliedRule], from: char, to: &str) -> Option<&'a AppliedRule> {
+    if to.is_empty() {
+        if let Some(rule) = rules.iter().find(|rule| rule.pass == 0) {
+            return Some(rule);
+        }
+    }
+    let to = to.to_lowercase();
+    let rewrites = |from: &str| {
+        rules.iter().find(|rule| {
+            rule.pattern
+                .split_once(" → ")
+                .is_some_and(|(left, right)| left.contains(from) && right.contains(to.as_str()))
+        })
+    };
+    let from = from.to_lowercase().to_string();
+    rewrites(&from).or_else(|| rewrites(&decompose_ligatures(&from))).or(rules.last())
+}
 
 // ---------------------------------------------------------------------------
 // Core normalization logic (always available)
 // ---------------------------------------------------------------------------
 
-fn pass1(word: &str) -> String {
-    // Detect case pattern before lowercasing
-    let chars: Vec<char> = word.chars().collect();
-    let is_upper = chars.len() > 1 && chars.iter().all(|c| !c.is_lowercase());
-    let is_title = chars.first().map_or(false, |c| c.is_uppercase())
-        && (chars.len() == 1 || !is_upper);
+/// LATIN SMALL LETTER LONG S. It has no uppercase form of its own
+/// (`'ſ'.to_uppercase()` is `S`), so it always counts as lowercase.
+pub const LONG_S: char = '\u{017F}';
+
+/// Map every genuine long-s character straight to `s`.
+///
+/// This is the pre-pass applied by `pass1`/`pass2`; it is exposed for callers
+/// who only want the unambiguous replacement without the f-confusion heuristics.
+pub fn replace_long_s(text: &str) -> String {
+    text.replace(LONG_S, "s")
+}
 
-    let mut normalized = word.to_lowercase();
+/// Typographic ligatures and their letters. `ﬅ` is a long s + t ligature, so
+/// it decomposes straight to `st`.
+pub const LIGATURES: &[(char, &str)] = &[
+    ('\u{FB00}', "ff"),
+    ('\u{FB01}', "fi"),
+    ('\u{FB02}', "fl"),
+    ('\u{FB03}', "ffi"),
+    ('\u{FB04}', "ffl"),
+    ('\u{FB05}', "st"),
+    ('\u{FB06}', "st"),
+];
 
-    let trigram_rules: &[(&str, &str)] = &[
-        ("fqu", "squ"),
-        ("fpe", "spe"),
-        ("fuf", "sus"),
-        ("fum", "sum"),
-    ];
+fn ligature(c: char) -> Option<&'static str> {
+    LIGATURES.iter().find(|&&(lig, _)| lig == c).map(|&(_, letters)| letters)
+}
 
-    for &(pattern, replacement) in trigram_rules {
-        if normalized.contains(pattern) {
-            normalized = normalized.replace(pattern, replacement);
+/// Expand the presentation ligatures in [`LIGATURES`] (`ﬁ` → `fi`, `ﬅ` →
+/// `st`, ...). Applied by `pass1`/`pass2` before any other rule.
+pub fn decompose_ligatures(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match ligature(c) {
+            Some(letters) => result.push_str(letters),
+            None => result.push(c),
         }
     }
+    result
+}
 
-    let bigram_rules: &[(&str, &str)] = &[
-        ("fp", "sp"),
-        ("ft", "st"),
-        ("fc", "sc"),
-    ];
+/// Letters before which historical typesetting keeps round s.
+const ROUND_S_BEFORE: &[char] = &['b', 'f', 'k'];
 
-    for &(pattern, replacement) in bigram_rules {
-        if normalized.contains(pattern) {
-            normalized = normalized.replace(pattern, replacement);
-        }
+/// Render modern text with historical long s, the inverse of the ſ → s
+/// pre-pass: a lowercase `s` becomes `ſ` inside a word, but stays round at
+/// the end of a word and before *b*, *f*, or *k* (*possessionis* →
+/// *poſſeſſionis*, *satisfacit* → *ſatisfacit*, *est* → *eſt*). Capital `S`
+/// has no long form and is left alone.
+pub fn archaize(text: &str) -> String {
+    let mut chars = text.chars().peekable();
+    let mut result = String::with_capacity(text.len());
+    while let Some(c) = chars.next() {
+        let long = c == 's'
+            && chars.peek().is_some_and(|next| {
+                next.is_alphabetic() && !next.to_lowercase().any(|lower| ROUND_S_BEFORE.contains(&lower))
+            });
+        result.push(if long { LONG_S } else { c });
     }
+    result
+}
 
-    if normalized.ends_with('f') {
-        let len = normalized.len();
-        normalized.replace_range(len - 1..len, "s");
+/// Decompose ligatures, recording one Pass 1 rule per distinct ligature.
+fn decompose_traced(word: &str, trace: Option<&mut Vec<AppliedRule>>) -> String {
+    if !word.chars().any(|c| ligature(c).is_some()) {
+        return word.to_string();
     }
-
-    // Restore original case pattern
-    if is_upper {
-        normalized = normalized.to_uppercase();
-    } else if is_title {
-        let mut result = String::with_capacity(normalized.len());
-        for (i, c) in normalized.chars().enumerate() {
-            if i == 0 {
-                result.extend(c.to_uppercase());
-            } else {
-                result.push(c);
+    if let Some(trace) = trace {
+        for &(lig, letters) in LIGATURES {
+            if word.contains(lig) {
+                trace.push(AppliedRule::pass1(&lig.to_string(), letters));
             }
         }
-        normalized = result;
     }
+    decompose_ligatures(word)
+}
+
+/// Split a token into its leading non-alphabetic characters, its alphabetic
+/// core (which keeps word-internal apostrophes and hyphens, and the marks on
+/// its letters), and its trailing non-alphabetic characters: `(fuper)` →
+/// `(`, `fuper`, `)`. A token with no letters is all prefix.
+fn split_affixes(word: &str) -> (&str, &str, &str) {
+    let Some(start) = word.find(char::is_alphabetic) else {
+        return (word, "", "");
+    };
+    let end = word
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| c.is_alphabetic())
+        .map_or(word.len(), |(i, c)| i + c.len_utf8());
+    // Marks on the last letter belong to it
+    let end = word[end..].find(|c| !graphemes::is_extender(c)).map_or(word.len(), |i| end + i);
+    (&word[..start], &word[start..end], &word[end..])
+}
+
+/// Apply a word-level function to the alphabetic core of `word`, keeping its
+/// surrounding punctuation (`eſt.` → `est.`).
+fn on_core(word: &str, normalize: impl FnOnce(&str) -> String) -> String {
+    match split_affixes(word) {
+        ("", _, "") => normalize(word),
+        (_, "", _) => word.to_string(),
+        (lead, core, trail) => format!("{}{}{}", lead, normalize(core), trail),
+    }
+}
+
+/// Whether every pass leaves `word` as it is when the Pass 1 rules all
+/// involve an f: plain ASCII letters with no f, which lowercasing and
+/// restoring the case gives back whatever their capitals. Text-level passes
+/// copy such words without running a rule.
+fn is_inert(word: &str) -> bool {
+    word.bytes().all(|b| b.is_ascii_alphabetic() && !matches!(b, b'f' | b'F'))
+}
+
+// ---------------------------------------------------------------------------
+// Pass 1 rule table
+// ---------------------------------------------------------------------------
+
+/// The built-in Pass 1 rules: f-clusters that are impossible or vanishingly
+/// rare in clean Latin, and word-final f.
+const PASS1_RULES: &[(&str, &str)] = &[
+    ("fqu", "squ"),
+    ("fpe", "spe"),
+    ("fuf", "sus"),
+    ("fum", "sum"),
+    ("fp", "sp"),
+    ("ft", "st"),
+    ("fc", "sc"),
+    ("f>", "s>"),
+];
+
+static DEFAULT_PASS1_RULES: LazyLock<Pass1Rules> = LazyLock::new(Pass1Rules::default);
+
+/// The fixed replacements applied by Pass 1, extensible at runtime (e.g.
+/// with `fl` → `sl` variants from a particular print shop).
+///
+/// Patterns are lowercase and may be anchored with `<` (word start) or `>`
+/// (word end), as in n-gram keys; a replacement has the same length and
+/// anchors as its pattern. Rules are applied in one left-to-right scan where
+/// the longest pattern matching at each position wins and the scan resumes
+/// after it, so one replacement never feeds another (`fufpe` → `suspe`).
+///
+/// ```
+/// use latincy_preprocess::long_s::Pass1Rules;
+///
+/// let mut rules = Pass1Rules::default();
+/// rules.add("fl<", "sl<").unwrap_err();
+/// rules.add("<fl", "<sl").unwrap();
+/// assert_eq!(rules.apply("flendit"), "slendit");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pass1Rules {
+    /// `(pattern, replacement)`, longest pattern first.
+    rules: Vec<(String, String)>,
+}
 
-    normalized
+impl Default for Pass1Rules {
+    /// The built-in rules.
+    fn default() -> Self {
+        let mut rules = Self::empty();
+        for &(pattern, replacement) in PASS1_RULES {
+            rules.add(pattern, replacement).expect("built-in Pass 1 rules are valid");
+        }
+        rules
+    }
 }
 
-fn restore_case(normalized: &str, is_upper: bool, is_title: bool) -> String {
-    if is_upper {
-        normalized.to_uppercase()
-    } else if is_title {
-        let mut result = String::with_capacity(normalized.len());
-        for (i, c) in normalized.chars().enumerate() {
-            if i == 0 {
-                result.extend(c.to_uppercase());
-            } else {
-                result.push(c);
+impl Pass1Rules {
+    /// A table with no rules.
+    pub fn empty() -> Self {
+        Pass1Rules { rules: Vec::new() }
+    }
+
+    /// Add a rule, replacing any rule with the same pattern. Fails with
+    /// `InvalidInput` if the pattern is empty or not lowercase, misplaces an
+    /// anchor, or differs from the replacement in length or anchors.
+    pub fn add(&mut self, pattern: &str, replacement: &str) -> io::Result<()> {
+        let invalid = |reason: &str| {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid Pass 1 rule {} → {}: {}", pattern, replacement, reason),
+            ))
+        };
+        let letters = unanchored(pattern);
+        if letters.is_empty() {
+            return invalid("empty pattern");
+        }
+        if letters.contains(['<', '>']) {
+            return invalid("anchors must open or close the pattern");
+        }
+        if pattern != pattern.to_lowercase() {
+            return invalid("pattern must be lowercase");
+        }
+        if replacement.chars().count() != pattern.chars().count()
+            || replacement.starts_with('<') != pattern.starts_with('<')
+            || replacement.ends_with('>') != pattern.ends_with('>')
+            || unanchored(replacement).contains(['<', '>'])
+        {
+            return invalid("replacement must match the pattern's length and anchors");
+        }
+
+        self.rules.retain(|(existing, _)| existing != pattern);
+        let len = pattern.chars().count();
+        let at = self.rules.iter().position(|(existing, _)| existing.chars().count() < len).unwrap_or(self.rules.len());
+        self.rules.insert(at, (pattern.to_string(), replacement.to_string()));
+        Ok(())
+    }
+
+    /// The rules as `(pattern, replacement)`, longest pattern first.
+    pub fn rules(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.rules.iter().map(|(pattern, replacement)| (pattern.as_str(), replacement.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether every pattern contains an f, so no rule fires on a word
+    /// without one.
+    fn need_f(&self) -> bool {
+        self.rules.iter().all(|(pattern, _)| pattern.contains('f'))
+    }
+
+    /// Apply the rules to a lowercase word.
+    pub fn apply(&self, word: &str) -> String {
+        self.apply_traced(word, None)
+    }
+
+    /// Apply the rules, recording one Pass 1 rule per rule that fired, in
+    /// table order.
+    fn apply_traced(&self, word: &str, trace: Option<&mut Vec<AppliedRule>>) -> String {
+        let bounded = format!("<{}>", word);
+        let mut fired = vec![false; self.rules.len()];
+        let mut result = String::with_capacity(bounded.len());
+        let mut i = 0;
+        while i < bounded.len() {
+            let rest = &bounded[i..];
+            match self.rules.iter().position(|(pattern, _)| rest.starts_with(pattern.as_str())) {
+                Some(index) => {
+                    let (pattern, replacement) = &self.rules[index];
+                    result.push_str(replacement);
+                    fired[index] = true;
+                    i += pattern.len();
+                }
+                None => {
+                    let c = rest.chars().next().expect("i is on a char boundary");
+                    result.push(c);
+                    i += c.len_utf8();
+                }
             }
         }
-        result
-    } else {
-        normalized.to_string()
+        if let Some(trace) = trace {
+            for (index, (pattern, replacement)) in self.rules.iter().enumerate() {
+                if fired[index] {
+                    trace.push(AppliedRule::pass1(pattern, replacement));
+                }
+            }
+        }
+        result[1..result.len() - 1].to_string()
     }
 }
 
-fn pass2(word: &str, threshold: f64) -> String {
+/// `key` without its `<` / `>` anchors.
+fn unanchored(key: &str) -> &str {
+    let key = key.strip_prefix('<').unwrap_or(key);
+    key.strip_suffix('>').unwrap_or(key)
+}
+
+/// Pass 1: high-confidence fixed-pattern corrections (`ft`, `fp`, `fc`, ...)
+/// with the built-in [`Pass1Rules`]. Punctuation around the word is kept.
+pub fn pass1(word: &str) -> String {
+    on_core(word, |core| pass1_traced(core, &DEFAULT_PASS1_RULES, None))
+}
+
+fn pass1_traced(word: &str, rules: &Pass1Rules, mut trace: Option<&mut Vec<AppliedRule>>) -> String {
     // Detect case pattern before lowercasing
-    let word_chars: Vec<char> = word.chars().collect();
-    let is_upper = word_chars.len() > 1 && word_chars.iter().all(|c| !c.is_lowercase());
-    let is_title = word_chars.first().map_or(false, |c| c.is_uppercase())
-        && (word_chars.len() == 1 || !is_upper);
+    let case = WordCase::of(word);
 
-    let normalized = word.to_lowercase();
-    let data = &*NGRAM_DATA;
+    let mut normalized = decompose_traced(case.lower(), trace.as_deref_mut());
 
-    if ALLOWLIST.contains(normalized.as_str()) {
-        return restore_case(&normalized, is_upper, is_title);
+    if normalized.contains(LONG_S) {
+        normalized = replace_long_s(&normalized);
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.push(AppliedRule::pass1("ſ", "s"));
+        }
     }
 
-    let chars: Vec<char> = normalized.chars().collect();
+    normalized = rules.apply_traced(&normalized, trace);
 
-    if chars.len() >= 2 && chars[0] == 'f' && chars[1] == 'u' {
-        let fu_freq = data.trigrams.get("<fu").copied().unwrap_or(0) as f64;
-        let su_freq = data.trigrams.get("<su").copied().unwrap_or(0) as f64;
+    // Restore original case pattern
+    case.restore(&normalized)
+}
 
-        if su_freq > fu_freq * threshold && su_freq > 0.0 {
-            let mut result = String::with_capacity(normalized.len());
-            result.push('s');
-            result.extend(chars[1..].iter());
-            return restore_case(&result, is_upper, is_title);
+/// Pass 2: n-gram frequency disambiguation of word-initial `fu`/`fe`/`fi`,
+/// using the built-in allowlist and bundled model. Punctuation around the
+/// word is kept.
+///
+/// Returns the word unchanged (after a one-time warning) if the bundled model
+/// cannot be loaded; see [`try_pass2`].
+pub fn pass2(word: &str, threshold: f64) -> String {
+    if !DEFAULT_NORMALIZER.pass2_available(true) {
+        return word.to_string();
+    }
+    on_core(word, |core| pass2_traced(&DEFAULT_NORMALIZER, core, threshold, None))
+}
+
+/// Like [`pass2`], but fails if the bundled model cannot be loaded.
+pub fn try_pass2(word: &str, threshold: f64) -> Result<String, NgramsUnavailable> {
+    DEFAULT_NORMALIZER.require_ngrams(true)?;
+    Ok(on_core(word, |core| pass2_traced(&DEFAULT_NORMALIZER, core, threshold, None)))
+}
+
+fn pass2_traced(
+    normalizer: &LongSNormalizer,
+    word: &str,
+    threshold: f64,
+    trace: Option<&mut Vec<AppliedRule>>,
+) -> String {
+    // Detect case pattern before lowercasing
+    let case = WordCase::of(word);
+
+    let normalized = replace_long_s(&decompose_ligatures(case.lower()));
+    let data = normalizer.model();
+
+    if normalizer.denylist.contains(&normalized) {
+        if let Some(rest) = normalized.strip_prefix('f') {
+            if let Some(trace) = trace {
+                trace.push(AppliedRule {
+                    pass: 2,
+                    pattern: "<f → <s (denylist)".to_string(),
+                    evidence: Vec::new(),
+                    score: None,
+                });
+            }
+            return case.restore(&format!("s{}", rest));
         }
-    } else if chars.len() >= 2 && chars[0] == 'f' && chars[1] == 'e' {
-        let fe_freq = data.trigrams.get("<fe").copied().unwrap_or(0) as f64;
-        let se_freq = data.trigrams.get("<se").copied().unwrap_or(0) as f64;
+    }
 
-        if se_freq > fe_freq * threshold && se_freq > 0.0 {
-            let mut result = String::with_capacity(normalized.len());
-            result.push('s');
-            result.extend(chars[1..].iter());
-            return restore_case(&result, is_upper, is_title);
+    if normalizer.allowlist.contains(&normalized) {
+        return case.restore(&normalized);
+    }
+
+    if normalizer.candidate_search {
+        let (best, score) = best_candidate(data, &normalized, threshold);
+        if best != normalized {
+            if let Some(trace) = trace {
+                trace.push(AppliedRule {
+                    pass: 2,
+                    pattern: format!("{} → {} (candidate search)", normalized, best),
+                    evidence: Vec::new(),
+                    score: Some(score),
+                });
+            }
         }
+        return case.restore(&best);
+    }
+
+    let chars: Vec<char> = normalized.chars().collect();
+
+    // fu/fe are decided on the word-initial trigram. The fi trigram ratio
+    // (<fi=7115 vs <si=18787) is only 2.6:1, too noisy, so fi uses the 4-gram
+    // including the next letter (e.g. <fim=13 vs <sim=2149).
+    let keys = if chars.len() >= 2 && chars[0] == 'f' && (chars[1] == 'u' || chars[1] == 'e') {
+        Gram::from_chars(&['<', 'f', chars[1]]).zip(Gram::from_chars(&['<', 's', chars[1]]))
     } else if chars.len() >= 3 && chars[0] == 'f' && chars[1] == 'i' {
-        let fi_key = format!("<fi{}", chars[2]);
-        let si_key = format!("<si{}", chars[2]);
-        let fi_freq = data.fourgrams.get(&fi_key).copied().unwrap_or(0) as f64;
-        let si_freq = data.fourgrams.get(&si_key).copied().unwrap_or(0) as f64;
+        Gram::from_chars(&['<', 'f', 'i', chars[2]]).zip(Gram::from_chars(&['<', 's', 'i', chars[2]]))
+    } else {
+        None
+    };
 
-        if si_freq > fi_freq * threshold && si_freq > 0.0 {
+    if let Some((f_key, s_key)) = keys {
+        let f_freq = data.gram_frequency(f_key);
+        let s_freq = data.gram_frequency(s_key);
+        let score = Pass2Score {
+            f_score: data.gram_log_prob(f_key),
+            s_score: data.gram_log_prob(s_key),
+        };
+        let convert = match normalizer.scoring {
+            Pass2Scoring::Ratio => s_freq as f64 > f_freq as f64 * threshold && s_freq > 0,
+            Pass2Scoring::LogProb => exceeds_margin(score, threshold),
+        };
+
+        if convert {
+            if let Some(trace) = trace {
+                trace.push(AppliedRule::pass2(f_key, f_freq, s_key, s_freq, score));
+            }
             let mut result = String::with_capacity(normalized.len());
             result.push('s');
             result.extend(chars[1..].iter());
-            return restore_case(&result, is_upper, is_title);
+            return case.restore(&result);
         }
     }
 
-    restore_case(&normalized, is_upper, is_title)
+    case.restore(&normalized)
+}
+
+/// How Pass 2 weighs the f- and s-forms of a word-initial n-gram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pass2Scoring {
+    /// Add-one smoothed log probabilities: convert when the s-form is more
+    /// than `threshold` times as probable, i.e. its log probability exceeds
+    /// the f-form's by more than `ln(threshold)`.
+    #[default]
+    LogProb,
+    /// Raw frequency ratio `s > f * threshold` (the behavior before
+    /// smoothing was introduced). Kept for compatibility.
+    Ratio,
+}
+
+/// Whether the s-reading wins by more than the `ln(threshold)` margin. Exact
+/// ties (e.g. 2:1 at threshold 2) can come out a rounding error either side
+/// of the margin once computed through logs, so they are treated as losses.
+fn exceeds_margin(score: Pass2Score, threshold: f64) -> bool {
+    threshold <= 0.0 || score.s_score - score.f_score > threshold.ln() + 1e-9
 }
 
 // ---------------------------------------------------------------------------
-// Public Rust API
+// Medial intervocalic f
 // ---------------------------------------------------------------------------
 
-pub fn normalize_word(word: &str, apply_pass2: bool) -> String {
-    let result = pass1(word);
-    if apply_pass2 {
-        pass2(&result, 2.0)
-    } else {
-        result
+/// Stems with a genuine intervocalic f, mostly prefix compounds (*pro-fero*,
+/// *re-fert*) and *-fex*/*-ficus*/*-fer* formations. The medial pass leaves
+/// any word containing one of these alone.
+const MEDIAL_F_STEMS: &[&str] = &[
+    "profer", "profect", "profic", "profug", "profund", "profan", "profit", "profes",
+    "refer", "refect", "refic", "refug", "refut", "refov",
+    "defend", "defens", "defer", "defect", "defic", "defunct", "defess", "defat",
+    "praefect", "praefer", "praefic", "praefat",
+    "aufer", "nefa", "nefan", "benefic", "malefic",
+    "ific", "ifex", "tifer", "cifer", "nifer", "ruf",
+];
+
+const VOWELS: &[char] = &['a', 'e', 'i', 'o', 'u', 'y'];
+
+fn is_vowel(c: char) -> bool {
+    VOWELS.contains(&c)
+}
+
+/// Medial pass: convert `f` between two vowels to `s` when the trigrams
+/// covering that position are more than `threshold` times as frequent (on
+/// average) with `s`. Words on the allowlist or containing one of
+/// [`MEDIAL_F_STEMS`] are left alone.
+fn medial_traced(
+    normalizer: &LongSNormalizer,
+    word: &str,
+    threshold: f64,
+    mut trace: Option<&mut Vec<AppliedRule>>,
+) -> String {
+    let case = WordCase::of(word);
+    let normalized = case.lower();
+    if normalizer.allowlist.contains(normalized) || MEDIAL_F_STEMS.iter().any(|stem| normalized.contains(stem)) {
+        return word.to_string();
+    }
+
+    let data = normalizer.model();
+    let margin = 3.0 * threshold.ln();
+    // Padded with boundary markers so trigrams at the edges match the tables
+    let mut chars: Vec<char> = std::iter::once('<').chain(normalized.chars()).chain(std::iter::once('>')).collect();
+    let mut changed = false;
+
+    for i in 2..chars.len().saturating_sub(2) {
+        if chars[i] != 'f' || !is_vowel(chars[i - 1]) || !is_vowel(chars[i + 1]) {
+            continue;
+        }
+        let mut keys = Vec::with_capacity(6);
+        let mut score = Pass2Score { f_score: 0.0, s_score: 0.0 };
+        for start in i - 2..=i {
+            let mut trigram = [chars[start], chars[start + 1], chars[start + 2]];
+            let f_key = Gram::from_chars(&trigram).expect("three chars");
+            trigram[i - start] = 's';
+            let s_key = Gram::from_chars(&trigram).expect("three chars");
+            score.f_score += data.gram_log_prob(f_key);
+            score.s_score += data.gram_log_prob(s_key);
+            keys.push(f_key);
+            keys.push(s_key);
+        }
+        if score.s_score - score.f_score > margin {
+            chars[i] = 's';
+            changed = true;
+            if let Some(trace) = trace.as_deref_mut() {
+                let evidence = keys.iter().map(|&key| (key.to_string(), data.gram_frequency(key))).collect();
+                trace.push(AppliedRule {
+                    pass: 2,
+                    pattern: format!("{}f{} → {}s{} (medial)", chars[i - 1], chars[i + 1], chars[i - 1], chars[i + 1]),
+                    evidence,
+                    score: Some(score),
+                });
+            }
+        }
+    }
+
+    if !changed {
+        return word.to_string();
     }
+    let result: String = chars[1..chars.len() - 1].iter().collect();
+    case.restore(&result)
 }
 
-pub fn normalize_text(text: &str, apply_pass2: bool) -> String {
-    text.split_whitespace()
-        .map(|word| normalize_word(word, apply_pass2))
-        .collect::<Vec<_>>()
-        .join(" ")
+// ---------------------------------------------------------------------------
+// Double f
+// ---------------------------------------------------------------------------
+
+/// Readings tried for an `ff` cluster besides `ff` itself.
+const DOUBLE_F_READINGS: &[[char; 2]] = &[['s', 's'], ['s', 'f'], ['f', 's']];
+
+/// Prefix compounds with a genuine `ff` whose n-gram evidence is too close
+/// to call (*ef-fundo*, *of-fendo*). Words containing one are left alone.
+const DOUBLE_F_STEMS: &[&str] = &[
+    "effund", "effus", "diffund", "diffus", "offend", "offens", "effer", "offer", "affer", "differ",
+];
+
+/// Double-f pass: resolve each `ff` cluster as `ff`, `ss`, `sf`, or `fs` by
+/// the 4-grams overlapping it (*poffum* → *possum*, *effe* → *esse*). A
+/// reading replaces `ff` when its 4-grams are more than `threshold` times
+/// as frequent on average; genuine clusters (*officium*, *sufficit*) mostly
+/// win on their own evidence. Allowlisted words and words containing one of
+/// [`DOUBLE_F_STEMS`] are left alone.
+fn double_f_traced(
+    normalizer: &LongSNormalizer,
+    word: &str,
+    threshold: f64,
+    mut trace: Option<&mut Vec<AppliedRule>>,
+) -> String {
+    let case = WordCase::of(word);
+    let normalized = case.lower();
+    if !normalized.contains("ff")
+        || normalizer.allowlist.contains(normalized)
+        || DOUBLE_F_STEMS.iter().any(|stem| normalized.contains(stem))
+    {
+        return word.to_string();
+    }
+
+    let data = normalizer.model();
+    let mut chars: Vec<char> = std::iter::once('<').chain(normalized.chars()).chain(std::iter::once('>')).collect();
+    let mut changed = false;
+
+    let mut i = 1;
+    while i + 2 < chars.len() {
+        if chars[i] != 'f' || chars[i + 1] != 'f' {
+            i += 1;
+            continue;
+        }
+        // 4-grams covering either letter of the cluster
+        let starts: Vec<usize> = (i.saturating_sub(3)..=i + 1).filter(|&start| start + 4 <= chars.len()).collect();
+        let score_reading = |reading: [char; 2]| {
+            let mut variant = chars.clone();
+            variant[i] = reading[0];
+            variant[i + 1] = reading[1];
+            let keys: Vec<Gram> = starts
+                .iter()
+                .map(|&start| Gram::from_chars(&variant[start..start + 4]).expect("four chars"))
+                .collect();
+            let score = keys.iter().map(|&key| data.gram_log_prob(key)).sum::<f64>();
+            (keys, score)
+        };
+
+        let (f_keys, f_score) = score_reading(['f', 'f']);
+        let (reading, s_keys, s_score) = DOUBLE_F_READINGS
+            .iter()
+            .map(|&reading| {
+                let (keys, score) = score_reading(reading);
+                (reading, keys, score)
+            })
+            .fold(None, |best: Option<([char; 2], Vec<Gram>, f64)>, item| match best {
+                Some(b) if b.2 >= item.2 => Some(b),
+                _ => Some(item),
+            })
+            .expect("DOUBLE_F_READINGS is not empty");
+
+        if s_score - f_score > starts.len() as f64 * threshold.ln() {
+            chars[i] = reading[0];
+            chars[i + 1] = reading[1];
+            changed = true;
+            if let Some(trace) = trace.as_deref_mut() {
+                let evidence = f_keys
+                    .into_iter()
+                    .zip(s_keys)
+                    .flat_map(|(f_key, s_key)| {
+                        let f_freq = data.gram_frequency(f_key);
+                        let s_freq = data.gram_frequency(s_key);
+                        [(f_key.to_string(), f_freq), (s_key.to_string(), s_freq)]
+                    })
+                    .collect();
+                trace.push(AppliedRule {
+                    pass: 2,
+                    pattern: format!("ff → {}{} (double f)", reading[0], reading[1]),
+                    evidence,
+                    score: Some(Pass2Score { f_score, s_score }),
+                });
+            }
+        }
+        i += 2;
+    }
+
+    if !changed {
+        return word.to_string();
+    }
+    let result: String = chars[1..chars.len() - 1].iter().collect();
+    case.restore(&result)
 }
 
 // ---------------------------------------------------------------------------
-// PyO3 wrappers
+// Candidate search
 // ---------------------------------------------------------------------------
 
-#[cfg(feature = "pyo3-backend")]
-#[pyfunction]
-pub fn normalize_long_s_word_pass1(word: &str) -> String {
-    pass1(word)
+/// Maximum number of `f` positions varied by [`candidates`]; later ones are
+/// left as they are, bounding the search at 2^10 candidates.
+pub const MAX_CANDIDATE_POSITIONS: usize = 10;
+
+/// `f` misread for long s, as an OCR confusion.
+static F_FOR_LONG_S: LazyLock<ConfusionMatrix> = LazyLock::new(|| {
+    let mut matrix = ConfusionMatrix::empty();
+    matrix.add("f", "s", 1.0).expect("f → s is a valid confusion");
+    matrix
+});
+
+/// Every spelling of `word` (lowercased) obtained by reading any subset of
+/// its `f`s as long s. The unchanged word comes first.
+pub fn candidates(word: &str) -> Vec<String> {
+    let chars: Vec<char> = replace_long_s(&decompose_ligatures(&word.to_lowercase())).chars().collect();
+    let sites = F_FOR_LONG_S.sites(&chars);
+    ocr::readings(&chars, &sites[..sites.len().min(MAX_CANDIDATE_POSITIONS)])
+        .into_iter()
+        .map(|(candidate, _)| candidate)
+        .collect()
 }
 
-#[cfg(feature = "pyo3-backend")]
-#[pyfunction]
-#[pyo3(signature = (word, threshold=2.0))]
-pub fn normalize_long_s_word_pass2(word: &str, threshold: f64) -> String {
-    pass2(word, threshold)
+/// The highest-scoring candidate for a lowercased word, if it beats the
+/// word itself by more than a factor of `threshold` in probability, with the
+/// log probabilities of the word and the best candidate.
+fn best_candidate(data: &NgramData, word: &str, threshold: f64) -> (String, Pass2Score) {
+    let original = data.log_prob(word);
+    let mut best = (original, word.to_string());
+    for candidate in candidates(word).into_iter().skip(1) {
+        let score = data.log_prob(&candidate);
+        if score > best.0 {
+            best = (score, candidate);
+        }
+    }
+    let score = Pass2Score {
+        f_score: original,
+        s_score: best.0,
+    };
+    if exceeds_margin(score, threshold) {
+        (best.1, score)
+    } else {
+        (word.to_string(), score)
+    }
 }
 
-#[cfg(feature = "pyo3-backend")]
-#[pyfunction]
-#[pyo3(signature = (word, apply_pass2=true))]
-pub fn normalize_long_s_word_full(word: &str, apply_pass2: bool) -> String {
-    normalize_word(word, apply_pass2)
+/// The `n` most probable [`candidates`] of `word` under `data`'s 4-gram
+/// model, best first, scored as probabilities that sum to 1 over all the
+/// candidates. Case and surrounding punctuation are kept.
+fn suggestions(data: &NgramData, word: &str, n: usize) -> Vec<(String, f64)> {
+    let (lead, core, trail) = split_affixes(word);
+    if core.is_empty() {
+        return vec![(word.to_string(), 1.0)].into_iter().take(n).collect();
+    }
+    let case = WordCase::of(core);
+    let mut scored: Vec<(String, f64)> = candidates(core)
+        .into_iter()
+        .map(|candidate| {
+            let score = data.log_prob(&candidate);
+            (candidate, score)
+        })
+        .collect();
+    let max = scored.iter().map(|(_, score)| *score).fold(f64::NEG_INFINITY, f64::max);
+    let total: f64 = scored.iter().map(|(_, score)| (score - max).exp()).sum();
+    for (_, score) in &mut scored {
+        *score = (*score - max).exp() / total;
+    }
+    // Stable, so equally likely readings keep the candidate order
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored
+        .into_iter()
+        .take(n)
+        .map(|(candidate, score)| (format!("{}{}{}", lead, case.restore(&candidate), trail), score))
+        .collect()
 }
 
-#[cfg(feature = "pyo3-backend")]
-#[pyfunction]
-#[pyo3(signature = (text, apply_pass2=true))]
-pub fn normalize_long_s_text_full(text: &str, apply_pass2: bool) -> String {
-    normalize_text(text, apply_pass2)
+/// The `n` most likely f/s readings of `word` under the bundled model (see
+/// [`LongSNormalizer::suggest`]).
+pub fn suggest(word: &str, n: usize) -> Vec<(String, f64)> {
+    DEFAULT_NORMALIZER.suggest(word, n)
 }
 
 // ---------------------------------------------------------------------------
-// Tests
+// Lexicon backoff (Pass 3)
 // ---------------------------------------------------------------------------
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_pass1_trigrams() {
-        assert_eq!(pass1("ftatua"), "statua");
-        assert_eq!(pass1("fpiritus"), "spiritus");
-        assert_eq!(pass1("fufcepit"), "suscepit");
-        assert_eq!(pass1("fumma"), "summa");
-        assert_eq!(pass1("fquama"), "squama");
+/// Pass 3: if `word` (the Pass 1/2 result for `original`) is not in the
+/// lexicon, switch to the most frequent attested f/s spelling of the
+/// original, in either direction (*caufa* → *causa*, or back to a genuine
+/// f-word that Pass 2 converted).
+fn pass3_traced(lexicon: &Lexicon, original: &str, word: &str, trace: Option<&mut Vec<AppliedRule>>) -> String {
+    if lexicon.contains(word) {
+        return word.to_string();
     }
+    let case = WordCase::of(word);
+    let lower = case.lower();
 
-    #[test]
-    fn test_pass1_bigrams() {
-        assert_eq!(pass1("fpecies"), "species");
-        assert_eq!(pass1("ftella"), "stella");
-        assert_eq!(pass1("fcientia"), "scientia");
-    }
+    let best = candidates(original)
+        .into_iter()
+        .map(|candidate| {
+            let freq = lexicon.frequency(&candidate);
+            (candidate, freq)
+        })
+        .filter(|(_, freq)| *freq > 0)
+        .fold(None, |best: Option<(String, u64)>, item| match best {
+            Some(b) if b.1 >= item.1 => Some(b),
+            _ => Some(item),
+        });
 
-    #[test]
-    fn test_pass1_word_final() {
-        assert_eq!(pass1("ef"), "es");
-        assert_eq!(pass1("reuf"), "reus");
+    match best {
+        Some((best, freq)) if best != lower => {
+            if let Some(trace) = trace {
+                trace.push(AppliedRule {
+                    pass: 3,
+                    pattern: format!("{} → {} (lexicon)", lower, best),
+                    evidence: vec![(lower.to_string(), 0), (best.clone(), freq)],
+                    score: None,
+                });
+            }
+            case.restore(&best)
+        }
+        _ => word.to_string(),
     }
+}
 
-    #[test]
-    fn test_pass1_case_preservation() {
-        assert_eq!(pass1("FTATUA"), "STATUA");
-        assert_eq!(pass1("Fpiritus"), "Spiritus");
-        assert_eq!(pass1("ftatua"), "statua");
-    }
+// ---------------------------------------------------------------------------
+// Word context
+// ---------------------------------------------------------------------------
 
-    #[test]
-    fn test_normalize_word_pass1_only() {
-        assert_eq!(normalize_word("ftatua", false), "statua");
-        assert_eq!(normalize_word("fpiritus", false), "spiritus");
+/// Smoothed log score of `word` between its neighbours in the word-bigram
+/// table, with the evidence looked up.
+fn context_score(data: &NgramData, word: &str, prev: Option<&str>, next: Option<&str>) -> (f64, Vec<(String, u64)>) {
+    let mut score = 0.0;
+    let mut evidence = Vec::new();
+    let pairs = [prev.map(|prev| (prev, word)), next.map(|next| (word, next))];
+    for (first, second) in pairs.into_iter().flatten() {
+        let count = data.word_bigram_count(first, second);
+        score += (count as f64 + NGRAM_SMOOTHING).ln();
+        evidence.push((format!("{} {}", first, second), count));
     }
+    (score, evidence)
+}
 
-    #[test]
-    fn test_normalize_word_with_pass2() {
-        assert_eq!(normalize_word("funt", true), "sunt");
+/// Context pass: rescore the f/s readings of `original` by how often each
+/// appears after `prev` and before `next` (lowercased neighbouring words),
+/// and switch from `word`, the result so far, when another reading is more
+/// than `threshold` times as likely. Settles short tokens that are words
+/// either way, such as *fi*/*si* and *fe*/*se*.
+fn context_traced(
+    data: &NgramData,
+    original: &str,
+    word: &str,
+    prev: Option<&str>,
+    next: Option<&str>,
+    threshold: f64,
+    trace: Option<&mut Vec<AppliedRule>>,
+) -> String {
+    let readings = candidates(original);
+    if readings.len() < 2 || (prev.is_none() && next.is_none()) {
+        return word.to_string();
     }
+    let case = WordCase::of(word);
+    let lower = case.lower();
+    let (current, current_evidence) = context_score(data, lower, prev, next);
 
-    #[test]
-    fn test_normalize_text() {
-        assert_eq!(
-            normalize_text("ftatua fpiritus funt", true),
-            "statua spiritus sunt"
-        );
+    let mut best = (current, lower.to_string(), Vec::new());
+    for reading in readings {
+        let (score, evidence) = context_score(data, &reading, prev, next);
+        if score > best.0 {
+            best = (score, reading, evidence);
+        }
     }
-
-    #[test]
-    fn test_normalize_text_case_preservation() {
-        assert_eq!(
-            normalize_text("Sic uita eft", true),
-            "Sic uita est"
-        );
+    let score = Pass2Score {
+        f_score: current,
+        s_score: best.0,
+    };
+    if best.1 == lower || !exceeds_margin(score, threshold) {
+        return word.to_string();
     }
 
-    #[test]
-    fn test_allowlist_preserved() {
-        assert_eq!(normalize_word("fuit", true), "fuit");
+    if let Some(trace) = trace {
+        trace.push(AppliedRule {
+            pass: 2,
+            pattern: format!("{} → {} (context)", lower, best.1),
+            evidence: current_evidence.into_iter().chain(best.2).collect(),
+            score: Some(score),
+        });
     }
+    case.restore(&best.1)
+}
 
-    #[test]
-    fn test_allowlist_case_preserved() {
-        assert_eq!(normalize_word("Fuit", true), "Fuit");
-        assert_eq!(normalize_word("FUIT", true), "FUIT");
+/// The lowercased word on each side of unit `i`, if it is a plain word
+/// separated from unit `i` by whitespace only, within the same paragraph.
+fn neighbours<'a>(units: &[Unit<'_>], words: &'a [Option<String>], i: usize) -> (Option<&'a str>, Option<&'a str>) {
+    let adjacent = |gap: usize, word: usize| match units.get(gap) {
+        Some(Unit::Gap(gap)) if gap.chars().all(char::is_whitespace) && !is_paragraph_break(gap) => {
+            words.get(word).and_then(|w| w.as_deref())
+        }
+        _ => None,
+    };
+    let prev = if i >= 2 { adjacent(i - 1, i - 2) } else { None };
+    (prev, adjacent(i + 1, i + 2))
+}
+
+// ---------------------------------------------------------------------------
+// Tokenization
+// ---------------------------------------------------------------------------
+
+/// A maximal run of either a word, letters and the combining marks on them,
+/// or the characters between words.
+struct Segment<'a> {
+    /// Character offset of the run in the original text.
+    position: usize,
+    text: &'a str,
+    is_word: bool,
+}
+
+/// Split text into alternating word / non-word runs without losing anything:
+/// concatenating every segment's text reproduces the input exactly.
+fn segments(text: &str) -> impl Iterator<Item = Segment<'_>> {
+    let mut rest = text;
+    let mut position = 0;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let is_word = first.is_alphabetic();
+        let mut end = rest.len();
+        let mut char_len = 0;
+        for (i, c) in rest.char_indices() {
+            let in_word = c.is_alphabetic() || (is_word && graphemes::is_extender(c));
+            if in_word != is_word {
+                end = i;
+                break;
+            }
+            char_len += 1;
+        }
+        let segment = Segment {
+            position,
+            text: &rest[..end],
+            is_word,
+        };
+        position += char_len;
+        rest = &rest[end..];
+        Some(segment)
+    })
+}
+
+/// Characters accepted as a line-break hyphen: hyphen-minus, Unicode hyphen,
+/// soft hyphen, and the `¬` used by many OCR engines.
+pub(crate) const LINE_BREAK_HYPHENS: &[char] = &['-', '\u{2010}', '\u{00AD}', '¬'];
+
+/// Whether a non-word run is a hyphen followed by a line break, allowing
+/// spaces or tabs around the break.
+fn is_line_break_hyphen(text: &str) -> bool {
+    let Some(rest) = text.strip_prefix(LINE_BREAK_HYPHENS) else {
+        return false;
+    };
+    matches!(rest.trim_matches([' ', '\t']), "\n" | "\r\n")
+}
+
+/// Whether a non-word run contains a blank line, i.e. two line breaks with
+/// only whitespace between them.
+fn is_paragraph_break(text: &str) -> bool {
+    let pieces: Vec<&str> = text.split('\n').collect();
+    pieces.len() > 2 && pieces[1..pieces.len() - 1].iter().any(|piece| piece.trim().is_empty())
+}
+
+/// Whether `line` ends with a word followed by a line-break hyphen, i.e.
+/// continues on the next line.
+pub(crate) fn ends_with_line_break_hyphen(line: &str) -> bool {
+    let Some(body) = line.strip_suffix('\n') else {
+        return false;
+    };
+    let body = body.strip_suffix('\r').unwrap_or(body).trim_end_matches([' ', '\t']);
+    let mut chars = body.chars().rev();
+    chars.next().is_some_and(|c| LINE_BREAK_HYPHENS.contains(&c)) && chars.next().is_some_and(char::is_alphabetic)
+}
+
+/// Smallest chunk handed to a worker by
+/// [`LongSNormalizer::normalize_text_parallel`].
+#[cfg(feature = "parallel")]
+pub const PARALLEL_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Split `text` after paragraph breaks into chunks of at least `min_bytes`
+/// (the last may be shorter). Concatenating the chunks gives `text` back.
+#[cfg(feature = "parallel")]
+fn paragraph_chunks(text: &str, min_bytes: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for segment in segments(text) {
+        offset += segment.text.len();
+        if !segment.is_word && offset - start >= min_bytes && is_paragraph_break(segment.text) {
+            chunks.push(&text[start..offset]);
+            start = offset;
+        }
+    }
+    if start < text.len() {
+        chunks.push(&text[start..]);
+    }
+    chunks
+}
+
+/// A word, a gap between words, or a word split by a line-break hyphen.
+enum Unit<'a> {
+    Gap(&'a str),
+    Word(Segment<'a>),
+    Hyphenated {
+        first: Segment<'a>,
+        separator: &'a str,
+        second: &'a str,
+    },
+}
+
+/// Group segments into units, joining `word-⏎word` runs when `join` is set.
+fn units(text: &str, join: bool) -> Vec<Unit<'_>> {
+    let mut units = Vec::new();
+    let mut segments = segments(text).peekable();
+    while let Some(segment) = segments.next() {
+        if !segment.is_word {
+            units.push(Unit::Gap(segment.text));
+            continue;
+        }
+        if let Some(separator) = segments.next_if(|next| join && is_line_break_hyphen(next.text)) {
+            match segments.next() {
+                Some(second) => units.push(Unit::Hyphenated {
+                    first: segment,
+                    separator: separator.text,
+                    second: second.text,
+                }),
+                None => {
+                    units.push(Unit::Word(segment));
+                    units.push(Unit::Gap(separator.text));
+                }
+            }
+            continue;
+        }
+        units.push(Unit::Word(segment));
+    }
+    units
+}
+
+/// How words split by a hyphenated line break (`poſſi-` / `mus`) are
+/// normalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Hyphenation {
+    /// Normalize each half as a word of its own (default).
+    #[default]
+    Separate,
+    /// Normalize the rejoined word and emit it without the hyphen and line
+    /// break.
+    Rejoin,
+    /// Normalize the rejoined word, but write it back around the original
+    /// hyphen and line break so the layout is unchanged.
+    Preserve,
+}
+
+// ---------------------------------------------------------------------------
+// Public Rust API
+// ---------------------------------------------------------------------------
+
+/// Default Pass 2 threshold: replace `f` with `s` when the s-form n-gram is
+/// more than twice as frequent as the f-form.
+pub const DEFAULT_THRESHOLD: f64 = 2.0;
+
+pub fn normalize_word(word: &str, apply_pass2: bool, threshold: f64) -> String {
+    DEFAULT_NORMALIZER.normalize_word_with(word, apply_pass2, threshold, None)
+}
+
+/// Normalize every word in `text`, copying whitespace and punctuation through
+/// unchanged.
+pub fn normalize_text(text: &str, apply_pass2: bool, threshold: f64) -> String {
+    DEFAULT_NORMALIZER.normalize_text_with(text, apply_pass2, threshold)
+}
+
+/// Normalize a word, recording every rule that fired.
+pub fn normalize_word_traced(
+    word: &str,
+    apply_pass2: bool,
+    threshold: f64,
+) -> (String, Vec<AppliedRule>) {
+    let mut rules = Vec::new();
+    let result = DEFAULT_NORMALIZER.normalize_word_with(word, apply_pass2, threshold, Some(&mut rules));
+    (result, rules)
+}
+
+pub fn normalize_detailed(text: &str, apply_pass2: bool, threshold: f64) -> DetailedResult {
+    DEFAULT_NORMALIZER.normalize_detailed_with(text, apply_pass2, threshold)
+}
+
+/// Like [`normalize_word`], but fails instead of falling back to Pass 1 when
+/// `apply_pass2` is set and the bundled model cannot be loaded.
+pub fn try_normalize_word(word: &str, apply_pass2: bool, threshold: f64) -> Result<String, NgramsUnavailable> {
+    DEFAULT_NORMALIZER.require_ngrams(apply_pass2)?;
+    Ok(normalize_word(word, apply_pass2, threshold))
+}
+
+/// Like [`normalize_text`], but fails if the bundled model is unavailable.
+pub fn try_normalize_text(text: &str, apply_pass2: bool, threshold: f64) -> Result<String, NgramsUnavailable> {
+    DEFAULT_NORMALIZER.require_ngrams(apply_pass2)?;
+    Ok(normalize_text(text, apply_pass2, threshold))
+}
+
+/// Like [`normalize_detailed`], but fails if the bundled model is unavailable.
+pub fn try_normalize_detailed(text: &str, apply_pass2: bool, threshold: f64) -> Result<DetailedResult, NgramsUnavailable> {
+    DEFAULT_NORMALIZER.require_ngrams(apply_pass2)?;
+    Ok(normalize_detailed(text, apply_pass2, threshold))
+}
+
+/// Normalize `text` split into paragraph chunks on all cores (see
+/// [`LongSNormalizer::normalize_text_parallel`]).
+#[cfg(feature = "parallel")]
+pub fn normalize_text_parallel(text: &str, apply_pass2: bool, threshold: f64) -> String {
+    DEFAULT_NORMALIZER.normalize_text_parallel_with(text, apply_pass2, threshold)
+}
+
+/// Normalize text read from `reader` into `writer` a paragraph at a time
+/// (see [`LongSNormalizer::normalize_stream`]).
+pub fn normalize_stream<R: BufRead, W: Write>(reader: R, writer: W, apply_pass2: bool, threshold: f64) -> io::Result<()> {
+    DEFAULT_NORMALIZER.normalize_stream_with(reader, writer, apply_pass2, threshold)
+}
+
+// ---------------------------------------------------------------------------
+// Languages
+// ---------------------------------------------------------------------------
+
+/// Language of the text being normalized. Long s was used in early modern
+/// English, German, and French prints as well as Latin, with different
+/// confusion patterns: `ft` is impossible in Latin but common in English
+/// *after* and German *Luft*, and word-final f is genuine in English *of*
+/// and French *neuf*.
+///
+/// Each language has its own Pass 1 rules and allowlist. Pass 2 needs an
+/// n-gram model for the language: the bundled model for Latin, or for the
+/// others an installed model named after the language (see
+/// [`Language::model_name`] and [`crate::ngrams::model`]). Without one,
+/// Pass 2 makes no changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    Latin,
+    English,
+    German,
+    French,
+}
+
+const ENGLISH_PASS1_RULES: &[(&str, &str)] = &[
+    ("fp", "sp"),
+    ("fc", "sc"),
+    ("fk", "sk"),
+    ("fq", "sq"),
+    ("<fh", "<sh"),
+    ("<fl", "<sl"),
+    ("<fm", "<sm"),
+    ("<fn", "<sn"),
+    ("<fw", "<sw"),
+];
+
+const GERMAN_PASS1_RULES: &[(&str, &str)] = &[
+    ("<fch", "<sch"),
+    ("<fp", "<sp"),
+    ("<ft", "<st"),
+    ("ifch>", "isch>"),
+];
+
+const FRENCH_PASS1_RULES: &[(&str, &str)] = &[
+    ("ft", "st"),
+    ("fp", "sp"),
+    ("fc", "sc"),
+    ("fq", "sq"),
+    ("fm", "sm"),
+];
+
+const ENGLISH_ALLOWLIST: &[&str] = &[
+    "fee", "feed", "feel", "feet", "fell", "fellow", "felt", "female", "fence", "fever", "few",
+    "field", "fierce", "fifty", "fight", "figure", "file", "fill", "final", "find", "fine",
+    "finger", "finish", "fire", "firm", "first", "fish", "fit", "five", "fix", "fuel", "full",
+    "fully", "fun", "fund", "funeral", "fur", "furnish", "further", "fury", "fuse", "future",
+];
+
+const GERMAN_ALLOWLIST: &[&str] = &[
+    "feder", "fehler", "feier", "fein", "feind", "feld", "fels", "fenster", "fern", "ferne",
+    "fest", "fett", "feuer", "fieber", "finden", "finger", "finster", "fisch", "fuchs", "fuhr",
+    "fund", "funke", "furcht", "fuß",
+];
+
+const FRENCH_ALLOWLIST: &[&str] = &[
+    "femme", "fenêtre", "fer", "ferme", "festin", "feu", "feuille", "fidèle", "fier", "figure",
+    "fille", "fils", "fin", "fine", "finir", "fit", "fuir", "fumée", "fureur", "furent",
+    "fusil", "fut", "futur",
+];
+
+impl Language {
+    /// ISO 639-1 code: `la`, `en`, `de`, or `fr`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::Latin => "la",
+            Language::English => "en",
+            Language::German => "de",
+            Language::French => "fr",
+        }
+    }
+
+    /// The language for an ISO 639-1 code or English name (`"de"`,
+    /// `"german"`), case-insensitively.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.to_lowercase().as_str() {
+            "la" | "latin" => Some(Language::Latin),
+            "en" | "english" => Some(Language::English),
+            "de" | "german" => Some(Language::German),
+            "fr" | "french" => Some(Language::French),
+            _ => None,
+        }
+    }
+
+    /// Name of the n-gram model Pass 2 uses: [`crate::ngrams::DEFAULT_MODEL`]
+    /// for Latin, otherwise the English name (`"german"`).
+    pub fn model_name(self) -> &'static str {
+        match self {
+            Language::Latin => crate::ngrams::DEFAULT_MODEL,
+            Language::English => "english",
+            Language::German => "german",
+            Language::French => "french",
+        }
+    }
+
+    /// The built-in Pass 1 rules for the language.
+    pub fn pass1_rules(self) -> Pass1Rules {
+        let rules = match self {
+            Language::Latin => return Pass1Rules::default(),
+            Language::English => ENGLISH_PASS1_RULES,
+            Language::German => GERMAN_PASS1_RULES,
+            Language::French => FRENCH_PASS1_RULES,
+        };
+        let mut table = Pass1Rules::empty();
+        for &(pattern, replacement) in rules {
+            table.add(pattern, replacement).expect("built-in Pass 1 rules are valid");
+        }
+        table
+    }
+
+    /// The built-in Pass 2 allowlist of genuine f-words for the language.
+    pub fn allowlist(self) -> &'static [&'static str] {
+        match self {
+            Language::Latin => ALLOWLIST,
+            Language::English => ENGLISH_ALLOWLIST,
+            Language::German => GERMAN_ALLOWLIST,
+            Language::French => FRENCH_ALLOWLIST,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Configurable normalizer
+// ---------------------------------------------------------------------------
+
+/// Shared instance with the default configuration, used by the free functions.
+static DEFAULT_NORMALIZER: LazyLock<LongSNormalizer> = LazyLock::new(LongSNormalizer::new);
+
+/// A long-s normalizer with its own configuration.
+///
+/// Words on the allowlist are never changed by Pass 2; words on the denylist
+/// always have their initial `f` converted to `s`, whatever the n-gram
+/// evidence. The denylist wins if a word is on both.
+///
+/// ```
+/// use latincy_preprocess::long_s::LongSNormalizer;
+///
+/// let normalizer = LongSNormalizer::builder()
+///     .threshold(3.0)
+///     .allowlist(["fuga"])
+///     .build();
+/// # #[cfg(feature = "embed-data")]
+/// assert_eq!(normalizer.normalize_text("fuga funt"), "fuga sunt");
+/// ```
+#[derive(Debug, Clone)]
+pub struct LongSNormalizer {
+    threshold: f64,
+    apply_pass2: bool,
+    allowlist: HashSet<String>,
+    denylist: HashSet<String>,
+    /// Score whole-word f/s candidates instead of the word-initial lookups.
+    candidate_search: bool,
+    /// Run the medial intervocalic f pass after Pass 2.
+    medial_pass: bool,
+    /// Resolve `ff` clusters before Pass 1.
+    double_f: bool,
+    scoring: Pass2Scoring,
+    /// Re-check words against their neighbours in the word-bigram table.
+    context: bool,
+    hyphenation: Hyphenation,
+    language: Language,
+    pass1_rules: Pass1Rules,
+    /// Wordform list for Pass 3; `None` skips the pass.
+    lexicon: Option<Arc<Lexicon>>,
+    /// Custom n-gram tables; `None` uses the bundled model.
+    ngrams: Option<Arc<NgramData>>,
+    /// Where `ngrams` came from, before any domain interpolation.
+    source: DataSource,
+    /// Words Pass 2 and the passes after it leave alone; `None` protects
+    /// nothing.
+    names: Option<NameGuard>,
+    /// Results of earlier words, if caching.
+    cache: Option<Arc<WordCache>>,
+    /// This configuration's key in `cache`.
+    config: u64,
+}
+
+impl Default for LongSNormalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LongSNormalizer {
+    /// A normalizer with the default threshold, Pass 2 enabled, the built-in
+    /// allowlist, an empty denylist, the built-in [`NameGuard`], and the
+    /// bundled n-gram model.
+    pub fn new() -> Self {
+        LongSNormalizer {
+            threshold: DEFAULT_THRESHOLD,
+            apply_pass2: true,
+            allowlist: ALLOWLIST.iter().map(|w| w.to_string()).collect(),
+            denylist: HashSet::new(),
+            candidate_search: false,
+            medial_pass: false,
+            double_f: false,
+            scoring: Pass2Scoring::default(),
+            context: false,
+            hyphenation: Hyphenation::default(),
+            language: Language::default(),
+            pass1_rules: Pass1Rules::default(),
+            lexicon: None,
+            ngrams: None,
+            source: DataSource::Bundled,
+            names: Some(NameGuard::new()),
+            cache: None,
+            config: 0,
+        }
+    }
+
+    /// A default normalizer using the installed n-gram model called `name`
+    /// (see [`crate::ngrams::model`]).
+    pub fn with_model(name: &str) -> io::Result<Self> {
+        Ok(Self::builder().model(name)?.build())
+    }
+
+    pub fn builder() -> LongSNormalizerBuilder {
+        LongSNormalizerBuilder {
+            normalizer: LongSNormalizer::new(),
+            domain: None,
+        }
+    }
+
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    pub fn apply_pass2(&self) -> bool {
+        self.apply_pass2
+    }
+
+    pub fn candidate_search(&self) -> bool {
+        self.candidate_search
+    }
+
+    pub fn medial_pass(&self) -> bool {
+        self.medial_pass
+    }
+
+    pub fn double_f(&self) -> bool {
+        self.double_f
+    }
+
+    pub fn scoring(&self) -> Pass2Scoring {
+        self.scoring
+    }
+
+    pub fn context(&self) -> bool {
+        self.context
+    }
+
+    pub fn hyphenation(&self) -> Hyphenation {
+        self.hyphenation
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn lexicon(&self) -> Option<&Lexicon> {
+        self.lexicon.as_deref()
+    }
+
+    pub fn name_guard(&self) -> Option<&NameGuard> {
+        self.names.as_ref()
+    }
+
+    pub fn word_cache(&self) -> Option<&Arc<WordCache>> {
+        self.cache.as_ref()
+    }
+
+    /// Whether `word`, after `before` in the text, is a name the statistical
+    /// passes must skip. Denylisted words are never protected.
+    fn is_protected(&self, word: &str, before: &[char]) -> bool {
+        self.names.as_ref().is_some_and(|guard| guard.protects(word, before)) && !self.is_denylisted(word)
+    }
+
+    /// Where the n-gram model was loaded from; `resolve()` it to see which
+    /// file the bundled model comes from.
+    pub fn data_source(&self) -> &DataSource {
+        &self.source
+    }
+
+    /// The n-gram model for Pass 2: the configured one, or the bundled model.
+    pub fn ngrams(&self) -> Result<&NgramData, NgramsUnavailable> {
+        match &self.ngrams {
+            Some(ngrams) => Ok(ngrams),
+            None => crate::ngrams::bundled(),
+        }
+    }
+
+    /// The model the passes score with. Entry points check [`Self::ngrams`]
+    /// first and skip Pass 2 if it failed, so the empty fallback never
+    /// decides anything.
+    fn model(&self) -> &NgramData {
+        static EMPTY: LazyLock<NgramData> = LazyLock::new(NgramData::default);
+        self.ngrams().unwrap_or(&EMPTY)
+    }
+
+    /// Whether Pass 2 can run when requested; warns once and degrades to
+    /// Pass 1 only if the bundled model failed to load.
+    fn pass2_available(&self, apply_pass2: bool) -> bool {
+        if !apply_pass2 {
+            return false;
+        }
+        match self.ngrams() {
+            Ok(_) => true,
+            Err(err) => {
+                crate::ngrams::warn_unavailable(&err);
+                false
+            }
+        }
+    }
+
+    /// Fail if Pass 2 is requested but the n-gram model is unavailable.
+    fn require_ngrams(&self, apply_pass2: bool) -> Result<(), NgramsUnavailable> {
+        if apply_pass2 {
+            self.ngrams()?;
+        }
+        Ok(())
+    }
+
+    /// Add legitimate f-words that Pass 2 must leave alone.
+    pub fn extend_allowlist<I, S>(&mut self, words: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.allowlist
+            .extend(words.into_iter().map(|w| w.as_ref().to_lowercase()));
+        self.config = crate::cache::new_config();
+    }
+
+    /// The Pass 1 rule table.
+    pub fn pass1_rules(&self) -> &Pass1Rules {
+        &self.pass1_rules
+    }
+
+    /// Add a Pass 1 rule (see [`Pass1Rules::add`]).
+    pub fn add_pass1_rule(&mut self, pattern: &str, replacement: &str) -> io::Result<()> {
+        self.pass1_rules.add(pattern, replacement)?;
+        self.config = crate::cache::new_config();
+        Ok(())
+    }
+
+    /// Add f-words that Pass 2 must always convert to s.
+    pub fn extend_denylist<I, S>(&mut self, words: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.denylist
+            .extend(words.into_iter().map(|w| w.as_ref().to_lowercase()));
+        self.config = crate::cache::new_config();
+    }
+
+    /// Load additional allowlist words from a file (JSON array or one word per line).
+    pub fn load_allowlist(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let words = read_word_list(path.as_ref())?;
+        self.extend_allowlist(words);
+        Ok(())
+    }
+
+    /// Load denylist words from a file (JSON array or one word per line).
+    pub fn load_denylist(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let words = read_word_list(path.as_ref())?;
+        self.extend_denylist(words);
+        Ok(())
+    }
+
+    pub fn is_allowlisted(&self, word: &str) -> bool {
+        self.allowlist.contains(&word.to_lowercase())
+    }
+
+    pub fn is_denylisted(&self, word: &str) -> bool {
+        self.denylist.contains(&word.to_lowercase())
+    }
+
+    pub fn normalize_word(&self, word: &str) -> String {
+        self.normalize_word_with(word, self.apply_pass2, self.threshold, None)
+    }
+
+    /// Normalize every word in `text`, copying whitespace and punctuation
+    /// through unchanged.
+    pub fn normalize_text(&self, text: &str) -> String {
+        self.normalize_text_with(text, self.apply_pass2, self.threshold)
+    }
+
+    /// Normalize a word, recording every rule that fired.
+    pub fn normalize_word_traced(&self, word: &str) -> (String, Vec<AppliedRule>) {
+        let mut rules = Vec::new();
+        let result = self.normalize_word_with(word, self.apply_pass2, self.threshold, Some(&mut rules));
+        (result, rules)
+    }
+
+    pub fn normalize_detailed(&self, text: &str) -> DetailedResult {
+        self.normalize_detailed_with(text, self.apply_pass2, self.threshold)
+    }
+
+    /// The `n` most likely f/s readings of `word`, best first, for editors
+    /// that offer alternatives instead of committing to one. Each reading is
+    /// scored by the n-gram model as a probability (the scores of all
+    /// readings sum to 1); the word lists and Pass 1 rules are not consulted,
+    /// so the top reading can differ from [`Self::normalize_word`]. Without
+    /// a model, the word is returned as is with score 1.
+    ///
+    /// ```
+    /// use latincy_preprocess::long_s::LongSNormalizer;
+    ///
+    /// let suggestions = LongSNormalizer::new().suggest("Fuit", 2);
+    /// # #[cfg(feature = "embed-data")] {
+    /// assert_eq!(suggestions[0].0, "Fuit");
+    /// assert_eq!(suggestions[1].0, "Suit");
+    /// # }
+    /// ```
+    pub fn suggest(&self, word: &str, n: usize) -> Vec<(String, f64)> {
+        if !self.pass2_available(true) {
+            return vec![(word.to_string(), 1.0)].into_iter().take(n).collect();
+        }
+        suggestions(self.model(), word, n)
+    }
+
+    /// Like [`Self::normalize_word`], but fails instead of falling back to
+    /// Pass 1 when Pass 2 is enabled and the n-gram model cannot be loaded.
+    pub fn try_normalize_word(&self, word: &str) -> Result<String, NgramsUnavailable> {
+        self.require_ngrams(self.apply_pass2)?;
+        Ok(self.normalize_word(word))
+    }
+
+    /// Like [`Self::normalize_text`], but fails if the n-gram model is
+    /// unavailable.
+    pub fn try_normalize_text(&self, text: &str) -> Result<String, NgramsUnavailable> {
+        self.require_ngrams(self.apply_pass2)?;
+        Ok(self.normalize_text(text))
+    }
+
+    /// Like [`Self::normalize_detailed`], but fails if the n-gram model is
+    /// unavailable.
+    pub fn try_normalize_detailed(&self, text: &str) -> Result<DetailedResult, NgramsUnavailable> {
+        self.require_ngrams(self.apply_pass2)?;
+        Ok(self.normalize_detailed(text))
+    }
+
+    /// Like [`Self::normalize_text`], but splits long texts at paragraph
+    /// breaks (blank lines) into chunks of at least [`PARALLEL_CHUNK_BYTES`]
+    /// and normalizes them in parallel with rayon. Every pass works within a
+    /// paragraph, so the output is identical to the sequential path.
+    #[cfg(feature = "parallel")]
+    pub fn normalize_text_parallel(&self, text: &str) -> String {
+        self.normalize_text_parallel_with(text, self.apply_pass2, self.threshold)
+    }
+
+    #[cfg(feature = "parallel")]
+    fn normalize_text_parallel_with(&self, text: &str, apply_pass2: bool, threshold: f64) -> String {
+        use rayon::prelude::*;
+
+        paragraph_chunks(text, PARALLEL_CHUNK_BYTES)
+            .par_iter()
+            .map(|chunk| self.normalize_text_with(chunk, apply_pass2, threshold))
+            .collect::<Vec<_>>()
+            .concat()
+    }
+
+    /// Normalize text read from `reader` into `writer` a paragraph at a
+    /// time, so input of any size runs in memory bounded by its longest
+    /// paragraph. Every pass works within a paragraph, so the output is
+    /// what [`Self::normalize_text`] gives for the whole input.
+    pub fn normalize_stream<R: BufRead, W: Write>(&self, reader: R, writer: W) -> io::Result<()> {
+        self.normalize_stream_with(reader, writer, self.apply_pass2, self.threshold)
+    }
+
+    fn normalize_stream_with<R: BufRead, W: Write>(
+        &self,
+        mut reader: R,
+        mut writer: W,
+        apply_pass2: bool,
+        threshold: f64,
+    ) -> io::Result<()> {
+        let mut buffer = String::new();
+        loop {
+            let start = buffer.len();
+            let read = reader.read_line(&mut buffer)?;
+            if read > 0 && !(buffer[start..].trim().is_empty() && start > 0) {
+                continue;
+            }
+            if !buffer.is_empty() {
+                writer.write_all(self.normalize_text_with(&buffer, apply_pass2, threshold).as_bytes())?;
+                buffer.clear();
+            }
+            if read == 0 {
+                return writer.flush();
+            }
+        }
+    }
+
+    fn normalize_word_with(
+        &self,
+        word: &str,
+        apply_pass2: bool,
+        threshold: f64,
+        trace: Option<&mut Vec<AppliedRule>>,
+    ) -> String {
+        match (&self.cache, trace) {
+            (Some(cache), None) => cache.get_or_insert_with(self.cache_key(apply_pass2, threshold), word, || {
+                self.normalize_uncached(word, apply_pass2, threshold, None)
+            }),
+            (_, trace) => self.normalize_uncached(word, apply_pass2, threshold, trace),
+        }
+    }
+
+    /// The [`WordCache`] key for words normalized with these arguments.
+    fn cache_key(&self, apply_pass2: bool, threshold: f64) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (self.config, apply_pass2, threshold.to_bits()).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn normalize_uncached(
+        &self,
+        word: &str,
+        apply_pass2: bool,
+        threshold: f64,
+        trace: Option<&mut Vec<AppliedRule>>,
+    ) -> String {
+        let apply_pass2 = apply_pass2 && !self.is_protected(word, &[]);
+        on_core(word, |core| {
+            graphemes::on_bases(core, |letters| self.normalize_core(letters, apply_pass2, threshold, trace))
+        })
+    }
+
+    fn normalize_core(
+        &self,
+        word: &str,
+        apply_pass2: bool,
+        threshold: f64,
+        mut trace: Option<&mut Vec<AppliedRule>>,
+    ) -> String {
+        let apply_pass2 = self.pass2_available(apply_pass2);
+        // Double f goes first so Pass 1's fum/fuf rules cannot split a cluster
+        let mut result = if apply_pass2 && self.double_f {
+            let word = decompose_traced(word, trace.as_deref_mut());
+            double_f_traced(self, &word, threshold, trace.as_deref_mut())
+        } else {
+            word.to_string()
+        };
+        result = pass1_traced(&result, &self.pass1_rules, trace.as_deref_mut());
+        if apply_pass2 {
+            result = pass2_traced(self, &result, threshold, trace.as_deref_mut());
+            if self.medial_pass {
+                result = medial_traced(self, &result, threshold, trace.as_deref_mut());
+            }
+        }
+        match &self.lexicon {
+            Some(lexicon) if !self.is_allowlisted(&result) && !self.is_denylisted(&result) => {
+                pass3_traced(lexicon, word, &result, trace)
+            }
+            _ => result,
+        }
+    }
+
+    fn normalize_text_with(&self, text: &str, apply_pass2: bool, threshold: f64) -> String {
+        let joins = self.hyphenation != Hyphenation::Separate && text.contains(LINE_BREAK_HYPHENS);
+        let context = self.pass2_available(apply_pass2) && self.context && self.model().has_word_bigrams();
+        if !joins && !context && self.pass1_rules.need_f() {
+            return self.normalize_candidates(text, apply_pass2, threshold);
+        }
+        self.normalize_units(text, apply_pass2, threshold, false)
+            .into_iter()
+            .map(|(_, normalized, _)| normalized)
+            .collect()
+    }
+
+    /// [`Self::normalize_text_with`] for a text whose words are normalized
+    /// each on its own: scan for the bytes that can start a change, run the
+    /// passes only on the words around them, and copy the rest in bulk.
+    fn normalize_candidates(&self, text: &str, apply_pass2: bool, threshold: f64) -> String {
+        let apply_pass2 = self.pass2_available(apply_pass2);
+        let mut result = String::with_capacity(text.len());
+        let mut copied = 0;
+        // The text before the current word, as the name guard reads it
+        let mut before: Vec<char> = Vec::new();
+        let mut read = 0;
+        let mut from = 0;
+        while let Some(found) = scan::find_long_s(text.as_bytes(), from) {
+            let c = text[found..].chars().next().expect("the scan stops on a char boundary");
+            if !c.is_alphabetic() {
+                // A non-ASCII character outside any word
+                from = found + c.len_utf8();
+                continue;
+            }
+            // Words run on over combining marks, as `segments` splits them,
+            // but start at a letter
+            let is_word_char = |c: char| c.is_alphabetic() || graphemes::is_extender(c);
+            let run = text[..found]
+                .char_indices()
+                .rev()
+                .take_while(|&(_, c)| is_word_char(c))
+                .last()
+                .map_or(found, |(i, _)| i);
+            let start = text[run..found]
+                .char_indices()
+                .find(|&(_, c)| c.is_alphabetic())
+                .map_or(found, |(i, _)| run + i);
+            let end = text[found..]
+                .char_indices()
+                .find(|&(_, c)| !is_word_char(c))
+                .map_or(text.len(), |(i, _)| found + i);
+            from = end;
+            let word = &text[start..end];
+            if is_inert(word) {
+                continue;
+            }
+
+            let protected = self.names.is_some() && {
+                before.extend(text[read..start].chars());
+                read = start;
+                self.is_protected(word, &before)
+            };
+            let normalized = self.normalize_word_with(word, apply_pass2 && !protected, threshold, None);
+            if normalized != word {
+                result.push_str(&text[copied..start]);
+                result.push_str(&normalized);
+                copied = end;
+            }
+        }
+        result.push_str(&text[copied..]);
+        result
+    }
+
+    /// Normalize every unit of `text`, returning each unit with its output
+    /// and, if `traced`, the rules applied to it.
+    fn normalize_units<'a>(
+        &self,
+        text: &'a str,
+        apply_pass2: bool,
+        threshold: f64,
+        traced: bool,
+    ) -> Vec<(Unit<'a>, String, Vec<AppliedRule>)> {
+        let apply_pass2 = self.pass2_available(apply_pass2);
+        let units = units(text, self.hyphenation != Hyphenation::Separate);
+        let skip_inert = self.pass1_rules.need_f();
+        // Names are judged in context, so only a guard needs the text as chars
+        let chars: Vec<char> = if self.names.is_some() { text.chars().collect() } else { Vec::new() };
+        let protected: Vec<bool> = units
+            .iter()
+            .map(|unit| match unit {
+                Unit::Gap(_) => false,
+                Unit::Word(segment) if skip_inert && is_inert(segment.text) => false,
+                Unit::Word(segment) => self.is_protected(segment.text, &chars[..segment.position.min(chars.len())]),
+                Unit::Hyphenated { first, second, .. } => self.is_protected(
+                    &format!("{}{}", first.text, second),
+                    &chars[..first.position.min(chars.len())],
+                ),
+            })
+            .collect();
+        let mut results: Vec<_> = units
+            .iter()
+            .zip(&protected)
+            .map(|(unit, &protected)| {
+                let mut rules = Vec::new();
+                let trace = if traced { Some(&mut rules) } else { None };
+                let apply_pass2 = apply_pass2 && !protected;
+                let normalized = match unit {
+                    Unit::Gap(gap) => gap.to_string(),
+                    Unit::Word(segment) if skip_inert && is_inert(segment.text) => segment.text.to_string(),
+                    Unit::Word(segment) => self.normalize_word_with(segment.text, apply_pass2, threshold, trace),
+                    Unit::Hyphenated { first, separator, second } => {
+                        self.normalize_hyphenated(first.text, separator, second, apply_pass2, threshold, trace)
+                    }
+                };
+                (normalized, rules)
+            })
+            .collect();
+
+        if apply_pass2 && self.context && self.model().has_word_bigrams() {
+            // Neighbours are read from the per-word results, before any
+            // context change
+            let words: Vec<Option<String>> = units
+                .iter()
+                .zip(&results)
+                .map(|(unit, (normalized, _))| match unit {
+                    Unit::Word(_) => Some(normalized.to_lowercase()),
+                    _ => None,
+                })
+                .collect();
+            for (i, unit) in units.iter().enumerate() {
+                let Unit::Word(segment) = unit else {
+                    continue;
+                };
+                if protected[i] || self.is_allowlisted(segment.text) || self.is_denylisted(segment.text) {
+                    continue;
+                }
+                let (prev, next) = neighbours(&units, &words, i);
+                let (normalized, rules) = &mut results[i];
+                let trace = if traced { Some(rules) } else { None };
+                *normalized = context_traced(self.model(), segment.text, normalized, prev, next, threshold, trace);
+            }
+        }
+
+        units.into_iter().zip(results).map(|(unit, (normalized, rules))| (unit, normalized, rules)).collect()
+    }
+
+    /// Normalize `first` + `second` as one word and lay it out per
+    /// `self.hyphenation`.
+    fn normalize_hyphenated(
+        &self,
+        first: &str,
+        separator: &str,
+        second: &str,
+        apply_pass2: bool,
+        threshold: f64,
+        mut trace: Option<&mut Vec<AppliedRule>>,
+    ) -> String {
+        let joined = format!("{}{}", first, second);
+        let word = self.normalize_word_with(&joined, apply_pass2, threshold, trace.as_deref_mut());
+        if self.hyphenation == Hyphenation::Rejoin {
+            if let Some(trace) = trace {
+                trace.push(AppliedRule {
+                    pass: 0,
+                    pattern: "line-break hyphen removed".to_string(),
+                    evidence: Vec::new(),
+                    score: None,
+                });
+            }
+            return word;
+        }
+        // Every rule after ligature decomposition maps one character to one
+        let split = decompose_ligatures(first).chars().count();
+        let mut result: String = word.chars().take(split).collect();
+        result.push_str(separator);
+        result.extend(word.chars().skip(split));
+        result
+    }
+
+    fn normalize_detailed_with(&self, text: &str, apply_pass2: bool, threshold: f64) -> DetailedResult {
+        let mut normalized = String::with_capacity(text.len());
+        let mut changes = Vec::new();
+
+        for (unit, word, rules) in self.normalize_units(text, apply_pass2, threshold, true) {
+            normalized.push_str(&word);
+            let (position, original) = match unit {
+                Unit::Gap(_) => continue,
+                Unit::Word(segment) => (segment.position, segment.text.to_string()),
+                Unit::Hyphenated { first, separator, second } => {
+                    (first.position, format!("{}{}{}", first.text, separator, second))
+                }
+            };
+            if !rules.is_empty() {
+                let confidence = rules.iter().map(AppliedRule::confidence).fold(1.0, f64::min);
+                changes.push(WordChange {
+                    position,
+                    original,
+                    normalized: word,
+                    rules,
+                    confidence,
+                });
+            }
+        }
+
+        DetailedResult {
+            original: text.to_string(),
+            normalized,
+            changes,
+        }
+    }
+}
+
+/// Builder for [`LongSNormalizer`].
+#[derive(Debug, Clone)]
+pub struct LongSNormalizerBuilder {
+    normalizer: LongSNormalizer,
+    /// In-domain n-grams to mix into the model at build time, with their
+    /// weight.
+    domain: Option<(Arc<NgramData>, f64)>,
+}
+
+/// Default weight of in-domain n-grams mixed in with
+/// [`LongSNormalizerBuilder::domain_ngrams`].
+pub const DEFAULT_DOMAIN_WEIGHT: f64 = 0.3;
+
+impl LongSNormalizerBuilder {
+    /// Pass 2 frequency-ratio threshold (default [`DEFAULT_THRESHOLD`]).
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.normalizer.threshold = threshold;
+        self
+    }
+
+    /// Whether to run Pass 2 at all (default `true`).
+    pub fn apply_pass2(mut self, apply_pass2: bool) -> Self {
+        self.normalizer.apply_pass2 = apply_pass2;
+        self
+    }
+
+    /// Replace the word-initial `fu`/`fe`/`fi` lookups of Pass 2 with a
+    /// search over every f/s reading of the word, scored by the character
+    /// n-gram language model (default `false`). A candidate must be more
+    /// than `threshold` times as probable as the word as printed.
+    pub fn candidate_search(mut self, candidate_search: bool) -> Self {
+        self.normalizer.candidate_search = candidate_search;
+        self
+    }
+
+    /// Also convert `f` between vowels inside a word (*rofa* → *rosa*,
+    /// *mifer* → *miser*) when the surrounding trigrams favor `s` (default
+    /// `false`). Runs after Pass 2 and only when Pass 2 is enabled.
+    pub fn medial_pass(mut self, medial_pass: bool) -> Self {
+        self.normalizer.medial_pass = medial_pass;
+        self
+    }
+
+    /// Also resolve `ff` clusters, which may be a doubled long s (*poffum* →
+    /// *possum*, *fuiffe* → *fuisse*), on 4-gram evidence (default `false`).
+    /// Runs before Pass 1, and only when Pass 2 is enabled.
+    pub fn double_f(mut self, double_f: bool) -> Self {
+        self.normalizer.double_f = double_f;
+        self
+    }
+
+    /// How Pass 2 compares word-initial n-grams (default
+    /// [`Pass2Scoring::LogProb`]).
+    pub fn scoring(mut self, scoring: Pass2Scoring) -> Self {
+        self.normalizer.scoring = scoring;
+        self
+    }
+
+    /// Re-check each word of a text against the words on either side, using
+    /// the word-bigram table of the n-gram model (default `false`). The f/s
+    /// reading most often seen between the same neighbours wins when it is
+    /// more than `threshold` times as frequent as the result of the other
+    /// passes. Runs only when Pass 2 is enabled, and does nothing with a
+    /// model that has no word bigrams (such as the bundled one).
+    pub fn context(mut self, context: bool) -> Self {
+        self.normalizer.context = context;
+        self
+    }
+
+    /// How `normalize_text`/`normalize_detailed` treat words split by a
+    /// hyphenated line break (default [`Hyphenation::Separate`]).
+    pub fn hyphenation(mut self, hyphenation: Hyphenation) -> Self {
+        self.normalizer.hyphenation = hyphenation;
+        self
+    }
+
+    /// Which words count as proper names, left alone by Pass 2, the medial,
+    /// double-f, and context passes (default [`NameGuard::new`], the built-in
+    /// names; `None` protects nothing). Pass 1 and Pass 3 still apply, and the
+    /// denylist wins over the guard.
+    pub fn name_guard(mut self, guard: Option<NameGuard>) -> Self {
+        self.normalizer.names = guard;
+        self
+    }
+
+    /// Look words up in `cache` before normalizing them, and store the
+    /// results there (default: no cache). The cache can be shared with other
+    /// normalizers, whatever their configuration, and kept across texts.
+    pub fn word_cache(mut self, cache: Arc<WordCache>) -> Self {
+        self.normalizer.cache = Some(cache);
+        self
+    }
+
+    /// Enable Pass 3: after Passes 1 and 2, a word missing from `lexicon` is
+    /// replaced by its most frequent attested f/s spelling. Allowlisted and
+    /// denylisted words are left as Pass 2 decided.
+    pub fn lexicon(mut self, lexicon: impl Into<Arc<Lexicon>>) -> Self {
+        self.normalizer.lexicon = Some(lexicon.into());
+        self
+    }
+
+    /// Enable Pass 3 with the lexicon in `path` (see [`Lexicon::from_file`]).
+    pub fn lexicon_file(self, path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(self.lexicon(Lexicon::from_file(path)?))
+    }
+
+    /// Add words to the built-in allowlist.
+    pub fn allowlist<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.normalizer.extend_allowlist(words);
+        self
+    }
+
+    /// Add words to the denylist.
+    pub fn denylist<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.normalizer.extend_denylist(words);
+        self
+    }
+
+    /// Normalize text in `language`: replaces the Pass 1 rules and the
+    /// allowlist with the language's built-in ones (so set it before adding
+    /// rules or words), and unless an n-gram model is set, Pass 2 uses the
+    /// language's installed model (see [`Language`]).
+    pub fn language(mut self, language: Language) -> Self {
+        self.normalizer.language = language;
+        self.normalizer.pass1_rules = language.pass1_rules();
+        self.normalizer.allowlist = language.allowlist().iter().map(|w| w.to_string()).collect();
+        self
+    }
+
+    /// Replace the Pass 1 rule table.
+    pub fn pass1_rules(mut self, rules: Pass1Rules) -> Self {
+        self.normalizer.pass1_rules = rules;
+        self
+    }
+
+    /// Add a Pass 1 rule to the table (see [`Pass1Rules::add`]).
+    pub fn pass1_rule(mut self, pattern: &str, replacement: &str) -> io::Result<Self> {
+        self.normalizer.add_pass1_rule(pattern, replacement)?;
+        Ok(self)
+    }
+
+    /// Use the given n-gram tables instead of the bundled model. Pass an
+    /// `Arc` to share one loaded model between several normalizers.
+    pub fn ngrams(mut self, ngrams: impl Into<Arc<NgramData>>) -> Self {
+        let ngrams = ngrams.into();
+        self.normalizer.source = DataSource::InMemory(Arc::clone(&ngrams));
+        self.normalizer.ngrams = Some(ngrams);
+        self
+    }
+
+    /// Load the n-gram model from `source`. [`DataSource::Bundled`] stays
+    /// lazy, as in [`LongSNormalizer::new`]; any other source is loaded now.
+    pub fn data_source(mut self, source: DataSource) -> Result<Self, NgramsUnavailable> {
+        self.normalizer.ngrams = match source {
+            DataSource::Bundled => None,
+            ref other => Some(other.load()?),
+        };
+        self.normalizer.source = source;
+        Ok(self)
+    }
+
+    /// Use the n-gram tables in `dir` instead of the bundled model.
+    pub fn ngram_dir(self, dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        let mut builder = self.ngrams(NgramData::from_dir(dir)?);
+        builder.normalizer.source = DataSource::Directory(dir.to_path_buf());
+        Ok(builder)
+    }
+
+    /// Use the installed n-gram model called `name`, e.g. `"neo-latin"`
+    /// (see [`crate::ngrams::available_models`]).
+    pub fn model(self, name: &str) -> io::Result<Self> {
+        let source = crate::ngrams::model_source(name)?;
+        let mut builder = self.ngrams(crate::ngrams::model(name)?);
+        builder.normalizer.source = source;
+        Ok(builder)
+    }
+
+    /// Adapt the model to a corpus: at build time, interpolate the n-gram
+    /// model (bundled, or set with [`Self::ngrams`]) with `domain`, giving
+    /// the in-domain relative frequencies `weight` (0 to 1; see
+    /// [`NgramData::interpolate`] and [`DEFAULT_DOMAIN_WEIGHT`]). If the
+    /// bundled model is unavailable, `domain` is used on its own.
+    pub fn domain_ngrams(mut self, domain: impl Into<Arc<NgramData>>, weight: f64) -> Self {
+        self.domain = Some((domain.into(), weight));
+        self
+    }
+
+    /// Like [`Self::domain_ngrams`], with the tables in `dir`.
+    pub fn domain_ngram_dir(self, dir: impl AsRef<Path>, weight: f64) -> io::Result<Self> {
+        Ok(self.domain_ngrams(NgramData::from_dir(dir)?, weight))
+    }
+
+    pub fn build(self) -> LongSNormalizer {
+        let mut normalizer = self.normalizer;
+        if normalizer.ngrams.is_none() && normalizer.language != Language::Latin {
+            // No evidence either way, so Pass 2 keeps every word
+            let name = normalizer.language.model_name();
+            let model = crate::ngrams::model(name).unwrap_or_else(|_| Arc::new(NgramData::default()));
+            normalizer.source = crate::ngrams::model_source(name).unwrap_or(DataSource::InMemory(Arc::clone(&model)));
+            normalizer.ngrams = Some(model);
+        }
+        if let Some((domain, weight)) = self.domain {
+            let mixed = match normalizer.ngrams() {
+                Ok(base) => Arc::new(base.interpolate(&domain, weight)),
+                Err(_) => domain,
+            };
+            normalizer.ngrams = Some(mixed);
+        }
+        normalizer.config = crate::cache::new_config();
+        normalizer
+    }
+}
+
+impl Normalizer for LongSNormalizer {
+    fn name(&self) -> &str {
+        "long_s"
+    }
+
+    /// The changes are the [`DetailedResult::char_changes`]; where they do
+    /// not spell out a word's edit, its offsets map word to word.
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = self.normalize_detailed(text);
+        let words: Vec<(usize, usize, usize)> = result
+            .changes
+            .iter()
+            .map(|change| (change.position, change.original.chars().count(), change.normalized.chars().count()))
+            .collect();
+        let changes = result.char_changes();
+        NormalizeOutput::from_edits(result.original, result.normalized, changes, &words)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+pub fn normalize_long_s_word_pass1(word: &str) -> String {
+    pass1(word)
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "decompose_ligatures")]
+pub fn py_decompose_ligatures(py: Python<'_>, text: &str) -> String {
+    crate::allow_threads(py, text, || decompose_ligatures(text))
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "archaize")]
+pub fn py_archaize(py: Python<'_>, text: &str) -> String {
+    crate::allow_threads(py, text, || archaize(text))
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(signature = (word, threshold=DEFAULT_THRESHOLD))]
+pub fn normalize_long_s_word_pass2(word: &str, threshold: f64) -> String {
+    pass2(word, threshold)
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(signature = (word, apply_pass2=true, threshold=DEFAULT_THRESHOLD))]
+pub fn normalize_long_s_word_full(word: &str, apply_pass2: bool, threshold: f64) -> String {
+    normalize_word(word, apply_pass2, threshold)
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(signature = (text, apply_pass2=true, threshold=DEFAULT_THRESHOLD))]
+pub fn normalize_long_s_text_full(py: Python<'_>, text: &str, apply_pass2: bool, threshold: f64) -> String {
+    crate::allow_threads(py, text, || normalize_text(text, apply_pass2, threshold))
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(signature = (word, n=5))]
+pub fn suggest_long_s(word: &str, n: usize) -> Vec<(String, f64)> {
+    suggest(word, n)
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(signature = (text, apply_pass2=true, threshold=DEFAULT_THRESHOLD, review_below=None))]
+pub fn normalize_long_s_detailed(
+    py: Python<'_>,
+    text: &str,
+    apply_pass2: bool,
+    threshold: f64,
+    review_below: Option<f64>,
+) -> PyResult<changes::PyDetailedResult> {
+    let result = crate::allow_threads(py, text, || normalize_detailed(text, apply_pass2, threshold));
+    detailed_to_py(py, &result, review_below)
+}
+
+/// Python view of a [`WordChange`], exposed as `_rust.WordChange`; each of
+/// its `rules` is a dict of `pass`, `pattern`, `evidence`, and `score`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "WordChange", frozen)]
+pub struct PyWordChange {
+    inner: WordChange,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyWordChange {
+    #[getter]
+    fn position(&self) -> usize {
+        self.inner.position
+    }
+
+    #[getter]
+    fn original(&self) -> &str {
+        &self.inner.original
+    }
+
+    #[getter]
+    fn normalized(&self) -> &str {
+        &self.inner.normalized
+    }
+
+    #[getter]
+    fn confidence(&self) -> f64 {
+        self.inner.confidence
+    }
+
+    #[getter]
+    fn rules<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let rules = PyList::empty(py);
+        for rule in &self.inner.rules {
+            let rule_dict = PyDict::new(py);
+            rule_dict.set_item("pass", rule.pass)?;
+            rule_dict.set_item("pattern", &rule.pattern)?;
+            let evidence = PyDict::new(py);
+            for (key, freq) in &rule.evidence {
+                evidence.set_item(key, freq)?;
+            }
+            rule_dict.set_item("evidence", evidence)?;
+            match rule.score {
+                Some(score) => {
+                    let score_dict = PyDict::new(py);
+                    score_dict.set_item("f", score.f_score)?;
+                    score_dict.set_item("s", score.s_score)?;
+                    rule_dict.set_item("score", score_dict)?;
+                }
+                None => rule_dict.set_item("score", py.None())?,
+            }
+            rules.append(rule_dict)?;
+        }
+        Ok(rules)
+    }
+
+    /// The change as a dict.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("position", self.inner.position)?;
+        dict.set_item("original", &self.inner.original)?;
+        dict.set_item("normalized", &self.inner.normalized)?;
+        dict.set_item("confidence", self.inner.confidence)?;
+        dict.set_item("rules", self.rules(py)?)?;
+        Ok(dict)
+    }
+
+    /// Dict-style access, for code written against the old dict results.
+    fn __getitem__<'py>(&self, py: Python<'py>, key: &str) -> PyResult<Bound<'py, PyAny>> {
+        changes::get_item(&self.to_dict(py)?, key)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "WordChange(position={}, original={:?}, normalized={:?}, confidence={})",
+            self.inner.position, self.inner.original, self.inner.normalized, self.inner.confidence
+        )
+    }
+}
+
+#[cfg(feature = "pyo3-backend")]
+/// Convert a detailed result for Python; with `review_below`, the review
+/// queue goes under `review`, as dicts.
+fn detailed_to_py(
+    py: Python<'_>,
+    result: &DetailedResult,
+    review_below: Option<f64>,
+) -> PyResult<changes::PyDetailedResult> {
+    let word_changes = PyList::empty(py);
+    for change in &result.changes {
+        word_changes.append(PyWordChange { inner: change.clone() })?;
+    }
+    let mut detailed = changes::PyDetailedResult::new(&result.original, &result.normalized, word_changes);
+    detailed.char_changes = Some(changes::to_py_list(py, &result.char_changes())?.unbind());
+
+    if let Some(below) = review_below {
+        let review = PyList::empty(py);
+        for item in result.review(below) {
+            let item_dict = PyDict::new(py);
+            item_dict.set_item("position", item.position)?;
+            item_dict.set_item("original", item.original)?;
+            item_dict.set_item("normalized", item.normalized)?;
+            item_dict.set_item("confidence", item.confidence)?;
+            item_dict.set_item("context", item.context)?;
+            item_dict.set_item("rules", item.rules)?;
+            review.append(item_dict)?;
+        }
+        detailed.review = Some(review.unbind());
+    }
+
+    Ok(detailed)
+}
+
+/// Python view of [`LongSNormalizer`], exposed as `_rust.LongSNormalizer`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "LongSNormalizer")]
+pub struct PyLongSNormalizer {
+    pub(crate) inner: LongSNormalizer,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyLongSNormalizer {
+    #[new]
+    #[pyo3(signature = (
+        threshold=DEFAULT_THRESHOLD,
+        apply_pass2=true,
+        allowlist=None,
+        denylist=None,
+        ngram_dir=None,
+        ngrams=None,
+        candidate_search=false,
+        medial_pass=false,
+        double_f=false,
+        scoring="logprob",
+        lexicon=None,
+        hyphenation="separate",
+        context=false,
+        domain_ngrams=None,
+        domain_weight=DEFAULT_DOMAIN_WEIGHT,
+        model=None,
+        pass1_rules=None,
+        language="la",
+        protect_names=true,
+        name_guard=None,
+        cache_size=None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        threshold: f64,
+        apply_pass2: bool,
+        allowlist: Option<Vec<String>>,
+        denylist: Option<Vec<String>>,
+        ngram_dir: Option<PathBuf>,
+        ngrams: Option<PyRef<'_, PyNgramData>>,
+        candidate_search: bool,
+        medial_pass: bool,
+        double_f: bool,
+        scoring: &str,
+        lexicon: Option<PathBuf>,
+        hyphenation: &str,
+        context: bool,
+        domain_ngrams: Option<PyRef<'_, PyNgramData>>,
+        domain_weight: f64,
+        model: Option<&str>,
+        pass1_rules: Option<Vec<(String, String)>>,
+        language: &str,
+        protect_names: bool,
+        name_guard: Option<PyRef<'_, PyNameGuard>>,
+        cache_size: Option<usize>,
+    ) -> PyResult<Self> {
+        if model.is_some() && (ngram_dir.is_some() || ngrams.is_some()) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "model cannot be combined with ngram_dir or ngrams",
+            ));
+        }
+        if !(0.0..=1.0).contains(&domain_weight) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "domain_weight must be between 0 and 1, got {}",
+                domain_weight
+            )));
+        }
+        let hyphenation = match hyphenation {
+            "separate" => Hyphenation::Separate,
+            "rejoin" => Hyphenation::Rejoin,
+            "preserve" => Hyphenation::Preserve,
+            other => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "hyphenation must be 'separate', 'rejoin', or 'preserve', got {:?}",
+                    other
+                )))
+            }
+        };
+        let language = Language::from_code(language).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "language must be 'la', 'en', 'de', or 'fr', got {:?}",
+                language
+            ))
+        })?;
+        let scoring = match scoring {
+            "logprob" => Pass2Scoring::LogProb,
+            "ratio" => Pass2Scoring::Ratio,
+            other => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "scoring must be 'logprob' or 'ratio', got {:?}",
+                    other
+                )))
+            }
+        };
+        let mut builder = LongSNormalizer::builder()
+            .language(language)
+            .threshold(threshold)
+            .apply_pass2(apply_pass2)
+            .candidate_search(candidate_search)
+            .medial_pass(medial_pass)
+            .double_f(double_f)
+            .scoring(scoring)
+            .hyphenation(hyphenation)
+            .context(context)
+            .allowlist(allowlist.unwrap_or_default())
+            .denylist(denylist.unwrap_or_default())
+            .name_guard(crate::names::from_py(protect_names, name_guard));
+        if let Some(dir) = ngram_dir {
+            builder = builder.ngram_dir(dir)?;
+        }
+        if let Some(ngrams) = ngrams {
+            builder = builder.ngrams(Arc::clone(&ngrams.inner));
+        }
+        if let Some(name) = model {
+            builder = builder.model(name)?;
+        }
+        if let Some(path) = lexicon {
+            builder = builder.lexicon_file(path)?;
+        }
+        if let Some(domain) = domain_ngrams {
+            builder = builder.domain_ngrams(Arc::clone(&domain.inner), domain_weight);
+        }
+        if let Some(capacity) = cache_size {
+            builder = builder.word_cache(Arc::new(WordCache::new(capacity)));
+        }
+        let mut inner = builder.build();
+        for (pattern, replacement) in pass1_rules.unwrap_or_default() {
+            inner
+                .add_pass1_rule(&pattern, &replacement)
+                .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        }
+        Ok(PyLongSNormalizer { inner })
+    }
+
+    #[getter]
+    fn threshold(&self) -> f64 {
+        self.inner.threshold()
+    }
+
+    /// Where the n-gram model was loaded from, e.g. `"bundled model
+    /// (embedded)"` or a directory.
+    #[getter]
+    fn data_source(&self) -> String {
+        self.inner.data_source().to_string()
+    }
+
+    #[getter]
+    fn apply_pass2(&self) -> bool {
+        self.inner.apply_pass2()
+    }
+
+    #[getter]
+    fn candidate_search(&self) -> bool {
+        self.inner.candidate_search()
+    }
+
+    #[getter]
+    fn medial_pass(&self) -> bool {
+        self.inner.medial_pass()
+    }
+
+    #[getter]
+    fn double_f(&self) -> bool {
+        self.inner.double_f()
+    }
+
+    #[getter]
+    fn context(&self) -> bool {
+        self.inner.context()
+    }
+
+    #[getter]
+    fn hyphenation(&self) -> &'static str {
+        match self.inner.hyphenation() {
+            Hyphenation::Separate => "separate",
+            Hyphenation::Rejoin => "rejoin",
+            Hyphenation::Preserve => "preserve",
+        }
+    }
+
+    #[getter]
+    fn scoring(&self) -> &'static str {
+        match self.inner.scoring() {
+            Pass2Scoring::LogProb => "logprob",
+            Pass2Scoring::Ratio => "ratio",
+        }
+    }
+
+    fn extend_allowlist(&mut self, words: Vec<String>) {
+        self.inner.extend_allowlist(words);
+    }
+
+    fn extend_denylist(&mut self, words: Vec<String>) {
+        self.inner.extend_denylist(words);
+    }
+
+    fn add_pass1_rule(&mut self, pattern: &str, replacement: &str) -> PyResult<()> {
+        self.inner
+            .add_pass1_rule(pattern, replacement)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+    }
+
+    /// The Pass 1 rules as `(pattern, replacement)` pairs, longest first.
+    #[getter]
+    fn pass1_rules(&self) -> Vec<(String, String)> {
+        self.inner
+            .pass1_rules()
+            .rules()
+            .map(|(pattern, replacement)| (pattern.to_string(), replacement.to_string()))
+            .collect()
+    }
+
+    fn load_allowlist(&mut self, path: PathBuf) -> PyResult<()> {
+        Ok(self.inner.load_allowlist(path)?)
+    }
+
+    fn load_denylist(&mut self, path: PathBuf) -> PyResult<()> {
+        Ok(self.inner.load_denylist(path)?)
+    }
+
+    fn is_allowlisted(&self, word: &str) -> bool {
+        self.inner.is_allowlisted(word)
+    }
+
+    fn is_denylisted(&self, word: &str) -> bool {
+        self.inner.is_denylisted(word)
+    }
+
+    fn normalize_word(&self, word: &str) -> String {
+        self.inner.normalize_word(word)
+    }
+
+    fn normalize_text(&self, py: Python<'_>, text: &str) -> String {
+        crate::allow_threads(py, text, || self.inner.normalize_text(text))
+    }
+
+    /// The `n` most likely f/s readings of `word` as `(reading, score)`
+    /// pairs, best first.
+    #[pyo3(signature = (word, n=5))]
+    fn suggest(&self, word: &str, n: usize) -> Vec<(String, f64)> {
+        self.inner.suggest(word, n)
+    }
+
+    #[pyo3(signature = (text, review_below=None))]
+    fn normalize_detailed(
+        &self,
+        py: Python<'_>,
+        text: &str,
+        review_below: Option<f64>,
+    ) -> PyResult<changes::PyDetailedResult> {
+        let result = crate::allow_threads(py, text, || self.inner.normalize_detailed(text));
+        detailed_to_py(py, &result, review_below)
+    }
+
+    /// The changed words as a JSON Lines patch for `_rust.apply_patch`,
+    /// each with its confidence.
+    fn export_patch(&self, py: Python<'_>, text: &str) -> String {
+        crate::allow_threads(py, text, || {
+            crate::patch::Patch::from_long_s(&self.inner.normalize_detailed(text)).to_jsonl()
+        })
+    }
+
+    /// ISO 639-1 code of the normalizer's language.
+    #[getter]
+    fn language(&self) -> &'static str {
+        self.inner.language().code()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "LongSNormalizer(threshold={:?}, apply_pass2={}, candidate_search={}, medial_pass={}, double_f={}, context={}, scoring='{}', hyphenation='{}', language='{}')",
+            self.inner.threshold(),
+            if self.inner.apply_pass2() { "True" } else { "False" },
+            if self.inner.candidate_search() { "True" } else { "False" },
+            if self.inner.medial_pass() { "True" } else { "False" },
+            if self.inner.double_f() { "True" } else { "False" },
+            if self.inner.context() { "True" } else { "False" },
+            self.scoring(),
+            self.hyphenation(),
+            self.language()
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_pass1_trigrams() {
+        assert_eq!(pass1("ftatua"), "statua");
+        assert_eq!(pass1("fpiritus"), "spiritus");
+        assert_eq!(pass1("fufcepit"), "suscepit");
+        assert_eq!(pass1("fumma"), "summa");
+        assert_eq!(pass1("fquama"), "squama");
+    }
+
+    #[test]
+    fn test_pass1_bigrams() {
+        assert_eq!(pass1("fpecies"), "species");
+        assert_eq!(pass1("ftella"), "stella");
+        assert_eq!(pass1("fcientia"), "scientia");
+    }
+
+    #[test]
+    fn test_pass1_word_final() {
+        assert_eq!(pass1("ef"), "es");
+        assert_eq!(pass1("reuf"), "reus");
+    }
+
+    #[test]
+    fn test_pass1_case_preservation() {
+        assert_eq!(pass1("FTATUA"), "STATUA");
+        assert_eq!(pass1("Fpiritus"), "Spiritus");
+        assert_eq!(pass1("ftatua"), "statua");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_word_punctuation() {
+        assert_eq!(pass1("eſt."), "est.");
+        assert_eq!(pass1("eft."), "est.");
+        assert_eq!(pass1("(Fuper)"), "(Fuper)");
+        assert_eq!(pass1("(EFT)"), "(EST)");
+        assert_eq!(pass1("«Ftatua»,"), "«Statua»,");
+        assert_eq!(pass1("..."), "...");
+        assert_eq!(pass2("(Fuper)", DEFAULT_THRESHOLD), "(Super)");
+        assert_eq!(normalize_word("\"funt!\"", true, DEFAULT_THRESHOLD), "\"sunt!\"");
+        // Word-internal apostrophes stay in the core
+        assert_eq!(split_affixes("'poteft'n'?"), ("'", "poteft'n", "'?"));
+        assert_eq!(normalize_word("poteft'n", true, DEFAULT_THRESHOLD), "potest'n");
+    }
+
+    #[test]
+    fn test_long_s_character() {
+        assert_eq!(replace_long_s("ſtatua"), "statua");
+        assert_eq!(pass1("ſtatua"), "statua");
+        assert_eq!(pass1("eſt"), "est");
+        assert_eq!(pass1("poſſum"), "possum");
+        assert_eq!(normalize_word("ſunt", true, DEFAULT_THRESHOLD), "sunt");
+    }
+
+    #[test]
+    fn test_long_s_with_f_confusion() {
+        // A token can mix real ſ with a mis-OCR'd f
+        assert_eq!(pass1("ſuf"), "sus");
+        assert_eq!(normalize_word("ſpiritu", true, DEFAULT_THRESHOLD), "spiritu");
+        assert_eq!(normalize_word("fufceptiſ", true, DEFAULT_THRESHOLD), "susceptis");
+    }
+
+    #[test]
+    fn test_long_s_case_mapping() {
+        assert_eq!(pass1("Eſt"), "Est");
+        assert_eq!(pass1("ſtatua"), "statua");
+        assert_eq!(pass1("POſSUM"), "POSSUM");
+        assert_eq!(normalize_word("Sic", true, DEFAULT_THRESHOLD), "Sic");
+    }
+
+    #[test]
+    fn test_mixed_case_restored() {
+        // Capitals inside a word and letters with unusual case mappings
+        // survive; changed letters take the case of the ones they replace
+        assert_eq!(pass1("McDonald"), "McDonald");
+        assert_eq!(pass1("McFufcepit"), "McSuscepit");
+        assert_eq!(pass1("Æftas"), "Æstas");
+        assert_eq!(pass1("ÆFTAS"), "ÆSTAS");
+        assert_eq!(pass1("ǅufcepit"), "ǅuscepit");
+        assert_eq!(pass1("İftud"), "İstud");
+        assert_eq!(pass1("ẞfuit"), "ẞfuit");
+        assert_eq!(pass1("FﬁLIUS"), "FFILIUS");
+        let text = "eft McFerfus, non MACFERFUS";
+        assert_eq!(normalize_text(text, true, DEFAULT_THRESHOLD), "est McFerfus, non MACFERFUS");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_combining_marks() {
+        // The rules read letters without their marks, and marks stay on
+        // their letters, on both the fast path and the detailed one
+        let normalizer = LongSNormalizer::default();
+        for (text, expected) in [
+            ("fu\u{0304}nt", "su\u{0304}nt"),
+            ("Fu\u{0304}nt", "Su\u{0304}nt"),
+            ("e\u{0323}ſt.", "e\u{0323}st."),
+            ("\u{FB01}\u{0304}lius", "fi\u{0304}lius"),
+            (" \u{0304}funt", " \u{0304}sunt"),
+            ("e\u{0304}fuit", "e\u{0304}fuit"),
+        ] {
+            assert_eq!(normalizer.normalize_text(text), expected, "{:?}", text);
+            assert_eq!(normalizer.normalize_detailed(text).normalized, expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_archaize() {
+        assert_eq!(archaize("possessionis"), "poſſeſſionis");
+        assert_eq!(archaize("Est satis, sed transfert"), "Eſt ſatis, ſed transfert");
+        assert_eq!(archaize("presbyter Sunt ESSE"), "presbyter Sunt ESSE");
+        // Word-final before punctuation or digits stays round
+        assert_eq!(archaize("usus-que ss2"), "uſus-que ſs2");
+        assert_eq!(archaize(""), "");
+    }
+
+    #[test]
+    fn test_archaize_round_trip() {
+        let text = "ipsum esse sunt, ut possumus: Christus est.";
+        assert_eq!(pass1(&archaize("possumus")), "possumus");
+        assert_eq!(normalize_text(&archaize(text), false, DEFAULT_THRESHOLD), text);
+    }
+
+    #[test]
+    fn test_decompose_ligatures() {
+        assert_eq!(decompose_ligatures("\u{FB01}lius \u{FB02}os o\u{FB00}ert\u{FB05}"), "filius flos offertst");
+        assert_eq!(decompose_ligatures("e\u{FB06}"), "est");
+        assert_eq!(pass1("e\u{FB05}"), "est");
+        assert_eq!(pass1("\u{FB01}lius"), "filius");
+        assert_eq!(pass1("o\u{FB00}ert"), "offert");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_ligatures_traced() {
+        let (word, rules) = normalize_word_traced("\u{FB05}atua", true, DEFAULT_THRESHOLD);
+        assert_eq!(word, "statua");
+        assert_eq!(rules[0].pattern, "\u{FB05} → st");
+        // Double-f sees through the ff ligature
+        let normalizer = LongSNormalizer::builder().double_f(true).build();
+        assert_eq!(normalizer.normalize_word("po\u{FB00}um"), "possum");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_ligature_offsets_preserved() {
+        let text = "\u{FB01}des e\u{FB05} funt";
+        let result = normalize_detailed(text, true, DEFAULT_THRESHOLD);
+        assert_eq!(result.normalized, "fides est sunt");
+        let positions: Vec<usize> = result.changes.iter().map(|c| c.position).collect();
+        assert_eq!(positions, vec![0, 5, 8]);
+        assert_eq!(result.changes[1].original, "e\u{FB05}");
+    }
+
+    #[test]
+    fn test_normalize_word_pass1_only() {
+        assert_eq!(normalize_word("ftatua", false, DEFAULT_THRESHOLD), "statua");
+        assert_eq!(normalize_word("fpiritus", false, DEFAULT_THRESHOLD), "spiritus");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_normalize_word_with_pass2() {
+        assert_eq!(normalize_word("funt", true, DEFAULT_THRESHOLD), "sunt");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_normalize_text() {
+        assert_eq!(
+            normalize_text("ftatua fpiritus funt", true, DEFAULT_THRESHOLD),
+            "statua spiritus sunt"
+        );
+    }
+
+    #[test]
+    fn test_normalize_text_case_preservation() {
+        assert_eq!(
+            normalize_text("Sic uita eft", true, DEFAULT_THRESHOLD),
+            "Sic uita est"
+        );
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_normalize_text_preserves_layout() {
+        assert_eq!(
+            normalize_text("ftatua\n\n\tfpiritus  funt\n", true, DEFAULT_THRESHOLD),
+            "statua\n\n\tspiritus  sunt\n"
+        );
+        assert_eq!(normalize_text("", true, DEFAULT_THRESHOLD), "");
+        assert_eq!(normalize_text("  \n", true, DEFAULT_THRESHOLD), "  \n");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_normalize_text_punctuation() {
+        assert_eq!(normalize_text("funt, eft. (ftatua)", true, DEFAULT_THRESHOLD), "sunt, est. (statua)");
+        assert_eq!(normalize_text("reuf;", false, DEFAULT_THRESHOLD), "reus;");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_hyphenation_separate_by_default() {
+        assert_eq!(normalize_text("eft-\nfunt", true, DEFAULT_THRESHOLD), "est-\nsunt");
+        assert_eq!(normalize_text("ipfe-\nmus", true, DEFAULT_THRESHOLD), "ipfe-\nmus");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_hyphenation_rejoin() {
+        let normalizer = LongSNormalizer::builder().hyphenation(Hyphenation::Rejoin).build();
+        assert_eq!(normalizer.normalize_text("Poteft-\nis, funt"), "Potestis, sunt");
+        assert_eq!(normalizer.normalize_text("vi-  \r\n  dit"), "vidit");
+        // Only hyphen + line break joins
+        assert_eq!(normalizer.normalize_text("vi- dit"), "vi- dit");
+        assert_eq!(normalizer.normalize_text("vidit-\n"), "vidit-\n");
+
+        let result = normalizer.normalize_detailed("a chrif-\ntus");
+        assert_eq!(result.normalized, "a christus");
+        assert_eq!(result.changes.len(), 1);
+        assert_eq!(result.changes[0].position, 2);
+        assert_eq!(result.changes[0].original, "chrif-\ntus");
+        assert_eq!(result.changes[0].rules.last().unwrap().pass, 0);
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_hyphenation_preserve() {
+        let normalizer = LongSNormalizer::builder().hyphenation(Hyphenation::Preserve).build();
+        // Layout is kept while the rules see the whole word, so the first
+        // half's final f is not mistaken for a word-final long s
+        assert_eq!(normalize_text("of-\nfendit", true, DEFAULT_THRESHOLD), "os-\nsendit");
+        assert_eq!(normalizer.normalize_text("of-\nfendit"), "of-\nfendit");
+        assert_eq!(normalizer.normalize_text("chrif-\ntus"), "chris-\ntus");
+        assert_eq!(normalizer.normalize_text("poteft-\nis"), "potest-\nis");
+        assert_eq!(normalizer.normalize_text("no\u{00AD}\nftra"), "no\u{00AD}\nstra");
+        assert_eq!(normalizer.normalize_text("\u{FB01}-\ndes"), "fi-\ndes");
+
+        let result = normalizer.normalize_detailed("vi-\ndit");
+        assert!(result.changes.is_empty());
+        assert_eq!(result.normalized, "vi-\ndit");
+    }
+
+    fn context_normalizer() -> LongSNormalizer {
+        let mut data = NgramData::from_dir(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../src/latincy_preprocess/long_s/data/ngrams"),
+        )
+        .unwrap();
+        data.add_text("quid si quis dicat si quis fecit");
+        data.add_text("tu fi dicis fi");
+        LongSNormalizer::builder().ngrams(data).context(true).build()
+    }
+
+    #[test]
+    fn test_context_pass() {
+        let normalizer = context_normalizer();
+        assert_eq!(normalizer.normalize_text("Fi quis venit, fi quis"), "Si quis venit, si quis");
+        // No neighbours on record: the word is left as printed
+        assert_eq!(normalizer.normalize_text("dixit fi"), "dixit fi");
+        // Punctuation cuts the word off from its neighbour
+        assert_eq!(normalizer.normalize_text("fi. quis"), "fi. quis");
+        // Off by default, and a no-op without word bigrams
+        assert_eq!(normalize_text("fi quis", true, DEFAULT_THRESHOLD), "fi quis");
+        let bundled = LongSNormalizer::builder().context(true).build();
+        assert_eq!(bundled.normalize_text("fi quis"), "fi quis");
+    }
+
+    #[test]
+    fn test_context_pass_traced() {
+        let result = context_normalizer().normalize_detailed("tum fi quis");
+        assert_eq!(result.normalized, "tum si quis");
+        assert_eq!(result.changes.len(), 1);
+        let rule = &result.changes[0].rules[0];
+        assert_eq!(rule.pass, 2);
+        assert_eq!(rule.pattern, "fi → si (context)");
+        assert_eq!(
+            rule.evidence,
+            vec![
+                ("tum fi".to_string(), 0),
+                ("fi quis".to_string(), 0),
+                ("tum si".to_string(), 0),
+                ("si quis".to_string(), 2),
+            ]
+        );
+        let score = rule.score.unwrap();
+        assert!((score.s_score - 3.0f64.ln()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_context_pass_respects_word_lists() {
+        let mut normalizer = context_normalizer();
+        normalizer.extend_allowlist(["fi"]);
+        assert_eq!(normalizer.normalize_text("fi quis"), "fi quis");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_confidence() {
+        let result = normalize_detailed("chriftus funt", true, DEFAULT_THRESHOLD);
+        // Pass 1 rules are certain; Pass 2 takes the margin of its n-grams
+        assert_eq!(result.changes[0].confidence, 1.0);
+        let rule = &result.changes[1].rules[0];
+        let score = rule.score.unwrap();
+        let odds = (score.s_score - score.f_score).exp();
+        assert!((result.changes[1].confidence - odds / (1.0 + odds)).abs() < 1e-9);
+        assert!(result.changes[1].confidence > 2.0 / 3.0);
+        assert!(result.changes[1].confidence < 1.0);
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_review_queue() {
+        let text = "Chriftus dixit: fi quis funt";
+        let result = normalize_detailed(text, true, DEFAULT_THRESHOLD);
+        assert!(result.review(0.0).is_empty());
+        assert_eq!(result.review(1.1).len(), result.changes.len());
+
+        let review = result.review(1.0);
+        assert_eq!(review.len(), 1);
+        assert_eq!(review[0].original, "funt");
+        assert_eq!(review[0].position, 24);
+        assert_eq!(review[0].context, text);
+        assert_eq!(review[0].rules, vec!["<fu → <su".to_string()]);
+
+        let jsonl = result.review_jsonl(1.0);
+        assert_eq!(jsonl.lines().count(), 1);
+        let parsed: serde_json::Value = serde_json::from_str(jsonl.trim_end()).unwrap();
+        assert_eq!(parsed["normalized"], "sunt");
+        assert_eq!(parsed["position"], 24);
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_review_context_window() {
+        let padding = "et ".repeat(20);
+        let text = format!("{}funt {}", padding, padding);
+        let review = normalize_detailed(&text, true, DEFAULT_THRESHOLD).review(1.0);
+        assert_eq!(review[0].context.chars().count(), 2 * REVIEW_CONTEXT_CHARS + 4);
+        assert!(review[0].context.contains(" funt et"));
+    }
+
+    fn stream(normalizer: &LongSNormalizer, text: &str) -> String {
+        let mut out = Vec::new();
+        normalizer.normalize_stream(text.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_normalize_stream() {
+        let text = "Chriftus eft\r\n\nfunt, poteft\nipfum";
+        let normalizer = LongSNormalizer::new();
+        assert_eq!(stream(&normalizer, text), normalize_text(text, true, DEFAULT_THRESHOLD));
+        assert_eq!(stream(&normalizer, text), "Christus est\r\n\nsunt, potest\nipsum");
+        assert_eq!(stream(&normalizer, ""), "");
+
+        let mut out = Vec::new();
+        normalize_stream("funt\n".as_bytes(), &mut out, false, DEFAULT_THRESHOLD).unwrap();
+        assert_eq!(out, b"funt\n");
+
+        // A capitalized word starting a line mid-sentence, and context
+        // across lines, as in the whole text
+        let guard = NameGuard::new().capitalized(true);
+        let normalizer = LongSNormalizer::builder().name_guard(Some(guard)).build();
+        let text = "dixit et\nFunt multi.\n\nFunt alii.\n";
+        assert_eq!(stream(&normalizer, text), normalizer.normalize_text(text));
+        assert_eq!(stream(&normalizer, text), "dixit et\nFunt multi.\n\nSunt alii.\n");
+        let normalizer = context_normalizer();
+        assert_eq!(stream(&normalizer, "fi\nquis\n"), "si\nquis\n");
+    }
+
+    #[test]
+    fn test_normalize_stream_hyphenation() {
+        let text = "a chrif-\ntus of- \r\nfen-\ndit\nvi -\ndit";
+        for hyphenation in [Hyphenation::Separate, Hyphenation::Rejoin, Hyphenation::Preserve] {
+            let normalizer = LongSNormalizer::builder().hyphenation(hyphenation).build();
+            assert_eq!(stream(&normalizer, text), normalizer.normalize_text(text));
+        }
+        assert!(ends_with_line_break_hyphen("chrif-\n"));
+        assert!(ends_with_line_break_hyphen("of- \r\n"));
+        assert!(!ends_with_line_break_hyphen("vi -\n"));
+        assert!(!ends_with_line_break_hyphen("chrif-"));
+    }
+
+    #[test]
+    fn test_normalize_stream_invalid_utf8() {
+        let mut out = Vec::new();
+        let err = LongSNormalizer::new().normalize_stream(&b"funt \xff\n"[..], &mut out).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_context_stops_at_paragraph_breaks() {
+        let normalizer = context_normalizer();
+        assert_eq!(normalizer.normalize_text("fi\nquis"), "si\nquis");
+        assert_eq!(normalizer.normalize_text("fi\n \r\nquis"), "fi\n \r\nquis");
+        assert!(is_paragraph_break("\n\n"));
+        assert!(is_paragraph_break(".\n\t\n"));
+        assert!(!is_paragraph_break(" \n "));
+        assert!(!is_paragraph_break("\n.\n"));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_paragraph_chunks() {
+        let text = "funt.\n\nfed eft\n\n\npoteft";
+        assert_eq!(paragraph_chunks(text, 0), vec!["funt.\n\n", "fed eft\n\n\n", "poteft"]);
+        assert_eq!(paragraph_chunks(text, 10), vec!["funt.\n\nfed eft\n\n\n", "poteft"]);
+        assert_eq!(paragraph_chunks(text, 100), vec![text]);
+        assert!(paragraph_chunks("", 0).is_empty());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_normalize_text_parallel_matches_sequential() {
+        let paragraph = "Chriftus eft, fi quis funt poteft-\nfumus ipfum\n\u{FB01}des eft.\n\n";
+        let text = paragraph.repeat(5000);
+        assert!(text.len() > 4 * PARALLEL_CHUNK_BYTES);
+        assert_eq!(normalize_text_parallel(&text, true, DEFAULT_THRESHOLD), normalize_text(&text, true, DEFAULT_THRESHOLD));
+        for hyphenation in [Hyphenation::Rejoin, Hyphenation::Preserve] {
+            let mut normalizer = context_normalizer();
+            normalizer.hyphenation = hyphenation;
+            assert_eq!(normalizer.normalize_text_parallel(&text), normalizer.normalize_text(&text));
+        }
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_domain_ngrams() {
+        // An in-domain sample where words starting with fu- are common
+        let domain = NgramData::from_json("{}", r#"{"<fu": 1000, "<su": 1}"#, "{}").unwrap();
+        let adapted = LongSNormalizer::builder().domain_ngrams(domain.clone(), 0.5).build();
+        assert_eq!(normalize_word("funt", true, DEFAULT_THRESHOLD), "sunt");
+        assert_eq!(adapted.normalize_word("funt"), "funt");
+
+        let base = crate::ngrams::bundled().unwrap();
+        let mixed = base.interpolate(&domain, 0.5);
+        assert_eq!(adapted.ngrams().unwrap().frequency("<fu"), mixed.frequency("<fu"));
+
+        // Weight 0 leaves the model as it was
+        let unchanged = LongSNormalizer::builder().domain_ngrams(domain, 0.0).build();
+        assert_eq!(unchanged.ngrams().unwrap().trigrams, base.trigrams);
+        assert_eq!(unchanged.normalize_word("funt"), "sunt");
+    }
+
+    #[test]
+    fn test_pass1_rules_longest_match() {
+        // Sequential replacement turned fpe into spe first, leaving fuspe
+        assert_eq!(pass1("fufpendit"), "suspendit");
+        assert_eq!(pass1("fff"), "ffs");
+
+        let mut rules = Pass1Rules::empty();
+        rules.add("fl", "sl").unwrap();
+        rules.add("<fla", "<sla").unwrap();
+        rules.add("f>", "s>").unwrap();
+        assert_eq!(rules.rules().next(), Some(("<fla", "<sla")));
+        assert_eq!(rules.apply("flamma"), "slamma");
+        assert_eq!(rules.apply("afflat"), "afslat");
+        assert_eq!(rules.apply("ef"), "es");
+        assert_eq!(rules.apply("fa"), "fa");
+
+        // Adding an existing pattern replaces it
+        rules.add("fl", "fl").unwrap();
+        assert_eq!(rules.len(), 3);
+        assert_eq!(rules.apply("afflat"), "afflat");
+    }
+
+    #[test]
+    fn test_pass1_rules_invalid() {
+        let mut rules = Pass1Rules::default();
+        for (pattern, replacement) in [("", ""), ("<>", "<>"), ("fl", "s"), ("Fl", "Sl"), ("f<l", "s<l"), ("<fl", "sl>"), ("f>", "s")] {
+            let err = rules.add(pattern, replacement).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{} → {}", pattern, replacement);
+        }
+        assert_eq!(rules, Pass1Rules::default());
+    }
+
+    #[test]
+    fn test_normalizer_pass1_rule() {
+        let normalizer = LongSNormalizer::builder().pass1_rule("fl", "sl").unwrap().apply_pass2(false).build();
+        let result = normalizer.normalize_detailed("flendo eft");
+        assert_eq!(result.normalized, "slendo est");
+        assert_eq!(result.changes[0].rules[0].pattern, "fl → sl");
+        assert_eq!(LongSNormalizer::new().normalize_word("flendo"), "flendo");
+        assert_eq!(LongSNormalizer::new().pass1_rules().len(), 8);
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_suggest() {
+        let suggestions = suggest("funt", 5);
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].0, "sunt");
+        assert_eq!(suggestions[1].0, "funt");
+        assert!(suggestions[0].1 > 0.9);
+        assert!((suggestions.iter().map(|(_, score)| score).sum::<f64>() - 1.0).abs() < 1e-9);
+
+        // Best first, truncated to n, with case and punctuation kept
+        let suggestions = suggest("(Caufa),", 1);
+        assert_eq!(suggestions, vec![("(Causa),".to_string(), suggestions[0].1)]);
+        assert_eq!(suggest("dominus", 3), vec![("dominus".to_string(), 1.0)]);
+        assert!(suggest("funt", 0).is_empty());
+        assert_eq!(suggest("...", 3), vec![("...".to_string(), 1.0)]);
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_languages() {
+        let english = LongSNormalizer::builder().language(Language::English).build();
+        assert_eq!(english.normalize_text("I fhall fpeak often of it"), "I shall speak often of it");
+        // No English model ships, so Pass 2 leaves fu-/fe-/fi- words alone
+        assert_eq!(english.normalize_word("funny"), "funny");
+        assert!(english.is_allowlisted("first"));
+        assert!(!english.is_allowlisted("fuit"));
+
+        let german = LongSNormalizer::builder().language(Language::German).build();
+        assert_eq!(german.normalize_text("Die Fchule, die Luft, telefch"), "Die Schule, die Luft, telefch");
+        assert_eq!(german.normalize_word("Fifch"), "Fisch");
+        assert_eq!(german.normalize_word("englifch"), "englisch");
+
+        let french = LongSNormalizer::builder().language(Language::French).build();
+        assert_eq!(french.normalize_text("il eft neuf jufqu'ici"), "il est neuf jusqu'ici");
+        assert_eq!(french.language(), Language::French);
+
+        assert_eq!(LongSNormalizer::new().language(), Language::Latin);
+        assert_eq!(LongSNormalizer::builder().language(Language::Latin).build().normalize_word("funt"), "sunt");
+        assert_eq!(Language::from_code("DE"), Some(Language::German));
+        assert_eq!(Language::from_code("french").map(Language::code), Some("fr"));
+        assert_eq!(Language::from_code("xx"), None);
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_with_model() {
+        let normalizer = LongSNormalizer::with_model(crate::ngrams::DEFAULT_MODEL).unwrap();
+        assert_eq!(normalizer.normalize_word("funt"), "sunt");
+        let err = LongSNormalizer::with_model("no-such-model").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_data_source() {
+        assert!(matches!(LongSNormalizer::new().data_source(), DataSource::Bundled));
+        let data = Arc::new(crate::ngrams::train_ngrams(["fuga"]));
+        let normalizer = LongSNormalizer::builder()
+            .data_source(DataSource::InMemory(Arc::clone(&data)))
+            .unwrap()
+            .build();
+        assert!(matches!(normalizer.data_source(), DataSource::InMemory(_)));
+        assert_eq!(normalizer.normalize_word("funt"), "funt");
+
+        let missing = std::env::temp_dir().join("no-such-ngrams");
+        let err = LongSNormalizer::builder().data_source(DataSource::Directory(missing)).unwrap_err();
+        assert!(err.message().contains("no-such-ngrams"));
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_threshold_plumbed_through() {
+        // <su outnumbers <fu roughly 6.6:1 in the bundled trigrams
+        assert_eq!(normalize_word("funt", true, 5.0), "sunt");
+        assert_eq!(normalize_word("funt", true, 10.0), "funt");
+        assert_eq!(normalize_text("funt, funt", true, 10.0), "funt, funt");
+        assert!(normalize_detailed("funt", true, 10.0).changes.is_empty());
+    }
+
+    #[test]
+    fn test_allowlist_preserved() {
+        assert_eq!(normalize_word("fuit", true, DEFAULT_THRESHOLD), "fuit");
+    }
+
+    #[test]
+    fn test_allowlist_case_preserved() {
+        assert_eq!(normalize_word("Fuit", true, DEFAULT_THRESHOLD), "Fuit");
+        assert_eq!(normalize_word("FUIT", true, DEFAULT_THRESHOLD), "FUIT");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_extend_allowlist() {
+        let mut normalizer = LongSNormalizer::new();
+        assert_eq!(normalizer.normalize_word("fides"), "fides");
+        assert_eq!(normalizer.normalize_word("fuga"), "suga");
+
+        normalizer.extend_allowlist(["Fuga"]);
+        assert!(normalizer.is_allowlisted("fuga"));
+        assert_eq!(normalizer.normalize_word("Fuga"), "Fuga");
+        // The shared default instance is unaffected
+        assert_eq!(normalize_word("fuga", true, DEFAULT_THRESHOLD), "suga");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_denylist_forces_conversion() {
+        let mut normalizer = LongSNormalizer::new();
+        // `fit` is on the built-in allowlist; the denylist overrides it
+        normalizer.extend_denylist(["fit"]);
+        assert_eq!(normalizer.normalize_word("fit"), "sit");
+        assert_eq!(normalizer.normalize_word("FIT"), "SIT");
+
+        let (_, rules) = normalizer.normalize_word_traced("fit");
+        assert_eq!(rules[0].pattern, "<f → <s (denylist)");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_names_skip_pass2() {
+        assert_eq!(normalize_text("Fulvius et Furius funt", true, DEFAULT_THRESHOLD), "Fulvius et Furius sunt");
+        assert_eq!(normalize_word("FVLVIVS", true, DEFAULT_THRESHOLD), "FVLVIVS");
+        // Pass 1 still applies to a name
+        assert_eq!(normalize_word("Fauftus", true, DEFAULT_THRESHOLD), "Faustus");
+        let unguarded = LongSNormalizer::builder().name_guard(None).build();
+        assert_eq!(unguarded.normalize_text("Fulvius"), "Sulvius");
+
+        let guard = NameGuard::new().capitalized(true);
+        let normalizer = LongSNormalizer::builder().name_guard(Some(guard)).context(true).build();
+        assert_eq!(normalizer.normalize_text("Funt. Et Funt"), "Sunt. Et Funt");
+        let (_, rules) = normalizer.normalize_word_traced("Fulvius");
+        assert!(rules.is_empty());
+        // The denylist wins over the guard
+        let mut normalizer = LongSNormalizer::new();
+        normalizer.extend_denylist(["fulvius"]);
+        assert_eq!(normalizer.normalize_word("Fulvius"), "Sulvius");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_builder_configuration() {
+        let strict = LongSNormalizer::builder().threshold(10.0).build();
+        let pass1_only = LongSNormalizer::builder().apply_pass2(false).build();
+        let default = LongSNormalizer::new();
+
+        assert_eq!(strict.threshold(), 10.0);
+        assert!(!pass1_only.apply_pass2());
+        // Differently configured normalizers coexist in one process
+        assert_eq!(default.normalize_text("funt eft"), "sunt est");
+        assert_eq!(strict.normalize_text("funt eft"), "funt est");
+        assert_eq!(pass1_only.normalize_text("funt eft"), "funt est");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_builder_word_lists() {
+        let normalizer = LongSNormalizer::builder()
+            .allowlist(["fuga"])
+            .denylist(["fit"])
+            .build();
+        assert_eq!(normalizer.normalize_text("fuga fit"), "fuga sit");
+        assert_eq!(normalizer.normalize_detailed("fit").changes[0].normalized, "sit");
+    }
+
+    #[test]
+    fn test_builder_ngram_dir() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../src/latincy_preprocess/long_s/data/ngrams");
+        let normalizer = LongSNormalizer::builder().ngram_dir(&dir).unwrap().build();
+        assert_eq!(normalizer.normalize_word("funt"), "sunt");
+
+        let missing = LongSNormalizer::builder().ngram_dir(dir.join("missing"));
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_builder_in_memory_ngrams() {
+        let ngrams = NgramData::from_maps(
+            HashMap::new(),
+            HashMap::from([("<fu".to_string(), 90), ("<su".to_string(), 10)]),
+            HashMap::new(),
+        );
+        // With this model f- is the common form, so pass 2 keeps it
+        let normalizer = LongSNormalizer::builder().ngrams(ngrams).build();
+        assert_eq!(normalizer.normalize_word("funt"), "funt");
+        #[cfg(feature = "embed-data")]
+        assert_eq!(normalize_word("funt", true, DEFAULT_THRESHOLD), "sunt");
+    }
+
+    #[test]
+    fn test_candidates() {
+        assert_eq!(candidates("caufa"), vec!["caufa", "causa"]);
+        assert_eq!(candidates("sunt"), vec!["sunt"]);
+        let all = candidates("Fenfus");
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0], "fenfus");
+        assert!(all.contains(&"sensus".to_string()));
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_candidate_search_medial() {
+        let normalizer = LongSNormalizer::builder().candidate_search(true).build();
+        assert_eq!(normalizer.normalize_word("caufa"), "causa");
+        assert_eq!(normalizer.normalize_word("menfis"), "mensis");
+        assert_eq!(normalizer.normalize_word("Confilium"), "Consilium");
+        assert_eq!(normalizer.normalize_word("funt"), "sunt");
+        // The fixed-pattern Pass 2 leaves medial f alone
+        assert_eq!(normalize_word("caufa", true, DEFAULT_THRESHOLD), "caufa");
+    }
+
+    #[test]
+    fn test_candidate_search_keeps_real_f() {
+        let normalizer = LongSNormalizer::builder().candidate_search(true).build();
+        for word in ["profero", "refert", "fecit", "officium", "facies", "fuga", "infra"] {
+            assert_eq!(normalizer.normalize_word(word), word);
+        }
+        let strict = LongSNormalizer::builder().candidate_search(true).threshold(1e12).build();
+        assert_eq!(strict.normalize_word("caufa"), "caufa");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_candidate_search_traced() {
+        let normalizer = LongSNormalizer::builder().candidate_search(true).build();
+        let (word, rules) = normalizer.normalize_word_traced("menfis");
+        assert_eq!(word, "mensis");
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].pass, 2);
+        assert_eq!(rules[0].pattern, "menfis → mensis (candidate search)");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_medial_pass() {
+        let normalizer = LongSNormalizer::builder().medial_pass(true).build();
+        assert_eq!(normalizer.normalize_word("rofa"), "rosa");
+        assert_eq!(normalizer.normalize_word("Mifer"), "Miser");
+        assert_eq!(normalizer.normalize_word("defiderium"), "desiderium");
+        assert_eq!(normalizer.normalize_text("caufa, quafi"), "causa, quasi");
+        // Off by default
+        assert_eq!(normalize_word("rofa", true, DEFAULT_THRESHOLD), "rofa");
+    }
+
+    #[test]
+    fn test_medial_pass_guards_real_f() {
+        let normalizer = LongSNormalizer::builder().medial_pass(true).build();
+        for word in ["profero", "refert", "praefectus", "aufero", "nefas", "beneficium", "magnificus", "refugium", "pontifex"] {
+            assert_eq!(normalizer.normalize_word(word), word);
+        }
+        let guarded = LongSNormalizer::builder().medial_pass(true).allowlist(["rofa"]).build();
+        assert_eq!(guarded.normalize_word("rofa"), "rofa");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_medial_pass_traced() {
+        let normalizer = LongSNormalizer::builder().medial_pass(true).build();
+        let (word, rules) = normalizer.normalize_word_traced("rofa");
+        assert_eq!(word, "rosa");
+        assert_eq!(rules[0].pattern, "ofa → osa (medial)");
+        assert_eq!(rules[0].evidence.len(), 6);
+        assert_eq!(rules[0].evidence[2].0, "ofa");
+        assert_eq!(rules[0].evidence[3].0, "osa");
+    }
+
+    #[test]
+    fn test_scoring_small_counts() {
+        // One observation of <su against none of <fu
+        let ngrams = Arc::new(NgramData::from_maps(
+            HashMap::new(),
+            HashMap::from([("<su".to_string(), 1)]),
+            HashMap::new(),
+        ));
+        let smoothed = LongSNormalizer::builder().ngrams(Arc::clone(&ngrams)).build();
+        let ratio = LongSNormalizer::builder().ngrams(ngrams).scoring(Pass2Scoring::Ratio).build();
+        assert_eq!(smoothed.scoring(), Pass2Scoring::LogProb);
+        assert_eq!(smoothed.normalize_word("funt"), "funt");
+        assert_eq!(ratio.normalize_word("funt"), "sunt");
+    }
+
+    #[test]
+    fn test_scoring_matches_ratio_on_bundled_model() {
+        let ratio = LongSNormalizer::builder().scoring(Pass2Scoring::Ratio).build();
+        let text = "funt fecit fimulacra fidem fuper femper fiue fi";
+        assert_eq!(ratio.normalize_text(text), normalize_text(text, true, DEFAULT_THRESHOLD));
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_detailed_pass2_score() {
+        let result = normalize_detailed("funt", true, DEFAULT_THRESHOLD);
+        let score = result.changes[0].rules[0].score.unwrap();
+        assert!(score.s_score - score.f_score > DEFAULT_THRESHOLD.ln());
+        assert!(score.f_score < 0.0);
+        let pass1 = normalize_detailed("eft", false, DEFAULT_THRESHOLD);
+        assert_eq!(pass1.changes[0].rules[0].score, None);
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_double_f() {
+        let normalizer = LongSNormalizer::builder().double_f(true).build();
+        assert_eq!(normalizer.normalize_word("poffum"), "possum");
+        assert_eq!(normalizer.normalize_word("Effe"), "Esse");
+        assert_eq!(normalizer.normalize_word("FUIFFE"), "FUISSE");
+        assert_eq!(normalizer.normalize_text("neceffe, promiffum"), "necesse, promissum");
+        // Off by default
+        // Off by default, where Pass 1's fum rule splits the cluster
+        assert_eq!(normalize_word("poffum", true, DEFAULT_THRESHOLD), "pofsum");
+    }
+
+    #[test]
+    fn test_double_f_keeps_genuine_ff() {
+        let normalizer = LongSNormalizer::builder().double_f(true).build();
+        for word in ["offert", "differt", "efficio", "officium", "sufficit", "affert", "effero", "offendo", "difficilis", "efferre", "effugit", "effundo"] {
+            assert_eq!(normalizer.normalize_word(word), word);
+        }
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_double_f_traced() {
+        let normalizer = LongSNormalizer::builder().double_f(true).build();
+        let (word, rules) = normalizer.normalize_word_traced("effe");
+        assert_eq!(word, "esse");
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].pattern, "ff → ss (double f)");
+        let keys: Vec<&str> = rules[0].evidence.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["<eff", "<ess", "effe", "esse", "ffe>", "sse>"]);
+        assert!(rules[0].score.is_some());
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_lexicon_pass() {
+        let lexicon = Arc::new(Lexicon::from_counts([("causa", 50), ("mensis", 20), ("fumus", 5), ("sumus", 90)]));
+        let normalizer = LongSNormalizer::builder().lexicon(Arc::clone(&lexicon)).build();
+        assert_eq!(normalizer.normalize_word("caufa"), "causa");
+        assert_eq!(normalizer.normalize_word("Menfis"), "Mensis");
+        // Attested results are kept, even if another spelling is attested too
+        assert_eq!(normalizer.normalize_word("fumus"), "sumus");
+        // Unknown words with no attested spelling are left as Pass 1/2 made them
+        assert_eq!(normalizer.normalize_word("funt"), "sunt");
+        // Allowlisted words are not second-guessed
+        let fuit = LongSNormalizer::builder().lexicon(Lexicon::from_words(["suit"])).build();
+        assert_eq!(fuit.normalize_word("fuit"), "fuit");
+        // Without a lexicon nothing changes
+        assert_eq!(normalize_word("caufa", true, DEFAULT_THRESHOLD), "caufa");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_lexicon_pass_reverts_to_attested_f() {
+        // Pass 2 reads funditus as sunditus; the lexicon knows better
+        let lexicon = Lexicon::from_words(["funditus"]);
+        let normalizer = LongSNormalizer::builder().lexicon(lexicon).build();
+        assert_eq!(normalize_word("funditus", true, DEFAULT_THRESHOLD), "sunditus");
+        let (word, rules) = normalizer.normalize_word_traced("funditus");
+        assert_eq!(word, "funditus");
+        let last = rules.last().unwrap();
+        assert_eq!(last.pass, 3);
+        assert_eq!(last.pattern, "sunditus → funditus (lexicon)");
+        assert_eq!(last.evidence, vec![("sunditus".to_string(), 0), ("funditus".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_load_word_lists() {
+        let dir = std::env::temp_dir().join(format!("long_s_lists_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let txt = dir.join("allow.txt");
+        let json = dir.join("deny.json");
+        std::fs::write(&txt, "# early modern forms\nfuga\n\nfugam  # acc.\n").unwrap();
+        std::fs::write(&json, r#"["fit"]"#).unwrap();
+
+        let mut normalizer = LongSNormalizer::new();
+        normalizer.load_allowlist(&txt).unwrap();
+        normalizer.load_denylist(&json).unwrap();
+        assert!(normalizer.is_allowlisted("fuga"));
+        assert!(normalizer.is_allowlisted("fugam"));
+        assert!(normalizer.is_denylisted("fit"));
+        assert!(normalizer.load_allowlist(dir.join("missing.txt")).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_detailed_pass1_rules() {
+        let result = normalize_detailed("Sic uita eft reuf", true, DEFAULT_THRESHOLD);
+        assert_eq!(result.normalized, "Sic uita est reus");
+        assert_eq!(result.changes.len(), 2);
+
+        let est = &result.changes[0];
+        assert_eq!(est.position, 9);
+        assert_eq!(est.original, "eft");
+        assert_eq!(est.normalized, "est");
+        assert_eq!(est.rules[0].pass, 1);
+        assert_eq!(est.rules[0].pattern, "ft → st");
+        assert!(est.rules[0].evidence.is_empty());
+
+        assert_eq!(result.changes[1].position, 13);
+        assert_eq!(result.changes[1].rules[0].pattern, "f> → s>");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_char_changes() {
+        let result = normalize_detailed("Sic uita eft funt \u{FB01}des", true, DEFAULT_THRESHOLD);
+        let records = result.char_changes();
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records[0],
+            ChangeRecord {
+                position: 10,
+                original: "f".to_string(),
+                normalized: "s".to_string(),
+                pass: 1,
+                rule: "ft → st".to_string(),
+                context: "a e[f]t f".to_string(),
+            }
+        );
+        assert_eq!((records[1].position, records[1].pass), (13, 2));
+        assert_eq!(records[1].rule, "<fu → <su");
+        assert_eq!((records[2].original.as_str(), records[2].normalized.as_str()), ("\u{FB01}", "fi"));
+        assert_eq!(records[2].rule, "\u{FB01} → fi");
+
+        // A rejoined word's separator is removed as one record
+        let normalizer = LongSNormalizer::builder().hyphenation(Hyphenation::Rejoin).build();
+        let records = normalizer.normalize_detailed("a chrif-\ntus").char_changes();
+        assert_eq!(records.len(), 2);
+        assert_eq!((records[0].position, records[0].normalized.as_str()), (6, "s"));
+        assert_eq!((records[1].position, records[1].original.as_str()), (7, "-\n"));
+        assert_eq!((records[1].normalized.as_str(), records[1].pass), ("", 0));
+        assert_eq!(records[1].context, "rif[-\n]tus");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_detailed_pass2_evidence() {
+        let result = normalize_detailed("funt", true, DEFAULT_THRESHOLD);
+        let rule = &result.changes[0].rules[0];
+        assert_eq!(rule.pass, 2);
+        assert_eq!(rule.pattern, "<fu → <su");
+        assert_eq!(rule.evidence[0].0, "<fu");
+        assert_eq!(rule.evidence[1].0, "<su");
+        assert!(rule.evidence[1].1 > rule.evidence[0].1 * 2);
+    }
+
+    #[test]
+    fn test_detailed_positions_with_punctuation() {
+        let result = normalize_detailed("«Sic» uita,\neft", true, DEFAULT_THRESHOLD);
+        assert_eq!(result.normalized, "«Sic» uita,\nest");
+        assert_eq!(result.changes[0].position, 12);
+        assert_eq!(result.changes[0].original, "eft");
+    }
+
+    #[test]
+    fn test_detailed_matches_normalize_text() {
+        let text = "funt in fundamento reipublicae ftatua";
+        assert_eq!(normalize_detailed(text, true, DEFAULT_THRESHOLD).normalized, normalize_text(text, true, DEFAULT_THRESHOLD));
+        assert!(normalize_detailed("fuit", true, DEFAULT_THRESHOLD).changes.is_empty());
+    }
+
+    #[test]
+    fn test_candidate_scan_matches_units() {
+        let text = "Arma virumque cano, Trojae qui primus ab oris. Italiam fato profugus\n\
+                    Lavinaque venit litora; MULTUM ille et terris jactatus et alto\n\
+                    vi fuperum, faevae memorem Junonis ob iram. McDonald eft «Cæfar» iſt\n\
+                    POſSUM ﬅatua offert fuiffe rofa Marcus Tullius Cicero fcripfit, 3fe fe—";
+        let normalizers = [
+            LongSNormalizer::new(),
+            LongSNormalizer::builder().candidate_search(true).medial_pass(true).double_f(true).build(),
+            LongSNormalizer::builder().name_guard(Some(NameGuard::new().capitalized(true))).build(),
+            LongSNormalizer::builder().apply_pass2(false).name_guard(None).build(),
+        ];
+        for normalizer in &normalizers {
+            let units: String = normalizer
+                .normalize_units(text, normalizer.apply_pass2, normalizer.threshold, false)
+                .into_iter()
+                .map(|(_, normalized, _)| normalized)
+                .collect();
+            assert_eq!(normalizer.normalize_text(text), units);
+            // Words the scans pass over really are left alone
+            for segment in segments(text).filter(|segment| is_inert(segment.text)) {
+                let normalized = normalizer.normalize_word_with(segment.text, true, DEFAULT_THRESHOLD, None);
+                assert_eq!(normalized, segment.text);
+            }
+        }
+        assert!(is_inert("Arma") && is_inert("MULTUM") && is_inert("a") && is_inert("McDonald"));
+        assert!(!is_inert("fato") && !is_inert("iſt") && !is_inert("Cæsar"));
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_word_cache() {
+        let cache = Arc::new(WordCache::new(64));
+        let text = "funt in fundamento fuit, funt Cicero et fuper eſt ftatua";
+        let mut cached = LongSNormalizer::builder().word_cache(Arc::clone(&cache)).build();
+        assert_eq!(cached.normalize_text(text), normalize_text(text, true, DEFAULT_THRESHOLD));
+        assert_eq!(cached.normalize_text(text), normalize_text(text, true, DEFAULT_THRESHOLD));
+        assert!(cache.hits() > cache.misses());
+        // Threshold and Pass 2 are part of the key
+        assert_eq!(cached.normalize_word_with("funt", false, DEFAULT_THRESHOLD, None), "funt");
+        assert_eq!(cached.normalize_word("funt"), "sunt");
+
+        // Changing the word lists retires the old entries
+        cached.extend_allowlist(["funt"]);
+        assert_eq!(cached.normalize_word("funt"), "funt");
+        let other = LongSNormalizer::builder().word_cache(Arc::clone(&cache)).build();
+        assert_eq!(other.normalize_word("funt"), "sunt");
+        // Traced paths keep running every rule
+        let misses = cache.misses();
+        assert!(!other.normalize_detailed(text).changes.is_empty());
+        assert_eq!(cache.misses(), misses);
+    }
+
+    proptest::proptest! {
+        /// The word passes take any text, and the text and detailed paths
+        /// agree with each other and with the unit-by-unit path.
+        #[test]
+        fn test_normalize_properties(text in crate::testing::text()) {
+            pass1(&text);
+            pass2(&text, DEFAULT_THRESHOLD);
+            let normalized = normalize_text(&text, true, DEFAULT_THRESHOLD);
+            proptest::prop_assert_eq!(&normalize_detailed(&text, true, DEFAULT_THRESHOLD).normalized, &normalized);
+            let chars = text.chars().count();
+            for change in normalize_detailed(&text, true, DEFAULT_THRESHOLD).changes {
+                proptest::prop_assert!(change.position < chars);
+            }
+            let normalizer = LongSNormalizer::new();
+            let units: String = normalizer
+                .normalize_units(&text, true, DEFAULT_THRESHOLD, false)
+                .into_iter()
+                .map(|(_, normalized, _)| normalized)
+                .collect();
+            proptest::prop_assert_eq!(units, normalized);
+        }
     }
 }
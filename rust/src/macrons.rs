@@ -0,0 +1,317 @@
+//! Vowel-length marks: stripping macrons (and breves) for macron-free input,
+//! and transferring them back from a macronized reference after
+//! normalization.
+
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+
+/// Combining macron (U+0304).
+pub const COMBINING_MACRON: char = '\u{0304}';
+/// Combining breve (U+0306), sometimes paired with macrons in pedagogical
+/// texts.
+pub const COMBINING_BREVE: char = '\u{0306}';
+
+/// A vowel-length mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    Macron,
+    Breve,
+}
+
+impl Mark {
+    fn combining(self) -> char {
+        match self {
+            Mark::Macron => COMBINING_MACRON,
+            Mark::Breve => COMBINING_BREVE,
+        }
+    }
+
+    fn from_combining(c: char) -> Option<Mark> {
+        match c {
+            COMBINING_MACRON => Some(Mark::Macron),
+            COMBINING_BREVE => Some(Mark::Breve),
+            _ => None,
+        }
+    }
+}
+
+/// Precomposed marked vowels: (marked, base, mark).
+const MARKED_VOWELS: &[(char, char, Mark)] = &[
+    ('ā', 'a', Mark::Macron),
+    ('ē', 'e', Mark::Macron),
+    ('ī', 'i', Mark::Macron),
+    ('ō', 'o', Mark::Macron),
+    ('ū', 'u', Mark::Macron),
+    ('ȳ', 'y', Mark::Macron),
+    ('ǣ', 'æ', Mark::Macron),
+    ('Ā', 'A', Mark::Macron),
+    ('Ē', 'E', Mark::Macron),
+    ('Ī', 'I', Mark::Macron),
+    ('Ō', 'O', Mark::Macron),
+    ('Ū', 'U', Mark::Macron),
+    ('Ȳ', 'Y', Mark::Macron),
+    ('Ǣ', 'Æ', Mark::Macron),
+    ('ă', 'a', Mark::Breve),
+    ('ĕ', 'e', Mark::Breve),
+    ('ĭ', 'i', Mark::Breve),
+    ('ŏ', 'o', Mark::Breve),
+    ('ŭ', 'u', Mark::Breve),
+    ('Ă', 'A', Mark::Breve),
+    ('Ĕ', 'E', Mark::Breve),
+    ('Ĭ', 'I', Mark::Breve),
+    ('Ŏ', 'O', Mark::Breve),
+    ('Ŭ', 'U', Mark::Breve),
+];
+
+fn marked(c: char) -> Option<(char, Mark)> {
+    MARKED_VOWELS.iter().find(|&&(m, _, _)| m == c).map(|&(_, base, mark)| (base, mark))
+}
+
+fn compose(base: char, mark: Option<Mark>, out: &mut String) {
+    let Some(mark) = mark else {
+        out.push(base);
+        return;
+    };
+    match MARKED_VOWELS.iter().find(|&&(_, b, m)| b == base && m == mark) {
+        Some(&(precomposed, _, _)) => out.push(precomposed),
+        None => {
+            out.push(base);
+            out.push(mark.combining());
+        }
+    }
+}
+
+/// Split text into base characters and their marks, whether precomposed
+/// (`ā`) or combining (`a` + U+0304). A stray combining mark with nothing to
+/// attach to is dropped.
+fn decompose(text: &str) -> Vec<(char, Option<Mark>)> {
+    let mut result: Vec<(char, Option<Mark>)> = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        if let Some(mark) = Mark::from_combining(c) {
+            if let Some(last) = result.last_mut() {
+                last.1.get_or_insert(mark);
+            }
+        } else if let Some((base, mark)) = marked(c) {
+            result.push((base, Some(mark)));
+        } else {
+            result.push((c, None));
+        }
+    }
+    result
+}
+
+fn is_mark(c: char) -> bool {
+    Mark::from_combining(c).is_some() || marked(c).is_some()
+}
+
+/// Whether `text` has any macrons or breves, precomposed or combining.
+pub fn has_macrons(text: &str) -> bool {
+    text.chars().any(is_mark)
+}
+
+/// Remove macrons (and breves) from Latin text, preserving case.
+///
+/// Handles both precomposed characters (`ā` → `a`) and combining marks
+/// (`a` + U+0304 → `a`), like the Python `strip_macrons`.
+///
+/// ```
+/// use latincy_preprocess::macrons::strip_macrons;
+///
+/// assert_eq!(strip_macrons("Rōma laudāre"), "Roma laudare");
+/// ```
+pub fn strip_macrons(text: &str) -> String {
+    if !has_macrons(text) {
+        return text.to_string();
+    }
+    decompose(text).into_iter().map(|(base, _)| base).collect()
+}
+
+/// Case- and u/v-, i/j-insensitive letter comparison, so marks survive
+/// normalization of the plain text.
+fn fold(c: char) -> char {
+    match c.to_lowercase().next().unwrap_or(c) {
+        'v' => 'u',
+        'j' => 'i',
+        lower => lower,
+    }
+}
+
+/// Maximal runs of alphabetic characters, as index ranges.
+fn words(chars: &[(char, Option<Mark>)]) -> Vec<(usize, usize)> {
+    let mut result = Vec::new();
+    let mut start = None;
+    for (i, &(c, _)) in chars.iter().enumerate() {
+        match (c.is_alphabetic(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                result.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        result.push((s, chars.len()));
+    }
+    result
+}
+
+/// Edit-distance alignment of `a` and `b` under `cost` (0 for a match),
+/// returning the matched and substituted index pairs in order.
+fn align<T>(a: &[T], b: &[T], cost: impl Fn(&T, &T) -> usize) -> Vec<(usize, usize)> {
+    let (n, m) = (a.len(), b.len());
+    let mut dist = vec![vec![0usize; m + 1]; n + 1];
+    for (i, row) in dist.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in dist[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let substitute = dist[i - 1][j - 1] + cost(&a[i - 1], &b[j - 1]);
+            dist[i][j] = substitute.min(dist[i - 1][j] + 1).min(dist[i][j - 1] + 1);
+        }
+    }
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (n, m);
+    while i > 0 && j > 0 {
+        if dist[i][j] == dist[i - 1][j - 1] + cost(&a[i - 1], &b[j - 1]) {
+            pairs.push((i - 1, j - 1));
+            i -= 1;
+            j -= 1;
+        } else if dist[i][j] == dist[i - 1][j] + 1 {
+            i -= 1;
+        } else {
+            j -= 1;
+        }
+    }
+    pairs.reverse();
+    pairs
+}
+
+/// Copy the macrons and breves of `macronized` onto `plain`, which is the
+/// same text without marks and perhaps normalized since (`u`/`v`, `i`/`j`,
+/// case, or a few changed letters or words).
+///
+/// Words are aligned first, then letters within each aligned pair of words;
+/// a mark is copied onto a letter of `plain` that aligns with the same letter
+/// in `macronized`. Marks already in `plain` are kept where the reference has
+/// none. Alignment is quadratic in the number of words, so pass sentences or
+/// paragraphs rather than whole books.
+///
+/// ```
+/// use latincy_preprocess::macrons::transfer_macrons;
+///
+/// assert_eq!(
+///     transfer_macrons("Gallia est omnis divisa", "Gallia est omnis dīuīsa"),
+///     "Gallia est omnis dīvīsa"
+/// );
+/// ```
+pub fn transfer_macrons(plain: &str, macronized: &str) -> String {
+    let mut target = decompose(plain);
+    let reference = decompose(macronized);
+    let target_words = words(&target);
+    let reference_words = words(&reference);
+
+    let folded = |chars: &[(char, Option<Mark>)], (start, end): (usize, usize)| -> Vec<char> {
+        chars[start..end].iter().map(|&(c, _)| fold(c)).collect()
+    };
+    let target_folded: Vec<Vec<char>> = target_words.iter().map(|&w| folded(&target, w)).collect();
+    let reference_folded: Vec<Vec<char>> = reference_words.iter().map(|&w| folded(&reference, w)).collect();
+
+    for (ti, ri) in align(&target_folded, &reference_folded, |a, b| usize::from(a != b)) {
+        let (t, r) = (&target_folded[ti], &reference_folded[ri]);
+        for (tc, rc) in align(t, r, |a, b| usize::from(a != b)) {
+            if t[tc] != r[rc] {
+                continue;
+            }
+            if let Some(mark) = reference[reference_words[ri].0 + rc].1 {
+                target[target_words[ti].0 + tc].1 = Some(mark);
+            }
+        }
+    }
+
+    let mut result = String::with_capacity(plain.len() + 8);
+    for (base, mark) in target {
+        compose(base, mark, &mut result);
+    }
+    result
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "strip_macrons")]
+pub fn py_strip_macrons(py: Python<'_>, text: &str) -> String {
+    crate::allow_threads(py, text, || strip_macrons(text))
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "has_macrons")]
+pub fn py_has_macrons(py: Python<'_>, text: &str) -> bool {
+    crate::allow_threads(py, text, || has_macrons(text))
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "transfer_macrons")]
+pub fn py_transfer_macrons(py: Python<'_>, plain: &str, macronized: &str) -> String {
+    crate::allow_threads(py, plain, || transfer_macrons(plain, macronized))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_macrons() {
+        assert_eq!(strip_macrons("laudāre"), "laudare");
+        assert_eq!(strip_macrons("RŌMA"), "ROMA");
+        assert_eq!(strip_macrons("a\u{0304}rma"), "arma");
+        assert_eq!(strip_macrons("rŏsă"), "rosa");
+        assert_eq!(strip_macrons("ǣquus"), "æquus");
+        assert_eq!(strip_macrons("arma"), "arma");
+        assert_eq!(strip_macrons(""), "");
+    }
+
+    #[test]
+    fn test_has_macrons() {
+        assert!(has_macrons("canō"));
+        assert!(has_macrons("cano\u{0304}"));
+        assert!(has_macrons("rosă"));
+        assert!(!has_macrons("cano"));
+    }
+
+    #[test]
+    fn test_transfer_macrons() {
+        assert_eq!(transfer_macrons("Arma virumque cano", "Arma uirumque canō"), "Arma virumque canō");
+        assert_eq!(transfer_macrons("ROMA", "Rōma"), "RŌMA");
+        assert_eq!(transfer_macrons("Iulius", "jūlius"), "Iūlius");
+        // A letter with no precomposed form takes a combining mark
+        assert_eq!(transfer_macrons("ba", "b\u{0304}a"), "b\u{0304}a");
+    }
+
+    #[test]
+    fn test_transfer_macrons_alignment() {
+        // Missing and extra words
+        assert_eq!(
+            transfer_macrons("Gallia est omnis divisa", "Gallia omnis dīvīsa est"),
+            "Gallia est omnis dīvīsa"
+        );
+        // A changed letter within a word
+        assert_eq!(transfer_macrons("caussa", "causā"), "caussā");
+        assert_eq!(transfer_macrons("quom", "quōm"), "quōm");
+        // Marks already present are kept
+        assert_eq!(transfer_macrons("canō arma", "cano arma"), "canō arma");
+        assert_eq!(transfer_macrons("", "canō"), "");
+    }
+}
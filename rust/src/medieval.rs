@@ -0,0 +1,531 @@
+//! Normalization of medieval Latin spellings to classical ones.
+//!
+//! Medieval scribes wrote *michi* and *nichil* for *mihi* and *nihil*,
+//! *dampnum* for *damnum*, *nacio* for *natio*, and *e* for the diphthongs
+//! *ae* and *oe* (*celum*, *pena*). [`MedievalNormalizer`] restores the
+//! classical forms with one toggle per [`MedievalRule`]. The *ci*/*ti* and
+//! *e*/*ae* rules are ambiguous word by word (*socius*, *facio*, *bene*), so
+//! the *e* rule needs a [`Lexicon`] of classical forms, and without one the
+//! *ci* rule only rewrites the unambiguous *-cion-* endings.
+
+use crate::changes::{self, ChangeRecord};
+use crate::lexicon::Lexicon;
+use crate::names::NameGuard;
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use crate::names::PyNameGuard;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use std::sync::Arc;
+
+/// One medieval spelling habit that [`MedievalNormalizer`] can undo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MedievalRule {
+    /// *ch* for *h* in *michi*, *nichil*, and their compounds.
+    ChForH,
+    /// *mpn* for *mn* (*dampnum*, *sompnus*, *contempnere*).
+    Mpn,
+    /// *ci* for *ti* before a vowel (*nacio*, *gracia*).
+    CiForTi,
+    /// *e* for the diphthongs *ae* and *oe* (*celum*, *pena*); needs a
+    /// lexicon.
+    EForAe,
+}
+
+impl MedievalRule {
+    pub const ALL: [MedievalRule; 4] = [MedievalRule::ChForH, MedievalRule::Mpn, MedievalRule::CiForTi, MedievalRule::EForAe];
+
+    /// The rule's name, as used by the Python bindings.
+    pub fn name(self) -> &'static str {
+        match self {
+            MedievalRule::ChForH => "ch_for_h",
+            MedievalRule::Mpn => "mpn",
+            MedievalRule::CiForTi => "ci_for_ti",
+            MedievalRule::EForAe => "e_for_ae",
+        }
+    }
+}
+
+/// Word beginnings where *ch* stands for *h*.
+const CH_FOR_H_STEMS: &[&str] = &["michi", "nichil"];
+
+/// Endings where *ci* before a vowel is always medieval *ti*: the *-tio*,
+/// *-tionis* nouns.
+const CION_ENDINGS: &[&str] = &["cionibus", "cionum", "cionis", "cionem", "ciones", "cioni", "cione"];
+
+/// Genuine *-cion-* stems (*suspicio*, *suspicionis*).
+const CION_EXCEPTIONS: &[&str] = &["suspicion"];
+
+/// Most ambiguous letters tried in one word against the lexicon; every
+/// combination of readings is looked up.
+pub const MAX_LEXICON_CHOICES: usize = 4;
+
+/// A replacement in a lowercase word: `len` letters at `start` become
+/// `replacement`.
+#[derive(Debug, Clone, PartialEq)]
+struct Edit {
+    start: usize,
+    len: usize,
+    replacement: &'static str,
+    rule: MedievalRule,
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y')
+}
+
+/// Positions of *c* in `ci` + vowel that could be medieval *ti*: not at the
+/// start of the word and not after *s* or *c* (*scio*, *ecce*).
+fn ci_positions(word: &[char]) -> Vec<usize> {
+    (1..word.len().saturating_sub(2))
+        .filter(|&i| word[i] == 'c' && word[i + 1] == 'i' && is_vowel(word[i + 2]) && !matches!(word[i - 1], 's' | 'c'))
+        .collect()
+}
+
+/// Positions of an *e* that could stand for *ae* or *oe*: not already part
+/// of a diphthong.
+fn e_positions(word: &[char]) -> Vec<usize> {
+    (0..word.len()).filter(|&i| word[i] == 'e' && (i == 0 || !matches!(word[i - 1], 'a' | 'o'))).collect()
+}
+
+fn apply(word: &[char], edits: &[Edit]) -> String {
+    let mut result = String::with_capacity(word.len() + 4);
+    let mut i = 0;
+    for edit in edits {
+        result.extend(&word[i..edit.start]);
+        result.push_str(edit.replacement);
+        i = edit.start + edit.len;
+    }
+    result.extend(&word[i..]);
+    result
+}
+
+/// Restores classical spellings in medieval Latin.
+///
+/// ```
+/// use latincy_preprocess::medieval::MedievalNormalizer;
+///
+/// let normalizer = MedievalNormalizer::default();
+/// assert_eq!(normalizer.normalize_text("Michi nichil dampnum racionis"), "Mihi nihil damnum rationis");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MedievalNormalizer {
+    rules: Vec<MedievalRule>,
+    lexicon: Option<Arc<Lexicon>>,
+    /// Words left as written.
+    names: Option<NameGuard>,
+}
+
+impl Default for MedievalNormalizer {
+    /// Every rule enabled, without a lexicon, leaving the built-in names
+    /// alone.
+    fn default() -> Self {
+        MedievalNormalizer {
+            rules: MedievalRule::ALL.to_vec(),
+            lexicon: None,
+            names: Some(NameGuard::new()),
+        }
+    }
+}
+
+impl MedievalNormalizer {
+    /// Enable or disable one rule.
+    pub fn rule(mut self, rule: MedievalRule, enabled: bool) -> Self {
+        self.rules.retain(|&r| r != rule);
+        if enabled {
+            self.rules.push(rule);
+        }
+        self
+    }
+
+    /// Check the ambiguous rules against `lexicon`: a reading is only
+    /// changed to a form the lexicon attests, and never if the medieval
+    /// form is itself attested.
+    pub fn lexicon(mut self, lexicon: Arc<Lexicon>) -> Self {
+        self.lexicon = Some(lexicon);
+        self
+    }
+
+    /// Which words count as proper names, left as written (default
+    /// [`NameGuard::new`]; `None` normalizes every word).
+    pub fn name_guard(mut self, guard: Option<NameGuard>) -> Self {
+        self.names = guard;
+        self
+    }
+
+    pub fn is_enabled(&self, rule: MedievalRule) -> bool {
+        self.rules.contains(&rule)
+    }
+
+    /// The enabled rules, in the order of [`MedievalRule::ALL`].
+    pub fn rules(&self) -> impl Iterator<Item = MedievalRule> + '_ {
+        MedievalRule::ALL.into_iter().filter(|&rule| self.is_enabled(rule))
+    }
+
+    /// The edits for a lowercase word, in order.
+    fn edits(&self, word: &[char]) -> Vec<Edit> {
+        let mut edits = Vec::new();
+        let text: String = word.iter().collect();
+
+        if self.is_enabled(MedievalRule::ChForH) && CH_FOR_H_STEMS.iter().any(|stem| text.starts_with(stem)) {
+            edits.push(Edit {
+                start: 2,
+                len: 2,
+                replacement: "h",
+                rule: MedievalRule::ChForH,
+            });
+        }
+        if self.is_enabled(MedievalRule::Mpn) {
+            for i in 1..word.len().saturating_sub(1) {
+                if word[i - 1..=i + 1] == ['m', 'p', 'n'] {
+                    edits.push(Edit {
+                        start: i,
+                        len: 1,
+                        replacement: "",
+                        rule: MedievalRule::Mpn,
+                    });
+                }
+            }
+        }
+
+        // Letters whose reading is ambiguous: (position, readings)
+        let mut choices: Vec<(usize, &[&'static str], MedievalRule)> = Vec::new();
+        if self.is_enabled(MedievalRule::CiForTi) {
+            for i in ci_positions(word) {
+                if CION_ENDINGS.contains(&word[i..].iter().collect::<String>().as_str())
+                    && !CION_EXCEPTIONS.iter().any(|stem| text.starts_with(stem))
+                {
+                    edits.push(Edit {
+                        start: i,
+                        len: 1,
+                        replacement: "t",
+                        rule: MedievalRule::CiForTi,
+                    });
+                } else if self.lexicon.is_some() {
+                    choices.push((i, &["c", "t"], MedievalRule::CiForTi));
+                }
+            }
+        }
+        if self.is_enabled(MedievalRule::EForAe) && self.lexicon.is_some() {
+            for i in e_positions(word) {
+                choices.push((i, &["e", "ae", "oe"], MedievalRule::EForAe));
+            }
+        }
+        if let (Some(lexicon), false) = (&self.lexicon, choices.is_empty()) {
+            if choices.len() <= MAX_LEXICON_CHOICES {
+                edits.extend(self.lexicon_edits(word, &edits, &choices, lexicon));
+            }
+        }
+
+        edits.sort_by_key(|edit| edit.start);
+        edits
+    }
+
+    /// The most frequent attested combination of `choices`, as edits on top
+    /// of the fixed `edits`; none if the word as it stands is attested.
+    fn lexicon_edits(
+        &self,
+        word: &[char],
+        edits: &[Edit],
+        choices: &[(usize, &[&'static str], MedievalRule)],
+        lexicon: &Lexicon,
+    ) -> Vec<Edit> {
+        let spell = |picked: &[usize]| -> (String, Vec<Edit>) {
+            let mut all = edits.to_vec();
+            for (&(start, readings, rule), &choice) in choices.iter().zip(picked) {
+                if choice > 0 {
+                    all.push(Edit {
+                        start,
+                        len: 1,
+                        replacement: readings[choice],
+                        rule,
+                    });
+                }
+            }
+            all.sort_by_key(|edit| edit.start);
+            let spelled = apply(word, &all);
+            all.retain(|edit| !edits.contains(edit));
+            (spelled, all)
+        };
+
+        let mut picked = vec![0usize; choices.len()];
+        if lexicon.contains(&spell(&picked).0) {
+            return Vec::new();
+        }
+        let mut best: Option<(u64, Vec<Edit>)> = None;
+        while let Some(choice) = (0..choices.len()).find(|&c| picked[c] + 1 < choices[c].1.len()) {
+            picked[choice] += 1;
+            picked[..choice].iter_mut().for_each(|p| *p = 0);
+            let (spelled, new_edits) = spell(&picked);
+            let frequency = lexicon.frequency(&spelled);
+            if frequency > 0 && best.as_ref().is_none_or(|(best, _)| frequency > *best) {
+                best = Some((frequency, new_edits));
+            }
+        }
+        best.map(|(_, edits)| edits).unwrap_or_default()
+    }
+
+    pub fn normalize_word(&self, word: &str) -> String {
+        self.normalize_detailed(word).normalized
+    }
+
+    pub fn normalize_text(&self, text: &str) -> String {
+        self.normalize_detailed(text).normalized
+    }
+
+    /// Normalize `text`, recording one [`ChangeRecord`] per edit.
+    pub fn normalize_detailed(&self, text: &str) -> DetailedResult {
+        let chars: Vec<char> = text.chars().collect();
+        let mut normalized = String::with_capacity(text.len() + 8);
+        let mut changes = Vec::new();
+
+        let mut i = 0;
+        while i < chars.len() {
+            if !chars[i].is_alphabetic() {
+                normalized.push(chars[i]);
+                i += 1;
+                continue;
+            }
+            let end = chars[i..].iter().position(|c| !c.is_alphabetic()).map_or(chars.len(), |p| i + p);
+            let original = &chars[i..end];
+            // Lowercasing can change the length of a few letters; leave such
+            // words alone rather than misplace the edits
+            let lower: Vec<char> = original.iter().flat_map(|c| c.to_lowercase()).collect();
+            let name = self.names.as_ref().is_some_and(|guard| {
+                guard.protects(&original.iter().collect::<String>(), &chars[..i])
+            });
+            let edits = if lower.len() == original.len() && !name { self.edits(&lower) } else { Vec::new() };
+
+            let upper = original.len() > 1 && original.iter().all(|c| !c.is_lowercase());
+            let mut at = 0;
+            for edit in &edits {
+                normalized.extend(&original[at..edit.start]);
+                let replaced = &original[edit.start..edit.start + edit.len];
+                let replacement = match replaced.first() {
+                    Some(c) if c.is_uppercase() && upper => edit.replacement.to_uppercase(),
+                    Some(c) if c.is_uppercase() => restore_first(edit.replacement),
+                    _ => edit.replacement.to_string(),
+                };
+                let position = i + edit.start;
+                changes.push(ChangeRecord {
+                    position,
+                    original: replaced.iter().collect(),
+                    rule: edit_rule(&lower, edit),
+                    normalized: replacement.clone(),
+                    pass: 1,
+                    context: changes::context(&chars, position, edit.len),
+                });
+                normalized.push_str(&replacement);
+                at = edit.start + edit.len;
+            }
+            normalized.extend(&original[at..]);
+            i = end;
+        }
+
+        DetailedResult {
+            original: text.to_string(),
+            normalized,
+            changes,
+        }
+    }
+}
+
+fn restore_first(letters: &str) -> String {
+    let mut chars = letters.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// The change a rule made, e.g. `mpn → mn` or `e → ae`.
+fn edit_rule(word: &[char], edit: &Edit) -> String {
+    match edit.rule {
+        MedievalRule::ChForH => "ch → h".to_string(),
+        MedievalRule::Mpn => "mpn → mn".to_string(),
+        MedievalRule::CiForTi => "ci → ti".to_string(),
+        MedievalRule::EForAe => {
+            let original: String = word[edit.start..edit.start + edit.len].iter().collect();
+            format!("{} → {}", original, edit.replacement)
+        }
+    }
+}
+
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<ChangeRecord>,
+}
+
+/// Normalize medieval spellings with every rule and no lexicon.
+pub fn normalize_text(text: &str) -> String {
+    MedievalNormalizer::default().normalize_text(text)
+}
+
+impl Normalizer for MedievalNormalizer {
+    fn name(&self) -> &str {
+        "medieval"
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = self.normalize_detailed(text);
+        NormalizeOutput::from_changes(result.original, result.normalized, result.changes)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "normalize_medieval")]
+pub fn py_normalize_medieval(py: Python<'_>, text: &str) -> String {
+    crate::allow_threads(py, text, || normalize_text(text))
+}
+
+/// Python view of [`MedievalNormalizer`], exposed as
+/// `_rust.MedievalNormalizer`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "MedievalNormalizer", frozen)]
+pub struct PyMedievalNormalizer {
+    pub(crate) inner: MedievalNormalizer,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyMedievalNormalizer {
+    #[new]
+    #[pyo3(signature = (ch_for_h=true, mpn=true, ci_for_ti=true, e_for_ae=true, lexicon=None, protect_names=true, name_guard=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        ch_for_h: bool,
+        mpn: bool,
+        ci_for_ti: bool,
+        e_for_ae: bool,
+        lexicon: Option<std::path::PathBuf>,
+        protect_names: bool,
+        name_guard: Option<PyRef<'_, PyNameGuard>>,
+    ) -> PyResult<Self> {
+        let mut inner = MedievalNormalizer::default()
+            .rule(MedievalRule::ChForH, ch_for_h)
+            .rule(MedievalRule::Mpn, mpn)
+            .rule(MedievalRule::CiForTi, ci_for_ti)
+            .rule(MedievalRule::EForAe, e_for_ae)
+            .name_guard(crate::names::from_py(protect_names, name_guard));
+        if let Some(path) = lexicon {
+            inner = inner.lexicon(Arc::new(Lexicon::from_file(path)?));
+        }
+        Ok(PyMedievalNormalizer { inner })
+    }
+
+    /// Names of the enabled rules.
+    #[getter]
+    fn rules(&self) -> Vec<&'static str> {
+        self.inner.rules().map(MedievalRule::name).collect()
+    }
+
+    fn normalize_word(&self, word: &str) -> String {
+        self.inner.normalize_word(word)
+    }
+
+    fn normalize_text(&self, py: Python<'_>, text: &str) -> String {
+        crate::allow_threads(py, text, || self.inner.normalize_text(text))
+    }
+
+    fn normalize_detailed(&self, py: Python<'_>, text: &str) -> PyResult<changes::PyDetailedResult> {
+        let result = crate::allow_threads(py, text, || self.inner.normalize_detailed(text));
+        changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("MedievalNormalizer(rules={:?})", self.rules())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lexicon() -> Arc<Lexicon> {
+        Arc::new(Lexicon::from_counts([
+            ("caelum", 50),
+            ("poena", 20),
+            ("paene", 10),
+            ("gratia", 40),
+            ("gratiae", 15),
+            ("socius", 10),
+            ("bene", 30),
+            ("quae", 100),
+        ]))
+    }
+
+    #[test]
+    fn test_fixed_rules() {
+        let normalizer = MedievalNormalizer::default();
+        assert_eq!(normalizer.normalize_text("michi nichilominus"), "mihi nihilominus");
+        assert_eq!(normalizer.normalize_text("dampnum sompnus contempnere"), "damnum somnus contemnere");
+        assert_eq!(normalizer.normalize_text("nacionem racionibus"), "nationem rationibus");
+        // Without a lexicon only -cion- endings change, and e is left alone
+        assert_eq!(normalizer.normalize_text("gracia socius suspicionem celum"), "gracia socius suspicionem celum");
+    }
+
+    #[test]
+    fn test_case() {
+        let normalizer = MedievalNormalizer::default();
+        assert_eq!(normalizer.normalize_text("Michi, NICHIL."), "Mihi, NIHIL.");
+        let normalizer = MedievalNormalizer::default().lexicon(lexicon());
+        assert_eq!(normalizer.normalize_text("Celum PENA Ecce"), "Caelum POENA Ecce");
+    }
+
+    #[test]
+    fn test_lexicon() {
+        let normalizer = MedievalNormalizer::default().lexicon(lexicon());
+        assert_eq!(normalizer.normalize_text("celum pena pene"), "caelum poena paene");
+        assert_eq!(normalizer.normalize_text("gracia gracie socius"), "gratia gratiae socius");
+        // Attested and unknown words are kept
+        assert_eq!(normalizer.normalize_text("bene que"), "bene quae");
+        assert_eq!(normalizer.normalize_text("fenestra"), "fenestra");
+    }
+
+    #[test]
+    fn test_toggles() {
+        let normalizer = MedievalNormalizer::default().rule(MedievalRule::ChForH, false).rule(MedievalRule::Mpn, false);
+        assert_eq!(normalizer.normalize_text("michi dampnum nacio"), "michi dampnum nacio");
+        assert_eq!(normalizer.rules().collect::<Vec<_>>(), [MedievalRule::CiForTi, MedievalRule::EForAe]);
+        let normalizer = MedievalNormalizer::default().lexicon(lexicon()).rule(MedievalRule::EForAe, false);
+        assert_eq!(normalizer.normalize_text("celum gracia"), "celum gratia");
+    }
+
+    #[test]
+    fn test_normalize_detailed() {
+        let normalizer = MedievalNormalizer::default().lexicon(lexicon());
+        let result = normalizer.normalize_detailed("in celo michi");
+        assert_eq!(result.normalized, "in celo mihi");
+        let result = normalizer.normalize_detailed("in celum michi");
+        assert_eq!(result.normalized, "in caelum mihi");
+        assert_eq!(result.changes.len(), 2);
+        assert_eq!(result.changes[0].position, 4);
+        assert_eq!(result.changes[0].original, "e");
+        assert_eq!(result.changes[0].normalized, "ae");
+        assert_eq!(result.changes[0].rule, "e → ae");
+        assert_eq!(result.changes[0].context, "n c[e]lum");
+        assert_eq!(result.changes[1].original, "ch");
+        assert_eq!(result.changes[1].rule, "ch → h");
+    }
+
+    #[test]
+    fn test_names() {
+        let guard = NameGuard::new().names(Arc::new(Lexicon::from_words(["dampnia"]))).capitalized(true);
+        let normalizer = MedievalNormalizer::default().lexicon(lexicon()).name_guard(Some(guard));
+        assert_eq!(normalizer.normalize_text("Michi dixit Dampnus pena"), "Mihi dixit Dampnus poena");
+        // A lexicon name is kept even at a sentence start
+        assert_eq!(normalizer.normalize_text("Dampnia"), "Dampnia");
+        let normalizer = normalizer.name_guard(None);
+        assert_eq!(normalizer.normalize_text("dixit Dampnus"), "dixit Damnus");
+    }
+}
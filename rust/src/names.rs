@@ -0,0 +1,260 @@
+//! Protection of proper names from the aggressive passes.
+//!
+//! The statistical passes judge a word by how Latin words usually look, and a
+//! name is not a usual word: long-s Pass 2 reads *Fulvius* as a misprinted
+//! *Sulvius*, and n-gram back-off "restores" diphthongs no inscription has.
+//! A [`NameGuard`] marks the words that are likely names, from a name lexicon
+//! and, if asked, from capitalization alone, so that these passes leave them
+//! alone or change them only on firmer evidence:
+//!
+//! - long-s skips Pass 2, the medial and double-f passes, and the context
+//!   pass for a name; Pass 1 and the lexicon of Pass 3 still apply,
+//! - [`DiphthongRestorer`](crate::diphthongs::DiphthongRestorer) restores a
+//!   name only to a reading its lexicon attests, never by back-off,
+//! - [`MedievalNormalizer`](crate::medieval::MedievalNormalizer) leaves names
+//!   as written.
+
+use crate::lexicon::Lexicon;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::types::{PyDict, PyList};
+use std::ops::Range;
+use std::sync::{Arc, LazyLock};
+
+/// Names the statistical passes are known to mangle: gentilicia, cognomina,
+/// and places with an *f* that long-s would read as *s*, or a spelling the
+/// n-gram model finds unlikely.
+pub const PROPER_NAMES: &[&str] = &[
+    "fabius", "fabii", "fabio", "fabium", "fabia", "fabiae", "fabiam", "fabianus", "fabricius",
+    "fabricii", "fabricio", "fabricium", "fulvius", "fulvii", "fulvio", "fulvium", "fulvia",
+    "fulviae", "fulviam", "furius", "furii", "furio", "furium", "furia", "furiae", "faustus",
+    "fausti", "fausto", "faustum", "fausta", "faustae", "faustina", "faustinae", "flaccus",
+    "flacci", "flacco", "flaccum", "flaminius", "flaminii", "flaminio", "flaminium", "flaminia",
+    "flaminiae", "flavius", "flavii", "flavio", "flavium", "flavia", "flaviae", "fannius",
+    "fannii", "fannio", "fannium", "fonteius", "fonteii", "fonteio", "fufius", "fufii",
+    "frontinus", "frontini", "fronto", "frontonis", "florus", "flori", "floro", "florentia",
+    "florentiae", "fidenae", "fidenarum", "fidenis", "fidenas", "falerii", "faleriorum",
+    "faleriis", "falernus", "falerni", "falerno", "falernum", "formiae", "formiarum", "formiis",
+    "fregellae", "fregellarum", "fregellis", "faesulae", "faesularum", "faesulis", "frusino",
+    "frusinonis", "fucinus", "fucini", "rufus", "rufi", "rufo", "rufum", "afranius", "afranii",
+    "afranio", "aufidius", "aufidii", "aufidio", "cornificius", "cornificii", "cornificio",
+];
+
+static BUILT_IN: LazyLock<Arc<Lexicon>> = LazyLock::new(|| {
+    Arc::new(Lexicon::from_words(PROPER_NAMES.iter().map(|name| name.replace('v', "u"))))
+});
+
+/// Decides which words of a text are likely proper names.
+///
+/// ```
+/// use latincy_preprocess::names::NameGuard;
+///
+/// let guard = NameGuard::new();
+/// assert!(guard.is_name("Fulvius"));
+/// assert!(!guard.is_name("fulvius"));
+/// let guard = guard.capitalized(true);
+/// assert_eq!(guard.find("Scripsit Tullius. Venit"), vec![9..16]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct NameGuard {
+    names: Arc<Lexicon>,
+    capitalized: bool,
+}
+
+impl Default for NameGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NameGuard {
+    /// Protect the [`PROPER_NAMES`], by lexicon only.
+    pub fn new() -> Self {
+        NameGuard {
+            names: Arc::clone(&BUILT_IN),
+            capitalized: false,
+        }
+    }
+
+    /// Protect the words of `names` instead. A name written with *v* for
+    /// *u* (*FVLVIVS*) or with a long s matches its *u* and round-s
+    /// spelling.
+    pub fn names(mut self, names: Arc<Lexicon>) -> Self {
+        self.names = names;
+        self
+    }
+
+    /// Also protect any capitalized word that does not start a sentence
+    /// (off by default: early prints capitalize nouns too).
+    pub fn capitalized(mut self, enabled: bool) -> Self {
+        self.capitalized = enabled;
+        self
+    }
+
+    /// Whether `word` is a capitalized word of the name lexicon. Leading and
+    /// trailing punctuation is ignored.
+    pub fn is_name(&self, word: &str) -> bool {
+        let word = word.trim_matches(|c: char| !c.is_alphabetic());
+        word.chars().next().is_some_and(char::is_uppercase) && lookup(&self.names, &word.to_lowercase())
+    }
+
+    /// Whether `word`, preceded in the text by `before`, is likely a name:
+    /// it is in the name lexicon or, with [`NameGuard::capitalized`], it is
+    /// capitalized and does not start a sentence.
+    pub fn protects(&self, word: &str, before: &[char]) -> bool {
+        if self.is_name(word) {
+            return true;
+        }
+        let word = word.trim_matches(|c: char| !c.is_alphabetic());
+        let mut letters = word.chars();
+        self.capitalized
+            && letters.next().is_some_and(char::is_uppercase)
+            && letters.any(char::is_lowercase)
+            && !starts_sentence(before)
+    }
+
+    /// The char ranges of the words of `text` that this guard protects.
+    pub fn find(&self, text: &str) -> Vec<Range<usize>> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut found = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            if !chars[i].is_alphabetic() {
+                i += 1;
+                continue;
+            }
+            let end = chars[i..].iter().position(|c| !c.is_alphabetic()).map_or(chars.len(), |p| i + p);
+            let word: String = chars[i..end].iter().collect();
+            if self.protects(&word, &chars[..i]) {
+                found.push(i..end);
+            }
+            i = end;
+        }
+        found
+    }
+}
+
+/// Whether `names` has the lowercase word `lower`, in its own spelling or
+/// with *u* for *v* and round s for long s.
+pub(crate) fn lookup(names: &Lexicon, lower: &str) -> bool {
+    if names.contains(lower) {
+        return true;
+    }
+    let respelled = lower.replace('v', "u").replace('ſ', "s");
+    respelled != lower && names.contains(&respelled)
+}
+
+/// Whether a word after `before` starts a sentence: it follows `.`, `!`,
+/// `?`, `:`, `;`, a blank line, or nothing. Other punctuation (quotes,
+/// brackets) is looked through.
+fn starts_sentence(before: &[char]) -> bool {
+    let mut line_breaks = 0;
+    for &c in before.iter().rev() {
+        if c == '\n' {
+            line_breaks += 1;
+        } else if matches!(c, '.' | '!' | '?' | ':' | ';') {
+            return true;
+        } else if c.is_alphanumeric() {
+            return line_breaks > 1;
+        }
+    }
+    true
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+/// Python view of [`NameGuard`], exposed as `_rust.NameGuard`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "NameGuard", frozen)]
+pub struct PyNameGuard {
+    pub(crate) inner: NameGuard,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyNameGuard {
+    /// `names` is a lexicon file of proper names, replacing the built-in
+    /// list.
+    #[new]
+    #[pyo3(signature = (names=None, capitalized=false))]
+    fn new(names: Option<std::path::PathBuf>, capitalized: bool) -> PyResult<Self> {
+        let mut inner = NameGuard::new().capitalized(capitalized);
+        if let Some(names) = names {
+            inner = inner.names(Arc::new(Lexicon::from_file(names)?));
+        }
+        Ok(PyNameGuard { inner })
+    }
+
+    fn is_name(&self, word: &str) -> bool {
+        self.inner.is_name(word)
+    }
+
+    /// The protected words of `text`, as dicts of `start`, `end` (char
+    /// offsets), and `text`.
+    fn find<'py>(&self, py: Python<'py>, text: &str) -> PyResult<Bound<'py, PyList>> {
+        let chars: Vec<char> = text.chars().collect();
+        let list = PyList::empty(py);
+        for range in crate::allow_threads(py, text, || self.inner.find(text)) {
+            let dict = PyDict::new(py);
+            dict.set_item("start", range.start)?;
+            dict.set_item("end", range.end)?;
+            dict.set_item("text", chars[range].iter().collect::<String>())?;
+            list.append(dict)?;
+        }
+        Ok(list)
+    }
+}
+
+#[cfg(feature = "pyo3-backend")]
+/// The guard for a normalizer's `protect_names` and `name_guard` arguments.
+pub(crate) fn from_py(protect_names: bool, name_guard: Option<PyRef<'_, PyNameGuard>>) -> Option<NameGuard> {
+    match name_guard {
+        _ if !protect_names => None,
+        Some(guard) => Some(guard.inner.clone()),
+        None => Some(NameGuard::new()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_name() {
+        let guard = NameGuard::new();
+        assert!(guard.is_name("Fabius"));
+        assert!(guard.is_name("FVLVIVS"));
+        assert!(guard.is_name("Fuluius,"));
+        assert!(guard.is_name("Fauſtus"));
+        // Lowercase words are not names, whatever their spelling
+        assert!(!guard.is_name("fabius"));
+        assert!(!guard.is_name("Funt"));
+    }
+
+    #[test]
+    fn test_capitalized() {
+        let guard = NameGuard::new().capitalized(true);
+        assert!(guard.protects("Tullius", &"scripsit ".chars().collect::<Vec<_>>()));
+        assert!(!guard.protects("Tullius", &"scripsit. ".chars().collect::<Vec<_>>()));
+        assert!(!guard.protects("Tullius", &[]));
+        assert!(!guard.protects("TVLLIVS", &"et ".chars().collect::<Vec<_>>()));
+        // A lexicon name is protected even at a sentence start
+        assert!(guard.protects("Fabius", &[]));
+        assert_eq!(guard.find("ait (Cicero) «Venit»\nMarcus\n\nTandem"), vec![5..11, 14..19, 21..27]);
+        assert!(NameGuard::new().find("ait Cicero").is_empty());
+    }
+
+    #[test]
+    fn test_custom_names() {
+        let guard = NameGuard::new().names(Arc::new(Lexicon::from_words(["traianus"])));
+        assert!(guard.is_name("TRAIANVS"));
+        assert!(!guard.is_name("Fabius"));
+    }
+}
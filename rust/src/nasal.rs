@@ -0,0 +1,425 @@
+//! Expansion of nasal bars: a macron or tilde over a vowel standing for an
+//! omitted *m* or *n* (*quā* → *quam*, *cōsul* → *consul*).
+//!
+//! The bar does not say which nasal was dropped, so [`NasalBars`] scores the
+//! readings of each word with the long-s n-gram model, falling back to the
+//! scribal rule (*m* before a labial or at the end of a word, *n* elsewhere)
+//! when no model is available. A text may also mark genuine vowel length with
+//! macrons; [`NasalBars::keep_length_marks`] lets the n-grams keep a macron
+//! rather than insert a letter.
+
+use crate::changes::{self, ChangeRecord};
+use crate::ngrams::{self, NgramData};
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use crate::ngrams::PyNgramData;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use std::sync::Arc;
+
+/// Combining tilde (U+0303).
+pub const COMBINING_TILDE: char = '\u{0303}';
+
+/// Most bars expanded in one word; every combination of readings is scored,
+/// so longer runs leave the word unchanged.
+pub const MAX_BARS: usize = 5;
+
+/// Letters before which a bar stands for *m*.
+const LABIALS: &[char] = &['b', 'p', 'm'];
+
+/// Precomposed barred vowels: (barred, vowel, is a tilde).
+const BARRED_VOWELS: &[(char, char, bool)] = &[
+    ('ā', 'a', false),
+    ('ē', 'e', false),
+    ('ī', 'i', false),
+    ('ō', 'o', false),
+    ('ū', 'u', false),
+    ('Ā', 'A', false),
+    ('Ē', 'E', false),
+    ('Ī', 'I', false),
+    ('Ō', 'O', false),
+    ('Ū', 'U', false),
+    ('ã', 'a', true),
+    ('ẽ', 'e', true),
+    ('ĩ', 'i', true),
+    ('õ', 'o', true),
+    ('ũ', 'u', true),
+    ('Ã', 'A', true),
+    ('Ẽ', 'E', true),
+    ('Ĩ', 'I', true),
+    ('Õ', 'O', true),
+    ('Ũ', 'U', true),
+];
+
+/// One character of input: a base letter, whether it carries a macron or a
+/// tilde, and the original characters it came from.
+#[derive(Debug, Clone)]
+struct Unit {
+    base: char,
+    bar: Option<bool>,
+    original: String,
+    position: usize,
+}
+
+fn units(text: &str) -> Vec<Unit> {
+    let mut result: Vec<Unit> = Vec::with_capacity(text.len());
+    for (position, c) in text.chars().enumerate() {
+        let combining = match c {
+            crate::macrons::COMBINING_MACRON => Some(false),
+            COMBINING_TILDE => Some(true),
+            _ => None,
+        };
+        if let Some(tilde) = combining {
+            if let Some(last) = result.last_mut().filter(|last| last.bar.is_none() && is_vowel(last.base)) {
+                last.bar = Some(tilde);
+                last.original.push(c);
+                continue;
+            }
+        }
+        let (base, bar) = match BARRED_VOWELS.iter().find(|&&(barred, _, _)| barred == c) {
+            Some(&(_, vowel, tilde)) => (vowel, Some(tilde)),
+            None => (c, None),
+        };
+        result.push(Unit {
+            base,
+            bar,
+            original: c.to_string(),
+            position,
+        });
+    }
+    result
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u')
+}
+
+/// The scribal default for the bar on `word[i]`: *m* before a labial or at
+/// the end of the word, *n* elsewhere.
+fn rule_nasal(word: &[Unit], i: usize) -> char {
+    match word.get(i + 1) {
+        Some(next) if !LABIALS.contains(&next.base.to_ascii_lowercase()) => 'n',
+        _ => 'm',
+    }
+}
+
+/// Expands nasal bars, deciding between *m*, *n*, and (optionally) a genuine
+/// long vowel with n-gram evidence.
+///
+/// ```
+/// use latincy_preprocess::nasal::NasalBars;
+///
+/// let nasal = NasalBars::default();
+/// assert_eq!(nasal.expand("quā cōsul"), "quam consul");
+/// ```
+#[derive(Debug, Clone)]
+pub struct NasalBars {
+    ngrams: Option<Arc<NgramData>>,
+    macrons: bool,
+    keep_length_marks: bool,
+    threshold: f64,
+}
+
+impl Default for NasalBars {
+    /// Macrons and tildes as nasal bars, scored with the bundled model.
+    fn default() -> Self {
+        NasalBars {
+            ngrams: None,
+            macrons: true,
+            keep_length_marks: false,
+            threshold: 1.0,
+        }
+    }
+}
+
+impl NasalBars {
+    /// Score readings with `ngrams` instead of the bundled model.
+    pub fn ngrams(mut self, ngrams: Arc<NgramData>) -> Self {
+        self.ngrams = Some(ngrams);
+        self
+    }
+
+    /// Whether macrons are nasal bars (default), or only tildes are.
+    pub fn macrons(mut self, macrons: bool) -> Self {
+        self.macrons = macrons;
+        self
+    }
+
+    /// Also consider reading a macron as vowel length and keeping it, for
+    /// texts that mix nasal bars with length marks. A letter is inserted only
+    /// if that reading is more probable by a factor of the threshold.
+    pub fn keep_length_marks(mut self, keep: bool) -> Self {
+        self.keep_length_marks = keep;
+        self
+    }
+
+    /// The probability ratio an inserted letter must beat a kept macron by
+    /// (default 1.0); see [`NasalBars::keep_length_marks`].
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    fn model(&self) -> Option<&NgramData> {
+        match &self.ngrams {
+            Some(ngrams) => Some(ngrams),
+            None => ngrams::bundled().ok(),
+        }
+    }
+
+    /// Expand the nasal bars in `text`.
+    pub fn expand(&self, text: &str) -> String {
+        self.expand_detailed(text).normalized
+    }
+
+    /// Like [`NasalBars::expand`], recording one [`ChangeRecord`] per
+    /// expanded bar.
+    pub fn expand_detailed(&self, text: &str) -> DetailedResult {
+        let chars: Vec<char> = text.chars().collect();
+        let units = units(text);
+        let model = self.model();
+        let mut normalized = String::with_capacity(text.len() + 8);
+        let mut changes = Vec::new();
+
+        let mut i = 0;
+        while i < units.len() {
+            if !units[i].base.is_alphabetic() {
+                normalized.push_str(&units[i].original);
+                i += 1;
+                continue;
+            }
+            let end = units[i..].iter().position(|u| !u.base.is_alphabetic()).map_or(units.len(), |p| i + p);
+            let word = &units[i..end];
+            let readings = self.readings(word, model);
+            for (unit, reading) in word.iter().zip(&readings) {
+                match reading {
+                    Some(nasal) => {
+                        let upper = unit.base.is_uppercase() && !word.iter().any(|u| u.base.is_lowercase());
+                        let nasal = if upper { nasal.to_ascii_uppercase() } else { *nasal };
+                        let expansion = format!("{}{}", unit.base, nasal);
+                        let len = unit.original.chars().count();
+                        changes.push(ChangeRecord {
+                            position: unit.position,
+                            original: unit.original.clone(),
+                            rule: format!("{} → {}", unit.original, expansion),
+                            normalized: expansion.clone(),
+                            pass: 1,
+                            context: changes::context(&chars, unit.position, len),
+                        });
+                        normalized.push_str(&expansion);
+                    }
+                    None => normalized.push_str(&unit.original),
+                }
+            }
+            i = end;
+        }
+
+        DetailedResult {
+            original: text.to_string(),
+            normalized,
+            changes,
+        }
+    }
+
+    /// The nasal to insert after each unit of `word`, or `None` to leave it.
+    fn readings(&self, word: &[Unit], model: Option<&NgramData>) -> Vec<Option<char>> {
+        let bars: Vec<usize> = (0..word.len())
+            .filter(|&i| word[i].bar.is_some_and(|tilde| tilde || self.macrons))
+            .collect();
+        let mut readings = vec![None; word.len()];
+        if bars.is_empty() || bars.len() > MAX_BARS {
+            return readings;
+        }
+
+        // Options per bar, the scribal rule first so it wins ties
+        let options: Vec<Vec<Option<char>>> = bars
+            .iter()
+            .map(|&i| {
+                let rule = rule_nasal(word, i);
+                let other = if rule == 'm' { 'n' } else { 'm' };
+                let mut options = vec![Some(rule), Some(other)];
+                if self.keep_length_marks && word[i].bar == Some(false) {
+                    options.push(None);
+                }
+                options
+            })
+            .collect();
+
+        let Some(model) = model else {
+            for (&i, options) in bars.iter().zip(&options) {
+                readings[i] = options[0];
+            }
+            return readings;
+        };
+
+        let spell = |choice: &[Option<char>]| -> String {
+            let mut spelled = String::new();
+            let mut bar = 0;
+            for (i, unit) in word.iter().enumerate() {
+                spelled.push(unit.base);
+                if bars.get(bar) == Some(&i) {
+                    if let Some(nasal) = choice[bar] {
+                        spelled.push(nasal);
+                    }
+                    bar += 1;
+                }
+            }
+            spelled
+        };
+
+        let mut best: Option<(f64, Vec<Option<char>>)> = None;
+        let mut best_kept: Option<(f64, Vec<Option<char>>)> = None;
+        let mut choice = vec![0usize; bars.len()];
+        loop {
+            let picked: Vec<Option<char>> = choice.iter().zip(&options).map(|(&c, options)| options[c]).collect();
+            let score = model.log_prob(&spell(&picked));
+            let slot = if picked.contains(&None) { &mut best_kept } else { &mut best };
+            if slot.as_ref().is_none_or(|(best, _)| score > *best) {
+                *slot = Some((score, picked));
+            }
+            // Next combination, odometer-style
+            let Some(bar) = (0..bars.len()).find(|&b| choice[b] + 1 < options[b].len()) else {
+                break;
+            };
+            choice[bar] += 1;
+            choice[..bar].iter_mut().for_each(|c| *c = 0);
+        }
+
+        let (score, mut picked) = best.expect("every bar has an m and an n reading");
+        if let Some((kept_score, kept)) = best_kept {
+            if self.threshold > 0.0 && score - kept_score <= self.threshold.ln() + 1e-9 {
+                picked = kept;
+            }
+        }
+        for (&i, nasal) in bars.iter().zip(picked) {
+            readings[i] = nasal;
+        }
+        readings
+    }
+}
+
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<ChangeRecord>,
+}
+
+/// Expand nasal bars with the default settings.
+pub fn expand(text: &str) -> String {
+    NasalBars::default().expand(text)
+}
+
+impl Normalizer for NasalBars {
+    fn name(&self) -> &str {
+        "nasal_bars"
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = self.expand_detailed(text);
+        NormalizeOutput::from_changes(result.original, result.normalized, result.changes)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+fn configured(
+    macrons: bool,
+    keep_length_marks: bool,
+    threshold: f64,
+    ngrams: Option<PyRef<'_, PyNgramData>>,
+) -> NasalBars {
+    let nasal = NasalBars::default().macrons(macrons).keep_length_marks(keep_length_marks).threshold(threshold);
+    match ngrams {
+        Some(ngrams) => nasal.ngrams(Arc::clone(&ngrams.inner)),
+        None => nasal,
+    }
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "expand_nasal_bars", signature = (text, macrons=true, keep_length_marks=false, threshold=1.0, ngrams=None))]
+pub fn py_expand_nasal_bars(
+    py: Python<'_>,
+    text: &str,
+    macrons: bool,
+    keep_length_marks: bool,
+    threshold: f64,
+    ngrams: Option<PyRef<'_, PyNgramData>>,
+) -> String {
+    let nasal = configured(macrons, keep_length_marks, threshold, ngrams);
+    crate::allow_threads(py, text, || nasal.expand(text))
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "expand_nasal_bars_detailed", signature = (text, macrons=true, keep_length_marks=false, threshold=1.0, ngrams=None))]
+pub fn py_expand_nasal_bars_detailed(
+    py: Python<'_>,
+    text: &str,
+    macrons: bool,
+    keep_length_marks: bool,
+    threshold: f64,
+    ngrams: Option<PyRef<'_, PyNgramData>>,
+) -> PyResult<changes::PyDetailedResult> {
+    let nasal = configured(macrons, keep_length_marks, threshold, ngrams);
+    let result = crate::allow_threads(py, text, || nasal.expand_detailed(text));
+    changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_expand() {
+        assert_eq!(expand("quā cōsul tēpus"), "quam consul tempus");
+        assert_eq!(expand("nō dicā"), "non dicam");
+        assert_eq!(expand("QUĀ"), "QUAM");
+        // Combining marks and tildes
+        assert_eq!(expand("qua\u{0304} co\u{0303}sul"), "quam consul");
+        assert_eq!(expand("arma"), "arma");
+        assert_eq!(expand(""), "");
+    }
+
+    #[test]
+    fn test_rule_fallback() {
+        let nasal = NasalBars::default().ngrams(Arc::new(NgramData::default()));
+        // Without evidence the scribal rule decides
+        assert_eq!(nasal.expand("cōpositus cōsul quā"), "compositus consul quam");
+    }
+
+    #[test]
+    fn test_tildes_only() {
+        let nasal = NasalBars::default().macrons(false);
+        assert_eq!(nasal.expand("quã rōsa"), "quam rōsa");
+    }
+
+    #[test]
+    fn test_keep_length_marks() {
+        let ngrams = Arc::new(crate::ngrams::train_ngrams(["rosa rosa rosa consul"]));
+        let nasal = NasalBars::default().ngrams(ngrams).keep_length_marks(true);
+        assert_eq!(nasal.expand("rosā cōsul"), "rosā consul");
+        // Tildes are always nasal bars
+        assert_eq!(nasal.expand("rosã"), "rosam");
+    }
+
+    #[test]
+    fn test_expand_detailed() {
+        let result = NasalBars::default().expand_detailed("ad quā");
+        assert_eq!(result.normalized, "ad quam");
+        assert_eq!(result.changes.len(), 1);
+        assert_eq!(result.changes[0].position, 5);
+        assert_eq!(result.changes[0].original, "ā");
+        assert_eq!(result.changes[0].normalized, "am");
+        assert_eq!(result.changes[0].rule, "ā → am");
+        assert_eq!(result.changes[0].context, " qu[ā]");
+    }
+}
@@ -0,0 +1,1609 @@
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::types::{PyBytes, PyDict};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Once, OnceLock};
+
+/// Character n-gram frequency tables (bigrams, trigrams, 4-grams).
+///
+/// Keys use `<` and `>` as word-boundary markers, e.g. `<fu` for a word
+/// starting with *fu*. An optional word-bigram table (keys `"prev next"`)
+/// supplies sentence context.
+#[derive(Debug, Clone, Default)]
+pub struct NgramData {
+    pub(crate) bigrams: GramTable,
+    pub(crate) trigrams: GramTable,
+    pub(crate) fourgrams: GramTable,
+    pub(crate) word_bigrams: HashMap<String, u64>,
+    metadata: ModelMetadata,
+    /// Totals used by the language model, computed on first use.
+    stats: OnceLock<LmStats>,
+}
+
+impl NgramData {
+    /// Build tables from in-memory maps. Keys longer than
+    /// [`Gram::MAX_LEN`] characters cannot be looked up and are dropped.
+    pub fn from_maps(
+        bigrams: HashMap<String, u64>,
+        trigrams: HashMap<String, u64>,
+        fourgrams: HashMap<String, u64>,
+    ) -> Self {
+        let table = |map: HashMap<String, u64>| gram_table(map.iter().map(|(key, &count)| (key.as_str(), count)));
+        Self::from_tables(table(bigrams), table(trigrams), table(fourgrams))
+    }
+
+    fn from_tables(bigrams: GramTable, trigrams: GramTable, fourgrams: GramTable) -> Self {
+        NgramData {
+            bigrams,
+            trigrams,
+            fourgrams,
+            word_bigrams: HashMap::new(),
+            metadata: ModelMetadata::default(),
+            stats: OnceLock::new(),
+        }
+    }
+
+    /// Attach a word-bigram table mapping `"prev next"` to count.
+    pub fn with_word_bigrams(mut self, word_bigrams: HashMap<String, u64>) -> Self {
+        self.word_bigrams = word_bigrams;
+        self
+    }
+
+    /// Parse tables from JSON objects mapping n-gram to count.
+    pub fn from_json(bigrams: &str, trigrams: &str, fourgrams: &str) -> io::Result<Self> {
+        Ok(Self::from_maps(
+            parse_ngram_json(bigrams, "bigrams")?,
+            parse_ngram_json(trigrams, "trigrams")?,
+            parse_ngram_json(fourgrams, "4grams")?,
+        ))
+    }
+
+    /// Attach descriptive metadata.
+    pub fn with_metadata(mut self, metadata: ModelMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Load `bigrams.json`, `trigrams.json`, and `4grams.json` from a
+    /// directory, plus `word_bigrams.json` and `metadata.json` if present.
+    pub fn from_dir(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        let mut data = Self::from_maps(
+            load_ngram_file(&dir.join("bigrams.json"))?,
+            load_ngram_file(&dir.join("trigrams.json"))?,
+            load_ngram_file(&dir.join("4grams.json"))?,
+        );
+        let word_bigrams = dir.join(WORD_BIGRAMS_FILE_NAME);
+        if word_bigrams.is_file() {
+            data = data.with_word_bigrams(load_ngram_file(&word_bigrams)?);
+        }
+        Ok(data.with_metadata(ModelMetadata::from_dir(dir)?))
+    }
+
+    pub fn metadata(&self) -> &ModelMetadata {
+        &self.metadata
+    }
+
+    /// Number of entries in each table.
+    pub fn table_sizes(&self) -> TableSizes {
+        TableSizes {
+            bigrams: self.bigrams.len(),
+            trigrams: self.trigrams.len(),
+            fourgrams: self.fourgrams.len(),
+            word_bigrams: self.word_bigrams.len(),
+        }
+    }
+
+    /// Number of tokens the tables were counted from (the sum of the
+    /// word-initial bigrams).
+    pub fn token_count(&self) -> u64 {
+        self.stats().tokens
+    }
+
+    /// Whether a word-bigram table is loaded.
+    pub fn has_word_bigrams(&self) -> bool {
+        !self.word_bigrams.is_empty()
+    }
+
+    /// How often `next` follows `prev` (both lowercase) in the word-bigram
+    /// table.
+    pub fn word_bigram_count(&self, prev: &str, next: &str) -> u64 {
+        if self.word_bigrams.is_empty() {
+            return 0;
+        }
+        self.word_bigrams.get(&format!("{} {}", prev, next)).copied().unwrap_or(0)
+    }
+}
+
+/// File name of the optional word-bigram table inside an n-gram directory.
+pub const WORD_BIGRAMS_FILE_NAME: &str = "word_bigrams.json";
+
+/// File name of the optional [`ModelMetadata`] inside an n-gram directory.
+pub const METADATA_FILE_NAME: &str = "metadata.json";
+
+/// Entry counts of the tables in an [`NgramData`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TableSizes {
+    pub bigrams: usize,
+    pub trigrams: usize,
+    pub fourgrams: usize,
+    pub word_bigrams: usize,
+}
+
+// ---------------------------------------------------------------------------
+// Keys
+// ---------------------------------------------------------------------------
+
+/// A character n-gram key such as `<fu`, packed into an integer so that
+/// building and hashing one allocates nothing.
+///
+/// Each character takes 21 bits, stored as its code point plus one so that a
+/// key's length is encoded too.
+///
+/// ```
+/// use latincy_preprocess::ngrams::Gram;
+///
+/// let gram = Gram::new("<fu").unwrap();
+/// assert_eq!(gram, Gram::from_chars(&['<', 'f', 'u']).unwrap());
+/// assert_eq!((gram.len(), gram.to_string()), (3, "<fu".to_string()));
+/// assert!(Gram::new("<fuit").is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Gram(u128);
+
+const GRAM_CHAR_BITS: u32 = 21;
+
+impl Gram {
+    /// The longest key that fits, in characters.
+    pub const MAX_LEN: usize = 4;
+
+    /// The key for `chars`, or `None` if there are more than
+    /// [`Gram::MAX_LEN`].
+    pub fn from_chars(chars: &[char]) -> Option<Self> {
+        if chars.len() > Self::MAX_LEN {
+            return None;
+        }
+        Some(Gram(chars.iter().fold(0, |packed, &c| packed << GRAM_CHAR_BITS | (c as u128 + 1))))
+    }
+
+    /// The key for `key`, or `None` if it is longer than [`Gram::MAX_LEN`]
+    /// characters.
+    pub fn new(key: &str) -> Option<Self> {
+        let mut packed = 0;
+        for (i, c) in key.chars().enumerate() {
+            if i == Self::MAX_LEN {
+                return None;
+            }
+            packed = packed << GRAM_CHAR_BITS | (c as u128 + 1);
+        }
+        Some(Gram(packed))
+    }
+
+    /// Number of characters.
+    pub fn len(self) -> usize {
+        (128 - self.0.leading_zeros()).div_ceil(GRAM_CHAR_BITS) as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The characters, first to last.
+    pub fn chars(self) -> impl Iterator<Item = char> {
+        let len = self.len() as u32;
+        (0..len).rev().map(move |i| {
+            let code = (self.0 >> (i * GRAM_CHAR_BITS)) as u32 & ((1 << GRAM_CHAR_BITS) - 1);
+            char::from_u32(code - 1).expect("packed from a char")
+        })
+    }
+
+    fn first(self) -> Option<char> {
+        self.chars().next()
+    }
+}
+
+impl fmt::Display for Gram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chars().try_for_each(|c| fmt::Write::write_char(f, c))
+    }
+}
+
+/// Hashes a [`Gram`] with one folded multiply instead of SipHash; the keys
+/// come from the model files, not from untrusted input.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct GramHasher(u64);
+
+const GRAM_HASH_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+const GRAM_HASH_MULTIPLIER: u64 = 0xff51_afd7_ed55_8ccd;
+
+impl Hasher for GramHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(GRAM_HASH_SEED).rotate_left(23);
+        }
+    }
+
+    fn write_u128(&mut self, n: u128) {
+        let (low, high) = (n as u64 ^ GRAM_HASH_SEED, (n >> 64) as u64 ^ self.0 ^ GRAM_HASH_MULTIPLIER);
+        let product = u128::from(low) * u128::from(high);
+        self.0 = product as u64 ^ (product >> 64) as u64;
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Counts of the character n-grams of one length.
+pub(crate) type GramTable = HashMap<Gram, u64, BuildHasherDefault<GramHasher>>;
+
+/// A table from string keys, dropping those too long to pack.
+fn gram_table<'a>(entries: impl IntoIterator<Item = (&'a str, u64)>) -> GramTable {
+    entries.into_iter().filter_map(|(key, count)| Some((Gram::new(key)?, count))).collect()
+}
+
+/// The entries of `table` with their keys as strings, sorted by key, for
+/// reproducible output.
+fn sorted_entries<K: fmt::Display, S>(table: &HashMap<K, u64, S>) -> Vec<(String, u64)> {
+    let mut entries: Vec<_> = table.iter().map(|(key, &count)| (key.to_string(), count)).collect();
+    entries.sort();
+    entries
+}
+
+// ---------------------------------------------------------------------------
+// Metadata
+// ---------------------------------------------------------------------------
+
+/// Description of a model, stored next to its tables as `metadata.json`.
+/// Every field is optional; unknown keys are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModelMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Corpus the counts were taken from.
+    pub source: Option<String>,
+    /// Number of tokens counted.
+    pub tokens: Option<u64>,
+    /// Build date, `YYYY-MM-DD`.
+    pub built: Option<String>,
+}
+
+impl ModelMetadata {
+    /// Parse a JSON object with any of the keys `name`, `description`,
+    /// `source`, `tokens`, and `built`.
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, format!("Failed to parse model metadata: {}", msg));
+        let value: serde_json::Value = serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+        let object = value.as_object().ok_or_else(|| invalid("expected a JSON object".to_string()))?;
+        let text = |key: &str| match object.get(key) {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(serde_json::Value::String(s)) => Ok(Some(s.clone())),
+            Some(_) => Err(invalid(format!("{} must be a string", key))),
+        };
+        let tokens = match object.get("tokens") {
+            None | Some(serde_json::Value::Null) => None,
+            Some(value) => Some(value.as_u64().ok_or_else(|| invalid("tokens must be a non-negative integer".to_string()))?),
+        };
+        Ok(ModelMetadata {
+            name: text("name")?,
+            description: text("description")?,
+            source: text("source")?,
+            tokens,
+            built: text("built")?,
+        })
+    }
+
+    /// Read `metadata.json` from `dir`; a missing file gives empty metadata.
+    pub fn from_dir(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let path = dir.as_ref().join(METADATA_FILE_NAME);
+        if !path.is_file() {
+            return Ok(ModelMetadata::default());
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            io::Error::new(e.kind(), format!("Failed to read metadata file {}: {}", path.display(), e))
+        })?;
+        Self::from_json(&content).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == ModelMetadata::default()
+    }
+
+    /// Pretty-printed JSON with the fields that are set.
+    pub fn to_json(&self) -> String {
+        let mut object = serde_json::Map::new();
+        for (key, value) in [
+            ("name", &self.name),
+            ("description", &self.description),
+            ("source", &self.source),
+            ("built", &self.built),
+        ] {
+            if let Some(value) = value {
+                object.insert(key.to_string(), value.as_str().into());
+            }
+        }
+        if let Some(tokens) = self.tokens {
+            object.insert("tokens".to_string(), tokens.into());
+        }
+        let mut json = serde_json::to_string_pretty(&object).unwrap_or_default();
+        json.push('\n');
+        json
+    }
+
+    /// Write `metadata.json` to `dir`.
+    pub fn write(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(dir.as_ref().join(METADATA_FILE_NAME), self.to_json())
+    }
+}
+
+impl NgramData {
+}
+
+// ---------------------------------------------------------------------------
+// Language model
+// ---------------------------------------------------------------------------
+
+/// Add-k smoothing constant for [`NgramData::log_prob`].
+pub const LM_SMOOTHING: f64 = 0.1;
+
+/// Add-k smoothing constant for [`NgramData::ngram_log_prob`]. Add-one keeps
+/// a single observation against none from counting as a 2:1 preference.
+pub const NGRAM_SMOOTHING: f64 = 1.0;
+
+#[derive(Debug, Clone)]
+struct LmStats {
+    /// Number of tokens counted (sum of word-initial bigrams).
+    tokens: u64,
+    /// Number of distinct characters, including the `>` end marker.
+    alphabet: usize,
+    /// Sum of counts in the bigram, trigram, and 4-gram tables.
+    totals: [u64; 3],
+}
+
+impl NgramData {
+    fn stats(&self) -> &LmStats {
+        self.stats.get_or_init(|| {
+            let mut tokens = 0;
+            let mut alphabet = std::collections::HashSet::new();
+            for (key, &count) in &self.bigrams {
+                if key.first() == Some('<') {
+                    tokens += count;
+                }
+                alphabet.extend(key.chars().filter(|&c| c != '<'));
+            }
+            LmStats {
+                tokens,
+                alphabet: alphabet.len().max(1),
+                totals: [
+                    self.bigrams.values().sum(),
+                    self.trigrams.values().sum(),
+                    self.fourgrams.values().sum(),
+                ],
+            }
+        })
+    }
+
+    /// The table holding n-grams of `n` characters (2, 3, or 4).
+    pub(crate) fn table(&self, n: usize) -> Option<&GramTable> {
+        match n {
+            2 => Some(&self.bigrams),
+            3 => Some(&self.trigrams),
+            4 => Some(&self.fourgrams),
+            _ => None,
+        }
+    }
+
+    /// Raw count of an n-gram key such as `<fu`, looked up in the table
+    /// matching its length; 0 for unseen keys.
+    pub fn frequency(&self, key: &str) -> u64 {
+        Gram::new(key).map_or(0, |gram| self.gram_frequency(gram))
+    }
+
+    /// Like [`Self::frequency`], for a packed key.
+    pub fn gram_frequency(&self, gram: Gram) -> u64 {
+        self.table(gram.len()).and_then(|table| table.get(&gram)).copied().unwrap_or(0)
+    }
+
+    /// Natural-log probability of an n-gram among all n-grams of its length,
+    /// with add-k smoothing ([`NGRAM_SMOOTHING`]). Keys of other lengths
+    /// score negative infinity.
+    pub fn ngram_log_prob(&self, key: &str) -> f64 {
+        Gram::new(key).map_or(f64::NEG_INFINITY, |gram| self.gram_log_prob(gram))
+    }
+
+    /// Like [`Self::ngram_log_prob`], for a packed key.
+    pub fn gram_log_prob(&self, gram: Gram) -> f64 {
+        let n = gram.len();
+        let Some(table) = self.table(n) else {
+            return f64::NEG_INFINITY;
+        };
+        let total = self.stats().totals[n - 2] as f64;
+        let count = table.get(&gram).copied().unwrap_or(0) as f64;
+        ((count + NGRAM_SMOOTHING) / (total + NGRAM_SMOOTHING * (table.len() + 1) as f64)).ln()
+    }
+
+    /// Natural-log probability of `word` under an add-k smoothed character
+    /// 4-gram model built from these tables.
+    ///
+    /// The word is lowercased and padded with `<` and `>`; each character is
+    /// conditioned on up to three preceding characters.
+    pub fn log_prob(&self, word: &str) -> f64 {
+        let padded: Vec<char> = std::iter::once('<').chain(word.to_lowercase().chars()).chain(std::iter::once('>')).collect();
+        let stats = self.stats();
+        let vocab = stats.alphabet as f64 * LM_SMOOTHING;
+
+        let mut total = 0.0;
+        for i in 1..padded.len() {
+            let start = i.saturating_sub(3);
+            let gram = Gram::from_chars(&padded[start..=i]).expect("at most four chars");
+            let history = Gram::from_chars(&padded[start..i]).expect("at most three chars");
+            let (count, history_count) = match gram.len() {
+                2 => (self.bigrams.get(&gram), Some(&stats.tokens)),
+                3 => (self.trigrams.get(&gram), self.bigrams.get(&history)),
+                _ => (self.fourgrams.get(&gram), self.trigrams.get(&history)),
+            };
+            let count = count.copied().unwrap_or(0) as f64;
+            let history_count = history_count.copied().unwrap_or(0) as f64;
+            total += ((count + LM_SMOOTHING) / (history_count + vocab)).ln();
+        }
+        total
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Training
+// ---------------------------------------------------------------------------
+
+/// Count bigrams, trigrams, and 4-grams over a corpus of clean Latin text.
+///
+/// Each text is lowercased and split on whitespace; punctuation characters
+/// become tokens of their own. Every token is padded with `<` and `>` before
+/// counting, matching the keys of the bundled model (`<fu`, `um>`, `<et>`).
+/// Adjacent alphabetic tokens within a text are also counted as word bigrams;
+/// punctuation and numbers break the sequence.
+pub fn train_ngrams<I, S>(corpus: I) -> NgramData
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut data = NgramData::default();
+    for text in corpus {
+        data.add_text(text.as_ref());
+    }
+    data
+}
+
+impl NgramData {
+    /// Add the n-gram counts of `text` to the tables (see [`train_ngrams`]).
+    pub fn add_text(&mut self, text: &str) {
+        let mut prev = None;
+        for token in text.split_whitespace() {
+            let lower = token.to_lowercase();
+            let mut start = 0;
+            for (i, c) in lower.char_indices() {
+                if c.is_alphanumeric() {
+                    continue;
+                }
+                self.add_token(&lower[start..i], &mut prev);
+                self.add_token(&lower[i..i + c.len_utf8()], &mut prev);
+                start = i + c.len_utf8();
+            }
+            self.add_token(&lower[start..], &mut prev);
+        }
+    }
+
+    /// Count one token, and the word bigram it forms with `prev`, the
+    /// preceding word (reset by anything that is not a word).
+    fn add_token(&mut self, token: &str, prev: &mut Option<String>) {
+        if token.is_empty() {
+            return;
+        }
+        if token.chars().all(char::is_alphabetic) {
+            if let Some(prev) = prev.as_deref() {
+                *self.word_bigrams.entry(format!("{} {}", prev, token)).or_insert(0) += 1;
+            }
+            *prev = Some(token.to_string());
+        } else {
+            *prev = None;
+        }
+        self.stats.take();
+        let padded: Vec<char> = std::iter::once('<').chain(token.chars()).chain(std::iter::once('>')).collect();
+        for (n, table) in [(2, &mut self.bigrams), (3, &mut self.trigrams), (4, &mut self.fourgrams)] {
+            for gram in padded.windows(n) {
+                *table.entry(Gram::from_chars(gram).expect("at most four chars")).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Add the counts of `other` to these tables.
+    pub fn merge(&mut self, other: NgramData) {
+        self.stats.take();
+        for (table, other) in [
+            (&mut self.bigrams, other.bigrams),
+            (&mut self.trigrams, other.trigrams),
+            (&mut self.fourgrams, other.fourgrams),
+        ] {
+            add_counts(table, other);
+        }
+        add_counts(&mut self.word_bigrams, other.word_bigrams);
+    }
+
+    /// Mix these tables with `other` (typically a small in-domain sample):
+    /// each n-gram's relative frequency becomes `(1 - weight)` times its
+    /// frequency here plus `weight` times its frequency in `other`, scaled
+    /// back to counts at this model's size. `weight` is clamped to `0..=1`;
+    /// 0 keeps this model, 1 keeps only `other`'s proportions. Metadata is
+    /// kept from `self`.
+    pub fn interpolate(&self, other: &NgramData, weight: f64) -> NgramData {
+        let weight = weight.clamp(0.0, 1.0);
+        let mut mixed = NgramData::from_tables(
+            interpolate_table(&self.bigrams, &other.bigrams, weight),
+            interpolate_table(&self.trigrams, &other.trigrams, weight),
+            interpolate_table(&self.fourgrams, &other.fourgrams, weight),
+        );
+        mixed.word_bigrams = interpolate_table(&self.word_bigrams, &other.word_bigrams, weight);
+        mixed.metadata = self.metadata.clone();
+        mixed
+    }
+
+    /// A smaller copy without the trigrams, 4-grams, and word bigrams
+    /// counted fewer than `min_count` times, e.g. for a model shipped to a
+    /// browser. The bigrams, which give the token count, are kept whole.
+    pub fn pruned(&self, min_count: u64) -> NgramData {
+        let mut pruned = NgramData::from_tables(
+            self.bigrams.clone(),
+            prune_table(&self.trigrams, min_count),
+            prune_table(&self.fourgrams, min_count),
+        );
+        pruned.word_bigrams = prune_table(&self.word_bigrams, min_count);
+        pruned.metadata = self.metadata.clone();
+        pruned
+    }
+
+    /// Write `bigrams.json`, `trigrams.json`, and `4grams.json` to `dir`,
+    /// plus `word_bigrams.json` and `metadata.json` when there are word
+    /// bigrams or metadata, in the layout read by [`NgramData::from_dir`].
+    pub fn write_json_dir(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        for (name, table) in [
+            ("bigrams.json", &self.bigrams),
+            ("trigrams.json", &self.trigrams),
+            ("4grams.json", &self.fourgrams),
+        ] {
+            std::fs::write(dir.join(name), table_to_json(table))?;
+        }
+        if self.has_word_bigrams() {
+            std::fs::write(dir.join(WORD_BIGRAMS_FILE_NAME), table_to_json(&self.word_bigrams))?;
+        }
+        if !self.metadata.is_empty() {
+            self.metadata.write(dir)?;
+        }
+        Ok(())
+    }
+}
+
+fn add_counts<K: Hash + Eq, S: std::hash::BuildHasher>(table: &mut HashMap<K, u64, S>, other: HashMap<K, u64, S>) {
+    for (key, count) in other {
+        *table.entry(key).or_insert(0) += count;
+    }
+}
+
+fn prune_table<K, S>(table: &HashMap<K, u64, S>, min_count: u64) -> HashMap<K, u64, S>
+where
+    K: Hash + Eq + Clone,
+    S: std::hash::BuildHasher + Default,
+{
+    table.iter().filter(|&(_, &count)| count >= min_count).map(|(k, &v)| (k.clone(), v)).collect()
+}
+
+/// Linear interpolation of two tables' relative frequencies, as counts at the
+/// size of `base` (or of `other` if `base` is empty). Entries rounding to zero
+/// are dropped.
+fn interpolate_table<K, S>(base: &HashMap<K, u64, S>, other: &HashMap<K, u64, S>, weight: f64) -> HashMap<K, u64, S>
+where
+    K: Hash + Eq + Clone,
+    S: std::hash::BuildHasher + Default,
+{
+    let base_total: u64 = base.values().sum();
+    let other_total: u64 = other.values().sum();
+    // An empty side has no proportions to contribute
+    let weight = match (base_total, other_total) {
+        (_, 0) => 0.0,
+        (0, _) => 1.0,
+        _ => weight,
+    };
+    let scale = if base_total > 0 { base_total } else { other_total } as f64;
+    let share = |table: &HashMap<K, u64, S>, total: u64, key: &K| {
+        if total == 0 {
+            0.0
+        } else {
+            table.get(key).copied().unwrap_or(0) as f64 / total as f64
+        }
+    };
+
+    base.keys()
+        .chain(other.keys().filter(|key| !base.contains_key(*key)))
+        .filter_map(|key| {
+            let p = (1.0 - weight) * share(base, base_total, key) + weight * share(other, other_total, key);
+            let count = (p * scale).round() as u64;
+            (count > 0).then(|| (key.clone(), count))
+        })
+        .collect()
+}
+
+/// Pretty-print a table most frequent first, like the bundled JSON files.
+fn table_to_json<K: fmt::Display, S>(table: &HashMap<K, u64, S>) -> String {
+    let mut entries = sorted_entries(table);
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let lines: Vec<String> = entries
+        .iter()
+        .map(|(key, count)| format!("  {}: {}", serde_json::Value::from(key.as_str()), count))
+        .collect();
+    format!("{{\n{}\n}}\n", lines.join(",\n"))
+}
+
+// ---------------------------------------------------------------------------
+// Binary format
+// ---------------------------------------------------------------------------
+//
+//   magic    b"LNGM"
+//   version  u8: 1, or 2 when a word-bigram table follows
+//   3 tables (bigrams, trigrams, 4-grams), then the word bigrams in
+//   version 2, each:
+//     count  u32 LE
+//     count entries of: key length u8, key bytes (UTF-8), count as LEB128
+//
+// Entries are written in sorted key order so the output is reproducible.
+// Models without word bigrams are written as version 1, which older
+// readers still accept.
+
+const BINARY_MAGIC: &[u8; 4] = b"LNGM";
+const BINARY_VERSION: u8 = 1;
+const BINARY_VERSION_WORD_BIGRAMS: u8 = 2;
+
+/// Conventional file name for the binary model inside an n-gram directory.
+pub const BINARY_FILE_NAME: &str = "ngrams.bin";
+
+impl NgramData {
+    /// Serialize the tables to the compact binary format read by
+    /// [`NgramData::from_bytes`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(BINARY_MAGIC);
+        let mut tables = vec![
+            sorted_entries(&self.bigrams),
+            sorted_entries(&self.trigrams),
+            sorted_entries(&self.fourgrams),
+        ];
+        if self.has_word_bigrams() {
+            out.push(BINARY_VERSION_WORD_BIGRAMS);
+            tables.push(sorted_entries(&self.word_bigrams));
+        } else {
+            out.push(BINARY_VERSION);
+        }
+        for mut entries in tables {
+            // Keys longer than 255 bytes cannot be stored and are skipped
+            entries.retain(|(key, _)| key.len() <= u8::MAX as usize);
+            out.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+            for (key, count) in entries {
+                out.push(key.len() as u8);
+                out.extend_from_slice(key.as_bytes());
+                write_varint(&mut out, count);
+            }
+        }
+        out
+    }
+
+    /// Parse tables from the binary format produced by [`NgramData::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = ByteReader { bytes, pos: 0 };
+        if reader.take(4)? != BINARY_MAGIC {
+            return Err(invalid_binary("bad magic"));
+        }
+        let version = reader.take(1)?[0];
+        if version != BINARY_VERSION && version != BINARY_VERSION_WORD_BIGRAMS {
+            return Err(invalid_binary(&format!("unsupported version {}", version)));
+        }
+        let bigrams = gram_table(reader.table()?);
+        let trigrams = gram_table(reader.table()?);
+        let fourgrams = gram_table(reader.table()?);
+        let mut data = Self::from_tables(bigrams, trigrams, fourgrams);
+        if version == BINARY_VERSION_WORD_BIGRAMS {
+            data.word_bigrams = reader.table()?.into_iter().map(|(key, count)| (key.to_string(), count)).collect();
+        }
+        if reader.pos != bytes.len() {
+            return Err(invalid_binary("trailing data"));
+        }
+        Ok(data)
+    }
+
+    /// Load a binary model file written by [`NgramData::write_binary`].
+    pub fn from_binary_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| {
+            io::Error::new(e.kind(), format!("Failed to read ngram file {}: {}", path.display(), e))
+        })?;
+        Self::from_bytes(&bytes).map_err(|e| {
+            io::Error::new(e.kind(), format!("Failed to parse ngram file {}: {}", path.display(), e))
+        })
+    }
+
+    /// Write the tables to `path` in the binary format.
+    pub fn write_binary(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn invalid_binary(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid binary ngram data: {}", msg))
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| invalid_binary("unexpected end of data"))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn varint(&mut self) -> io::Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid_binary("varint overflow"))
+    }
+
+    fn table(&mut self) -> io::Result<Vec<(&'a str, u64)>> {
+        let count = u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize;
+        let mut table = Vec::with_capacity(count.min(self.bytes.len()));
+        for _ in 0..count {
+            let len = self.take(1)?[0] as usize;
+            let key = std::str::from_utf8(self.take(len)?)
+                .map_err(|_| invalid_binary("key is not valid UTF-8"))?;
+            table.push((key, self.varint()?));
+        }
+        Ok(table)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// JSON format
+// ---------------------------------------------------------------------------
+
+fn parse_ngram_json(json: &str, name: &str) -> io::Result<HashMap<String, u64>> {
+    serde_json::from_str(json).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Failed to parse {} table: {}", name, e),
+        )
+    })
+}
+
+fn load_ngram_file(path: &Path) -> io::Result<HashMap<String, u64>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        io::Error::new(e.kind(), format!("Failed to read ngram file {}: {}", path.display(), e))
+    })?;
+    serde_json::from_str(&content).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Failed to parse ngram file {}: {}", path.display(), e),
+        )
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Bundled model: two loading paths depending on feature flags
+// ---------------------------------------------------------------------------
+
+/// The bundled n-gram model could not be loaded, so Pass 2 and the passes
+/// built on it cannot run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NgramsUnavailable {
+    message: String,
+}
+
+impl NgramsUnavailable {
+    /// Why loading failed, e.g. the missing file.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for NgramsUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "n-gram model unavailable: {}", self.message)
+    }
+}
+
+impl std::error::Error for NgramsUnavailable {}
+
+impl From<NgramsUnavailable> for io::Error {
+    fn from(err: NgramsUnavailable) -> Self {
+        io::Error::new(io::ErrorKind::NotFound, err)
+    }
+}
+
+#[cfg(feature = "pyo3-backend")]
+impl From<NgramsUnavailable> for PyErr {
+    fn from(err: NgramsUnavailable) -> Self {
+        pyo3::exceptions::PyRuntimeError::new_err(err.to_string())
+    }
+}
+
+/// The bundled model, loaded on first use.
+pub fn bundled() -> Result<&'static NgramData, NgramsUnavailable> {
+    bundled_model().as_deref().map_err(Clone::clone)
+}
+
+/// Load the bundled model and the totals its language model scores with
+/// now rather than on the first long-s call, so a service pays the cost at
+/// startup and learns there whether the model is usable.
+pub fn preload() -> Result<&'static NgramData, NgramsUnavailable> {
+    let data = bundled()?;
+    data.stats();
+    Ok(data)
+}
+
+/// Raw count of an n-gram key such as `<fu` in the bundled model.
+pub fn ngram_frequency(key: &str) -> Result<u64, NgramsUnavailable> {
+    Ok(bundled()?.frequency(key))
+}
+
+/// Report once per process that the bundled model is missing and long-s
+/// normalization is running Pass 1 only: as a Python `RuntimeWarning` in the
+/// extension module, on stderr otherwise.
+pub(crate) fn warn_unavailable(err: &NgramsUnavailable) {
+    static WARNED: Once = Once::new();
+    WARNED.call_once(|| {
+        let message = format!("{}; long-s normalization falls back to Pass 1 only", err);
+        #[cfg(feature = "pyo3-backend")]
+        Python::with_gil(|py| {
+            let category = py.get_type::<pyo3::exceptions::PyRuntimeWarning>();
+            let message = std::ffi::CString::new(message).unwrap_or_default();
+            // An error here means warnings are filtered into exceptions;
+            // there is no caller to raise it to
+            let _ = PyErr::warn(py, &category, &message, 1);
+        });
+        #[cfg(not(feature = "pyo3-backend"))]
+        eprintln!("warning: {}", message);
+    });
+}
+
+/// Loaded once, on first use. A failure is kept and returned on every call,
+/// and a panic while loading (say, on a corrupt file) becomes one too rather
+/// than poisoning the lock.
+static BUNDLED: OnceLock<Result<Arc<NgramData>, NgramsUnavailable>> = OnceLock::new();
+
+fn bundled_model() -> &'static Result<Arc<NgramData>, NgramsUnavailable> {
+    BUNDLED.get_or_init(|| load_unwinding(load_bundled))
+}
+
+fn load_unwinding<F>(load: F) -> Result<Arc<NgramData>, NgramsUnavailable>
+where
+    F: FnOnce() -> Result<Arc<NgramData>, NgramsUnavailable> + std::panic::UnwindSafe,
+{
+    std::panic::catch_unwind(load).unwrap_or_else(|_| {
+        Err(NgramsUnavailable {
+            message: "loading the ngram tables panicked".to_string(),
+        })
+    })
+}
+
+/// With `embed-data`, the binary model in `rust/data` is compiled in, so the
+/// crate needs no files at run time. `$LATINCY_PREPROCESS_EMBED_NGRAMS` at
+/// build time embeds another one instead (see `build.rs`).
+#[cfg(feature = "embed-data")]
+fn load_bundled() -> Result<Arc<NgramData>, NgramsUnavailable> {
+    let data = NgramData::from_bytes(include_bytes!(env!("LATINCY_EMBEDDED_NGRAMS"))).and_then(|data| {
+        let metadata = include_str!(env!("LATINCY_EMBEDDED_METADATA"));
+        Ok(data.with_metadata(ModelMetadata::from_json(metadata)?))
+    });
+    data.map(Arc::new).map_err(|e| NgramsUnavailable {
+        message: format!("embedded ngram tables are invalid: {}", e),
+    })
+}
+
+/// Without it, the model is loaded at run time from
+/// `$LATINCY_PREPROCESS_NGRAMS` or else the Python package's data
+/// directory, preferring the binary model over the JSON tables.
+#[cfg(not(feature = "embed-data"))]
+fn load_bundled() -> Result<Arc<NgramData>, NgramsUnavailable> {
+    NgramData::from_model_dir(find_ngram_dir())
+        .map(Arc::new)
+        .map_err(|e| NgramsUnavailable { message: e.to_string() })
+}
+
+#[cfg(not(feature = "embed-data"))]
+fn find_ngram_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("LATINCY_PREPROCESS_NGRAMS") {
+        return PathBuf::from(dir);
+    }
+
+    #[cfg(feature = "pyo3-backend")]
+    if let Some(dir) = package_data_dir() {
+        return dir.join("ngrams");
+    }
+    PathBuf::from("src/latincy_preprocess/long_s/data/ngrams")
+}
+
+/// The `long_s/data` directory of the installed Python package.
+#[cfg(feature = "pyo3-backend")]
+fn package_data_dir() -> Option<PathBuf> {
+    Python::with_gil(|py| {
+        let module = py.import("latincy_preprocess.long_s._rules").ok()?;
+        let file_attr = module.getattr("__file__").ok()?;
+        let file_str: String = file_attr.extract().ok()?;
+        let module_dir = PathBuf::from(file_str).parent()?.to_path_buf();
+        Some(module_dir.join("data"))
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Data sources
+// ---------------------------------------------------------------------------
+
+/// Where a normalizer's n-gram model comes from, given at construction (see
+/// `LongSNormalizerBuilder::data_source`) and reported by
+/// `LongSNormalizer::data_source`.
+#[derive(Debug, Clone, Default)]
+pub enum DataSource {
+    /// The bundled model, loaded once per process: embedded with the
+    /// `embed-data` feature, otherwise found at run time (see
+    /// [`DataSource::resolve`]).
+    #[default]
+    Bundled,
+    /// The model compiled into the crate.
+    #[cfg(feature = "embed-data")]
+    Embedded,
+    /// A model directory: `ngrams.bin` if present, otherwise the JSON tables.
+    Directory(PathBuf),
+    /// Tables built in memory, e.g. with [`NgramData::from_maps`].
+    InMemory(Arc<NgramData>),
+}
+
+impl DataSource {
+    /// The concrete source behind [`DataSource::Bundled`]: `Embedded` with
+    /// `embed-data`, otherwise the directory from
+    /// `$LATINCY_PREPROCESS_NGRAMS`, the Python package, or
+    /// `src/latincy_preprocess/long_s/data/ngrams`, in that order. Other
+    /// sources are returned as they are.
+    pub fn resolve(&self) -> DataSource {
+        match self {
+            #[cfg(feature = "embed-data")]
+            DataSource::Bundled => DataSource::Embedded,
+            #[cfg(not(feature = "embed-data"))]
+            DataSource::Bundled => DataSource::Directory(find_ngram_dir()),
+            other => other.clone(),
+        }
+    }
+
+    /// Load the model. The bundled model is shared and loaded only once;
+    /// a directory is read on every call.
+    pub fn load(&self) -> Result<Arc<NgramData>, NgramsUnavailable> {
+        match self {
+            DataSource::Bundled => bundled_model().clone(),
+            #[cfg(feature = "embed-data")]
+            DataSource::Embedded => bundled_model().clone(),
+            DataSource::Directory(dir) => NgramData::from_model_dir(dir).map(Arc::new).map_err(|e| NgramsUnavailable {
+                message: format!("{}: {}", dir.display(), e),
+            }),
+            DataSource::InMemory(data) => Ok(Arc::clone(data)),
+        }
+    }
+}
+
+impl fmt::Display for DataSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataSource::Bundled => write!(f, "bundled model ({})", self.resolve()),
+            #[cfg(feature = "embed-data")]
+            DataSource::Embedded => f.write_str("embedded"),
+            DataSource::Directory(dir) => write!(f, "{}", dir.display()),
+            DataSource::InMemory(_) => f.write_str("in memory"),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Named models
+// ---------------------------------------------------------------------------
+
+/// Name of the bundled model.
+pub const DEFAULT_MODEL: &str = "default";
+
+/// Environment variable listing extra model directories, separated like
+/// `PATH`.
+pub const MODELS_ENV: &str = "LATINCY_PREPROCESS_MODELS";
+
+impl NgramData {
+    /// Load a model directory: `ngrams.bin` if present, otherwise the JSON
+    /// tables, with `metadata.json` either way.
+    pub fn from_model_dir(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        let binary = dir.join(BINARY_FILE_NAME);
+        if binary.is_file() {
+            let data = Self::from_binary_file(binary)?;
+            return Ok(data.with_metadata(ModelMetadata::from_dir(dir)?));
+        }
+        Self::from_dir(dir)
+    }
+}
+
+/// Directories searched for named models, in order: those listed in
+/// [`MODELS_ENV`], the Python package's `long_s/data/models` (extension
+/// module only), and `$XDG_DATA_HOME/latincy-preprocess/models` (by default
+/// `~/.local/share/latincy-preprocess/models`).
+pub fn model_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(paths) = std::env::var_os(MODELS_ENV) {
+        dirs.extend(std::env::split_paths(&paths).filter(|dir| !dir.as_os_str().is_empty()));
+    }
+    #[cfg(feature = "pyo3-backend")]
+    dirs.extend(package_data_dir().map(|dir| dir.join("models")));
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")));
+    dirs.extend(data_home.map(|dir| dir.join("latincy-preprocess").join("models")));
+    dirs
+}
+
+fn is_model_dir(dir: &Path) -> bool {
+    dir.join(BINARY_FILE_NAME).is_file() || dir.join("bigrams.json").is_file()
+}
+
+/// Names of the models in `dirs`, sorted, [`DEFAULT_MODEL`] first.
+fn models_in(dirs: &[PathBuf]) -> Vec<String> {
+    let mut names = std::collections::BTreeSet::new();
+    for entry in dirs.iter().filter_map(|dir| std::fs::read_dir(dir).ok()).flatten().flatten() {
+        if is_model_dir(&entry.path()) {
+            if let Some(name) = entry.file_name().to_str() {
+                names.insert(name.to_string());
+            }
+        }
+    }
+    names.remove(DEFAULT_MODEL);
+    std::iter::once(DEFAULT_MODEL.to_string()).chain(names).collect()
+}
+
+/// Load the model called `name` from `dirs` (the first match wins);
+/// [`DEFAULT_MODEL`] is the bundled model.
+fn model_in(dirs: &[PathBuf], name: &str) -> io::Result<Arc<NgramData>> {
+    match model_source_in(dirs, name)? {
+        DataSource::Directory(dir) => Ok(Arc::new(NgramData::from_model_dir(dir)?)),
+        source => Ok(source.load()?),
+    }
+}
+
+fn model_source_in(dirs: &[PathBuf], name: &str) -> io::Result<DataSource> {
+    if name == DEFAULT_MODEL {
+        return Ok(DataSource::Bundled);
+    }
+    if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid model name {:?}", name)));
+    }
+    match dirs.iter().map(|dir| dir.join(name)).find(|dir| is_model_dir(dir)) {
+        Some(dir) => Ok(DataSource::Directory(dir)),
+        None => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("unknown n-gram model {:?}; available: {}", name, models_in(dirs).join(", ")),
+        )),
+    }
+}
+
+/// Names of the installed models: [`DEFAULT_MODEL`], then every directory
+/// under [`model_dirs`] holding a model, sorted.
+pub fn available_models() -> Vec<String> {
+    models_in(&model_dirs())
+}
+
+/// Load a model by name, e.g. `"neo-latin"` from
+/// `~/.local/share/latincy-preprocess/models/neo-latin/` (a directory as
+/// written by `train-ngrams`). [`DEFAULT_MODEL`] is the bundled model.
+pub fn model(name: &str) -> io::Result<Arc<NgramData>> {
+    model_in(&model_dirs(), name)
+}
+
+/// Where [`model`] would load `name` from, without loading it.
+pub fn model_source(name: &str) -> io::Result<DataSource> {
+    model_source_in(&model_dirs(), name)
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+/// Names of the installed n-gram models.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "available_models")]
+pub fn py_available_models() -> Vec<String> {
+    available_models()
+}
+
+/// Load the bundled model now; raises `RuntimeError` if it is unavailable.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "preload")]
+pub fn py_preload(py: Python<'_>) -> PyResult<()> {
+    py.allow_threads(preload)?;
+    Ok(())
+}
+
+/// Raw count of an n-gram key in the bundled model.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "ngram_frequency")]
+pub fn py_ngram_frequency(key: &str) -> PyResult<u64> {
+    Ok(ngram_frequency(key)?)
+}
+
+/// Python view of [`NgramData`], exposed as `_rust.NgramData`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "NgramData", frozen)]
+pub struct PyNgramData {
+    pub(crate) inner: Arc<NgramData>,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyNgramData {
+    #[staticmethod]
+    fn from_dir(dir: PathBuf) -> PyResult<Self> {
+        Ok(PyNgramData {
+            inner: Arc::new(NgramData::from_dir(dir)?),
+        })
+    }
+
+    #[staticmethod]
+    fn from_json(bigrams: &str, trigrams: &str, fourgrams: &str) -> PyResult<Self> {
+        Ok(PyNgramData {
+            inner: Arc::new(NgramData::from_json(bigrams, trigrams, fourgrams)?),
+        })
+    }
+
+    #[staticmethod]
+    fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        Ok(PyNgramData {
+            inner: Arc::new(NgramData::from_bytes(bytes)?),
+        })
+    }
+
+    #[staticmethod]
+    fn from_binary_file(path: PathBuf) -> PyResult<Self> {
+        Ok(PyNgramData {
+            inner: Arc::new(NgramData::from_binary_file(path)?),
+        })
+    }
+
+    #[staticmethod]
+    fn train(corpus: Vec<String>) -> Self {
+        PyNgramData {
+            inner: Arc::new(train_ngrams(corpus)),
+        }
+    }
+
+    fn log_prob(&self, word: &str) -> f64 {
+        self.inner.log_prob(word)
+    }
+
+    fn word_bigram_count(&self, prev: &str, next: &str) -> u64 {
+        self.inner.word_bigram_count(prev, next)
+    }
+
+    fn interpolate(&self, other: PyRef<'_, PyNgramData>, weight: f64) -> Self {
+        PyNgramData {
+            inner: Arc::new(self.inner.interpolate(&other.inner, weight)),
+        }
+    }
+
+    /// An installed model by name (see `available_models()`).
+    #[staticmethod]
+    fn from_model(name: &str) -> PyResult<Self> {
+        Ok(PyNgramData { inner: model(name)? })
+    }
+
+    /// The bundled model.
+    #[staticmethod]
+    fn bundled() -> PyResult<Self> {
+        Ok(PyNgramData {
+            inner: bundled_model().as_ref().map_err(Clone::clone)?.clone(),
+        })
+    }
+
+    fn frequency(&self, key: &str) -> u64 {
+        self.inner.frequency(key)
+    }
+
+    fn token_count(&self) -> u64 {
+        self.inner.token_count()
+    }
+
+    /// Entry counts as a dict keyed by table name.
+    fn table_sizes(&self) -> HashMap<&'static str, usize> {
+        let sizes = self.inner.table_sizes();
+        HashMap::from([
+            ("bigrams", sizes.bigrams),
+            ("trigrams", sizes.trigrams),
+            ("4grams", sizes.fourgrams),
+            ("word_bigrams", sizes.word_bigrams),
+        ])
+    }
+
+    /// Metadata as a dict; unset fields are `None`.
+    fn metadata<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let metadata = self.inner.metadata();
+        let dict = PyDict::new(py);
+        dict.set_item("name", &metadata.name)?;
+        dict.set_item("description", &metadata.description)?;
+        dict.set_item("source", &metadata.source)?;
+        dict.set_item("tokens", metadata.tokens)?;
+        dict.set_item("built", &metadata.built)?;
+        Ok(dict)
+    }
+
+    fn write_json_dir(&self, dir: PathBuf) -> PyResult<()> {
+        Ok(self.inner.write_json_dir(dir)?)
+    }
+
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.inner.to_bytes())
+    }
+
+    fn write_binary(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.inner.write_binary(path)?)
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (bigrams, trigrams, fourgrams, word_bigrams=None))]
+    fn from_maps(
+        bigrams: HashMap<String, u64>,
+        trigrams: HashMap<String, u64>,
+        fourgrams: HashMap<String, u64>,
+        word_bigrams: Option<HashMap<String, u64>>,
+    ) -> Self {
+        let data = NgramData::from_maps(bigrams, trigrams, fourgrams);
+        PyNgramData {
+            inner: Arc::new(data.with_word_bigrams(word_bigrams.unwrap_or_default())),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundled_dir() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../src/latincy_preprocess/long_s/data/ngrams")
+    }
+
+    impl NgramData {
+        #[cfg(feature = "embed-data")]
+        fn write_binary_dir_for_test(&self, dir: &Path) {
+            std::fs::create_dir_all(dir).unwrap();
+            self.write_binary(dir.join(BINARY_FILE_NAME)).unwrap();
+        }
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_from_dir_matches_bundled() {
+        let data = NgramData::from_dir(bundled_dir()).unwrap();
+        let bundled = bundled().unwrap();
+        assert_eq!(data.frequency("<su"), bundled.frequency("<su"));
+        assert_eq!(data.fourgrams.len(), bundled.fourgrams.len());
+    }
+
+    #[test]
+    fn test_from_json() {
+        let data = NgramData::from_json("{}", r#"{"<fu": 3, "<su": 7}"#, "{}").unwrap();
+        assert_eq!(data.frequency("<su"), 7);
+        assert!(data.fourgrams.is_empty());
+    }
+
+    #[test]
+    fn test_from_json_invalid() {
+        let err = NgramData::from_json("{}", "[1, 2]", "{}").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("trigrams"));
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let data = NgramData::from_dir(bundled_dir()).unwrap();
+        let decoded = NgramData::from_bytes(&data.to_bytes()).unwrap();
+        assert_eq!(decoded.bigrams, data.bigrams);
+        assert_eq!(decoded.trigrams, data.trigrams);
+        assert_eq!(decoded.fourgrams, data.fourgrams);
+    }
+
+    #[test]
+    fn test_bundled_binary_up_to_date() {
+        // Regenerate with: cargo run --example convert_ngrams
+        let json = NgramData::from_dir(bundled_dir()).unwrap();
+        let binary = std::fs::read(bundled_dir().join(BINARY_FILE_NAME)).unwrap();
+        assert!(binary == json.to_bytes(), "ngrams.bin is stale");
+        let embedded = Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        assert!(std::fs::read(embedded.join(BINARY_FILE_NAME)).unwrap() == binary, "data/ngrams.bin is stale");
+        let metadata = std::fs::read(bundled_dir().join(METADATA_FILE_NAME)).unwrap();
+        assert!(std::fs::read(embedded.join(METADATA_FILE_NAME)).unwrap() == metadata, "data/metadata.json is stale");
+    }
+
+    #[test]
+    fn test_from_bytes_invalid() {
+        let bytes = NgramData::from_json("{}", r#"{"<fu": 300}"#, "{}").unwrap().to_bytes();
+        assert!(NgramData::from_bytes(b"JSON").is_err());
+        assert!(NgramData::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut future = bytes.clone();
+        future[4] = 3;
+        let err = NgramData::from_bytes(&future).unwrap_err();
+        assert!(err.to_string().contains("unsupported version 3"));
+    }
+
+    #[test]
+    fn test_log_prob_prefers_attested_forms() {
+        let data = NgramData::from_dir(bundled_dir()).unwrap();
+        assert!(data.log_prob("sunt") > data.log_prob("funt"));
+        assert!(data.log_prob("causa") > data.log_prob("caufa"));
+        assert!(data.log_prob("fuit") > data.log_prob("suit"));
+        assert_eq!(data.log_prob("Sunt"), data.log_prob("sunt"));
+        assert!(data.log_prob("sunt") < 0.0);
+    }
+
+    #[test]
+    fn test_ngram_log_prob() {
+        let data = NgramData::from_json("{}", r#"{"<fu": 0, "<su": 3}"#, "{}").unwrap();
+        // (3 + 1) / (0 + 1) against the unseen key
+        let diff = data.ngram_log_prob("<su") - data.ngram_log_prob("<fu");
+        assert!((diff - 4.0f64.ln()).abs() < 1e-9);
+        assert_eq!(data.ngram_log_prob("<zz"), data.ngram_log_prob("<fu"));
+        assert_eq!(data.ngram_log_prob("<"), f64::NEG_INFINITY);
+        assert_eq!(data.frequency("<su"), 3);
+    }
+
+    #[test]
+    fn test_log_prob_updates_after_training() {
+        let mut data = train_ngrams(["sunt"]);
+        let before = data.log_prob("funt");
+        data.add_text("funt funt funt");
+        assert!(data.log_prob("funt") > before);
+    }
+
+    #[test]
+    fn test_train_ngrams() {
+        let data = train_ngrams(["Et funt.", "et"]);
+        assert_eq!(data.frequency("<et"), 2);
+        assert_eq!(data.frequency("<et>"), 2);
+        assert_eq!(data.frequency("<fu"), 1);
+        assert_eq!(data.frequency("t>"), 3);
+        // Punctuation is split off and padded as its own token
+        assert_eq!(data.frequency("<.>"), 1);
+        assert_eq!(data.frequency("unt."), 0);
+    }
+
+    #[test]
+    fn test_train_word_bigrams() {
+        let data = train_ngrams(["Si quis, fi quis", "quis si"]);
+        assert_eq!(data.word_bigram_count("si", "quis"), 1);
+        assert_eq!(data.word_bigram_count("fi", "quis"), 1);
+        // Punctuation and line boundaries break the sequence
+        assert_eq!(data.word_bigram_count("quis", "fi"), 0);
+        assert_eq!(data.word_bigram_count("quis", "si"), 1);
+        assert_eq!(data.word_bigram_count("si", "fi"), 0);
+    }
+
+    #[test]
+    fn test_word_bigrams_round_trip() {
+        let data = train_ngrams(["si quis"]);
+        let bytes = data.to_bytes();
+        assert_eq!(bytes[4], 2);
+        assert_eq!(NgramData::from_bytes(&bytes).unwrap().word_bigram_count("si", "quis"), 1);
+        // Without word bigrams the version 1 layout is kept
+        assert_eq!(train_ngrams(["si"]).to_bytes()[4], 1);
+
+        let dir = std::env::temp_dir().join(format!("ngrams_word_bigrams_{}", std::process::id()));
+        data.write_json_dir(&dir).unwrap();
+        let loaded = NgramData::from_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.word_bigrams, data.word_bigrams);
+        assert!(!NgramData::from_dir(bundled_dir()).unwrap().has_word_bigrams());
+    }
+
+    #[test]
+    fn test_merge_and_json_round_trip() {
+        let mut data = train_ngrams(["sunt"]);
+        data.merge(train_ngrams(["sunt fuga"]));
+        assert_eq!(data.frequency("<su"), 2);
+
+        let dir = std::env::temp_dir().join(format!("ngrams_train_{}", std::process::id()));
+        data.write_json_dir(&dir).unwrap();
+        let loaded = NgramData::from_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.bigrams, data.bigrams);
+        assert_eq!(loaded.trigrams, data.trigrams);
+        assert_eq!(loaded.fourgrams, data.fourgrams);
+    }
+
+    #[test]
+    fn test_ngrams_unavailable() {
+        let err = NgramsUnavailable {
+            message: "Failed to read ngram file data/bigrams.json".to_string(),
+        };
+        assert_eq!(err.to_string(), "n-gram model unavailable: Failed to read ngram file data/bigrams.json");
+        let io_err: io::Error = err.into();
+        assert_eq!(io_err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_query_api() {
+        let data = bundled().unwrap();
+        assert_eq!(ngram_frequency("<fu").unwrap(), data.frequency("<fu"));
+        assert_eq!(data.frequency("<fu"), 3135);
+        assert_eq!(data.frequency("<zzz"), 0);
+        assert_eq!(data.frequency(""), 0);
+        let sizes = data.table_sizes();
+        assert_eq!(sizes.bigrams, 1308);
+        assert_eq!(sizes.word_bigrams, 0);
+        assert_eq!(data.token_count(), 842177);
+        assert_eq!(data.metadata().name.as_deref(), Some("default"));
+        assert_eq!(data.metadata().tokens, Some(data.token_count()));
+        assert_eq!(NgramData::from_dir(bundled_dir()).unwrap().metadata(), data.metadata());
+    }
+
+    #[test]
+    fn test_metadata_json() {
+        let metadata = ModelMetadata::from_json(r#"{"source": "corpus/", "tokens": 12, "built": "2026-01-02", "extra": 1}"#).unwrap();
+        assert_eq!(metadata.source.as_deref(), Some("corpus/"));
+        assert_eq!(metadata.tokens, Some(12));
+        assert_eq!(metadata.name, None);
+        assert_eq!(ModelMetadata::from_json(&metadata.to_json()).unwrap(), metadata);
+        assert!(ModelMetadata::from_json("{}").unwrap().is_empty());
+        assert!(ModelMetadata::from_json(r#"{"tokens": "many"}"#).is_err());
+        assert!(ModelMetadata::from_json("[]").is_err());
+
+        let data = train_ngrams(["sunt"]).with_metadata(metadata.clone());
+        let dir = std::env::temp_dir().join(format!("ngrams_metadata_{}", std::process::id()));
+        data.write_json_dir(&dir).unwrap();
+        let loaded = NgramData::from_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.metadata(), &metadata);
+    }
+
+    #[test]
+    fn test_pruned() {
+        let data = NgramData::from_json(r#"{"<f": 1}"#, r#"{"<fu": 30, "<xy": 1}"#, r#"{"<fug": 2}"#).unwrap();
+        let pruned = data.pruned(2);
+        assert_eq!((pruned.frequency("<fu"), pruned.frequency("<xy")), (30, 0));
+        assert_eq!((pruned.frequency("<fug"), pruned.frequency("<f")), (2, 1));
+        assert_eq!(data.pruned(0).table_sizes(), data.table_sizes());
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let base = NgramData::from_json("{}", r#"{"<fu": 30, "<su": 70}"#, "{}").unwrap();
+        let domain = NgramData::from_json("{}", r#"{"<fu": 1, "<xy": 1}"#, "{}").unwrap();
+
+        let mixed = base.interpolate(&domain, 0.5);
+        // (0.5 * 0.3 + 0.5 * 0.5) * 100, (0.5 * 0.7) * 100, (0.5 * 0.5) * 100
+        assert_eq!(mixed.frequency("<fu"), 40);
+        assert_eq!(mixed.frequency("<su"), 35);
+        assert_eq!(mixed.frequency("<xy"), 25);
+
+        assert_eq!(base.interpolate(&domain, 0.0).trigrams, base.trigrams);
+        assert_eq!(base.interpolate(&domain, -1.0).trigrams, base.trigrams);
+        assert_eq!(base.interpolate(&domain, 1.0).frequency("<su"), 0);
+        // Tables empty on one side are taken from the other
+        assert_eq!(NgramData::default().interpolate(&domain, 0.1).frequency("<xy"), 1);
+        assert_eq!(base.interpolate(&NgramData::default(), 0.9).trigrams, base.trigrams);
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_named_models() {
+        let root = std::env::temp_dir().join(format!("ngrams_models_{}", std::process::id()));
+        let (first, second) = (root.join("a"), root.join("b"));
+        train_ngrams(["sunt"]).write_json_dir(first.join("medieval")).unwrap();
+        train_ngrams(["fuit"]).write_binary_dir_for_test(&second.join("medieval"));
+        train_ngrams(["est"]).write_binary_dir_for_test(&second.join("neo-latin"));
+        std::fs::create_dir_all(second.join("empty")).unwrap();
+        let dirs = vec![first, second, root.join("missing")];
+
+        assert_eq!(models_in(&dirs), vec!["default", "medieval", "neo-latin"]);
+        // The first directory wins
+        assert_eq!(model_in(&dirs, "medieval").unwrap().frequency("<su"), 1);
+        assert_eq!(model_in(&dirs, "neo-latin").unwrap().frequency("<es"), 1);
+        assert!(Arc::ptr_eq(&model_in(&dirs, DEFAULT_MODEL).unwrap(), bundled_model().as_ref().unwrap()));
+
+        let err = model_in(&dirs, "classical").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("available: default, medieval, neo-latin"));
+        assert_eq!(model_in(&dirs, "../a").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        let source = model_source_in(&dirs, "neo-latin").unwrap();
+        assert!(matches!(source, DataSource::Directory(dir) if dir == root.join("b/neo-latin")));
+        assert!(matches!(model_source_in(&dirs, DEFAULT_MODEL).unwrap(), DataSource::Bundled));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_gram() {
+        for key in ["", "<", "<fu", "æð>", "<ſi\u{10ffff}"] {
+            let gram = Gram::new(key).unwrap();
+            assert_eq!(gram.to_string(), key);
+            assert_eq!(gram.len(), key.chars().count());
+        }
+        assert_ne!(Gram::new("a"), Gram::new("\0a"));
+        assert_eq!(Gram::from_chars(&['u'; 5]), None);
+
+        let maps = |key: &str| HashMap::from([(key.to_string(), 3)]);
+        let data = NgramData::from_maps(maps("<f"), maps("<fu"), maps("<fuit"));
+        assert_eq!((data.frequency("<fu"), data.gram_frequency(Gram::new("<f").unwrap())), (3, 3));
+        assert!(data.fourgrams.is_empty());
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_preload() {
+        assert!(std::ptr::eq(preload().unwrap(), bundled().unwrap()));
+        assert!(bundled().unwrap().stats.get().is_some());
+        let err = load_unwinding(|| panic!("corrupt")).unwrap_err();
+        assert!(err.message().contains("panicked"));
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_data_source() {
+        let bundled = DataSource::default();
+        assert!(Arc::ptr_eq(&bundled.load().unwrap(), bundled_model().as_ref().unwrap()));
+        assert!(bundled.to_string().starts_with("bundled model ("));
+        #[cfg(feature = "embed-data")]
+        assert!(matches!(bundled.resolve(), DataSource::Embedded));
+
+        let dir = DataSource::Directory(bundled_dir());
+        assert_eq!(dir.load().unwrap().frequency("<fu"), bundled.load().unwrap().frequency("<fu"));
+        assert_eq!(dir.to_string(), bundled_dir().display().to_string());
+        let err = DataSource::Directory(bundled_dir().join("missing")).load().unwrap_err();
+        assert!(err.message().contains("missing"));
+
+        let data = Arc::new(train_ngrams(["sunt"]));
+        let memory = DataSource::InMemory(Arc::clone(&data));
+        assert!(Arc::ptr_eq(&memory.load().unwrap(), &data));
+        assert_eq!(memory.to_string(), "in memory");
+    }
+
+    #[test]
+    fn test_from_dir_missing() {
+        let err = NgramData::from_dir(bundled_dir().join("missing")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("bigrams.json"));
+    }
+}
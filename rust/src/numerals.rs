@@ -0,0 +1,357 @@
+//! Roman numerals: finding them in running text, checking that they are
+//! well formed, and reading their value.
+//!
+//! A numeral is a whole word written in numeral signs: the letters *I V X L
+//! C D M* in either case, with a final *j* after *i* (*viij*), the archaic
+//! thousands `ↀ` `ↁ` `ↂ` `ↇ` `ↈ` and their apostrophic spellings (*CIↃ*,
+//! *IↃↃ*), or the Unicode numeral signs (`Ⅻ`). A combining overline
+//! (U+0305, the vinculum) multiplies the sign under it by a thousand. The
+//! u/v and i/j normalizers leave what [`find_roman_numerals`] finds alone,
+//! so *XVI* does not become *XUI*.
+//!
+//! Some numeral-shaped words are ordinary Latin. Mixed case is never a
+//! numeral (*Di*, *Vi*), nor are the lowercase words in [`COMMON_WORDS`],
+//! nor a capital *C*, *D*, *L*, or *M* with a full stop before a
+//! capitalized word: the abbreviated praenomina of *M. Tullius*.
+
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::types::{PyDict, PyList};
+
+/// The combining overline that marks thousands (*X̅* = 10,000).
+pub const VINCULUM: char = '\u{0305}';
+
+/// Lowercase words that are well-formed numerals but far more often Latin:
+/// *i* (go), *vi* (by force), *mi* (my), *di* and *dii* (gods).
+pub const COMMON_WORDS: &[&str] = &["i", "vi", "mi", "di", "dii"];
+
+/// A numeral found in text, by char offsets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    /// One past the last char, including any vinculum.
+    pub end: usize,
+    pub value: u64,
+}
+
+/// The values a sign stands for, left to right (`Ⅻ` is three signs).
+fn sign_values(c: char) -> Option<&'static [u64]> {
+    Some(match c {
+        'I' | 'i' | 'Ⅰ' | 'ⅰ' => &[1],
+        'V' | 'v' | 'Ⅴ' | 'ⅴ' => &[5],
+        'X' | 'x' | 'Ⅹ' | 'ⅹ' => &[10],
+        'L' | 'l' | 'Ⅼ' | 'ⅼ' => &[50],
+        'C' | 'c' | 'Ⅽ' | 'ⅽ' => &[100],
+        'D' | 'd' | 'Ⅾ' | 'ⅾ' => &[500],
+        'M' | 'm' | 'Ⅿ' | 'ⅿ' | 'ↀ' => &[1000],
+        'ↁ' => &[5000],
+        'ↂ' => &[10000],
+        'ↇ' => &[50000],
+        'ↈ' => &[100000],
+        'Ⅱ' | 'ⅱ' => &[1, 1],
+        'Ⅲ' | 'ⅲ' => &[1, 1, 1],
+        'Ⅳ' | 'ⅳ' => &[1, 5],
+        'Ⅵ' | 'ⅵ' => &[5, 1],
+        'Ⅶ' | 'ⅶ' => &[5, 1, 1],
+        'Ⅷ' | 'ⅷ' => &[5, 1, 1, 1],
+        'Ⅸ' | 'ⅸ' => &[1, 10],
+        'Ⅺ' | 'ⅺ' => &[10, 1],
+        'Ⅻ' | 'ⅻ' => &[10, 1, 1],
+        _ => return None,
+    })
+}
+
+/// An apostrophic sign at the start of `chars` as `(value, length)`: *CIↃ*
+/// (1,000) and *CCIↃↃ* (10,000), *IↃ* (500) and *IↃↃ* (5,000).
+fn apostrophic(chars: &[char]) -> Option<(u64, usize)> {
+    let cs = chars.iter().take_while(|c| c.eq_ignore_ascii_case(&'c')).count();
+    if cs > 3 || !chars.get(cs)?.eq_ignore_ascii_case(&'i') {
+        return None;
+    }
+    let reversed = chars[cs + 1..].iter().take_while(|&&c| matches!(c, 'Ↄ' | 'ↄ')).count();
+    if cs == 0 {
+        (1..=3).contains(&reversed).then(|| (5 * 10u64.pow(reversed as u32 + 1), 1 + reversed))
+    } else {
+        (reversed >= cs).then(|| (10u64.pow(cs as u32 + 2), 2 * cs + 1))
+    }
+}
+
+/// The sign values of a word, or `None` if it has a non-numeral char.
+fn values(chars: &[char]) -> Option<Vec<u64>> {
+    let mut values = Vec::with_capacity(chars.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some((value, len)) = apostrophic(&chars[i..]) {
+            values.push(value);
+            i += len;
+            continue;
+        }
+        let signs = match chars[i] {
+            // The final i of a run, written long: viij, ij
+            'j' | 'J' if i + 1 == chars.len() && i > 0 && chars[i - 1].eq_ignore_ascii_case(&'i') => &[1],
+            c => sign_values(c)?,
+        };
+        i += 1;
+        let scale = if chars.get(i) == Some(&VINCULUM) {
+            i += 1;
+            1000
+        } else {
+            1
+        };
+        values.extend(signs.iter().map(|v| v * scale));
+    }
+    Some(values)
+}
+
+/// Read sign values as a well-formed numeral. From the largest order down,
+/// each decimal digit is written in that order's signs as *I*, *II*,
+/// *III*, *IV* or *IIII*, *V*, *VI*, *VII*, *VIII*, *IX* or *VIIII*; the
+/// thousands and above may also repeat their sign freely (*MMMMM*).
+fn read(values: &[u64]) -> Option<u64> {
+    let max = *values.iter().max()?;
+    let mut unit = 1;
+    while unit * 10 <= max {
+        unit *= 10;
+    }
+    let mut pos = 0;
+    let mut total = 0;
+    loop {
+        let (one, five, ten) = (unit, 5 * unit, 10 * unit);
+        let rest = &values[pos..];
+        if rest.starts_with(&[one, ten]) {
+            total += 9 * unit;
+            pos += 2;
+        } else if rest.starts_with(&[one, five]) {
+            total += 4 * unit;
+            pos += 2;
+        } else {
+            let fives = usize::from(rest.first() == Some(&five));
+            let limit = if fives == 0 && unit >= 1000 { usize::MAX } else { 4 };
+            let ones = rest[fives..].iter().take_while(|&&v| v == one).count().min(limit);
+            total += (5 * fives + ones) as u64 * unit;
+            pos += fives + ones;
+        }
+        if unit == 1 {
+            break;
+        }
+        unit /= 10;
+    }
+    (pos == values.len()).then_some(total)
+}
+
+/// The value of `numeral` if it is one well-formed Roman numeral.
+///
+/// ```
+/// use latincy_preprocess::numerals::parse;
+///
+/// assert_eq!(parse("MDCXV"), Some(1615));
+/// assert_eq!(parse("viij"), Some(8));
+/// assert_eq!(parse("X\u{0305}V"), Some(10005));
+/// assert_eq!(parse("IIX"), None);
+/// ```
+pub fn parse(numeral: &str) -> Option<u64> {
+    let chars: Vec<char> = numeral.chars().collect();
+    read(&values(&chars)?)
+}
+
+/// Whether `numeral` is one well-formed Roman numeral.
+pub fn is_well_formed(numeral: &str) -> bool {
+    parse(numeral).is_some()
+}
+
+/// Whether `c` is part of a word: a letter, or a mark extending one (the
+/// vinculum of X̅).
+pub(crate) fn is_word_char(c: char) -> bool {
+    c.is_alphabetic() || crate::graphemes::is_extender(c)
+}
+
+/// A single praenomen letter: *M.* before a capitalized word.
+fn is_praenomen(chars: &[char], start: usize, end: usize) -> bool {
+    if end != start + 1 || !matches!(chars[start], 'C' | 'D' | 'L' | 'M') || chars.get(end) != Some(&'.') {
+        return false;
+    }
+    let next = chars[end + 1..].iter().position(|c| !c.is_whitespace()).map(|p| end + 1 + p);
+    next.is_some_and(|n| chars[n].is_uppercase() && chars.get(n + 1).is_some_and(|c| c.is_lowercase()))
+}
+
+/// The value of the word `chars[start..end]` if it is read as a numeral.
+fn word_value(chars: &[char], start: usize, end: usize) -> Option<u64> {
+    let word = &chars[start..end];
+    // Most words have a letter no numeral uses; rule them out cheaply
+    let signs = |c: char| sign_values(c).is_some() || matches!(c, 'Ↄ' | 'ↄ' | 'j' | 'J' | VINCULUM);
+    if !word.iter().all(|&c| signs(c)) {
+        return None;
+    }
+    let upper = word.iter().any(|c| c.is_uppercase());
+    let lower = word.iter().any(|c| c.is_lowercase());
+    if (upper && lower) || is_praenomen(chars, start, end) {
+        return None;
+    }
+    if lower && COMMON_WORDS.iter().any(|common| common.chars().eq(word.iter().copied())) {
+        return None;
+    }
+    read(&values(word)?)
+}
+
+/// Whether `chars[idx]` is part of a numeral.
+pub(crate) fn in_numeral(chars: &[char], idx: usize) -> bool {
+    let start = chars[..idx].iter().rposition(|&c| !is_word_char(c)).map_or(0, |p| p + 1);
+    let end = chars[idx..].iter().position(|&c| !is_word_char(c)).map_or(chars.len(), |p| idx + p);
+    start < end && word_value(chars, start, end).is_some()
+}
+
+/// Find the Roman numerals in `text`.
+///
+/// ```
+/// use latincy_preprocess::numerals::{find_roman_numerals, Span};
+///
+/// assert_eq!(
+///     find_roman_numerals("liber XVI, cap. iv"),
+///     vec![Span { start: 6, end: 9, value: 16 }, Span { start: 16, end: 18, value: 4 }]
+/// );
+/// ```
+pub fn find_roman_numerals(text: &str) -> Vec<Span> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if !is_word_char(chars[i]) {
+            i += 1;
+            continue;
+        }
+        let end = chars[i..].iter().position(|&c| !is_word_char(c)).map_or(chars.len(), |p| i + p);
+        if let Some(value) = word_value(&chars, i, end) {
+            spans.push(Span { start: i, end, value });
+        }
+        i = end;
+    }
+    spans
+}
+
+/// Write each numeral [`find_roman_numerals`] finds in Arabic digits.
+///
+/// ```
+/// use latincy_preprocess::numerals::to_arabic;
+///
+/// assert_eq!(to_arabic("anno MDCXV, M. Tullius"), "anno 1615, M. Tullius");
+/// ```
+pub fn to_arabic(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for span in find_roman_numerals(text) {
+        result.extend(&chars[last..span.start]);
+        result.push_str(&span.value.to_string());
+        last = span.end;
+    }
+    result.extend(&chars[last..]);
+    result
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "find_roman_numerals")]
+pub fn py_find_roman_numerals<'py>(py: Python<'py>, text: &str) -> PyResult<Bound<'py, PyList>> {
+    let chars: Vec<char> = text.chars().collect();
+    let list = PyList::empty(py);
+    for span in crate::allow_threads(py, text, || find_roman_numerals(text)) {
+        let dict = PyDict::new(py);
+        dict.set_item("start", span.start)?;
+        dict.set_item("end", span.end)?;
+        dict.set_item("text", chars[span.start..span.end].iter().collect::<String>())?;
+        dict.set_item("value", span.value)?;
+        list.append(dict)?;
+    }
+    Ok(list)
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "parse_roman")]
+pub fn py_parse_roman(numeral: &str) -> Option<u64> {
+    parse(numeral)
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "roman_to_arabic")]
+pub fn py_roman_to_arabic(py: Python<'_>, text: &str) -> String {
+    crate::allow_threads(py, text, || to_arabic(text))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("XIV"), Some(14));
+        assert_eq!(parse("MCMXCIV"), Some(1994));
+        assert_eq!(parse("xcix"), Some(99));
+        // Additive fours and nines, repeated thousands
+        assert_eq!(parse("IIII"), Some(4));
+        assert_eq!(parse("XVIIII"), Some(19));
+        assert_eq!(parse("MMMMD"), Some(4500));
+        assert_eq!(parse("Ⅻ"), Some(12));
+    }
+
+    #[test]
+    fn test_ill_formed() {
+        for numeral in ["IIX", "VV", "IC", "XM", "IVI", "IIIII", "LL", "VX", "jv", "", "XV2"] {
+            assert_eq!(parse(numeral), None, "{}", numeral);
+        }
+    }
+
+    #[test]
+    fn test_archaic_forms() {
+        assert_eq!(parse("ↀↀD"), Some(2500));
+        assert_eq!(parse("ↁↀ"), Some(6000));
+        assert_eq!(parse("CIↃ"), Some(1000));
+        assert_eq!(parse("IↃↃCIↃ"), Some(6000));
+        assert_eq!(parse("CCIↃↃ"), Some(10000));
+        assert_eq!(parse("IↃ"), Some(500));
+    }
+
+    #[test]
+    fn test_vinculum() {
+        assert_eq!(parse("V\u{0305}"), Some(5000));
+        assert_eq!(parse("X\u{0305}L\u{0305}"), Some(40000));
+        assert_eq!(parse("M\u{0305}CC"), Some(1_000_200));
+        // The overline counts as part of the found span
+        assert_eq!(find_roman_numerals("C\u{0305} milia"), vec![Span { start: 0, end: 2, value: 100000 }]);
+    }
+
+    #[test]
+    fn test_find_skips_words() {
+        assert!(find_roman_numerals("vi et armis, mi fili, di immortales, Vivit").is_empty());
+        assert!(find_roman_numerals("M. Tullius Cicero et C. Iulius").is_empty());
+        assert!(find_roman_numerals("civis dixit dic vidi").is_empty());
+        // A decomposed accent is not a vinculum
+        assert!(find_roman_numerals("v\u{0301}").is_empty());
+        let spans = find_roman_numerals("anno M DC, VI kal.");
+        assert_eq!(spans.iter().map(|s| s.value).collect::<Vec<_>>(), vec![1000, 600, 6]);
+    }
+
+    #[test]
+    fn test_to_arabic() {
+        assert_eq!(to_arabic("cap. xij et XVI"), "cap. 12 et 16");
+        assert_eq!(to_arabic("mille"), "mille");
+    }
+
+    #[test]
+    fn test_in_numeral() {
+        let chars: Vec<char> = "ad XVI uiam".chars().collect();
+        assert!(in_numeral(&chars, 4));
+        assert!(!in_numeral(&chars, 2));
+        assert!(!in_numeral(&chars, 7));
+    }
+}
@@ -0,0 +1,543 @@
+//! Correction of OCR character confusions (`rnagnus` → `magnus`, `c1arus`
+//! → `clarus`).
+//!
+//! OCR engines misread letter shapes in predictable ways. A
+//! [`ConfusionMatrix`] lists them, each with the probability ratio a
+//! correction must win by; [`OcrCorrector`] reads every word with each
+//! combination of possible confusions and keeps the reading the lexicon
+//! attests or the n-gram model prefers. Long-s Pass 2 is one instance of
+//! the pattern (`f` misread for `ſ`): [`crate::long_s::candidates`] builds
+//! its readings with [`readings`].
+
+use crate::changes::{self, ChangeRecord};
+use crate::diagnostics::{Warning, WarningKind};
+use crate::lexicon::Lexicon;
+use crate::ngrams::{self, NgramData};
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use crate::ngrams::PyNgramData;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use std::io;
+use std::sync::Arc;
+
+/// The built-in confusions: `(misread, correct, threshold)`. Digits inside a
+/// word are almost always misread letters, so they need only beat the word
+/// as written. Letter confusions must be ten thousand times as probable,
+/// which keeps *modernus* and *nocturnus* but still corrects *rnagnus* and
+/// *dorninus*.
+pub const DEFAULT_CONFUSIONS: &[(&str, &str, f64)] = &[
+    ("rn", "m", 10000.0),
+    ("cl", "d", 10000.0),
+    ("li", "h", 10000.0),
+    ("1", "l", 1.0),
+    ("1", "i", 1.0),
+    ("0", "o", 1.0),
+];
+
+/// Most confusion sites varied in one word; every combination is scored,
+/// so later sites are left as they are, bounding the search at 2^10
+/// readings.
+pub const MAX_SITES: usize = 10;
+
+/// One confusion: `from` is what the OCR engine wrote for `to`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Confusion {
+    pub from: String,
+    pub to: String,
+    /// The probability ratio a reading with this correction must beat the
+    /// word as written by; ratios of corrections made together multiply.
+    pub threshold: f64,
+}
+
+/// A set of [`Confusion`]s.
+///
+/// ```
+/// use latincy_preprocess::ocr::ConfusionMatrix;
+///
+/// let mut matrix = ConfusionMatrix::empty();
+/// matrix.add("ii", "u", 5.0).unwrap();
+/// matrix.add("II", "u", 5.0).unwrap_err();
+/// assert_eq!(matrix.len(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfusionMatrix {
+    confusions: Vec<Confusion>,
+}
+
+impl Default for ConfusionMatrix {
+    /// The [`DEFAULT_CONFUSIONS`].
+    fn default() -> Self {
+        let mut matrix = Self::empty();
+        for &(from, to, threshold) in DEFAULT_CONFUSIONS {
+            matrix.add(from, to, threshold).expect("built-in confusions are valid");
+        }
+        matrix
+    }
+}
+
+impl ConfusionMatrix {
+    /// A matrix with no confusions.
+    pub fn empty() -> Self {
+        ConfusionMatrix { confusions: Vec::new() }
+    }
+
+    /// Add a confusion, replacing any with the same `from` and `to`. Fails
+    /// with `InvalidInput` if `from` is empty, either side is not
+    /// lowercase, or the threshold is negative or not a number.
+    pub fn add(&mut self, from: &str, to: &str, threshold: f64) -> io::Result<()> {
+        let invalid = |reason: &str| {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid confusion {} → {}: {}", from, to, reason),
+            ))
+        };
+        if from.is_empty() {
+            return invalid("empty pattern");
+        }
+        if from != from.to_lowercase() || to != to.to_lowercase() {
+            return invalid("confusions must be lowercase");
+        }
+        if threshold.is_nan() || threshold < 0.0 {
+            return invalid("threshold must be a non-negative number");
+        }
+        self.confusions.retain(|c| c.from != from || c.to != to);
+        self.confusions.push(Confusion {
+            from: from.to_string(),
+            to: to.to_string(),
+            threshold,
+        });
+        Ok(())
+    }
+
+    pub fn confusions(&self) -> &[Confusion] {
+        &self.confusions
+    }
+
+    pub fn len(&self) -> usize {
+        self.confusions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.confusions.is_empty()
+    }
+
+    /// Every place in a lowercase `word` where a confusion applies, in
+    /// order of position, then of the matrix.
+    pub fn sites(&self, word: &[char]) -> Vec<Site> {
+        let mut sites = Vec::new();
+        for start in 0..word.len() {
+            for confusion in &self.confusions {
+                let len = confusion.from.chars().count();
+                if word.len() >= start + len && confusion.from.chars().eq(word[start..start + len].iter().copied()) {
+                    sites.push(Site {
+                        start,
+                        len,
+                        to: confusion.to.clone(),
+                        threshold: confusion.threshold,
+                    });
+                }
+            }
+        }
+        sites
+    }
+}
+
+/// A place in a word where a [`Confusion`] applies: the `len` characters at
+/// `start` may stand for `to`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Site {
+    pub start: usize,
+    pub len: usize,
+    pub to: String,
+    pub threshold: f64,
+}
+
+/// Every reading of `word` with a subset of `sites` (sorted by position)
+/// corrected, with the indices of the sites used; sites that overlap are
+/// never used together. The word as written comes first, and site `k`
+/// varies with bit `k` of the enumeration order. Only the first
+/// [`MAX_SITES`] sites are varied.
+pub fn readings(word: &[char], sites: &[Site]) -> Vec<(String, Vec<usize>)> {
+    let sites = &sites[..sites.len().min(MAX_SITES)];
+    let mut result = Vec::with_capacity(1 << sites.len());
+    'masks: for mask in 0..1usize << sites.len() {
+        let used: Vec<usize> = (0..sites.len()).filter(|&k| mask & (1 << k) != 0).collect();
+        let mut reading = String::with_capacity(word.len());
+        let mut i = 0;
+        for &k in &used {
+            let site = &sites[k];
+            if site.start < i {
+                continue 'masks;
+            }
+            reading.extend(&word[i..site.start]);
+            reading.push_str(&site.to);
+            i = site.start + site.len;
+        }
+        reading.extend(&word[i..]);
+        result.push((reading, used));
+    }
+    result
+}
+
+/// Corrects OCR confusions word by word.
+///
+/// A word the lexicon attests is left alone; otherwise the most frequent
+/// attested reading wins, and failing that the reading the n-gram model
+/// prefers by more than the thresholds of its corrections. Runs of letters
+/// and digits count as words, but numbers (all digits) are left alone.
+///
+/// ```
+/// use latincy_preprocess::ocr::OcrCorrector;
+///
+/// let corrector = OcrCorrector::new();
+/// # #[cfg(feature = "embed-data")]
+/// assert_eq!(corrector.correct("Rnagnus et c1arus anno 1500"), "Magnus et clarus anno 1500");
+/// ```
+#[derive(Debug, Clone)]
+pub struct OcrCorrector {
+    matrix: ConfusionMatrix,
+    lexicon: Option<Arc<Lexicon>>,
+    ngrams: Option<Arc<NgramData>>,
+}
+
+impl Default for OcrCorrector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OcrCorrector {
+    /// The [`DEFAULT_CONFUSIONS`], scored by the bundled model.
+    pub fn new() -> Self {
+        OcrCorrector {
+            matrix: ConfusionMatrix::default(),
+            lexicon: None,
+            ngrams: None,
+        }
+    }
+
+    pub fn confusions(mut self, matrix: ConfusionMatrix) -> Self {
+        self.matrix = matrix;
+        self
+    }
+
+    /// Leave words in `lexicon` alone, and prefer attested readings.
+    pub fn lexicon(mut self, lexicon: Arc<Lexicon>) -> Self {
+        self.lexicon = Some(lexicon);
+        self
+    }
+
+    /// Score readings with `ngrams` instead of the bundled model.
+    pub fn ngrams(mut self, ngrams: Arc<NgramData>) -> Self {
+        self.ngrams = Some(ngrams);
+        self
+    }
+
+    pub fn matrix(&self) -> &ConfusionMatrix {
+        &self.matrix
+    }
+
+    fn model(&self) -> Option<&NgramData> {
+        match &self.ngrams {
+            Some(ngrams) => Some(ngrams),
+            None => ngrams::bundled().ok(),
+        }
+    }
+
+    /// The sites to correct in a lowercase word.
+    fn corrections(&self, word: &[char]) -> Vec<Site> {
+        let sites = self.matrix.sites(word);
+        if sites.is_empty() || self.lexicon.as_ref().is_some_and(|l| l.contains(&word.iter().collect::<String>())) {
+            return Vec::new();
+        }
+        let readings = readings(word, &sites);
+        let chosen = |used: &[usize]| used.iter().map(|&k| sites[k].clone()).collect();
+
+        if let Some(lexicon) = &self.lexicon {
+            let mut best: Option<(u64, &[usize])> = None;
+            for (reading, used) in &readings[1..] {
+                let frequency = lexicon.frequency(reading);
+                if frequency > 0 && best.is_none_or(|(best, _)| frequency > best) {
+                    best = Some((frequency, used));
+                }
+            }
+            if let Some((_, used)) = best {
+                return chosen(used);
+            }
+        }
+
+        let Some(model) = self.model() else {
+            return Vec::new();
+        };
+        let original = model.log_prob(&readings[0].0);
+        let mut best: Option<(f64, &[usize])> = None;
+        for (reading, used) in &readings[1..] {
+            let score = model.log_prob(reading);
+            let margin: f64 = used.iter().map(|&k| sites[k].threshold.ln()).sum();
+            if score - original > margin + 1e-9 && best.is_none_or(|(best, _)| score > best) {
+                best = Some((score, used));
+            }
+        }
+        best.map_or_else(Vec::new, |(_, used)| chosen(used))
+    }
+
+    /// Correct one word.
+    pub fn correct_word(&self, word: &str) -> String {
+        self.correct_detailed(word).normalized
+    }
+
+    /// Correct every word in `text`.
+    pub fn correct(&self, text: &str) -> String {
+        self.correct_detailed(text).normalized
+    }
+
+    /// Like [`OcrCorrector::correct`], recording one [`ChangeRecord`] per
+    /// corrected confusion, with a rule such as `"rn → m"`, and a
+    /// [`WarningKind::MixedDigits`] warning for each word still mixing
+    /// digits and letters once corrected.
+    pub fn correct_detailed(&self, text: &str) -> DetailedResult {
+        let chars: Vec<char> = text.chars().collect();
+        let mut normalized = String::with_capacity(text.len());
+        let mut changes = Vec::new();
+        let mut warnings = Vec::new();
+
+        let mut i = 0;
+        while i < chars.len() {
+            if !chars[i].is_alphanumeric() {
+                normalized.push(chars[i]);
+                i += 1;
+                continue;
+            }
+            let end = chars[i..].iter().position(|c| !c.is_alphanumeric()).map_or(chars.len(), |p| i + p);
+            let original = &chars[i..end];
+            let lower: Vec<char> = original.iter().flat_map(|c| c.to_lowercase()).collect();
+            let sites = if original.iter().any(|c| c.is_alphabetic()) && lower.len() == original.len() {
+                self.corrections(&lower)
+            } else {
+                Vec::new()
+            };
+            let word: String = original.iter().collect();
+            let (is_upper, is_title) = crate::case::pattern(&word);
+
+            let start = normalized.len();
+            let mut j = 0;
+            for site in sites {
+                normalized.extend(&original[j..site.start]);
+                // A correction takes the case of the word (RNAGNUS → MAGNUS)
+                // or, at its start, of the capital (Rnagnus → Magnus)
+                let corrected = if is_upper {
+                    site.to.to_uppercase()
+                } else if is_title && site.start == 0 {
+                    let mut letters = site.to.chars();
+                    letters.next().map_or_else(String::new, |c| c.to_uppercase().chain(letters).collect())
+                } else {
+                    site.to.clone()
+                };
+                let position = i + site.start;
+                let from: String = lower[site.start..site.start + site.len].iter().collect();
+                changes.push(ChangeRecord {
+                    position,
+                    original: original[site.start..site.start + site.len].iter().collect(),
+                    normalized: corrected.clone(),
+                    pass: 1,
+                    rule: format!("{} → {}", from, site.to),
+                    context: changes::context(&chars, position, site.len),
+                });
+                normalized.push_str(&corrected);
+                j = site.start + site.len;
+            }
+            normalized.extend(&original[j..]);
+            let corrected = &normalized[start..];
+            if corrected.chars().any(|c| c.is_numeric()) && corrected.chars().any(|c| c.is_alphabetic()) {
+                let message = format!("`{}` mixes digits and letters", word);
+                warnings.push(Warning::new(&chars, i, end - i, WarningKind::MixedDigits, message));
+            }
+            i = end;
+        }
+
+        DetailedResult {
+            original: text.to_string(),
+            normalized,
+            changes,
+            warnings,
+        }
+    }
+}
+
+/// Correct OCR confusions in `text` with the default [`OcrCorrector`].
+pub fn correct(text: &str) -> String {
+    OcrCorrector::new().correct(text)
+}
+
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<ChangeRecord>,
+    pub warnings: Vec<Warning>,
+}
+
+impl Normalizer for OcrCorrector {
+    fn name(&self) -> &str {
+        "ocr"
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = self.correct_detailed(text);
+        NormalizeOutput::from_changes(result.original, result.normalized, result.changes).with_warnings(result.warnings)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "correct_ocr")]
+pub fn py_correct_ocr(py: Python<'_>, text: &str) -> String {
+    crate::allow_threads(py, text, || correct(text))
+}
+
+/// Python view of [`OcrCorrector`], exposed as `_rust.OcrCorrector`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "OcrCorrector", frozen)]
+pub struct PyOcrCorrector {
+    pub(crate) inner: OcrCorrector,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyOcrCorrector {
+    /// `confusions` is a list of `(misread, correct, threshold)` tuples
+    /// replacing the built-in ones.
+    #[new]
+    #[pyo3(signature = (confusions=None, lexicon=None, ngrams=None))]
+    fn new(
+        confusions: Option<Vec<(String, String, f64)>>,
+        lexicon: Option<std::path::PathBuf>,
+        ngrams: Option<PyRef<'_, PyNgramData>>,
+    ) -> PyResult<Self> {
+        let mut inner = OcrCorrector::new();
+        if let Some(confusions) = confusions {
+            let mut matrix = ConfusionMatrix::empty();
+            for (from, to, threshold) in confusions {
+                matrix
+                    .add(&from, &to, threshold)
+                    .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+            }
+            inner = inner.confusions(matrix);
+        }
+        if let Some(lexicon) = lexicon {
+            inner = inner.lexicon(Arc::new(Lexicon::from_file(lexicon)?));
+        }
+        if let Some(ngrams) = ngrams {
+            inner = inner.ngrams(Arc::clone(&ngrams.inner));
+        }
+        Ok(PyOcrCorrector { inner })
+    }
+
+    /// The confusions as `(misread, correct, threshold)` tuples.
+    #[getter]
+    fn confusions(&self) -> Vec<(String, String, f64)> {
+        self.inner.matrix().confusions().iter().map(|c| (c.from.clone(), c.to.clone(), c.threshold)).collect()
+    }
+
+    fn correct_word(&self, word: &str) -> String {
+        self.inner.correct_word(word)
+    }
+
+    fn correct(&self, py: Python<'_>, text: &str) -> String {
+        crate::allow_threads(py, text, || self.inner.correct(text))
+    }
+
+    fn correct_detailed(&self, py: Python<'_>, text: &str) -> PyResult<changes::PyDetailedResult> {
+        let result = crate::allow_threads(py, text, || self.inner.correct_detailed(text));
+        changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)?
+            .with_warnings(py, &result.warnings)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(word: &str) -> Vec<char> {
+        word.chars().collect()
+    }
+
+    #[test]
+    fn test_readings() {
+        let mut matrix = ConfusionMatrix::empty();
+        matrix.add("rn", "m", 1.0).unwrap();
+        matrix.add("n", "u", 1.0).unwrap();
+        let word = chars("rnn");
+        let sites = matrix.sites(&word);
+        assert_eq!(sites.len(), 3);
+        let readings: Vec<String> = readings(&word, &sites).into_iter().map(|(r, _)| r).collect();
+        // rn and the n inside it overlap, so are never read together
+        assert_eq!(readings, ["rnn", "mn", "run", "rnu", "mu", "ruu"]);
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_correct() {
+        let corrector = OcrCorrector::new();
+        assert_eq!(corrector.correct("rnagnus c1arus n0n"), "magnus clarus non");
+        assert_eq!(corrector.correct("RNAGNUS"), "MAGNUS");
+        // Genuine words and numbers are kept
+        assert_eq!(corrector.correct("cernit modernus clavis 1500"), "cernit modernus clavis 1500");
+        assert_eq!(corrector.correct("dorninus lioc"), "dominus hoc");
+    }
+
+    #[test]
+    fn test_lexicon() {
+        let lexicon = Arc::new(Lexicon::from_counts([("modernus", 3), ("clamor", 5)]));
+        let corrector = OcrCorrector::new().lexicon(lexicon);
+        assert_eq!(corrector.correct("modernus"), "modernus");
+        assert_eq!(corrector.correct("c1amor"), "clamor");
+    }
+
+    #[test]
+    fn test_confusion_matrix() {
+        let mut matrix = ConfusionMatrix::default();
+        assert_eq!(matrix.len(), DEFAULT_CONFUSIONS.len());
+        matrix.add("rn", "m", 2.0).unwrap();
+        assert_eq!(matrix.len(), DEFAULT_CONFUSIONS.len());
+        assert!(matrix.add("", "m", 1.0).is_err());
+        assert!(matrix.add("rn", "m", -1.0).is_err());
+        let corrector = OcrCorrector::new().confusions(ConfusionMatrix::empty());
+        assert_eq!(corrector.correct("rnagnus"), "rnagnus");
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_correct_detailed() {
+        let result = OcrCorrector::new().correct_detailed("est Rnagnus");
+        assert_eq!(result.normalized, "est Magnus");
+        assert_eq!(result.changes.len(), 1);
+        assert_eq!(result.changes[0].position, 4);
+        assert_eq!(result.changes[0].original, "Rn");
+        assert_eq!(result.changes[0].normalized, "M");
+        assert_eq!(result.changes[0].rule, "rn → m");
+        assert_eq!(result.changes[0].context, "st [Rn]agn");
+        assert!(result.warnings.is_empty());
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_mixed_digits_warning() {
+        let result = OcrCorrector::new().correct_detailed("c3sar c1arus anno 1500");
+        assert_eq!(result.normalized, "c3sar clarus anno 1500");
+        assert_eq!(result.warnings.len(), 1);
+        let warning = &result.warnings[0];
+        assert_eq!((warning.position, warning.len), (0, 5));
+        assert_eq!(warning.kind, WarningKind::MixedDigits);
+        assert_eq!(warning.message, "`c3sar` mixes digits and letters");
+        assert_eq!(warning.context, "[c3sar] c1");
+    }
+}
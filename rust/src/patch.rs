@@ -0,0 +1,354 @@
+//! Normalization as a reviewable patch.
+//!
+//! Instead of rewriting a text, [`export_patch`] lists what a normalizer
+//! would change, each [`Edit`] a span of the original text, its
+//! replacement, the rules behind it, and a confidence. The patch is written
+//! as JSON Lines, one edit per line, so a reviewer can drop or correct
+//! edits with any editor or `grep`; [`apply_patch`] then applies what is
+//! left to the original text.
+//!
+//! ```
+//! use latincy_preprocess::patch::{apply_patch, export_patch, Patch};
+//! use latincy_preprocess::uv::UvNormalizer;
+//!
+//! let text = "uir uult";
+//! let patch = export_patch(&UvNormalizer::new(), text);
+//! assert_eq!(patch.edits.len(), 2);
+//! // Keep only the first edit
+//! let reviewed = Patch::from_jsonl(patch.to_jsonl().lines().next().unwrap()).unwrap();
+//! assert_eq!(apply_patch(text, &reviewed).unwrap(), "vir uult");
+//! ```
+
+use crate::long_s;
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use serde_json::Value;
+use std::io;
+
+/// One proposed replacement of `original[start..end]` (in characters).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edit {
+    pub start: usize,
+    pub end: usize,
+    /// The original characters, checked before the edit is applied.
+    pub original: String,
+    pub replacement: String,
+    /// The rules that made the change, e.g. `"uv: consonantal"` in a
+    /// pipeline.
+    pub rules: Vec<String>,
+    /// How sure the normalizer is of the edit, from 0 to 1. Only long-s
+    /// word edits carry a confidence of their own (see
+    /// [`long_s::WordChange::confidence`]); rule-based edits count as 1.
+    pub confidence: f64,
+}
+
+/// Edits in text order, none overlapping.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Patch {
+    pub edits: Vec<Edit>,
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl Patch {
+    /// The edits that turn `output.original` into `output.normalized`, from
+    /// its offset map: each run of original characters that are not copied
+    /// through unchanged is one edit, attributed to the change records
+    /// positioned in it.
+    pub fn from_output(output: &NormalizeOutput) -> Self {
+        let from: Vec<char> = output.original.chars().collect();
+        let to: Vec<char> = output.normalized.chars().collect();
+        let mapped = &output.offsets[..to.len()];
+        // The normalized characters that came from original character p
+        // are lo(p)..lo(p + 1)
+        let lo = |p: usize| mapped.partition_point(|&offset| offset < p);
+        let mut edits: Vec<Edit> = Vec::new();
+        for p in 0..=from.len() {
+            let (start, end) = (lo(p), if p < from.len() { lo(p + 1) } else { to.len() });
+            let copied = p < from.len() && end == start + 1 && to[start] == from[p];
+            if copied || (p == from.len() && start == end) {
+                continue;
+            }
+            let len = usize::from(p < from.len());
+            match edits.last_mut() {
+                Some(edit) if edit.end == p => {
+                    edit.end += len;
+                    edit.original.extend(&from[p..p + len]);
+                    edit.replacement.extend(&to[start..end]);
+                }
+                _ => edits.push(Edit {
+                    start: p,
+                    end: p + len,
+                    original: from[p..p + len].iter().collect(),
+                    replacement: to[start..end].iter().collect(),
+                    rules: Vec::new(),
+                    confidence: 1.0,
+                }),
+            }
+        }
+        for change in &output.changes {
+            let edit = edits.iter_mut().find(|edit| {
+                (edit.start..edit.end.max(edit.start + 1)).contains(&change.position.min(from.len()))
+            });
+            if let Some(edit) = edit {
+                if !edit.rules.contains(&change.rule) {
+                    edit.rules.push(change.rule.clone());
+                }
+            }
+        }
+        Patch { edits }
+    }
+
+    /// One edit per changed word of a long-s result, with the word's
+    /// confidence.
+    pub fn from_long_s(result: &long_s::DetailedResult) -> Self {
+        let edits = result
+            .changes
+            .iter()
+            .map(|change| Edit {
+                start: change.position,
+                end: change.position + change.original.chars().count(),
+                original: change.original.clone(),
+                replacement: change.normalized.clone(),
+                rules: change.rules.iter().map(|rule| rule.pattern.clone()).collect(),
+                confidence: change.confidence,
+            })
+            .collect();
+        Patch { edits }
+    }
+
+    /// The edits with confidence below `below`, e.g. to send only those to
+    /// review and accept the rest.
+    pub fn below(&self, below: f64) -> Patch {
+        Patch {
+            edits: self.edits.iter().filter(|edit| edit.confidence < below).cloned().collect(),
+        }
+    }
+
+    /// The patch as JSON Lines, one edit object per line.
+    pub fn to_jsonl(&self) -> String {
+        self.edits
+            .iter()
+            .map(|edit| {
+                serde_json::json!({
+                    "start": edit.start,
+                    "end": edit.end,
+                    "original": edit.original,
+                    "replacement": edit.replacement,
+                    "rules": edit.rules,
+                    "confidence": edit.confidence,
+                })
+                .to_string()
+                    + "\n"
+            })
+            .collect()
+    }
+
+    /// The patch written by [`Patch::to_jsonl`], possibly edited: blank
+    /// lines are skipped, and `rules` and `confidence` may be left out.
+    pub fn from_jsonl(jsonl: &str) -> io::Result<Self> {
+        let mut edits = Vec::new();
+        for (lineno, line) in jsonl.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let bad = |message: &str| invalid(format!("line {}: {}", lineno + 1, message));
+            let value: Value = serde_json::from_str(line).map_err(|e| bad(&e.to_string()))?;
+            let index = |key: &str| {
+                value[key].as_u64().map(|n| n as usize).ok_or_else(|| bad(&format!("`{key}` must be an integer")))
+            };
+            let string = |key: &str| {
+                value[key].as_str().map(str::to_string).ok_or_else(|| bad(&format!("`{key}` must be a string")))
+            };
+            let rules = match &value["rules"] {
+                Value::Null => Vec::new(),
+                rules => serde_json::from_value(rules.clone()).map_err(|_| bad("`rules` must be a list of strings"))?,
+            };
+            edits.push(Edit {
+                start: index("start")?,
+                end: index("end")?,
+                original: string("original")?,
+                replacement: string("replacement")?,
+                rules,
+                confidence: value["confidence"].as_f64().unwrap_or(1.0),
+            });
+        }
+        Ok(Patch { edits })
+    }
+
+    /// `text` with the edits applied. Fails, changing nothing, if an edit
+    /// overlaps another or its `original` is not what `text` has at its
+    /// span, e.g. because the patch was made for another version of the
+    /// text.
+    pub fn apply(&self, text: &str) -> io::Result<String> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut edits: Vec<&Edit> = self.edits.iter().collect();
+        edits.sort_by_key(|edit| (edit.start, edit.end));
+        let mut result = String::with_capacity(text.len());
+        let mut pos = 0;
+        for edit in edits {
+            if edit.start < pos || edit.end < edit.start {
+                return Err(invalid(format!("edit at {} overlaps another edit", edit.start)));
+            }
+            let found: String = chars.get(edit.start..edit.end).unwrap_or_default().iter().collect();
+            if edit.end > chars.len() || found != edit.original {
+                return Err(invalid(format!(
+                    "edit at {}: expected {:?}, found {:?}",
+                    edit.start, edit.original, found
+                )));
+            }
+            result.extend(&chars[pos..edit.start]);
+            result.push_str(&edit.replacement);
+            pos = edit.end;
+        }
+        result.extend(&chars[pos..]);
+        Ok(result)
+    }
+}
+
+/// The edits `normalizer` would make to `text`.
+pub fn export_patch(normalizer: &dyn Normalizer, text: &str) -> Patch {
+    Patch::from_output(&normalizer.normalize(text))
+}
+
+/// The words `output` changed, as `(original, normalized)` pairs in text
+/// order: each edit widened to the alphanumeric characters around it, with
+/// edits in the same word merged.
+pub fn changed_words(output: &NormalizeOutput) -> Vec<(String, String)> {
+    let from: Vec<char> = output.original.chars().collect();
+    let to: Vec<char> = output.normalized.chars().collect();
+    let lo = |p: usize| output.offsets[..to.len()].partition_point(|&offset| offset < p);
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    for edit in Patch::from_output(output).edits {
+        let (mut start, mut end) = (edit.start, edit.end);
+        while start > 0 && from[start - 1].is_alphanumeric() {
+            start -= 1;
+        }
+        while end < from.len() && from[end].is_alphanumeric() {
+            end += 1;
+        }
+        match spans.last_mut() {
+            Some(last) if last.1 >= start => last.1 = end,
+            _ => spans.push((start, end)),
+        }
+    }
+    spans
+        .into_iter()
+        .map(|(start, end)| {
+            let normalized = lo(start)..if end < from.len() { lo(end) } else { to.len() };
+            (from[start..end].iter().collect(), to[normalized].iter().collect())
+        })
+        .collect()
+}
+
+/// `text` with the edits of `patch` applied (see [`Patch::apply`]).
+pub fn apply_patch(text: &str, patch: &Patch) -> io::Result<String> {
+    patch.apply(text)
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+/// `text` with the edits of a JSON Lines patch applied; `ValueError` if
+/// the patch is malformed or does not fit `text`.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "apply_patch")]
+pub fn py_apply_patch(py: Python<'_>, text: &str, patch: &str) -> PyResult<String> {
+    crate::allow_threads(py, text, || Patch::from_jsonl(patch).and_then(|patch| patch.apply(text)))
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enclitics::EncliticSplitter;
+    use crate::ligatures::Capitals;
+    use crate::long_s::LongSNormalizer;
+    use crate::pipeline::Pipeline;
+    use crate::uv::UvNormalizer;
+
+    #[test]
+    fn test_export_pipeline() {
+        let pipeline = Pipeline::new().stage(Capitals::Auto).stage(LongSNormalizer::new()).stage(UvNormalizer::new());
+        let text = "Cæsar ﬅat ſeruus";
+        let patch = export_patch(&pipeline, text);
+        let spans: Vec<(usize, usize, &str, &str)> = patch
+            .edits
+            .iter()
+            .map(|edit| (edit.start, edit.end, edit.original.as_str(), edit.replacement.as_str()))
+            .collect();
+        assert_eq!(spans, [(1, 2, "æ", "ae"), (6, 7, "ﬅ", "st"), (10, 11, "ſ", "s"), (13, 14, "u", "v")]);
+        assert!(patch.edits[0].rules[0].starts_with("ligatures: "));
+        assert!(patch.edits[3].rules[0].starts_with("uv: "));
+        assert_eq!(patch.apply(text).unwrap(), pipeline.normalize_text(text));
+    }
+
+    #[test]
+    fn test_insertions_and_deletions() {
+        let splitter = EncliticSplitter::new();
+        let patch = export_patch(&splitter, "uirumque");
+        assert_eq!(patch.apply("uirumque").unwrap(), "uirum que");
+        assert_eq!(patch.edits.len(), 1);
+        assert_eq!(patch.edits[0].rules, ["-que"]);
+        // Deleted characters and characters added at the end
+        let output = NormalizeOutput::from_changes("ab-c".into(), "abcd".into(), Vec::new());
+        let patch = Patch::from_output(&output);
+        assert_eq!(patch.apply("ab-c").unwrap(), "abcd");
+    }
+
+    #[test]
+    fn test_long_s_confidence() {
+        let text = "ﬁne fuit muſa";
+        let result = LongSNormalizer::new().normalize_detailed(text);
+        let patch = Patch::from_long_s(&result);
+        assert_eq!(patch.apply(text).unwrap(), result.normalized);
+        assert!(patch.edits.iter().all(|edit| (0.0..=1.0).contains(&edit.confidence)));
+        assert_eq!(patch.below(0.0).edits.len(), 0);
+    }
+
+    #[test]
+    fn test_jsonl_round_trip() {
+        let text = "uir uult";
+        let patch = export_patch(&UvNormalizer::new(), text);
+        assert_eq!(Patch::from_jsonl(&patch.to_jsonl()).unwrap(), patch);
+        // A hand-edited line: a corrected replacement, no rules or confidence
+        let edited = "\n{\"start\": 4, \"end\": 5, \"original\": \"u\", \"replacement\": \"V\"}\n";
+        let patch = Patch::from_jsonl(edited).unwrap();
+        assert_eq!(patch.apply(text).unwrap(), "uir Vult");
+        assert_eq!(patch.edits[0].confidence, 1.0);
+    }
+
+    #[test]
+    fn test_changed_words() {
+        let pipeline = Pipeline::new().stage(LongSNormalizer::new()).stage(UvNormalizer::new());
+        let output = pipeline.normalize("ſeruus et ﬅat, uirumque");
+        let words = changed_words(&output);
+        let pairs: Vec<(&str, &str)> = words.iter().map(|(from, to)| (from.as_str(), to.as_str())).collect();
+        assert_eq!(pairs, [("ſeruus", "servus"), ("ﬅat", "stat"), ("uirumque", "virumque")]);
+        let splitter = EncliticSplitter::new();
+        assert_eq!(changed_words(&splitter.normalize("arma uirumque")), [("uirumque".into(), "uirum que".into())]);
+        assert!(changed_words(&UvNormalizer::new().normalize("Troia")).is_empty());
+    }
+
+    #[test]
+    fn test_apply_errors() {
+        let patch = export_patch(&UvNormalizer::new(), "uir");
+        let err = patch.apply("vir").unwrap_err();
+        assert_eq!(err.to_string(), "edit at 0: expected \"u\", found \"v\"");
+        assert!(patch.apply("").is_err());
+        let mut overlapping = patch.clone();
+        overlapping.edits.push(patch.edits[0].clone());
+        assert!(overlapping.apply("uir").unwrap_err().to_string().contains("overlaps"));
+        assert!(Patch::from_jsonl("{\"start\": 0}").unwrap_err().to_string().starts_with("line 1: "));
+        assert_eq!(Patch::default().apply("uir").unwrap(), "uir");
+    }
+}
@@ -0,0 +1,1842 @@
+//! One interface for every normalizer, and pipelines that chain them.
+//!
+//! Each module grew its own entry points: `uv::normalize_detailed`, a
+//! [`LongSNormalizer`](crate::long_s::LongSNormalizer) with a builder,
+//! `expand_detailed`, `clean_detailed`. They all implement [`Normalizer`]:
+//! one `normalize` call that returns a [`NormalizeOutput`] with the result,
+//! its [`ChangeRecord`]s, and an offset map back onto the input.
+//! A [`Pipeline`] runs normalizers in order and composes their offset maps,
+//! so a span of the final text maps back to the original text in one step.
+
+use crate::changes::{ChangeRecord, ChangeSummary, OutputDetail};
+use crate::diagnostics::Warning;
+use crate::protect::{Detector, ProtectedSpans};
+use crate::telemetry;
+#[cfg(feature = "pyo3-backend")]
+use crate::changes::{PyChangeRecord, PyChangeSummary, PyDetailedResult};
+#[cfg(feature = "pyo3-backend")]
+use crate::diagnostics::PyWarning;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::types::{PyDict, PyList, PyString};
+use std::io::{self, BufRead, Read, Write};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// The result of one [`Normalizer::normalize`] call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizeOutput {
+    pub original: String,
+    pub normalized: String,
+    /// The changes, with positions in `original`.
+    pub changes: Vec<ChangeRecord>,
+    /// The character offset in `original` of each character of
+    /// `normalized`, then the length of `original`: normalized characters
+    /// `a..b` came from original characters `offsets[a]..offsets[b]`.
+    pub offsets: Vec<usize>,
+    /// Suspicious input the normalizer left alone, with positions in
+    /// `original`.
+    pub warnings: Vec<Warning>,
+}
+
+impl NormalizeOutput {
+    /// `text` left as is.
+    pub fn unchanged(text: &str) -> Self {
+        NormalizeOutput {
+            original: text.to_string(),
+            normalized: text.to_string(),
+            changes: Vec::new(),
+            offsets: (0..=text.chars().count()).collect(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// This output with `warnings` about its `original`.
+    pub fn with_warnings(mut self, warnings: Vec<Warning>) -> Self {
+        self.warnings = warnings;
+        self
+    }
+
+    /// The output of a normalizer whose `changes` each replace their
+    /// `original` at `position` with their `normalized`, in text order. The
+    /// offset map is built from the changes; if they do not spell out every
+    /// edit, it falls back to mapping the common prefix and suffix of the
+    /// two texts and the differing middle to its start.
+    pub fn from_changes(original: String, normalized: String, changes: Vec<ChangeRecord>) -> Self {
+        Self::from_edits(original, normalized, changes, &[])
+    }
+
+    /// Like [`NormalizeOutput::from_changes`], trying `edits` before the
+    /// prefix and suffix fallback if the changes do not spell out every
+    /// edit: `(position, original length, normalized length)` spans in text
+    /// order, e.g. the words a normalizer changed.
+    pub(crate) fn from_edits(
+        original: String,
+        normalized: String,
+        changes: Vec<ChangeRecord>,
+        edits: &[(usize, usize, usize)],
+    ) -> Self {
+        let from: Vec<char> = original.chars().collect();
+        let to: Vec<char> = normalized.chars().collect();
+        let change_edits: Vec<(usize, usize, usize)> = changes
+            .iter()
+            .map(|change| (change.position, change.original.chars().count(), change.normalized.chars().count()))
+            .collect();
+        let offsets = edit_offsets(&from, &to, &change_edits)
+            .or_else(|| edit_offsets(&from, &to, edits))
+            .unwrap_or_else(|| affix_offsets(&from, &to));
+        NormalizeOutput {
+            original,
+            normalized,
+            changes,
+            offsets,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// The original char range that the normalized char range `range` came
+    /// from.
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        self.offsets[range.start]..self.offsets[range.end]
+    }
+
+    /// The inverse of [`NormalizeOutput::offsets`]: the character offset in
+    /// `normalized` of each character of `original`, then the length of
+    /// `normalized`. Original characters `a..b` became normalized
+    /// characters `map[a]..map[b]`; a deleted character maps to the
+    /// normalized character after it.
+    pub fn normalized_offsets(&self) -> Vec<usize> {
+        let original_len = self.offsets[self.offsets.len() - 1];
+        let mut map = Vec::with_capacity(original_len + 1);
+        let mut k = 0;
+        for i in 0..original_len {
+            while k + 1 < self.offsets.len() && self.offsets[k] < i {
+                k += 1;
+            }
+            map.push(k);
+        }
+        map.push(self.offsets.len() - 1);
+        map
+    }
+
+    /// The normalized char range that the original char range `range`
+    /// became.
+    pub fn normalized_range(&self, range: Range<usize>) -> Range<usize> {
+        let map = self.normalized_offsets();
+        map[range.start]..map[range.end]
+    }
+
+    /// This output followed by `next`, which normalized this output's
+    /// `normalized` text: the offsets of `next` are mapped through this
+    /// output's, and its change and warning positions with them. `stage`
+    /// prefixes the rules of the changes of `next` (`"uv: consonantal"`)
+    /// and the messages of its warnings.
+    pub fn then(mut self, next: NormalizeOutput, stage: &str) -> Self {
+        let offsets: Vec<usize> = next.offsets.iter().map(|&k| self.offsets[k]).collect();
+        self.changes.extend(next.changes.into_iter().map(|change| ChangeRecord {
+            position: self.offsets[change.position],
+            rule: format!("{stage}: {}", change.rule),
+            ..change
+        }));
+        for warning in next.warnings {
+            let warning = map_warning(&self.offsets, warning);
+            self.warnings.push(Warning {
+                message: format!("{stage}: {}", warning.message),
+                ..warning
+            });
+        }
+        self.normalized = next.normalized;
+        self.offsets = offsets;
+        self
+    }
+
+    /// Add on `next`, the output for the text after this output's
+    /// `original`.
+    fn append(&mut self, next: NormalizeOutput) {
+        let base = self.offsets.pop().expect("offsets end with the original length");
+        self.offsets.extend(next.offsets.iter().map(|&k| base + k));
+        self.changes.extend(next.changes.into_iter().map(|change| ChangeRecord {
+            position: base + change.position,
+            ..change
+        }));
+        self.warnings.extend(next.warnings.into_iter().map(|warning| Warning {
+            position: base + warning.position,
+            ..warning
+        }));
+        self.original.push_str(&next.original);
+        self.normalized.push_str(&next.normalized);
+    }
+}
+
+/// `warning`, about a text whose chars came from the chars `offsets` gives
+/// of an earlier text, moved onto that text.
+fn map_warning(offsets: &[usize], warning: Warning) -> Warning {
+    let start = offsets[warning.position.min(offsets.len() - 1)];
+    let end = offsets[(warning.position + warning.len).min(offsets.len() - 1)];
+    Warning {
+        position: start,
+        len: end.saturating_sub(start),
+        ..warning
+    }
+}
+
+/// The offset map of `edits`, or `None` if they overlap or do not turn
+/// `from` into `to`.
+fn edit_offsets(from: &[char], to: &[char], edits: &[(usize, usize, usize)]) -> Option<Vec<usize>> {
+    let mut offsets = Vec::with_capacity(to.len() + 1);
+    let mut pos = 0;
+    for &(position, len, replacement) in edits {
+        if position < pos || position + len > from.len() {
+            return None;
+        }
+        offsets.extend(pos..position);
+        offsets.extend((0..replacement).map(|k| position + k.min(len.saturating_sub(1))));
+        pos = position + len;
+    }
+    offsets.extend(pos..from.len());
+    if offsets.len() != to.len() {
+        return None;
+    }
+    // Characters outside the edits must be copied through
+    let mut edit = edits.iter().peekable();
+    let mut k = 0;
+    while k < to.len() {
+        if let Some(&&(position, _, replacement)) = edit.peek() {
+            if offsets[k] >= position {
+                k += replacement;
+                edit.next();
+                continue;
+            }
+        }
+        if from[offsets[k]] != to[k] {
+            return None;
+        }
+        k += 1;
+    }
+    offsets.push(from.len());
+    Some(offsets)
+}
+
+/// A coarse offset map: the common prefix and suffix of `from` and `to` map
+/// one to one, and the differing middle to its start.
+fn affix_offsets(from: &[char], to: &[char]) -> Vec<usize> {
+    let prefix = from.iter().zip(to).take_while(|(a, b)| a == b).count();
+    let suffix = from[prefix..].iter().rev().zip(to[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let mut offsets: Vec<usize> = (0..prefix).collect();
+    offsets.extend(std::iter::repeat_n(prefix, to.len() - prefix - suffix));
+    offsets.extend(from.len() - suffix..=from.len());
+    offsets
+}
+
+/// A text normalizer with a uniform interface.
+pub trait Normalizer: Send + Sync {
+    /// The normalizer's stage name, as used in pipeline change records and
+    /// by [`stage`].
+    fn name(&self) -> &str;
+
+    fn normalize(&self, text: &str) -> NormalizeOutput;
+
+    fn normalize_text(&self, text: &str) -> String {
+        self.normalize(text).normalized
+    }
+}
+
+/// The built-in stages, which [`stage`] builds by name.
+pub const STAGES: &[&str] = &[
+    "unicode",
+    "dehyphenate",
+    "markup",
+    "ligatures",
+    "abbreviations",
+    "nasal_bars",
+    "long_s",
+    "ocr",
+    "uv",
+    "medieval",
+    "loanwords",
+    "epigraphic",
+    "assimilation",
+    "accents",
+    "recase",
+    "enclitics",
+];
+
+/// A new normalizer for the stage `name`: one of [`STAGES`] in its default
+/// configuration, or a stage added to the [`registry`](crate::registry).
+pub fn stage(name: &str) -> Option<Box<dyn Normalizer>> {
+    crate::registry::get(name)
+}
+
+/// The built-in stage `name`, one of [`STAGES`], in its default
+/// configuration.
+pub(crate) fn builtin(name: &str) -> Option<Box<dyn Normalizer>> {
+    use crate::*;
+    Some(match name {
+        "unicode" => Box::new(unicode::Form::default()),
+        "dehyphenate" => Box::new(dehyphenate::Dehyphenator::new()),
+        "markup" => Box::new(cleanup::MarkupCleaner::new()),
+        "ligatures" => Box::new(ligatures::Capitals::default()),
+        "abbreviations" => Box::new(abbrev::Abbreviations::default()),
+        "nasal_bars" => Box::new(nasal::NasalBars::default()),
+        "long_s" => Box::new(long_s::LongSNormalizer::new()),
+        "ocr" => Box::new(ocr::OcrCorrector::new()),
+        "uv" => Box::new(uv::UvNormalizer::new()),
+        "medieval" => Box::new(medieval::MedievalNormalizer::default()),
+        "loanwords" => Box::new(loanwords::LoanNormalizer::new()),
+        "epigraphic" => Box::new(epigraphic::EpigraphicNormalizer::default()),
+        "assimilation" => Box::new(assimilation::Style::default()),
+        "accents" => Box::new(accents::AccentStripper::new()),
+        "recase" => Box::new(recase::Recaser::new()),
+        "enclitics" => Box::new(enclitics::EncliticSplitter::new()),
+        _ => return None,
+    })
+}
+
+/// A stage from a function, for fixes that need no normalizer of their own.
+///
+/// It reports no change records, and its offsets map the common prefix and
+/// suffix of its input and output one to one and the differing middle to
+/// its start. Implement [`Normalizer`] instead where finer offsets matter.
+///
+/// ```
+/// use latincy_preprocess::pipeline::{FnNormalizer, Normalizer, Pipeline};
+/// use latincy_preprocess::uv::UvNormalizer;
+///
+/// let fix = FnNormalizer::new("tironian", |text: &str| text.replace('⁊', "et"));
+/// let pipeline = Pipeline::new().stage(fix).stage(UvNormalizer::new());
+/// assert_eq!(pipeline.normalize_text("uir ⁊ uxor"), "vir et uxor");
+/// ```
+pub struct FnNormalizer<F> {
+    name: String,
+    f: F,
+}
+
+impl<F: Fn(&str) -> String + Send + Sync> FnNormalizer<F> {
+    pub fn new(name: &str, f: F) -> Self {
+        FnNormalizer { name: name.to_string(), f }
+    }
+}
+
+impl<F: Fn(&str) -> String + Send + Sync> Normalizer for FnNormalizer<F> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        NormalizeOutput::from_changes(text.to_string(), (self.f)(text), Vec::new())
+    }
+}
+
+/// Normalizers run one after another.
+///
+/// ```
+/// use latincy_preprocess::long_s::LongSNormalizer;
+/// use latincy_preprocess::pipeline::{Normalizer, Pipeline};
+/// use latincy_preprocess::uv::UvNormalizer;
+///
+/// let pipeline = Pipeline::new().stage(LongSNormalizer::new()).stage(UvNormalizer::new());
+/// let output = pipeline.normalize("ﬅat ſeruus");
+/// assert_eq!(output.normalized, "stat servus");
+/// assert_eq!(output.original_range(0..4), 0..3);
+/// assert_eq!(output.changes.len(), 3);
+/// ```
+#[derive(Default)]
+pub struct Pipeline {
+    stages: Vec<Box<dyn Normalizer>>,
+    detectors: Vec<Detector>,
+}
+
+impl Pipeline {
+    /// An empty pipeline, which leaves text as is.
+    pub fn new() -> Self {
+        Pipeline::default()
+    }
+
+    /// Run `normalizer` after the stages so far.
+    pub fn stage(mut self, normalizer: impl Normalizer + 'static) -> Self {
+        self.stages.push(Box::new(normalizer));
+        self
+    }
+
+    /// Run `normalizer` after the stages so far.
+    pub fn push(&mut self, normalizer: Box<dyn Normalizer>) {
+        self.stages.push(normalizer);
+    }
+
+    /// Run `normalizer` as stage `index`, before the stage now at `index`
+    /// if any, e.g. to put a project-specific fix between built-in stages.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is greater than [`Pipeline::len`].
+    pub fn insert(&mut self, index: usize, normalizer: Box<dyn Normalizer>) {
+        self.stages.insert(index, normalizer);
+    }
+
+    /// The stage names, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.stages.iter().map(|stage| stage.name())
+    }
+
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Copy the spans `detector` finds verbatim, whatever the stages would
+    /// make of them (see [`protect`](crate::protect)).
+    pub fn protect(mut self, detector: Detector) -> Self {
+        if !self.detectors.contains(&detector) {
+            self.detectors.push(detector);
+        }
+        self
+    }
+
+    /// The detectors of the spans the pipeline protects, in the order they
+    /// were added.
+    pub fn detectors(&self) -> &[Detector] {
+        &self.detectors
+    }
+
+    /// The spans of `text` the pipeline protects.
+    pub fn protected_spans(&self, text: &str) -> ProtectedSpans {
+        ProtectedSpans::detect(text, &self.detectors)
+    }
+
+    /// The output of each stage, each against the text the stage was given,
+    /// with the spans the pipeline protects copied through.
+    pub fn normalize_stages(&self, text: &str) -> Vec<NormalizeOutput> {
+        self.normalize_stages_protected(text, &ProtectedSpans::new())
+    }
+
+    /// [`Pipeline::normalize_stages`], copying `spans` through every stage
+    /// as well as those the pipeline detects. The stages are run on the text
+    /// between the spans, a piece at a time, and never see the spans.
+    pub fn normalize_stages_protected(&self, text: &str, spans: &ProtectedSpans) -> Vec<NormalizeOutput> {
+        telemetry::pipeline(self.stages.len(), text.len(), || self.run_protected(text, spans))
+    }
+
+    /// `text` cut at `spans` and the spans the pipeline detects: each
+    /// piece, and whether it is protected. `None` if nothing is protected.
+    fn pieces(&self, text: &str, spans: &ProtectedSpans) -> Option<Vec<(String, bool)>> {
+        let mut spans = spans.clone();
+        spans.extend(&self.protected_spans(text));
+        if spans.is_empty() {
+            return None;
+        }
+        let chars: Vec<char> = text.chars().collect();
+        let mut pieces = Vec::new();
+        let mut pos = 0;
+        for range in spans.ranges() {
+            let range = range.start.min(chars.len())..range.end.min(chars.len());
+            if range.start > pos {
+                pieces.push((chars[pos..range.start].iter().collect(), false));
+            }
+            pieces.push((chars[range.clone()].iter().collect(), true));
+            pos = range.end;
+        }
+        if pos < chars.len() {
+            pieces.push((chars[pos..].iter().collect(), false));
+        }
+        Some(pieces)
+    }
+
+    fn run_protected(&self, text: &str, spans: &ProtectedSpans) -> Vec<NormalizeOutput> {
+        let Some(pieces) = self.pieces(text, spans) else {
+            return self.run_stages(text);
+        };
+        let mut outputs = vec![NormalizeOutput::unchanged(""); self.stages.len()];
+        for (piece, protected) in pieces {
+            let parts = match protected {
+                true => vec![NormalizeOutput::unchanged(&piece); self.stages.len()],
+                false => self.run_stages(&piece),
+            };
+            for (output, part) in outputs.iter_mut().zip(parts) {
+                output.append(part);
+            }
+        }
+        outputs
+    }
+
+    /// The text the stages make of `text`, copying `spans` through. Each
+    /// stage's output is dropped once the next stage has its text, after
+    /// its changes are counted into `summary`.
+    fn run_lean(&self, text: &str, spans: &ProtectedSpans, mut summary: Option<&mut ChangeSummary>) -> String {
+        telemetry::pipeline(self.stages.len(), text.len(), || {
+            let Some(pieces) = self.pieces(text, spans) else {
+                return self.run_stages_lean(text, summary);
+            };
+            let mut normalized = String::with_capacity(text.len());
+            for (piece, protected) in pieces {
+                match protected {
+                    true => normalized.push_str(&piece),
+                    false => normalized.push_str(&self.run_stages_lean(&piece, summary.as_deref_mut())),
+                }
+            }
+            normalized
+        })
+    }
+
+    fn run_stages_lean(&self, text: &str, mut summary: Option<&mut ChangeSummary>) -> String {
+        let mut text = text.to_string();
+        for (index, stage) in self.stages.iter().enumerate() {
+            let output = telemetry::stage(stage.name(), index, &text, || stage.normalize(&text));
+            if let Some(summary) = summary.as_deref_mut() {
+                summary.extend(stage.name(), &output.changes);
+            }
+            text = output.normalized;
+        }
+        text
+    }
+
+    fn run_stages(&self, text: &str) -> Vec<NormalizeOutput> {
+        let mut outputs: Vec<NormalizeOutput> = Vec::with_capacity(self.stages.len());
+        for (index, stage) in self.stages.iter().enumerate() {
+            let input = outputs.last().map_or(text, |output| output.normalized.as_str());
+            let output = telemetry::stage(stage.name(), index, input, || stage.normalize(input));
+            outputs.push(output);
+        }
+        outputs
+    }
+}
+
+impl Normalizer for Pipeline {
+    fn name(&self) -> &str {
+        "pipeline"
+    }
+
+    /// The composed output of every stage. Each change's rule is prefixed
+    /// with its stage name.
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        self.normalize_detailed(text).into_output()
+    }
+
+    /// The text alone, without keeping any stage's changes or offsets.
+    fn normalize_text(&self, text: &str) -> String {
+        self.run_lean(text, &ProtectedSpans::new(), None)
+    }
+}
+
+impl Pipeline {
+    /// The consolidated change log of every stage, with change positions in
+    /// `text`.
+    ///
+    /// ```
+    /// use latincy_preprocess::ligatures::Capitals;
+    /// use latincy_preprocess::pipeline::Pipeline;
+    /// use latincy_preprocess::uv::UvNormalizer;
+    ///
+    /// let pipeline = Pipeline::new().stage(Capitals::Auto).stage(UvNormalizer::new());
+    /// let result = pipeline.normalize_detailed("æuum");
+    /// assert_eq!(result.normalized, "aevum");
+    /// let uv = &result.changes[1];
+    /// assert_eq!((uv.stage.as_str(), uv.stage_position, uv.change.position), ("uv", 2, 1));
+    /// ```
+    pub fn normalize_detailed(&self, text: &str) -> DetailedResult {
+        self.normalize_detailed_protected(text, &ProtectedSpans::new())
+    }
+
+    /// [`Pipeline::normalize_detailed`], copying `spans` through verbatim
+    /// as well as those the pipeline detects.
+    ///
+    /// ```
+    /// use latincy_preprocess::pipeline::Pipeline;
+    /// use latincy_preprocess::protect::ProtectedSpans;
+    /// use latincy_preprocess::uv::UvNormalizer;
+    ///
+    /// let pipeline = Pipeline::new().stage(UvNormalizer::new());
+    /// let spans: ProtectedSpans = [0..4].into_iter().collect();
+    /// let result = pipeline.normalize_detailed_protected("uiae uiae", &spans);
+    /// assert_eq!(result.normalized, "uiae viae");
+    /// assert_eq!(result.changes[0].change.position, 5);
+    /// ```
+    pub fn normalize_detailed_protected(&self, text: &str, spans: &ProtectedSpans) -> DetailedResult {
+        let mut result = DetailedResult {
+            original: text.to_string(),
+            normalized: text.to_string(),
+            changes: Vec::new(),
+            offsets: (0..=text.chars().count()).collect(),
+            warnings: Vec::new(),
+        };
+        let outputs = self.normalize_stages_protected(text, spans);
+        for (index, (output, stage)) in outputs.into_iter().zip(self.names()).enumerate() {
+            result.warnings.extend(output.warnings.into_iter().map(|warning| StageWarning {
+                stage: stage.to_string(),
+                index,
+                warning: map_warning(&result.offsets, warning),
+            }));
+            result.changes.extend(output.changes.into_iter().map(|change| StageChange {
+                stage: stage.to_string(),
+                index,
+                stage_position: change.position,
+                change: ChangeRecord {
+                    position: result.offsets[change.position],
+                    ..change
+                },
+            }));
+            result.offsets = output.offsets.iter().map(|&k| result.offsets[k]).collect();
+            result.normalized = output.normalized;
+        }
+        result
+    }
+
+    /// `text` normalized, with as much of the change log as `detail` asks
+    /// for. [`OutputDetail::Full`] is [`Pipeline::normalize_detailed`];
+    /// the others count each stage's records and then drop them, rather
+    /// than composing them into one log.
+    ///
+    /// ```
+    /// use latincy_preprocess::changes::OutputDetail;
+    /// use latincy_preprocess::pipeline::{Outcome, Pipeline};
+    /// use latincy_preprocess::uv::UvNormalizer;
+    ///
+    /// let pipeline = Pipeline::new().stage(UvNormalizer::new());
+    /// let Outcome::Summary { normalized, summary } = pipeline.normalize_with_detail("uia uita", OutputDetail::Summary)
+    /// else {
+    ///     unreachable!()
+    /// };
+    /// assert_eq!(normalized, "via vita");
+    /// assert_eq!(summary.modules["uv"], 2);
+    /// ```
+    pub fn normalize_with_detail(&self, text: &str, detail: OutputDetail) -> Outcome {
+        self.normalize_with_detail_protected(text, &ProtectedSpans::new(), detail)
+    }
+
+    /// [`Pipeline::normalize_with_detail`], copying `spans` through
+    /// verbatim as well as those the pipeline detects.
+    pub fn normalize_with_detail_protected(&self, text: &str, spans: &ProtectedSpans, detail: OutputDetail) -> Outcome {
+        match detail {
+            OutputDetail::None => Outcome::Text(self.run_lean(text, spans, None)),
+            OutputDetail::Summary => {
+                let mut summary = ChangeSummary::new();
+                let normalized = self.run_lean(text, spans, Some(&mut summary));
+                Outcome::Summary { normalized, summary }
+            }
+            OutputDetail::Full => Outcome::Full(self.normalize_detailed_protected(text, spans)),
+        }
+    }
+}
+
+/// What [`Pipeline::normalize_with_detail`] returns, by [`OutputDetail`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Text(String),
+    Summary { normalized: String, summary: ChangeSummary },
+    Full(DetailedResult),
+}
+
+impl Outcome {
+    pub fn normalized(&self) -> &str {
+        match self {
+            Outcome::Text(normalized) | Outcome::Summary { normalized, .. } => normalized,
+            Outcome::Full(result) => &result.normalized,
+        }
+    }
+
+    /// The summary, counted from the records of a full result; `None` for
+    /// the text alone.
+    pub fn summary(&self) -> Option<ChangeSummary> {
+        match self {
+            Outcome::Text(_) => None,
+            Outcome::Summary { summary, .. } => Some(summary.clone()),
+            Outcome::Full(result) => Some(result.summary()),
+        }
+    }
+}
+
+/// One change of a [`Pipeline`] run, with the stage that made it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageChange {
+    /// The name of the stage.
+    pub stage: String,
+    /// The index of the stage in the pipeline.
+    pub index: usize,
+    /// The character offset of the change in the text the stage was given.
+    pub stage_position: usize,
+    /// The change, with its `position` in the pipeline's input. Its
+    /// `original` and `context` are those of the text the stage was given.
+    pub change: ChangeRecord,
+}
+
+/// One warning of a [`Pipeline`] run, with the stage that reported it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageWarning {
+    pub stage: String,
+    pub index: usize,
+    /// The warning, with its `position` and `len` in the pipeline's input.
+    /// Its `context` is that of the text the stage was given.
+    pub warning: Warning,
+}
+
+/// The consolidated change log of [`Pipeline::normalize_detailed`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    /// The changes of every stage, stage by stage.
+    pub changes: Vec<StageChange>,
+    /// As [`NormalizeOutput::offsets`], composed through every stage.
+    pub offsets: Vec<usize>,
+    /// The warnings of every stage, stage by stage.
+    pub warnings: Vec<StageWarning>,
+}
+
+impl DetailedResult {
+    /// Counts of the changes, per rule and per stage.
+    pub fn summary(&self) -> ChangeSummary {
+        let mut summary = ChangeSummary::new();
+        for StageChange { stage, change, .. } in &self.changes {
+            summary.add(stage, change);
+        }
+        summary
+    }
+
+    /// The result as a [`NormalizeOutput`], each rule and warning message
+    /// prefixed with its stage (`"uv: consonantal"`).
+    pub fn into_output(self) -> NormalizeOutput {
+        let changes = self
+            .changes
+            .into_iter()
+            .map(|StageChange { stage, change, .. }| ChangeRecord {
+                rule: format!("{stage}: {}", change.rule),
+                ..change
+            })
+            .collect();
+        let warnings = self
+            .warnings
+            .into_iter()
+            .map(|StageWarning { stage, warning, .. }| Warning {
+                message: format!("{stage}: {}", warning.message),
+                ..warning
+            })
+            .collect();
+        NormalizeOutput {
+            original: self.original,
+            normalized: self.normalized,
+            changes,
+            offsets: self.offsets,
+            warnings,
+        }
+    }
+
+    /// The result as a JSON object with the original and normalized text,
+    /// the offsets, the changes, each with the [`ChangeRecord`] fields plus
+    /// `stage`, `index`, and `stage_position`, and the warnings, each with
+    /// the [`Warning`] fields plus `stage` and `index`.
+    pub fn to_json(&self) -> String {
+        let changes: Vec<serde_json::Value> = self
+            .changes
+            .iter()
+            .map(|StageChange { stage, index, stage_position, change }| {
+                serde_json::json!({
+                    "stage": stage,
+                    "index": index,
+                    "position": change.position,
+                    "stage_position": stage_position,
+                    "original": change.original,
+                    "normalized": change.normalized,
+                    "pass": change.pass,
+                    "rule": change.rule,
+                    "context": change.context,
+                })
+            })
+            .collect();
+        let warnings: Vec<serde_json::Value> = self
+            .warnings
+            .iter()
+            .map(|StageWarning { stage, index, warning }| {
+                serde_json::json!({
+                    "stage": stage,
+                    "index": index,
+                    "position": warning.position,
+                    "len": warning.len,
+                    "kind": warning.kind.name(),
+                    "message": warning.message,
+                    "context": warning.context,
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "original": self.original,
+            "normalized": self.normalized,
+            "changes": changes,
+            "offsets": self.offsets,
+            "warnings": warnings,
+        })
+        .to_string()
+    }
+}
+
+impl std::fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("stages", &self.names().collect::<Vec<_>>())
+            .field("detectors", &self.detectors)
+            .finish()
+    }
+}
+
+/// Input [`normalize_stream`] gathers before it stops waiting for a
+/// paragraph break.
+pub const STREAM_CHUNK_BYTES: usize = 1 << 20;
+
+/// Normalize text read from `reader` into `writer` a paragraph at a time,
+/// so input of any size runs in bounded memory. Paragraphs, ended by a
+/// blank line, are normalized whole, because stages such as `dehyphenate`
+/// look across line breaks; one longer than [`STREAM_CHUNK_BYTES`] is cut at
+/// the next line that does not end in a hyphenated break, and a line that
+/// long at its last whitespace before the limit. The output is what
+/// [`Normalizer::normalize_text`] gives for the whole input unless a stage
+/// looks across paragraphs.
+pub fn normalize_stream<R: BufRead, W: Write>(normalizer: &dyn Normalizer, reader: R, writer: W) -> io::Result<()> {
+    normalize_stream_with(normalizer, reader, writer, STREAM_CHUNK_BYTES, &mut |_| Ok(()))
+}
+
+/// A cooperative cancellation flag. Clones share the flag, so a caller can
+/// hand one to a long run and cancel it from another thread; the run stops
+/// at its next paragraph.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// How far a monitored run has got (see [`normalize_monitored`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Bytes of input normalized so far.
+    pub bytes: usize,
+    /// Bytes of input in all, if known.
+    pub total_bytes: Option<usize>,
+    /// Words (whitespace-separated tokens) normalized so far.
+    pub words: usize,
+}
+
+/// [`normalize_stream`], calling `progress` after each paragraph and
+/// stopping with an [`io::ErrorKind::Interrupted`] error once `cancel` is
+/// cancelled. What was written before then is complete paragraphs.
+pub fn normalize_stream_monitored<R: BufRead, W: Write>(
+    normalizer: &dyn Normalizer,
+    reader: R,
+    writer: W,
+    progress: &mut dyn FnMut(&Progress),
+    cancel: Option<&CancelToken>,
+) -> io::Result<()> {
+    monitored(normalizer, reader, writer, None, progress, cancel)
+}
+
+/// `normalizer` run over `text` a paragraph at a time, as by
+/// [`normalize_stream_monitored`], for progress reports and cancellation
+/// during long texts.
+pub fn normalize_monitored(
+    normalizer: &dyn Normalizer,
+    text: &str,
+    progress: &mut dyn FnMut(&Progress),
+    cancel: Option<&CancelToken>,
+) -> io::Result<String> {
+    let mut out = Vec::with_capacity(text.len());
+    monitored(normalizer, text.as_bytes(), &mut out, Some(text.len()), progress, cancel)?;
+    String::from_utf8(out).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn monitored<R: BufRead, W: Write>(
+    normalizer: &dyn Normalizer,
+    reader: R,
+    writer: W,
+    total_bytes: Option<usize>,
+    progress: &mut dyn FnMut(&Progress),
+    cancel: Option<&CancelToken>,
+) -> io::Result<()> {
+    let check = || match cancel.is_some_and(CancelToken::is_cancelled) {
+        true => Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled")),
+        false => Ok(()),
+    };
+    check()?;
+    let mut done = Progress { bytes: 0, total_bytes, words: 0 };
+    normalize_stream_with(normalizer, reader, writer, STREAM_CHUNK_BYTES, &mut |chunk| {
+        done.bytes += chunk.len();
+        done.words += chunk.split_whitespace().count();
+        progress(&done);
+        check()
+    })
+}
+
+/// The streaming loop. `on_chunk` is called with each chunk once it is
+/// written; an error from it ends the run.
+fn normalize_stream_with<R: BufRead, W: Write>(
+    normalizer: &dyn Normalizer,
+    mut reader: R,
+    mut writer: W,
+    chunk_bytes: usize,
+    on_chunk: &mut dyn FnMut(&str) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut buffer = String::new();
+    let mut carry = Vec::new();
+    loop {
+        let start = buffer.len();
+        let read = read_piece(&mut reader, &mut carry, &mut buffer, chunk_bytes)?;
+        let line = &buffer[start..];
+        let paragraph_end = line.trim().is_empty() && start > 0;
+        let cut = buffer.len() >= chunk_bytes && !crate::long_s::ends_with_line_break_hyphen(line);
+        if read > 0 && !paragraph_end && !cut {
+            continue;
+        }
+        if !buffer.is_empty() {
+            writer.write_all(normalizer.normalize_text(&buffer).as_bytes())?;
+            on_chunk(&buffer)?;
+            buffer.clear();
+        }
+        if read == 0 {
+            return writer.flush();
+        }
+    }
+}
+
+/// Read the next line of `reader` onto `buffer`, as [`BufRead::read_line`]
+/// does, but end a line longer than `limit` bytes after its last whitespace
+/// before the limit (in a word that long, the first after it), keeping the
+/// rest in `carry` for the next call, so that input without line breaks is
+/// still read in bounded pieces. Returns the bytes appended.
+fn read_piece<R: BufRead>(reader: &mut R, carry: &mut Vec<u8>, buffer: &mut String, limit: usize) -> io::Result<usize> {
+    let mut bytes = std::mem::take(carry);
+    let mut scanned = 0;
+    loop {
+        let read = Read::take(&mut *reader, limit.max(1) as u64).read_until(b'\n', &mut bytes)?;
+        if read == 0 || bytes.ends_with(b"\n") {
+            break;
+        }
+        if bytes.len() >= limit {
+            // ASCII whitespace is never part of a multibyte character
+            if let Some(i) = bytes[scanned..].iter().rposition(u8::is_ascii_whitespace) {
+                *carry = bytes.split_off(scanned + i + 1);
+                break;
+            }
+            scanned = bytes.len();
+        }
+    }
+    let text = String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    buffer.push_str(&text);
+    Ok(text.len())
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+/// The first exception raised by a callback stage during a pipeline run.
+#[cfg(feature = "pyo3-backend")]
+type ErrorSlot = std::sync::Arc<std::sync::Mutex<Option<PyErr>>>;
+
+/// A stage backed by a Python function. `Normalizer::normalize` cannot
+/// fail, so an exception leaves the text as is and is stored in `error`, to
+/// be raised once the run is over.
+#[cfg(feature = "pyo3-backend")]
+struct CallbackNormalizer {
+    name: String,
+    func: Py<PyAny>,
+    error: ErrorSlot,
+}
+
+#[cfg(feature = "pyo3-backend")]
+impl CallbackNormalizer {
+    /// The output of one call: `func(text)` returns the normalized text, or
+    /// a `(text, changes)` tuple whose changes are `ChangeRecord`s or dicts
+    /// with `position`, `original`, `normalized`, and optionally `rule` and
+    /// `pass`.
+    fn call(&self, py: Python<'_>, text: &str) -> PyResult<NormalizeOutput> {
+        let result = self.func.bind(py).call1((text,))?;
+        let (normalized, changes): (String, Vec<Bound<'_, PyAny>>) = match result.extract::<String>() {
+            Ok(normalized) => (normalized, Vec::new()),
+            Err(_) => result.extract().map_err(|_| {
+                pyo3::exceptions::PyTypeError::new_err(format!(
+                    "stage {:?} must return a str or a (str, list of change dicts) tuple",
+                    self.name
+                ))
+            })?,
+        };
+        let chars: Vec<char> = text.chars().collect();
+        let mut records = Vec::with_capacity(changes.len());
+        for change in changes {
+            let change = match change.downcast::<PyChangeRecord>() {
+                Ok(record) => record.get().to_dict(py)?,
+                Err(_) => change.downcast_into::<PyDict>()?,
+            };
+            let field = |key: &str| -> PyResult<Option<Bound<'_, PyAny>>> { change.get_item(key) };
+            let required = |key: &str| {
+                field(key)?.ok_or_else(|| {
+                    pyo3::exceptions::PyKeyError::new_err(format!("stage {:?} change is missing {key:?}", self.name))
+                })
+            };
+            let position: usize = required("position")?.extract()?;
+            let original: String = required("original")?.extract()?;
+            let len = original.chars().count();
+            if position + len > chars.len() {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "stage {:?} change at {position} is past the end of the text",
+                    self.name
+                )));
+            }
+            records.push(ChangeRecord {
+                position,
+                normalized: required("normalized")?.extract()?,
+                pass: field("pass")?.map(|pass| pass.extract()).transpose()?.unwrap_or(1),
+                rule: field("rule")?.map(|rule| rule.extract()).transpose()?.unwrap_or_else(|| self.name.clone()),
+                context: crate::changes::context(&chars, position, len),
+                original,
+            });
+        }
+        Ok(NormalizeOutput::from_changes(text.to_string(), normalized, records))
+    }
+}
+
+#[cfg(feature = "pyo3-backend")]
+impl Normalizer for CallbackNormalizer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        Python::with_gil(|py| {
+            self.call(py, text).unwrap_or_else(|e| {
+                let mut error = self.error.lock().unwrap_or_else(|e| e.into_inner());
+                error.get_or_insert(e);
+                NormalizeOutput::unchanged(text)
+            })
+        })
+    }
+}
+
+/// A Python function as a pipeline stage, exposed as `_rust.CallbackStage`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "CallbackStage", frozen)]
+pub struct PyCallbackStage {
+    name: String,
+    func: Py<PyAny>,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyCallbackStage {
+    /// `func(text)` returns the normalized text, or a `(text, changes)`
+    /// tuple of the text and change dicts with `position`, `original`,
+    /// `normalized`, and optionally `rule` and `pass`. The stage is named
+    /// `name`, or after the function.
+    #[new]
+    #[pyo3(signature = (func, name=None))]
+    fn new(func: Bound<'_, PyAny>, name: Option<String>) -> PyResult<Self> {
+        if !func.is_callable() {
+            return Err(pyo3::exceptions::PyTypeError::new_err("a callback stage needs a callable"));
+        }
+        let name = match name {
+            Some(name) => name,
+            None => callable_name(&func),
+        };
+        Ok(PyCallbackStage { name, func: func.unbind() })
+    }
+
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CallbackStage(name={:?})", self.name)
+    }
+}
+
+/// The `__name__` of a Python callable, or `"custom"` without one.
+#[cfg(feature = "pyo3-backend")]
+fn callable_name(func: &Bound<'_, PyAny>) -> String {
+    func.getattr("__name__").and_then(|name| name.extract()).unwrap_or_else(|_| "custom".to_string())
+}
+
+/// The normalizer for a Python pipeline stage: a registered stage name, one of
+/// the `_rust` normalizer objects, or a callback stage or plain callable,
+/// whose exceptions go to `error`.
+#[cfg(feature = "pyo3-backend")]
+fn stage_from_py(stage: &Bound<'_, PyAny>, error: &ErrorSlot) -> PyResult<Box<dyn Normalizer>> {
+    use crate::*;
+    if let Ok(name) = stage.downcast::<PyString>() {
+        let name = name.to_cow()?;
+        return self::stage(&name).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown pipeline stage {name:?}; expected one of {}",
+                crate::registry::list().join(", ")
+            ))
+        });
+    }
+    macro_rules! extract {
+        ($($class:ty),*) => {
+            $(if let Ok(stage) = stage.downcast::<$class>() {
+                return Ok(Box::new(stage.borrow().inner.clone()));
+            })*
+        };
+    }
+    extract!(
+        uv::PyUvNormalizer,
+        long_s::PyLongSNormalizer,
+        abbrev::PyAbbreviations,
+        medieval::PyMedievalNormalizer,
+        diphthongs::PyDiphthongRestorer,
+        cleanup::PyMarkupCleaner,
+        ocr::PyOcrCorrector,
+        dehyphenate::PyDehyphenator,
+        recase::PyRecaser,
+        epigraphic::PyEpigraphicNormalizer,
+        enclitics::PyEncliticSplitter
+    );
+    let (name, func) = match stage.downcast::<PyCallbackStage>() {
+        Ok(callback) => (callback.get().name.clone(), callback.get().func.clone_ref(stage.py())),
+        Err(_) if stage.is_callable() => (callable_name(stage), stage.clone().unbind()),
+        Err(_) => {
+            return Err(pyo3::exceptions::PyTypeError::new_err(format!(
+                "a pipeline stage must be a stage name, a normalizer, or a callable, not {}",
+                stage.get_type().name()?
+            )))
+        }
+    };
+    Ok(Box::new(CallbackNormalizer {
+        name,
+        func,
+        error: std::sync::Arc::clone(error),
+    }))
+}
+
+/// Python view of [`Pipeline`], exposed as `_rust.Pipeline`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "Pipeline", frozen)]
+pub struct PyPipeline {
+    pub(crate) inner: Pipeline,
+    error: ErrorSlot,
+    /// Whether any stage is a Python function.
+    python_stages: bool,
+}
+
+#[cfg(feature = "pyo3-backend")]
+impl PyPipeline {
+    /// A pipeline of Rust stages only.
+    pub(crate) fn from_pipeline(inner: Pipeline) -> Self {
+        PyPipeline {
+            inner,
+            error: ErrorSlot::default(),
+            python_stages: false,
+        }
+    }
+
+    /// `result`, or the exception a callback stage raised while computing
+    /// it.
+    pub(crate) fn checked<T>(&self, result: T) -> PyResult<T> {
+        match self.error.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            Some(error) => Err(error),
+            None => Ok(result),
+        }
+    }
+
+    /// Whether any stage is a Python function.
+    pub(crate) fn python_stages(&self) -> bool {
+        self.python_stages
+    }
+
+    /// `f()`, with the GIL released for long texts unless a stage is a
+    /// Python function: that would take the GIL back for every call, and
+    /// runs on other threads could pick up its exceptions.
+    pub(crate) fn allow_threads<T, F>(&self, py: Python<'_>, text: &str, f: F) -> T
+    where
+        F: pyo3::marker::Ungil + FnOnce() -> T,
+        T: pyo3::marker::Ungil,
+    {
+        if self.python_stages {
+            f()
+        } else {
+            crate::allow_threads(py, text, f)
+        }
+    }
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyPipeline {
+    /// `stages` are stage names (`"uv"`, `"long_s"`), for the default
+    /// configuration, configured normalizer objects, or `CallbackStage`s
+    /// and plain functions from `str` to `str`. An exception in a function
+    /// is raised once the run is over. `protect` names the detectors
+    /// (`"urls"`, `"entities"`, `"greek"`, `"citations"`) of spans to copy
+    /// verbatim.
+    #[new]
+    #[pyo3(signature = (stages=Vec::new(), protect=Vec::new()))]
+    fn new(stages: Vec<Bound<'_, PyAny>>, protect: Vec<String>) -> PyResult<Self> {
+        let error = ErrorSlot::default();
+        let mut inner = Pipeline::new();
+        for stage in &stages {
+            inner.push(stage_from_py(stage, &error)?);
+        }
+        for name in &protect {
+            let detector = Detector::from_name(name).ok_or_else(|| {
+                let names: Vec<&str> = Detector::ALL.iter().map(|d| d.name()).collect();
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "unknown detector {name:?}; expected one of {}",
+                    names.join(", ")
+                ))
+            })?;
+            inner = inner.protect(detector);
+        }
+        // Each Python stage holds a clone of the error slot
+        let python_stages = std::sync::Arc::strong_count(&error) > 1;
+        Ok(PyPipeline {
+            inner,
+            error,
+            python_stages,
+        })
+    }
+
+    /// The pipeline declared in a TOML or JSON config file (see
+    /// [`crate::config`]). A malformed config raises `ValueError`.
+    #[staticmethod]
+    fn from_config(path: std::path::PathBuf) -> PyResult<Self> {
+        let inner = Pipeline::from_config(path).map_err(crate::config::config_error)?;
+        Ok(PyPipeline::from_pipeline(inner))
+    }
+
+    /// Names of the stages, in order.
+    #[getter]
+    fn stages(&self) -> Vec<String> {
+        self.inner.names().map(str::to_string).collect()
+    }
+
+    /// Names of the detectors of protected spans.
+    #[getter]
+    fn protect(&self) -> Vec<&'static str> {
+        self.inner.detectors().iter().map(|d| d.name()).collect()
+    }
+
+    /// The `(start, end)` char spans of `text` the pipeline copies
+    /// verbatim.
+    fn protected_spans(&self, text: &str) -> Vec<(usize, usize)> {
+        self.inner.protected_spans(text).ranges().iter().map(|r| (r.start, r.end)).collect()
+    }
+
+    /// `text` normalized. With `progress` or `cancel`, the text is
+    /// normalized a paragraph at a time: `progress(bytes, total_bytes,
+    /// words)` is called after each paragraph, and once the `CancelToken`
+    /// `cancel` is cancelled, or `progress` raises, the run stops and raises
+    /// `InterruptedError` or the callback's exception.
+    #[pyo3(signature = (text, progress=None, cancel=None))]
+    fn normalize(
+        &self,
+        py: Python<'_>,
+        text: &str,
+        progress: Option<Bound<'_, PyAny>>,
+        cancel: Option<PyRef<'_, PyCancelToken>>,
+    ) -> PyResult<String> {
+        if progress.is_none() && cancel.is_none() {
+            let result = self.allow_threads(py, text, || self.inner.normalize_text(text));
+            return self.checked(result);
+        }
+        // Stopped by the caller's token or by an exception in `progress`
+        let stop = CancelToken::new();
+        let progress = progress.map(Bound::unbind);
+        let cancel = cancel.map(|token| token.inner.clone());
+        let cancelled = || cancel.as_ref().is_some_and(CancelToken::is_cancelled);
+        if cancelled() {
+            stop.cancel();
+        }
+        let mut raised = None;
+        let result = self.allow_threads(py, text, || {
+            let mut report = |p: &Progress| {
+                if let Some(progress) = &progress {
+                    if let Err(e) = Python::with_gil(|py| progress.call1(py, (p.bytes, p.total_bytes, p.words))) {
+                        raised.get_or_insert(e);
+                    }
+                }
+                if raised.is_some() || cancelled() {
+                    stop.cancel();
+                }
+            };
+            normalize_monitored(&self.inner, text, &mut report, Some(&stop))
+        });
+        if let Some(error) = raised {
+            return Err(error);
+        }
+        self.checked(result)?.map_err(PyErr::from)
+    }
+
+    /// A `DetailedResult` of `original`, `normalized`, `changes` (with
+    /// positions in `original`, rules prefixed by their stage, and `stage`,
+    /// `index`, and `stage_position` set), `offsets`, and `warnings` (with
+    /// `stage` set). `protected` lists
+    /// `(start, end)` char spans to copy verbatim besides those the
+    /// pipeline detects. `detail="summary"` keeps a `ChangeSummary` as
+    /// `summary` instead of the changes, and `detail="none"` just the
+    /// text; either leaves `changes` empty.
+    #[pyo3(signature = (text, protected=Vec::new(), detail="full"))]
+    fn normalize_detailed(
+        &self,
+        py: Python<'_>,
+        text: &str,
+        protected: Vec<(usize, usize)>,
+        detail: &str,
+    ) -> PyResult<PyDetailedResult> {
+        let detail = OutputDetail::from_name(detail).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown detail {:?}: expected \"none\", \"summary\", or \"full\"",
+                detail
+            ))
+        })?;
+        let spans: ProtectedSpans = protected.into_iter().map(|(start, end)| start..end).collect();
+        if detail != OutputDetail::Full {
+            let outcome = self.checked(
+                self.allow_threads(py, text, || self.inner.normalize_with_detail_protected(text, &spans, detail)),
+            )?;
+            let mut result = PyDetailedResult::new(text, outcome.normalized(), PyList::empty(py));
+            if let Some(summary) = outcome.summary() {
+                result.summary = Some(Py::new(py, PyChangeSummary { inner: summary })?);
+            }
+            return Ok(result);
+        }
+        let (stages, warning_stages, output) = self.checked(self.allow_threads(py, text, || {
+            let result = self.inner.normalize_detailed_protected(text, &spans);
+            let stages: Vec<(String, usize, usize)> =
+                result.changes.iter().map(|c| (c.stage.clone(), c.index, c.stage_position)).collect();
+            let warning_stages: Vec<String> = result.warnings.iter().map(|w| w.stage.clone()).collect();
+            (stages, warning_stages, result.into_output())
+        }))?;
+        let changes = PyList::empty(py);
+        for (change, stage) in output.changes.iter().zip(stages) {
+            changes.append(PyChangeRecord { inner: change.clone(), stage: Some(stage) })?;
+        }
+        let warnings = PyList::empty(py);
+        for (warning, stage) in output.warnings.into_iter().zip(warning_stages) {
+            warnings.append(PyWarning { inner: warning, stage: Some(stage) })?;
+        }
+        let mut detailed = PyDetailedResult::new(&output.original, &output.normalized, changes);
+        detailed.offsets = Some(output.offsets);
+        detailed.warnings = Some(warnings.unbind());
+        Ok(detailed)
+    }
+
+    /// `(normalized, old_to_new, new_to_old)`: `text` normalized, the char
+    /// index in `normalized` of each char of `text`, and the char index in
+    /// `text` of each char of `normalized`. Each map ends with the length
+    /// of the other text, so a char span `a..b` of one text maps to
+    /// `map[a]..map[b]` of the other, e.g. to move spaCy token boundaries
+    /// across ligature or abbreviation expansion.
+    fn normalize_with_alignment(&self, py: Python<'_>, text: &str) -> PyResult<(String, Vec<usize>, Vec<usize>)> {
+        self.checked(self.allow_threads(py, text, || {
+            let output = self.inner.normalize(text);
+            let old_to_new = output.normalized_offsets();
+            (output.normalized, old_to_new, output.offsets)
+        }))
+    }
+
+    /// A dry run: the diff between `text` and its normalized form, unified
+    /// with `context` lines of context under the name `name`, or
+    /// word-level with `words=True`.
+    #[pyo3(signature = (text, name="text", words=false, context=3))]
+    fn diff(&self, py: Python<'_>, text: &str, name: &str, words: bool, context: usize) -> PyResult<String> {
+        self.checked(self.allow_threads(py, text, || {
+            let normalized = self.inner.normalize_text(text);
+            if words {
+                crate::diff::words(text, &normalized)
+            } else {
+                crate::diff::unified(text, &normalized, name, name, context)
+            }
+        }))
+    }
+
+    /// The proposed edits as a JSON Lines patch, one edit per line, for
+    /// review and `_rust.apply_patch`.
+    fn export_patch(&self, py: Python<'_>, text: &str) -> PyResult<String> {
+        self.checked(self.allow_threads(py, text, || crate::patch::export_patch(&self.inner, text).to_jsonl()))
+    }
+
+    /// The consolidated change log as a JSON string, with unprefixed rules.
+    fn normalize_json(&self, py: Python<'_>, text: &str) -> PyResult<String> {
+        self.checked(self.allow_threads(py, text, || self.inner.normalize_detailed(text).to_json()))
+    }
+
+    /// A lazy iterator over `texts`, any iterable of strings, yielding each
+    /// normalized. With `threads` other than 1 (0 for one per core), it
+    /// takes `batch_size` texts at a time and normalizes them in parallel,
+    /// in a pool of its own; otherwise it takes one text at a time.
+    /// Pipelines with a Python function as a stage run on one thread.
+    #[pyo3(signature = (texts, threads=1, batch_size=64))]
+    fn normalize_iter(
+        slf: Bound<'_, Self>,
+        texts: &Bound<'_, PyAny>,
+        threads: usize,
+        batch_size: usize,
+    ) -> PyResult<PyNormalizeIter> {
+        if batch_size == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err("batch_size must be at least 1"));
+        }
+        let pool = match threads {
+            1 => None,
+            _ if slf.get().python_stages => None,
+            _ => Some(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?,
+            ),
+        };
+        Ok(PyNormalizeIter {
+            texts: texts.try_iter()?.unbind(),
+            batch_size: if pool.is_some() { batch_size } else { 1 },
+            pipeline: slf.unbind(),
+            pool,
+            ready: std::collections::VecDeque::new(),
+            error: None,
+        })
+    }
+
+    /// The Arrow string array or stream `array` (a pyarrow `Array` or
+    /// `ChunkedArray`, a Polars `Series`) normalized row by row, as an
+    /// `ArrowStrings` of the same string type; nulls stay null. The rows are
+    /// read without copying and normalized on `threads` threads (0 for one
+    /// per core). Non-string arrays raise `ValueError`.
+    #[cfg(feature = "arrow")]
+    #[pyo3(signature = (array, threads=0))]
+    fn normalize_arrow(
+        &self,
+        py: Python<'_>,
+        array: &Bound<'_, PyAny>,
+        threads: usize,
+    ) -> PyResult<crate::arrow::PyArrowStrings> {
+        crate::arrow::py_normalize_arrow(self, py, array, threads)
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Pipeline(stages={:?})", self.stages())
+    }
+}
+
+/// The iterator `Pipeline.normalize_iter` returns, exposed as
+/// `_rust.NormalizeIter`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "NormalizeIter")]
+pub struct PyNormalizeIter {
+    pipeline: Py<PyPipeline>,
+    texts: Py<pyo3::types::PyIterator>,
+    batch_size: usize,
+    pool: Option<rayon::ThreadPool>,
+    /// Normalized texts not yet yielded.
+    ready: std::collections::VecDeque<String>,
+    /// An error taking the next text, raised once the texts before it are
+    /// yielded.
+    error: Option<PyErr>,
+}
+
+#[cfg(feature = "pyo3-backend")]
+impl PyNormalizeIter {
+    /// Takes and normalizes the next batch of texts.
+    fn fill(&mut self, py: Python<'_>) -> PyResult<()> {
+        let mut texts = self.texts.bind(py).clone();
+        let mut batch = Vec::with_capacity(self.batch_size);
+        while batch.len() < self.batch_size {
+            match texts.next().map(|text| text.and_then(|text| text.extract::<String>())) {
+                Some(Ok(text)) => batch.push(text),
+                Some(Err(e)) => {
+                    self.error = Some(e);
+                    break;
+                }
+                None => break,
+            }
+        }
+        let pipeline = self.pipeline.get();
+        let normalized: Vec<String> = match &self.pool {
+            Some(pool) => {
+                use rayon::prelude::*;
+                let normalize = || batch.par_iter().map(|text| pipeline.inner.normalize_text(text)).collect();
+                py.allow_threads(|| pool.install(normalize))
+            }
+            None => batch
+                .iter()
+                .map(|text| pipeline.allow_threads(py, text, || pipeline.inner.normalize_text(text)))
+                .collect(),
+        };
+        self.ready.extend(pipeline.checked(normalized)?);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyNormalizeIter {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<String>> {
+        if self.ready.is_empty() && self.error.is_none() {
+            self.fill(py)?;
+        }
+        match self.ready.pop_front() {
+            Some(text) => Ok(Some(text)),
+            None => self.error.take().map_or(Ok(None), Err),
+        }
+    }
+}
+
+/// Python view of [`CancelToken`], exposed as `_rust.CancelToken`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "CancelToken", frozen)]
+#[derive(Default)]
+pub struct PyCancelToken {
+    inner: CancelToken,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyCancelToken {
+    #[new]
+    fn new() -> Self {
+        PyCancelToken::default()
+    }
+
+    /// Stop the runs given this token at their next paragraph.
+    fn cancel(&self) {
+        self.inner.cancel();
+    }
+
+    #[getter]
+    fn cancelled(&self) -> bool {
+        self.inner.is_cancelled()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CancelToken(cancelled={})", if self.inner.is_cancelled() { "True" } else { "False" })
+    }
+}
+
+/// `Pipeline.normalize_with_alignment` for `stages`, by default long-s
+/// correction then u/v normalization, as `latincy_preprocess.normalize`.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "normalize_with_alignment", signature = (text, stages=None))]
+pub fn py_normalize_with_alignment(
+    py: Python<'_>,
+    text: &str,
+    stages: Option<Vec<Bound<'_, PyAny>>>,
+) -> PyResult<(String, Vec<usize>, Vec<usize>)> {
+    let stages = match stages {
+        Some(stages) => stages,
+        None => vec![PyString::new(py, "long_s").into_any(), PyString::new(py, "uv").into_any()],
+    };
+    PyPipeline::new(stages, Vec::new())?.normalize_with_alignment(py, text)
+}
+
+/// The stage names `_rust.Pipeline` accepts.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "pipeline_stages")]
+pub fn py_pipeline_stages() -> Vec<String> {
+    crate::registry::list()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ligatures, long_s, uv};
+
+    #[test]
+    fn test_normalize_stream() {
+        let pipeline = Pipeline::new().stage(crate::dehyphenate::Dehyphenator::new()).stage(uv::UvNormalizer::new());
+        let text = "uir-\ntus et\n\nuita\n\n\nuox\r\n";
+        let mut out = Vec::new();
+        normalize_stream(&pipeline, text.as_bytes(), &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), pipeline.normalize_text(text));
+        // Long paragraphs are cut at lines, but not at hyphenated breaks
+        let mut out = Vec::new();
+        normalize_stream_with(&pipeline, text.as_bytes(), &mut out, 1, &mut |_| Ok(())).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), pipeline.normalize_text(text));
+    }
+
+    #[test]
+    fn test_stream_without_line_breaks() {
+        let longest = std::sync::atomic::AtomicUsize::new(0);
+        let uv = uv::UvNormalizer::new();
+        let recorder = FnNormalizer::new("uv", |text: &str| {
+            longest.fetch_max(text.len(), Ordering::Relaxed);
+            Normalizer::normalize_text(&uv, text)
+        });
+        let text = "uia uita ".repeat(1000) + "uinum";
+        let mut out = Vec::new();
+        normalize_stream_with(&recorder, text.as_bytes(), &mut out, 64, &mut |_| Ok(())).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), Normalizer::normalize_text(&uv, &text));
+        // At most a chunk plus a piece, not the whole input
+        assert!(longest.load(Ordering::Relaxed) < 3 * 64);
+
+        // A word longer than the limit is read whole
+        let mut carry = Vec::new();
+        let mut buffer = String::new();
+        let mut reader = "uiuuuuus uia".as_bytes();
+        assert_eq!(read_piece(&mut reader, &mut carry, &mut buffer, 4).unwrap(), 9);
+        assert_eq!((buffer.as_str(), carry.as_slice()), ("uiuuuuus ", &b"uia"[..]));
+        assert_eq!(read_piece(&mut reader, &mut carry, &mut buffer, 4).unwrap(), 3);
+        assert_eq!(read_piece(&mut reader, &mut carry, &mut buffer, 4).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_monitored() {
+        let text = "uir\n\nuita\n\n";
+        let mut reports = Vec::new();
+        let normalized = normalize_monitored(&uv::UvNormalizer::new(), text, &mut |p| reports.push(*p), None).unwrap();
+        assert_eq!(normalized, "vir\n\nvita\n\n");
+        let last = Progress { bytes: text.len(), total_bytes: Some(text.len()), words: 2 };
+        assert_eq!(reports.last(), Some(&last));
+        // Cancelled after the first paragraph
+        let cancel = CancelToken::new();
+        let mut out = Vec::new();
+        let mut stop = |_: &Progress| cancel.cancel();
+        let uv = uv::UvNormalizer::new();
+        let err = normalize_stream_monitored(&uv, text.as_bytes(), &mut out, &mut stop, Some(&cancel)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Interrupted);
+        assert_eq!(out, b"vir\n\n");
+        assert!(normalize_monitored(&uv, text, &mut |_| {}, Some(&cancel)).is_err());
+    }
+
+    #[test]
+    fn test_from_changes() {
+        let result = uv::normalize_detailed("uia");
+        let output = NormalizeOutput::from_changes(result.original, result.normalized, result.changes);
+        assert_eq!(output.normalized, "via");
+        assert_eq!(output.offsets, vec![0, 1, 2, 3]);
+        let result = ligatures::expand_detailed("Cæsar", ligatures::Capitals::Auto);
+        let output = NormalizeOutput::from_changes(result.original, result.normalized, result.changes);
+        assert_eq!(output.normalized, "Caesar");
+        assert_eq!(output.offsets, vec![0, 1, 1, 2, 3, 4, 5]);
+        assert_eq!(output.original_range(1..3), 1..2);
+    }
+
+    #[test]
+    fn test_affix_fallback() {
+        // Changes that do not spell out the edit
+        let output = NormalizeOutput::from_changes("abxyz".into(), "abQz".into(), Vec::new());
+        assert_eq!(output.offsets, vec![0, 1, 2, 4, 5]);
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let pipeline = Pipeline::new().stage(ligatures::Capitals::Auto).stage(uv::UvNormalizer::new());
+        assert_eq!(pipeline.names().collect::<Vec<_>>(), ["ligatures", "uv"]);
+        let output = pipeline.normalize("æuum");
+        assert_eq!(output.normalized, "aevum");
+        assert_eq!(output.offsets, vec![0, 0, 1, 2, 3, 4]);
+        assert_eq!(output.changes.len(), 2);
+        assert_eq!(output.changes[0].rule, "ligatures: æ → ae");
+        assert_eq!(output.changes[1].position, 1);
+        assert!(output.changes[1].rule.starts_with("uv: "));
+        assert_eq!(pipeline.normalize_stages("æuum")[1].original, "aeuum");
+    }
+
+    #[test]
+    fn test_normalize_detailed() {
+        let pipeline = Pipeline::new().stage(long_s::LongSNormalizer::new()).stage(uv::UvNormalizer::new());
+        let result = pipeline.normalize_detailed("ﬅat ſeruus");
+        assert_eq!(result.normalized, "stat servus");
+        let stages: Vec<(&str, usize, usize, usize)> = result
+            .changes
+            .iter()
+            .map(|c| (c.stage.as_str(), c.index, c.stage_position, c.change.position))
+            .collect();
+        assert_eq!(stages, [("long_s", 0, 0, 0), ("long_s", 0, 4, 4), ("uv", 1, 8, 7)]);
+        assert!(!result.changes[2].change.rule.starts_with("uv: "));
+        assert_eq!(result.clone().into_output(), pipeline.normalize("ﬅat ſeruus"));
+
+        let json: serde_json::Value = serde_json::from_str(&result.to_json()).unwrap();
+        assert_eq!(json["normalized"], "stat servus");
+        assert_eq!(json["changes"][2]["stage"], "uv");
+        assert_eq!(json["changes"][2]["position"], 7);
+        assert_eq!(json["changes"][2]["stage_position"], 8);
+        assert_eq!(json["offsets"].as_array().unwrap().len(), 12);
+    }
+
+    #[test]
+    fn test_stage_warnings() {
+        let pipeline = Pipeline::new().stage(ligatures::Capitals::Auto).stage(crate::cleanup::MarkupCleaner::new());
+        let result = pipeline.normalize_detailed("Cæsar [ab urbe");
+        assert_eq!(result.normalized, "Caesar [ab urbe");
+        assert_eq!(result.warnings.len(), 1);
+        let StageWarning { stage, index, warning } = &result.warnings[0];
+        assert_eq!((stage.as_str(), *index, warning.position, warning.len), ("markup", 1, 6, 1));
+        let output = pipeline.normalize("Cæsar [ab urbe");
+        assert_eq!(output.warnings, result.clone().into_output().warnings);
+        assert_eq!(output.warnings[0].message, "markup: `[` with no closing `]`");
+
+        let json: serde_json::Value = serde_json::from_str(&result.to_json()).unwrap();
+        assert_eq!(json["warnings"][0]["kind"], "unbalanced_bracket");
+        assert_eq!(json["warnings"][0]["position"], 6);
+    }
+
+    #[test]
+    fn test_output_detail() {
+        let pipeline = Pipeline::new().stage(long_s::LongSNormalizer::new()).stage(uv::UvNormalizer::new());
+        let text = "ﬅat ſeruus http://ſeruus.uia/";
+        let full = pipeline.normalize_with_detail(text, OutputDetail::Full);
+        let summary = pipeline.normalize_with_detail(text, OutputDetail::Summary);
+        assert_eq!(pipeline.normalize_with_detail(text, OutputDetail::None).summary(), None);
+        assert_eq!(summary.normalized(), full.normalized());
+        assert_eq!(summary.summary(), full.summary());
+        let counts = summary.summary().unwrap();
+        assert_eq!((counts.changes, counts.chars_changed), (6, 7));
+        assert_eq!((counts.modules["long_s"], counts.rules["long_s: ﬅ → st"]), (3, 1));
+
+        let protected = Pipeline::new()
+            .stage(long_s::LongSNormalizer::new())
+            .stage(uv::UvNormalizer::new())
+            .protect(Detector::Urls);
+        let Outcome::Summary { normalized, summary } = protected.normalize_with_detail(text, OutputDetail::Summary) else {
+            panic!("not a summary");
+        };
+        assert_eq!(normalized, protected.normalize_text(text));
+        assert_eq!(normalized, "stat servus http://ſeruus.uia/");
+        assert_eq!(summary, protected.normalize_detailed(text).summary());
+    }
+
+    #[test]
+    fn test_custom_stage() {
+        let mut pipeline = Pipeline::new().stage(ligatures::Capitals::Auto).stage(uv::UvNormalizer::new());
+        pipeline.insert(1, Box::new(FnNormalizer::new("caesar", |text: &str| text.replace("Caesar", "Cesar"))));
+        assert_eq!(pipeline.names().collect::<Vec<_>>(), ["ligatures", "caesar", "uv"]);
+        let output = pipeline.normalize("Cæsar uult");
+        assert_eq!(output.normalized, "Cesar vult");
+        assert_eq!(output.original_range(6..10), 6..10);
+        assert_eq!(output.changes.len(), 2);
+    }
+
+    #[test]
+    fn test_normalized_offsets() {
+        let pipeline = Pipeline::new().stage(ligatures::Capitals::Auto).stage(crate::abbrev::Abbreviations::default());
+        let output = pipeline.normalize("Cæsar ⁊ ꝓ");
+        assert_eq!(output.normalized, "Caesar et pro");
+        let map = output.normalized_offsets();
+        assert_eq!(map.len(), output.original.chars().count() + 1);
+        assert_eq!(&map[..3], [0, 1, 3]);
+        assert_eq!(output.normalized_range(6..9), 7..13);
+        assert_eq!(output.original_range(output.normalized_range(0..5)), 0..5);
+        let deleted = NormalizeOutput::from_changes("a-b".into(), "ab".into(), Vec::new());
+        assert_eq!(deleted.normalized_offsets(), [0, 1, 1, 2]);
+    }
+
+    #[test]
+    fn test_long_s_offsets() {
+        let output = Pipeline::new().stage(long_s::LongSNormalizer::new()).normalize("ﬅat ſed");
+        assert_eq!(output.normalized, "stat sed");
+        assert_eq!(output.offsets, vec![0, 0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(output.original_range(5..8), 4..7);
+    }
+
+    #[test]
+    fn test_stages_by_name() {
+        for name in STAGES {
+            assert_eq!(stage(name).map(|stage| stage.name().to_string()).as_deref(), Some(*name));
+        }
+        assert!(stage("nonesuch").is_none());
+        assert_eq!(Pipeline::new().normalize("arma").offsets, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_protected_spans() {
+        let pipeline = Pipeline::new().stage(long_s::LongSNormalizer::new()).stage(uv::UvNormalizer::new());
+        let text = "ſeruus http://ſeruus.uia/ ſed «λόγος» &amp; Cic. Att. 1.2 uir";
+        assert_eq!(pipeline.normalize_text(text), "servus http://servus.via/ sed «λόγος» &amp; Cic. Att. 1.2 vir");
+        let pipeline = Detector::ALL.into_iter().fold(pipeline, Pipeline::protect);
+        let output = pipeline.normalize(text);
+        assert_eq!(output.normalized, "servus http://ſeruus.uia/ sed «λόγος» &amp; Cic. Att. 1.2 vir");
+        check_offsets(&output).unwrap();
+        let positions: Vec<usize> = output.changes.iter().map(|change| change.position).collect();
+        assert_eq!(positions, [0, 26, 3, 58]);
+        assert_eq!(output.original_range(58..61), 58..61);
+
+        // Caller spans add to the detected ones
+        let spans: ProtectedSpans = std::iter::once(0..6).collect();
+        let result = pipeline.normalize_detailed_protected(text, &spans);
+        assert_eq!(result.normalized, "ſeruus http://ſeruus.uia/ sed «λόγος» &amp; Cic. Att. 1.2 vir");
+        assert_eq!(result.changes[0].change.position, 26);
+        let stages = pipeline.normalize_stages_protected(text, &spans);
+        assert_eq!(stages[1].original, stages[0].normalized);
+        assert_eq!(pipeline.protected_spans(text).len(), 4);
+    }
+
+    /// The offset maps of `output` are monotonic and span both texts.
+    fn check_offsets(output: &NormalizeOutput) -> Result<(), proptest::test_runner::TestCaseError> {
+        let (original, normalized) = (output.original.chars().count(), output.normalized.chars().count());
+        proptest::prop_assert_eq!(output.offsets.len(), normalized + 1);
+        proptest::prop_assert_eq!(output.offsets[normalized], original);
+        proptest::prop_assert!(output.offsets.windows(2).all(|pair| pair[0] <= pair[1]));
+        let inverse = output.normalized_offsets();
+        proptest::prop_assert_eq!(inverse.len(), original + 1);
+        proptest::prop_assert_eq!(inverse[original], normalized);
+        proptest::prop_assert!(inverse.windows(2).all(|pair| pair[0] <= pair[1]));
+        for change in &output.changes {
+            proptest::prop_assert!(change.position <= original);
+        }
+        Ok(())
+    }
+
+    proptest::proptest! {
+        /// Every stage, alone and all together, keeps its offset map
+        /// consistent with its output.
+        #[test]
+        fn test_offset_properties(text in crate::testing::text()) {
+            let mut all = Pipeline::new();
+            for &name in STAGES {
+                let normalizer = stage(name).expect("a built-in stage");
+                let output = normalizer.normalize(&text);
+                proptest::prop_assert_eq!(&output.original, &text);
+                check_offsets(&output)?;
+                all.push(normalizer);
+            }
+            check_offsets(&all.normalize(&text))?;
+        }
+
+        /// Protected spans come through every stage verbatim, where the
+        /// offset map puts them.
+        #[test]
+        fn test_protected_properties(text in crate::testing::text(), start in 0usize..80, len in 0usize..20) {
+            let mut all = Detector::ALL.into_iter().fold(Pipeline::new(), Pipeline::protect);
+            for &name in STAGES {
+                all.push(stage(name).expect("a built-in stage"));
+            }
+            let chars: Vec<char> = text.chars().collect();
+            let start = start.min(chars.len());
+            let end = (start + len).min(chars.len());
+            let spans: ProtectedSpans = std::iter::once(start..end).collect();
+            let result = all.normalize_detailed_protected(&text, &spans);
+            let output = result.into_output();
+            check_offsets(&output)?;
+            let normalized: Vec<char> = output.normalized.chars().collect();
+            let mut spans = spans;
+            spans.extend(&all.protected_spans(&text));
+            for range in spans.ranges() {
+                let at = output.normalized_range(range.clone());
+                proptest::prop_assert_eq!(&normalized[at], &chars[range.clone()]);
+                proptest::prop_assert!(output.changes.iter().all(|change| !range.contains(&change.position)));
+            }
+        }
+    }
+}
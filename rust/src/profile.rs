@@ -0,0 +1,595 @@
+//! Corpus profiling: what a text has in it that the normalizers deal with.
+//!
+//! Before choosing pipeline stages it helps to know what a corpus is made
+//! of: whether it writes *v* at all, has long s or f-for-s misreadings,
+//! ligatures, scribal sigla and nasal bars, accents, Greek, words set in
+//! capitals. [`profile`] counts each of these in one scan, and
+//! [`CorpusProfile::era`] guesses from them the kind of text it is.
+//! [`recommend_pipeline`] turns the counts into a pipeline config.
+//!
+//! ```
+//! use latincy_preprocess::profile::{self, Era};
+//!
+//! let profile = profile::profile("Arma uirumque cano, Troiæ qui primus ab oris\nItaliam fato profugus ...");
+//! assert_eq!((profile.words, profile.vowel_ligatures), (11, 1));
+//! assert!(!profile.writes_v());
+//! assert_eq!(profile.era(), Era::EarlyModern);
+//! ```
+
+use crate::abbrev;
+use crate::config::{PipelineConfig, StageConfig};
+use crate::long_s;
+use crate::protect::{Detector, ProtectedSpans};
+use crate::unicode;
+#[cfg(feature = "pyo3-backend")]
+use crate::config::PyPipelineConfig;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::types::PyDict;
+use std::fmt;
+
+/// A share of lowercase *v* (or *j*) among *u* and *v* (or *i* and *j*)
+/// below which a text counts as not writing the letter: stray OCR errors
+/// and foreign words stay under it.
+pub const MIN_LETTER_SHARE: f64 = 0.02;
+/// A rate per thousand words at or above which features of an era mark a
+/// text as of that era.
+pub const ERA_RATE: f64 = 5.0;
+
+/// The typographic ligatures of early prints.
+const TYPOGRAPHIC_LIGATURES: &[char] = &['ﬀ', 'ﬁ', 'ﬂ', 'ﬃ', 'ﬄ', 'ﬅ', 'ﬆ'];
+
+/// The kind of text a profile suggests, from [`CorpusProfile::era`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Era {
+    /// Mostly words in capitals, as inscriptions are transcribed.
+    Inscriptional,
+    /// Scribal sigla and nasal bars: manuscripts and incunabula.
+    Medieval,
+    /// Long s, f-for-s misreadings, typographic and vowel ligatures, and
+    /// accents: prints of the 16th to 18th centuries and their OCR.
+    EarlyModern,
+    /// None of these: modern editions.
+    Modern,
+}
+
+impl Era {
+    pub const ALL: [Era; 4] = [Era::Inscriptional, Era::Medieval, Era::EarlyModern, Era::Modern];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Era::Inscriptional => "inscriptional",
+            Era::Medieval => "medieval",
+            Era::EarlyModern => "early-modern",
+            Era::Modern => "modern",
+        }
+    }
+}
+
+/// Counts of the features of a text or corpus, from [`profile`] or
+/// [`CorpusProfile::add_text`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CorpusProfile {
+    /// How many texts were added.
+    pub texts: usize,
+    pub chars: usize,
+    pub lines: usize,
+    /// Runs of letters (with their combining marks).
+    pub words: usize,
+    /// Words of two or more letters, all capitals.
+    pub all_caps_words: usize,
+    /// *u*, *v*, *i*, and *j* in lowercase and as capitals, outside words
+    /// set in capitals, where V and I write both letters.
+    pub u: usize,
+    pub v: usize,
+    pub capital_u: usize,
+    pub capital_v: usize,
+    pub i: usize,
+    pub j: usize,
+    /// `ſ`.
+    pub long_s: usize,
+    /// Words with an *f* that the long-s Pass 1 rules read as a misread
+    /// long s (*ftatua*, *eft*).
+    pub f_for_s: usize,
+    /// `æ`, `œ`, and `ę`, in either case.
+    pub vowel_ligatures: usize,
+    /// `ﬁ`, `ﬂ`, `ﬅ`, and the other typographic ligatures.
+    pub typographic_ligatures: usize,
+    /// Scribal abbreviations of the built-in table (`⁊`, `ꝑ`, `q;`) and
+    /// superscript letters.
+    pub sigla: usize,
+    /// Letters with a tilde, which marks an omitted nasal.
+    pub tildes: usize,
+    /// Letters with a macron or breve: length marks, or nasal bars.
+    pub macrons: usize,
+    /// Letters with an acute, grave, or circumflex accent.
+    pub accents: usize,
+    pub greek_spans: usize,
+    pub greek_chars: usize,
+    /// Lines ending in a hyphen after a letter, as words broken across
+    /// lines do.
+    pub hyphenated_lines: usize,
+}
+
+/// The profile of `text`.
+pub fn profile(text: &str) -> CorpusProfile {
+    let mut profile = CorpusProfile::default();
+    profile.add_text(text);
+    profile
+}
+
+/// Whether `c` is a combining superscript letter (U+0363–U+036F), written
+/// above the line for an abbreviated syllable (`qͥ`).
+fn is_superscript(c: char) -> bool {
+    ('\u{0363}'..='\u{036F}').contains(&c)
+}
+
+/// Whether `c` is a letter of a word, or a mark on one.
+fn is_word_char(c: char) -> bool {
+    c.is_alphabetic() || crate::graphemes::is_extender(c)
+}
+
+impl CorpusProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the counts of `text`.
+    pub fn add_text(&mut self, text: &str) {
+        let chars: Vec<char> = text.chars().collect();
+        self.texts += 1;
+        self.chars += chars.len();
+        self.lines += text.lines().count();
+        self.hyphenated_lines += text
+            .lines()
+            .filter(|line| {
+                let mut end = line.trim_end().chars().rev();
+                end.next() == Some('-') && end.next().is_some_and(char::is_alphabetic)
+            })
+            .count();
+        let greek = ProtectedSpans::detect(text, &[Detector::Greek]);
+        self.greek_spans += greek.ranges().len();
+        self.greek_chars += greek.ranges().iter().map(|range| range.len()).sum::<usize>();
+
+        for (i, &c) in chars.iter().enumerate() {
+            if !c.is_ascii() && !greek.contains(i) {
+                self.add_char(c);
+            }
+        }
+        // Sigla of more than one char, but for superscripts, counted as chars
+        for (abbreviation, _) in abbrev::ABBREVIATIONS {
+            if abbreviation.chars().count() > 1 && !abbreviation.chars().any(is_superscript) {
+                self.sigla += text.matches(abbreviation).count();
+            }
+        }
+
+        for word in chars.split(|&c| !is_word_char(c)) {
+            if !word.first().is_some_and(|c| c.is_alphabetic()) {
+                continue;
+            }
+            self.words += 1;
+            if word.len() > 1 && word.iter().all(|&c| !c.is_alphabetic() || crate::case::is_capital(c)) {
+                self.all_caps_words += 1;
+            } else {
+                for &c in word {
+                    match c {
+                        'u' => self.u += 1,
+                        'v' => self.v += 1,
+                        'U' => self.capital_u += 1,
+                        'V' => self.capital_v += 1,
+                        'i' => self.i += 1,
+                        'j' | 'J' => self.j += 1,
+                        _ => {}
+                    }
+                }
+            }
+            if word.iter().any(|&c| c == 'f' || c == 'F') {
+                let word: String = word.iter().collect();
+                if long_s::pass1(&word) != word {
+                    self.f_for_s += 1;
+                }
+            }
+        }
+    }
+
+    /// Count the non-ASCII char `c`, outside Greek.
+    fn add_char(&mut self, c: char) {
+        match c {
+            'ſ' => self.long_s += 1,
+            'æ' | 'Æ' | 'œ' | 'Œ' | 'ę' | 'Ę' => self.vowel_ligatures += 1,
+            c if TYPOGRAPHIC_LIGATURES.contains(&c) => self.typographic_ligatures += 1,
+            c if is_superscript(c) => self.sigla += 1,
+            c if abbrev::ABBREVIATIONS.iter().any(|(abbreviation, _)| abbreviation.chars().eq([c])) => self.sigla += 1,
+            _ => {}
+        }
+        let decomposed;
+        let marks = if crate::graphemes::is_extender(c) {
+            std::slice::from_ref(&c)
+        } else {
+            decomposed = unicode::decompose(&[c], false);
+            &decomposed[1..]
+        };
+        for mark in marks {
+            match mark {
+                '\u{0303}' => self.tildes += 1,
+                '\u{0304}' | '\u{0306}' => self.macrons += 1,
+                '\u{0300}'..='\u{0302}' => self.accents += 1,
+                _ => {}
+            }
+        }
+    }
+
+    /// Add the counts of `other`.
+    pub fn merge(&mut self, other: &CorpusProfile) {
+        for (count, other) in self.counts_mut().into_iter().zip(other.counts()) {
+            *count.1 += other.1;
+        }
+    }
+
+    /// Every count, by field name, in field order.
+    pub fn counts(&self) -> Vec<(&'static str, usize)> {
+        let mut counts = self.clone();
+        counts.counts_mut().into_iter().map(|(name, count)| (name, *count)).collect()
+    }
+
+    fn counts_mut(&mut self) -> Vec<(&'static str, &mut usize)> {
+        vec![
+            ("texts", &mut self.texts),
+            ("chars", &mut self.chars),
+            ("lines", &mut self.lines),
+            ("words", &mut self.words),
+            ("all_caps_words", &mut self.all_caps_words),
+            ("u", &mut self.u),
+            ("v", &mut self.v),
+            ("capital_u", &mut self.capital_u),
+            ("capital_v", &mut self.capital_v),
+            ("i", &mut self.i),
+            ("j", &mut self.j),
+            ("long_s", &mut self.long_s),
+            ("f_for_s", &mut self.f_for_s),
+            ("vowel_ligatures", &mut self.vowel_ligatures),
+            ("typographic_ligatures", &mut self.typographic_ligatures),
+            ("sigla", &mut self.sigla),
+            ("tildes", &mut self.tildes),
+            ("macrons", &mut self.macrons),
+            ("accents", &mut self.accents),
+            ("greek_spans", &mut self.greek_spans),
+            ("greek_chars", &mut self.greek_chars),
+            ("hyphenated_lines", &mut self.hyphenated_lines),
+        ]
+    }
+
+    /// `count` per thousand words; 0 for no words.
+    pub fn rate(&self, count: usize) -> f64 {
+        if self.words == 0 {
+            0.0
+        } else {
+            count as f64 * 1000.0 / self.words as f64
+        }
+    }
+
+    /// The share of words set in capitals.
+    pub fn all_caps_ratio(&self) -> f64 {
+        if self.words == 0 {
+            0.0
+        } else {
+            self.all_caps_words as f64 / self.words as f64
+        }
+    }
+
+    /// Whether the text writes consonantal *v* in lowercase: at least
+    /// [`MIN_LETTER_SHARE`] of its lowercase *u* and *v*.
+    pub fn writes_v(&self) -> bool {
+        self.v > 0 && self.v as f64 >= MIN_LETTER_SHARE * (self.u + self.v) as f64
+    }
+
+    /// Whether the text writes consonantal *j*, as [`writes_v`](Self::writes_v).
+    pub fn writes_j(&self) -> bool {
+        self.j > 0 && self.j as f64 >= MIN_LETTER_SHARE * (self.i + self.j) as f64
+    }
+
+    /// The kind of text the counts suggest: inscriptional if most words are
+    /// in capitals, else the first of medieval and early modern whose
+    /// features reach [`ERA_RATE`] per thousand words, else modern.
+    pub fn era(&self) -> Era {
+        if self.all_caps_ratio() >= 0.5 {
+            Era::Inscriptional
+        } else if self.rate(self.sigla + self.tildes) >= ERA_RATE {
+            Era::Medieval
+        } else if self.long_s > 0
+            || self.rate(self.f_for_s + self.typographic_ligatures + self.vowel_ligatures + self.accents) >= ERA_RATE
+        {
+            Era::EarlyModern
+        } else {
+            Era::Modern
+        }
+    }
+
+    /// The counts as a JSON object, with `era`, `writes_v`, `writes_j`, and
+    /// `all_caps_ratio`.
+    pub fn to_json(&self) -> String {
+        let mut object: serde_json::Map<String, serde_json::Value> =
+            self.counts().into_iter().map(|(name, count)| (name.to_string(), count.into())).collect();
+        object.insert("all_caps_ratio".into(), self.all_caps_ratio().into());
+        object.insert("writes_v".into(), self.writes_v().into());
+        object.insert("writes_j".into(), self.writes_j().into());
+        object.insert("era".into(), self.era().name().into());
+        serde_json::Value::Object(object).to_string()
+    }
+}
+
+/// Each count with its rate per thousand words, then the estimates.
+impl fmt::Display for CorpusProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:>10}  {:>9}", "count", "per 1000")?;
+        for (name, count) in self.counts() {
+            if matches!(name, "texts" | "chars" | "lines" | "words") {
+                writeln!(f, "{:>10}  {:>9}  {}", count, "", name)?;
+            } else {
+                writeln!(f, "{:>10}  {:>9.2}  {}", count, self.rate(count), name)?;
+            }
+        }
+        writeln!(f)?;
+        writeln!(f, "all caps: {:.1}% of words", self.all_caps_ratio() * 100.0)?;
+        writeln!(f, "writes v: {}", if self.writes_v() { "yes" } else { "no" })?;
+        writeln!(f, "writes j: {}", if self.writes_j() { "yes" } else { "no" })?;
+        write!(f, "era: {}", self.era().name())
+    }
+}
+
+/// The stages `profile` suggests, each with the counts that call for it,
+/// in an order that works: recasing before the stages that expect
+/// lowercase, ligatures and sigla expanded before long s and u/v look at
+/// the words.
+///
+/// `unicode` always comes first. Then `dehyphenate` for lines ending in a
+/// hyphen, `recase` and `epigraphic` for inscriptional text, `ligatures`
+/// for vowel ligatures, `abbreviations` for sigla, `nasal_bars` for tildes
+/// (and macrons in medieval text; elsewhere they mark length, and the
+/// stage keeps them), `long_s` for long s, f-for-s, and typographic ligatures, `uv` for
+/// text that does not write *v*, `medieval` for medieval text, and
+/// `accents` for accents. Greek is protected. Stages that are a matter of
+/// editorial choice (`assimilation`, `loanwords`, `enclitics`) or that no
+/// count detects (`markup`, `ocr`) are never suggested.
+///
+/// ```
+/// use latincy_preprocess::profile::{self, recommend_pipeline};
+///
+/// let config = recommend_pipeline(&profile::profile("Arma uirumque cano, Troiæ qui primus ab oris ſtat"));
+/// assert_eq!(config.names().collect::<Vec<_>>(), ["unicode", "ligatures", "long_s", "uv"]);
+/// ```
+pub fn recommend_pipeline(profile: &CorpusProfile) -> PipelineConfig {
+    let era = profile.era();
+    let mut stages = vec![StageConfig::new("unicode").reason("compose combining marks and remove invisible characters")];
+    if profile.hyphenated_lines > 0 {
+        let reason = format!("lines ending in a hyphen: {}", profile.hyphenated_lines);
+        stages.push(StageConfig::new("dehyphenate").reason(reason));
+    }
+    if era == Era::Inscriptional {
+        let reason = format!("words in capitals: {:.0}%", profile.all_caps_ratio() * 100.0);
+        stages.push(StageConfig::new("recase").reason(reason));
+    }
+    if profile.vowel_ligatures > 0 {
+        let reason = format!("vowel ligatures (æ, œ, ę): {}", profile.vowel_ligatures);
+        stages.push(StageConfig::new("ligatures").reason(reason));
+    }
+    if profile.sigla > 0 {
+        stages.push(StageConfig::new("abbreviations").reason(format!("scribal sigla: {}", profile.sigla)));
+    }
+    if era == Era::Medieval && profile.tildes + profile.macrons > 0 {
+        let reason = format!("nasal bars: {} tildes, {} macrons", profile.tildes, profile.macrons);
+        stages.push(StageConfig::new("nasal_bars").reason(reason));
+    } else if profile.tildes > 0 {
+        let mut stage = StageConfig::new("nasal_bars").reason(format!("nasal bars: {} tildes; macrons mark length", profile.tildes));
+        if profile.macrons > 0 {
+            stage = stage.option("macrons", false);
+        }
+        stages.push(stage);
+    }
+    if profile.long_s + profile.f_for_s + profile.typographic_ligatures > 0 {
+        let reason = format!(
+            "long s: {}, words with f for s: {}, typographic ligatures: {}",
+            profile.long_s, profile.f_for_s, profile.typographic_ligatures
+        );
+        stages.push(StageConfig::new("long_s").reason(reason));
+    }
+    if profile.u > 0 && !profile.writes_v() {
+        let reason = format!("u: {}, v: {}; the text writes no v", profile.u, profile.v);
+        stages.push(StageConfig::new("uv").reason(reason));
+    }
+    if era == Era::Medieval {
+        let reason = format!("sigla and tildes per 1000 words: {:.1}", profile.rate(profile.sigla + profile.tildes));
+        stages.push(StageConfig::new("medieval").reason(reason));
+    }
+    if era == Era::Inscriptional {
+        stages.push(StageConfig::new("epigraphic").reason("inscriptional text: spellings such as servos and donom are likely"));
+    }
+    if profile.accents > 0 {
+        stages.push(StageConfig::new("accents").reason(format!("accented letters: {}", profile.accents)));
+    }
+    let protect = if profile.greek_spans > 0 { vec![Detector::Greek] } else { Vec::new() };
+    PipelineConfig { stages, protect }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+/// Python view of a [`CorpusProfile`], exposed as `_rust.CorpusProfile`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "CorpusProfile", frozen)]
+pub struct PyCorpusProfile {
+    pub(crate) inner: CorpusProfile,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyCorpusProfile {
+    /// Every count, by name.
+    #[getter]
+    fn counts<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for (name, count) in self.inner.counts() {
+            dict.set_item(name, count)?;
+        }
+        Ok(dict)
+    }
+
+    fn rate(&self, name: &str) -> PyResult<f64> {
+        match self.inner.counts().into_iter().find(|&(field, _)| field == name) {
+            Some((_, count)) => Ok(self.inner.rate(count)),
+            None => Err(pyo3::exceptions::PyKeyError::new_err(name.to_string())),
+        }
+    }
+
+    #[getter]
+    fn all_caps_ratio(&self) -> f64 {
+        self.inner.all_caps_ratio()
+    }
+
+    #[getter]
+    fn writes_v(&self) -> bool {
+        self.inner.writes_v()
+    }
+
+    #[getter]
+    fn writes_j(&self) -> bool {
+        self.inner.writes_j()
+    }
+
+    /// `"inscriptional"`, `"medieval"`, `"early-modern"`, or `"modern"`.
+    #[getter]
+    fn era(&self) -> &'static str {
+        self.inner.era().name()
+    }
+
+    fn to_json(&self) -> String {
+        self.inner.to_json()
+    }
+
+    /// The stages the profile suggests, as a `PipelineConfig`.
+    fn recommend_pipeline(&self) -> PyPipelineConfig {
+        PyPipelineConfig {
+            inner: recommend_pipeline(&self.inner),
+        }
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CorpusProfile(texts={}, words={}, era={:?})",
+            self.inner.texts,
+            self.inner.words,
+            self.inner.era().name()
+        )
+    }
+}
+
+/// The profile of `text`.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "profile_text")]
+pub fn py_profile_text(py: Python<'_>, text: &str) -> PyCorpusProfile {
+    let inner = crate::allow_threads(py, text, || profile(text));
+    PyCorpusProfile { inner }
+}
+
+/// The stages `profile` suggests, as a `PipelineConfig`.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "recommend_pipeline")]
+pub fn py_recommend_pipeline(profile: &PyCorpusProfile) -> PyPipelineConfig {
+    profile.recommend_pipeline()
+}
+
+/// The profile of the texts together.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "profile_texts")]
+pub fn py_profile_texts(py: Python<'_>, texts: Vec<String>) -> PyCorpusProfile {
+    let inner = py.allow_threads(|| {
+        let mut profile = CorpusProfile::new();
+        for text in &texts {
+            profile.add_text(text);
+        }
+        profile
+    });
+    PyCorpusProfile { inner }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts() {
+        let text = "Vir ſapiens eft: ⁊ quā atq; ἀνήρ\nSENATVS POPVLVSQVE cœlum ﬁdes musâ iam ma-\nuxor";
+        let profile = profile(text);
+        assert_eq!((profile.texts, profile.lines, profile.words), (1, 3, 14));
+        assert_eq!((profile.all_caps_words, profile.capital_v, profile.v), (2, 1, 0));
+        assert_eq!((profile.long_s, profile.f_for_s), (1, 1));
+        assert_eq!((profile.vowel_ligatures, profile.typographic_ligatures), (1, 1));
+        // ⁊ and q;
+        assert_eq!(profile.sigla, 2);
+        assert_eq!((profile.macrons, profile.accents, profile.tildes), (1, 1, 0));
+        assert_eq!((profile.greek_spans, profile.greek_chars), (1, 4));
+        assert_eq!(profile.hyphenated_lines, 1);
+        assert!(!profile.writes_v() && !profile.writes_j());
+        // Sigla outweigh the signs of print
+        assert_eq!(profile.era(), Era::Medieval);
+
+        let mut both = profile.clone();
+        both.merge(&self::profile("servus iam"));
+        assert_eq!((both.texts, both.v, both.words), (2, 1, 16));
+        let json: serde_json::Value = serde_json::from_str(&both.to_json()).unwrap();
+        assert_eq!((json["v"].as_u64(), json["era"].as_str()), (Some(1), Some("medieval")));
+        assert!(both.to_string().ends_with("era: medieval"));
+    }
+
+    #[test]
+    fn test_era() {
+        assert_eq!(profile("").era(), Era::Modern);
+        assert_eq!(profile("arma virumque cano").era(), Era::Modern);
+        assert!(profile("arma virumque cano").writes_v());
+        assert_eq!(profile("SENATVS POPVLVSQVE ROMANVS").era(), Era::Inscriptional);
+        assert_eq!(profile("ꝑ deum quā").era(), Era::Medieval);
+        assert_eq!(profile("coelũ").tildes, 1);
+        assert_eq!(profile("iustitia ejus").era(), Era::Modern);
+        assert!(profile("iustitia ejus").writes_j());
+    }
+
+    #[test]
+    fn test_recommend_pipeline() {
+        let names = |text: &str| recommend_pipeline(&profile(text)).names().map(str::to_string).collect::<Vec<_>>();
+        assert_eq!(names(""), ["unicode"]);
+        assert_eq!(names("arma virumque cano"), ["unicode"]);
+        assert_eq!(names("SENATVS POPVLVSQVE ROMANVS"), ["unicode", "recase", "epigraphic"]);
+        assert_eq!(
+            names("ꝑ deum quā uir ⁊ uxor"),
+            ["unicode", "abbreviations", "nasal_bars", "uv", "medieval"]
+        );
+        assert_eq!(names("cœlum ﬁdes certè vivit ma-\nior"), ["unicode", "dehyphenate", "ligatures", "long_s", "accents"]);
+
+        // Outside medieval text macrons mark length, so only tildes are bars
+        let text = format!("Rōma coelũ {}", "virtus ".repeat(300));
+        assert_eq!(profile(&text).era(), Era::Modern);
+        let config = recommend_pipeline(&profile(&text));
+        let bars = config.stages.iter().find(|stage| stage.name == "nasal_bars").unwrap();
+        assert_eq!(bars.options.get("macrons"), Some(&serde_json::Value::Bool(false)));
+
+        let config = recommend_pipeline(&profile("uir ἀνήρ eft"));
+        assert_eq!(config.protect, [Detector::Greek]);
+        assert_eq!(config.stages[2].reason.as_deref(), Some("u: 1, v: 0; the text writes no v"));
+        use crate::pipeline::Normalizer;
+        let pipeline = config.build(std::path::Path::new("")).unwrap();
+        assert_eq!(pipeline.normalize_text("uir ἀνήρ eft"), "vir ἀνήρ est");
+    }
+}
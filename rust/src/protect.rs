@@ -0,0 +1,327 @@
+//! Spans of a text that no normalizer may touch.
+//!
+//! Latin texts quote Greek and cite their sources (*Cic. Att. 1.2*), and
+//! digitized ones carry URLs and XML character entities. None of it is
+//! Latin, and a rule let loose on it corrupts it: `http://uia.org` would
+//! become `http://via.org`. [`ProtectedSpans`] marks such spans, given by
+//! the caller or found by the [`Detector`]s, and a
+//! [`Pipeline`](crate::pipeline::Pipeline) copies them verbatim: its stages
+//! only ever see the text between them.
+//!
+//! ```
+//! use latincy_preprocess::pipeline::{Normalizer, Pipeline};
+//! use latincy_preprocess::protect::Detector;
+//! use latincy_preprocess::uv::UvNormalizer;
+//!
+//! let pipeline = Pipeline::new().stage(UvNormalizer::new()).protect(Detector::Urls);
+//! assert_eq!(pipeline.normalize_text("uide http://uia.org"), "vide http://uia.org");
+//! ```
+
+use crate::graphemes;
+use std::ops::Range;
+
+/// A kind of span that [`ProtectedSpans::detect`] finds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Detector {
+    /// `http://`, `https://`, `ftp://`, `mailto:`, and `www.` addresses, up
+    /// to the next space, without trailing punctuation.
+    Urls,
+    /// XML and HTML character references: `&amp;`, `&#257;`, `&#x101;`.
+    Entities,
+    /// Runs of Greek letters with their marks, and the spaces and
+    /// punctuation between Greek words.
+    Greek,
+    /// Abbreviated references to a work and a passage: an author and a
+    /// work (*Cic. Att. 1.2*, *Verg. Aen. 6*) or an author and a
+    /// dotted passage number (*Liv. 21.1*).
+    Citations,
+}
+
+impl Detector {
+    pub const ALL: [Detector; 4] = [Detector::Urls, Detector::Entities, Detector::Greek, Detector::Citations];
+
+    /// The detector's name, as used in pipeline configs and by the Python
+    /// bindings.
+    pub fn name(self) -> &'static str {
+        match self {
+            Detector::Urls => "urls",
+            Detector::Entities => "entities",
+            Detector::Greek => "greek",
+            Detector::Citations => "citations",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Detector> {
+        Detector::ALL.into_iter().find(|detector| detector.name() == name)
+    }
+
+    /// The spans of `chars` this detector finds, in text order.
+    fn find(self, chars: &[char]) -> Vec<Range<usize>> {
+        let find: fn(&[char], usize) -> Option<usize> = match self {
+            Detector::Urls => url_end,
+            Detector::Entities => entity_end,
+            Detector::Greek => greek_end,
+            Detector::Citations => citation_end,
+        };
+        let mut spans = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match find(chars, i) {
+                Some(end) => {
+                    spans.push(i..end);
+                    i = end;
+                }
+                None => i += 1,
+            }
+        }
+        spans
+    }
+}
+
+/// Char ranges of a text to copy verbatim, kept sorted, with overlapping
+/// and adjacent spans merged.
+///
+/// ```
+/// use latincy_preprocess::protect::{Detector, ProtectedSpans};
+///
+/// let mut spans = ProtectedSpans::detect("ut ait Cic. Att. 1.2, λόγος", &Detector::ALL);
+/// assert_eq!(spans.ranges(), [7..20, 22..27]);
+/// spans.add(0..2);
+/// assert!(spans.contains(1) && !spans.contains(3));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProtectedSpans {
+    ranges: Vec<Range<usize>>,
+}
+
+impl ProtectedSpans {
+    pub fn new() -> Self {
+        ProtectedSpans::default()
+    }
+
+    /// The spans of `text` that `detectors` find.
+    pub fn detect(text: &str, detectors: &[Detector]) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let mut spans = ProtectedSpans::new();
+        for detector in detectors {
+            for range in detector.find(&chars) {
+                spans.add(range);
+            }
+        }
+        spans
+    }
+
+    /// Protect the chars `range`. An empty range protects nothing.
+    pub fn add(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        // The spans from `first` to `last` overlap or touch `range`
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+        let merged = match self.ranges[first..last] {
+            [] => range,
+            ref touching => {
+                touching[0].start.min(range.start)..touching[touching.len() - 1].end.max(range.end)
+            }
+        };
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    /// Protect every span of `other` too.
+    pub fn extend(&mut self, other: &ProtectedSpans) {
+        for range in &other.ranges {
+            self.add(range.clone());
+        }
+    }
+
+    /// The spans, in text order.
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+
+    /// Whether the char at `position` is protected.
+    pub fn contains(&self, position: usize) -> bool {
+        let i = self.ranges.partition_point(|r| r.end <= position);
+        self.ranges.get(i).is_some_and(|r| r.contains(&position))
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+impl FromIterator<Range<usize>> for ProtectedSpans {
+    fn from_iter<I: IntoIterator<Item = Range<usize>>>(ranges: I) -> Self {
+        let mut spans = ProtectedSpans::new();
+        for range in ranges {
+            spans.add(range);
+        }
+        spans
+    }
+}
+
+/// Whether `chars` has `prefix` at `i`, ignoring ASCII case.
+fn starts_with(chars: &[char], i: usize, prefix: &str) -> bool {
+    let len = prefix.chars().count();
+    chars.get(i..i + len).is_some_and(|at| at.iter().zip(prefix.chars()).all(|(c, p)| c.eq_ignore_ascii_case(&p)))
+}
+
+/// Whether a span may start at `i`: not in the middle of a word.
+fn at_word_start(chars: &[char], i: usize) -> bool {
+    i == 0 || !chars[i - 1].is_alphanumeric()
+}
+
+fn url_end(chars: &[char], i: usize) -> Option<usize> {
+    if !at_word_start(chars, i) {
+        return None;
+    }
+    let scheme = ["http://", "https://", "ftp://", "mailto:", "www."]
+        .into_iter()
+        .find(|scheme| starts_with(chars, i, scheme))?;
+    let start = i + scheme.chars().count();
+    let mut end = chars[start..]
+        .iter()
+        .position(|&c| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '«' | '»'))
+        .map_or(chars.len(), |p| start + p);
+    // Punctuation after an address belongs to the sentence
+    while end > start && matches!(chars[end - 1], '.' | ',' | ';' | ':' | '!' | '?' | ')' | ']' | '\'') {
+        end -= 1;
+    }
+    (end > start).then_some(end)
+}
+
+fn entity_end(chars: &[char], i: usize) -> Option<usize> {
+    const MAX_NAME: usize = 32;
+    if chars[i] != '&' {
+        return None;
+    }
+    let (start, valid): (usize, fn(char) -> bool) = match (chars.get(i + 1), chars.get(i + 2)) {
+        (Some('#'), Some('x' | 'X')) => (i + 3, |c| c.is_ascii_hexdigit()),
+        (Some('#'), _) => (i + 2, |c| c.is_ascii_digit()),
+        (Some(c), _) if c.is_ascii_alphabetic() => (i + 1, |c| c.is_ascii_alphanumeric()),
+        _ => return None,
+    };
+    let len = chars[start..].iter().take(MAX_NAME + 1).take_while(|&&c| valid(c)).count();
+    (len > 0 && len <= MAX_NAME && chars.get(start + len) == Some(&';')).then_some(start + len + 1)
+}
+
+fn is_greek(c: char) -> bool {
+    matches!(c, '\u{0370}'..='\u{03FF}' | '\u{1F00}'..='\u{1FFF}') && c.is_alphabetic()
+}
+
+fn greek_end(chars: &[char], i: usize) -> Option<usize> {
+    if !is_greek(chars[i]) || (i > 0 && is_greek(chars[i - 1])) {
+        return None;
+    }
+    let mut end = i;
+    loop {
+        // A word: letters, their marks, and an elision mark on the last
+        while end < chars.len() && (is_greek(chars[end]) || (end > i && graphemes::is_extender(chars[end]))) {
+            end += 1;
+        }
+        if matches!(chars.get(end), Some('\'' | '’' | 'ʼ')) {
+            end += 1;
+        }
+        // Further words after spaces and Greek punctuation
+        let gap = chars[end..]
+            .iter()
+            .take_while(|&&c| matches!(c, ' ' | '\t' | ',' | '.' | '·' | ';' | '\u{0387}'))
+            .count();
+        match chars.get(end + gap) {
+            Some(&c) if gap > 0 && is_greek(c) => end += gap,
+            _ => return Some(end),
+        }
+    }
+}
+
+fn citation_end(chars: &[char], i: usize) -> Option<usize> {
+    const MAX_ABBREVIATIONS: usize = 3;
+    if !at_word_start(chars, i) {
+        return None;
+    }
+    // Abbreviations: a capital, a few lowercase letters, and a full stop
+    let mut k = i;
+    let mut abbreviations = 0;
+    while abbreviations < MAX_ABBREVIATIONS && chars.get(k).is_some_and(char::is_ascii_uppercase) {
+        let letters = chars[k + 1..].iter().take_while(|c| c.is_ascii_lowercase()).count();
+        if !(1..=5).contains(&letters) || chars.get(k + 1 + letters) != Some(&'.') {
+            break;
+        }
+        k += letters + 2;
+        abbreviations += 1;
+        let spaces = chars[k..].iter().take_while(|&&c| c == ' ').count();
+        if spaces == 0 {
+            return None;
+        }
+        k += spaces;
+    }
+    // The passage: numbers joined by full stops
+    let mut end = k;
+    let mut numbers = 0;
+    loop {
+        let digits = chars[end..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            break;
+        }
+        end += digits;
+        numbers += 1;
+        if chars.get(end) == Some(&'.') && chars.get(end + 1).is_some_and(char::is_ascii_digit) {
+            end += 1;
+        } else {
+            break;
+        }
+    }
+    // One abbreviated word before a single number is too often a sentence
+    // ending before a numeral
+    let citation = numbers > 0 && (abbreviations >= 2 || (abbreviations == 1 && numbers >= 2));
+    citation.then_some(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(detector: Detector, text: &str) -> Vec<String> {
+        let chars: Vec<char> = text.chars().collect();
+        detector.find(&chars).into_iter().map(|r| chars[r].iter().collect()).collect()
+    }
+
+    #[test]
+    fn test_detectors() {
+        assert_eq!(
+            found(Detector::Urls, "uide http://uia.org/a?b=1, et (www.uergilius.net). mailto:x@y.z"),
+            ["http://uia.org/a?b=1", "www.uergilius.net", "mailto:x@y.z"]
+        );
+        assert_eq!(found(Detector::Urls, "uiawww.org http://"), Vec::<String>::new());
+        assert_eq!(found(Detector::Entities, "&amp; &#257;&#x101; &nope & &x;y"), ["&amp;", "&#257;", "&#x101;", "&x;"]);
+        assert_eq!(
+            found(Detector::Greek, "ut ait ἄνδρα μοι ἔννεπε, Μοῦσα· et δ’ alia λο\u{0301}γος"),
+            ["ἄνδρα μοι ἔννεπε, Μοῦσα", "δ’", "λο\u{0301}γος"]
+        );
+        assert_eq!(
+            found(Detector::Citations, "Cic. Att. 1.2, Verg. Aen. 6 et Liv. 21.1.3; Romam. 12 milia"),
+            ["Cic. Att. 1.2", "Verg. Aen. 6", "Liv. 21.1.3"]
+        );
+        assert_eq!(found(Detector::Citations, "Cic.Att. 1 MCic. Att. 1"), Vec::<String>::new());
+        assert_eq!(Detector::from_name("greek"), Some(Detector::Greek));
+        assert!(Detector::ALL.iter().all(|&d| Detector::from_name(d.name()) == Some(d)));
+    }
+
+    #[test]
+    fn test_spans_merge() {
+        let mut spans: ProtectedSpans = [5..8, 0..2].into_iter().collect();
+        assert_eq!(spans.ranges(), [0..2, 5..8]);
+        spans.add(2..3);
+        spans.add(4..4);
+        assert_eq!(spans.ranges(), [0..3, 5..8]);
+        spans.add(1..6);
+        assert_eq!(spans.ranges(), std::slice::from_ref(&(0..8)));
+        spans.extend(&std::iter::once(10..12).collect());
+        assert_eq!((spans.len(), spans.contains(7), spans.contains(8), spans.contains(11)), (2, true, false, true));
+    }
+}
@@ -0,0 +1,324 @@
+//! Recasing of all-caps text: inscriptions, title pages, running heads.
+//!
+//! *SENATVS POPVLVSQVE ROMANVS* reads as shouting to a tagger trained on
+//! mixed case, and each capitalized form is a stranger to its lexicon.
+//! [`Recaser`] lowercases every run of all-caps words, then gives a capital
+//! back to the first word of a sentence (after `.`, `!`, `?`, or a blank
+//! line, at the start of the text, or a run that starts a line) and to the
+//! proper names in its name lexicon. Roman numerals keep their capitals,
+//! and text already in mixed case is left alone.
+
+use crate::changes::{self, ChangeRecord};
+use crate::diagnostics::{Warning, WarningKind};
+use crate::lexicon::Lexicon;
+use crate::names;
+use crate::numerals;
+use crate::pipeline::{NormalizeOutput, Normalizer};
+use crate::styles::CLASSICAL_NAMES;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use std::sync::Arc;
+
+/// Recases all-caps spans, capitalizing sentence starts and known names.
+///
+/// ```
+/// use latincy_preprocess::lexicon::Lexicon;
+/// use latincy_preprocess::recase::Recaser;
+/// use std::sync::Arc;
+///
+/// let recaser = Recaser::new();
+/// assert_eq!(recaser.recase("SENATVS POPVLVSQVE ROMANVS"), "Senatvs popvlvsqve Romanvs");
+/// let recaser = recaser.names(Arc::new(Lexicon::from_words(["hadriano"])));
+/// assert_eq!(recaser.recase("DIVO HADRIANO"), "Divo Hadriano");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Recaser {
+    names: Arc<Lexicon>,
+}
+
+impl Default for Recaser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One word of the text, by char offsets.
+struct Word {
+    start: usize,
+    end: usize,
+    all_caps: bool,
+}
+
+impl Recaser {
+    /// Capitalize the [`CLASSICAL_NAMES`].
+    pub fn new() -> Self {
+        Recaser {
+            names: Arc::new(Lexicon::from_words(CLASSICAL_NAMES)),
+        }
+    }
+
+    /// Capitalize the words of `names` instead. A name written with *v* for
+    /// *u* (*ROMANVS*) matches its *u* spelling.
+    pub fn names(mut self, names: Arc<Lexicon>) -> Self {
+        self.names = names;
+        self
+    }
+
+    fn is_name(&self, lower: &str) -> bool {
+        names::lookup(&self.names, lower)
+    }
+
+    pub fn recase(&self, text: &str) -> String {
+        self.recase_detailed(text).normalized
+    }
+
+    /// Like [`Recaser::recase`], recording one [`ChangeRecord`] per recased
+    /// word. The rule is `"lowercase"`, `"sentence start"`, or `"proper
+    /// name"`. A skipped run of single capitals (*S P Q R*) gets a
+    /// [`WarningKind::AllCapsSkipped`] warning.
+    pub fn recase_detailed(&self, text: &str) -> DetailedResult {
+        let chars: Vec<char> = text.chars().collect();
+        let words = words(&chars);
+        let mut normalized = String::with_capacity(text.len());
+        let mut changes = Vec::new();
+        let mut warnings = Vec::new();
+
+        let mut last = 0;
+        let mut w = 0;
+        while w < words.len() {
+            if !words[w].all_caps {
+                w += 1;
+                continue;
+            }
+            let span_end = words[w..].iter().position(|word| !word.all_caps).map_or(words.len(), |p| w + p);
+            // A span of single capitals (*A*, *I*) may be ordinary
+            // capitalization
+            if words[w..span_end].iter().all(|word| word.end - word.start < 2) {
+                if span_end - w > 1 {
+                    let (start, end) = (words[w].start, words[span_end - 1].end);
+                    let span: String = chars[start..end].iter().collect();
+                    let message = format!("`{}` left in capitals: may be initials or an abbreviation", span);
+                    warnings.push(Warning::new(&chars, start, end - start, WarningKind::AllCapsSkipped, message));
+                }
+                w = span_end;
+                continue;
+            }
+            for (k, word) in words[w..span_end].iter().enumerate() {
+                let original: String = chars[word.start..word.end].iter().collect();
+                let lower = original.to_lowercase();
+                // Numerals keep their capitals; in capitals, *VI* and *DI* are
+                // as likely words as numbers, *I* more likely a number
+                let numeral_shaped_word = lower.len() > 1 && numerals::COMMON_WORDS.contains(&lower.as_str());
+                if numerals::in_numeral(&chars, word.start) && !numeral_shaped_word {
+                    continue;
+                }
+                let rule = if starts_sentence(&chars, word.start, k == 0) {
+                    "sentence start"
+                } else if self.is_name(&lower) {
+                    "proper name"
+                } else {
+                    "lowercase"
+                };
+                let recased = if rule == "lowercase" { lower } else { capitalize(&lower) };
+                if recased == original {
+                    continue;
+                }
+                normalized.extend(&chars[last..word.start]);
+                normalized.push_str(&recased);
+                last = word.end;
+                changes.push(ChangeRecord {
+                    position: word.start,
+                    original,
+                    normalized: recased,
+                    pass: 1,
+                    rule: rule.to_string(),
+                    context: changes::context(&chars, word.start, word.end - word.start),
+                });
+            }
+            w = span_end;
+        }
+        normalized.extend(&chars[last..]);
+
+        DetailedResult {
+            original: text.to_string(),
+            normalized,
+            changes,
+            warnings,
+        }
+    }
+}
+
+/// The words of `chars`, each marked all-caps if it has a capital and no
+/// lowercase letter.
+fn words(chars: &[char]) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_alphabetic() {
+            i += 1;
+            continue;
+        }
+        let end = chars[i..].iter().position(|c| !c.is_alphabetic()).map_or(chars.len(), |p| i + p);
+        let word = &chars[i..end];
+        let all_caps = word.iter().any(|c| c.is_uppercase()) && !word.iter().any(|c| c.is_lowercase());
+        words.push(Word { start: i, end, all_caps });
+        i = end;
+    }
+    words
+}
+
+/// Whether the word at `start` begins a sentence: it follows `.`, `!`,
+/// `?`, a blank line, or nothing, or (for the first word of a span) a line
+/// break.
+fn starts_sentence(chars: &[char], start: usize, first_in_span: bool) -> bool {
+    let mut line_breaks = 0;
+    for &c in chars[..start].iter().rev() {
+        if c == '\n' {
+            line_breaks += 1;
+        } else if !c.is_whitespace() {
+            return matches!(c, '.' | '!' | '?') || line_breaks > 1 || (line_breaks == 1 && first_in_span);
+        }
+    }
+    true
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+}
+
+/// Recase all-caps spans with the default [`Recaser`].
+///
+/// ```
+/// use latincy_preprocess::recase::recase;
+///
+/// assert_eq!(recase("DE BELLO GALLICO\n\nLIBER I"), "De bello gallico\n\nLiber I");
+/// ```
+pub fn recase(text: &str) -> String {
+    Recaser::new().recase(text)
+}
+
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<ChangeRecord>,
+    pub warnings: Vec<Warning>,
+}
+
+impl Normalizer for Recaser {
+    fn name(&self) -> &str {
+        "recase"
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = self.recase_detailed(text);
+        NormalizeOutput::from_changes(result.original, result.normalized, result.changes).with_warnings(result.warnings)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "recase")]
+pub fn py_recase(py: Python<'_>, text: &str) -> String {
+    crate::allow_threads(py, text, || recase(text))
+}
+
+/// Python view of [`Recaser`], exposed as `_rust.Recaser`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "Recaser", frozen)]
+pub struct PyRecaser {
+    pub(crate) inner: Recaser,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyRecaser {
+    /// `names` is a lexicon file of proper names, replacing the built-in
+    /// classical names.
+    #[new]
+    #[pyo3(signature = (names=None))]
+    fn new(names: Option<std::path::PathBuf>) -> PyResult<Self> {
+        let mut inner = Recaser::new();
+        if let Some(names) = names {
+            inner = inner.names(Arc::new(Lexicon::from_file(names)?));
+        }
+        Ok(PyRecaser { inner })
+    }
+
+    fn recase(&self, py: Python<'_>, text: &str) -> String {
+        crate::allow_threads(py, text, || self.inner.recase(text))
+    }
+
+    fn recase_detailed(&self, py: Python<'_>, text: &str) -> PyResult<changes::PyDetailedResult> {
+        let result = crate::allow_threads(py, text, || self.inner.recase_detailed(text));
+        changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)?
+            .with_warnings(py, &result.warnings)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recase() {
+        assert_eq!(recase("SENATVS POPVLVSQVE ROMANVS"), "Senatvs popvlvsqve Romanvs");
+        assert_eq!(recase("ARMA VIRVMQVE CANO. TROIAE QVI PRIMVS"), "Arma virvmqve cano. Troiae qvi primvs");
+    }
+
+    #[test]
+    fn test_mixed_case_untouched() {
+        assert_eq!(recase("Gallia est omnis divisa"), "Gallia est omnis divisa");
+        // A capital word inside running text is recased on its own
+        assert_eq!(recase("ut ait ROMA et CAESAR dixit"), "ut ait Roma et Caesar dixit");
+        assert_eq!(recase("A Roma I domum"), "A Roma I domum");
+    }
+
+    #[test]
+    fn test_numerals_and_lines() {
+        assert_eq!(recase("LIBER XVI\n\nDE ORATORE"), "Liber XVI\n\nDe oratore");
+        assert_eq!(recase("Prooemium\nDE ORATORE"), "Prooemium\nDe oratore");
+        // A line break inside a span is not a sentence start
+        assert_eq!(recase("IN HOC\nSIGNO VINCES"), "In hoc\nsigno vinces");
+        assert_eq!(recase("DIS MANIBVS. DI MANES"), "Dis manibvs. Di manes");
+    }
+
+    #[test]
+    fn test_names() {
+        let recaser = Recaser::new().names(Arc::new(Lexicon::from_words(["traianus", "dacia"])));
+        assert_eq!(recaser.recase("IMP. TRAIANVS DACIAM VICIT"), "Imp. Traianvs daciam vicit");
+        assert_eq!(recaser.recase("IN DACIA"), "In Dacia");
+    }
+
+    #[test]
+    fn test_recase_detailed() {
+        let result = Recaser::new().recase_detailed("et ROMA");
+        assert_eq!(result.normalized, "et Roma");
+        assert_eq!(result.changes.len(), 1);
+        assert_eq!(result.changes[0].position, 3);
+        assert_eq!(result.changes[0].original, "ROMA");
+        assert_eq!(result.changes[0].rule, "proper name");
+        let rules: Vec<_> =
+            Recaser::new().recase_detailed("ARMA CANO").changes.into_iter().map(|c| c.rule).collect();
+        assert_eq!(rules, vec!["sentence start", "lowercase"]);
+    }
+
+    #[test]
+    fn test_all_caps_skipped_warning() {
+        let result = Recaser::new().recase_detailed("S P Q R et A Roma");
+        assert_eq!(result.normalized, "S P Q R et A Roma");
+        assert_eq!(result.warnings.len(), 1);
+        let warning = &result.warnings[0];
+        assert_eq!((warning.position, warning.len), (0, 7));
+        assert_eq!(warning.kind, WarningKind::AllCapsSkipped);
+        assert_eq!(warning.message, "`S P Q R` left in capitals: may be initials or an abbreviation");
+    }
+}
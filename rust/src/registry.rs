@@ -0,0 +1,94 @@
+//! Stage names mapped to normalizer constructors.
+//!
+//! The registry starts with the built-in [`STAGES`], each built in its
+//! default configuration. Pipeline config files, the Python `Pipeline`, and
+//! [`pipeline::stage`] look stage names up here, so a crate that
+//! [`register`]s a stage of its own can use it by name everywhere a
+//! built-in stage can be used.
+//!
+//! ```
+//! use latincy_preprocess::pipeline::{FnNormalizer, Normalizer};
+//! use latincy_preprocess::registry;
+//!
+//! let tironian = |text: &str| text.replace('⁊', "et");
+//! registry::register("tironian", move || Box::new(FnNormalizer::new("tironian", tironian)));
+//! assert!(registry::list().iter().any(|name| name == "tironian"));
+//! assert_eq!(registry::get("tironian").unwrap().normalize_text("⁊ uxor"), "et uxor");
+//! ```
+
+use crate::pipeline::{self, Normalizer, STAGES};
+use std::sync::{Arc, LazyLock, RwLock};
+
+/// Builds a stage's normalizer.
+pub type Constructor = Arc<dyn Fn() -> Box<dyn Normalizer> + Send + Sync>;
+
+static REGISTRY: LazyLock<RwLock<Vec<(String, Constructor)>>> = LazyLock::new(|| {
+    let builtins = STAGES.iter().map(|&name| {
+        let constructor: Constructor = Arc::new(move || pipeline::builtin(name).expect("a built-in stage"));
+        (name.to_string(), constructor)
+    });
+    RwLock::new(builtins.collect())
+});
+
+/// A new normalizer for the stage `name`, or `None` if no stage of that
+/// name is registered.
+pub fn get(name: &str) -> Option<Box<dyn Normalizer>> {
+    let constructor = {
+        let registry = REGISTRY.read().unwrap_or_else(|e| e.into_inner());
+        registry.iter().find(|(registered, _)| registered == name).map(|(_, constructor)| Arc::clone(constructor))?
+    };
+    Some(constructor())
+}
+
+/// Whether a stage called `name` is registered.
+pub fn contains(name: &str) -> bool {
+    REGISTRY.read().unwrap_or_else(|e| e.into_inner()).iter().any(|(registered, _)| registered == name)
+}
+
+/// The registered stage names: the built-in [`STAGES`], then the
+/// [`register`]ed ones in the order they were first registered.
+pub fn list() -> Vec<String> {
+    REGISTRY.read().unwrap_or_else(|e| e.into_inner()).iter().map(|(name, _)| name.clone()).collect()
+}
+
+/// Register `constructor` as the stage `name`. Registering a name again,
+/// built-in or not, replaces its constructor.
+pub fn register(name: &str, constructor: impl Fn() -> Box<dyn Normalizer> + Send + Sync + 'static) {
+    let constructor: Constructor = Arc::new(constructor);
+    let mut registry = REGISTRY.write().unwrap_or_else(|e| e.into_inner());
+    match registry.iter_mut().find(|(registered, _)| registered == name) {
+        Some((_, existing)) => *existing = constructor,
+        None => registry.push((name.to_string(), constructor)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::FnNormalizer;
+
+    #[test]
+    fn test_builtins() {
+        let names = list();
+        assert_eq!(&names[..STAGES.len()], STAGES);
+        for name in STAGES {
+            assert_eq!(get(name).unwrap().name(), *name);
+        }
+        assert!(get("nonesuch").is_none());
+        assert!(!contains("nonesuch"));
+    }
+
+    #[test]
+    fn test_register() {
+        register("registry_test", || Box::new(FnNormalizer::new("registry_test", |text: &str| text.to_uppercase())));
+        assert!(contains("registry_test"));
+        assert_eq!(get("registry_test").unwrap().normalize_text("uia"), "UIA");
+        register("registry_test", || Box::new(FnNormalizer::new("registry_test", |text: &str| text.to_string())));
+        assert_eq!(get("registry_test").unwrap().normalize_text("uia"), "uia");
+        assert_eq!(list().iter().filter(|name| *name == "registry_test").count(), 1);
+    }
+}
@@ -0,0 +1,252 @@
+//! Golden-corpus tests: a directory of inputs and the output expected of
+//! them, checked against a pipeline.
+//!
+//! A project that pins this crate can keep texts of its own with the
+//! normalized form it relies on, and learn from a failing check, rather
+//! than from its results, that a new version normalizes them differently.
+//! Each case is a pair of files, `NAME.input.txt` and `NAME.expected.txt`;
+//! [`bless`] writes the expected files from a pipeline's current output, to
+//! start a corpus or accept a change in behavior.
+//!
+//! ```
+//! use latincy_preprocess::regression::{self, Corpus};
+//! use latincy_preprocess::uv::UvNormalizer;
+//!
+//! let dir = std::env::temp_dir().join(format!("latincy_regression_doc_{}", std::process::id()));
+//! std::fs::create_dir_all(&dir).unwrap();
+//! std::fs::write(dir.join("aeneid.input.txt"), "arma uirumque cano\n").unwrap();
+//! std::fs::write(dir.join("aeneid.expected.txt"), "arma virumque cano\n").unwrap();
+//!
+//! let report = regression::run(&UvNormalizer::new(), &Corpus::load(&dir).unwrap());
+//! assert!(report.passed());
+//! # std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+
+use crate::pipeline::Normalizer;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The file name suffix of a case's input.
+pub const INPUT_SUFFIX: &str = ".input.txt";
+/// The file name suffix of a case's expected output.
+pub const EXPECTED_SUFFIX: &str = ".expected.txt";
+
+/// One input and the output expected of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    /// The file name without its suffix.
+    pub name: String,
+    pub input: String,
+    pub expected: String,
+}
+
+/// The cases of a golden-corpus directory, in name order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Corpus {
+    pub cases: Vec<Case>,
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// The names of the cases in `dir`: the files ending in `suffix`, without
+/// it.
+fn names(dir: &Path, suffix: &str) -> io::Result<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(|e| located(dir, e))? {
+        let file_name = entry.map_err(|e| located(dir, e))?.file_name();
+        if let Some(name) = file_name.to_str().and_then(|name| name.strip_suffix(suffix)) {
+            names.push(name.to_string());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// `e`, naming `path`.
+fn located(path: &Path, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {}", path.display(), e))
+}
+
+fn read(path: &Path) -> io::Result<String> {
+    std::fs::read_to_string(path).map_err(|e| located(path, e))
+}
+
+impl Corpus {
+    /// The cases of the directory `dir`. Errors name the file at fault; an
+    /// input without an expected file, or an expected file without an
+    /// input, is an [`io::ErrorKind::InvalidData`] error, so a case cannot
+    /// drop out of the corpus unnoticed.
+    pub fn load(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        let inputs = names(dir, INPUT_SUFFIX)?;
+        if let Some(orphan) = names(dir, EXPECTED_SUFFIX)?.into_iter().find(|name| !inputs.contains(name)) {
+            let orphan = path(dir, &orphan, EXPECTED_SUFFIX);
+            return Err(invalid(format!("{}: no input for this expected output", orphan.display())));
+        }
+        let mut cases = Vec::with_capacity(inputs.len());
+        for name in inputs {
+            let expected = path(dir, &name, EXPECTED_SUFFIX);
+            if !expected.exists() {
+                return Err(invalid(format!("{}: missing; run `bless` to create it", expected.display())));
+            }
+            cases.push(Case {
+                input: read(&path(dir, &name, INPUT_SUFFIX))?,
+                expected: read(&expected)?,
+                name,
+            });
+        }
+        Ok(Corpus { cases })
+    }
+
+    pub fn len(&self) -> usize {
+        self.cases.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cases.is_empty()
+    }
+}
+
+fn path(dir: &Path, name: &str, suffix: &str) -> PathBuf {
+    dir.join(format!("{name}{suffix}"))
+}
+
+/// A case whose output is not the one expected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub name: String,
+    /// The 1-based number of the first line that differs.
+    pub line: usize,
+    pub expected: String,
+    pub actual: String,
+    /// A unified diff from the expected output to the actual one.
+    pub diff: String,
+}
+
+/// The outcome of [`run`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// How many cases were checked.
+    pub cases: usize,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl Report {
+    /// Whether every case gave its expected output.
+    pub fn passed(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// The report as a JSON object with the number of cases, the number
+    /// passed, and each mismatch with its name, line, and diff.
+    pub fn to_json(&self) -> String {
+        let mismatches: Vec<serde_json::Value> = self
+            .mismatches
+            .iter()
+            .map(|m| serde_json::json!({"name": m.name, "line": m.line, "diff": m.diff}))
+            .collect();
+        serde_json::json!({
+            "cases": self.cases,
+            "passed": self.cases - self.mismatches.len(),
+            "mismatches": mismatches,
+        })
+        .to_string()
+    }
+}
+
+/// Each mismatch's diff, then a summary line.
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for mismatch in &self.mismatches {
+            write!(f, "{}", mismatch.diff)?;
+        }
+        let failed = self.mismatches.len();
+        write!(f, "{} cases, {} passed, {} failed", self.cases, self.cases - failed, failed)
+    }
+}
+
+/// `normalizer` checked against every case of `corpus`.
+pub fn run(normalizer: &dyn Normalizer, corpus: &Corpus) -> Report {
+    let mismatches = corpus
+        .cases
+        .iter()
+        .filter_map(|case| {
+            let actual = normalizer.normalize_text(&case.input);
+            (actual != case.expected).then(|| {
+                let line = case.expected.split('\n').zip(actual.split('\n')).take_while(|(a, b)| a == b).count() + 1;
+                let (from, to) = (format!("{}{EXPECTED_SUFFIX}", case.name), format!("{} (actual)", case.name));
+                Mismatch {
+                    name: case.name.clone(),
+                    line,
+                    diff: crate::diff::unified(&case.expected, &actual, &from, &to, 3),
+                    expected: case.expected.clone(),
+                    actual,
+                }
+            })
+        })
+        .collect();
+    Report {
+        cases: corpus.len(),
+        mismatches,
+    }
+}
+
+/// Write each `NAME.expected.txt` of `dir` as `normalizer` normalizes its
+/// `NAME.input.txt`, returning the names of the cases whose expected file
+/// was created or changed.
+pub fn bless(normalizer: &dyn Normalizer, dir: impl AsRef<Path>) -> io::Result<Vec<String>> {
+    let dir = dir.as_ref();
+    let mut changed = Vec::new();
+    for name in names(dir, INPUT_SUFFIX)? {
+        let output = normalizer.normalize_text(&read(&path(dir, &name, INPUT_SUFFIX))?);
+        let expected = path(dir, &name, EXPECTED_SUFFIX);
+        if std::fs::read_to_string(&expected).ok().as_deref() != Some(output.as_str()) {
+            std::fs::write(&expected, output).map_err(|e| located(&expected, e))?;
+            changed.push(name);
+        }
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uv::UvNormalizer;
+
+    #[test]
+    fn test_run_and_bless() {
+        let dir = std::env::temp_dir().join(format!("latincy_regression_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.input.txt"), "arma\nuirumque cano\n").unwrap();
+        std::fs::write(dir.join("a.expected.txt"), "arma\nvirumque cano\n").unwrap();
+        std::fs::write(dir.join("b.input.txt"), "seruus\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a case").unwrap();
+        let error = Corpus::load(&dir).unwrap_err().to_string();
+        assert!(error.ends_with("b.expected.txt: missing; run `bless` to create it"));
+
+        let normalizer = UvNormalizer::new();
+        assert_eq!(bless(&normalizer, &dir).unwrap(), ["b"]);
+        assert_eq!(bless(&normalizer, &dir).unwrap(), Vec::<String>::new());
+        let corpus = Corpus::load(&dir).unwrap();
+        assert_eq!(corpus.cases.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+        assert!(run(&normalizer, &corpus).passed());
+
+        // A change in behavior is a mismatch at the line it shows in
+        let report = run(&normalizer.vocalic_words(["uirumque"]), &corpus);
+        assert_eq!(report.mismatches.len(), 1);
+        let mismatch = &report.mismatches[0];
+        assert_eq!((mismatch.name.as_str(), mismatch.line), ("a", 2));
+        assert_eq!(mismatch.actual, "arma\nuirumque cano\n");
+        assert!(mismatch.diff.contains("-virumque cano\n+uirumque cano\n"));
+        assert!(report.to_string().ends_with("2 cases, 1 passed, 1 failed"));
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!((json["cases"].as_u64(), json["passed"].as_u64()), (Some(2), Some(1)));
+
+        std::fs::write(dir.join("c.expected.txt"), "").unwrap();
+        assert_eq!(Corpus::load(&dir).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+//! Byte scans for the few positions a text-level pass has to look at.
+//!
+//! Most of a text is letters the u/v and long-s rules never touch. These
+//! scans read it eight bytes at a time, so the passes can jump from one
+//! candidate to the next and copy everything in between as it is.
+
+const ONES: u64 = 0x0101_0101_0101_0101;
+const HIGH: u64 = 0x8080_8080_8080_8080;
+
+/// The high bit of every zero byte of `x`. Bytes above the first zero byte
+/// may be flagged wrongly, so only the lowest flag is exact.
+fn zero_bytes(x: u64) -> u64 {
+    x.wrapping_sub(ONES) & !x & HIGH
+}
+
+/// The high bit of every byte of `chunk` equal to `byte`, ignoring ASCII
+/// case.
+fn folded_bytes(chunk: u64, byte: u8) -> u64 {
+    zero_bytes((chunk | (0x20 * ONES)) ^ ((byte | 0x20) as u64 * ONES))
+}
+
+/// The index of the first byte of `bytes` at or after `from` flagged by
+/// `chunk_hits`, which `byte_hit` decides for a single byte.
+fn find(bytes: &[u8], from: usize, chunk_hits: impl Fn(u64) -> u64, byte_hit: impl Fn(u8) -> bool) -> Option<usize> {
+    let mut i = from;
+    while let Some(chunk) = bytes.get(i..i + 8) {
+        let hits = chunk_hits(u64::from_le_bytes(chunk.try_into().expect("chunk is eight bytes")));
+        if hits != 0 {
+            return Some(i + hits.trailing_zeros() as usize / 8);
+        }
+        i += 8;
+    }
+    bytes.get(i..)?.iter().position(|&b| byte_hit(b)).map(|p| i + p)
+}
+
+/// The first `u`, `v`, `U` or `V` at or after byte `from`.
+pub(crate) fn find_uv(bytes: &[u8], from: usize) -> Option<usize> {
+    find(
+        bytes,
+        from,
+        |chunk| folded_bytes(chunk, b'u') | folded_bytes(chunk, b'v'),
+        |b| matches!(b, b'u' | b'v' | b'U' | b'V'),
+    )
+}
+
+/// The first byte at or after `from` that may start the long-s passes
+/// changing a word: an `f` or `F`, or any byte of a non-ASCII character
+/// (long s, ligatures).
+pub(crate) fn find_long_s(bytes: &[u8], from: usize) -> Option<usize> {
+    find(
+        bytes,
+        from,
+        |chunk| folded_bytes(chunk, b'f') | (chunk & HIGH),
+        |b| matches!(b, b'f' | b'F') || !b.is_ascii(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every byte value at every offset of a chunk and of the tail agrees
+    /// with the byte-at-a-time definition.
+    fn check(scan: fn(&[u8], usize) -> Option<usize>, hit: fn(u8) -> bool) {
+        for byte in 0..=u8::MAX {
+            for at in 0..13 {
+                let mut bytes = vec![b'a'; 13];
+                bytes[at] = byte;
+                let expected = bytes.iter().position(|&b| hit(b));
+                assert_eq!(scan(&bytes, 0), expected, "byte {byte:#x} at {at}");
+                assert_eq!(scan(&bytes, at + 1), None, "byte {byte:#x} at {at}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_scans_match_bytewise() {
+        check(find_uv, |b| matches!(b, b'u' | b'v' | b'U' | b'V'));
+        check(find_long_s, |b| matches!(b, b'f' | b'F') || !b.is_ascii());
+        assert_eq!(find_uv(b"arma uirumque", 0), Some(5));
+        assert_eq!(find_uv(b"arma uirumque", 6), Some(8));
+        assert_eq!(find_uv(b"arma", 9), None);
+        assert_eq!(find_long_s("Arma \u{017F}".as_bytes(), 0), Some(5));
+    }
+}
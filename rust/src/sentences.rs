@@ -0,0 +1,341 @@
+//! Sentence segmentation of Latin text.
+//!
+//! LatinCy expects one sentence at a time, and a full stop in Latin prose
+//! often ends no sentence: the praenomina of *M. Tullius* and *Cn. Pompeius*,
+//! dates (*a. d. III Kal. Ian.*), offices (*cos.*, *trib. pl.*), references
+//! (*lib. III. cap. 4*), and the sigla of an apparatus (*om.*, *codd.*).
+//! [`Segmenter`] ends a sentence at `.`, `!`, or `?` followed by whitespace,
+//! unless:
+//!
+//! - the full stop follows a known abbreviation or a single letter,
+//! - the full stop follows a number (Arabic or Roman) and the next word is
+//!   not capitalized,
+//! - the stop is an ellipsis or lacuna dots (`...`, `…`) and the next word is
+//!   not capitalized,
+//! - it stands inside a bracket pair (`(...)`, `[...]`, `<...>`, `⟨...⟩`,
+//!   `{...}`, `⟦...⟧`) or between obeli (`†...†`).
+//!
+//! A sentence takes the closing quotes after its stop; a blank line always
+//! ends one.
+
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::types::{PyDict, PyList};
+
+/// The abbreviated praenomina. They match case-sensitively, so *Sex.* is
+/// Sextus and *sex.* six.
+pub const PRAENOMINA: &[&str] = &[
+    "A", "Ap", "C", "Cn", "D", "K", "L", "M", "M'", "Mam", "N", "Num", "P", "Q", "Ser", "Sex", "Sp", "St", "T",
+    "Ti", "Tib", "V", "Vib",
+];
+
+/// Abbreviations followed by a full stop: offices, dates, references, and
+/// apparatus sigla. A lowercase entry also matches its capitalized and
+/// all-caps forms (*cos.*, *Cos.*, *COS.*); a capitalized one matches only
+/// as written (*Non.*, not *non.*).
+pub const ABBREVIATIONS: &[&str] = &[
+    // Offices and titles
+    "cos", "coss", "procos", "imp", "trib", "pl", "pont", "max", "pot", "cens", "aed", "pr", "praef", "leg", "sen",
+    "eq", "fil", "lib", "fl", "des",
+    // Dates
+    "Kal", "Non", "Id", "Idib", "Ian", "Febr", "Feb", "Mart", "Apr", "Mai", "Iun", "Iul", "Quint", "Sext", "Sept",
+    "Oct", "Nov", "Dec",
+    // References
+    "cap", "capp", "libr", "fr", "frg", "fol", "pp", "vv", "vol", "tom", "ep", "epist", "adn", "ann", "nr", "sect",
+    "cf", "sc", "scil", "ib", "ibid", "etc", "sq", "sqq", "al", "ed", "edd",
+    // Apparatus sigla
+    "om", "add", "del", "corr", "coni", "conj", "suppl", "secl", "transp", "cod", "codd", "cett", "dett", "rec",
+    "recc", "vulg", "mg", "marg", "ms", "mss",
+];
+
+/// Brackets within which no sentence ends.
+const BRACKETS: &[(char, char)] = &[('(', ')'), ('[', ']'), ('<', '>'), ('⟨', '⟩'), ('{', '}'), ('⟦', '⟧')];
+
+/// Closing quotes taken into the sentence they follow.
+const CLOSING_QUOTES: &[char] = &['"', '\'', '»', '«', '”', '’', '›'];
+
+/// Opening quotes and brackets looked through to find the next word.
+const OPENERS: &[char] = &['"', '\'', '«', '»', '“', '‘', '‹', '(', '[', '<', '⟨', '{', '⟦'];
+
+/// A sentence of the text, by char offsets, without surrounding whitespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Splits text into sentences.
+///
+/// ```
+/// use latincy_preprocess::sentences::{Segmenter, Span};
+///
+/// let segmenter = Segmenter::new();
+/// assert_eq!(
+///     segmenter.split("Scripsit M. Tullius. Venit."),
+///     vec![Span { start: 0, end: 20 }, Span { start: 21, end: 27 }]
+/// );
+/// let segmenter = segmenter.abbreviations(["Tullius"]);
+/// assert_eq!(segmenter.split("Scripsit M. Tullius. Venit.").len(), 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Segmenter {
+    extra: Vec<String>,
+}
+
+impl Segmenter {
+    /// Recognize the [`PRAENOMINA`] and [`ABBREVIATIONS`].
+    pub fn new() -> Self {
+        Segmenter { extra: Vec::new() }
+    }
+
+    /// Also recognize `words` (without their full stop), matched as the
+    /// [`ABBREVIATIONS`] are.
+    pub fn abbreviations<S: Into<String>>(mut self, words: impl IntoIterator<Item = S>) -> Self {
+        self.extra.extend(words.into_iter().map(Into::into));
+        self
+    }
+
+    /// Whether `word` is a known abbreviation.
+    pub fn is_abbreviation(&self, word: &str) -> bool {
+        let lower = word.to_lowercase();
+        let matches = |entry: &str| entry == word || (entry == lower && !entry.chars().any(char::is_uppercase));
+        PRAENOMINA.contains(&word) || ABBREVIATIONS.iter().any(|e| matches(e)) || self.extra.iter().any(|e| matches(e))
+    }
+
+    /// The sentences of `text`.
+    pub fn split(&self, text: &str) -> Vec<Span> {
+        let chars: Vec<char> = text.chars().collect();
+        let bracketed = bracketed(&chars);
+        let mut spans = Vec::new();
+        let mut start = None;
+
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            if start.is_none() {
+                if !c.is_whitespace() {
+                    start = Some(i);
+                }
+                i += 1;
+                continue;
+            }
+            if c == '\n' && blank_line(&chars, i) {
+                close(&chars, &mut start, i, &mut spans);
+                i += 1;
+                continue;
+            }
+            if !is_stop(c) || bracketed[i] {
+                i += 1;
+                continue;
+            }
+            let stops = chars[i..].iter().take_while(|&&c| is_stop(c)).count();
+            let mut end = i + stops;
+            end += chars[end..].iter().take_while(|c| CLOSING_QUOTES.contains(c)).count();
+            if chars.get(end).is_none_or(|c| c.is_whitespace()) && self.ends_sentence(&chars, i, stops, end) {
+                close(&chars, &mut start, end, &mut spans);
+            }
+            i = end;
+        }
+        close(&chars, &mut start, chars.len(), &mut spans);
+        spans
+    }
+
+    /// Whether the stops `chars[i..i + stops]`, with the text resuming at
+    /// `next`, end a sentence.
+    fn ends_sentence(&self, chars: &[char], i: usize, stops: usize, next: usize) -> bool {
+        let run = &chars[i..i + stops];
+        if run.contains(&'…') || (stops >= 3 && run.iter().all(|&c| c == '.')) {
+            return next_capitalized(chars, next);
+        }
+        if run.iter().any(|&c| c != '.') {
+            return true;
+        }
+        let word_start = chars[..i].iter().rposition(|&c| !c.is_alphabetic() && c != '\'').map_or(0, |p| p + 1);
+        if word_start == i {
+            let number = i > 0 && chars[i - 1].is_ascii_digit();
+            return !number || next_capitalized(chars, next);
+        }
+        let word: String = chars[word_start..i].iter().collect();
+        if self.is_abbreviation(&word) || word.chars().count() == 1 {
+            false
+        } else if crate::numerals::in_numeral(chars, i - 1) {
+            next_capitalized(chars, next)
+        } else {
+            true
+        }
+    }
+}
+
+fn is_stop(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '…')
+}
+
+/// Whether the line break at `chars[i]` is followed by a blank line.
+fn blank_line(chars: &[char], i: usize) -> bool {
+    chars[i + 1..].iter().take_while(|c| c.is_whitespace()).any(|&c| c == '\n')
+}
+
+/// End the open sentence at `end`, trimming trailing whitespace.
+fn close(chars: &[char], start: &mut Option<usize>, end: usize, spans: &mut Vec<Span>) {
+    if let Some(s) = start.take() {
+        let end = chars[s..end].iter().rposition(|c| !c.is_whitespace()).map_or(s, |p| s + p + 1);
+        if end > s {
+            spans.push(Span { start: s, end });
+        }
+    }
+}
+
+/// Whether the first word at or after `next`, past whitespace and opening
+/// quotes or brackets, is capitalized. The end of the text counts as one.
+fn next_capitalized(chars: &[char], next: usize) -> bool {
+    chars[next..]
+        .iter()
+        .find(|c| !c.is_whitespace() && !OPENERS.contains(c))
+        .is_none_or(|c| c.is_uppercase() || c.is_ascii_digit())
+}
+
+/// For each char, whether it lies inside a matched bracket pair or between
+/// two obeli. Unmatched brackets enclose nothing.
+fn bracketed(chars: &[char]) -> Vec<bool> {
+    let mut inside = vec![false; chars.len()];
+    let mut open: Vec<(usize, char)> = Vec::new();
+    let mut obelus = None;
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '†' {
+            match obelus.take() {
+                Some(o) => inside[o + 1..i].fill(true),
+                None => obelus = Some(i),
+            }
+        } else if let Some(&(_, close)) = BRACKETS.iter().find(|(o, _)| *o == c) {
+            open.push((i, close));
+        } else if BRACKETS.iter().any(|(_, close)| *close == c) {
+            if let Some(depth) = open.iter().rposition(|&(_, close)| close == c) {
+                inside[open[depth].0 + 1..i].fill(true);
+                open.truncate(depth);
+            }
+        }
+    }
+    inside
+}
+
+/// Split `text` into sentences with the default [`Segmenter`].
+///
+/// ```
+/// use latincy_preprocess::sentences::split;
+///
+/// let text = "Cn. Pompeius cos. III fuit. Quid (inquit) ais? Nihil.";
+/// let sentences: Vec<String> =
+///     split(text).into_iter().map(|s| text.chars().skip(s.start).take(s.end - s.start).collect()).collect();
+/// assert_eq!(sentences, ["Cn. Pompeius cos. III fuit.", "Quid (inquit) ais?", "Nihil."]);
+/// ```
+pub fn split(text: &str) -> Vec<Span> {
+    Segmenter::new().split(text)
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+/// The sentences of `text` as dicts of `start`, `end` (char offsets), and
+/// `text`. `abbreviations` adds to the built-in abbreviations.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "split_sentences", signature = (text, abbreviations=None))]
+pub fn py_split_sentences<'py>(
+    py: Python<'py>,
+    text: &str,
+    abbreviations: Option<Vec<String>>,
+) -> PyResult<Bound<'py, PyList>> {
+    let segmenter = Segmenter::new().abbreviations(abbreviations.unwrap_or_default());
+    let chars: Vec<char> = text.chars().collect();
+    let list = PyList::empty(py);
+    for span in crate::allow_threads(py, text, || segmenter.split(text)) {
+        let dict = PyDict::new(py);
+        dict.set_item("start", span.start)?;
+        dict.set_item("end", span.end)?;
+        dict.set_item("text", chars[span.start..span.end].iter().collect::<String>())?;
+        list.append(dict)?;
+    }
+    Ok(list)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sentences(text: &str) -> Vec<String> {
+        let chars: Vec<char> = text.chars().collect();
+        split(text).into_iter().map(|s| chars[s.start..s.end].iter().collect()).collect()
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!(sentences("Gallia est omnis divisa. Horum omnium fortissimi sunt Belgae!"), [
+            "Gallia est omnis divisa.",
+            "Horum omnium fortissimi sunt Belgae!"
+        ]);
+        assert_eq!(sentences("  quid agis?  nihil  "), ["quid agis?", "nihil"]);
+        assert!(sentences(" \n ").is_empty());
+    }
+
+    #[test]
+    fn test_abbreviations() {
+        assert_eq!(sentences("M. Tullius et Cn. Pompeius et M'. Curius venerunt. Tum abierunt."), [
+            "M. Tullius et Cn. Pompeius et M'. Curius venerunt.",
+            "Tum abierunt."
+        ]);
+        assert_eq!(sentences("a. d. III Kal. Ian. L. Domitio Ap. Claudio cos. senatus fuit."), [
+            "a. d. III Kal. Ian. L. Domitio Ap. Claudio cos. senatus fuit."
+        ]);
+        // Case-sensitive praenomina and dates
+        assert_eq!(sentences("erant sex. Non. Ian. venit"), ["erant sex.", "Non. Ian. venit"]);
+        assert_eq!(sentences("uenit post. deinde abiit"), ["uenit post.", "deinde abiit"]);
+        assert!(Segmenter::new().is_abbreviation("COS"));
+        assert!(!Segmenter::new().is_abbreviation("non"));
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(sentences("vide lib. III. cap. 4. sect. 2. Nunc dicam."), [
+            "vide lib. III. cap. 4. sect. 2.",
+            "Nunc dicam."
+        ]);
+        assert_eq!(sentences("anno MDCXV. Tum pax."), ["anno MDCXV.", "Tum pax."]);
+        assert_eq!(sentences("annos xv. inde rediit"), ["annos xv. inde rediit"]);
+        // *vi* is a word, not a numeral
+        assert_eq!(sentences("cepit vi. deinde"), ["cepit vi.", "deinde"]);
+    }
+
+    #[test]
+    fn test_brackets_and_sigla() {
+        assert_eq!(sentences("hoc dixit (quid enim? nihil.) et abiit. Tum"), [
+            "hoc dixit (quid enim? nihil.) et abiit.",
+            "Tum"
+        ]);
+        assert_eq!(sentences("uenit <ad urbem. et> mansit."), ["uenit <ad urbem. et> mansit."]);
+        assert_eq!(sentences("†quid. ait† ille."), ["†quid. ait† ille."]);
+        assert_eq!(sentences("et ... uenit. Sed … Tum"), ["et ... uenit.", "Sed …", "Tum"]);
+        assert_eq!(sentences("nobis om. codd. recc. dett."), ["nobis om. codd. recc. dett."]);
+        // An unclosed bracket encloses nothing
+        assert_eq!(sentences("(uenit. Tum"), ["(uenit.", "Tum"]);
+    }
+
+    #[test]
+    fn test_quotes_and_paragraphs() {
+        assert_eq!(sentences("«Veni.» Tum ille “quid?” dixit"), ["«Veni.»", "Tum ille “quid?”", "dixit"]);
+        assert_eq!(sentences("arma uirumque cano\n\nTroiae qui primus"), ["arma uirumque cano", "Troiae qui primus"]);
+        assert_eq!(sentences("arma uirumque\ncano"), ["arma uirumque\ncano"]);
+    }
+
+    #[test]
+    fn test_extra_abbreviations() {
+        let segmenter = Segmenter::new().abbreviations(["Sall"]);
+        assert_eq!(segmenter.split("ut Sall. ait").len(), 1);
+        assert_eq!(split("ut Sall. ait").len(), 2);
+    }
+}
@@ -0,0 +1,358 @@
+//! Orthographic style profiles: one call that brings a text into a coherent
+//! house style.
+//!
+//! A [`Profile`] names a bundle of decisions ([`StyleSettings`]) across the
+//! other modules: u/v, i/j, the diphthongs, Greek loan spellings, prefix
+//! assimilation, and which proper names are capitalized. [`standardize`]
+//! applies them in a fixed order (ligatures and diphthongs, loan spellings,
+//! u/v, i/j, assimilation, names) so that each step sees the letters the
+//! previous one settled.
+
+use crate::assimilation;
+use crate::diphthongs;
+use crate::ligatures;
+use crate::loanwords::LoanNormalizer;
+use crate::numerals;
+use crate::uv;
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+
+/// How to write *u* and *v*.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UvMode {
+    /// Leave as written.
+    Keep,
+    /// Distinguish vowel *u* from consonant *v* with the u/v rules.
+    Distinguish,
+    /// *u* for both, in either case (*VIRTVS* → *UIRTUS*).
+    UOnly,
+    /// Teubner convention: *u* in lowercase, *V* as the capital (*Vt
+    /// uirtus*).
+    Teubner,
+}
+
+/// How to write *i* and *j*.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IjMode {
+    /// Leave as written.
+    Keep,
+    /// *i* for both.
+    IOnly,
+    /// *j* for consonantal *i*: word-initially before a vowel (*iam* →
+    /// *jam*) and between vowels (*eius* → *ejus*).
+    Distinguish,
+}
+
+/// How to write the diphthongs *ae* and *oe*.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiphthongMode {
+    /// Leave as written.
+    Keep,
+    /// Two letters, expanding `æ` and `œ`.
+    Expand,
+    /// The ligatures `æ` and `œ`.
+    Ligatures,
+    /// Plain *e* (*caelum* → *celum*).
+    Flatten,
+}
+
+/// The decisions a [`Profile`] bundles. Build one directly for a house
+/// style that no profile covers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleSettings {
+    pub uv: UvMode,
+    pub ij: IjMode,
+    pub diphthongs: DiphthongMode,
+    /// Rewrite Greek loan spellings (*sylva* → *silva*), or leave them.
+    pub loanwords: Option<LoanNormalizer>,
+    /// Rewrite assimilable prefixes to this style, or leave them.
+    pub assimilation: Option<assimilation::Style>,
+    /// Words, in lowercase, to write with a capital initial.
+    pub proper_names: &'static [&'static str],
+}
+
+/// Classical names capitalized by the [`Profile::Old`] profile.
+pub const CLASSICAL_NAMES: &[&str] = &[
+    "roma", "romae", "romam", "romanus", "romani", "romanorum", "romanis", "romanos", "italia",
+    "italiae", "italiam", "graecia", "graeciae", "graeci", "graecorum", "gallia", "galliae", "galli",
+    "gallorum", "caesar", "caesaris", "caesari", "caesarem", "iuppiter", "iovis", "iovi", "iovem",
+    "venus", "veneris", "mars", "martis", "minerva", "minervae", "apollo", "apollinis",
+];
+
+/// Sacred names capitalized by the [`Profile::Ecclesiastical`] profile.
+pub const SACRED_NAMES: &[&str] = &[
+    "deus", "dei", "deo", "deum", "dominus", "domini", "domino", "dominum", "domine", "christus",
+    "christi", "christo", "christum", "christe", "iesus", "iesu", "iesum", "jesus", "jesu", "jesum",
+    "maria", "mariae", "mariam",
+];
+
+/// A named orthography profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// The Oxford Latin Dictionary: *u*/*v* distinguished, no *j*,
+    /// diphthongs written out, prefixes assimilated, names capitalized.
+    Old,
+    /// Liturgical and church editions: *u*/*v* and *i*/*j* distinguished,
+    /// ligatures `æ`/`œ`, sacred names capitalized.
+    Ecclesiastical,
+    /// Teubner-style critical texts: *u* only (capital *V*), no *j*,
+    /// diphthongs written out, everything else as printed.
+    TeubnerUOnly,
+}
+
+impl Profile {
+    pub const ALL: [Profile; 3] = [Profile::Old, Profile::Ecclesiastical, Profile::TeubnerUOnly];
+
+    /// The profile's name: `"old"`, `"ecclesiastical"`, or
+    /// `"teubner-u-only"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Profile::Old => "old",
+            Profile::Ecclesiastical => "ecclesiastical",
+            Profile::TeubnerUOnly => "teubner-u-only",
+        }
+    }
+
+    /// Look a profile up by [`Profile::name`], ignoring case.
+    pub fn from_name(name: &str) -> Option<Profile> {
+        Profile::ALL.into_iter().find(|profile| profile.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn settings(self) -> StyleSettings {
+        match self {
+            Profile::Old => StyleSettings {
+                uv: UvMode::Distinguish,
+                ij: IjMode::IOnly,
+                diphthongs: DiphthongMode::Expand,
+                loanwords: None,
+                assimilation: Some(assimilation::Style::Assimilated),
+                proper_names: CLASSICAL_NAMES,
+            },
+            Profile::Ecclesiastical => StyleSettings {
+                uv: UvMode::Distinguish,
+                ij: IjMode::Distinguish,
+                diphthongs: DiphthongMode::Ligatures,
+                loanwords: None,
+                assimilation: None,
+                proper_names: SACRED_NAMES,
+            },
+            Profile::TeubnerUOnly => StyleSettings {
+                uv: UvMode::Teubner,
+                ij: IjMode::IOnly,
+                diphthongs: DiphthongMode::Expand,
+                loanwords: None,
+                assimilation: None,
+                proper_names: &[],
+            },
+        }
+    }
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u' | 'y')
+}
+
+/// Replace `v` with `u`, capitals too unless `keep_capital_v`. Roman
+/// numerals keep their *V*.
+fn u_only(text: &str, keep_capital_v: bool) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    (0..chars.len())
+        .map(|i| match chars[i] {
+            'v' | 'V' if numerals::in_numeral(&chars, i) => chars[i],
+            'v' => 'u',
+            'V' if !keep_capital_v => 'U',
+            'U' if keep_capital_v => 'V',
+            c => c,
+        })
+        .collect()
+}
+
+/// Replace `j` with `i`, except in Roman numerals (*viij*).
+fn i_only(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    (0..chars.len())
+        .map(|i| match chars[i] {
+            'j' | 'J' if numerals::in_numeral(&chars, i) => chars[i],
+            'j' => 'i',
+            'J' => 'I',
+            c => c,
+        })
+        .collect()
+}
+
+/// Write consonantal *i* as *j*: at the start of a word before a vowel
+/// other than *i* (*iam*, *Iesus*), and between vowels (*eius*, *maior*),
+/// but not after the *u* of *qu*/*gu* (*quia*), nor in Roman numerals.
+pub fn restore_j(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        if !matches!(c, 'i' | 'I') || numerals::in_numeral(&chars, i) {
+            result.push(c);
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1).copied();
+        let before_vowel = next.is_some_and(|n| is_vowel(n) && !matches!(n, 'i' | 'I'));
+        let initial = prev.is_none_or(|p| !p.is_alphabetic());
+        let after_vowel = prev.is_some_and(|p| {
+            is_vowel(p)
+                && !(matches!(p, 'u' | 'U')
+                    && i >= 2
+                    && matches!(chars[i - 2].to_ascii_lowercase(), 'q' | 'g'))
+        });
+        if before_vowel && (initial || after_vowel) {
+            result.push(if c == 'I' { 'J' } else { 'j' });
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Capitalize the words in `names`, matched in lowercase.
+fn capitalize_names(text: &str, names: &[&str]) -> String {
+    if names.is_empty() {
+        return text.to_string();
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_alphabetic() {
+            result.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let end = chars[i..].iter().position(|c| !c.is_alphabetic()).map_or(chars.len(), |p| i + p);
+        let word: String = chars[i..end].iter().collect();
+        if chars[i].is_lowercase() && names.contains(&word.to_lowercase().as_str()) {
+            result.extend(chars[i].to_uppercase());
+            result.extend(&chars[i + 1..end]);
+        } else {
+            result.push_str(&word);
+        }
+        i = end;
+    }
+    result
+}
+
+/// Apply `settings` to `text`.
+pub fn standardize_with(text: &str, settings: &StyleSettings) -> String {
+    let mut text = match settings.diphthongs {
+        DiphthongMode::Keep => text.to_string(),
+        DiphthongMode::Expand => ligatures::expand(text),
+        DiphthongMode::Ligatures => ligatures::contract(&ligatures::expand(text)),
+        DiphthongMode::Flatten => diphthongs::flatten(text),
+    };
+    if let Some(normalizer) = &settings.loanwords {
+        text = normalizer.normalize(&text);
+    }
+    text = match settings.uv {
+        UvMode::Keep => text,
+        UvMode::Distinguish => uv::normalize(&text).into_owned(),
+        UvMode::UOnly => u_only(&text, false),
+        UvMode::Teubner => u_only(&text, true),
+    };
+    text = match settings.ij {
+        IjMode::Keep => text,
+        IjMode::IOnly => i_only(&text),
+        IjMode::Distinguish => restore_j(&i_only(&text)),
+    };
+    if let Some(style) = settings.assimilation {
+        text = assimilation::normalize(&text, style);
+    }
+    capitalize_names(&text, settings.proper_names)
+}
+
+/// Bring `text` into the house style of `profile`.
+///
+/// ```
+/// use latincy_preprocess::styles::{standardize, Profile};
+///
+/// assert_eq!(
+///     standardize("gloria in excelsis deo et in terra pax hominibus bonae uoluntatis", Profile::Ecclesiastical),
+///     "gloria in excelsis Deo et in terra pax hominibus bonæ voluntatis"
+/// );
+/// ```
+pub fn standardize(text: &str, profile: Profile) -> String {
+    standardize_with(text, &profile.settings())
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "standardize")]
+pub fn py_standardize(py: Python<'_>, text: &str, profile: &str) -> PyResult<String> {
+    let profile = Profile::from_name(profile).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "unknown style profile {:?}; available: {}",
+            profile,
+            Profile::ALL.map(Profile::name).join(", ")
+        ))
+    })?;
+    Ok(crate::allow_threads(py, text, || standardize(text, profile)))
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "style_profiles")]
+pub fn py_style_profiles() -> Vec<&'static str> {
+    Profile::ALL.map(Profile::name).to_vec()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles() {
+        assert_eq!(standardize("adfert iam uirtus cæli romae", Profile::Old), "affert iam virtus caeli Romae");
+        assert_eq!(standardize("iam eius uirtus caeli", Profile::Ecclesiastical), "jam ejus virtus cæli");
+        assert_eq!(standardize("Vt virtus jam VIRTUS", Profile::TeubnerUOnly), "Vt uirtus iam VIRTVS");
+    }
+
+    #[test]
+    fn test_numerals_untouched() {
+        assert_eq!(standardize("liber XVI, cap. xv, viij", Profile::Old), "liber XVI, cap. xv, viij");
+        assert_eq!(standardize("Lib. IV uirtus", Profile::Ecclesiastical), "Lib. IV virtus");
+        assert_eq!(u_only("VI xvi vivit", false), "VI xvi uiuit");
+    }
+
+    #[test]
+    fn test_restore_j() {
+        assert_eq!(restore_j("iam Iesus eius maior cuius"), "jam Jesus ejus major cujus");
+        // Vowel i, and the u of qu, are not consonants
+        assert_eq!(restore_j("quia iit filii pius"), "quia iit filii pius");
+    }
+
+    #[test]
+    fn test_standardize_with() {
+        let settings = StyleSettings {
+            uv: UvMode::UOnly,
+            ij: IjMode::Keep,
+            diphthongs: DiphthongMode::Flatten,
+            loanwords: Some(LoanNormalizer::new()),
+            assimilation: Some(assimilation::Style::Unassimilated),
+            proper_names: &["roma"],
+        };
+        assert_eq!(
+            standardize_with("VIRTUS caelum affert roma Jam sylva", &settings),
+            "UIRTUS celum adfert Roma Jam silua"
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(Profile::from_name("Ecclesiastical"), Some(Profile::Ecclesiastical));
+        assert_eq!(Profile::from_name("teubner-u-only"), Some(Profile::TeubnerUOnly));
+        assert_eq!(Profile::from_name("modern"), None);
+    }
+}
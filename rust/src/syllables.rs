@@ -0,0 +1,313 @@
+//! Syllabification of Latin words.
+//!
+//! [`Syllabifier`] splits a word between its vowel nuclei by onset
+//! maximization: of the consonants between two nuclei, the last begins the
+//! next syllable (*ar-ma*, *mag-nus*), and with the *muta cum liquida* option
+//! a stop before *l* or *r* goes with it (*pa-tris*). *qu*, *gu* after *n*
+//! (*lin-gua*), and the digraphs *ph*, *th*, *ch* count as one consonant;
+//! *ae*, *oe*, *au*, and final *eu* are diphthongs, except in known hiatus
+//! words (*po-e-ta*, *a-er*). Consonantal *u* is told from vocalic *u* by
+//! the u/v rules, so *nouus* and *seruus* split like *novus* and *servus*;
+//! initial and intervocalic *i* before a vowel is consonantal (*iam*,
+//! *ma-ior*). Macrons and other marks stay on their vowel.
+
+use crate::{ligatures, unicode, uv};
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use std::ops::Range;
+
+/// Splits Latin words into syllables.
+///
+/// ```
+/// use latincy_preprocess::syllables::Syllabifier;
+///
+/// let syllabifier = Syllabifier::new();
+/// assert_eq!(syllabifier.syllables("patria"), ["pa", "tri", "a"]);
+/// let syllabifier = syllabifier.muta_cum_liquida(false);
+/// assert_eq!(syllabifier.syllables("patria"), ["pat", "ri", "a"]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Syllabifier {
+    muta_cum_liquida: bool,
+}
+
+impl Default for Syllabifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Syllabifier {
+    /// Keep *muta cum liquida* together.
+    pub fn new() -> Self {
+        Syllabifier { muta_cum_liquida: true }
+    }
+
+    /// Start a syllable with a stop (or *f*) before *l* or *r*, as in prose
+    /// (*pa-tris*, the default), or split the pair, as the heavy syllable
+    /// of verse can (*pat-ris*).
+    pub fn muta_cum_liquida(mut self, together: bool) -> Self {
+        self.muta_cum_liquida = together;
+        self
+    }
+
+    /// The char offsets in `word` at which its second and later syllables
+    /// begin; empty for a word of one syllable or none.
+    pub fn boundaries(&self, word: &str) -> Vec<usize> {
+        let chars: Vec<char> = word.chars().collect();
+        self.char_boundaries(&chars)
+    }
+
+    /// The syllables of `word`.
+    pub fn syllables(&self, word: &str) -> Vec<String> {
+        let chars: Vec<char> = word.chars().collect();
+        let mut syllables = Vec::new();
+        let mut start = 0;
+        for end in self.char_boundaries(&chars).into_iter().chain([chars.len()]) {
+            syllables.push(chars[start..end].iter().collect());
+            start = end;
+        }
+        syllables
+    }
+
+    /// The syllables of each word of `text`, as char ranges of `text`.
+    pub fn syllabify_text(&self, text: &str) -> Vec<Vec<Range<usize>>> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut words = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            if !chars[i].is_alphabetic() {
+                i += 1;
+                continue;
+            }
+            let end = chars[i..].iter().position(|&c| !is_word_char(c)).map_or(chars.len(), |p| i + p);
+            let mut start = i;
+            let mut syllables = Vec::new();
+            for boundary in self.char_boundaries(&chars[i..end]).into_iter().chain([end - i]) {
+                syllables.push(start..i + boundary);
+                start = i + boundary;
+            }
+            words.push(syllables);
+            i = end;
+        }
+        words
+    }
+
+    fn char_boundaries(&self, word: &[char]) -> Vec<usize> {
+        // Letters, with the combining marks that follow them left out
+        let letters: Vec<usize> = (0..word.len()).filter(|&i| unicode::combining_class(word[i]) == 0).collect();
+        let base: Vec<char> = letters.iter().map(|&i| base_letter(word[i])).collect();
+        let vowel = vowels(&base);
+
+        // Nuclei as letter ranges
+        let mut nuclei: Vec<Range<usize>> = Vec::new();
+        let mut k = 0;
+        while k < base.len() {
+            if !vowel[k] {
+                k += 1;
+                continue;
+            }
+            let len = if vowel.get(k + 1) == Some(&true) && is_diphthong(&base, k) { 2 } else { 1 };
+            nuclei.push(k..k + len);
+            k += len;
+        }
+
+        nuclei
+            .windows(2)
+            .map(|pair| letters[self.onset(&base, pair[0].end, pair[1].start)])
+            .collect()
+    }
+
+    /// The letter at which the syllable starting before the nucleus at
+    /// `next` begins, the consonants `from..next` lying between nuclei.
+    fn onset(&self, base: &[char], from: usize, next: usize) -> usize {
+        // Consonant units: single letters, or ph/th/ch, qu, gu
+        let mut units: Vec<usize> = Vec::new();
+        let mut k = from;
+        while k < next {
+            units.push(k);
+            let pair = base.get(k + 1).map(|&second| (base[k], second));
+            let digraph = matches!(pair, Some(('p' | 't' | 'c', 'h') | ('q' | 'g', 'u' | 'v')));
+            k += if digraph && k + 1 < next { 2 } else { 1 };
+        }
+        let Some(&last) = units.last() else {
+            return next;
+        };
+        if self.muta_cum_liquida && units.len() > 1 && matches!(base[last], 'l' | 'r') {
+            let stop = units[units.len() - 2];
+            let is_stop = matches!(base[stop], 'b' | 'c' | 'd' | 'g' | 'p' | 't' | 'f');
+            if is_stop && !matches!((base[stop], base[last]), ('d' | 't', 'l')) {
+                return stop;
+            }
+        }
+        last
+    }
+}
+
+/// Whether `c` belongs to a word: a letter or a combining mark.
+fn is_word_char(c: char) -> bool {
+    c.is_alphabetic() || unicode::combining_class(c) != 0
+}
+
+/// The lowercase letter under any accents or macrons.
+fn base_letter(c: char) -> char {
+    let base = unicode::decompose(&[c], false)[0];
+    base.to_lowercase().next().unwrap_or(base)
+}
+
+/// Which of the base letters are vowels.
+fn vowels(base: &[char]) -> Vec<bool> {
+    let mut vowel: Vec<bool> =
+        base.iter().map(|&c| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y' | 'æ' | 'œ')).collect();
+    for k in 0..base.len() {
+        if matches!(base[k], 'u' | 'v') {
+            let after_q = k > 0 && base[k - 1] == 'q';
+            let after_ng = k > 1 && base[k - 1] == 'g' && base[k - 2] == 'n' && vowel.get(k + 1) == Some(&true);
+            vowel[k] = !(after_q || after_ng || uv::is_consonantal(base, k));
+        }
+    }
+    for k in 0..base.len() {
+        if base[k] == 'i' && vowel.get(k + 1) == Some(&true) && base[k + 1] != 'i' {
+            let initial = k == 0;
+            let intervocalic = k > 0 && vowel[k - 1] && base[k - 1] != 'i';
+            vowel[k] = !(initial || intervocalic);
+        }
+    }
+    vowel
+}
+
+/// Whether the vowels at `k` and `k + 1` form a diphthong.
+fn is_diphthong(base: &[char], k: usize) -> bool {
+    match (base[k], base[k + 1]) {
+        ('a' | 'o', 'e') => !ligatures::is_hiatus(base, k),
+        ('a', 'u') => true,
+        // heu, seu, neu; elsewhere e-u is a hiatus (de-us, me-us)
+        ('e', 'u') => k + 2 == base.len(),
+        _ => false,
+    }
+}
+
+/// The syllables of `word`, keeping *muta cum liquida* together.
+///
+/// ```
+/// use latincy_preprocess::syllables::syllabify;
+///
+/// assert_eq!(syllabify("Rōmānōrum"), ["Rō", "mā", "nō", "rum"]);
+/// ```
+pub fn syllabify(word: &str) -> Vec<String> {
+    Syllabifier::new().syllables(word)
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "syllabify", signature = (word, muta_cum_liquida=true))]
+pub fn py_syllabify(word: &str, muta_cum_liquida: bool) -> Vec<String> {
+    Syllabifier::new().muta_cum_liquida(muta_cum_liquida).syllables(word)
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "syllable_boundaries", signature = (word, muta_cum_liquida=true))]
+pub fn py_syllable_boundaries(word: &str, muta_cum_liquida: bool) -> Vec<usize> {
+    Syllabifier::new().muta_cum_liquida(muta_cum_liquida).boundaries(word)
+}
+
+/// Each word of `text` as a list of `(start, end)` char ranges, one per
+/// syllable.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "syllabify_text", signature = (text, muta_cum_liquida=true))]
+pub fn py_syllabify_text(py: Python<'_>, text: &str, muta_cum_liquida: bool) -> Vec<Vec<(usize, usize)>> {
+    crate::allow_threads(py, text, || {
+        Syllabifier::new()
+            .muta_cum_liquida(muta_cum_liquida)
+            .syllabify_text(text)
+            .into_iter()
+            .map(|word| word.into_iter().map(|syllable| (syllable.start, syllable.end)).collect())
+            .collect()
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(word: &str) -> String {
+        syllabify(word).join("-")
+    }
+
+    #[test]
+    fn test_onsets() {
+        assert_eq!(split("arma"), "ar-ma");
+        assert_eq!(split("virumque"), "vi-rum-que");
+        assert_eq!(split("castra"), "cas-tra");
+        assert_eq!(split("magnus"), "mag-nus");
+        assert_eq!(split("patris"), "pa-tris");
+        assert_eq!(split("nox"), "nox");
+        assert_eq!(split("saxum"), "sa-xum");
+    }
+
+    #[test]
+    fn test_muta_cum_liquida() {
+        let split = Syllabifier::new().muta_cum_liquida(false);
+        assert_eq!(split.syllables("patris"), ["pat", "ris"]);
+        assert_eq!(split.syllables("castra"), ["cast", "ra"]);
+        // d and t never begin a syllable before l
+        assert_eq!(syllabify("Atlas"), ["At", "las"]);
+    }
+
+    #[test]
+    fn test_digraphs() {
+        assert_eq!(split("aqua"), "a-qua");
+        assert_eq!(split("lingua"), "lin-gua");
+        assert_eq!(split("philosophia"), "phi-lo-so-phi-a");
+        assert_eq!(split("pulchra"), "pul-chra");
+    }
+
+    #[test]
+    fn test_vowels() {
+        assert_eq!(split("caelum"), "cae-lum");
+        assert_eq!(split("poena"), "poe-na");
+        assert_eq!(split("laudo"), "lau-do");
+        assert_eq!(split("poeta"), "po-e-ta");
+        assert_eq!(split("aer"), "a-er");
+        assert_eq!(split("deus"), "de-us");
+        assert_eq!(split("heu"), "heu");
+        assert_eq!(split("cæli"), "cæ-li");
+    }
+
+    #[test]
+    fn test_consonantal_i_and_u() {
+        assert_eq!(split("iam"), "iam");
+        assert_eq!(split("maior"), "ma-ior");
+        assert_eq!(split("Troia"), "Tro-ia");
+        assert_eq!(split("nouus"), "no-uus");
+        assert_eq!(split("seruus"), "ser-uus");
+        assert_eq!(split("tuus"), "tu-us");
+        assert_eq!(split("uir"), "uir");
+    }
+
+    #[test]
+    fn test_marks_and_case() {
+        assert_eq!(split("Rōmānus"), "Rō-mā-nus");
+        assert_eq!(split("ro\u{0304}sa"), "ro\u{0304}-sa");
+        assert_eq!(split("ARMA"), "AR-MA");
+        assert_eq!(Syllabifier::new().boundaries("ro\u{0304}sa"), [3]);
+        assert!(Syllabifier::new().boundaries("st").is_empty());
+    }
+
+    #[test]
+    fn test_syllabify_text() {
+        let words = Syllabifier::new().syllabify_text("arma, virumque");
+        assert_eq!(words, vec![vec![0..2, 2..4], vec![6..8, 8..11, 11..14]]);
+        assert!(Syllabifier::new().syllabify_text(" 12 ").is_empty());
+    }
+}
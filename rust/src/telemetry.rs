@@ -0,0 +1,223 @@
+//! `tracing` instrumentation, behind the `tracing` feature.
+//!
+//! A service embedding the crate can watch what its pipelines do through
+//! any `tracing` subscriber. Each [`Pipeline`](crate::pipeline::Pipeline)
+//! run is a `pipeline` span (fields `stages` and `bytes`), and each stage a
+//! `stage` span inside it (`stage`, `index`, `bytes`). As a stage finishes
+//! it emits a DEBUG event with target [`STATS`] and its counters, named in
+//! the `monotonic_counter.` convention that metrics layers such as
+//! `tracing-opentelemetry` turn into counters:
+//!
+//! - `monotonic_counter.changes`: the change records the stage made;
+//! - `monotonic_counter.words_changed`: the words those changes fall in;
+//!
+//! then a TRACE event per rule that fired, with the `rule` and its count as
+//! `monotonic_counter.rule_fired`. Each lookup in a
+//! [`WordCache`](crate::cache::WordCache) emits a TRACE event (field `cache = "word"`) counting
+//! `monotonic_counter.cache_hits` or `monotonic_counter.cache_misses`.
+//!
+//! Without the feature every function here just runs its closure.
+
+use crate::pipeline::NormalizeOutput;
+
+/// The target of the counter events.
+#[cfg(feature = "tracing")]
+pub const STATS: &str = "latincy_preprocess::stats";
+
+/// `run()`, a pipeline of `stages` stages on a text of `bytes` bytes, in a
+/// `pipeline` span.
+#[cfg(feature = "tracing")]
+pub(crate) fn pipeline<T>(stages: usize, bytes: usize, run: impl FnOnce() -> T) -> T {
+    tracing::info_span!("pipeline", stages, bytes).in_scope(run)
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline]
+pub(crate) fn pipeline<T>(_stages: usize, _bytes: usize, run: impl FnOnce() -> T) -> T {
+    run()
+}
+
+/// `run()`, stage `index` of a pipeline normalizing `text`, in a `stage`
+/// span, then its counters.
+#[cfg(feature = "tracing")]
+pub(crate) fn stage(name: &str, index: usize, text: &str, run: impl FnOnce() -> NormalizeOutput) -> NormalizeOutput {
+    use tracing::Level;
+    let span = tracing::info_span!("stage", stage = name, index, bytes = text.len());
+    span.in_scope(|| {
+        let output = run();
+        if tracing::enabled!(target: STATS, Level::DEBUG) {
+            tracing::debug!(
+                target: STATS,
+                stage = name,
+                monotonic_counter.changes = output.changes.len() as u64,
+                monotonic_counter.words_changed = words_changed(text, &output) as u64,
+            );
+        }
+        if tracing::enabled!(target: STATS, Level::TRACE) {
+            let mut rules: Vec<(&str, u64)> = Vec::new();
+            for change in &output.changes {
+                match rules.iter_mut().find(|(rule, _)| *rule == change.rule) {
+                    Some((_, count)) => *count += 1,
+                    None => rules.push((&change.rule, 1)),
+                }
+            }
+            for (rule, count) in rules {
+                tracing::trace!(target: STATS, stage = name, rule, monotonic_counter.rule_fired = count);
+            }
+        }
+        output
+    })
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline]
+pub(crate) fn stage(_name: &str, _index: usize, _text: &str, run: impl FnOnce() -> NormalizeOutput) -> NormalizeOutput {
+    run()
+}
+
+/// How many words of `text` hold at least one of the changes of `output`.
+#[cfg(feature = "tracing")]
+fn words_changed(text: &str, output: &NormalizeOutput) -> usize {
+    let mut positions: Vec<usize> = output.changes.iter().map(|change| change.position).collect();
+    positions.sort_unstable();
+    let mut positions = positions.into_iter().peekable();
+    let (mut words, mut word, mut counted) = (0, 0, None);
+    let mut in_word = false;
+    for (i, c) in text.chars().enumerate() {
+        let letter = c.is_alphabetic() || crate::graphemes::is_extender(c);
+        if letter && !in_word {
+            word += 1;
+        }
+        in_word = letter;
+        while positions.next_if(|&position| position <= i).is_some() {
+            // A change between words, such as a removed space, counts on
+            // its own
+            let key = if in_word { word } else { usize::MAX - i };
+            if counted != Some(key) {
+                counted = Some(key);
+                words += 1;
+            }
+        }
+    }
+    words + positions.count().min(1)
+}
+
+/// Count a [`WordCache`](crate::cache::WordCache) lookup.
+#[cfg(feature = "tracing")]
+pub(crate) fn cache_lookup(hit: bool) {
+    if hit {
+        tracing::trace!(target: STATS, cache = "word", monotonic_counter.cache_hits = 1u64);
+    } else {
+        tracing::trace!(target: STATS, cache = "word", monotonic_counter.cache_misses = 1u64);
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline]
+pub(crate) fn cache_lookup(_hit: bool) {}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::cache::WordCache;
+    use crate::long_s::LongSNormalizer;
+    use crate::pipeline::{Normalizer, Pipeline};
+    use crate::uv::UvNormalizer;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Span names and event fields, as `name=value` strings.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Recorded>);
+
+    #[derive(Default)]
+    struct Recorded {
+        spans: Mutex<Vec<String>>,
+        events: Mutex<Vec<Vec<String>>>,
+        next: AtomicU64,
+    }
+
+    struct Fields(Vec<String>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.push(format!("{}={:?}", field.name(), value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.push(format!("{}={}", field.name(), value));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = Fields(vec![span.metadata().name().to_string()]);
+            span.record(&mut fields);
+            self.0.spans.lock().unwrap().push(fields.0.join(" "));
+            Id::from_u64(self.0.next.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields(Vec::new());
+            event.record(&mut fields);
+            self.0.events.lock().unwrap().push(fields.0);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn test_stage_spans_and_counters() {
+        let recorder = Recorder::default();
+        let pipeline = Pipeline::new().stage(LongSNormalizer::new()).stage(UvNormalizer::new());
+        let cache = Arc::new(WordCache::new(100));
+        let cached = LongSNormalizer::builder().word_cache(Arc::clone(&cache)).build();
+        let normalized = tracing::subscriber::with_default(recorder.clone(), || {
+            assert_eq!(cached.normalize_text("funt funt"), "sunt sunt");
+            pipeline.normalize_text("ſeruus funt funt uidit")
+        });
+        assert_eq!(normalized, "servus sunt sunt vidit");
+
+        let spans = recorder.0.spans.lock().unwrap();
+        assert_eq!(
+            *spans,
+            [
+                "pipeline stages=2 bytes=23",
+                "stage stage=long_s index=0 bytes=23",
+                "stage stage=uv index=1 bytes=22"
+            ]
+        );
+        let events = recorder.0.events.lock().unwrap();
+        let has = |fields: &[&str]| events.iter().any(|event| fields.iter().all(|f| event.contains(&f.to_string())));
+        assert!(has(&["stage=long_s", "monotonic_counter.changes=3", "monotonic_counter.words_changed=3"]));
+        assert!(has(&["stage=uv", "monotonic_counter.changes=2", "monotonic_counter.words_changed=2"]));
+        assert!(has(&["stage=uv", "rule=initial_before_vowel", "monotonic_counter.rule_fired=1"]));
+        let count = |field: &str| events.iter().filter(|event| event.contains(&field.to_string())).count();
+        assert_eq!(count("monotonic_counter.cache_hits=1"), cache.hits() as usize);
+        assert_eq!(count("monotonic_counter.cache_misses=1"), cache.misses() as usize);
+        assert_eq!(cache.hits(), 1);
+    }
+
+    #[test]
+    fn test_words_changed() {
+        let output = UvNormalizer::new().normalize("uiuus et uia");
+        assert_eq!(output.changes.len(), 3);
+        assert_eq!(super::words_changed("uiuus et uia", &output), 2);
+    }
+}
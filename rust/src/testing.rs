@@ -0,0 +1,38 @@
+//! Proptest generators shared by the property tests of the normalizers.
+//!
+//! Arbitrary strings rarely spell a Latin word, so besides `any::<String>()`
+//! the properties draw from [`latin_text`]: the letters, marks, and
+//! separators the rules look at, mixed with characters whose case mapping
+//! or width is unusual, in runs long enough to form words.
+
+use proptest::prelude::*;
+
+/// Characters the rules treat specially, or that stress case and offset
+/// handling: long s and ligatures, combining marks, letters whose case
+/// mapping changes their length, and line-break hyphens.
+const SPECIAL: &[char] = &[
+    'ſ', 'ﬁ', 'ﬂ', 'ﬀ', 'ﬃ', 'ﬄ', 'ﬅ', 'ﬆ', 'æ', 'Æ', 'œ', 'Œ', 'ß', 'ẞ', 'İ', 'ı', 'ǅ', 'ǈ', 'ŉ', 'ΐ',
+    'ā', 'ē', 'ū', 'Ū', 'ↄ', 'Ↄ', 'ⅰ', 'Ⅴ', '\u{0301}', '\u{0304}', '\u{0305}', '\u{200D}',
+    '-', '¬', '\u{00AD}', '\n', '\t', ' ', '.', ',', ';', '\'', '«', '»', '0', '7',
+];
+
+/// One character of Latin-looking text: mostly letters, with every
+/// `SPECIAL` character and the odd arbitrary one.
+pub(crate) fn latin_char() -> impl Strategy<Value = char> {
+    prop_oneof![
+        8 => prop::sample::select(b"aeiouvfsctnmlrqgx".map(char::from).to_vec()),
+        3 => prop::sample::select(b"AEIOUVFSCTNMLRQGX".map(char::from).to_vec()),
+        3 => prop::sample::select(SPECIAL),
+        1 => any::<char>(),
+    ]
+}
+
+/// Up to 80 [`latin_char`]s, words and separators alike.
+pub(crate) fn latin_text() -> impl Strategy<Value = String> {
+    prop::collection::vec(latin_char(), 0..80).prop_map(|chars| chars.into_iter().collect())
+}
+
+/// Either generator, so a property sees both shapes of input.
+pub(crate) fn text() -> impl Strategy<Value = String> {
+    prop_oneof![3 => latin_text(), 1 => any::<String>()]
+}
@@ -0,0 +1,556 @@
+//! Unicode normalization pre-pass: NFC or NFKC, and removal of invisible
+//! characters (zero-width spaces and joiners, soft hyphens, byte order
+//! marks).
+//!
+//! Digitized texts mix precomposed and combining forms (`é` and `e` +
+//! U+0301), compatibility characters (`ﬁ`, no-break spaces, fullwidth
+//! letters from CJK OCR engines), and invisible characters left by copying
+//! from PDFs; run this before the other normalizers so they see one
+//! spelling of each letter. Changes are reported as [`ChangeRecord`]s like
+//! the rest of the crate; a record's `original` and `normalized` may differ
+//! in length (`ﬁ` → `fi`, a removed soft hyphen), so alignment code can map
+//! offsets across the pass.
+//!
+//! The tables cover the Latin script (Latin-1 Supplement through IPA
+//! Extensions, and Latin Extended Additional), the punctuation, super- and
+//! subscript, currency, letterlike, and number-form blocks, the Latin
+//! ligatures, and the fullwidth forms, generated from the Unicode 14.0
+//! character database. Characters of other scripts pass through unchanged.
+
+use crate::changes::{self, ChangeRecord};
+use crate::pipeline::{NormalizeOutput, Normalizer};
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+
+/// A Unicode normalization form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Form {
+    /// Canonical composition: combining sequences become precomposed
+    /// letters (`e` + U+0301 → `é`); the default.
+    #[default]
+    Nfc,
+    /// Compatibility composition: NFC, and compatibility characters are
+    /// replaced by their plain equivalents (`ﬁ` → `fi`, `ſ` → `s`, `Ⅻ` →
+    /// `XII`, a no-break space → a space).
+    Nfkc,
+}
+
+impl Form {
+    /// Parse `"nfc"` or `"nfkc"`, ignoring case.
+    pub fn from_name(name: &str) -> Option<Form> {
+        if name.eq_ignore_ascii_case("nfc") {
+            Some(Form::Nfc)
+        } else if name.eq_ignore_ascii_case("nfkc") {
+            Some(Form::Nfkc)
+        } else {
+            None
+        }
+    }
+}
+
+/// Characters removed in either form, with the names used in change
+/// records.
+pub const INVISIBLES: &[(char, &str)] = &[
+    ('\u{200B}', "zero-width space"),
+    ('\u{200C}', "zero-width non-joiner"),
+    ('\u{200D}', "zero-width joiner"),
+    ('\u{2060}', "word joiner"),
+    ('\u{FEFF}', "byte order mark"),
+    ('\u{00AD}', "soft hyphen"),
+];
+
+pub(crate) fn combining_class(c: char) -> u8 {
+    if c < '\u{0300}' {
+        return 0;
+    }
+    COMBINING_CLASSES.iter().find(|&&(mark, _)| mark == c).map_or(0, |&(_, class)| class)
+}
+
+/// Append the full decomposition of `c` to `out`.
+fn decompose_into(c: char, compatibility: bool, out: &mut Vec<char>) {
+    if c.is_ascii() {
+        out.push(c);
+        return;
+    }
+    if compatibility {
+        if let Some(&(_, mapped)) = COMPATIBILITY.iter().find(|&&(k, _)| k == c) {
+            // The mappings are themselves canonically composed
+            for m in mapped.chars() {
+                decompose_into(m, false, out);
+            }
+            return;
+        }
+    }
+    if let Some(&(_, mapped)) = SINGLETONS.iter().find(|&&(k, _)| k == c) {
+        decompose_into(mapped, compatibility, out);
+    } else if let Some(&(base, mark, _)) = CANONICAL_PAIRS.iter().find(|&&(_, _, composed)| composed == c) {
+        decompose_into(base, compatibility, out);
+        out.push(mark);
+    } else {
+        out.push(c);
+    }
+}
+
+/// Sort each run of combining marks by combining class, keeping the order
+/// of marks of the same class.
+fn reorder(chars: &mut [char]) {
+    let mut i = 0;
+    while i < chars.len() {
+        if combining_class(chars[i]) == 0 {
+            i += 1;
+            continue;
+        }
+        let end = chars[i..].iter().position(|&c| combining_class(c) == 0).map_or(chars.len(), |p| i + p);
+        chars[i..end].sort_by_key(|&c| combining_class(c));
+        i = end;
+    }
+}
+
+/// Canonical composition of decomposed, reordered `chars`.
+pub(crate) fn compose(chars: &[char]) -> String {
+    let mut result: Vec<char> = Vec::with_capacity(chars.len());
+    let mut starter: Option<usize> = None;
+    let mut last_class = 0;
+    for &c in chars {
+        let class = combining_class(c);
+        if let Some(s) = starter {
+            // A mark composes with the starter unless a mark of the same or
+            // a higher class lies between them
+            if result.len() == s + 1 || last_class < class {
+                let pair = CANONICAL_PAIRS.iter().find(|&&(base, mark, _)| base == result[s] && mark == c);
+                if let Some(&(_, _, composed)) = pair {
+                    result[s] = composed;
+                    continue;
+                }
+            }
+        }
+        if class == 0 {
+            starter = Some(result.len());
+        }
+        last_class = class;
+        result.push(c);
+    }
+    result.into_iter().collect()
+}
+
+/// The full decomposition of `cluster`, canonical or with `compatibility`,
+/// marks in canonical order.
+pub(crate) fn decompose(cluster: &[char], compatibility: bool) -> Vec<char> {
+    let mut decomposed = Vec::with_capacity(cluster.len() + 2);
+    for &c in cluster {
+        decompose_into(c, compatibility, &mut decomposed);
+    }
+    reorder(&mut decomposed);
+    decomposed
+}
+
+fn normalize_cluster(cluster: &[char], compatibility: bool) -> String {
+    compose(&decompose(cluster, compatibility))
+}
+
+/// Normalize `text` to `form`, removing the [`INVISIBLES`].
+///
+/// ```
+/// use latincy_preprocess::unicode::{normalize, Form};
+///
+/// assert_eq!(normalize("Ro\u{0304}ma\u{200B}", Form::Nfc), "Rōma");
+/// assert_eq!(normalize("ﬁnis\u{00A0}eſt", Form::Nfkc), "finis est");
+/// ```
+pub fn normalize(text: &str, form: Form) -> String {
+    if text.is_ascii() {
+        return text.to_string();
+    }
+    normalize_detailed(text, form).normalized
+}
+
+/// Like [`normalize`], recording one [`ChangeRecord`] per removed character
+/// and per changed letter with its combining marks. The rule is `"NFC"`,
+/// `"NFKC"` (for changes NFC alone would not make), or `"remove "` and the
+/// name of the invisible character.
+pub fn normalize_detailed(text: &str, form: Form) -> DetailedResult {
+    let chars: Vec<char> = text.chars().collect();
+    let mut normalized = String::with_capacity(text.len());
+    let mut changes = Vec::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_ascii() && chars.get(i + 1).is_none_or(|n| n.is_ascii()) {
+            normalized.push(c);
+            i += 1;
+            continue;
+        }
+        if let Some(&(_, name)) = INVISIBLES.iter().find(|&&(k, _)| k == c) {
+            changes.push(ChangeRecord {
+                position: i,
+                original: c.to_string(),
+                normalized: String::new(),
+                pass: 1,
+                rule: format!("remove {}", name),
+                context: changes::context(&chars, i, 1),
+            });
+            i += 1;
+            continue;
+        }
+        // A letter (or a stray mark) and the combining marks that follow it
+        let end = chars[i + 1..].iter().position(|&m| combining_class(m) == 0).map_or(chars.len(), |p| i + 1 + p);
+        let cluster = &chars[i..end];
+        let canonical = normalize_cluster(cluster, false);
+        let result = match form {
+            Form::Nfc => canonical.clone(),
+            Form::Nfkc => normalize_cluster(cluster, true),
+        };
+        if result.chars().ne(cluster.iter().copied()) {
+            changes.push(ChangeRecord {
+                position: i,
+                original: cluster.iter().collect(),
+                normalized: result.clone(),
+                pass: 1,
+                rule: if result == canonical { "NFC" } else { "NFKC" }.to_string(),
+                context: changes::context(&chars, i, cluster.len()),
+            });
+        }
+        normalized.push_str(&result);
+        i = end;
+    }
+
+    DetailedResult {
+        original: text.to_string(),
+        normalized,
+        changes,
+    }
+}
+
+pub struct DetailedResult {
+    pub original: String,
+    pub normalized: String,
+    pub changes: Vec<ChangeRecord>,
+}
+
+// ---------------------------------------------------------------------------
+// Unicode data
+// ---------------------------------------------------------------------------
+
+/// Canonical compositions: (base, combining mark, precomposed).
+const CANONICAL_PAIRS: &[(char, char, char)] = &[
+    ('A', '\u{300}', 'À'), ('A', '\u{301}', 'Á'), ('A', '\u{302}', 'Â'), ('A', '\u{303}', 'Ã'),
+    ('A', '\u{308}', 'Ä'), ('A', '\u{30a}', 'Å'), ('C', '\u{327}', 'Ç'), ('E', '\u{300}', 'È'),
+    ('E', '\u{301}', 'É'), ('E', '\u{302}', 'Ê'), ('E', '\u{308}', 'Ë'), ('I', '\u{300}', 'Ì'),
+    ('I', '\u{301}', 'Í'), ('I', '\u{302}', 'Î'), ('I', '\u{308}', 'Ï'), ('N', '\u{303}', 'Ñ'),
+    ('O', '\u{300}', 'Ò'), ('O', '\u{301}', 'Ó'), ('O', '\u{302}', 'Ô'), ('O', '\u{303}', 'Õ'),
+    ('O', '\u{308}', 'Ö'), ('U', '\u{300}', 'Ù'), ('U', '\u{301}', 'Ú'), ('U', '\u{302}', 'Û'),
+    ('U', '\u{308}', 'Ü'), ('Y', '\u{301}', 'Ý'), ('a', '\u{300}', 'à'), ('a', '\u{301}', 'á'),
+    ('a', '\u{302}', 'â'), ('a', '\u{303}', 'ã'), ('a', '\u{308}', 'ä'), ('a', '\u{30a}', 'å'),
+    ('c', '\u{327}', 'ç'), ('e', '\u{300}', 'è'), ('e', '\u{301}', 'é'), ('e', '\u{302}', 'ê'),
+    ('e', '\u{308}', 'ë'), ('i', '\u{300}', 'ì'), ('i', '\u{301}', 'í'), ('i', '\u{302}', 'î'),
+    ('i', '\u{308}', 'ï'), ('n', '\u{303}', 'ñ'), ('o', '\u{300}', 'ò'), ('o', '\u{301}', 'ó'),
+    ('o', '\u{302}', 'ô'), ('o', '\u{303}', 'õ'), ('o', '\u{308}', 'ö'), ('u', '\u{300}', 'ù'),
+    ('u', '\u{301}', 'ú'), ('u', '\u{302}', 'û'), ('u', '\u{308}', 'ü'), ('y', '\u{301}', 'ý'),
+    ('y', '\u{308}', 'ÿ'), ('A', '\u{304}', 'Ā'), ('a', '\u{304}', 'ā'), ('A', '\u{306}', 'Ă'),
+    ('a', '\u{306}', 'ă'), ('A', '\u{328}', 'Ą'), ('a', '\u{328}', 'ą'), ('C', '\u{301}', 'Ć'),
+    ('c', '\u{301}', 'ć'), ('C', '\u{302}', 'Ĉ'), ('c', '\u{302}', 'ĉ'), ('C', '\u{307}', 'Ċ'),
+    ('c', '\u{307}', 'ċ'), ('C', '\u{30c}', 'Č'), ('c', '\u{30c}', 'č'), ('D', '\u{30c}', 'Ď'),
+    ('d', '\u{30c}', 'ď'), ('E', '\u{304}', 'Ē'), ('e', '\u{304}', 'ē'), ('E', '\u{306}', 'Ĕ'),
+    ('e', '\u{306}', 'ĕ'), ('E', '\u{307}', 'Ė'), ('e', '\u{307}', 'ė'), ('E', '\u{328}', 'Ę'),
+    ('e', '\u{328}', 'ę'), ('E', '\u{30c}', 'Ě'), ('e', '\u{30c}', 'ě'), ('G', '\u{302}', 'Ĝ'),
+    ('g', '\u{302}', 'ĝ'), ('G', '\u{306}', 'Ğ'), ('g', '\u{306}', 'ğ'), ('G', '\u{307}', 'Ġ'),
+    ('g', '\u{307}', 'ġ'), ('G', '\u{327}', 'Ģ'), ('g', '\u{327}', 'ģ'), ('H', '\u{302}', 'Ĥ'),
+    ('h', '\u{302}', 'ĥ'), ('I', '\u{303}', 'Ĩ'), ('i', '\u{303}', 'ĩ'), ('I', '\u{304}', 'Ī'),
+    ('i', '\u{304}', 'ī'), ('I', '\u{306}', 'Ĭ'), ('i', '\u{306}', 'ĭ'), ('I', '\u{328}', 'Į'),
+    ('i', '\u{328}', 'į'), ('I', '\u{307}', 'İ'), ('J', '\u{302}', 'Ĵ'), ('j', '\u{302}', 'ĵ'),
+    ('K', '\u{327}', 'Ķ'), ('k', '\u{327}', 'ķ'), ('L', '\u{301}', 'Ĺ'), ('l', '\u{301}', 'ĺ'),
+    ('L', '\u{327}', 'Ļ'), ('l', '\u{327}', 'ļ'), ('L', '\u{30c}', 'Ľ'), ('l', '\u{30c}', 'ľ'),
+    ('N', '\u{301}', 'Ń'), ('n', '\u{301}', 'ń'), ('N', '\u{327}', 'Ņ'), ('n', '\u{327}', 'ņ'),
+    ('N', '\u{30c}', 'Ň'), ('n', '\u{30c}', 'ň'), ('O', '\u{304}', 'Ō'), ('o', '\u{304}', 'ō'),
+    ('O', '\u{306}', 'Ŏ'), ('o', '\u{306}', 'ŏ'), ('O', '\u{30b}', 'Ő'), ('o', '\u{30b}', 'ő'),
+    ('R', '\u{301}', 'Ŕ'), ('r', '\u{301}', 'ŕ'), ('R', '\u{327}', 'Ŗ'), ('r', '\u{327}', 'ŗ'),
+    ('R', '\u{30c}', 'Ř'), ('r', '\u{30c}', 'ř'), ('S', '\u{301}', 'Ś'), ('s', '\u{301}', 'ś'),
+    ('S', '\u{302}', 'Ŝ'), ('s', '\u{302}', 'ŝ'), ('S', '\u{327}', 'Ş'), ('s', '\u{327}', 'ş'),
+    ('S', '\u{30c}', 'Š'), ('s', '\u{30c}', 'š'), ('T', '\u{327}', 'Ţ'), ('t', '\u{327}', 'ţ'),
+    ('T', '\u{30c}', 'Ť'), ('t', '\u{30c}', 'ť'), ('U', '\u{303}', 'Ũ'), ('u', '\u{303}', 'ũ'),
+    ('U', '\u{304}', 'Ū'), ('u', '\u{304}', 'ū'), ('U', '\u{306}', 'Ŭ'), ('u', '\u{306}', 'ŭ'),
+    ('U', '\u{30a}', 'Ů'), ('u', '\u{30a}', 'ů'), ('U', '\u{30b}', 'Ű'), ('u', '\u{30b}', 'ű'),
+    ('U', '\u{328}', 'Ų'), ('u', '\u{328}', 'ų'), ('W', '\u{302}', 'Ŵ'), ('w', '\u{302}', 'ŵ'),
+    ('Y', '\u{302}', 'Ŷ'), ('y', '\u{302}', 'ŷ'), ('Y', '\u{308}', 'Ÿ'), ('Z', '\u{301}', 'Ź'),
+    ('z', '\u{301}', 'ź'), ('Z', '\u{307}', 'Ż'), ('z', '\u{307}', 'ż'), ('Z', '\u{30c}', 'Ž'),
+    ('z', '\u{30c}', 'ž'), ('O', '\u{31b}', 'Ơ'), ('o', '\u{31b}', 'ơ'), ('U', '\u{31b}', 'Ư'),
+    ('u', '\u{31b}', 'ư'), ('A', '\u{30c}', 'Ǎ'), ('a', '\u{30c}', 'ǎ'), ('I', '\u{30c}', 'Ǐ'),
+    ('i', '\u{30c}', 'ǐ'), ('O', '\u{30c}', 'Ǒ'), ('o', '\u{30c}', 'ǒ'), ('U', '\u{30c}', 'Ǔ'),
+    ('u', '\u{30c}', 'ǔ'), ('Ü', '\u{304}', 'Ǖ'), ('ü', '\u{304}', 'ǖ'), ('Ü', '\u{301}', 'Ǘ'),
+    ('ü', '\u{301}', 'ǘ'), ('Ü', '\u{30c}', 'Ǚ'), ('ü', '\u{30c}', 'ǚ'), ('Ü', '\u{300}', 'Ǜ'),
+    ('ü', '\u{300}', 'ǜ'), ('Ä', '\u{304}', 'Ǟ'), ('ä', '\u{304}', 'ǟ'), ('Ȧ', '\u{304}', 'Ǡ'),
+    ('ȧ', '\u{304}', 'ǡ'), ('Æ', '\u{304}', 'Ǣ'), ('æ', '\u{304}', 'ǣ'), ('G', '\u{30c}', 'Ǧ'),
+    ('g', '\u{30c}', 'ǧ'), ('K', '\u{30c}', 'Ǩ'), ('k', '\u{30c}', 'ǩ'), ('O', '\u{328}', 'Ǫ'),
+    ('o', '\u{328}', 'ǫ'), ('Ǫ', '\u{304}', 'Ǭ'), ('ǫ', '\u{304}', 'ǭ'), ('Ʒ', '\u{30c}', 'Ǯ'),
+    ('ʒ', '\u{30c}', 'ǯ'), ('j', '\u{30c}', 'ǰ'), ('G', '\u{301}', 'Ǵ'), ('g', '\u{301}', 'ǵ'),
+    ('N', '\u{300}', 'Ǹ'), ('n', '\u{300}', 'ǹ'), ('Å', '\u{301}', 'Ǻ'), ('å', '\u{301}', 'ǻ'),
+    ('Æ', '\u{301}', 'Ǽ'), ('æ', '\u{301}', 'ǽ'), ('Ø', '\u{301}', 'Ǿ'), ('ø', '\u{301}', 'ǿ'),
+    ('A', '\u{30f}', 'Ȁ'), ('a', '\u{30f}', 'ȁ'), ('A', '\u{311}', 'Ȃ'), ('a', '\u{311}', 'ȃ'),
+    ('E', '\u{30f}', 'Ȅ'), ('e', '\u{30f}', 'ȅ'), ('E', '\u{311}', 'Ȇ'), ('e', '\u{311}', 'ȇ'),
+    ('I', '\u{30f}', 'Ȉ'), ('i', '\u{30f}', 'ȉ'), ('I', '\u{311}', 'Ȋ'), ('i', '\u{311}', 'ȋ'),
+    ('O', '\u{30f}', 'Ȍ'), ('o', '\u{30f}', 'ȍ'), ('O', '\u{311}', 'Ȏ'), ('o', '\u{311}', 'ȏ'),
+    ('R', '\u{30f}', 'Ȑ'), ('r', '\u{30f}', 'ȑ'), ('R', '\u{311}', 'Ȓ'), ('r', '\u{311}', 'ȓ'),
+    ('U', '\u{30f}', 'Ȕ'), ('u', '\u{30f}', 'ȕ'), ('U', '\u{311}', 'Ȗ'), ('u', '\u{311}', 'ȗ'),
+    ('S', '\u{326}', 'Ș'), ('s', '\u{326}', 'ș'), ('T', '\u{326}', 'Ț'), ('t', '\u{326}', 'ț'),
+    ('H', '\u{30c}', 'Ȟ'), ('h', '\u{30c}', 'ȟ'), ('A', '\u{307}', 'Ȧ'), ('a', '\u{307}', 'ȧ'),
+    ('E', '\u{327}', 'Ȩ'), ('e', '\u{327}', 'ȩ'), ('Ö', '\u{304}', 'Ȫ'), ('ö', '\u{304}', 'ȫ'),
+    ('Õ', '\u{304}', 'Ȭ'), ('õ', '\u{304}', 'ȭ'), ('O', '\u{307}', 'Ȯ'), ('o', '\u{307}', 'ȯ'),
+    ('Ȯ', '\u{304}', 'Ȱ'), ('ȯ', '\u{304}', 'ȱ'), ('Y', '\u{304}', 'Ȳ'), ('y', '\u{304}', 'ȳ'),
+    ('¨', '\u{301}', '΅'), ('A', '\u{325}', 'Ḁ'), ('a', '\u{325}', 'ḁ'), ('B', '\u{307}', 'Ḃ'),
+    ('b', '\u{307}', 'ḃ'), ('B', '\u{323}', 'Ḅ'), ('b', '\u{323}', 'ḅ'), ('B', '\u{331}', 'Ḇ'),
+    ('b', '\u{331}', 'ḇ'), ('Ç', '\u{301}', 'Ḉ'), ('ç', '\u{301}', 'ḉ'), ('D', '\u{307}', 'Ḋ'),
+    ('d', '\u{307}', 'ḋ'), ('D', '\u{323}', 'Ḍ'), ('d', '\u{323}', 'ḍ'), ('D', '\u{331}', 'Ḏ'),
+    ('d', '\u{331}', 'ḏ'), ('D', '\u{327}', 'Ḑ'), ('d', '\u{327}', 'ḑ'), ('D', '\u{32d}', 'Ḓ'),
+    ('d', '\u{32d}', 'ḓ'), ('Ē', '\u{300}', 'Ḕ'), ('ē', '\u{300}', 'ḕ'), ('Ē', '\u{301}', 'Ḗ'),
+    ('ē', '\u{301}', 'ḗ'), ('E', '\u{32d}', 'Ḙ'), ('e', '\u{32d}', 'ḙ'), ('E', '\u{330}', 'Ḛ'),
+    ('e', '\u{330}', 'ḛ'), ('Ȩ', '\u{306}', 'Ḝ'), ('ȩ', '\u{306}', 'ḝ'), ('F', '\u{307}', 'Ḟ'),
+    ('f', '\u{307}', 'ḟ'), ('G', '\u{304}', 'Ḡ'), ('g', '\u{304}', 'ḡ'), ('H', '\u{307}', 'Ḣ'),
+    ('h', '\u{307}', 'ḣ'), ('H', '\u{323}', 'Ḥ'), ('h', '\u{323}', 'ḥ'), ('H', '\u{308}', 'Ḧ'),
+    ('h', '\u{308}', 'ḧ'), ('H', '\u{327}', 'Ḩ'), ('h', '\u{327}', 'ḩ'), ('H', '\u{32e}', 'Ḫ'),
+    ('h', '\u{32e}', 'ḫ'), ('I', '\u{330}', 'Ḭ'), ('i', '\u{330}', 'ḭ'), ('Ï', '\u{301}', 'Ḯ'),
+    ('ï', '\u{301}', 'ḯ'), ('K', '\u{301}', 'Ḱ'), ('k', '\u{301}', 'ḱ'), ('K', '\u{323}', 'Ḳ'),
+    ('k', '\u{323}', 'ḳ'), ('K', '\u{331}', 'Ḵ'), ('k', '\u{331}', 'ḵ'), ('L', '\u{323}', 'Ḷ'),
+    ('l', '\u{323}', 'ḷ'), ('Ḷ', '\u{304}', 'Ḹ'), ('ḷ', '\u{304}', 'ḹ'), ('L', '\u{331}', 'Ḻ'),
+    ('l', '\u{331}', 'ḻ'), ('L', '\u{32d}', 'Ḽ'), ('l', '\u{32d}', 'ḽ'), ('M', '\u{301}', 'Ḿ'),
+    ('m', '\u{301}', 'ḿ'), ('M', '\u{307}', 'Ṁ'), ('m', '\u{307}', 'ṁ'), ('M', '\u{323}', 'Ṃ'),
+    ('m', '\u{323}', 'ṃ'), ('N', '\u{307}', 'Ṅ'), ('n', '\u{307}', 'ṅ'), ('N', '\u{323}', 'Ṇ'),
+    ('n', '\u{323}', 'ṇ'), ('N', '\u{331}', 'Ṉ'), ('n', '\u{331}', 'ṉ'), ('N', '\u{32d}', 'Ṋ'),
+    ('n', '\u{32d}', 'ṋ'), ('Õ', '\u{301}', 'Ṍ'), ('õ', '\u{301}', 'ṍ'), ('Õ', '\u{308}', 'Ṏ'),
+    ('õ', '\u{308}', 'ṏ'), ('Ō', '\u{300}', 'Ṑ'), ('ō', '\u{300}', 'ṑ'), ('Ō', '\u{301}', 'Ṓ'),
+    ('ō', '\u{301}', 'ṓ'), ('P', '\u{301}', 'Ṕ'), ('p', '\u{301}', 'ṕ'), ('P', '\u{307}', 'Ṗ'),
+    ('p', '\u{307}', 'ṗ'), ('R', '\u{307}', 'Ṙ'), ('r', '\u{307}', 'ṙ'), ('R', '\u{323}', 'Ṛ'),
+    ('r', '\u{323}', 'ṛ'), ('Ṛ', '\u{304}', 'Ṝ'), ('ṛ', '\u{304}', 'ṝ'), ('R', '\u{331}', 'Ṟ'),
+    ('r', '\u{331}', 'ṟ'), ('S', '\u{307}', 'Ṡ'), ('s', '\u{307}', 'ṡ'), ('S', '\u{323}', 'Ṣ'),
+    ('s', '\u{323}', 'ṣ'), ('Ś', '\u{307}', 'Ṥ'), ('ś', '\u{307}', 'ṥ'), ('Š', '\u{307}', 'Ṧ'),
+    ('š', '\u{307}', 'ṧ'), ('Ṣ', '\u{307}', 'Ṩ'), ('ṣ', '\u{307}', 'ṩ'), ('T', '\u{307}', 'Ṫ'),
+    ('t', '\u{307}', 'ṫ'), ('T', '\u{323}', 'Ṭ'), ('t', '\u{323}', 'ṭ'), ('T', '\u{331}', 'Ṯ'),
+    ('t', '\u{331}', 'ṯ'), ('T', '\u{32d}', 'Ṱ'), ('t', '\u{32d}', 'ṱ'), ('U', '\u{324}', 'Ṳ'),
+    ('u', '\u{324}', 'ṳ'), ('U', '\u{330}', 'Ṵ'), ('u', '\u{330}', 'ṵ'), ('U', '\u{32d}', 'Ṷ'),
+    ('u', '\u{32d}', 'ṷ'), ('Ũ', '\u{301}', 'Ṹ'), ('ũ', '\u{301}', 'ṹ'), ('Ū', '\u{308}', 'Ṻ'),
+    ('ū', '\u{308}', 'ṻ'), ('V', '\u{303}', 'Ṽ'), ('v', '\u{303}', 'ṽ'), ('V', '\u{323}', 'Ṿ'),
+    ('v', '\u{323}', 'ṿ'), ('W', '\u{300}', 'Ẁ'), ('w', '\u{300}', 'ẁ'), ('W', '\u{301}', 'Ẃ'),
+    ('w', '\u{301}', 'ẃ'), ('W', '\u{308}', 'Ẅ'), ('w', '\u{308}', 'ẅ'), ('W', '\u{307}', 'Ẇ'),
+    ('w', '\u{307}', 'ẇ'), ('W', '\u{323}', 'Ẉ'), ('w', '\u{323}', 'ẉ'), ('X', '\u{307}', 'Ẋ'),
+    ('x', '\u{307}', 'ẋ'), ('X', '\u{308}', 'Ẍ'), ('x', '\u{308}', 'ẍ'), ('Y', '\u{307}', 'Ẏ'),
+    ('y', '\u{307}', 'ẏ'), ('Z', '\u{302}', 'Ẑ'), ('z', '\u{302}', 'ẑ'), ('Z', '\u{323}', 'Ẓ'),
+    ('z', '\u{323}', 'ẓ'), ('Z', '\u{331}', 'Ẕ'), ('z', '\u{331}', 'ẕ'), ('h', '\u{331}', 'ẖ'),
+    ('t', '\u{308}', 'ẗ'), ('w', '\u{30a}', 'ẘ'), ('y', '\u{30a}', 'ẙ'), ('ſ', '\u{307}', 'ẛ'),
+    ('A', '\u{323}', 'Ạ'), ('a', '\u{323}', 'ạ'), ('A', '\u{309}', 'Ả'), ('a', '\u{309}', 'ả'),
+    ('Â', '\u{301}', 'Ấ'), ('â', '\u{301}', 'ấ'), ('Â', '\u{300}', 'Ầ'), ('â', '\u{300}', 'ầ'),
+    ('Â', '\u{309}', 'Ẩ'), ('â', '\u{309}', 'ẩ'), ('Â', '\u{303}', 'Ẫ'), ('â', '\u{303}', 'ẫ'),
+    ('Ạ', '\u{302}', 'Ậ'), ('ạ', '\u{302}', 'ậ'), ('Ă', '\u{301}', 'Ắ'), ('ă', '\u{301}', 'ắ'),
+    ('Ă', '\u{300}', 'Ằ'), ('ă', '\u{300}', 'ằ'), ('Ă', '\u{309}', 'Ẳ'), ('ă', '\u{309}', 'ẳ'),
+    ('Ă', '\u{303}', 'Ẵ'), ('ă', '\u{303}', 'ẵ'), ('Ạ', '\u{306}', 'Ặ'), ('ạ', '\u{306}', 'ặ'),
+    ('E', '\u{323}', 'Ẹ'), ('e', '\u{323}', 'ẹ'), ('E', '\u{309}', 'Ẻ'), ('e', '\u{309}', 'ẻ'),
+    ('E', '\u{303}', 'Ẽ'), ('e', '\u{303}', 'ẽ'), ('Ê', '\u{301}', 'Ế'), ('ê', '\u{301}', 'ế'),
+    ('Ê', '\u{300}', 'Ề'), ('ê', '\u{300}', 'ề'), ('Ê', '\u{309}', 'Ể'), ('ê', '\u{309}', 'ể'),
+    ('Ê', '\u{303}', 'Ễ'), ('ê', '\u{303}', 'ễ'), ('Ẹ', '\u{302}', 'Ệ'), ('ẹ', '\u{302}', 'ệ'),
+    ('I', '\u{309}', 'Ỉ'), ('i', '\u{309}', 'ỉ'), ('I', '\u{323}', 'Ị'), ('i', '\u{323}', 'ị'),
+    ('O', '\u{323}', 'Ọ'), ('o', '\u{323}', 'ọ'), ('O', '\u{309}', 'Ỏ'), ('o', '\u{309}', 'ỏ'),
+    ('Ô', '\u{301}', 'Ố'), ('ô', '\u{301}', 'ố'), ('Ô', '\u{300}', 'Ồ'), ('ô', '\u{300}', 'ồ'),
+    ('Ô', '\u{309}', 'Ổ'), ('ô', '\u{309}', 'ổ'), ('Ô', '\u{303}', 'Ỗ'), ('ô', '\u{303}', 'ỗ'),
+    ('Ọ', '\u{302}', 'Ộ'), ('ọ', '\u{302}', 'ộ'), ('Ơ', '\u{301}', 'Ớ'), ('ơ', '\u{301}', 'ớ'),
+    ('Ơ', '\u{300}', 'Ờ'), ('ơ', '\u{300}', 'ờ'), ('Ơ', '\u{309}', 'Ở'), ('ơ', '\u{309}', 'ở'),
+    ('Ơ', '\u{303}', 'Ỡ'), ('ơ', '\u{303}', 'ỡ'), ('Ơ', '\u{323}', 'Ợ'), ('ơ', '\u{323}', 'ợ'),
+    ('U', '\u{323}', 'Ụ'), ('u', '\u{323}', 'ụ'), ('U', '\u{309}', 'Ủ'), ('u', '\u{309}', 'ủ'),
+    ('Ư', '\u{301}', 'Ứ'), ('ư', '\u{301}', 'ứ'), ('Ư', '\u{300}', 'Ừ'), ('ư', '\u{300}', 'ừ'),
+    ('Ư', '\u{309}', 'Ử'), ('ư', '\u{309}', 'ử'), ('Ư', '\u{303}', 'Ữ'), ('ư', '\u{303}', 'ữ'),
+    ('Ư', '\u{323}', 'Ự'), ('ư', '\u{323}', 'ự'), ('Y', '\u{300}', 'Ỳ'), ('y', '\u{300}', 'ỳ'),
+    ('Y', '\u{323}', 'Ỵ'), ('y', '\u{323}', 'ỵ'), ('Y', '\u{309}', 'Ỷ'), ('y', '\u{309}', 'ỷ'),
+    ('Y', '\u{303}', 'Ỹ'), ('y', '\u{303}', 'ỹ'), ('¨', '\u{342}', '῁'), ('¨', '\u{300}', '῭'),
+];
+
+/// Canonical combining classes of the combining diacritical marks.
+const COMBINING_CLASSES: &[(char, u8)] = &[
+    ('\u{300}', 230), ('\u{301}', 230), ('\u{302}', 230), ('\u{303}', 230), ('\u{304}', 230),
+    ('\u{305}', 230), ('\u{306}', 230), ('\u{307}', 230), ('\u{308}', 230), ('\u{309}', 230),
+    ('\u{30a}', 230), ('\u{30b}', 230), ('\u{30c}', 230), ('\u{30d}', 230), ('\u{30e}', 230),
+    ('\u{30f}', 230), ('\u{310}', 230), ('\u{311}', 230), ('\u{312}', 230), ('\u{313}', 230),
+    ('\u{314}', 230), ('\u{315}', 232), ('\u{316}', 220), ('\u{317}', 220), ('\u{318}', 220),
+    ('\u{319}', 220), ('\u{31a}', 232), ('\u{31b}', 216), ('\u{31c}', 220), ('\u{31d}', 220),
+    ('\u{31e}', 220), ('\u{31f}', 220), ('\u{320}', 220), ('\u{321}', 202), ('\u{322}', 202),
+    ('\u{323}', 220), ('\u{324}', 220), ('\u{325}', 220), ('\u{326}', 220), ('\u{327}', 202),
+    ('\u{328}', 202), ('\u{329}', 220), ('\u{32a}', 220), ('\u{32b}', 220), ('\u{32c}', 220),
+    ('\u{32d}', 220), ('\u{32e}', 220), ('\u{32f}', 220), ('\u{330}', 220), ('\u{331}', 220),
+    ('\u{332}', 220), ('\u{333}', 220), ('\u{334}', 1), ('\u{335}', 1), ('\u{336}', 1),
+    ('\u{337}', 1), ('\u{338}', 1), ('\u{339}', 220), ('\u{33a}', 220), ('\u{33b}', 220),
+    ('\u{33c}', 220), ('\u{33d}', 230), ('\u{33e}', 230), ('\u{33f}', 230), ('\u{340}', 230),
+    ('\u{341}', 230), ('\u{342}', 230), ('\u{343}', 230), ('\u{344}', 230), ('\u{345}', 240),
+    ('\u{346}', 230), ('\u{347}', 220), ('\u{348}', 220), ('\u{349}', 220), ('\u{34a}', 230),
+    ('\u{34b}', 230), ('\u{34c}', 230), ('\u{34d}', 220), ('\u{34e}', 220), ('\u{350}', 230),
+    ('\u{351}', 230), ('\u{352}', 230), ('\u{353}', 220), ('\u{354}', 220), ('\u{355}', 220),
+    ('\u{356}', 220), ('\u{357}', 230), ('\u{358}', 232), ('\u{359}', 220), ('\u{35a}', 220),
+    ('\u{35b}', 230), ('\u{35c}', 233), ('\u{35d}', 234), ('\u{35e}', 234), ('\u{35f}', 233),
+    ('\u{360}', 234), ('\u{361}', 234), ('\u{362}', 233), ('\u{363}', 230), ('\u{364}', 230),
+    ('\u{365}', 230), ('\u{366}', 230), ('\u{367}', 230), ('\u{368}', 230), ('\u{369}', 230),
+    ('\u{36a}', 230), ('\u{36b}', 230), ('\u{36c}', 230), ('\u{36d}', 230), ('\u{36e}', 230),
+    ('\u{36f}', 230), ('\u{1dc0}', 230), ('\u{1dc1}', 230), ('\u{1dc2}', 220), ('\u{1dc3}', 230),
+    ('\u{1dc4}', 230), ('\u{1dc5}', 230), ('\u{1dc6}', 230), ('\u{1dc7}', 230), ('\u{1dc8}', 230),
+    ('\u{1dc9}', 230), ('\u{1dca}', 220), ('\u{1dcb}', 230), ('\u{1dcc}', 230), ('\u{1dcd}', 234),
+    ('\u{1dce}', 214), ('\u{1dcf}', 220), ('\u{1dd0}', 202), ('\u{1dd1}', 230), ('\u{1dd2}', 230),
+    ('\u{1dd3}', 230), ('\u{1dd4}', 230), ('\u{1dd5}', 230), ('\u{1dd6}', 230), ('\u{1dd7}', 230),
+    ('\u{1dd8}', 230), ('\u{1dd9}', 230), ('\u{1dda}', 230), ('\u{1ddb}', 230), ('\u{1ddc}', 230),
+    ('\u{1ddd}', 230), ('\u{1dde}', 230), ('\u{1ddf}', 230), ('\u{1de0}', 230), ('\u{1de1}', 230),
+    ('\u{1de2}', 230), ('\u{1de3}', 230), ('\u{1de4}', 230), ('\u{1de5}', 230), ('\u{1de6}', 230),
+    ('\u{1de7}', 230), ('\u{1de8}', 230), ('\u{1de9}', 230), ('\u{1dea}', 230), ('\u{1deb}', 230),
+    ('\u{1dec}', 230), ('\u{1ded}', 230), ('\u{1dee}', 230), ('\u{1def}', 230), ('\u{1df0}', 230),
+    ('\u{1df1}', 230), ('\u{1df2}', 230), ('\u{1df3}', 230), ('\u{1df4}', 230), ('\u{1df5}', 230),
+    ('\u{1df6}', 232), ('\u{1df7}', 228), ('\u{1df8}', 228), ('\u{1df9}', 220), ('\u{1dfa}', 218),
+    ('\u{1dfb}', 230), ('\u{1dfc}', 233), ('\u{1dfd}', 220), ('\u{1dfe}', 230), ('\u{1dff}', 220),
+    ('\u{20d0}', 230), ('\u{20d1}', 230), ('\u{20d2}', 1), ('\u{20d3}', 1), ('\u{20d4}', 230),
+    ('\u{20d5}', 230), ('\u{20d6}', 230), ('\u{20d7}', 230), ('\u{20d8}', 1), ('\u{20d9}', 1),
+    ('\u{20da}', 1), ('\u{20db}', 230), ('\u{20dc}', 230), ('\u{20e1}', 230), ('\u{20e5}', 1),
+    ('\u{20e6}', 1), ('\u{20e7}', 230), ('\u{20e8}', 220), ('\u{20e9}', 230), ('\u{20ea}', 1),
+    ('\u{20eb}', 1), ('\u{20ec}', 220), ('\u{20ed}', 220), ('\u{20ee}', 220), ('\u{20ef}', 220),
+    ('\u{20f0}', 230), ('\u{fe20}', 230), ('\u{fe21}', 230), ('\u{fe22}', 230), ('\u{fe23}', 230),
+    ('\u{fe24}', 230), ('\u{fe25}', 230), ('\u{fe26}', 230), ('\u{fe27}', 220), ('\u{fe28}', 220),
+    ('\u{fe29}', 220), ('\u{fe2a}', 220), ('\u{fe2b}', 220), ('\u{fe2c}', 220), ('\u{fe2d}', 220),
+    ('\u{fe2e}', 230), ('\u{fe2f}', 230),
+];
+
+/// Characters canonically equivalent to a single other character.
+const SINGLETONS: &[(char, char)] = &[
+    ('\u{340}', '\u{300}'), ('\u{341}', '\u{301}'), ('\u{343}', '\u{313}'), ('\u{374}', 'ʹ'),
+    ('\u{37e}', ';'), ('\u{387}', '·'), ('\u{2000}', '\u{2002}'), ('\u{2001}', '\u{2003}'),
+    ('\u{2126}', 'Ω'), ('\u{212a}', 'K'), ('\u{212b}', 'Å'),
+];
+
+/// Compatibility mappings, to NFKC.
+const COMPATIBILITY: &[(char, &str)] = &[
+    ('\u{a0}', " "), ('¨', " \u{308}"), ('ª', "a"), ('¯', " \u{304}"), ('²', "2"), ('³', "3"),
+    ('´', " \u{301}"), ('µ', "μ"), ('¸', " \u{327}"), ('¹', "1"), ('º', "o"), ('¼', "1⁄4"),
+    ('½', "1⁄2"), ('¾', "3⁄4"), ('Ĳ', "IJ"), ('ĳ', "ij"), ('Ŀ', "L·"), ('ŀ', "l·"), ('ŉ', "ʼn"),
+    ('ſ', "s"), ('Ǆ', "DŽ"), ('ǅ', "Dž"), ('ǆ', "dž"), ('Ǉ', "LJ"), ('ǈ', "Lj"), ('ǉ', "lj"),
+    ('Ǌ', "NJ"), ('ǋ', "Nj"), ('ǌ', "nj"), ('Ǳ', "DZ"), ('ǲ', "Dz"), ('ǳ', "dz"), ('\u{2002}', " "),
+    ('\u{2003}', " "), ('\u{2004}', " "), ('\u{2005}', " "), ('\u{2006}', " "), ('\u{2007}', " "),
+    ('\u{2008}', " "), ('\u{2009}', " "), ('\u{200a}', " "), ('‑', "‐"), ('‗', " \u{333}"),
+    ('․', "."), ('‥', ".."), ('…', "..."), ('\u{202f}', " "), ('″', "′′"), ('‴', "′′′"),
+    ('‶', "‵‵"), ('‷', "‵‵‵"), ('‼', "!!"), ('‾', " \u{305}"), ('⁇', "??"), ('⁈', "?!"),
+    ('⁉', "!?"), ('⁗', "′′′′"), ('\u{205f}', " "), ('⁰', "0"), ('ⁱ', "i"), ('⁴', "4"), ('⁵', "5"),
+    ('⁶', "6"), ('⁷', "7"), ('⁸', "8"), ('⁹', "9"), ('⁺', "+"), ('⁻', "−"), ('⁼', "="), ('⁽', "("),
+    ('⁾', ")"), ('ⁿ', "n"), ('₀', "0"), ('₁', "1"), ('₂', "2"), ('₃', "3"), ('₄', "4"), ('₅', "5"),
+    ('₆', "6"), ('₇', "7"), ('₈', "8"), ('₉', "9"), ('₊', "+"), ('₋', "−"), ('₌', "="), ('₍', "("),
+    ('₎', ")"), ('ₐ', "a"), ('ₑ', "e"), ('ₒ', "o"), ('ₓ', "x"), ('ₔ', "ə"), ('ₕ', "h"), ('ₖ', "k"),
+    ('ₗ', "l"), ('ₘ', "m"), ('ₙ', "n"), ('ₚ', "p"), ('ₛ', "s"), ('ₜ', "t"), ('₨', "Rs"),
+    ('ẚ', "aʾ"), ('℀', "a/c"), ('℁', "a/s"), ('ℂ', "C"), ('℃', "°C"), ('℅', "c/o"), ('℆', "c/u"),
+    ('ℇ', "Ɛ"), ('℉', "°F"), ('ℊ', "g"), ('ℋ', "H"), ('ℌ', "H"), ('ℍ', "H"), ('ℎ', "h"), ('ℏ', "ħ"),
+    ('ℐ', "I"), ('ℑ', "I"), ('ℒ', "L"), ('ℓ', "l"), ('ℕ', "N"), ('№', "No"), ('ℙ', "P"), ('ℚ', "Q"),
+    ('ℛ', "R"), ('ℜ', "R"), ('ℝ', "R"), ('℠', "SM"), ('℡', "TEL"), ('™', "TM"), ('ℤ', "Z"),
+    ('ℨ', "Z"), ('ℬ', "B"), ('ℭ', "C"), ('ℯ', "e"), ('ℰ', "E"), ('ℱ', "F"), ('ℳ', "M"), ('ℴ', "o"),
+    ('ℵ', "א"), ('ℶ', "ב"), ('ℷ', "ג"), ('ℸ', "ד"), ('ℹ', "i"), ('℻', "FAX"), ('ℼ', "π"),
+    ('ℽ', "γ"), ('ℾ', "Γ"), ('ℿ', "Π"), ('⅀', "∑"), ('ⅅ', "D"), ('ⅆ', "d"), ('ⅇ', "e"), ('ⅈ', "i"),
+    ('ⅉ', "j"), ('⅐', "1⁄7"), ('⅑', "1⁄9"), ('⅒', "1⁄10"), ('⅓', "1⁄3"), ('⅔', "2⁄3"), ('⅕', "1⁄5"),
+    ('⅖', "2⁄5"), ('⅗', "3⁄5"), ('⅘', "4⁄5"), ('⅙', "1⁄6"), ('⅚', "5⁄6"), ('⅛', "1⁄8"),
+    ('⅜', "3⁄8"), ('⅝', "5⁄8"), ('⅞', "7⁄8"), ('⅟', "1⁄"), ('Ⅰ', "I"), ('Ⅱ', "II"), ('Ⅲ', "III"),
+    ('Ⅳ', "IV"), ('Ⅴ', "V"), ('Ⅵ', "VI"), ('Ⅶ', "VII"), ('Ⅷ', "VIII"), ('Ⅸ', "IX"), ('Ⅹ', "X"),
+    ('Ⅺ', "XI"), ('Ⅻ', "XII"), ('Ⅼ', "L"), ('Ⅽ', "C"), ('Ⅾ', "D"), ('Ⅿ', "M"), ('ⅰ', "i"),
+    ('ⅱ', "ii"), ('ⅲ', "iii"), ('ⅳ', "iv"), ('ⅴ', "v"), ('ⅵ', "vi"), ('ⅶ', "vii"), ('ⅷ', "viii"),
+    ('ⅸ', "ix"), ('ⅹ', "x"), ('ⅺ', "xi"), ('ⅻ', "xii"), ('ⅼ', "l"), ('ⅽ', "c"), ('ⅾ', "d"),
+    ('ⅿ', "m"), ('↉', "0⁄3"), ('ﬀ', "ff"), ('ﬁ', "fi"), ('ﬂ', "fl"), ('ﬃ', "ffi"), ('ﬄ', "ffl"),
+    ('ﬅ', "st"), ('ﬆ', "st"), ('！', "!"), ('＂', "\u{22}"), ('＃', "#"), ('＄', "$"), ('％', "%"),
+    ('＆', "&"), ('＇', "'"), ('（', "("), ('）', ")"), ('＊', "*"), ('＋', "+"), ('，', ","), ('－', "-"),
+    ('．', "."), ('／', "/"), ('０', "0"), ('１', "1"), ('２', "2"), ('３', "3"), ('４', "4"), ('５', "5"),
+    ('６', "6"), ('７', "7"), ('８', "8"), ('９', "9"), ('：', ":"), ('；', ";"), ('＜', "<"), ('＝', "="),
+    ('＞', ">"), ('？', "?"), ('＠', "@"), ('Ａ', "A"), ('Ｂ', "B"), ('Ｃ', "C"), ('Ｄ', "D"), ('Ｅ', "E"),
+    ('Ｆ', "F"), ('Ｇ', "G"), ('Ｈ', "H"), ('Ｉ', "I"), ('Ｊ', "J"), ('Ｋ', "K"), ('Ｌ', "L"), ('Ｍ', "M"),
+    ('Ｎ', "N"), ('Ｏ', "O"), ('Ｐ', "P"), ('Ｑ', "Q"), ('Ｒ', "R"), ('Ｓ', "S"), ('Ｔ', "T"), ('Ｕ', "U"),
+    ('Ｖ', "V"), ('Ｗ', "W"), ('Ｘ', "X"), ('Ｙ', "Y"), ('Ｚ', "Z"), ('［', "["), ('＼', "\u{5c}"),
+    ('］', "]"), ('＾', "^"), ('＿', "_"), ('｀', "`"), ('ａ', "a"), ('ｂ', "b"), ('ｃ', "c"), ('ｄ', "d"),
+    ('ｅ', "e"), ('ｆ', "f"), ('ｇ', "g"), ('ｈ', "h"), ('ｉ', "i"), ('ｊ', "j"), ('ｋ', "k"), ('ｌ', "l"),
+    ('ｍ', "m"), ('ｎ', "n"), ('ｏ', "o"), ('ｐ', "p"), ('ｑ', "q"), ('ｒ', "r"), ('ｓ', "s"), ('ｔ', "t"),
+    ('ｕ', "u"), ('ｖ', "v"), ('ｗ', "w"), ('ｘ', "x"), ('ｙ', "y"), ('ｚ', "z"), ('｛', "{"), ('｜', "|"),
+    ('｝', "}"), ('～', "~"),
+];
+
+impl Normalizer for Form {
+    fn name(&self) -> &str {
+        "unicode"
+    }
+
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = normalize_detailed(text, *self);
+        NormalizeOutput::from_changes(result.original, result.normalized, result.changes)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "pyo3-backend")]
+fn parse_form(form: &str) -> PyResult<Form> {
+    Form::from_name(form).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!("form must be 'nfc' or 'nfkc', got {:?}", form))
+    })
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "normalize_unicode", signature = (text, form="nfc"))]
+pub fn py_normalize_unicode(py: Python<'_>, text: &str, form: &str) -> PyResult<String> {
+    let form = parse_form(form)?;
+    Ok(crate::allow_threads(py, text, || normalize(text, form)))
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "normalize_unicode_detailed", signature = (text, form="nfc"))]
+pub fn py_normalize_unicode_detailed(py: Python<'_>, text: &str, form: &str) -> PyResult<changes::PyDetailedResult> {
+    let form = parse_form(form)?;
+    let result = crate::allow_threads(py, text, || normalize_detailed(text, form));
+    changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nfc() {
+        assert_eq!(normalize("e\u{0301}a\u{0304}", Form::Nfc), "éā");
+        assert_eq!(normalize("u\u{0308}\u{0304}", Form::Nfc), "ǖ");
+        // Marks are reordered by class before composing
+        assert_eq!(normalize("a\u{0304}\u{0323}", Form::Nfc), "ạ\u{0304}");
+        assert_eq!(normalize("\u{212A}", Form::Nfc), "K");
+        // Already composed text, other scripts, and compatibility
+        // characters are left
+        assert_eq!(normalize("Rōma λόγος ﬁnis", Form::Nfc), "Rōma λόγος ﬁnis");
+    }
+
+    #[test]
+    fn test_nfkc() {
+        assert_eq!(normalize("ﬁnis\u{00A0}eſt Ⅻ", Form::Nfkc), "finis est XII");
+        assert_eq!(normalize("ＡＲＭＡ", Form::Nfkc), "ARMA");
+        assert_eq!(normalize("ǅ", Form::Nfkc), "Dž");
+    }
+
+    #[test]
+    fn test_invisibles() {
+        assert_eq!(normalize("\u{FEFF}im\u{00AD}pe\u{200B}rium", Form::Nfc), "imperium");
+    }
+
+    #[test]
+    fn test_normalize_detailed() {
+        let result = normalize_detailed("\u{FEFF}ﬁde\u{0304}s", Form::Nfkc);
+        assert_eq!(result.normalized, "fidēs");
+        assert_eq!(result.changes.len(), 3);
+        assert_eq!(result.changes[0].rule, "remove byte order mark");
+        assert_eq!(result.changes[1].position, 1);
+        assert_eq!(result.changes[1].normalized, "fi");
+        assert_eq!(result.changes[1].rule, "NFKC");
+        assert_eq!(result.changes[2].original, "e\u{0304}");
+        assert_eq!(result.changes[2].rule, "NFC");
+        assert_eq!(result.changes[2].context, "\u{FEFF}ﬁd[e\u{0304}]s");
+    }
+
+    #[test]
+    fn test_form_from_name() {
+        assert_eq!(Form::from_name("NFKC"), Some(Form::Nfkc));
+        assert_eq!(Form::from_name("nfc"), Some(Form::Nfc));
+        assert_eq!(Form::from_name("nfd"), None);
+    }
+}
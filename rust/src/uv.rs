@@ -1,9 +1,16 @@
+use crate::cache::WordCache;
+use crate::case;
+use crate::changes;
+use crate::graphemes;
+use crate::numerals;
+use crate::pipeline::{NormalizeOutput, Normalizer};
+use crate::scan;
+use crate::uv_model::UvModel;
 #[cfg(feature = "pyo3-backend")]
 use pyo3::prelude::*;
-#[cfg(feature = "pyo3-backend")]
-use pyo3::types::{PyDict, PyList};
+use std::borrow::Cow;
 use std::collections::HashSet;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 
 // =============================================================================
 // Character Classification Helpers
@@ -65,26 +72,11 @@ fn extract_word(chars: &[char], idx: usize) -> String {
     while end < chars.len() - 1 && is_alpha(chars[end + 1]) {
         end += 1;
     }
-    chars[start..=end]
-        .iter()
-        .map(|c| c.to_lowercase().next().unwrap_or(*c))
-        .collect()
-}
-
-fn get_context(chars: &[char], idx: usize, window: usize) -> String {
-    let start = idx.saturating_sub(window);
-    let end = (idx + window + 1).min(chars.len());
-    let mut result = String::new();
-    for &c in &chars[start..idx] {
-        result.push(c);
+    let mut word = String::with_capacity(end + 1 - start);
+    for &c in &chars[start..=end] {
+        case::push_lowercase(&mut word, c);
     }
-    result.push('[');
-    result.push(chars[idx]);
-    result.push(']');
-    for &c in &chars[idx + 1..end] {
-        result.push(c);
-    }
-    result
+    word
 }
 
 // =============================================================================
@@ -152,10 +144,49 @@ const VOCALIC_U_STEMS: &[&str] = &[
 // Core Classification Logic
 // =============================================================================
 
-/// Classify a u/v character at position idx.
+/// Whether the letter at `idx` carries a combining mark. A marked u or v
+/// stays as written, as a precomposed `ū` does: the mark is an editor's
+/// reading of the letter.
+fn is_marked(chars: &[char], idx: usize) -> bool {
+    chars.get(idx + 1).is_some_and(|&c| graphemes::is_extender(c))
+}
+
+/// Classify a u/v character at position idx, with the extra exceptions of
+/// `exceptions`.
 /// Returns (normalized_char_lowercase, rule_name).
-fn classify_uv(chars: &[char], idx: usize) -> (char, &'static str) {
-    let c = chars[idx].to_lowercase().next().unwrap();
+fn classify_uv(chars: &[char], idx: usize, exceptions: &UvNormalizer) -> (char, &'static str) {
+    let (letters, indices) = graphemes::bases(chars);
+    classify_letter(chars, idx, &letters, indices.partition_point(|&i| i < idx), exceptions)
+}
+
+/// [`classify_uv`] given `letters`, `chars` without their combining marks,
+/// of which `letters[letter]` is `chars[idx]`. The rules look at the
+/// letters on either side whatever marks they carry, but the numeral check
+/// reads the marks too (X̅ is a numeral).
+fn classify_letter(
+    chars: &[char],
+    idx: usize,
+    letters: &[char],
+    letter: usize,
+    exceptions: &UvNormalizer,
+) -> (char, &'static str) {
+    if is_marked(chars, idx) {
+        return (chars[idx].to_ascii_lowercase(), "combining_mark");
+    }
+    let word = extract_word(letters, letter);
+    classify_in_word(letters, letter, &word, numerals::in_numeral(chars, idx), exceptions)
+}
+
+/// [`classify_uv`] given the lowercase word around `idx` and whether it is
+/// a numeral, which are the same for every u and v of a word.
+fn classify_in_word(
+    chars: &[char],
+    idx: usize,
+    word: &str,
+    numeral: bool,
+    exceptions: &UvNormalizer,
+) -> (char, &'static str) {
+    let c = chars[idx].to_ascii_lowercase();
     debug_assert!(c == 'u' || c == 'v');
 
     let len = chars.len();
@@ -170,22 +201,25 @@ fn classify_uv(chars: &[char], idx: usize) -> (char, &'static str) {
     let next4 = if idx + 4 < len { Some(chars[idx + 4]) } else { None };
     let next5 = if idx + 5 < len { Some(chars[idx + 5]) } else { None };
 
-    let word = extract_word(chars, idx);
+    // Rule 0: Inside a Roman numeral → unchanged (XVI, not XUI)
+    if numeral {
+        return (c, "roman_numeral");
+    }
 
     // Rule 1: After 'q' → ALWAYS 'u'
     if let Some(p) = prev {
-        if p.to_ascii_lowercase() == 'q' {
+        if p.eq_ignore_ascii_case(&'q') {
             return ('u', "after_q");
         }
     }
 
     // Rule 2: 'ngu' before vowel → 'u' (digraph pattern)
     if let Some(p) = prev {
-        if p.to_ascii_lowercase() == 'g' {
+        if p.eq_ignore_ascii_case(&'g') {
             if let Some(n) = next1 {
                 if is_vowel(n) {
                     if let Some(p2) = prev2 {
-                        if p2.to_ascii_lowercase() == 'n' {
+                        if p2.eq_ignore_ascii_case(&'n') {
                             return ('u', "ngu_digraph");
                         }
                     }
@@ -196,25 +230,25 @@ fn classify_uv(chars: &[char], idx: usize) -> (char, &'static str) {
     }
 
     // Rule 3: Word exceptions (morphological)
-    if VOCALIC_U_WORDS.contains(word.as_str()) {
+    if VOCALIC_U_WORDS.contains(word) || exceptions.is_vocalic_word(word) {
         return ('u', "word_exception");
     }
 
     // Rule 4: Perfect tense patterns
     // Special case: volo/nolo/malo have u-perfect with 'l'
     if let (Some(n1), Some(p)) = (next1, prev) {
-        if n1.to_ascii_lowercase() == 'i' && p.to_ascii_lowercase() == 'l' {
-            if word.starts_with("vol")
+        if n1.eq_ignore_ascii_case(&'i')
+            && p.eq_ignore_ascii_case(&'l')
+            && (word.starts_with("vol")
                 || word.starts_with("nol")
                 || word.starts_with("mal")
-                || word.starts_with("uol")
-            {
-                if let Some(n2) = next2 {
-                    if n2.to_ascii_lowercase() == 't' {
-                        let n3_end = next3.map_or(true, |c| !is_alpha(c));
-                        if n3_end {
-                            return ('u', "volo_perfect");
-                        }
+                || word.starts_with("uol"))
+        {
+            if let Some(n2) = next2 {
+                if n2.eq_ignore_ascii_case(&'t') {
+                    let n3_end = next3.is_none_or(|c| !is_alpha(c));
+                    if n3_end {
+                        return ('u', "volo_perfect");
                     }
                 }
             }
@@ -223,11 +257,11 @@ fn classify_uv(chars: &[char], idx: usize) -> (char, &'static str) {
 
     // Syncopated perfect -uere (3pl: potuere, fuere)
     if let (Some(n1), Some(n2), Some(n3)) = (next1, next2, next3) {
-        if n1.to_ascii_lowercase() == 'e'
-            && n2.to_ascii_lowercase() == 'r'
-            && n3.to_ascii_lowercase() == 'e'
+        if n1.eq_ignore_ascii_case(&'e')
+            && n2.eq_ignore_ascii_case(&'r')
+            && n3.eq_ignore_ascii_case(&'e')
         {
-            let n4_end = next4.map_or(true, |c| !is_alpha(c));
+            let n4_end = next4.is_none_or(|c| !is_alpha(c));
             if n4_end {
                 if let Some(p) = prev {
                     if is_u_perfect_consonant(p) {
@@ -240,9 +274,9 @@ fn classify_uv(chars: &[char], idx: usize) -> (char, &'static str) {
 
     // Standard -ui, -uit patterns
     if let Some(n1) = next1 {
-        if n1.to_ascii_lowercase() == 'i' {
+        if n1.eq_ignore_ascii_case(&'i') {
             // -ui at word end (1sg perfect: fui, potui)
-            let n2_end = next2.map_or(true, |c| !is_alpha(c));
+            let n2_end = next2.is_none_or(|c| !is_alpha(c));
             if n2_end {
                 if let Some(p) = prev {
                     if is_u_perfect_consonant(p) {
@@ -253,8 +287,8 @@ fn classify_uv(chars: &[char], idx: usize) -> (char, &'static str) {
 
             // -uit at word end (3sg perfect: fuit, potuit)
             if let Some(n2) = next2 {
-                if n2.to_ascii_lowercase() == 't' {
-                    let n3_end = next3.map_or(true, |c| !is_alpha(c));
+                if n2.eq_ignore_ascii_case(&'t') {
+                    let n3_end = next3.is_none_or(|c| !is_alpha(c));
                     if n3_end {
                         if let Some(p) = prev {
                             if is_u_perfect_consonant(p) {
@@ -267,11 +301,11 @@ fn classify_uv(chars: &[char], idx: usize) -> (char, &'static str) {
 
             // -uimus pattern (1pl perfect)
             if let (Some(n2), Some(n3), Some(n4)) = (next2, next3, next4) {
-                if n2.to_ascii_lowercase() == 'm'
-                    && n3.to_ascii_lowercase() == 'u'
-                    && n4.to_ascii_lowercase() == 's'
+                if n2.eq_ignore_ascii_case(&'m')
+                    && n3.eq_ignore_ascii_case(&'u')
+                    && n4.eq_ignore_ascii_case(&'s')
                 {
-                    let n5_end = next5.map_or(true, |c| !is_alpha(c));
+                    let n5_end = next5.is_none_or(|c| !is_alpha(c));
                     if n5_end {
                         if let Some(p) = prev {
                             if is_u_perfect_consonant(p) {
@@ -284,11 +318,11 @@ fn classify_uv(chars: &[char], idx: usize) -> (char, &'static str) {
 
             // Perfect -uisse (infinitive)
             if let (Some(n2), Some(n3), Some(n4)) = (next2, next3, next4) {
-                if n2.to_ascii_lowercase() == 's'
-                    && n3.to_ascii_lowercase() == 's'
-                    && n4.to_ascii_lowercase() == 'e'
+                if n2.eq_ignore_ascii_case(&'s')
+                    && n3.eq_ignore_ascii_case(&'s')
+                    && n4.eq_ignore_ascii_case(&'e')
                 {
-                    let n5_end = next5.map_or(true, |c| !is_alpha(c));
+                    let n5_end = next5.is_none_or(|c| !is_alpha(c));
                     if n5_end {
                         if let Some(p) = prev {
                             if is_consonant(p) {
@@ -303,8 +337,8 @@ fn classify_uv(chars: &[char], idx: usize) -> (char, &'static str) {
 
     // Perfect -uera-, -ueri-, -uero- (pluperfect/future perfect)
     if let (Some(n1), Some(n2), Some(n3)) = (next1, next2, next3) {
-        if n1.to_ascii_lowercase() == 'e'
-            && n2.to_ascii_lowercase() == 'r'
+        if n1.eq_ignore_ascii_case(&'e')
+            && n2.eq_ignore_ascii_case(&'r')
             && matches!(n3.to_ascii_lowercase(), 'a' | 'i' | 'o')
         {
             if let Some(p) = prev {
@@ -331,7 +365,7 @@ fn classify_uv(chars: &[char], idx: usize) -> (char, &'static str) {
                         return ('u', "double_u_first_CCuu");
                     }
                 } else if is_vowel(p) {
-                    if p.to_ascii_lowercase() == 'i' && is_word_boundary(chars, idx - 1) {
+                    if p.eq_ignore_ascii_case(&'i') && is_word_boundary(chars, idx - 1) {
                         return ('u', "double_u_first_initial_i");
                     } else {
                         return ('v', "double_u_first_Vuu");
@@ -356,7 +390,7 @@ fn classify_uv(chars: &[char], idx: usize) -> (char, &'static str) {
                         return ('v', "double_u_second_CCuu");
                     }
                 } else if is_vowel(p2) {
-                    if p2.to_ascii_lowercase() == 'i' && is_word_boundary(chars, idx - 2) {
+                    if p2.eq_ignore_ascii_case(&'i') && is_word_boundary(chars, idx - 2) {
                         return ('v', "double_u_second_initial_i");
                     } else {
                         return ('u', "double_u_second_Vuu");
@@ -410,6 +444,12 @@ fn classify_uv(chars: &[char], idx: usize) -> (char, &'static str) {
                     return ('u', "vocalic_u_stem");
                 }
             }
+            if exceptions.has_vocalic_stem(&word_lower) {
+                return ('u', "vocalic_u_stem");
+            }
+            if let Some(decided) = exceptions.model_decision(chars, idx) {
+                return decided;
+            }
             return ('v', "post_consonant_before_vowel");
         }
     }
@@ -418,57 +458,41 @@ fn classify_uv(chars: &[char], idx: usize) -> (char, &'static str) {
     if let Some(p) = prev {
         if is_consonant(p) {
             let next_is_consonant_or_end =
-                next1.map_or(true, |n| is_consonant(n) || !is_alpha(n));
+                next1.is_none_or(|n| is_consonant(n) || !is_alpha(n));
             if next_is_consonant_or_end {
                 return ('u', "post_consonant_before_consonant");
             }
         }
     }
 
-    // Default: keep as 'u' (conservative)
-    ('u', "default")
+    // Default: keep as 'u' (conservative), unless a model knows better
+    exceptions.model_decision(chars, idx).unwrap_or(('u', "default"))
+}
+
+/// Whether the u or v at `idx` is consonantal by the rules of
+/// [`normalize`].
+pub(crate) fn is_consonantal(chars: &[char], idx: usize) -> bool {
+    classify_uv(chars, idx, &UvNormalizer::new()).0 == 'v'
 }
 
 // =============================================================================
 // Public Rust API
 // =============================================================================
 
-pub fn normalize(text: &str) -> String {
-    if text.is_empty() {
-        return String::new();
-    }
-
-    let chars: Vec<char> = text.chars().collect();
-    let mut result = String::with_capacity(text.len());
-
-    for (i, &ch) in chars.iter().enumerate() {
-        if matches!(ch.to_ascii_lowercase(), 'u' | 'v') {
-            let (normalized, _) = classify_uv(&chars, i);
-            if ch.is_uppercase() {
-                result.push(normalized.to_uppercase().next().unwrap());
-            } else {
-                result.push(normalized);
-            }
-        } else {
-            result.push(ch);
-        }
-    }
-
-    result
+/// `text` with u/v normalized, borrowed back unchanged when no u or v
+/// changes.
+pub fn normalize(text: &str) -> Cow<'_, str> {
+    UvNormalizer::new().normalize_text(text)
 }
 
+/// The u or v at char `idx` of `text` normalized in context, in its
+/// original case, and the rule that decided it.
+///
+/// # Panics
+///
+/// If char `idx` of `text` is not a u or v.
 pub fn normalize_char(text: &str, idx: usize) -> (String, &'static str) {
-    let chars: Vec<char> = text.chars().collect();
-    let ch = chars[idx];
-    let (normalized, rule) = classify_uv(&chars, idx);
-
-    let result_char = if ch.is_uppercase() {
-        normalized.to_uppercase().collect()
-    } else {
-        normalized.to_string()
-    };
-
-    (result_char, rule)
+    UvNormalizer::new().normalize_char(text, idx)
 }
 
 pub struct DetailedResult {
@@ -477,56 +501,311 @@ pub struct DetailedResult {
     pub changes: Vec<ChangeRecord>,
 }
 
-pub struct ChangeRecord {
-    pub position: usize,
-    pub original: String,
-    pub normalized: String,
-    pub rule: &'static str,
-    pub context: String,
-}
+pub use crate::changes::ChangeRecord;
 
 pub fn normalize_detailed(text: &str) -> DetailedResult {
-    if text.is_empty() {
-        return DetailedResult {
-            original: String::new(),
-            normalized: String::new(),
-            changes: Vec::new(),
+    UvNormalizer::new().normalize_detailed(text)
+}
+
+/// The u/v rules of [`normalize_detailed`] as a [`Normalizer`], with any
+/// extra words and stems whose u before a vowel is vocalic.
+///
+/// ```
+/// use latincy_preprocess::uv::{self, UvNormalizer};
+///
+/// assert_eq!(uv::normalize("Dauus genua"), "Davus genva");
+/// let normalizer = UvNormalizer::new().vocalic_words(["dauus"]).vocalic_stems(["genu"]);
+/// assert_eq!(normalizer.normalize_text("Dauus genua"), "Dauus genua");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UvNormalizer {
+    /// Lowercase, with v spelled u.
+    vocalic_words: HashSet<String>,
+    vocalic_stems: Vec<String>,
+    /// Decides what the rules can only guess by position, if set.
+    model: Option<Arc<UvModel>>,
+    /// Results of earlier words, if caching.
+    cache: Option<Arc<WordCache>>,
+    /// This configuration's key in `cache`.
+    config: u64,
+}
+
+/// Normalizers are equal when they have the same exceptions and model,
+/// whatever their caches.
+impl PartialEq for UvNormalizer {
+    fn eq(&self, other: &Self) -> bool {
+        self.vocalic_words == other.vocalic_words
+            && self.vocalic_stems == other.vocalic_stems
+            && self.model == other.model
+    }
+}
+
+impl Eq for UvNormalizer {}
+
+/// `word` in lowercase with v spelled u, the form exceptions are kept in.
+fn exception_form(word: &str) -> String {
+    word.to_lowercase().replace('v', "u")
+}
+
+impl UvNormalizer {
+    /// The built-in rules, with no extra exceptions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds words whose every u stays vocalic, like the built-in *cui* and
+    /// *tuus*. Case and u/v spelling do not matter.
+    pub fn vocalic_words<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.vocalic_words.extend(words.into_iter().map(|word| exception_form(word.as_ref())));
+        self.config = crate::cache::new_config();
+        self
+    }
+
+    /// Adds stems in which a u after a consonant and before a vowel stays
+    /// vocalic, like the built-in *statu-* (*statua*, *statuae*), in any
+    /// word containing them.
+    pub fn vocalic_stems<I, S>(mut self, stems: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.vocalic_stems.extend(stems.into_iter().map(|stem| exception_form(stem.as_ref())));
+        self.config = crate::cache::new_config();
+        self
+    }
+
+    /// Ask `model` about the u and v the rules decide by position alone:
+    /// those after a consonant and before a vowel in no vocalic stem, and
+    /// those no rule covers. A confident prediction is taken, with the
+    /// rule name `model`; otherwise the rule stands. See
+    /// [`uv_model`](crate::uv_model).
+    pub fn model(mut self, model: Arc<UvModel>) -> Self {
+        self.model = Some(model);
+        self.config = crate::cache::new_config();
+        self
+    }
+
+    /// The fallback model, if any.
+    pub fn fallback_model(&self) -> Option<&Arc<UvModel>> {
+        self.model.as_ref()
+    }
+
+    /// Look words up in `cache` before classifying their u and v, and store
+    /// the results there. The cache can be shared with other normalizers,
+    /// whatever their configuration, and kept across texts.
+    pub fn word_cache(mut self, cache: Arc<WordCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// The extra vocalic words, sorted.
+    pub fn extra_vocalic_words(&self) -> Vec<&str> {
+        let mut words: Vec<&str> = self.vocalic_words.iter().map(String::as_str).collect();
+        words.sort_unstable();
+        words
+    }
+
+    /// The extra vocalic stems, in the order added.
+    pub fn extra_vocalic_stems(&self) -> &[String] {
+        &self.vocalic_stems
+    }
+
+    fn is_vocalic_word(&self, word: &str) -> bool {
+        !self.vocalic_words.is_empty() && self.vocalic_words.contains(&exception_form(word))
+    }
+
+    fn model_decision(&self, chars: &[char], idx: usize) -> Option<(char, &'static str)> {
+        Some((self.model.as_ref()?.decide(chars, idx)?, "model"))
+    }
+
+    fn has_vocalic_stem(&self, word: &str) -> bool {
+        if self.vocalic_stems.is_empty() {
+            return false;
+        }
+        let word = exception_form(word);
+        self.vocalic_stems.iter().any(|stem| word.contains(stem.as_str()))
+    }
+
+    /// `text` with u/v normalized, as by [`normalize`].
+    ///
+    /// Every rule looks only at the word around the u or v (letters and
+    /// combining marks, as [`numerals`] reads words), so the text is scanned
+    /// for u and v a chunk of bytes at a time, only the words around them
+    /// are classified, and the text is copied only once something changes.
+    pub fn normalize_text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut result = String::new();
+        let mut copied = 0;
+        let mut word: Vec<char> = Vec::new();
+        let mut lower = String::new();
+        let mut from = 0;
+        while let Some(found) = scan::find_uv(text.as_bytes(), from) {
+            let start = text[..found]
+                .char_indices()
+                .rev()
+                .take_while(|&(_, c)| numerals::is_word_char(c))
+                .last()
+                .map_or(found, |(i, _)| i);
+            let end = text[found..]
+                .char_indices()
+                .find(|&(_, c)| !numerals::is_word_char(c))
+                .map_or(text.len(), |(i, _)| found + i);
+            word.clear();
+            word.extend(text[start..end].chars());
+
+            match &self.cache {
+                Some(cache) => {
+                    let original = &text[start..end];
+                    let normalized = cache.get_or_insert_with(self.config, original, || {
+                        let mut normalized = String::with_capacity(original.len());
+                        let mut kept = 0;
+                        self.word_changes(&word, &mut lower, |offset, ch, replacement| {
+                            normalized.push_str(&original[kept..offset]);
+                            normalized.push(replacement);
+                            kept = offset + ch.len_utf8();
+                        });
+                        normalized.push_str(&original[kept..]);
+                        normalized
+                    });
+                    if normalized != original {
+                        result.push_str(&text[copied..start]);
+                        result.push_str(&normalized);
+                        copied = end;
+                    }
+                }
+                None => self.word_changes(&word, &mut lower, |offset, ch, replacement| {
+                    result.push_str(&text[copied..start + offset]);
+                    result.push(replacement);
+                    copied = start + offset + ch.len_utf8();
+                }),
+            }
+            from = end;
+        }
+
+        if copied == 0 {
+            return Cow::Borrowed(text);
+        }
+        result.push_str(&text[copied..]);
+        Cow::Owned(result)
+    }
+
+    /// Classify every u and v of `word`, calling `change(offset, ch,
+    /// replacement)` with the byte offset of each that changes. `lower` is
+    /// scratch space for the lowercase word.
+    fn word_changes(&self, word: &[char], lower: &mut String, mut change: impl FnMut(usize, char, char)) {
+        // The rules see the letters without their combining marks, which
+        // are the whole word around every u and v, so it is lowercased and
+        // parsed only once
+        let marked;
+        let (letters, indices) = if !word.iter().any(|&c| graphemes::is_extender(c)) {
+            (word, None)
+        } else {
+            marked = graphemes::bases(word);
+            (&marked.0[..], Some(&marked.1[..]))
         };
+        lower.clear();
+        for &c in letters {
+            case::push_lowercase(lower, c);
+        }
+        let numeral = numerals::in_numeral(word, 0);
+
+        let (mut offset, mut at) = (0, 0);
+        for (i, &ch) in letters.iter().enumerate() {
+            let idx = indices.map_or(i, |indices| indices[i]);
+            if matches!(ch, 'u' | 'v' | 'U' | 'V') && !is_marked(word, idx) {
+                let (normalized, _) = classify_in_word(letters, i, lower, numeral, self);
+                let normalized = if case::is_capital(ch) { normalized.to_ascii_uppercase() } else { normalized };
+                if normalized != ch {
+                    offset += word[at..idx].iter().map(|c| c.len_utf8()).sum::<usize>();
+                    at = idx;
+                    change(offset, ch, normalized);
+                }
+            }
+        }
     }
 
-    let chars: Vec<char> = text.chars().collect();
-    let mut result_chars = String::with_capacity(text.len());
-    let mut changes = Vec::new();
+    /// The u or v at char `idx` of `text` normalized, and the rule that
+    /// decided it, as by [`normalize_char`].
+    pub fn normalize_char(&self, text: &str, idx: usize) -> (String, &'static str) {
+        let chars: Vec<char> = text.chars().collect();
+        let ch = chars[idx];
+        assert!(matches!(ch, 'u' | 'v' | 'U' | 'V'), "char {} is {:?}, not u or v", idx, ch);
+        let (normalized, rule) = classify_uv(&chars, idx, self);
 
-    for (i, &ch) in chars.iter().enumerate() {
-        if matches!(ch.to_ascii_lowercase(), 'u' | 'v') {
-            let (norm_lower, rule) = classify_uv(&chars, i);
-            let normalized = if ch.is_uppercase() {
-                norm_lower.to_uppercase().next().unwrap()
-            } else {
-                norm_lower
+        let result_char = if case::is_capital(ch) {
+            normalized.to_uppercase().collect()
+        } else {
+            normalized.to_string()
+        };
+
+        (result_char, rule)
+    }
+
+    /// `text` normalized with a record of each change, as by
+    /// [`normalize_detailed`].
+    pub fn normalize_detailed(&self, text: &str) -> DetailedResult {
+        if text.is_empty() {
+            return DetailedResult {
+                original: String::new(),
+                normalized: String::new(),
+                changes: Vec::new(),
             };
+        }
 
-            result_chars.push(normalized);
+        let chars: Vec<char> = text.chars().collect();
+        let (letters, indices) = graphemes::bases(&chars);
+        let mut result_chars = String::with_capacity(text.len());
+        let mut changes = Vec::new();
 
-            if normalized != ch {
-                changes.push(ChangeRecord {
-                    position: i,
-                    original: ch.to_string(),
-                    normalized: normalized.to_string(),
-                    rule,
-                    context: get_context(&chars, i, 3),
-                });
+        let mut letter = 0;
+        for (i, &ch) in chars.iter().enumerate() {
+            if indices.get(letter) == Some(&i) {
+                letter += 1;
             }
-        } else {
-            result_chars.push(ch);
+            if matches!(ch.to_ascii_lowercase(), 'u' | 'v') {
+                let (norm_lower, rule) = classify_letter(&chars, i, &letters, letter - 1, self);
+                let normalized = if case::is_capital(ch) {
+                    norm_lower.to_ascii_uppercase()
+                } else {
+                    norm_lower
+                };
+
+                result_chars.push(normalized);
+
+                if normalized != ch {
+                    changes.push(ChangeRecord {
+                        position: i,
+                        original: ch.to_string(),
+                        normalized: normalized.to_string(),
+                        pass: 1,
+                        rule: rule.to_string(),
+                        context: changes::context(&chars, i, 1),
+                    });
+                }
+            } else {
+                result_chars.push(ch);
+            }
+        }
+
+        DetailedResult {
+            original: text.to_string(),
+            normalized: result_chars,
+            changes,
         }
     }
+}
+
+impl Normalizer for UvNormalizer {
+    fn name(&self) -> &str {
+        "uv"
+    }
 
-    DetailedResult {
-        original: text.to_string(),
-        normalized: result_chars,
-        changes,
+    fn normalize(&self, text: &str) -> NormalizeOutput {
+        let result = self.normalize_detailed(text);
+        NormalizeOutput::from_changes(result.original, result.normalized, result.changes)
     }
 }
 
@@ -536,39 +815,118 @@ pub fn normalize_detailed(text: &str) -> DetailedResult {
 
 #[cfg(feature = "pyo3-backend")]
 #[pyfunction]
-pub fn normalize_uv(text: &str) -> String {
-    normalize(text)
+pub fn normalize_uv(py: Python<'_>, text: &str) -> String {
+    crate::allow_threads(py, text, || normalize(text).into_owned())
 }
 
 #[cfg(feature = "pyo3-backend")]
 #[pyfunction]
-pub fn normalize_uv_char(text: &str, idx: usize) -> (String, String) {
+pub fn normalize_uv_char(text: &str, idx: usize) -> PyResult<(String, String)> {
+    check_uv_index(text, idx)?;
     let (ch, rule) = normalize_char(text, idx);
-    (ch, rule.to_string())
+    Ok((ch, rule.to_string()))
+}
+
+/// The `IndexError` or `ValueError` the Python `normalize_char` raises if
+/// char `idx` of `text` is not a u or v, rather than a panic.
+#[cfg(feature = "pyo3-backend")]
+fn check_uv_index(text: &str, idx: usize) -> PyResult<()> {
+    match text.chars().nth(idx) {
+        Some('u' | 'v' | 'U' | 'V') => Ok(()),
+        Some(c) => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Character at position {} is not u/v: '{}'",
+            idx, c
+        ))),
+        None => Err(pyo3::exceptions::PyIndexError::new_err("string index out of range")),
+    }
 }
 
 #[cfg(feature = "pyo3-backend")]
 #[pyfunction]
-pub fn normalize_uv_detailed(py: Python<'_>, text: &str) -> PyResult<PyObject> {
-    let result = normalize_detailed(text);
-
-    let dict = PyDict::new(py);
-    dict.set_item("original", &result.original)?;
-    dict.set_item("normalized", &result.normalized)?;
-
-    let changes = PyList::empty(py);
-    for change in &result.changes {
-        let change_dict = PyDict::new(py);
-        change_dict.set_item("position", change.position)?;
-        change_dict.set_item("original", &change.original)?;
-        change_dict.set_item("normalized", &change.normalized)?;
-        change_dict.set_item("rule", change.rule)?;
-        change_dict.set_item("context", &change.context)?;
-        changes.append(change_dict)?;
-    }
-    dict.set_item("changes", changes)?;
-
-    Ok(dict.into())
+pub fn normalize_uv_detailed(py: Python<'_>, text: &str) -> PyResult<changes::PyDetailedResult> {
+    let result = crate::allow_threads(py, text, || normalize_detailed(text));
+    changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)
+}
+
+/// Python view of [`UvNormalizer`], exposed as `_rust.UvNormalizer`; build
+/// it once and reuse it for every text.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "UvNormalizer")]
+pub struct PyUvNormalizer {
+    pub(crate) inner: UvNormalizer,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyUvNormalizer {
+    #[new]
+    #[pyo3(signature = (vocalic_words=None, vocalic_stems=None, cache_size=None, model=None))]
+    fn new(
+        vocalic_words: Option<Vec<String>>,
+        vocalic_stems: Option<Vec<String>>,
+        cache_size: Option<usize>,
+        model: Option<PyRef<'_, crate::uv_model::PyUvModel>>,
+    ) -> Self {
+        let mut inner = UvNormalizer::new()
+            .vocalic_words(vocalic_words.unwrap_or_default())
+            .vocalic_stems(vocalic_stems.unwrap_or_default());
+        if let Some(model) = model {
+            inner = inner.model(model.inner.clone());
+        }
+        if let Some(capacity) = cache_size {
+            inner = inner.word_cache(Arc::new(WordCache::new(capacity)));
+        }
+        PyUvNormalizer { inner }
+    }
+
+    /// The extra vocalic words, lowercase with v spelled u, sorted.
+    #[getter]
+    fn vocalic_words(&self) -> Vec<String> {
+        self.inner.extra_vocalic_words().into_iter().map(str::to_string).collect()
+    }
+
+    #[getter]
+    fn vocalic_stems(&self) -> Vec<String> {
+        self.inner.extra_vocalic_stems().to_vec()
+    }
+
+    /// The fallback model, if any.
+    #[getter]
+    fn model(&self) -> Option<crate::uv_model::PyUvModel> {
+        let inner = self.inner.fallback_model()?.clone();
+        Some(crate::uv_model::PyUvModel { inner })
+    }
+
+    fn extend_vocalic_words(&mut self, words: Vec<String>) {
+        self.inner = std::mem::take(&mut self.inner).vocalic_words(words);
+    }
+
+    fn extend_vocalic_stems(&mut self, stems: Vec<String>) {
+        self.inner = std::mem::take(&mut self.inner).vocalic_stems(stems);
+    }
+
+    fn normalize(&self, py: Python<'_>, text: &str) -> String {
+        crate::allow_threads(py, text, || self.inner.normalize_text(text).into_owned())
+    }
+
+    fn normalize_char(&self, text: &str, idx: usize) -> PyResult<(String, String)> {
+        check_uv_index(text, idx)?;
+        let (ch, rule) = self.inner.normalize_char(text, idx);
+        Ok((ch, rule.to_string()))
+    }
+
+    fn normalize_detailed(&self, py: Python<'_>, text: &str) -> PyResult<changes::PyDetailedResult> {
+        let result = crate::allow_threads(py, text, || self.inner.normalize_detailed(text));
+        changes::PyDetailedResult::from_records(py, &result.original, &result.normalized, &result.changes)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "UvNormalizer(vocalic_words={:?}, vocalic_stems={:?})",
+            self.inner.extra_vocalic_words(),
+            self.inner.extra_vocalic_stems()
+        )
+    }
 }
 
 // =============================================================================
@@ -579,6 +937,21 @@ pub fn normalize_uv_detailed(py: Python<'_>, text: &str) -> PyResult<PyObject> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_extra_exceptions() {
+        assert_eq!(normalize("Dauus"), "Davus");
+        let normalizer = UvNormalizer::new().vocalic_words(["DAVVS"]).vocalic_stems(["genu"]);
+        assert_eq!(normalizer.extra_vocalic_words(), ["dauus"]);
+        assert_eq!(normalizer.normalize_text("Dauus uidit"), "Dauus vidit");
+        assert_eq!(normalizer.normalize_char("Dauus", 2), ("u".to_string(), "word_exception"));
+        assert_eq!(normalize("genua"), "genva");
+        assert_eq!(normalizer.normalize_text("genua"), "genua");
+        let result = normalizer.normalize_detailed("Dauus uenit");
+        assert_eq!(result.normalized, "Dauus venit");
+        assert_eq!(result.changes.len(), 1);
+        assert_eq!(UvNormalizer::new().normalize_text("Dauus"), normalize("Dauus"));
+    }
+
     #[test]
     fn test_after_q() {
         assert_eq!(normalize("quod"), "quod");
@@ -650,6 +1023,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mixed_case() {
+        assert_eq!(normalize("McUir UirUm"), "McVir VirUm");
+        assert_eq!(normalize("ǅuuenis İuuenis"), "ǅuvenis İuvenis");
+    }
+
+    #[test]
+    fn test_combining_marks() {
+        // Decomposed marks stay on their letters and out of the rules' way;
+        // a marked u or v is left as written, like a precomposed one
+        for (text, expected) in [
+            ("u\u{0304}ua", "u\u{0304}va"),
+            ("u\u{0323}ua", "u\u{0323}va"),
+            ("ui\u{0304}a", "vi\u{0304}a"),
+            ("seruu\u{0304}s", "servu\u{0304}s"),
+            ("uo\u{0301}x", "vo\u{0301}x"),
+        ] {
+            assert_eq!(normalize(text), expected, "{}", text);
+            assert_eq!(normalize(text), normalize_detailed(text).normalized, "{}", text);
+        }
+        let text = "seru\u{0304}us";
+        let idx = text.chars().position(|c| c == '\u{0304}').unwrap() + 1;
+        assert_eq!(normalize_char(text, idx).0, normalize(text).chars().nth(idx).unwrap().to_string());
+        // The vinculum still marks a numeral
+        assert_eq!(normalize("X\u{0305}V"), "X\u{0305}V");
+    }
+
     #[test]
     fn test_soluit_distinguished() {
         assert_eq!(normalize("soluit"), "solvit");
@@ -667,4 +1067,97 @@ mod tests {
         assert_eq!(normalize("conspicua"), "conspicua");
         assert_eq!(normalize("individua"), "individua");
     }
+
+    #[test]
+    fn test_roman_numerals() {
+        assert_eq!(normalize("liber XVI, cap. xv"), "liber XVI, cap. xv");
+        assert_eq!(normalize("MDCXV uidi"), "MDCXV vidi");
+        assert_eq!(normalize_char("cap. xv", 6), ("v".to_string(), "roman_numeral"));
+        // Numeral-shaped words are still normalized
+        assert_eq!(normalize("ui et armis"), "vi et armis");
+    }
+
+    #[test]
+    fn test_normalize_borrows_unchanged() {
+        assert!(matches!(normalize("Gallia est omnis"), Cow::Borrowed(_)));
+        assert!(matches!(normalize("uultus seruus"), Cow::Owned(_)));
+        assert!(matches!(normalize("cui tuum"), Cow::Borrowed(_)));
+        assert!(matches!(normalize(""), Cow::Borrowed("")));
+    }
+
+    #[test]
+    fn test_word_windows_match_whole_text() {
+        // normalize_detailed still classifies over the whole text
+        let texts = [
+            "Arma uirumque cano, Troiae qui primus ab oris",
+            "VVLTVS seruus; nouus-uir (uiuus) \"uae\" 3uir uir3",
+            "lingua sanguis quum equus fuit potuit fuere potuisse docuerat",
+            "MDCXV uidi, cap. xv. M. Uarro uenit; iuuenis iuuat",
+            "u\u{0301}ua ue\u{0304}nit ūua æuum cuiusque uu uuu v",
+            "soluit statuae genua Dauus ...u v. u",
+            "«Uenus»—uir’s ﬁlius, ǽuum uel\tuox, nouem\u{00AD}uiri",
+        ];
+        for text in texts {
+            assert_eq!(normalize(text), UvNormalizer::new().normalize_detailed(text).normalized, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_word_cache() {
+        let cache = Arc::new(WordCache::new(64));
+        let cached = UvNormalizer::new().word_cache(Arc::clone(&cache));
+        let text = "Arma uirumque cano; uirum VVLTVS uideo, seruus seruum u\u{0301}ua";
+        assert_eq!(cached.normalize_text(text), normalize(text));
+        assert_eq!(cached.normalize_text(text), normalize(text));
+        assert_eq!((cache.hits(), cache.misses()), (7, 7));
+        assert_eq!(cached, UvNormalizer::new());
+
+        // New exceptions get a key of their own in the shared cache
+        let exceptions = cached.vocalic_words(["seruus"]);
+        assert_eq!(exceptions.normalize_text("seruus"), "seruus");
+        assert_eq!(UvNormalizer::new().word_cache(cache).normalize_text("seruus"), "servus");
+    }
+
+    #[test]
+    fn test_model_fallback() {
+        let model = Arc::new(UvModel::train(["genua genuum genuamus genuent genuare", "larva larvae larvam larvas"]));
+        let normalizer = UvNormalizer::new().model(model.clone());
+        // The model overrules a positional rule, but not a lexical one
+        assert_eq!(normalizer.normalize_char("genua", 3), ("u".to_string(), "model"));
+        assert_eq!(normalizer.normalize_char("quae", 1), ("u".to_string(), "after_q"));
+        assert_eq!(normalizer.normalize_text("genua larua seruus"), "genua larva servus");
+        assert_eq!(normalizer.normalize_detailed("larua").changes[0].rule, "model");
+        // Where the model is unsure, the rule stands
+        assert_eq!(normalizer.normalize_detailed("silua").changes[0].rule, "post_consonant_before_vowel");
+        assert_ne!(normalizer, UvNormalizer::new());
+        assert_eq!(normalizer, UvNormalizer::new().model(model));
+
+        // A cached normalizer with a model keeps its results apart
+        let cache = Arc::new(WordCache::new(16));
+        assert_eq!(UvNormalizer::new().word_cache(cache.clone()).normalize_text("genua"), "genva");
+        assert_eq!(normalizer.word_cache(cache).normalize_text("genua"), "genua");
+    }
+
+    proptest::proptest! {
+        /// Only u and v change, one for one, each as `normalize_char`
+        /// decides it, and the detailed path agrees.
+        #[test]
+        fn test_normalize_properties(text in crate::testing::text()) {
+            let from: Vec<char> = text.chars().collect();
+            let to: Vec<char> = normalize(&text).chars().collect();
+            proptest::prop_assert_eq!(from.len(), to.len());
+            for (i, (&a, &b)) in from.iter().zip(&to).enumerate() {
+                if matches!(a, 'u' | 'v' | 'U' | 'V') {
+                    proptest::prop_assert_eq!(normalize_char(&text, i).0, b.to_string());
+                } else {
+                    proptest::prop_assert_eq!(a, b);
+                }
+            }
+            let detailed = normalize_detailed(&text);
+            proptest::prop_assert_eq!(detailed.normalized, to.iter().collect::<String>());
+            for change in detailed.changes {
+                proptest::prop_assert_eq!(from[change.position].to_string(), change.original);
+            }
+        }
+    }
 }
@@ -0,0 +1,487 @@
+//! A trainable u/v model: a decision list over the letters around each u
+//! and v, counted on clean text.
+//!
+//! The rules of [`uv`](crate::uv) decide most u and v from lexical
+//! patterns (after *q*, perfect endings, exception words) and the rest by
+//! position alone. A model trained on a corpus spelled with both u and v
+//! can take over those positional guesses:
+//! [`UvNormalizer::model`](crate::uv::UvNormalizer::model) asks it
+//! about the u and v the rules would otherwise leave to a default, and
+//! keeps the rule's answer when the model is not confident.
+//!
+//! ```
+//! use latincy_preprocess::uv::UvNormalizer;
+//! use latincy_preprocess::uv_model::UvModel;
+//! use std::sync::Arc;
+//!
+//! // After a consonant and before a vowel the rules write v
+//! assert_eq!(UvNormalizer::new().normalize_text("genua"), "genva");
+//! let model = UvModel::train(["genua genuum genuamus genuent genuare"]);
+//! let normalizer = UvNormalizer::new().model(Arc::new(model));
+//! assert_eq!(normalizer.normalize_text("genua"), "genua");
+//! ```
+//!
+//! # File format
+//!
+//! [`UvModel::save`] writes a JSON object:
+//!
+//! ```json
+//! {
+//!   "format": "latincy-uv-model",
+//!   "version": 1,
+//!   "min_count": 3,
+//!   "min_confidence": 0.9,
+//!   "occurrences": 1520,
+//!   "features": {"er_e": [3, 41], "#_i": [12, 230]}
+//! }
+//! ```
+//!
+//! Each feature is a window of letters around a u or v, with `_` in its
+//! place and `#` for the word boundary: `er_e` is a u or v after *er* and
+//! before *e*. Its counts are how often the corpus has u and v there. The
+//! letters are lowercase, with v written u and j written i, so a model
+//! reads raw texts the way it read the corpus. `occurrences` is the number
+//! of u and v counted, and the other keys are the ones [`UvModel::predict`]
+//! and the normalizer use.
+
+use serde_json::{json, Value};
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+#[cfg(feature = "pyo3-backend")]
+use std::path::PathBuf;
+#[cfg(feature = "pyo3-backend")]
+use std::sync::Arc;
+
+/// The `format` of a model file.
+pub const MODEL_FORMAT: &str = "latincy-uv-model";
+/// The `version` of the model files this crate writes and reads.
+pub const FORMAT_VERSION: u64 = 1;
+/// Features seen fewer times than this are not used, by default.
+pub const DEFAULT_MIN_COUNT: u64 = 3;
+/// The confidence a prediction needs to override a rule, by default.
+pub const DEFAULT_MIN_CONFIDENCE: f64 = 0.9;
+
+/// The letters on either side of a u or v that make up a feature, as
+/// (left, right) window sizes.
+const WINDOWS: &[(usize, usize)] = &[
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (1, 0),
+    (1, 1),
+    (1, 2),
+    (1, 3),
+    (2, 0),
+    (2, 1),
+    (2, 2),
+    (3, 0),
+    (3, 1),
+    (4, 0),
+    (0, 4),
+];
+
+/// The counts of u and v of each feature, from [`UvModel::train`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct UvModel {
+    /// `[u, v]` for each feature.
+    counts: HashMap<String, [u64; 2]>,
+    occurrences: u64,
+    min_count: u64,
+    min_confidence: f64,
+}
+
+impl Default for UvModel {
+    fn default() -> Self {
+        UvModel {
+            counts: HashMap::new(),
+            occurrences: 0,
+            min_count: DEFAULT_MIN_COUNT,
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+        }
+    }
+}
+
+/// What a model reads a u or v as, from [`UvModel::predict`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prediction {
+    /// `'u'` or `'v'`.
+    pub letter: char,
+    /// The smoothed share of the feature's occurrences that are `letter`.
+    pub confidence: f64,
+    /// The feature that decided, as in the model file.
+    pub feature: String,
+    /// How often the corpus has the feature.
+    pub count: u64,
+}
+
+/// `c` as features spell it.
+fn fold(c: char) -> char {
+    match c {
+        'v' => 'u',
+        'j' => 'i',
+        c => c,
+    }
+}
+
+/// `c` in lowercase, one char for one, so indices carry over.
+fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// The features of the u or v at `idx` of `word`, which is lowercase.
+fn features(word: &[char], idx: usize) -> Vec<String> {
+    let folded = word.iter().map(|&c| fold(c));
+    let padded: Vec<char> = std::iter::once('#').chain(folded).chain(std::iter::once('#')).collect();
+    let at = idx + 1;
+    let mut features: Vec<String> = Vec::with_capacity(WINDOWS.len());
+    for &(left, right) in WINDOWS {
+        let mut feature: String = padded[at.saturating_sub(left)..at].iter().collect();
+        feature.push('_');
+        feature.extend(&padded[at + 1..(at + 1 + right).min(padded.len())]);
+        // Windows cut short by the word's ends repeat shorter ones
+        if !features.contains(&feature) {
+            features.push(feature);
+        }
+    }
+    features
+}
+
+/// The lowercase word of `chars` around `idx`, a run of letters, and the
+/// index of `idx` in it.
+fn word_at(chars: &[char], idx: usize) -> (Vec<char>, usize) {
+    let start = idx - chars[..idx].iter().rev().take_while(|c| c.is_alphabetic()).count();
+    let end = idx + chars[idx..].iter().take_while(|c| c.is_alphabetic()).count();
+    let word = chars[start..end.max(idx + 1)].iter().map(|&c| lower(c)).collect();
+    (word, idx - start)
+}
+
+impl UvModel {
+    /// A model of the u and v of `corpus`, a clean text or texts that
+    /// spell consonantal u as v.
+    pub fn train<I, S>(corpus: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut model = UvModel::default();
+        for text in corpus {
+            model.add_text(text.as_ref());
+        }
+        model
+    }
+
+    /// Count the u and v of `text`. Words written in capitals are skipped,
+    /// as inscriptional capitals write V for both.
+    pub fn add_text(&mut self, text: &str) {
+        let chars: Vec<char> = text.chars().collect();
+        for word in chars.split(|c| !c.is_alphabetic()) {
+            if word.len() > 1 && word.iter().all(|&c| crate::case::is_capital(c)) {
+                continue;
+            }
+            let lower: Vec<char> = word.iter().map(|&c| lower(c)).collect();
+            for (i, &c) in lower.iter().enumerate() {
+                if c == 'u' || c == 'v' {
+                    for feature in features(&lower, i) {
+                        self.counts.entry(feature).or_default()[usize::from(c == 'v')] += 1;
+                    }
+                    self.occurrences += 1;
+                }
+            }
+        }
+    }
+
+    /// Add the counts of `other` to this model's.
+    pub fn merge(&mut self, other: UvModel) {
+        for (feature, [u, v]) in other.counts {
+            let counts = self.counts.entry(feature).or_default();
+            counts[0] += u;
+            counts[1] += v;
+        }
+        self.occurrences += other.occurrences;
+    }
+
+    /// Use only features seen at least `count` times.
+    pub fn min_count(mut self, count: u64) -> Self {
+        self.min_count = count;
+        self
+    }
+
+    /// Let predictions override a rule only with at least this confidence.
+    pub fn min_confidence(mut self, confidence: f64) -> Self {
+        self.min_confidence = confidence;
+        self
+    }
+
+    pub fn min_count_value(&self) -> u64 {
+        self.min_count
+    }
+
+    pub fn min_confidence_value(&self) -> f64 {
+        self.min_confidence
+    }
+
+    /// How many u and v were counted.
+    pub fn occurrences(&self) -> u64 {
+        self.occurrences
+    }
+
+    /// How many features the model has.
+    pub fn feature_count(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// What the model reads the u or v at char `idx` of `word` as: the
+    /// answer of the most confident of its features seen often enough,
+    /// the longest on a tie. `None` if it has none, or char `idx` is not a
+    /// u or v.
+    pub fn predict(&self, word: &str, idx: usize) -> Option<Prediction> {
+        let chars: Vec<char> = word.chars().collect();
+        if !matches!(chars.get(idx), Some('u' | 'v' | 'U' | 'V')) {
+            return None;
+        }
+        self.predict_at(&chars, idx)
+    }
+
+    /// [`predict`](Self::predict) for the u or v at `idx` of `chars`,
+    /// in the word of letters around it.
+    pub(crate) fn predict_at(&self, chars: &[char], idx: usize) -> Option<Prediction> {
+        let (word, idx) = word_at(chars, idx);
+        let mut best: Option<Prediction> = None;
+        for feature in features(&word, idx) {
+            let Some(&[u, v]) = self.counts.get(&feature) else {
+                continue;
+            };
+            let count = u + v;
+            if count < self.min_count {
+                continue;
+            }
+            // Add-half smoothing, so a handful of occurrences is not certainty
+            let share = (v as f64 + 0.5) / (count as f64 + 1.0);
+            let (letter, confidence) = if share > 0.5 { ('v', share) } else { ('u', 1.0 - share) };
+            let better = best.as_ref().is_none_or(|best| {
+                (confidence, feature.chars().count()) > (best.confidence, best.feature.chars().count())
+            });
+            if better {
+                best = Some(Prediction { letter, confidence, feature, count });
+            }
+        }
+        best
+    }
+
+    /// The prediction for the u or v at `idx` of `chars`, lowercase, if it
+    /// is confident enough to override a rule.
+    pub(crate) fn decide(&self, chars: &[char], idx: usize) -> Option<char> {
+        self.predict_at(chars, idx)
+            .filter(|prediction| prediction.confidence >= self.min_confidence)
+            .map(|prediction| prediction.letter)
+    }
+
+    /// The model in its file format (see the [module docs](self)), with the
+    /// features sorted.
+    pub fn to_json(&self) -> String {
+        let mut features: Vec<(&String, &[u64; 2])> = self.counts.iter().collect();
+        features.sort_unstable();
+        let features: serde_json::Map<String, Value> =
+            features.into_iter().map(|(feature, counts)| (feature.clone(), json!(counts))).collect();
+        json!({
+            "format": MODEL_FORMAT,
+            "version": FORMAT_VERSION,
+            "min_count": self.min_count,
+            "min_confidence": self.min_confidence,
+            "occurrences": self.occurrences,
+            "features": features,
+        })
+        .to_string()
+    }
+
+    /// A model from its file format. A file of another format or version is
+    /// an [`io::ErrorKind::InvalidData`] error.
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, format!("invalid u/v model: {}", msg));
+        let value: Value = serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+        if value["format"] != MODEL_FORMAT {
+            return Err(invalid(format!("format is not {:?}", MODEL_FORMAT)));
+        }
+        if value["version"] != FORMAT_VERSION {
+            return Err(invalid(format!("unsupported version {}", value["version"])));
+        }
+        let number = |key: &str| {
+            value[key].as_u64().ok_or_else(|| invalid(format!("{} must be a non-negative integer", key)))
+        };
+        let min_confidence =
+            value["min_confidence"].as_f64().ok_or_else(|| invalid("min_confidence must be a number".to_string()))?;
+        let features = value["features"].as_object().ok_or_else(|| invalid("features must be an object".to_string()))?;
+        let mut counts = HashMap::with_capacity(features.len());
+        for (feature, pair) in features {
+            let pair: Option<Vec<u64>> = pair.as_array().and_then(|pair| pair.iter().map(Value::as_u64).collect());
+            match pair.as_deref() {
+                Some(&[u, v]) => counts.insert(feature.clone(), [u, v]),
+                _ => return Err(invalid(format!("feature {:?} must be a pair of counts", feature))),
+            };
+        }
+        Ok(UvModel {
+            counts,
+            occurrences: number("occurrences")?,
+            min_count: number("min_count")?,
+            min_confidence,
+        })
+    }
+
+    /// Write the model to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_json()).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+    }
+
+    /// The model saved at `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let located = |e: io::Error| io::Error::new(e.kind(), format!("{}: {}", path.display(), e));
+        Self::from_json(&std::fs::read_to_string(path).map_err(located)?).map_err(located)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+/// Python view of [`UvModel`], exposed as `_rust.UvModel`; pass it to
+/// `UvNormalizer(model=...)`.
+#[cfg(feature = "pyo3-backend")]
+#[pyclass(name = "UvModel", frozen)]
+pub struct PyUvModel {
+    pub(crate) inner: Arc<UvModel>,
+}
+
+#[cfg(feature = "pyo3-backend")]
+#[pymethods]
+impl PyUvModel {
+    #[staticmethod]
+    #[pyo3(signature = (corpus, min_count=DEFAULT_MIN_COUNT, min_confidence=DEFAULT_MIN_CONFIDENCE))]
+    fn train(py: Python<'_>, corpus: Vec<String>, min_count: u64, min_confidence: f64) -> Self {
+        let model = py.allow_threads(|| UvModel::train(&corpus));
+        PyUvModel {
+            inner: Arc::new(model.min_count(min_count).min_confidence(min_confidence)),
+        }
+    }
+
+    #[staticmethod]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        Ok(PyUvModel {
+            inner: Arc::new(UvModel::load(path)?),
+        })
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        Ok(PyUvModel {
+            inner: Arc::new(UvModel::from_json(json)?),
+        })
+    }
+
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.inner.save(path)?)
+    }
+
+    fn to_json(&self) -> String {
+        self.inner.to_json()
+    }
+
+    /// `(letter, confidence, feature)` for the u or v at `idx` of `word`,
+    /// or `None`.
+    fn predict(&self, word: &str, idx: usize) -> Option<(char, f64, String)> {
+        let prediction = self.inner.predict(word, idx)?;
+        Some((prediction.letter, prediction.confidence, prediction.feature))
+    }
+
+    #[getter]
+    fn occurrences(&self) -> u64 {
+        self.inner.occurrences()
+    }
+
+    #[getter]
+    fn feature_count(&self) -> usize {
+        self.inner.feature_count()
+    }
+
+    #[getter]
+    fn min_count(&self) -> u64 {
+        self.inner.min_count_value()
+    }
+
+    #[getter]
+    fn min_confidence(&self) -> f64 {
+        self.inner.min_confidence_value()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "UvModel(occurrences={}, features={}, min_count={}, min_confidence={})",
+            self.inner.occurrences(),
+            self.inner.feature_count(),
+            self.inner.min_count_value(),
+            self.inner.min_confidence_value()
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_features() {
+        let word: Vec<char> = "servus".chars().collect();
+        let features = features(&word, 3);
+        assert!(features.contains(&"r_u".to_string()));
+        assert!(features.contains(&"er_us".to_string()));
+        // The window stops at the word boundary
+        assert!(features.contains(&"#ser_".to_string()));
+        assert!(features.contains(&"_us#".to_string()));
+        assert_eq!(features.iter().filter(|f| *f == "_us#").count(), 1);
+    }
+
+    #[test]
+    fn test_train_and_predict() {
+        let model = UvModel::train(["Servus servum servo", "tuus tuum tuo", "VIVS SERVVS"]);
+        assert_eq!(model.occurrences(), 10);
+        let prediction = model.predict("seruos", 3).unwrap();
+        assert_eq!(prediction.letter, 'v');
+        assert_eq!((prediction.count, prediction.feature.as_str()), (3, "#ser_"));
+        assert!((prediction.confidence - 3.5 / 4.0).abs() < 1e-12);
+        assert_eq!(model.predict("tuis", 1).unwrap().letter, 'u');
+        assert_eq!(model.predict("tuis", 0), None);
+        assert_eq!(model.predict("abdo", 1), None);
+        assert_eq!(model.clone().min_count(4).predict("seruos", 3), None);
+        // Confident enough to decide, at 3.5 / 4, only below the default
+        let chars: Vec<char> = "seruos".chars().collect();
+        assert_eq!(model.decide(&chars, 3), None);
+        assert_eq!(model.clone().min_confidence(0.8).decide(&chars, 3), Some('v'));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut model = UvModel::train(["servus"]).min_count(1);
+        model.merge(UvModel::train(["tuus"]));
+        assert_eq!(model.occurrences(), 4);
+        let path = std::env::temp_dir().join(format!("latincy_uv_model_{}.json", std::process::id()));
+        model.save(&path).unwrap();
+        assert_eq!(UvModel::load(&path).unwrap(), model);
+        std::fs::remove_file(&path).unwrap();
+
+        let json: Value = serde_json::from_str(&model.to_json()).unwrap();
+        assert_eq!((json["format"].as_str(), json["version"].as_u64()), (Some(MODEL_FORMAT), Some(1)));
+        assert_eq!(json["features"]["r_u"], json!([0, 1]));
+        let error = UvModel::from_json(r#"{"format": "latincy-uv-model", "version": 2}"#).unwrap_err();
+        assert_eq!(error.to_string(), "invalid u/v model: unsupported version 2");
+        assert_eq!(UvModel::from_json("{}").unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(UvModel::load(path).unwrap_err().to_string().contains("latincy_uv_model_"));
+    }
+}
@@ -0,0 +1,96 @@
+//! WebAssembly bindings, generated with wasm-bindgen.
+//!
+//! Built for `wasm32-unknown-unknown` with the `wasm` feature, the library
+//! is a self-contained module: the n-gram model is embedded, nothing is
+//! read from disk or the environment, and no threads are started.
+//! `wasm-bindgen --target web` then writes the JavaScript wrapper, which
+//! exports the functions here under their `js_name`s. The embedded model
+//! can be swapped for a smaller one pruned with
+//! `cargo run --example convert_ngrams -- --min-count N`, either at build
+//! time (see `build.rs`) or at run time with [`load_ngrams`].
+
+use crate::long_s::LongSNormalizer;
+use crate::ngrams::NgramData;
+use crate::pipeline::{Normalizer, Pipeline};
+use crate::uv::UvNormalizer;
+use std::sync::{Arc, RwLock};
+use wasm_bindgen::prelude::*;
+
+/// The model [`load_ngrams`] loaded, in place of the embedded one.
+static NGRAMS: RwLock<Option<Arc<NgramData>>> = RwLock::new(None);
+
+/// The normalizer of the long-s functions.
+fn long_s() -> LongSNormalizer {
+    match &*NGRAMS.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(ngrams) => LongSNormalizer::builder().ngrams(Arc::clone(ngrams)).build(),
+        None => LongSNormalizer::new(),
+    }
+}
+
+fn detailed_json(normalizer: impl Normalizer + 'static, text: &str) -> String {
+    Pipeline::new().stage(normalizer).normalize_detailed(text).to_json()
+}
+
+/// The crate version.
+#[wasm_bindgen]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+/// `text` with u/v normalized.
+#[wasm_bindgen(js_name = normalizeUv)]
+pub fn normalize_uv(text: &str) -> String {
+    crate::uv::normalize(text).into_owned()
+}
+
+/// The u/v normalization of `text` as JSON, as from `Pipeline.normalize_json`.
+#[wasm_bindgen(js_name = normalizeUvDetailed)]
+pub fn normalize_uv_detailed(text: &str) -> String {
+    detailed_json(UvNormalizer::new(), text)
+}
+
+/// `text` with long s corrected, using the model of [`load_ngrams`] if one
+/// was loaded.
+#[wasm_bindgen(js_name = normalizeLongS)]
+pub fn normalize_long_s(text: &str) -> String {
+    long_s().normalize_text(text)
+}
+
+/// The long-s correction of `text` as JSON, as from `Pipeline.normalize_json`.
+#[wasm_bindgen(js_name = normalizeLongSDetailed)]
+pub fn normalize_long_s_detailed(text: &str) -> String {
+    detailed_json(long_s(), text)
+}
+
+/// Replaces the model of the long-s functions with the binary model (as
+/// written by `NgramData::write_binary`) in `bytes`. Throws, keeping the
+/// current model, if they are not a valid model.
+#[wasm_bindgen(js_name = loadNgrams)]
+pub fn load_ngrams(bytes: &[u8]) -> Result<(), JsError> {
+    let data = NgramData::from_bytes(bytes).map_err(|e| JsError::new(&e.to_string()))?;
+    *NGRAMS.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(data));
+    Ok(())
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(normalize_uv("Arma uirumque cano"), "Arma virumque cano");
+        assert!(normalize_uv_detailed("uir").contains("\"normalized\":\"vir\""));
+        assert_eq!(normalize_long_s("funt"), "sunt");
+        assert!(normalize_long_s_detailed("funt").contains("\"normalized\":\"sunt\""));
+        assert_eq!(version(), env!("CARGO_PKG_VERSION"));
+
+        let bytes = crate::ngrams::bundled().unwrap().pruned(3).to_bytes();
+        assert!(load_ngrams(&bytes).is_ok());
+        assert_eq!(normalize_long_s("funt"), "sunt");
+        *NGRAMS.write().unwrap() = None;
+    }
+}
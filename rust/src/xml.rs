@@ -0,0 +1,619 @@
+//! Normalization of TEI and other XML documents, leaving the markup alone.
+//!
+//! [`XmlNormalizer`] runs a [`Normalizer`] over the text nodes of a
+//! document, and optionally over selected attribute values, and writes the
+//! document back byte for byte except for the normalized characters: tags,
+//! comments, processing instructions, the DOCTYPE, CDATA sections, and
+//! character references in unchanged text are all kept as written.
+//!
+//! ```
+//! use latincy_preprocess::uv::UvNormalizer;
+//! use latincy_preprocess::xml::XmlNormalizer;
+//!
+//! let tei = r#"<TEI><text><l n="1">Arma <hi rend="it">uirumque</hi> cano</l></text></TEI>"#;
+//! let normalized = XmlNormalizer::new(&UvNormalizer::new()).normalize(tei).unwrap();
+//! assert_eq!(normalized, r#"<TEI><text><l n="1">Arma <hi rend="it">virumque</hi> cano</l></text></TEI>"#);
+//! ```
+//!
+//! Each text node is normalized on its own, so a word split by markup
+//! (`uir<lb break="no"/>tus`) is seen as two fragments. Elements listed
+//! with [`XmlNormalizer::skip`] (by default the `teiHeader`) are left as
+//! they are, and so, unless [`XmlNormalizer::latin_only`] is turned off, is
+//! text whose nearest `xml:lang` is not Latin (`la`, `lat`, or a subtag of
+//! either).
+//!
+//! This is a scanner, not a validating parser: it checks that tags nest and
+//! that markup is terminated, and reports the line of the first problem,
+//! but does not resolve DTDs. Named entities other than the five XML ones
+//! split the text around them and are kept as they are.
+
+#[cfg(feature = "pyo3-backend")]
+use pyo3::prelude::*;
+
+use crate::patch::Patch;
+use crate::pipeline::Normalizer;
+use std::io;
+use std::ops::Range;
+
+/// Elements [`XmlNormalizer::new`] skips.
+pub const DEFAULT_SKIP: &[&str] = &["teiHeader"];
+
+/// Runs a normalizer over the text of an XML document.
+pub struct XmlNormalizer<'a> {
+    normalizer: &'a dyn Normalizer,
+    attributes: Vec<String>,
+    skip: Vec<String>,
+    latin_only: bool,
+}
+
+/// An open element.
+struct Frame {
+    name: String,
+    /// Whether the element or an ancestor is skipped.
+    skipped: bool,
+    /// Whether the element's nearest `xml:lang`, if any, is Latin.
+    latin: bool,
+}
+
+impl<'a> XmlNormalizer<'a> {
+    /// Normalize text nodes with `normalizer`, skipping the [`DEFAULT_SKIP`]
+    /// elements and non-Latin text, and no attributes.
+    pub fn new(normalizer: &'a dyn Normalizer) -> Self {
+        XmlNormalizer {
+            normalizer,
+            attributes: Vec::new(),
+            skip: DEFAULT_SKIP.iter().map(|name| name.to_string()).collect(),
+            latin_only: true,
+        }
+    }
+
+    /// Also normalize the values of attributes called `name` (e.g.
+    /// `"lemma"`).
+    pub fn attribute(mut self, name: &str) -> Self {
+        self.attributes.push(name.to_string());
+        self
+    }
+
+    /// Skip elements called `name`, with everything in them, in place of
+    /// the [`DEFAULT_SKIP`] list. Call once per element; with no calls the
+    /// default applies.
+    pub fn skip(mut self, name: &str) -> Self {
+        if self.skip.iter().map(String::as_str).eq(DEFAULT_SKIP.iter().copied()) {
+            self.skip.clear();
+        }
+        self.skip.push(name.to_string());
+        self
+    }
+
+    /// Skip nothing, not even the [`DEFAULT_SKIP`] elements.
+    pub fn skip_none(mut self) -> Self {
+        self.skip.clear();
+        self
+    }
+
+    /// Whether to leave text whose `xml:lang` is not Latin alone (default
+    /// true).
+    pub fn latin_only(mut self, latin_only: bool) -> Self {
+        self.latin_only = latin_only;
+        self
+    }
+
+    /// `xml` with its text normalized. Fails on markup that is not
+    /// terminated or tags that do not nest.
+    pub fn normalize(&self, xml: &str) -> io::Result<String> {
+        let mut out = String::with_capacity(xml.len());
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut pos = 0;
+        while pos < xml.len() {
+            let Some(lt) = xml[pos..].find('<').map(|i| pos + i) else {
+                self.text(&xml[pos..], &stack, &mut out);
+                break;
+            };
+            self.text(&xml[pos..lt], &stack, &mut out);
+            let rest = &xml[lt..];
+            let end = if rest.starts_with("<!--") {
+                find_end(xml, lt, "-->")?
+            } else if rest.starts_with("<![CDATA[") {
+                find_end(xml, lt, "]]>")?
+            } else if rest.starts_with("<?") {
+                find_end(xml, lt, "?>")?
+            } else if rest.starts_with("<!") {
+                declaration_end(xml, lt)?
+            } else if rest.starts_with("</") {
+                let end = tag_end(xml, lt)?;
+                let name = xml[lt + 2..end - 1].trim();
+                match stack.pop() {
+                    Some(frame) if frame.name == name => {}
+                    Some(frame) => {
+                        return Err(error(xml, lt, &format!("</{name}> closes <{}>", frame.name)));
+                    }
+                    None => return Err(error(xml, lt, &format!("</{name}> closes no element"))),
+                }
+                end
+            } else {
+                let end = tag_end(xml, lt)?;
+                let self_closing = xml[..end - 1].ends_with('/');
+                let frame = self.start_tag(xml, lt, end, self_closing, &stack, &mut out)?;
+                if !self_closing {
+                    stack.push(frame);
+                }
+                pos = end;
+                continue;
+            };
+            out.push_str(&xml[lt..end]);
+            pos = end;
+        }
+        if let Some(frame) = stack.last() {
+            return Err(error(xml, xml.len(), &format!("<{}> is not closed", frame.name)));
+        }
+        Ok(out)
+    }
+
+    fn active(&self, stack: &[Frame]) -> bool {
+        stack.last().is_none_or(|frame| !frame.skipped && (frame.latin || !self.latin_only))
+    }
+
+    /// Write the text node `raw`, normalized if its element is active.
+    fn text(&self, raw: &str, stack: &[Frame], out: &mut String) {
+        if !self.active(stack) || raw.trim().is_empty() {
+            out.push_str(raw);
+        } else {
+            self.normalize_raw(raw, None, out);
+        }
+    }
+
+    /// Write the start tag `xml[lt..end]`, with its selected attribute
+    /// values normalized, and return its frame.
+    fn start_tag(
+        &self,
+        xml: &str,
+        lt: usize,
+        end: usize,
+        self_closing: bool,
+        stack: &[Frame],
+        out: &mut String,
+    ) -> io::Result<Frame> {
+        let inner_end = if self_closing { end - 2 } else { end - 1 };
+        let tag = &xml[lt + 1..inner_end];
+        let name_len = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+        let name = &tag[..name_len];
+        if name.is_empty() {
+            return Err(error(xml, lt, "a tag without a name"));
+        }
+        let attributes =
+            attributes(tag, name_len).ok_or_else(|| error(xml, lt, &format!("bad attributes in <{name}>")))?;
+        let parent = stack.last();
+        let lang = attributes.iter().find(|(key, _)| &tag[key.clone()] == "xml:lang");
+        let frame = Frame {
+            name: name.to_string(),
+            skipped: parent.is_some_and(|frame| frame.skipped) || self.skip.iter().any(|skip| skip == name),
+            latin: match lang {
+                Some((_, value)) => is_latin(&tag[value.clone()]),
+                None => parent.is_none_or(|frame| frame.latin),
+            },
+        };
+        let active = !frame.skipped && (frame.latin || !self.latin_only);
+        let mut copied = lt + 1;
+        for (key, value) in &attributes {
+            if !active || !self.attributes.iter().any(|attribute| attribute == &tag[key.clone()]) {
+                continue;
+            }
+            let quote = tag[..value.start].chars().next_back();
+            out.push_str(&xml[copied - 1..lt + 1 + value.start]);
+            self.normalize_raw(&tag[value.clone()], quote, out);
+            copied = lt + 2 + value.end;
+        }
+        out.push_str(&xml[copied - 1..end]);
+        Ok(frame)
+    }
+
+    /// Write `raw` (text or an attribute value quoted with `quote`)
+    /// normalized: characters the normalizer leaves alone are copied as
+    /// written, including character references, and replacements are
+    /// escaped.
+    fn normalize_raw(&self, raw: &str, quote: Option<char>, out: &mut String) {
+        for (chunk, opaque) in chunks(raw) {
+            if opaque {
+                out.push_str(chunk);
+                continue;
+            }
+            let (decoded, sources) = decode(chunk);
+            let output = self.normalizer.normalize(&decoded);
+            if output.normalized == decoded {
+                out.push_str(chunk);
+                continue;
+            }
+            let mut pos = 0;
+            for edit in Patch::from_output(&output).edits {
+                sources[pos..edit.start].iter().for_each(|source| out.push_str(&chunk[source.clone()]));
+                escape(&edit.replacement, quote, out);
+                pos = edit.end;
+            }
+            sources[pos..].iter().for_each(|source| out.push_str(&chunk[source.clone()]));
+        }
+    }
+}
+
+/// Whether an `xml:lang` value names Latin.
+fn is_latin(lang: &str) -> bool {
+    let primary = lang.split('-').next().unwrap_or("");
+    primary.eq_ignore_ascii_case("la") || primary.eq_ignore_ascii_case("lat")
+}
+
+fn error(xml: &str, pos: usize, message: &str) -> io::Error {
+    let line = xml[..pos].matches('\n').count() + 1;
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {line}: {message}"))
+}
+
+/// The end of the markup starting at `start` and closed by `close`.
+fn find_end(xml: &str, start: usize, close: &str) -> io::Result<usize> {
+    xml[start + 2..]
+        .find(close)
+        .map(|i| start + 2 + i + close.len())
+        .ok_or_else(|| error(xml, start, &format!("markup not closed by {close}")))
+}
+
+/// The end of the tag starting at `start`: the first `>` outside quotes.
+fn tag_end(xml: &str, start: usize) -> io::Result<usize> {
+    let mut quote = None;
+    for (i, c) in xml[start + 1..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return Ok(start + 1 + i + 1),
+            (None, '<') => break,
+            _ => {}
+        }
+    }
+    Err(error(xml, start, "tag not closed by >"))
+}
+
+/// The end of a `<!DOCTYPE …>` or other declaration, which may hold an
+/// internal subset in brackets.
+fn declaration_end(xml: &str, start: usize) -> io::Result<usize> {
+    let mut quote = None;
+    let mut depth = 0;
+    for (i, c) in xml[start + 2..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '[') => depth += 1,
+            (None, ']') => depth -= 1,
+            (None, '>') if depth <= 0 => return Ok(start + 2 + i + 1),
+            _ => {}
+        }
+    }
+    Err(error(xml, start, "declaration not closed by >"))
+}
+
+/// The name and value ranges (within `tag`) of the attributes after the
+/// element name, or `None` if they are malformed.
+fn attributes(tag: &str, from: usize) -> Option<Vec<(Range<usize>, Range<usize>)>> {
+    let bytes = tag.as_bytes();
+    let skip_space = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        i
+    };
+    let mut attributes = Vec::new();
+    let mut i = skip_space(from);
+    while i < bytes.len() {
+        let key_start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'=' {
+            i += 1;
+        }
+        let key = key_start..i;
+        i = skip_space(i);
+        if bytes.get(i) != Some(&b'=') || key.is_empty() {
+            return None;
+        }
+        i = skip_space(i + 1);
+        let quote = *bytes.get(i).filter(|&&b| b == b'"' || b == b'\'')?;
+        let value_start = i + 1;
+        let value_end = value_start + tag[value_start..].find(quote as char)?;
+        attributes.push((key, value_start..value_end));
+        i = skip_space(value_end + 1);
+    }
+    Some(attributes)
+}
+
+/// The five entities XML predefines.
+const ENTITIES: &[(&str, char)] = &[("amp", '&'), ("lt", '<'), ("gt", '>'), ("quot", '"'), ("apos", '\'')];
+
+/// The reference starting `raw`, as `(length, character)`; `None` for a
+/// named entity XML does not predefine, or no reference at all.
+fn reference(raw: &str) -> Option<(usize, char)> {
+    let end = raw.strip_prefix('&')?.find(';')? + 1;
+    let name = &raw[1..end];
+    let c = if let Some(number) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+        char::from_u32(u32::from_str_radix(number, 16).ok()?)?
+    } else if let Some(number) = name.strip_prefix('#') {
+        char::from_u32(number.parse().ok()?)?
+    } else {
+        ENTITIES.iter().find(|(entity, _)| *entity == name)?.1
+    };
+    Some((end + 1, c))
+}
+
+/// `raw` split around the references [`reference`] cannot decode, each
+/// piece marked with whether it is such a reference.
+fn chunks(raw: &str) -> Vec<(&str, bool)> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    for (i, _) in raw.match_indices('&') {
+        if i < start || reference(&raw[i..]).is_some() {
+            continue;
+        }
+        let end = raw[i..].find(';').map_or(i + 1, |e| i + e + 1);
+        if start < i {
+            chunks.push((&raw[start..i], false));
+        }
+        chunks.push((&raw[i..end], true));
+        start = end;
+    }
+    if start < raw.len() {
+        chunks.push((&raw[start..], false));
+    }
+    chunks
+}
+
+/// `raw` with its references decoded, and the byte range in `raw` of each
+/// decoded character.
+fn decode(raw: &str) -> (String, Vec<Range<usize>>) {
+    let mut decoded = String::with_capacity(raw.len());
+    let mut sources = Vec::new();
+    let mut chars = raw.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (c == '&').then(|| reference(&raw[i..])).flatten() {
+            Some((len, c)) => {
+                decoded.push(c);
+                sources.push(i..i + len);
+                chars.nth(len - 2);
+            }
+            _ => {
+                decoded.push(c);
+                sources.push(i..i + c.len_utf8());
+            }
+        }
+    }
+    (decoded, sources)
+}
+
+/// Write `text` with `&`, `<`, and `quote` escaped.
+fn escape(text: &str, quote: Option<char>, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '"' if quote == Some('"') => out.push_str("&quot;"),
+            '\'' if quote == Some('\'') => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// The `<choice>` children that give the text as it stands, and those that
+/// give it corrected.
+const ORIGINALS: &[&str] = &["orig", "sic", "abbr"];
+const CORRECTIONS: &[&str] = &["reg", "corr", "expan"];
+
+/// An open element of [`choice_texts`], with where its text goes.
+struct Reading {
+    name: String,
+    original: bool,
+    corrected: bool,
+    /// Whether the element's own text is kept; not that of a `<choice>`,
+    /// which is only the space between its children.
+    text: bool,
+}
+
+/// The text of a TEI document twice over: as it stands, with the `<orig>`,
+/// `<sic>`, or `<abbr>` of each `<choice>`, and corrected, with its `<reg>`,
+/// `<corr>`, or `<expan>`. Other text goes in both, with references
+/// decoded, CDATA sections as they are, and the [`DEFAULT_SKIP`] elements,
+/// comments, and tags left out.
+pub(crate) fn choice_texts(xml: &str) -> io::Result<(String, String)> {
+    let (mut original, mut corrected) = (String::new(), String::new());
+    let mut stack: Vec<Reading> = Vec::new();
+    let mut push = |stack: &[Reading], text: &str, cdata: bool| {
+        let (to_original, to_corrected) = match stack.last() {
+            Some(reading) if !reading.text => return,
+            Some(reading) => (reading.original, reading.corrected),
+            None => (true, true),
+        };
+        let text: String = if cdata {
+            text.to_string()
+        } else {
+            chunks(text)
+                .into_iter()
+                .map(|(chunk, opaque)| if opaque { chunk.to_string() } else { decode(chunk).0 })
+                .collect()
+        };
+        if to_original {
+            original.push_str(&text);
+        }
+        if to_corrected {
+            corrected.push_str(&text);
+        }
+    };
+    let mut pos = 0;
+    while pos < xml.len() {
+        let Some(lt) = xml[pos..].find('<').map(|i| pos + i) else {
+            push(&stack, &xml[pos..], false);
+            break;
+        };
+        push(&stack, &xml[pos..lt], false);
+        let rest = &xml[lt..];
+        pos = if rest.starts_with("<!--") {
+            find_end(xml, lt, "-->")?
+        } else if rest.starts_with("<![CDATA[") {
+            let end = find_end(xml, lt, "]]>")?;
+            push(&stack, &xml[lt + 9..end - 3], true);
+            end
+        } else if rest.starts_with("<?") {
+            find_end(xml, lt, "?>")?
+        } else if rest.starts_with("<!") {
+            declaration_end(xml, lt)?
+        } else if rest.starts_with("</") {
+            let end = tag_end(xml, lt)?;
+            let name = xml[lt + 2..end - 1].trim();
+            match stack.pop() {
+                Some(reading) if reading.name == name => {}
+                Some(reading) => {
+                    return Err(error(xml, lt, &format!("</{name}> closes <{}>", reading.name)));
+                }
+                None => return Err(error(xml, lt, &format!("</{name}> closes no element"))),
+            }
+            end
+        } else {
+            let end = tag_end(xml, lt)?;
+            let self_closing = xml[..end - 1].ends_with('/');
+            let tag = &xml[lt + 1..if self_closing { end - 2 } else { end - 1 }];
+            let name = &tag[..tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len())];
+            if name.is_empty() {
+                return Err(error(xml, lt, "a tag without a name"));
+            }
+            let (mut original, mut corrected) = stack.last().map_or((true, true), |r| (r.original, r.corrected));
+            if stack.last().is_some_and(|parent| parent.name == "choice") {
+                original &= ORIGINALS.contains(&name);
+                corrected &= CORRECTIONS.contains(&name);
+            }
+            if DEFAULT_SKIP.contains(&name) {
+                (original, corrected) = (false, false);
+            }
+            if !self_closing {
+                stack.push(Reading {
+                    name: name.to_string(),
+                    original,
+                    corrected,
+                    text: name != "choice",
+                });
+            }
+            end
+        };
+    }
+    if let Some(reading) = stack.last() {
+        return Err(error(xml, xml.len(), &format!("<{}> is not closed", reading.name)));
+    }
+    Ok((original, corrected))
+}
+
+// ---------------------------------------------------------------------------
+// PyO3 wrappers
+// ---------------------------------------------------------------------------
+
+/// `xml` with its text normalized by `pipeline`. `attributes` are also
+/// normalized; `skip` replaces the skipped elements (default
+/// `["teiHeader"]`). Malformed markup raises `ValueError`.
+#[cfg(feature = "pyo3-backend")]
+#[pyfunction]
+#[pyo3(name = "normalize_xml", signature = (xml, pipeline, attributes=None, skip=None, latin_only=true))]
+pub fn py_normalize_xml(
+    py: Python<'_>,
+    xml: &str,
+    pipeline: PyRef<'_, crate::pipeline::PyPipeline>,
+    attributes: Option<Vec<String>>,
+    skip: Option<Vec<String>>,
+    latin_only: bool,
+) -> PyResult<String> {
+    let mut normalizer = XmlNormalizer::new(&pipeline.inner).latin_only(latin_only);
+    for attribute in attributes.unwrap_or_default() {
+        normalizer = normalizer.attribute(&attribute);
+    }
+    if let Some(skip) = skip {
+        normalizer = normalizer.skip_none();
+        for element in skip {
+            normalizer.skip.push(element);
+        }
+    }
+    let result = pipeline
+        .allow_threads(py, xml, || normalizer.normalize(xml))
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()));
+    pipeline.checked(result)?
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::long_s::LongSNormalizer;
+    use crate::pipeline::Pipeline;
+    use crate::uv::UvNormalizer;
+
+    #[test]
+    fn test_markup_is_kept() {
+        let tei = concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<!DOCTYPE TEI [ <!ENTITY aelig \"&#230;\"> ]>\n",
+            "<TEI xmlns=\"http://www.tei-c.org/ns/1.0\">\n",
+            "  <teiHeader><title>De uita</title></teiHeader>\n",
+            "  <!-- uita -->\n",
+            "  <text><body><p n='1' rend = \"x>y\">uita <lb/>&amp; uirtus<![CDATA[uita]]></p></body></text>\n",
+            "</TEI>\n",
+        );
+        let normalized = XmlNormalizer::new(&UvNormalizer::new()).normalize(tei).unwrap();
+        assert_eq!(normalized, tei.replace("uita <lb/>&amp; uirtus", "vita <lb/>&amp; virtus"));
+    }
+
+    #[test]
+    fn test_references() {
+        let pipeline = Pipeline::new().stage(LongSNormalizer::new());
+        let xml = "<l>&#383;ed &#x17F;ic &aelig; &lt;ſed&gt;</l>";
+        let normalized = XmlNormalizer::new(&pipeline).normalize(xml).unwrap();
+        assert_eq!(normalized, "<l>sed sic &aelig; &lt;sed&gt;</l>");
+        // Unchanged references are kept as written
+        assert_eq!(XmlNormalizer::new(&UvNormalizer::new()).normalize("<l>&#97;</l>").unwrap(), "<l>&#97;</l>");
+    }
+
+    #[test]
+    fn test_attributes_and_languages() {
+        let xml = concat!(
+            "<text><w lemma=\"uirtus\" n=\"uu\">uirtus</w>",
+            "<note xml:lang=\"en\">uouchers</note><q xml:lang=\"la-x-medieval\">uia</q></text>",
+        );
+        let uv = UvNormalizer::new();
+        let normalizer = XmlNormalizer::new(&uv).attribute("lemma");
+        assert_eq!(
+            normalizer.normalize(xml).unwrap(),
+            concat!(
+                "<text><w lemma=\"virtus\" n=\"uu\">virtus</w>",
+                "<note xml:lang=\"en\">uouchers</note><q xml:lang=\"la-x-medieval\">via</q></text>",
+            )
+        );
+        let all = XmlNormalizer::new(&uv).latin_only(false).skip("w").normalize(xml).unwrap();
+        assert!(all.contains(">uirtus</w>") && all.contains(">vouchers</note>"));
+    }
+
+    #[test]
+    fn test_malformed() {
+        let uv = UvNormalizer::new();
+        let normalizer = XmlNormalizer::new(&uv);
+        let message = |xml: &str| normalizer.normalize(xml).unwrap_err().to_string();
+        assert_eq!(message("<a>\n<b></a>"), "line 2: </a> closes <b>");
+        assert_eq!(message("<a>uia"), "line 1: <a> is not closed");
+        assert_eq!(message("<a><!-- uia</a>"), "line 1: markup not closed by -->");
+        assert_eq!(message("<a x=1>uia</a>"), "line 1: bad attributes in <a>");
+        assert_eq!(message("</a>"), "line 1: </a> closes no element");
+        assert_eq!(normalizer.normalize("uia").unwrap(), "via");
+    }
+
+    #[test]
+    fn test_choice_texts() {
+        let tei = concat!(
+            "<TEI><teiHeader><title>De uita</title></teiHeader><text><body>\n",
+            "<p><choice><orig>uita</orig> <reg>vita</reg></choice> &amp; ",
+            "<choice><sic>uirtns</sic><corr>virtus</corr></choice>",
+            "<choice><abbr>q&#x303;</abbr><expan>que</expan></choice><lb/> est</p>\n",
+            "</body></text></TEI>",
+        );
+        let (original, corrected) = choice_texts(tei).unwrap();
+        assert_eq!(original, "\nuita & uirtnsq\u{0303} est\n");
+        assert_eq!(corrected, "\nvita & virtusque est\n");
+        let error = choice_texts("<p><choice><orig>uia</orig></p>").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: </p> closes <choice>");
+    }
+}
@@ -0,0 +1,180 @@
+//! End-to-end tests of the `latin-preprocess` command: exit codes, file
+//! rewriting, directory walking, and encodings.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// A fresh directory for one test, removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("latin-preprocess-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    /// Write `bytes` to `relative`, creating its directories.
+    fn write(&self, relative: &str, bytes: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.0.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    fn path(&self, relative: &str) -> PathBuf {
+        self.0.join(relative)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn run(args: &[&dyn AsRef<std::ffi::OsStr>], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_latin-preprocess"))
+        .args(args.iter().map(|arg| arg.as_ref()))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn read(path: &Path) -> Vec<u8> {
+    std::fs::read(path).unwrap()
+}
+
+#[test]
+fn test_check_exit_codes() {
+    let dir = TempDir::new("check");
+    let dirty = dir.write("dirty.txt", "uia\n");
+    let clean = dir.write("clean.txt", "via\n");
+
+    let output = run(&[&"uv", &"--check", &dirty], b"");
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("dirty.txt:1:1: \"u\" -> \"v\""), "{}", stdout);
+    assert_eq!(read(&dirty), b"uia\n");
+
+    assert_eq!(run(&[&"uv", &"--check", &"--max-changes", &"1", &dirty], b"").status.code(), Some(0));
+    assert_eq!(run(&[&"uv", &"--check", &clean], b"").status.code(), Some(0));
+}
+
+#[test]
+fn test_in_place() {
+    let dir = TempDir::new("in-place");
+    let plain = dir.write("plain.txt", "uia\n");
+    let output = run(&[&"uv", &"--in-place", &plain], b"");
+    assert!(output.status.success());
+    assert_eq!(read(&plain), b"via\n");
+    assert!(!dir.path("plain.txt.bak").exists());
+
+    let backed_up = dir.write("backed.txt", "uia\n");
+    let output = run(&[&"uv", &"--in-place", &"--backup-suffix", &".bak", &backed_up], b"");
+    assert!(output.status.success());
+    assert_eq!(read(&backed_up), b"via\n");
+    assert_eq!(read(&dir.path("backed.txt.bak")), b"uia\n");
+
+    // An unchanged file is not rewritten, so gets no backup
+    let clean = dir.write("clean.txt", "via\n");
+    assert!(run(&[&"uv", &"--in-place", &"--backup-suffix", &".bak", &clean], b"").status.success());
+    assert!(!dir.path("clean.txt.bak").exists());
+
+    // Nor is one already in the output encoding, but others are converted
+    let latin1 = dir.write("latin1.txt", b"C\xe6sar\n");
+    assert!(run(&[&"uv", &"--in-place", &"--output-encoding", &"latin-1", &latin1], b"").status.success());
+    assert_eq!(read(&latin1), b"C\xe6sar\n");
+    let output = run(&[&"uv", &"--in-place", &"--output-encoding", &"utf-8", &latin1], b"");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(read(&latin1), "Cæsar\n".as_bytes());
+}
+
+#[test]
+fn test_input_dir_globs() {
+    let dir = TempDir::new("input-dir");
+    dir.write("in/a.txt", "uia\n");
+    dir.write("in/sub/b.txt", "uita\n");
+    dir.write("in/sub/skip.txt", "uia\n");
+    dir.write("in/notes.md", "uia\n");
+    let (input, out) = (dir.path("in"), dir.path("out"));
+    let args: [&dyn AsRef<std::ffi::OsStr>; 9] =
+        [&"uv", &"--input-dir", &input, &"--include", &"*.txt", &"--exclude", &"sub/skip*", &"-o", &out];
+    let output = run(&args, b"");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(read(&out.join("a.txt")), b"via\n");
+    assert_eq!(read(&out.join("sub/b.txt")), b"vita\n");
+    assert!(!out.join("sub/skip.txt").exists());
+    assert!(!out.join("notes.md").exists());
+}
+
+#[test]
+fn test_duplicate_output_names() {
+    let dir = TempDir::new("duplicates");
+    let (a, b, out) = (dir.write("a/x.txt", "uia\n"), dir.write("b/x.txt", "uita\n"), dir.path("out"));
+    let output = run(&[&"uv", &a, &b, &"-o", &out], b"");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("would both be written to"));
+    assert!(!out.join("x.txt").exists());
+    // To stdout they are fine
+    let output = run(&[&"uv", &a, &b], b"");
+    assert_eq!(output.stdout, b"via\nvita\n");
+}
+
+#[test]
+fn test_file_encodings() {
+    let dir = TempDir::new("encodings");
+    let out = dir.path("out");
+    // uinum Cæsar, in Latin-1, Windows-1252 (with a curly quote), and
+    // UTF-16LE with a byte order mark
+    let latin1 = dir.write("latin1.txt", b"uinum C\xe6sar\n");
+    let cp1252 = dir.write("cp1252.txt", b"\x93uinum\x94\n");
+    let utf16: Vec<u8> = [0xFF, 0xFE].into_iter().chain("uinum\n".encode_utf16().flat_map(u16::to_le_bytes)).collect();
+    let utf16_path = dir.write("utf16.txt", &utf16);
+    let output = run(&[&"uv", &latin1, &cp1252, &utf16_path, &"-o", &out], b"");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(read(&out.join("latin1.txt")), b"vinum C\xe6sar\n");
+    assert_eq!(read(&out.join("cp1252.txt")), b"\x93vinum\x94\n");
+    let expected: Vec<u8> = [0xFF, 0xFE].into_iter().chain("vinum\n".encode_utf16().flat_map(u16::to_le_bytes)).collect();
+    assert_eq!(read(&out.join("utf16.txt")), expected);
+
+    let output = run(&[&"uv", &latin1, &"--output-encoding", &"utf-8"], b"");
+    assert_eq!(output.stdout, "vinum Cæsar\n".as_bytes());
+}
+
+#[test]
+fn test_stdin_encodings() {
+    let output = run(&[&"uv"], "uinum Cæsar\n".as_bytes());
+    assert!(output.status.success());
+    assert_eq!(output.stdout, "vinum Cæsar\n".as_bytes());
+
+    // Detected as for files, and written back in the same encoding
+    let output = run(&[&"uv"], b"uinum C\xe6sar\n");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(output.stdout, b"vinum C\xe6sar\n");
+    let output = run(&[&"uv", &"--output-encoding", &"utf-8"], b"\x93uinum\x94\n");
+    assert_eq!(output.stdout, "“vinum”\n".as_bytes());
+
+    let output = run(&[&"uv", &"--encoding", &"latin-1"], b"uinum C\xe6sar\n");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(output.stdout, b"vinum C\xe6sar\n");
+
+    // A Latin-1 byte well past the first buffer
+    let mut input = "arma virumque cano\n".repeat(600).into_bytes();
+    input.extend(b"C\xe6sar funt caufa\n");
+    let output = run(&[&"long-s"], &input);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.stdout.starts_with(b"arma virumque cano\n"));
+    assert!(output.stdout.ends_with(b"C\xe6sar sunt caufa\n"));
+
+    // Streamed when declared UTF-8
+    let output = run(&[&"long-s", &"--encoding", &"utf-8"], "Cæsar funt\n".as_bytes());
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(output.stdout, "Cæsar sunt\n".as_bytes());
+}
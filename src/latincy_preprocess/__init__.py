@@ -85,8 +85,8 @@ def normalize(text: str) -> str:
 if _rust is not None:
     from latincy_preprocess.uv import _apply_rust_backend as _apply_uv_rust
     from latincy_preprocess.long_s import _apply_rust_backend as _apply_long_s_rust
-    _apply_uv_rust(_rust)
-    _apply_long_s_rust(_rust)
+    _apply_uv_rust(_rust.uv)
+    _apply_long_s_rust(_rust.long_s)
 
 
 # Lazy import for spaCy components (only when spacy is installed)
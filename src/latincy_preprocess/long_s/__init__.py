@@ -5,53 +5,61 @@ Re-exports the rule engine and applies Rust backend acceleration
 when available.
 """
 
-from latincy_preprocess.long_s._rules import LongSNormalizer, TransformationRule
-
-__all__ = ["LongSNormalizer", "TransformationRule"]
-
-
-# Pass 1 rule descriptions (must match the originals from _define_pass1_rules
-# so that tests checking `any("ft" in r for r in rules)` etc. still pass).
-_PASS1_TRIGRAM_RULES = [
-    ("fqu", "squ", "fqu \u2192 squ (impossible)"),
-    ("fpe", "spe", "fpe \u2192 spe (impossible)"),
-    ("fuf", "sus", "fuf \u2192 sus (0.18% ratio)"),
-    ("fum", "sum", "fum \u2192 sum (0.64% ratio)"),
-]
-_PASS1_BIGRAM_RULES = [
-    ("fp", "sp", "fp \u2192 sp (impossible in Latin)"),
-    ("ft", "st", "ft \u2192 st (impossible in Latin)"),
-    ("fc", "sc", "fc \u2192 sc (impossible in Latin)"),
-]
+from latincy_preprocess.long_s._rules import (
+    DEFAULT_NGRAM_DIR,
+    LEGITIMATE_F_WORDS,
+    LIGATURES,
+    LONG_S,
+    LongSNormalizer,
+    TransformationRule,
+    _apply_rules,
+    _on_core,
+    archaize,
+    decompose_ligatures,
+)
+
+__all__ = ["LongSNormalizer", "TransformationRule", "archaize", "decompose_ligatures"]
 
 
 def _apply_rust_backend(rust_module):
     """Monkey-patch Rust implementations onto LongSNormalizer."""
 
+    python_word_pass1 = LongSNormalizer.normalize_word_pass1
+    default_pass1_rules = LongSNormalizer._define_pass1_rules(None)
+
     def _rust_word_pass1(self, word):
         """Apply Pass 1 using Rust backend, with Python-compatible stats/rules."""
-        result = rust_module.normalize_long_s_word_pass1(word)
+        # The Rust backend only knows the built-in rule table
+        if self.pass1_rules != default_pass1_rules:
+            return python_word_pass1(self, word)
+
+        result = rust_module.pass1(word)
         lower = word.lower()
         applied_rules = []
 
         if result != lower:
             tracking = lower
 
-            for pattern, replacement, description in _PASS1_TRIGRAM_RULES:
-                if pattern in tracking:
-                    tracking = tracking.replace(pattern, replacement)
-                    applied_rules.append(description)
-                    rule_key = f"{pattern} \u2192 {replacement}"
+            for ligature, letters in LIGATURES.items():
+                if ligature in tracking:
+                    tracking = tracking.replace(ligature, letters)
+                    rule_key = f'{ligature} \u2192 {letters}'
+                    applied_rules.append(f'{rule_key} (ligature)')
                     self.stats['transformations'][rule_key] = \
                         self.stats['transformations'].get(rule_key, 0) + 1
 
-            for pattern, replacement, description in _PASS1_BIGRAM_RULES:
-                if pattern in tracking:
-                    tracking = tracking.replace(pattern, replacement)
-                    applied_rules.append(description)
-                    rule_key = f"{pattern} \u2192 {replacement}"
-                    self.stats['transformations'][rule_key] = \
-                        self.stats['transformations'].get(rule_key, 0) + 1
+            if LONG_S in tracking:
+                tracking = tracking.replace(LONG_S, 's')
+                applied_rules.append('\u017f \u2192 s (long-s character)')
+                self.stats['transformations']['\u017f \u2192 s'] = \
+                    self.stats['transformations'].get('\u017f \u2192 s', 0) + 1
+
+            tracking, fired = _apply_rules(tracking, self.pass1_rules)
+            for rule in fired:
+                applied_rules.append(rule.description)
+                rule_key = f"{rule.pattern} \u2192 {rule.replacement}"
+                self.stats['transformations'][rule_key] = \
+                    self.stats['transformations'].get(rule_key, 0) + 1
 
             # Word-final f -> s
             if tracking.endswith('f'):
@@ -62,10 +70,20 @@ def _apply_rust_backend(rust_module):
 
         return result, applied_rules
 
+    python_word_pass2 = LongSNormalizer.normalize_word_pass2
+
     def _rust_word_pass2(self, word, threshold=2.0):
         """Apply Pass 2 using Rust backend, with Python-compatible stats/rules."""
-        result = rust_module.normalize_long_s_word_pass2(word, threshold)
-        lower = word.lower()
+        # The Rust backend only knows the built-in allowlist (which custom
+        # lists can only grow, so a length check is enough), the bundled
+        # n-gram tables, and the default scoring
+        if (self.denylist or len(self.allowlist) != len(LEGITIMATE_F_WORDS)
+                or self.ngram_dir != DEFAULT_NGRAM_DIR
+                or self.scoring != 'logprob'):
+            return python_word_pass2(self, word, threshold)
+
+        result = rust_module.pass2(word, threshold)
+        lower = word.lower().replace(LONG_S, 's')
         applied_rules = []
 
         if result != lower:
@@ -84,5 +102,5 @@ def _apply_rust_backend(rust_module):
 
         return result, applied_rules
 
-    LongSNormalizer.normalize_word_pass1 = _rust_word_pass1
-    LongSNormalizer.normalize_word_pass2 = _rust_word_pass2
+    LongSNormalizer.normalize_word_pass1 = _on_core(_rust_word_pass1)
+    LongSNormalizer.normalize_word_pass2 = _on_core(_rust_word_pass2)
@@ -8,12 +8,173 @@ Multi-pass transformation system:
 Based on n-gram frequency analysis of 842K Latin words.
 """
 
+import functools
 import json
 from pathlib import Path
-from typing import Dict, Tuple, List
+from typing import Dict, Iterable, List, Set, Tuple, Union
 from dataclasses import dataclass
 
 
+LONG_S = '\u017f'
+
+# Typographic ligatures and their letters; the long s + t ligature
+# decomposes straight to 'st'
+LIGATURES = {
+    '\ufb00': 'ff',
+    '\ufb01': 'fi',
+    '\ufb02': 'fl',
+    '\ufb03': 'ffi',
+    '\ufb04': 'ffl',
+    '\ufb05': 'st',
+    '\ufb06': 'st',
+}
+
+
+def decompose_ligatures(text: str) -> str:
+    """Expand typographic ligatures (e.g. '\ufb01' -> 'fi') in ``text``."""
+    for ligature, letters in LIGATURES.items():
+        text = text.replace(ligature, letters)
+    return text
+
+
+# Letters before which historical typesetting keeps round s
+ROUND_S_BEFORE = frozenset('bfk')
+
+
+def archaize(text: str) -> str:
+    """
+    Render modern text with historical long s (the inverse of the
+    long-s -> s pre-pass).
+
+    A lowercase 's' becomes long s inside a word, but stays round at the end
+    of a word and before b, f, or k. Capital S is left alone.
+    """
+    chars = list(text)
+    for i, c in enumerate(chars[:-1]):
+        following = chars[i + 1]
+        if c == 's' and following.isalpha() and following.lower() not in ROUND_S_BEFORE:
+            chars[i] = LONG_S
+    return ''.join(chars)
+
+
+# Known legitimate Latin words starting with 'fu', 'fe', 'fi' (alpha-sorted).
+# Pass 2 skips these to avoid false positives.
+LEGITIMATE_F_WORDS = frozenset({
+    'facere', 'facio', 'facit', 'faciunt', 'feceram', 'fecerant', 'fecerat', 'fecere',
+    'fecerim', 'fecerint', 'fecerit', 'fecerunt', 'feci', 'fecimus', 'fecisse', 'fecissem',
+    'fecissent', 'fecisset', 'fecisti', 'fecistis', 'fecit', 'fecunda', 'fecundam', 'fecundi',
+    'fecundis', 'fecunditas', 'fecunditatem', 'fecundus', 'felice', 'felicem', 'felices', 'felici',
+    'felicibus', 'felicis', 'feliciter', 'felicium', 'felix', 'femina', 'feminae', 'feminam',
+    'feminarum', 'feminas', 'feminis', 'fenestra', 'fenestram', 'fenestras', 'fenestris', 'feram',
+    'ferebam', 'ferebant', 'ferebat', 'ferebatur', 'feremus', 'ferendi', 'ferendo', 'ferendum',
+    'ferens', 'ferent', 'ferentem', 'ferentis', 'feres', 'feret', 'ferimus', 'fero',
+    'ferocem', 'feroces', 'feroci', 'ferocis', 'ferociter', 'ferox', 'ferre', 'ferrem',
+    'ferrent', 'ferret', 'ferri', 'ferro', 'ferrum', 'fers', 'fert', 'fertis',
+    'fertur', 'ferunt', 'feruntur', 'festa', 'festi', 'festis', 'festo', 'festum',
+    'fiant', 'fiat', 'fide', 'fidei', 'fideles', 'fidelibus', 'fidelis', 'fideliter',
+    'fidelium', 'fidem', 'fides', 'fiebant', 'fiebat', 'fierent', 'fieret', 'fieri',
+    'figura', 'figurae', 'figuram', 'figurarum', 'figuras', 'figuris', 'filia', 'filiae',
+    'filiam', 'filiarum', 'filias', 'filii', 'filiis', 'filio', 'filiorum', 'filios',
+    'filium', 'filius', 'finem', 'fines', 'finibus', 'finire', 'finis', 'finit',
+    'finita', 'finitum', 'finitur', 'finium', 'fio', 'firma', 'firmam', 'firmamenti',
+    'firmamento', 'firmamentum', 'firmare', 'firmat', 'firmi', 'firmiter', 'firmum', 'firmus',
+    'fit', 'fiunt', 'forma', 'formae', 'formam', 'formas', 'fuerat', 'fuerint',
+    'fuerit', 'fuerunt', 'fugere', 'fugerunt', 'fugi', 'fugiens', 'fugio', 'fugisse',
+    'fugit', 'fugiunt', 'fuisse', 'fuissem', 'fuissent', 'fuisset', 'fuit', 'fundamenta',
+    'fundamenti', 'fundamento', 'fundamentum', 'furor', 'furore', 'furorem', 'furoris', 'futura',
+    'futuram', 'futuri', 'futuris', 'futurum', 'futurus',
+})
+
+
+# Proper names that Pass 2 leaves alone (see the Rust names module);
+# matched with u for v
+PROPER_NAMES = frozenset(name.replace('v', 'u') for name in (
+    'fabius', 'fabii', 'fabio', 'fabium', 'fabia', 'fabiae', 'fabiam', 'fabianus',
+    'fabricius', 'fabricii', 'fabricio', 'fabricium', 'fulvius', 'fulvii', 'fulvio',
+    'fulvium', 'fulvia', 'fulviae', 'fulviam', 'furius', 'furii', 'furio', 'furium',
+    'furia', 'furiae', 'faustus', 'fausti', 'fausto', 'faustum', 'fausta', 'faustae',
+    'faustina', 'faustinae', 'flaccus', 'flacci', 'flacco', 'flaccum', 'flaminius',
+    'flaminii', 'flaminio', 'flaminium', 'flaminia', 'flaminiae', 'flavius', 'flavii',
+    'flavio', 'flavium', 'flavia', 'flaviae', 'fannius', 'fannii', 'fannio', 'fannium',
+    'fonteius', 'fonteii', 'fonteio', 'fufius', 'fufii', 'frontinus', 'frontini', 'fronto',
+    'frontonis', 'florus', 'flori', 'floro', 'florentia', 'florentiae', 'fidenae',
+    'fidenarum', 'fidenis', 'fidenas', 'falerii', 'faleriorum', 'faleriis', 'falernus',
+    'falerni', 'falerno', 'falernum', 'formiae', 'formiarum', 'formiis', 'fregellae',
+    'fregellarum', 'fregellis', 'faesulae', 'faesularum', 'faesulis', 'frusino',
+    'frusinonis', 'fucinus', 'fucini', 'rufus', 'rufi', 'rufo', 'rufum', 'afranius',
+    'afranii', 'afranio', 'aufidius', 'aufidii', 'aufidio', 'cornificius', 'cornificii',
+    'cornificio',
+))
+
+
+def _load_word_list(source: Union[str, Path, Iterable[str], None]) -> Set[str]:
+    """
+    Load a word list from a file path or an iterable of words (lowercased).
+
+    Files ending in .json must hold a JSON array; other files are read as one
+    word per line, ignoring blank lines and '#' comments.
+    """
+    if source is None:
+        return set()
+    if isinstance(source, (str, Path)):
+        path = Path(source)
+        with open(path, 'r', encoding='utf-8') as f:
+            if path.suffix == '.json':
+                words = json.load(f)
+            else:
+                words = [line.split('#', 1)[0].strip() for line in f]
+        return {w.lower() for w in words if w}
+    return {w.lower() for w in source}
+
+
+def _split_affixes(word: str) -> Tuple[str, str, str]:
+    """
+    Split a token into leading non-letters, its alphabetic core (keeping
+    word-internal apostrophes and hyphens), and trailing non-letters:
+    '(fuper)' -> ('(', 'fuper', ')'). A token with no letters is all prefix.
+    """
+    letters = [i for i, c in enumerate(word) if c.isalpha()]
+    if not letters:
+        return word, '', ''
+    start, end = letters[0], letters[-1] + 1
+    return word[:start], word[start:end], word[end:]
+
+
+def _on_core(method):
+    """Apply a word-level method to the token's alphabetic core only,
+    re-attaching the surrounding punctuation."""
+
+    @functools.wraps(method)
+    def wrapper(self, word, *args, **kwargs):
+        lead, core, trail = _split_affixes(word)
+        if not (lead or trail):
+            return method(self, word, *args, **kwargs)
+        if not core:
+            return word, []
+        normalized, rules = method(self, core, *args, **kwargs)
+        return lead + normalized + trail, rules
+
+    return wrapper
+
+
+def _case_pattern(word: str) -> Tuple[bool, bool]:
+    """
+    Detect (is_upper, is_title) for a word before lowercasing, from its
+    letters only.
+
+    Long-s is treated as caseless, so 'PO\u017fSUM' still counts as all-caps
+    even though '\u017f' is itself a lowercase letter.
+    """
+    word = ''.join(c for c in word if c.isalpha())
+    if LONG_S in word:
+        rest = word.replace(LONG_S, '')
+        is_upper = len(word) > 1 and rest.isupper()
+    else:
+        is_upper = len(word) > 1 and word.isupper()
+    is_title = word[0:1].isupper() and (len(word) == 1 or not is_upper)
+    return is_upper, is_title
+
+
 @dataclass
 class TransformationRule:
     """A character-level transformation rule."""
@@ -24,6 +185,35 @@ class TransformationRule:
     ratio: float  # Frequency ratio from corpus analysis
 
 
+def _apply_rules(word: str, rules: List[TransformationRule]) -> Tuple[str, List[TransformationRule]]:
+    """
+    Apply replacement rules in one left-to-right scan.
+
+    At each position the longest matching pattern wins and the scan resumes
+    after it, so one replacement never feeds another (``fufpe`` -> ``suspe``).
+    Returns the result and the rules that fired, in rule order.
+    """
+    ordered = sorted(rules, key=lambda rule: len(rule.pattern), reverse=True)
+    result = []
+    fired = set()
+    i = 0
+    while i < len(word):
+        for rule in ordered:
+            if rule.pattern and word.startswith(rule.pattern, i):
+                result.append(rule.replacement)
+                fired.add(id(rule))
+                i += len(rule.pattern)
+                break
+        else:
+            result.append(word[i])
+            i += 1
+    return ''.join(result), [rule for rule in rules if id(rule) in fired]
+
+
+# Bundled n-gram tables
+DEFAULT_NGRAM_DIR = Path(__file__).parent / 'data' / 'ngrams'
+
+
 class LongSNormalizer:
     """
     Normalize OCR long-s (ſ → f) artifacts in Latin text.
@@ -31,16 +221,37 @@ class LongSNormalizer:
     Uses transformation-based learning with n-gram frequency validation.
     """
 
-    def __init__(self, ngram_dir: Path = None):
+    def __init__(
+        self,
+        ngram_dir: Path = None,
+        allowlist: Union[str, Path, Iterable[str], None] = None,
+        denylist: Union[str, Path, Iterable[str], None] = None,
+        scoring: str = 'logprob',
+        protect_names: bool = True,
+    ):
         """
         Initialize normalizer with n-gram frequency tables.
 
         Args:
             ngram_dir: Path to directory containing n-gram JSON files.
                       Defaults to bundled package data.
+            allowlist: Extra legitimate f-words that Pass 2 must leave alone,
+                      as a list of words or a path to a word-list file.
+            denylist: f-words that Pass 2 must always convert to s, as a list
+                      of words or a path to a word-list file. Takes precedence
+                      over the allowlist.
+            scoring: How Pass 2 compares f- and s-forms: 'logprob' (add-one
+                      smoothed log probabilities, the default) or 'ratio'
+                      (raw frequency ratio, the behavior before smoothing).
+            protect_names: Skip Pass 2 for capitalized words in
+                      PROPER_NAMES (Fulvius, Furius), unless denylisted.
         """
+        if scoring not in ('logprob', 'ratio'):
+            raise ValueError(f"scoring must be 'logprob' or 'ratio', got {scoring!r}")
+        self.scoring = scoring
+
         if ngram_dir is None:
-            ngram_dir = Path(__file__).parent / 'data' / 'ngrams'
+            ngram_dir = DEFAULT_NGRAM_DIR
 
         self.ngram_dir = Path(ngram_dir)
 
@@ -49,6 +260,11 @@ class LongSNormalizer:
         self.trigrams = self._load_ngrams('trigrams.json')
         self.fourgrams = self._load_ngrams('4grams.json')
 
+        # Pass 2 word lists
+        self.allowlist = set(LEGITIMATE_F_WORDS) | _load_word_list(allowlist)
+        self.denylist = _load_word_list(denylist)
+        self.protect_names = protect_names
+
         # Define transformation rules
         self.pass1_rules = self._define_pass1_rules()
         self.pass2_rules = self._define_pass2_rules()
@@ -60,6 +276,14 @@ class LongSNormalizer:
             'transformations': {},
         }
 
+    def _prefers_s(self, f_freq: int, s_freq: int, threshold: float) -> bool:
+        """Whether Pass 2 should read the f-form as s, per ``self.scoring``."""
+        if self.scoring == 'ratio':
+            return s_freq > f_freq * threshold and s_freq > 0
+        # log P(s) - log P(f) > log(threshold), with add-one smoothing; the
+        # shared denominator cancels
+        return s_freq + 1 > (f_freq + 1) * threshold
+
     def _load_ngrams(self, filename: str) -> Dict[str, int]:
         """Load n-gram frequency table from JSON."""
         filepath = self.ngram_dir / filename
@@ -77,8 +301,9 @@ class LongSNormalizer:
         """
         Define Pass 1 transformation rules (high-confidence, automatic).
 
-        These patterns have < 1% occurrence in clean Latin corpus.
-        Order matters: Apply longer patterns first to avoid partial matches.
+        These patterns have < 1% occurrence in clean Latin corpus. They are
+        applied in one scan with the longest match winning, so more rules
+        can be appended to ``self.pass1_rules`` in any order.
         """
         return [
             # Trigram rules first (longer patterns take precedence)
@@ -108,6 +333,7 @@ class LongSNormalizer:
             TransformationRule('fi', 'si', 'fi vs si (27.94% ratio)', 'low', 27.94),
         ]
 
+    @_on_core
     def normalize_word_pass1(self, word: str) -> Tuple[str, List[str]]:
         """
         Apply Pass 1 high-confidence transformations to a single word.
@@ -119,23 +345,37 @@ class LongSNormalizer:
             (normalized_word, list_of_applied_rules)
         """
         # Detect case pattern before lowercasing
-        is_upper = len(word) > 1 and word.isupper()
-        is_title = word[0:1].isupper() and (len(word) == 1 or not word.isupper())
+        is_upper, is_title = _case_pattern(word)
 
         normalized = word.lower()
         applied_rules = []
 
-        # Apply bigram and trigram rules
-        for rule in self.pass1_rules:
-            if rule.pattern in normalized:
-                normalized = normalized.replace(rule.pattern, rule.replacement)
-                applied_rules.append(rule.description)
-
-                # Track statistics
-                rule_key = f"{rule.pattern} \u2192 {rule.replacement}"
+        # Typographic ligatures decompose before any other rule
+        for ligature, letters in LIGATURES.items():
+            if ligature in normalized:
+                normalized = normalized.replace(ligature, letters)
+                rule_key = f'{ligature} \u2192 {letters}'
+                applied_rules.append(f'{rule_key} (ligature)')
                 self.stats['transformations'][rule_key] = \
                     self.stats['transformations'].get(rule_key, 0) + 1
 
+        # Genuine long-s characters map straight to 's'
+        if LONG_S in normalized:
+            normalized = normalized.replace(LONG_S, 's')
+            applied_rules.append('\u017f \u2192 s (long-s character)')
+            self.stats['transformations']['\u017f \u2192 s'] = \
+                self.stats['transformations'].get('\u017f \u2192 s', 0) + 1
+
+        # Apply bigram and trigram rules, longest match first
+        normalized, fired = _apply_rules(normalized, self.pass1_rules)
+        for rule in fired:
+            applied_rules.append(rule.description)
+
+            # Track statistics
+            rule_key = f"{rule.pattern} \u2192 {rule.replacement}"
+            self.stats['transformations'][rule_key] = \
+                self.stats['transformations'].get(rule_key, 0) + 1
+
         # Special case: Word-final 'f' → 's'
         # Only 6 words ending in 'f' in 842K corpus (0.01% ratio)
         if normalized.endswith('f'):
@@ -152,54 +392,40 @@ class LongSNormalizer:
 
         return normalized, applied_rules
 
+    @_on_core
     def normalize_word_pass2(self, word: str, threshold: float = 2.0) -> Tuple[str, List[str]]:
         """
         Apply Pass 2 context-dependent transformations using n-gram frequency.
 
         Args:
             word: Input word (after Pass 1)
-            threshold: Frequency ratio threshold for transformation
-                      (default: 2.0 = replace if 's' form is 2x more common)
+            threshold: Probability ratio threshold for transformation
+                      (default: 2.0 = replace if 's' form is 2x more likely)
 
         Returns:
             (normalized_word, list_of_applied_rules)
         """
         # Detect case pattern before lowercasing
-        is_upper = len(word) > 1 and word.isupper()
-        is_title = word[0:1].isupper() and (len(word) == 1 or not word.isupper())
+        is_upper, is_title = _case_pattern(word)
 
-        normalized = word.lower()
+        normalized = decompose_ligatures(word.lower()).replace(LONG_S, 's')
         applied_rules = []
 
-        # Allowlist: known legitimate Latin words starting with 'fu', 'fe', 'fi'
-        # Skip Pass 2 for these to avoid false positives (alpha-sorted)
-        legitimate_f_words = {
-            'facere', 'facio', 'facit', 'faciunt', 'feceram', 'fecerant', 'fecerat', 'fecere',
-            'fecerim', 'fecerint', 'fecerit', 'fecerunt', 'feci', 'fecimus', 'fecisse', 'fecissem',
-            'fecissent', 'fecisset', 'fecisti', 'fecistis', 'fecit', 'fecunda', 'fecundam', 'fecundi',
-            'fecundis', 'fecunditas', 'fecunditatem', 'fecundus', 'felice', 'felicem', 'felices', 'felici',
-            'felicibus', 'felicis', 'feliciter', 'felicium', 'felix', 'femina', 'feminae', 'feminam',
-            'feminarum', 'feminas', 'feminis', 'fenestra', 'fenestram', 'fenestras', 'fenestris', 'feram',
-            'ferebam', 'ferebant', 'ferebat', 'ferebatur', 'feremus', 'ferendi', 'ferendo', 'ferendum',
-            'ferens', 'ferent', 'ferentem', 'ferentis', 'feres', 'feret', 'ferimus', 'fero',
-            'ferocem', 'feroces', 'feroci', 'ferocis', 'ferociter', 'ferox', 'ferre', 'ferrem',
-            'ferrent', 'ferret', 'ferri', 'ferro', 'ferrum', 'fers', 'fert', 'fertis',
-            'fertur', 'ferunt', 'feruntur', 'festa', 'festi', 'festis', 'festo', 'festum',
-            'fiant', 'fiat', 'fide', 'fidei', 'fideles', 'fidelibus', 'fidelis', 'fideliter',
-            'fidelium', 'fidem', 'fides', 'fiebant', 'fiebat', 'fierent', 'fieret', 'fieri',
-            'figura', 'figurae', 'figuram', 'figurarum', 'figuras', 'figuris', 'filia', 'filiae',
-            'filiam', 'filiarum', 'filias', 'filii', 'filiis', 'filio', 'filiorum', 'filios',
-            'filium', 'filius', 'finem', 'fines', 'finibus', 'finire', 'finis', 'finit',
-            'finita', 'finitum', 'finitur', 'finium', 'fio', 'firma', 'firmam', 'firmamenti',
-            'firmamento', 'firmamentum', 'firmare', 'firmat', 'firmi', 'firmiter', 'firmum', 'firmus',
-            'fit', 'fiunt', 'forma', 'formae', 'formam', 'formas', 'fuerat', 'fuerint',
-            'fuerit', 'fuerunt', 'fugere', 'fugerunt', 'fugi', 'fugiens', 'fugio', 'fugisse',
-            'fugit', 'fugiunt', 'fuisse', 'fuissem', 'fuissent', 'fuisset', 'fuit', 'fundamenta',
-            'fundamenti', 'fundamento', 'fundamentum', 'furor', 'furore', 'furorem', 'furoris', 'futura',
-            'futuram', 'futuri', 'futuris', 'futurum', 'futurus',
-        }
 
-        if normalized in legitimate_f_words:
+        # Denylist: always convert the initial f, whatever the n-gram evidence
+        if normalized in self.denylist and normalized.startswith('f'):
+            normalized = 's' + normalized[1:]
+            applied_rules.append('<f \u2192 <s (denylist)')
+            self.stats['transformations']['<f \u2192 <s (denylist)'] = \
+                self.stats['transformations'].get('<f \u2192 <s (denylist)', 0) + 1
+            if is_upper:
+                normalized = normalized.upper()
+            elif is_title:
+                normalized = normalized[0].upper() + normalized[1:]
+            return normalized, applied_rules
+
+        # Allowlist: skip Pass 2 for known legitimate f-words
+        if normalized in self.allowlist:
             # Restore case even for allowlisted words
             if is_upper:
                 normalized = normalized.upper()
@@ -216,7 +442,7 @@ class LongSNormalizer:
             su_freq = self.trigrams.get(su_trigram, 0)
 
             # If 'su' is significantly more common, transform
-            if su_freq > fu_freq * threshold and su_freq > 0:
+            if self._prefers_s(fu_freq, su_freq, threshold):
                 normalized = 's' + normalized[1:]
                 applied_rules.append(f'<fu \u2192 <su (freq: {su_freq} vs {fu_freq})')
                 self.stats['transformations']['<fu \u2192 <su (Pass 2)'] = \
@@ -230,7 +456,7 @@ class LongSNormalizer:
             fe_freq = self.trigrams.get(fe_trigram, 0)
             se_freq = self.trigrams.get(se_trigram, 0)
 
-            if se_freq > fe_freq * threshold and se_freq > 0:
+            if self._prefers_s(fe_freq, se_freq, threshold):
                 normalized = 's' + normalized[1:]
                 applied_rules.append(f'<fe \u2192 <se (freq: {se_freq} vs {fe_freq})')
                 self.stats['transformations']['<fe \u2192 <se (Pass 2)'] = \
@@ -246,7 +472,7 @@ class LongSNormalizer:
             fi_freq = self.fourgrams.get(fi_quadgram, 0)
             si_freq = self.fourgrams.get(si_quadgram, 0)
 
-            if si_freq > fi_freq * threshold and si_freq > 0:
+            if self._prefers_s(fi_freq, si_freq, threshold):
                 normalized = 's' + normalized[1:]
                 applied_rules.append(f'<fi \u2192 <si (4gram freq: {si_freq} vs {fi_freq})')
                 self.stats['transformations']['<fi \u2192 <si (Pass 2)'] = \
@@ -260,6 +486,16 @@ class LongSNormalizer:
 
         return normalized, applied_rules
 
+    def _is_protected_name(self, word: str) -> bool:
+        """Whether Pass 2 must leave ``word`` alone as a proper name."""
+        if not self.protect_names or word.lower() in self.denylist:
+            return False
+        _, core, _ = _split_affixes(word)
+        if not core[:1].isupper():
+            return False
+        lower = core.lower()
+        return lower in PROPER_NAMES or lower.replace('v', 'u').replace(LONG_S, 's') in PROPER_NAMES
+
     def normalize_word_full(self, word: str, apply_pass2: bool = True) -> Tuple[str, List[str]]:
         """
         Apply all transformation passes to a word.
@@ -275,7 +511,7 @@ class LongSNormalizer:
         normalized, rules_p1 = self.normalize_word_pass1(word)
 
         # Pass 2: Context-dependent rules
-        if apply_pass2:
+        if apply_pass2 and not self._is_protected_name(word):
             normalized, rules_p2 = self.normalize_word_pass2(normalized)
             all_rules = rules_p1 + rules_p2
         else:
@@ -29,31 +29,31 @@ def _apply_rust_backend(rust_module):
     def _rust_normalize(self, text: str) -> str:
         if not text:
             return text
-        return rust_module.normalize_uv(text)
+        return rust_module.normalize(text)
 
     def _rust_normalize_char(self, text: str, idx: int) -> tuple[str, str]:
         char = text[idx]
         if char.lower() not in ("u", "v"):
             raise ValueError(f"Character at position {idx} is not u/v: '{char}'")
-        return rust_module.normalize_uv_char(text, idx)
+        return rust_module.normalize_char(text, idx)
 
     def _rust_normalize_detailed(self, text: str) -> NormalizationResult:
         if not text:
             return NormalizationResult(original=text, normalized=text, changes=[])
-        result = rust_module.normalize_uv_detailed(text)
+        result = rust_module.normalize_detailed(text)
         changes = [
             Change(
-                position=c["position"],
-                original=c["original"],
-                normalized=c["normalized"],
-                rule=c["rule"],
-                context=c["context"],
+                position=c.position,
+                original=c.original,
+                normalized=c.normalized,
+                rule=c.rule,
+                context=c.context,
             )
-            for c in result["changes"]
+            for c in result.changes
         ]
         return NormalizationResult(
-            original=result["original"],
-            normalized=result["normalized"],
+            original=result.original,
+            normalized=result.normalized,
             changes=changes,
         )
 
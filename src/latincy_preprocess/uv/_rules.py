@@ -126,6 +126,146 @@ def _get_context(text: str, idx: int, window: int = 3) -> str:
     return text[start:idx] + "[" + text[idx] + "]" + text[idx + 1 : end]
 
 
+# =============================================================================
+# Roman Numerals
+# =============================================================================
+
+# Mirrors rust/src/numerals.rs: u/v inside a numeral (XVI, cap. xv) is
+# left as written.
+
+_VINCULUM = "\u0305"
+
+# Lowercase words that are well-formed numerals but far more often Latin
+_NUMERAL_SHAPED_WORDS = frozenset({"i", "vi", "mi", "di", "dii"})
+
+_SIGN_VALUES = {
+    **dict.fromkeys("IiⅠⅰ", (1,)),
+    **dict.fromkeys("VvⅤⅴ", (5,)),
+    **dict.fromkeys("XxⅩⅹ", (10,)),
+    **dict.fromkeys("LlⅬⅼ", (50,)),
+    **dict.fromkeys("CcⅭⅽ", (100,)),
+    **dict.fromkeys("DdⅮⅾ", (500,)),
+    **dict.fromkeys("MmⅯⅿↀ", (1000,)),
+    "ↁ": (5000,),
+    "ↂ": (10000,),
+    "ↇ": (50000,),
+    "ↈ": (100000,),
+    **dict.fromkeys("Ⅱⅱ", (1, 1)),
+    **dict.fromkeys("Ⅲⅲ", (1, 1, 1)),
+    **dict.fromkeys("Ⅳⅳ", (1, 5)),
+    **dict.fromkeys("Ⅵⅵ", (5, 1)),
+    **dict.fromkeys("Ⅶⅶ", (5, 1, 1)),
+    **dict.fromkeys("Ⅷⅷ", (5, 1, 1, 1)),
+    **dict.fromkeys("Ⅸⅸ", (1, 10)),
+    **dict.fromkeys("Ⅺⅺ", (10, 1)),
+    **dict.fromkeys("Ⅻⅻ", (10, 1, 1)),
+}
+
+
+def _is_numeral_word_char(char: str) -> bool:
+    return char.isalpha() or char in _SIGN_VALUES or "\u0300" <= char <= "\u036f"
+
+
+def _apostrophic(word: str, i: int) -> Optional[tuple[int, int]]:
+    """An apostrophic sign (CIↃ, IↃↃ) at word[i], as (value, length)."""
+    cs = 0
+    while i + cs < len(word) and word[i + cs] in "cC":
+        cs += 1
+    if cs > 3 or i + cs >= len(word) or word[i + cs] not in "iI":
+        return None
+    reversed_cs = 0
+    while i + cs + 1 + reversed_cs < len(word) and word[i + cs + 1 + reversed_cs] in "Ↄↄ":
+        reversed_cs += 1
+    if cs == 0:
+        if 1 <= reversed_cs <= 3:
+            return (5 * 10 ** (reversed_cs + 1), 1 + reversed_cs)
+        return None
+    if reversed_cs >= cs:
+        return (10 ** (cs + 2), 2 * cs + 1)
+    return None
+
+
+def _numeral_values(word: str) -> Optional[list[int]]:
+    values = []
+    i = 0
+    while i < len(word):
+        apostrophic = _apostrophic(word, i)
+        if apostrophic:
+            values.append(apostrophic[0])
+            i += apostrophic[1]
+            continue
+        char = word[i]
+        if char in "jJ" and i + 1 == len(word) and i > 0 and word[i - 1] in "iI":
+            signs = (1,)
+        elif char in _SIGN_VALUES:
+            signs = _SIGN_VALUES[char]
+        else:
+            return None
+        i += 1
+        scale = 1
+        if i < len(word) and word[i] == _VINCULUM:
+            i += 1
+            scale = 1000
+        values.extend(v * scale for v in signs)
+    return values
+
+
+def _read_numeral(values: list[int]) -> Optional[int]:
+    if not values:
+        return None
+    unit = 1
+    while unit * 10 <= max(values):
+        unit *= 10
+    pos = 0
+    total = 0
+    while unit >= 1:
+        one, five, ten = unit, 5 * unit, 10 * unit
+        rest = values[pos:]
+        if rest[:2] == [one, ten]:
+            total += 9 * unit
+            pos += 2
+        elif rest[:2] == [one, five]:
+            total += 4 * unit
+            pos += 2
+        else:
+            fives = 1 if rest[:1] == [five] else 0
+            ones = 0
+            while fives + ones < len(rest) and rest[fives + ones] == one:
+                ones += 1
+            if fives or unit < 1000:
+                ones = min(ones, 4)
+            total += (5 * fives + ones) * unit
+            pos += fives + ones
+        unit //= 10
+    return total if pos == len(values) else None
+
+
+def _in_roman_numeral(text: str, idx: int) -> bool:
+    """Check if the character at idx is part of a Roman numeral."""
+    start = idx
+    while start > 0 and _is_numeral_word_char(text[start - 1]):
+        start -= 1
+    end = idx
+    while end < len(text) and _is_numeral_word_char(text[end]):
+        end += 1
+    word = text[start:end]
+    if not word:
+        return False
+    upper = any(c.isupper() for c in word)
+    lower = any(c.islower() for c in word)
+    if upper and lower:
+        return False
+    if lower and word in _NUMERAL_SHAPED_WORDS:
+        return False
+    # A praenomen: M. Tullius, C. Iulius
+    if word in ("C", "D", "L", "M") and text[end : end + 1] == ".":
+        after = text[end + 1 :].lstrip()
+        if len(after) > 1 and after[0].isupper() and after[1].islower():
+            return False
+    values = _numeral_values(word)
+    return values is not None and _read_numeral(values) is not None
+
+
 # =============================================================================
 # Word Exception Lists
 # =============================================================================
@@ -325,6 +465,13 @@ def _classify_uv(text: str, idx: int) -> tuple[str, str]:
     # Extract current word for exception checking
     word = _extract_word(text, idx)
 
+    # =========================================================================
+    # Rule 0: Inside a Roman numeral → unchanged
+    # Examples: XVI, cap. xv
+    # =========================================================================
+    if _in_roman_numeral(text, idx):
+        return (char, "roman_numeral")
+
     # =========================================================================
     # Rule 1: After 'q' → ALWAYS 'u'
     # The 'qu' digraph represents /kw/, u is always vocalic
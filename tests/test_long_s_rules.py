@@ -11,7 +11,7 @@ from pathlib import Path
 
 import pytest
 
-from latincy_preprocess.long_s import LongSNormalizer
+from latincy_preprocess.long_s import LongSNormalizer, TransformationRule
 
 DATA_DIR = Path(__file__).parent / "data"
 
@@ -139,6 +139,21 @@ class TestPass1Rules:
         assert result == "dominus"
         assert rules == []
 
+    def test_longest_match_first(self, normalizer):
+        # fuf wins over the overlapping fpe instead of leaving "fuspendit"
+        result, rules = normalizer.normalize_word_pass1("fufpendit")
+        assert result == "suspendit"
+        assert rules == ["fuf \u2192 sus (0.18% ratio)"]
+
+    def test_custom_rule(self):
+        normalizer = LongSNormalizer()
+        normalizer.pass1_rules.append(
+            TransformationRule('fl', 'sl', 'fl \u2192 sl (print shop)', 'high', 0.0)
+        )
+        result, rules = normalizer.normalize_word_pass1("flendo")
+        assert result == "slendo"
+        assert rules == ["fl \u2192 sl (print shop)"]
+
 
 # ===========================================================================
 # Section 4: Word-final f -> s
@@ -167,6 +182,16 @@ class TestWordFinalF:
         assert result == "rex"
         assert not any("word-final" in r for r in rules)
 
+    def test_trailing_punctuation(self, normalizer):
+        result, rules = normalizer.normalize_word_pass1("eft.")
+        assert result == "est."
+        assert normalizer.normalize_word_pass1("e\u017ft.")[0] == "est."
+
+    def test_surrounding_punctuation_keeps_case(self, normalizer):
+        assert normalizer.normalize_word_full("(Fuper)")[0] == "(Super)"
+        assert normalizer.normalize_word_pass1("(EFT)")[0] == "(EST)"
+        assert normalizer.normalize_word_pass1("...") == ("...", [])
+
 
 # ===========================================================================
 # Section 5: Pass 2 context-dependent rules
@@ -193,6 +218,46 @@ class TestPass2Rules:
         result, rules = normalizer.normalize_word_full("funt", apply_pass2=False)
         assert result == "funt"
 
+    def test_extra_allowlist_words(self):
+        normalizer = LongSNormalizer(allowlist=["fuga", "Fugam"])
+        assert normalizer.normalize_word_full("fuga")[0] == "fuga"
+        assert normalizer.normalize_word_full("Fugam")[0] == "Fugam"
+        assert LongSNormalizer().normalize_word_full("fuga")[0] == "suga"
+
+    def test_denylist_overrides_allowlist(self):
+        normalizer = LongSNormalizer(denylist=["fit"])
+        result, rules = normalizer.normalize_word_full("Fit")
+        assert result == "Sit"
+        assert any("denylist" in r for r in rules)
+
+    def test_proper_names_skip_pass2(self, normalizer):
+        assert normalizer.normalize_word_full("Fulvius")[0] == "Fulvius"
+        assert normalizer.normalize_word_full("(Furius),")[0] == "(Furius),"
+        assert LongSNormalizer(protect_names=False).normalize_word_full("Fulvius")[0] == "Sulvius"
+        assert LongSNormalizer(denylist=["fulvius"]).normalize_word_full("Fulvius")[0] == "Sulvius"
+
+    def test_word_lists_from_files(self, tmp_path):
+        allow = tmp_path / "allow.txt"
+        allow.write_text("# extra forms\nfuga\n\n", encoding="utf-8")
+        deny = tmp_path / "deny.json"
+        deny.write_text('["fit"]', encoding="utf-8")
+        normalizer = LongSNormalizer(allowlist=allow, denylist=deny)
+        assert "fuga" in normalizer.allowlist
+        assert normalizer.normalize_word_full("fit")[0] == "sit"
+
+    def test_scoring_small_counts(self, tmp_path):
+        # One observation of <su against none of <fu
+        for name, table in [("bigrams", {}), ("trigrams", {"<su": 1}), ("4grams", {})]:
+            (tmp_path / f"{name}.json").write_text(json.dumps(table), encoding="utf-8")
+        smoothed = LongSNormalizer(ngram_dir=tmp_path)
+        assert smoothed.normalize_word_pass2("funt")[0] == "funt"
+        ratio = LongSNormalizer(ngram_dir=tmp_path, scoring="ratio")
+        assert ratio.normalize_word_pass2("funt")[0] == "sunt"
+
+    def test_scoring_invalid(self):
+        with pytest.raises(ValueError, match="scoring"):
+            LongSNormalizer(scoring="kneser-ney")
+
 
 # ===========================================================================
 # Section 6: Text-level normalization
@@ -280,7 +345,39 @@ class TestEdgeCases:
 
     def test_actual_long_s_character(self, normalizer):
         result, rules = normalizer.normalize_word_pass1("\u017ftatua")
-        assert result == "\u017ftatua"
+        assert result == "statua"
+        assert any("long-s" in r for r in rules)
+
+    def test_long_s_with_f_confusion(self, normalizer):
+        result, rules = normalizer.normalize_word_pass1("\u017fuf")
+        assert result == "sus"
+
+    def test_long_s_in_uppercase_word(self, normalizer):
+        result, rules = normalizer.normalize_word_pass1("PO\u017fSUM")
+        assert result == "POSSUM"
+
+    def test_long_s_full(self, normalizer):
+        result, rules = normalizer.normalize_word_full("\u017funt")
+        assert result == "sunt"
+
+    def test_ligatures(self, normalizer):
+        result, rules = normalizer.normalize_word_pass1("e\ufb05")
+        assert result == "est"
+        assert any("ligature" in r for r in rules)
+        assert normalizer.normalize_word_pass1("\ufb01lius")[0] == "filius"
+        assert normalizer.normalize_word_pass1("o\ufb00ert")[0] == "offert"
+
+    def test_decompose_ligatures(self):
+        from latincy_preprocess.long_s import decompose_ligatures
+        assert decompose_ligatures("\ufb02os \ufb06ella") == "flos stella"
+
+    def test_archaize(self, normalizer):
+        from latincy_preprocess.long_s import archaize
+        assert archaize("possessionis") == "po\u017f\u017fe\u017f\u017fionis"
+        assert archaize("Est satis, sed transfert") == "E\u017ft \u017fatis, \u017fed transfert"
+        assert archaize("presbyter ESSE") == "presbyter ESSE"
+        text = "ipsum esse sunt, ut possumus"
+        assert normalizer.normalize_text_pass1(archaize(text)) == text
 
 
 # ===========================================================================
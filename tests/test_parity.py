@@ -2,6 +2,10 @@
 Backend detection and Rust/Python parity tests for both normalizers.
 """
 
+import os
+import subprocess
+import sys
+
 import pytest
 
 from latincy_preprocess import backend
@@ -150,6 +154,11 @@ class TestLongSParity:
         "dominus", "rex", "", "f", "fff", "FTATUA", "123",
         "feipfum", "teipfum", "chriftus", "noftra", "ipfum",
         "ef", "poteft", "fenatuf",
+        "\u017ftatua", "e\u017ft", "po\u017f\u017fum", "\u017funt", "\u017fuf",
+        "PO\u017fSUM", "E\u017ft",
+        "\ufb01des", "e\ufb05", "po\ufb00e", "\ufb02os",
+        "eft.", "(Fuper)", "\u00abFTATUA\u00bb,", "...",
+        "Fulvius", "fulvius", "FVLVIVS", "(Furius),", "Fau\u017ftus",
     ]
 
     @pytest.mark.parametrize("word", WORDS)
@@ -176,3 +185,785 @@ class TestLongSParity:
         py_result = py_normalizer.normalize_text_full(text, apply_pass2=True)
         rust_result = normalize_long_s_text_full(text, True)
         assert rust_result == py_result
+
+    def test_archaize_parity(self, has_rust):
+        from latincy_preprocess._rust import archaize as rust_archaize
+        from latincy_preprocess.long_s import archaize
+        text = "Sed possessiones transfert, presbyter usus-que ss2 \u00e6s\u00e6 SUNT"
+        assert rust_archaize(text) == archaize(text)
+
+    def test_missing_ngrams_falls_back_to_pass1(self, has_rust, tmp_path):
+        # Run in a fresh interpreter: the bundled model loads once per process
+        script = (
+            "import warnings\n"
+            "from latincy_preprocess import _rust\n"
+            "with warnings.catch_warnings(record=True) as caught:\n"
+            "    warnings.simplefilter('always')\n"
+            "    print(_rust.normalize_long_s_text_full('funt ftatua', True))\n"
+            "print(caught[0].category.__name__)\n"
+        )
+        env = dict(os.environ, LATINCY_PREPROCESS_NGRAMS=str(tmp_path / "missing"))
+        result = subprocess.run(
+            [sys.executable, "-c", script], env=env, capture_output=True, text=True
+        )
+        assert result.returncode == 0, result.stderr
+        assert result.stdout.split("\n")[:2] == ["funt statua", "RuntimeWarning"]
+
+    def test_named_model(self, has_rust, tmp_path, monkeypatch):
+        from latincy_preprocess import _rust
+        _rust.NgramData.train(["fundamentum fuit"]).write_json_dir(str(tmp_path / "neo-latin"))
+        monkeypatch.setenv("LATINCY_PREPROCESS_MODELS", str(tmp_path))
+        assert _rust.available_models()[:2] == ["default", "neo-latin"]
+        assert _rust.LongSNormalizer(model="neo-latin").normalize_text("funt") == "funt"
+        assert _rust.LongSNormalizer(model="default").normalize_text("funt") == "sunt"
+        with pytest.raises(FileNotFoundError):
+            _rust.LongSNormalizer(model="no-such-model")
+
+    def test_char_changes_match_uv_records(self, has_rust):
+        from latincy_preprocess import _rust
+        long_s = _rust.normalize_long_s_detailed("eft funt")
+        uv = _rust.normalize_uv_detailed("uita")
+        assert [c["position"] for c in long_s["char_changes"]] == [1, 4]
+        assert long_s["char_changes"][0]["context"] == "e[f]t f"
+        assert set(long_s.char_changes[0].to_dict()) == set(uv.changes[0].to_dict())
+
+    def test_custom_pass1_rules(self, has_rust):
+        from latincy_preprocess import _rust
+        normalizer = _rust.LongSNormalizer(pass1_rules=[("fl", "sl")], apply_pass2=False)
+        assert normalizer.normalize_text("flendo fufpendit") == "slendo suspendit"
+        assert ("fl", "sl") in normalizer.pass1_rules
+        with pytest.raises(ValueError):
+            normalizer.add_pass1_rule("fl", "s")
+
+    def test_suggest(self, has_rust):
+        from latincy_preprocess import _rust
+        suggestions = _rust.suggest_long_s("funt")
+        assert [reading for reading, _ in suggestions] == ["sunt", "funt"]
+        assert abs(sum(score for _, score in suggestions) - 1.0) < 1e-9
+        assert _rust.LongSNormalizer().suggest("Caufa", n=1)[0][0] == "Causa"
+
+    def test_language(self, has_rust):
+        from latincy_preprocess import _rust
+        english = _rust.LongSNormalizer(language="en")
+        assert english.language == "en"
+        assert english.normalize_text("I fhall fpeak often of it") == "I shall speak often of it"
+        assert "language='en'" in repr(english)
+        with pytest.raises(ValueError):
+            _rust.LongSNormalizer(language="xx")
+
+    def test_ligatures(self, has_rust):
+        from latincy_preprocess import _rust
+        assert _rust.expand_ligatures("Cœlum et Æneas quę CÆSAR") == "Coelum et Aeneas quae CAESAR"
+        assert _rust.expand_ligatures("Æneas", capitals="upper") == "AEneas"
+        detailed = _rust.expand_ligatures_detailed("in cœlo")
+        assert detailed["normalized"] == "in coelo"
+        assert detailed["changes"][0]["context"] == "n c[œ]lo"
+        assert _rust.contract_ligatures("Caesar poeta") == "Cæsar poeta"
+        with pytest.raises(ValueError):
+            _rust.expand_ligatures("Æ", capitals="lower")
+
+    def test_macrons(self, has_rust):
+        from latincy_preprocess import _rust, strip_macrons
+        for text in ["laudāre", "RŌMA", "ārma", "rŏsă", "ǣquus", "arma"]:
+            assert _rust.strip_macrons(text) == strip_macrons(text)
+        assert _rust.has_macrons("canō") and not _rust.has_macrons("cano")
+        assert _rust.transfer_macrons("Arma virumque cano", "Arma uirumque canō") == "Arma virumque canō"
+
+    def test_abbreviations(self, has_rust, tmp_path):
+        from latincy_preprocess import _rust
+        assert _rust.expand_abbreviations("populusq; ꝑ ⁊ ꝓ") == "populusque per et pro"
+        assert _rust.expand_abbreviations("arma & uirum", ampersand=True) == "arma et uirum"
+        table = tmp_path / "abbreviations.txt"
+        table.write_text("dns dominus\n")
+        abbreviations = _rust.Abbreviations(entries=[("scs", "sanctus")], table=str(table))
+        assert abbreviations.expand("scs dns") == "sanctus dominus"
+        detailed = abbreviations.expand_detailed("atq;")
+        assert detailed["changes"][0]["rule"] == "q; → que"
+        with pytest.raises(ValueError):
+            abbreviations.add("", "et")
+
+    def test_nasal_bars(self, has_rust):
+        from latincy_preprocess import _rust
+        assert _rust.expand_nasal_bars("quā cōsul tēpus") == "quam consul tempus"
+        assert _rust.expand_nasal_bars("quã rōsa", macrons=False) == "quam rōsa"
+        detailed = _rust.expand_nasal_bars_detailed("ad quā")
+        assert detailed["changes"][0]["rule"] == "ā → am"
+        ngrams = _rust.NgramData.train(["rosa rosa rosa"])
+        assert _rust.expand_nasal_bars("rosā", keep_length_marks=True, ngrams=ngrams) == "rosā"
+
+    def test_medieval(self, has_rust, tmp_path):
+        from latincy_preprocess import _rust
+        assert _rust.normalize_medieval("Michi nichil dampnum racionis") == "Mihi nihil damnum rationis"
+        lexicon = tmp_path / "lexicon.txt"
+        lexicon.write_text("caelum 50\ngratia 40\n")
+        normalizer = _rust.MedievalNormalizer(lexicon=str(lexicon), mpn=False)
+        assert normalizer.normalize_text("celum gracia dampnum") == "caelum gratia dampnum"
+        assert normalizer.rules == ["ch_for_h", "ci_for_ti", "e_for_ae"]
+        detailed = normalizer.normalize_detailed("celum")
+        assert detailed["changes"][0]["rule"] == "e → ae"
+
+    def test_diphthongs(self, has_rust, tmp_path):
+        from latincy_preprocess import _rust
+        assert _rust.flatten_diphthongs("Caesar poenas caeli dedit") == "Cesar penas celi dedit"
+        lexicon = tmp_path / "lexicon.txt"
+        lexicon.write_text("caelum 40\ncepit 25\npoena 10\n")
+        restorer = _rust.DiphthongRestorer(str(lexicon))
+        assert restorer.restore("celum cepit pena") == "caelum cepit poena"
+        assert restorer.restore_detailed("pena")["changes"][0]["rule"] == "e → oe"
+
+    def test_assimilation(self, has_rust):
+        from latincy_preprocess import _rust
+        assert _rust.normalize_assimilation("adfert inperium conlega") == "affert imperium collega"
+        assert _rust.normalize_assimilation("affert ille", style="unassimilated") == "adfert ille"
+        detailed = _rust.normalize_assimilation_detailed("adfert")
+        assert detailed["changes"][0]["rule"] == "adf- → aff-"
+        with pytest.raises(ValueError):
+            _rust.normalize_assimilation("adfert", style="classical")
+
+    def test_standardize(self, has_rust):
+        from latincy_preprocess import _rust
+        assert _rust.style_profiles() == ["old", "ecclesiastical", "teubner-u-only"]
+        assert _rust.standardize("iam eius uirtus caeli deo", "ecclesiastical") == "jam ejus virtus cæli Deo"
+        assert _rust.standardize("Ut virtus jam", "teubner-u-only") == "Vt uirtus iam"
+        with pytest.raises(ValueError):
+            _rust.standardize("arma", "modern")
+
+    def test_normalize_unicode(self, has_rust):
+        import unicodedata
+        from latincy_preprocess import _rust
+        text = "Ro\u0304ma e\u0301t u\u0308\u0304 \ufb01nis e\u017ft \u216b \u212a"
+        for form in ("NFC", "NFKC"):
+            assert _rust.normalize_unicode(text, form) == unicodedata.normalize(form, text)
+        result = _rust.normalize_unicode_detailed("im\u00adperium", "nfc")
+        assert result["normalized"] == "imperium"
+        assert result["changes"][0]["rule"] == "remove soft hyphen"
+        with pytest.raises(ValueError):
+            _rust.normalize_unicode("arma", "nfd")
+
+    def test_dehyphenate(self, has_rust, tmp_path):
+        from latincy_preprocess import _rust
+        assert _rust.dehyphenate("impera-\ntor est") == "imperator est"
+        assert _rust.dehyphenate("impera-\ntor est", "preserve") == "imperator\nest"
+        lexicon = tmp_path / "lexicon.txt"
+        lexicon.write_text("quasi\ncontractus\n")
+        dehyphenator = _rust.Dehyphenator(lexicon)
+        result = dehyphenator.dehyphenate_detailed("quasi-\ncontractus")
+        assert result["normalized"] == "quasi-contractus"
+        assert result["changes"][0]["rule"] == "line break removed after hyphen"
+        assert len(result["offsets"]) == len(result["normalized"]) + 1
+        with pytest.raises(ValueError):
+            _rust.dehyphenate("arma", "wrap")
+
+    def test_clean_markup(self, has_rust):
+        from latincy_preprocess import _rust
+        text = "12\nGallia est omnis <di>uisa {in} partes tres 5"
+        assert _rust.clean_markup(text) == "Gallia est omnis diuisa partes tres"
+        cleaner = _rust.MarkupCleaner(brace="keep_content", line_number="keep_marker")
+        assert cleaner.clean(text) == "Gallia est omnis diuisa in partes tres 5"
+        assert ("brace", "keep_content") in cleaner.policies
+        result = cleaner.clean_detailed("uerbum (!) est")
+        assert result["normalized"] == "uerbum est"
+        assert result["changes"][0]["rule"] == "sic: drop_content"
+        with pytest.raises(ValueError):
+            _rust.MarkupCleaner(square="drop")
+
+    def test_correct_ocr(self, has_rust, tmp_path):
+        from latincy_preprocess import _rust
+        assert _rust.correct_ocr("Rnagnus et c1arus anno 1500") == "Magnus et clarus anno 1500"
+        corrector = _rust.OcrCorrector(confusions=[("vv", "u", 1.0)])
+        assert corrector.confusions == [("vv", "u", 1.0)]
+        assert corrector.correct("rnagnus") == "rnagnus"
+        lexicon = tmp_path / "lexicon.txt"
+        lexicon.write_text("clamor 5\n")
+        result = _rust.OcrCorrector(lexicon=lexicon).correct_detailed("c1amor")
+        assert result["normalized"] == "clamor"
+        assert result["changes"][0]["rule"] == "1 → l"
+        with pytest.raises(ValueError):
+            _rust.OcrCorrector(confusions=[("RN", "m", 1.0)])
+
+    def test_roman_numerals(self, has_rust):
+        from latincy_preprocess import _rust
+        spans = _rust.find_roman_numerals("liber XVI, cap. iv, M. Tullius")
+        assert [(s["text"], s["value"]) for s in spans] == [("XVI", 16), ("iv", 4)]
+        assert _rust.parse_roman("viij") == 8
+        assert _rust.parse_roman("X\u0305") == 10000
+        assert _rust.parse_roman("IIX") is None
+        assert _rust.roman_to_arabic("anno MDCXV") == "anno 1615"
+        assert _rust.normalize_uv("cap. xv uidi") == python_normalize_uv("cap. xv uidi") == "cap. xv vidi"
+
+    def test_normalize_loanwords(self, has_rust):
+        from latincy_preprocess import _rust
+        assert _rust.normalize_loanwords("Kalendis in sylva") == "Calendis in silva"
+        assert _rust.normalize_loanwords("philosophia posthac", y_to_i=False, ph="simplify", th="simplify") == "filosofia posthac"
+        result = _rust.normalize_loanwords_detailed("nymphae", ph="simplify")
+        assert result["normalized"] == "nimfae"
+        assert [c["rule"] for c in result["changes"]] == ["y → i", "ph → f"]
+        with pytest.raises(ValueError):
+            _rust.normalize_loanwords("pulcher", ch="drop")
+
+    def test_strip_accents(self, has_rust):
+        from latincy_preprocess import _rust
+        assert _rust.strip_accents("à certè musâ ǽtas") == "a certe musa ætas"
+        assert _rust.strip_accents("benè bene\u0301 musâ", acute=False, grave=False) == "benè bene\u0301 musa"
+        assert _rust.strip_accents("ā\u0301") == "ā"
+        result = _rust.strip_accents_detailed("quàm")
+        assert result["normalized"] == "quam"
+        assert result["changes"][0]["rule"] == "remove grave"
+
+    def test_recase(self, has_rust, tmp_path):
+        from latincy_preprocess import _rust
+        assert _rust.recase("SENATVS POPVLVSQVE ROMANVS") == "Senatvs popvlvsqve Romanvs"
+        assert _rust.recase("LIBER XVI") == "Liber XVI"
+        assert _rust.recase("Gallia est omnis divisa") == "Gallia est omnis divisa"
+        names = tmp_path / "names.txt"
+        names.write_text("dacia\n")
+        recaser = _rust.Recaser(names=names)
+        assert recaser.recase("IN DACIA ET ROMA") == "In Dacia et roma"
+        result = recaser.recase_detailed("in DACIA")
+        assert result["normalized"] == "in Dacia"
+        assert result["changes"][0]["rule"] == "proper name"
+
+    def test_name_guard(self, has_rust, tmp_path):
+        from latincy_preprocess import _rust
+        guard = _rust.NameGuard()
+        assert guard.is_name("Fulvius")
+        assert not guard.is_name("fulvius")
+        assert _rust.normalize_long_s_text_full("Fulvius funt", True) == "Fulvius sunt"
+        assert _rust.LongSNormalizer(protect_names=False).normalize_text("Fulvius") == "Sulvius"
+        guard = _rust.NameGuard(capitalized=True)
+        assert [n["text"] for n in guard.find("Funt. Et Funt")] == ["Funt"]
+        assert _rust.LongSNormalizer(name_guard=guard).normalize_text("Funt. Et Funt") == "Sunt. Et Funt"
+        names = tmp_path / "names.txt"
+        names.write_text("dampnus\n")
+        medieval = _rust.MedievalNormalizer(name_guard=_rust.NameGuard(names=names))
+        assert medieval.normalize_text("Dampnus dampnum") == "Dampnus damnum"
+
+    def test_epigraphic(self, has_rust, tmp_path):
+        from latincy_preprocess import _rust
+        assert _rust.normalize_epigraphic("servos donom dedit uxsori virei") == "servus donum dedit uxori viri"
+        assert _rust.normalize_epigraphic("exspecto vos rei") == "exspecto vos rei"
+        lexicon = tmp_path / "lexicon.txt"
+        lexicon.write_text("servus\nservos\ndonum\n")
+        normalizer = _rust.EpigraphicNormalizer(xs_for_x=False, lexicon=lexicon)
+        assert normalizer.rules == ["vos_for_vus", "om_for_um", "ei_for_i"]
+        assert normalizer.normalize_text("servos donom uxsor") == "servos donum uxsor"
+        result = normalizer.normalize_detailed("donom")
+        assert result["changes"][0]["rule"] == "-om → -um"
+
+    def test_syllabify(self, has_rust):
+        from latincy_preprocess import _rust
+        assert _rust.syllabify("patria") == ["pa", "tri", "a"]
+        assert _rust.syllabify("patria", muta_cum_liquida=False) == ["pat", "ri", "a"]
+        assert _rust.syllabify("aqua") == ["a", "qua"]
+        assert _rust.syllabify("Rōmānus") == ["Rō", "mā", "nus"]
+        assert _rust.syllable_boundaries("caelum") == [3]
+        assert _rust.syllabify_text("arma, virumque") == [[(0, 2), (2, 4)], [(6, 8), (8, 11), (11, 14)]]
+
+    def test_split_sentences(self, has_rust):
+        from latincy_preprocess import _rust
+        text = "M. Tullius Cn. Pompeio cos. scripsit. Quid (inquit) ais? Nihil."
+        assert [s["text"] for s in _rust.split_sentences(text)] == [
+            "M. Tullius Cn. Pompeio cos. scripsit.",
+            "Quid (inquit) ais?",
+            "Nihil.",
+        ]
+        first = _rust.split_sentences(text)[1]
+        assert (first["start"], first["end"]) == (38, 56)
+        assert len(_rust.split_sentences("ut Sall. ait", abbreviations=["Sall"])) == 1
+        assert _rust.split_sentences("") == []
+
+    def test_enclitics(self, has_rust):
+        from latincy_preprocess import _rust
+        assert _rust.split_enclitic("virumque") == ("virum", "que")
+        assert _rust.split_enclitic("mecum") == ("me", "cum")
+        assert _rust.split_enclitic("quoque") is None
+        assert _rust.split_enclitics("arma virumque cano") == "arma virum que cano"
+        assert _rust.split_enclitics("videsne", separator=" -") == "vides -ne"
+        splitter = _rust.EncliticSplitter(cum=False)
+        assert splitter.enclitics == ["que", "ne", "ve"]
+        assert splitter.split("nobiscum") is None
+        assert splitter.boundaries("Senatus populusque Romanus") == [15]
+
+    def test_pipeline(self, has_rust):
+        from latincy_preprocess import _rust
+        pipeline = _rust.Pipeline(["ligatures", "uv"])
+        assert pipeline.stages == ["ligatures", "uv"]
+        assert len(pipeline) == 2
+        assert pipeline.normalize("æuum") == "aevum"
+        result = pipeline.normalize_detailed("æuum")
+        assert result["offsets"] == [0, 0, 1, 2, 3, 4]
+        assert [c["rule"].split(":")[0] for c in result["changes"]] == ["ligatures", "uv"]
+        assert result["changes"][1]["position"] == 1
+        # Configured normalizer objects are stages too
+        pipeline = _rust.Pipeline([_rust.EncliticSplitter(separator=" -"), "uv"])
+        assert pipeline.normalize("uirumque") == "virum -que"
+        assert "long_s" in _rust.pipeline_stages()
+        with pytest.raises(ValueError):
+            _rust.Pipeline(["nonesuch"])
+        with pytest.raises(TypeError):
+            _rust.Pipeline([42])
+
+    def test_pipeline_from_config(self, has_rust, tmp_path):
+        from latincy_preprocess import _rust
+        config = tmp_path / "pipeline.toml"
+        config.write_text(
+            '[[stages]]\nname = "ligatures"\n\n'
+            '[[stages]]\nname = "enclitics"\nseparator = " -"\n\n'
+            '[[stages]]\nname = "uv"\n'
+        )
+        pipeline = _rust.Pipeline.from_config(str(config))
+        assert pipeline.stages == ["ligatures", "enclitics", "uv"]
+        assert pipeline.normalize("Cæsar uirumque") == "Caesar virum -que"
+        config = tmp_path / "pipeline.json"
+        config.write_text('{"stages": [{"name": "uv", "threshold": 2}]}')
+        with pytest.raises(ValueError, match="unknown option"):
+            _rust.Pipeline.from_config(str(config))
+        with pytest.raises(OSError):
+            _rust.Pipeline.from_config(str(tmp_path / "missing.json"))
+
+    def test_pipeline_provenance(self, has_rust):
+        import json
+        from latincy_preprocess import _rust
+        pipeline = _rust.Pipeline(["long_s", "uv"])
+        result = pipeline.normalize_detailed("ﬅat ſeruus")
+        assert [(c["stage"], c["index"], c["stage_position"], c["position"]) for c in result["changes"]] == [
+            ("long_s", 0, 0, 0),
+            ("long_s", 0, 4, 4),
+            ("uv", 1, 8, 7),
+        ]
+        log = json.loads(pipeline.normalize_json("ﬅat ſeruus"))
+        assert log["normalized"] == "stat servus"
+        assert log["changes"][2]["stage"] == "uv"
+        assert not log["changes"][2]["rule"].startswith("uv:")
+
+    def test_pipeline_callback_stages(self, has_rust):
+        from latincy_preprocess import _rust
+
+        def caesar(text):
+            return text.replace("Caesar", "Cesar")
+
+        pipeline = _rust.Pipeline(["ligatures", caesar, "uv"])
+        assert pipeline.stages == ["ligatures", "caesar", "uv"]
+        assert pipeline.normalize("Cæsar uult") == "Cesar vult"
+
+        def tironian(text):
+            changes = [
+                {"position": i, "original": "⁊", "normalized": "et", "rule": "⁊ → et"}
+                for i, c in enumerate(text) if c == "⁊"
+            ]
+            return text.replace("⁊", "et"), changes
+
+        pipeline = _rust.Pipeline([_rust.CallbackStage(tironian, name="tironian"), "uv"])
+        result = pipeline.normalize_detailed("uir ⁊ uxor")
+        assert result["normalized"] == "vir et uxor"
+        assert [(c["stage"], c["rule"], c["position"]) for c in result["changes"]] == [
+            ("tironian", "tironian: ⁊ → et", 4),
+            ("uv", result["changes"][1]["rule"], 0),
+        ]
+        assert result["changes"][0]["context"] == "ir [⁊] ux"
+
+        def broken(text):
+            raise RuntimeError("boom")
+
+        with pytest.raises(RuntimeError, match="boom"):
+            _rust.Pipeline(["uv", broken]).normalize("uia")
+        with pytest.raises(TypeError):
+            _rust.Pipeline([lambda text: 42]).normalize("uia")
+        with pytest.raises(TypeError):
+            _rust.CallbackStage("uv")
+
+    def test_diffs(self, has_rust):
+        from latincy_preprocess import _rust
+        text = "arma uirumque cano\nTroiae qui primus\n"
+        assert _rust.unified_diff(text, text.replace("uir", "vir"), "a/aen.txt", "b/aen.txt") == (
+            "--- a/aen.txt\n+++ b/aen.txt\n@@ -1,2 +1,2 @@\n"
+            "-arma uirumque cano\n+arma virumque cano\n Troiae qui primus\n"
+        )
+        assert _rust.word_diff(text, text.replace("uir", "vir")) == "1: arma [-uirumque-]{+virumque+} cano\n"
+        pipeline = _rust.Pipeline(["uv"])
+        assert pipeline.diff(text, name="aen.txt").startswith("--- aen.txt\n+++ aen.txt\n@@ -1,2 +1,2 @@\n")
+        assert pipeline.diff(text, words=True) == "1: arma [-uirumque-]{+virumque+} cano\n"
+        assert pipeline.diff("arma\n") == ""
+
+    def test_patches(self, has_rust):
+        import json
+        from latincy_preprocess import _rust
+        text = "Cæsar ſeruus uult"
+        pipeline = _rust.Pipeline(["ligatures", "long_s", "uv"])
+        patch = pipeline.export_patch(text)
+        edits = [json.loads(line) for line in patch.splitlines()]
+        assert [(e["start"], e["original"], e["replacement"]) for e in edits] == [
+            (1, "æ", "ae"),
+            (6, "ſ", "s"),
+            (9, "u", "v"),
+            (13, "u", "v"),
+        ]
+        assert _rust.apply_patch(text, patch) == pipeline.normalize(text)
+        # Reject the last edit
+        reviewed = "\n".join(patch.splitlines()[:-1])
+        assert _rust.apply_patch(text, reviewed) == "Caesar servus uult"
+        with pytest.raises(ValueError):
+            _rust.apply_patch("Caesar", patch)
+        edits = [json.loads(line) for line in _rust.LongSNormalizer().export_patch("ﬅat ſed").splitlines()]
+        assert [e["replacement"] for e in edits] == ["stat", "sed"]
+        assert all(0.0 <= e["confidence"] <= 1.0 for e in edits)
+
+    def test_normalize_xml(self, has_rust):
+        from latincy_preprocess import _rust
+        tei = (
+            '<TEI><teiHeader><title>De uita</title></teiHeader>\n'
+            '<text><!-- uita --><l n="1"><w lemma="uir">uiros</w> &amp; <foreign xml:lang="en">uouchers</foreign></l>'
+            '</text></TEI>'
+        )
+        pipeline = _rust.Pipeline(["uv"])
+        assert _rust.normalize_xml(tei, pipeline, attributes=["lemma"]) == tei.replace(
+            '<w lemma="uir">uiros', '<w lemma="vir">viros'
+        )
+        everything = _rust.normalize_xml(tei, pipeline, skip=[], latin_only=False)
+        assert "De vita" in everything and "vouchers" in everything and "<!-- uita -->" in everything
+        with pytest.raises(ValueError, match="line 1"):
+            _rust.normalize_xml("<l>uia</w>", pipeline)
+
+    def test_progress_and_cancellation(self, has_rust):
+        from latincy_preprocess import _rust
+        text = "uir\n\nuita\n\nuox\n"
+        pipeline = _rust.Pipeline(["uv"])
+        reports = []
+        assert pipeline.normalize(text, progress=lambda *p: reports.append(p)) == "vir\n\nvita\n\nvox\n"
+        assert reports[-1] == (len(text), len(text), 3)
+        token = _rust.CancelToken()
+
+        def stop(done, total, words):
+            token.cancel()
+
+        with pytest.raises(InterruptedError):
+            pipeline.normalize(text, progress=stop, cancel=token)
+        assert token.cancelled
+        with pytest.raises(InterruptedError):
+            pipeline.normalize(text, cancel=token)
+
+        def fail(done, total, words):
+            raise KeyError("stop")
+
+        with pytest.raises(KeyError):
+            pipeline.normalize(text, progress=fail)
+
+    def test_detailed_result_objects(self, has_rust):
+        from latincy_preprocess import _rust
+        result = _rust.normalize_uv_detailed("uita")
+        assert isinstance(result, _rust.DetailedResult)
+        assert (result.original, result.normalized, len(result)) == ("uita", "vita", 1)
+        change = result.changes[0]
+        assert isinstance(change, _rust.ChangeRecord)
+        assert (change.position, change.original, change.normalized, change.pass_) == (0, "u", "v", 1)
+        assert change.stage is None and result.offsets is None
+        assert repr(change).startswith("ChangeRecord(position=0, ")
+        assert result.to_dict()["changes"][0] == change.to_dict() == {
+            "position": 0, "original": "u", "normalized": "v", "pass": 1,
+            "rule": change.rule, "context": change.context,
+        }
+        assert result["changes"][0]["rule"] == change.rule
+        with pytest.raises(KeyError):
+            result["offsets"]
+
+        long_s = _rust.normalize_long_s_detailed("eft", review_below=1.1)
+        assert isinstance(long_s.changes[0], _rust.WordChange)
+        assert long_s.changes[0].rules[0]["pass"] == 1
+        assert [item["original"] for item in long_s.review] == ["eft"]
+        assert set(long_s.to_dict()) == {"original", "normalized", "changes", "char_changes", "review"}
+
+        pipeline = _rust.Pipeline(["uv"])
+        staged = pipeline.normalize_detailed("uita")
+        assert (staged.changes[0].stage, staged.changes[0].index) == ("uv", 0)
+        assert staged.offsets == [0, 1, 2, 3, 4]
+        relay = _rust.CallbackStage(lambda text: (staged.normalized, staged.changes), name="relay")
+        assert _rust.Pipeline([relay]).normalize_detailed("uita").changes[0].rule == "relay: uv: " + change.rule
+
+    def test_warnings(self, has_rust):
+        from latincy_preprocess import _rust
+        result = _rust.MarkupCleaner().clean_detailed("[ab urbe condita")
+        warning = result.warnings[0]
+        assert isinstance(warning, _rust.NormalizerWarning)
+        assert (warning.position, warning.len, warning.kind) == (0, 1, "unbalanced_bracket")
+        assert warning.stage is None
+        assert result.to_dict()["warnings"] == [warning.to_dict()]
+        assert _rust.normalize_uv_detailed("uita").warnings is None
+        assert _rust.OcrCorrector().correct_detailed("c3sar").warnings[0].kind == "mixed_digits"
+        assert _rust.Recaser().recase_detailed("S P Q R").warnings[0].kind == "all_caps_skipped"
+
+        staged = _rust.Pipeline(["ligatures", "markup"]).normalize_detailed("Cæsar [ab urbe")
+        assert (staged.warnings[0].stage, staged.warnings[0].position) == ("markup", 6)
+        assert staged["warnings"][0].message == "markup: `[` with no closing `]`"
+
+    def test_change_summary(self, has_rust):
+        from latincy_preprocess import _rust
+        pipeline = _rust.Pipeline(["long_s", "uv"])
+        full = pipeline.normalize_detailed("ſeruus uia")
+        result = pipeline.normalize_detailed("ſeruus uia", detail="summary")
+        assert result.normalized == full.normalized == "servus via"
+        assert len(result) == 0 and result.offsets is None
+        summary = result.summary
+        assert isinstance(summary, _rust.ChangeSummary)
+        assert summary.changes == len(full) and full.summary is None
+        assert summary.modules == {"long_s": 1, "uv": 2}
+        assert sum(summary.rules.values()) == summary.changes
+        assert result.to_dict()["summary"]["chars_changed"] == summary.chars_changed
+        assert pipeline.normalize_detailed("ſeruus uia", detail="none").summary is None
+        with pytest.raises(ValueError):
+            pipeline.normalize_detailed("uia", detail="records")
+
+    def test_threads_normalize_long_texts(self, has_rust):
+        from concurrent.futures import ThreadPoolExecutor
+        from latincy_preprocess import _rust
+        text = "Cæsar uirumque ſeruat eft.\n\n" * 4000
+        expected = _rust.normalize_uv(text)
+        pipeline = _rust.Pipeline(["ligatures", "uv", lambda t: t.replace("Caesar", "Caesar")])
+        serial = pipeline.normalize(text)
+        seen = []
+        with ThreadPoolExecutor(4) as pool:
+            uv = list(pool.map(_rust.normalize_uv, [text] * 4))
+            piped = list(pool.map(pipeline.normalize, [text] * 4))
+            detailed = pool.submit(_rust.Pipeline(["uv"]).normalize_detailed, text).result()
+            monitored = pool.submit(
+                _rust.Pipeline(["uv"]).normalize, text, lambda *p: seen.append(p)
+            ).result()
+        assert uv == [expected] * 4
+        assert piped == [serial] * 4
+        assert detailed.normalized == monitored == expected
+        assert seen[-1][0] == len(text.encode())
+
+    def test_submodules(self, has_rust):
+        import importlib
+        from latincy_preprocess import _rust
+        assert _rust.uv.normalize is _rust.normalize_uv
+        assert _rust.uv.normalize("uita") == "vita"
+        assert _rust.long_s.normalize_text("eft funt") == _rust.normalize_long_s_text_full("eft funt")
+        assert _rust.long_s.LongSNormalizer is _rust.LongSNormalizer
+        assert _rust.abbrev.expand("atq;") == "atque"
+        assert _rust.dehyphenation.Dehyphenator is _rust.Dehyphenator
+        assert _rust.dehyphenate("impera-\ntor est") == "imperator est"
+        assert importlib.import_module("latincy_preprocess._rust.long_s") is _rust.long_s
+        from latincy_preprocess._rust.uv import normalize_detailed
+        assert normalize_detailed("uita").normalized == "vita"
+        assert set(_rust.uv.__all__) == {"normalize", "normalize_char", "normalize_detailed", "UvNormalizer", "UvModel"}
+
+    def test_normalize_iter(self, has_rust):
+        from latincy_preprocess import _rust
+        pipeline = _rust.Pipeline(["ligatures", "uv"])
+        pulled = []
+
+        def texts():
+            for i in range(200):
+                pulled.append(i)
+                yield f"Cæsar uidit {i}"
+
+        lazy = pipeline.normalize_iter(texts())
+        assert isinstance(lazy, _rust.NormalizeIter)
+        assert next(lazy) == "Caesar vidit 0"
+        assert pulled == [0]
+        expected = [pipeline.normalize(f"Cæsar uidit {i}") for i in range(200)]
+        assert list(pipeline.normalize_iter(texts(), threads=4, batch_size=16)) == expected
+        assert list(_rust.Pipeline([str.upper]).normalize_iter(["a", "b"], threads=2)) == ["A", "B"]
+
+        def broken():
+            yield "uita"
+            raise RuntimeError("boom")
+
+        parallel = pipeline.normalize_iter(broken(), threads=2)
+        assert next(parallel) == "vita"
+        with pytest.raises(RuntimeError):
+            next(parallel)
+        with pytest.raises(TypeError):
+            list(pipeline.normalize_iter(["uita", 3]))
+        with pytest.raises(ValueError):
+            pipeline.normalize_iter([], batch_size=0)
+
+    def test_uv_normalizer_class(self, has_rust):
+        from latincy_preprocess import _rust
+        assert _rust.uv.UvNormalizer is _rust.UvNormalizer
+        plain = _rust.UvNormalizer()
+        assert plain.normalize("Dauus genua") == _rust.normalize_uv("Dauus genua") == "Davus genva"
+        normalizer = _rust.UvNormalizer(vocalic_words=["DAVVS"], vocalic_stems=["genu"])
+        assert normalizer.vocalic_words == ["dauus"]
+        assert normalizer.normalize("Dauus genua uidit") == "Dauus genua vidit"
+        assert normalizer.normalize_char("Dauus", 2) == ("u", "word_exception")
+        result = normalizer.normalize_detailed("Dauus uenit")
+        assert result.normalized == "Dauus venit"
+        assert [change.rule for change in result.changes] == ["initial_before_vowel"]
+        plain.extend_vocalic_stems(["genu"])
+        assert plain.normalize("genua") == "genua"
+        assert repr(plain) == "UvNormalizer(vocalic_words=[], vocalic_stems=[\"genu\"])"
+        assert _rust.Pipeline([normalizer]).normalize("Dauus uidit") == "Dauus vidit"
+
+    def test_normalize_with_alignment(self, has_rust):
+        from latincy_preprocess import _rust
+        text = "Cæsar ⁊ ꝓ uirtute"
+        pipeline = _rust.Pipeline(["ligatures", "abbreviations", "uv"])
+        normalized, old_to_new, new_to_old = pipeline.normalize_with_alignment(text)
+        assert normalized == "Caesar et pro virtute"
+        assert len(old_to_new) == len(text) + 1 and len(new_to_old) == len(normalized) + 1
+        start = text.index("uirtute")
+        assert normalized[old_to_new[start]:old_to_new[start + 7]] == "virtute"
+        assert text[new_to_old[7]:new_to_old[9]] == "⁊"
+        assert old_to_new[1:3] == [1, 3]
+        assert _rust.normalize_with_alignment("uirumque")[0] == "virumque"
+        assert _rust.pipeline.normalize_with_alignment is _rust.normalize_with_alignment
+        assert _rust.normalize_with_alignment("Cæsar", stages=["ligatures"]) == ("Caesar", [0, 1, 3, 4, 5, 6], [0, 1, 1, 2, 3, 4, 5])
+
+    def test_normalize_arrow(self, has_rust):
+        from latincy_preprocess import _rust
+        pipeline = _rust.Pipeline(["ligatures", "uv"])
+        column = _rust.ArrowStrings(["Cæsar uidit", None, "", "uia"])
+        assert len(column) == 4 and column.null_count == 1 and column.string_type == "large_utf8"
+        normalized = pipeline.normalize_arrow(column, threads=2)
+        assert isinstance(normalized, _rust.arrow.ArrowStrings)
+        assert normalized.to_pylist() == ["Caesar vidit", None, "", "via"]
+        small = pipeline.normalize_arrow(_rust.ArrowStrings(["uir"], large=False))
+        assert small.string_type == "utf8" and small.to_pylist() == ["vir"]
+        assert _rust.Pipeline([str.upper]).normalize_arrow(column).to_pylist() == ["CÆSAR UIDIT", None, "", "UIA"]
+        with pytest.raises(TypeError):
+            pipeline.normalize_arrow(["uir"])
+
+    def test_normalize_arrow_pyarrow(self, has_rust):
+        pa = pytest.importorskip("pyarrow")
+        from latincy_preprocess import _rust
+        pipeline = _rust.Pipeline(["uv"])
+        array = pa.array(["uir", None, "uia"])
+        assert pa.array(pipeline.normalize_arrow(array)).to_pylist() == ["vir", None, "via"]
+        chunked = pa.chunked_array([["uir"], ["uia", None]], type=pa.large_string())
+        assert pipeline.normalize_arrow(chunked).to_pylist() == ["vir", "via", None]
+        with pytest.raises(ValueError):
+            pipeline.normalize_arrow(pa.array([1, 2]))
+
+    def test_long_s_data_source(self, has_rust):
+        from pathlib import Path
+        from latincy_preprocess import _rust
+        ngrams = Path(_rust.__file__).parent / "long_s" / "data" / "ngrams"
+        assert _rust.LongSNormalizer().data_source == f"bundled model ({ngrams})"
+        assert _rust.LongSNormalizer(ngram_dir=str(ngrams)).data_source == str(ngrams)
+        assert _rust.LongSNormalizer(ngrams=_rust.NgramData.train(["fuga"])).data_source == "in memory"
+
+    def test_preload(self, has_rust):
+        from latincy_preprocess import _rust
+        assert _rust.preload() is None
+        assert _rust.ngrams.preload is _rust.preload
+        assert _rust.normalize_long_s_text_full("funt") == "sunt"
+
+    def test_word_cache(self, has_rust):
+        from latincy_preprocess import _rust
+        text = "funt in fundamento fuit, funt et seruus seruum uidit"
+        long_s = _rust.LongSNormalizer(cache_size=1000)
+        assert long_s.normalize_text(text) == long_s.normalize_text(text) == _rust.LongSNormalizer().normalize_text(text)
+        uv = _rust.UvNormalizer(cache_size=1000)
+        assert uv.normalize(text) == uv.normalize(text) == _rust.normalize_uv(text)
+        uv.extend_vocalic_words(["seruus"])
+        assert uv.normalize("seruus") == "seruus"
+
+    def test_normalize_char_rejects_other_positions(self, has_rust):
+        from latincy_preprocess import _rust
+        assert _rust.normalize_uv_char("uia", 0) == ("v", "initial_before_vowel")
+        with pytest.raises(ValueError, match="not u/v"):
+            _rust.normalize_uv_char("abc", 0)
+        with pytest.raises(IndexError):
+            _rust.UvNormalizer().normalize_char("uia", 3)
+
+    def test_mixed_case_restored(self, has_rust):
+        from latincy_preprocess import _rust
+        assert _rust.normalize_long_s_text_full("McFufcepit eft, non McDonald") == "McSuscepit est, non McDonald"
+        assert _rust.normalize_uv("McUir ǅuuenis") == "McVir ǅuvenis"
+
+    def test_combining_marks_stay_on_letters(self, has_rust):
+        from latincy_preprocess import _rust
+        assert _rust.normalize_uv("u\u0304ua seruu\u0304s") == "u\u0304va servu\u0304s"
+        assert _rust.normalize_long_s_text_full("fu\u0304nt e\u0304fuit") == "su\u0304nt e\u0304fuit"
+
+    def test_protected_spans(self, has_rust):
+        from latincy_preprocess import _rust
+        pipeline = _rust.Pipeline(["long_s", "uv"], protect=["urls", "greek", "citations"])
+        assert pipeline.protect == ["urls", "greek", "citations"]
+        text = "ſeruus http://ſeruus.uia/ et λόγος, Cic. Att. 1.2 uir"
+        assert pipeline.normalize(text) == "servus http://ſeruus.uia/ et λόγος, Cic. Att. 1.2 vir"
+        assert pipeline.protected_spans("uide http://uia.org") == [(5, 19)]
+        assert pipeline.normalize_detailed("uia uia", protected=[(0, 3)]).normalized == "uia via"
+        with pytest.raises(ValueError, match="unknown detector"):
+            _rust.Pipeline(["uv"], protect=["latin"])
+
+    def test_evaluate(self, has_rust):
+        from latincy_preprocess import _rust
+        pipeline = _rust.Pipeline(["long_s", "uv"])
+        evaluation = _rust.eval.evaluate(pipeline, "uir uxor ſeruus", "vir vxor servus")
+        assert evaluation.pairs == 1
+        assert evaluation.overall["true_positives"] == 3
+        assert evaluation.overall["false_negatives"] == 1
+        assert evaluation.overall["recall"] == 0.75
+        assert evaluation.errors[0]["kind"] == "missed"
+        assert evaluation.errors[0]["context"] == "ir [u]xor"
+        confusions = {(c["from"], c["to"]): c["true_positives"] for c in evaluation.confusions}
+        assert confusions == {("u", "v"): 2, ("ſ", "s"): 1}
+        assert all(rule.startswith(("long_s: ", "uv: ")) for rule in evaluation.rules)
+        both = _rust.evaluate_pairs(pipeline, [("uia", "via"), ("uxor", "vxor")])
+        assert both.pairs == 2 and both.errors[0]["pair"] == 1
+        assert "overall" in str(both)
+
+    def test_evaluation_loaders(self, has_rust):
+        from latincy_preprocess import _rust
+        assert _rust.eval.load_aligned("uir\nuxor", "vir\nvxor") == [("uir", "vir"), ("uxor", "vxor")]
+        assert _rust.eval.load_tsv("# raw\tgold\nſeruus\tservus\n") == [("ſeruus", "servus")]
+        tei = "<TEI><text><p><choice><orig>uir</orig><reg>vir</reg></choice> bonus</p></text></TEI>"
+        assert _rust.eval.load_tei(tei) == ("uir bonus", "vir bonus")
+        with pytest.raises(ValueError, match="line 1: expected 2 tab-separated columns"):
+            _rust.eval.load_tsv("uir\n")
+        with pytest.raises(ValueError, match="lines"):
+            _rust.eval.load_aligned("uir\nuxor", "vir")
+        pairs = _rust.eval.load_tsv("uir\tvir\nuxor\tvxor\n")
+        assert _rust.eval.evaluate_pairs(_rust.Pipeline(["uv"]), pairs).overall["false_negatives"] == 1
+
+    def test_mine_errors(self, has_rust):
+        from latincy_preprocess import _rust
+        pairs = [("uxor uxoris", "vxor vxoris"), ("uxorem", "vxorem")]
+        patterns = _rust.eval.evaluate_pairs(_rust.Pipeline(["uv"]), pairs).mine_errors()
+        assert len(patterns) == 1
+        pattern = patterns[0]
+        assert (pattern["kind"], pattern["shape"], pattern["bigram"]) == ("missed", "#[u]C", "#")
+        assert (pattern["count"], pattern["candidate"], pattern["suggestion"]) == (3, "stem", "uxor")
+        assert pattern["words"][0] == ("uxor", 1)
+        assert _rust.eval.evaluate(_rust.Pipeline(["uv"]), "uir", "vir").mine_errors() == []
+
+    def test_uv_model(self, has_rust, tmp_path):
+        from latincy_preprocess import _rust
+        model = _rust.uv.UvModel.train(["genua genuum genuamus genuent genuare"])
+        assert model.occurrences == 7 and model.min_count == 3
+        letter, confidence, feature = model.predict("genua", 3)
+        assert letter == "u" and confidence > 0.9 and "_" in feature
+        normalizer = _rust.UvNormalizer(model=model)
+        assert normalizer.normalize("genua uidit") == "genua vidit"
+        assert _rust.UvNormalizer().normalize("genua") == "genva"
+        path = tmp_path / "uv.json"
+        model.save(path)
+        assert _rust.UvModel.load(path).to_json() == model.to_json()
+        assert normalizer.model.feature_count == model.feature_count
+        with pytest.raises(OSError, match="invalid u/v model"):
+            _rust.UvModel.from_json("{}")
+
+    def test_profile(self, has_rust):
+        from latincy_preprocess import _rust
+        profile = _rust.profile.profile("Arma uirumque cano, Troiæ qui primus ab oris")
+        assert profile.counts["words"] == 8 and profile.counts["vowel_ligatures"] == 1
+        assert profile.rate("vowel_ligatures") == 125.0
+        assert not profile.writes_v and profile.era == "early-modern"
+        merged = _rust.profile.profile_texts(["ſed", "Gallia est omnis diuisa"])
+        assert (merged.counts["texts"], merged.counts["long_s"]) == (2, 1)
+        assert '"era":"early-modern"' in merged.to_json()
+        with pytest.raises(KeyError):
+            profile.rate("nonsense")
+
+    def test_recommend_pipeline(self, has_rust, tmp_path):
+        from latincy_preprocess import _rust
+        profile = _rust.profile.profile("Cæſar uidit ἀνήρ")
+        config = profile.recommend_pipeline()
+        assert config.stages == ["unicode", "ligatures", "long_s", "uv"]
+        assert config.protect == ["greek"] and ("ligatures", "vowel ligatures (æ, œ, ę): 1") in config.reasons
+        assert config.pipeline().normalize("Cæſar uidit ἀνήρ") == "Caesar vidit ἀνήρ"
+        config.save(tmp_path / "pipeline.toml")
+        assert _rust.Pipeline.from_config(tmp_path / "pipeline.toml").stages == config.stages
+        assert _rust.profile.recommend_pipeline(profile).to_json() == config.to_json()